@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -8,6 +9,8 @@ pub enum BackendKind {
     Clap,
     Lv2,
     Vst3,
+    /// Processors built into the host
+    Native,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,6 +35,7 @@ pub enum ParamKey {
     Clap(u32),
     Lv2(String),
     Vst3(u32),
+    Native(u32),
 }
 
 #[derive(Clone, Debug)]
@@ -65,11 +69,13 @@ pub struct UnifiedPluginInfo {
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct ProcessCtx {
+pub struct ProcessCtx<'a> {
     pub frames: usize,
     pub bpm: f32,
     pub time_samples: f64,
     pub loop_active: bool,
+    /// Key input from another track, one slice per channel; empty when no sidechain is routed
+    pub sidechain: &'a [&'a [f32]],
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -80,6 +86,16 @@ pub struct MidiEvent {
     pub time_frames: i64,
 }
 
+/// Saves and loads a plugin's state from any thread but the audio thread;
+/// both calls may block until the plugin answers
+pub trait PluginStateHandle: Send + Sync {
+    /// Opaque blob of the plugin's full internal state; None if it can't be saved
+    fn save(&self) -> Option<Vec<u8>>;
+
+    /// Restores a blob from [`PluginStateHandle::save`], false if it was rejected
+    fn load(&self, data: &[u8]) -> bool;
+}
+
 pub trait PluginInstance {
     fn process(
         &mut self,
@@ -93,14 +109,12 @@ pub trait PluginInstance {
     fn get_param(&self, key: &ParamKey) -> Option<f32>;
     fn params(&self) -> &[UnifiedParamInfo];
 
-    fn save_state(&mut self) -> Option<Vec<u8>> {
+    /// Handle for saving and restoring the plugin's full internal state, or
+    /// None if it has no state beyond its params
+    fn state_handle(&self) -> Option<Arc<dyn PluginStateHandle>> {
         None
     }
 
-    fn load_state(&mut self, _data: &[u8]) -> bool {
-        false
-    }
-
     fn open_editor(&mut self) -> anyhow::Result<()> {
         Err(anyhow::anyhow!("Editor not supported"))
     }
@@ -108,6 +122,17 @@ pub trait PluginInstance {
     fn has_editor(&self) -> bool {
         false
     }
+
+    /// Samples the plugin delays its output by (lookahead, linear-phase filtering)
+    fn latency_samples(&self) -> u32 {
+        0
+    }
+
+    /// Deepest gain reduction over the last processed block, in positive dB;
+    /// None for plugins that don't meter it
+    fn gain_reduction_db(&self) -> Option<f32> {
+        None
+    }
 }
 
 pub trait PluginBackend: Send + Sync {
@@ -21,18 +21,25 @@ mod clap_impl {
     #[cfg(feature = "clap-host")]
     use clack_host::process::StartedPluginAudioProcessor;
 
+    #[cfg(feature = "clap-host")]
+    use clack_extensions::audio_ports::{AudioPortInfoBuffer, PluginAudioPorts};
     #[cfg(feature = "clap-host")]
     use clack_extensions::gui::{
         GuiApiType, GuiConfiguration, GuiSize, HostGui, HostGuiImpl, PluginGui,
     };
+    #[cfg(feature = "clap-host")]
+    use clack_extensions::latency::PluginLatency;
     use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
     #[cfg(feature = "clap-host")]
     use clack_extensions::params::{ParamInfoBuffer, ParamInfoFlags, PluginParams as ParamsExt};
+    #[cfg(feature = "clap-host")]
+    use clack_extensions::state::PluginState;
     use clack_extensions::timer::{HostTimer, HostTimerImpl, PluginTimer, TimerId};
 
     use yadaw_plugin_api::{
         BackendKind, HostConfig, MidiEvent, ParamKey, ParamKind, PluginBackend,
-        PluginInstance as UniInstance, ProcessCtx, UnifiedParamInfo, UnifiedPluginInfo,
+        PluginInstance as UniInstance, PluginStateHandle, ProcessCtx, UnifiedParamInfo,
+        UnifiedPluginInfo,
     };
 
     struct MyHostShared {
@@ -321,6 +328,25 @@ mod clap_impl {
                 })
         }
 
+        /// Channels on the second input port, which CLAP effects expose for sidechain
+        fn fetch_sidechain_channels(instance: &mut PluginInstance<MyHost>) -> usize {
+            let Some(ports) = instance
+                .plugin_shared_handle()
+                .get_extension::<PluginAudioPorts>()
+            else {
+                return 0;
+            };
+
+            let mut plugin = instance.plugin_handle();
+            if ports.count(&mut plugin, true) < 2 {
+                return 0;
+            }
+            let mut buf = AudioPortInfoBuffer::new();
+            ports
+                .get(&mut plugin, 1, true, &mut buf)
+                .map_or(0, |info| info.channel_count as usize)
+        }
+
         fn fetch_params_with_values(
             instance: &mut PluginInstance<MyHost>,
         ) -> (Vec<UnifiedParamInfo>, HashMap<u32, f32>) {
@@ -485,6 +511,8 @@ mod clap_impl {
 
                         let (params, param_values) =
                             ClapHostBackend::fetch_params_with_values(&mut instance);
+                        let sidechain_channels =
+                            ClapHostBackend::fetch_sidechain_channels(&mut instance);
 
                         let cfg = PluginAudioConfiguration {
                             sample_rate,
@@ -503,6 +531,13 @@ mod clap_impl {
                             .get_extension::<PluginGui>()
                             .is_some();
 
+                        // Only valid once activated
+                        let latency_ext = instance
+                            .plugin_shared_handle()
+                            .get_extension::<PluginLatency>();
+                        let latency =
+                            latency_ext.map_or(0, |ext| ext.get(&mut instance.plugin_handle()));
+
                         Ok::<_, anyhow::Error>((
                             instance,
                             entry,
@@ -510,14 +545,32 @@ mod clap_impl {
                             param_values,
                             processor,
                             has_gui,
+                            sidechain_channels,
+                            latency,
                         ))
                     }));
 
                     match create_result {
-                        Ok(Ok((instance, entry, params, param_values, processor, has_gui))) => {
+                        Ok(Ok((
+                            instance,
+                            entry,
+                            params,
+                            param_values,
+                            processor,
+                            has_gui,
+                            sidechain_channels,
+                            latency,
+                        ))) => {
                             register_main_thread(instance_id, cmd_tx.clone());
                             result_tx
-                                .send(Ok((processor, params, param_values, has_gui)))
+                                .send(Ok((
+                                    processor,
+                                    params,
+                                    param_values,
+                                    has_gui,
+                                    sidechain_channels,
+                                    latency,
+                                )))
                                 .ok();
                             clap_main_loop(
                                 instance,
@@ -545,7 +598,7 @@ mod clap_impl {
                 })
                 .map_err(|e| anyhow!("Failed to spawn CLAP main thread: {e}"))?;
 
-            let (processor, params, param_values, has_gui) = result_rx
+            let (processor, params, param_values, has_gui, sidechain_channels, latency) = result_rx
                 .recv()
                 .map_err(|e| anyhow!("CLAP main thread failed to start: {}", e))??;
 
@@ -555,7 +608,8 @@ mod clap_impl {
                 param_values,
                 main_thread_id: instance_id,
                 has_gui,
-                input_copies: vec![vec![0.0; max_block]; 2],
+                latency,
+                input_copies: vec![vec![0.0; max_block]; 2 + sidechain_channels],
                 note_ons: Vec::with_capacity(128),
                 note_offs: Vec::with_capacity(128),
                 pending_param_changes: Vec::new(),
@@ -594,6 +648,8 @@ mod clap_impl {
         param_values: HashMap<u32, f32>,
         main_thread_id: MainThreadId,
         has_gui: bool,
+        latency: u32,
+        /// Main stereo input, followed by one buffer per sidechain port channel
         input_copies: Vec<Vec<f32>>,
         note_ons: Vec<NoteOnEvent>,
         note_offs: Vec<NoteOffEvent>,
@@ -619,6 +675,40 @@ mod clap_impl {
         }
     }
 
+    /// Runs state saves and loads on the instance's CLAP main thread, waiting
+    /// for the answer
+    struct ClapStateHandle {
+        main_thread_id: MainThreadId,
+    }
+
+    impl PluginStateHandle for ClapStateHandle {
+        fn save(&self) -> Option<Vec<u8>> {
+            let tx = lookup_main_thread(self.main_thread_id)?;
+            let (result_tx, result_rx) = mpsc::channel();
+            tx.send(MainThreadCommand::SaveState(result_tx)).ok()?;
+            result_rx
+                .recv_timeout(Duration::from_secs(2))
+                .ok()
+                .flatten()
+        }
+
+        fn load(&self, data: &[u8]) -> bool {
+            let Some(tx) = lookup_main_thread(self.main_thread_id) else {
+                return false;
+            };
+            let (result_tx, result_rx) = mpsc::channel();
+            if tx
+                .send(MainThreadCommand::LoadState(data.to_vec(), result_tx))
+                .is_err()
+            {
+                return false;
+            }
+            result_rx
+                .recv_timeout(Duration::from_secs(2))
+                .unwrap_or(false)
+        }
+    }
+
     impl UniInstance for ClapAudioInstance {
         fn process(
             &mut self,
@@ -655,25 +745,44 @@ mod clap_impl {
             self.note_ons.sort_by_key(|e| e.time());
             self.note_offs.sort_by_key(|e| e.time());
 
+            let (main_copies, sidechain_copies) = self.input_copies.split_at_mut(2);
             for (i, &input_channel) in audio_in.iter().enumerate() {
-                if i < self.input_copies.len() {
+                if i < main_copies.len() {
                     let len = frames.min(input_channel.len());
-                    self.input_copies[i][..len].copy_from_slice(&input_channel[..len]);
+                    main_copies[i][..len].copy_from_slice(&input_channel[..len]);
+                }
+            }
+            // A mono key feeds every sidechain channel; no key means silence
+            for (i, buf) in sidechain_copies.iter_mut().enumerate() {
+                buf[..frames].fill(0.0);
+                if let Some(src) = ctx.sidechain.get(i).or(ctx.sidechain.last()) {
+                    let len = frames.min(src.len());
+                    buf[..len].copy_from_slice(&src[..len]);
                 }
             }
 
-            let mut in_ports = AudioPorts::with_capacity(audio_in.len(), 1);
+            let main_count = audio_in.len().min(main_copies.len());
+            let mut in_ports = AudioPorts::with_capacity(main_count + sidechain_copies.len(), 2);
             let mut out_ports = AudioPorts::with_capacity(audio_out.len(), 1);
 
-            let in_buffers = vec![AudioPortBuffer {
+            let main_channels: Vec<_> = main_copies[..main_count]
+                .iter_mut()
+                .map(|buf| InputChannel::variable(&mut buf[..frames]))
+                .collect();
+            let mut in_buffers = vec![AudioPortBuffer {
                 latency: 0,
-                channels: AudioPortBufferType::f32_input_only(
-                    self.input_copies
-                        .iter_mut()
-                        .take(audio_in.len())
-                        .map(|buf| InputChannel::variable(&mut buf[..frames])),
-                ),
+                channels: AudioPortBufferType::f32_input_only(main_channels),
             }];
+            if !sidechain_copies.is_empty() {
+                let sidechain_channels: Vec<_> = sidechain_copies
+                    .iter_mut()
+                    .map(|buf| InputChannel::variable(&mut buf[..frames]))
+                    .collect();
+                in_buffers.push(AudioPortBuffer {
+                    latency: 0,
+                    channels: AudioPortBufferType::f32_input_only(sidechain_channels),
+                });
+            }
 
             let out_buffers = vec![AudioPortBuffer {
                 latency: 0,
@@ -741,16 +850,18 @@ mod clap_impl {
             }
         }
 
-        fn params(&self) -> &[UnifiedParamInfo] {
-            &self.params
+        fn latency_samples(&self) -> u32 {
+            self.latency
         }
 
-        fn save_state(&mut self) -> Option<Vec<u8>> {
-            None
+        fn params(&self) -> &[UnifiedParamInfo] {
+            &self.params
         }
 
-        fn load_state(&mut self, _data: &[u8]) -> bool {
-            false
+        fn state_handle(&self) -> Option<Arc<dyn PluginStateHandle>> {
+            Some(Arc::new(ClapStateHandle {
+                main_thread_id: self.main_thread_id,
+            }))
         }
 
         fn open_editor(&mut self) -> Result<()> {
@@ -777,6 +888,8 @@ mod clap_impl {
         CloseEditor,
         RequestResize(GuiSize),
         GuiClosed,
+        SaveState(mpsc::Sender<Option<Vec<u8>>>),
+        LoadState(Vec<u8>, mpsc::Sender<bool>),
         Shutdown {
             processor: StartedPluginAudioProcessor<MyHost>,
             result_tx: mpsc::Sender<()>,
@@ -850,6 +963,12 @@ mod clap_impl {
                         close_editor_state(&mut instance, state);
                     }
                 }
+                Ok(MainThreadCommand::SaveState(result_tx)) => {
+                    let _ = result_tx.send(save_state_on_main_thread(&mut instance));
+                }
+                Ok(MainThreadCommand::LoadState(data, result_tx)) => {
+                    let _ = result_tx.send(load_state_on_main_thread(&mut instance, &data));
+                }
                 Ok(MainThreadCommand::Shutdown {
                     processor,
                     result_tx,
@@ -885,6 +1004,32 @@ mod clap_impl {
         unregister_main_thread(instance_id);
     }
 
+    fn save_state_on_main_thread(instance: &mut PluginInstance<MyHost>) -> Option<Vec<u8>> {
+        let state_ext = instance.plugin_handle().get_extension::<PluginState>()?;
+        let mut data = Vec::new();
+        match state_ext.save(&mut instance.plugin_handle(), &mut data) {
+            Ok(()) => Some(data),
+            Err(e) => {
+                log::warn!("CLAP state save failed: {e}");
+                None
+            }
+        }
+    }
+
+    fn load_state_on_main_thread(instance: &mut PluginInstance<MyHost>, data: &[u8]) -> bool {
+        let Some(state_ext) = instance.plugin_handle().get_extension::<PluginState>() else {
+            return false;
+        };
+        let mut reader = std::io::Cursor::new(data);
+        match state_ext.load(&mut instance.plugin_handle(), &mut reader) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("CLAP state load failed: {e}");
+                false
+            }
+        }
+    }
+
     enum EditorState {
         Floating,
         #[cfg(unix)]
@@ -5,8 +5,8 @@ use anyhow::{Result, anyhow};
 #[cfg(unix)]
 use crate::editor_host::{EditorBackend, EditorHost};
 use yadaw_plugin_api::{
-    BackendKind, HostConfig, MidiEvent, ParamKey, PluginBackend, PluginInstance, ProcessCtx,
-    UnifiedParamInfo, UnifiedPluginInfo,
+    BackendKind, HostConfig, MidiEvent, ParamKey, PluginBackend, PluginInstance, PluginStateHandle,
+    ProcessCtx, UnifiedParamInfo, UnifiedPluginInfo,
 };
 
 #[cfg(feature = "lv2-legacy")]
@@ -106,6 +106,52 @@ pub struct Lv2Instance {
     editor_host: Option<EditorHost>,
 }
 
+/// Saves and restores every control port value, including the ones the
+/// plugin's own UI changed behind the descriptor's back. The blob is one
+/// `symbol value` line per port. State a plugin keeps outside its ports
+/// through the LV2 State interface isn't covered: yeli doesn't hand out the
+/// plugin's extension data.
+#[cfg(feature = "lv2-legacy")]
+struct Lv2StateHandle {
+    params: Arc<dashmap::DashMap<String, f32>>,
+    pending: Arc<parking_lot::Mutex<Option<Vec<(String, f32)>>>>,
+}
+
+#[cfg(feature = "lv2-legacy")]
+impl PluginStateHandle for Lv2StateHandle {
+    fn save(&self) -> Option<Vec<u8>> {
+        let mut ports: Vec<(String, f32)> = self
+            .params
+            .iter()
+            .map(|kv| (kv.key().clone(), *kv.value()))
+            .collect();
+        ports.sort_by(|a, b| a.0.cmp(&b.0));
+        let text: String = ports
+            .into_iter()
+            .map(|(symbol, value)| format!("{symbol} {value}\n"))
+            .collect();
+        Some(text.into_bytes())
+    }
+
+    fn load(&self, data: &[u8]) -> bool {
+        let Ok(text) = std::str::from_utf8(data) else {
+            return false;
+        };
+        let ports: Option<Vec<(String, f32)>> = text
+            .lines()
+            .map(|line| {
+                let (symbol, value) = line.split_once(' ')?;
+                Some((symbol.to_string(), value.parse().ok()?))
+            })
+            .collect();
+        let Some(ports) = ports else {
+            return false;
+        };
+        *self.pending.lock() = Some(ports);
+        true
+    }
+}
+
 /// Backend used by [`EditorHost`] to manage the LV2 UI lifecycle.
 ///
 /// The UI is pre-opened by `Lv2Instance::open_editor` and handed into this
@@ -176,8 +222,17 @@ impl PluginInstance for Lv2Instance {
             self.inner.clear_midi_events();
         }
 
-        self.inner
-            .process_multi(audio_in, audio_out, !events.is_empty(), ctx.frames)?;
+        // Audio inputs past the main pair are the plugin's sidechain ports
+        let (port_inputs, _) = self.inner.audio_in_out_counts();
+        if !ctx.sidechain.is_empty() && port_inputs > audio_in.len() {
+            let mut inputs: smallvec::SmallVec<[&[f32]; 4]> = audio_in.iter().copied().collect();
+            inputs.extend(ctx.sidechain.iter().copied());
+            self.inner
+                .process_multi(&inputs, audio_out, !events.is_empty(), ctx.frames)?;
+        } else {
+            self.inner
+                .process_multi(audio_in, audio_out, !events.is_empty(), ctx.frames)?;
+        }
         Ok(())
     }
 
@@ -198,16 +253,19 @@ impl PluginInstance for Lv2Instance {
         &self.params
     }
 
-    fn save_state(&mut self) -> Option<Vec<u8>> {
-        None
+    fn has_editor(&self) -> bool {
+        self.inner.has_editor()
     }
 
-    fn load_state(&mut self, _data: &[u8]) -> bool {
-        false
+    fn latency_samples(&self) -> u32 {
+        self.inner.latency_samples()
     }
 
-    fn has_editor(&self) -> bool {
-        self.inner.has_editor()
+    fn state_handle(&self) -> Option<Arc<dyn PluginStateHandle>> {
+        Some(Arc::new(Lv2StateHandle {
+            params: self.inner.get_params(),
+            pending: self.inner.pending_state(),
+        }))
     }
 
     fn open_editor(&mut self) -> Result<()> {
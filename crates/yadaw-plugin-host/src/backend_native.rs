@@ -0,0 +1,51 @@
+use anyhow::{Result, anyhow};
+use yadaw_plugin_api::{BackendKind, HostConfig, PluginBackend, PluginInstance, UnifiedPluginInfo};
+
+use crate::{native_dynamics, native_eq};
+
+/// Processors built into the host, available on every platform
+pub struct Backend {
+    sample_rate: f64,
+}
+
+impl Backend {
+    pub fn new(cfg: &HostConfig) -> Self {
+        Self {
+            sample_rate: cfg.sample_rate,
+        }
+    }
+}
+
+impl PluginBackend for Backend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Native
+    }
+
+    fn init(&self, _cfg: &HostConfig) -> Result<()> {
+        Ok(())
+    }
+
+    fn scan(&self) -> Result<Vec<UnifiedPluginInfo>> {
+        let effect = |uri: &str, name: &str| UnifiedPluginInfo {
+            backend: BackendKind::Native,
+            uri: uri.to_string(),
+            name: name.to_string(),
+            is_instrument: false,
+            audio_inputs: 2,
+            audio_outputs: 2,
+            has_midi: false,
+        };
+        Ok(vec![
+            effect(native_eq::URI, native_eq::NAME),
+            effect(native_dynamics::URI, native_dynamics::NAME),
+        ])
+    }
+
+    fn instantiate(&self, uri: &str) -> Result<Box<dyn PluginInstance>> {
+        match uri {
+            native_eq::URI => Ok(Box::new(native_eq::ParametricEq::new(self.sample_rate))),
+            native_dynamics::URI => Ok(Box::new(native_dynamics::Dynamics::new(self.sample_rate))),
+            _ => Err(anyhow!("Unknown native processor: {uri}")),
+        }
+    }
+}
@@ -8,7 +8,8 @@ mod vst3_impl {
     use vst3_host;
     use yadaw_plugin_api::{
         BackendKind, HostConfig, MidiEvent, ParamKey, ParamKind, PluginBackend,
-        PluginInstance as UniInstance, ProcessCtx, UnifiedParamInfo, UnifiedPluginInfo,
+        PluginInstance as UniInstance, PluginStateHandle, ProcessCtx, UnifiedParamInfo,
+        UnifiedPluginInfo,
     };
 
     #[cfg(unix)]
@@ -221,6 +222,24 @@ mod vst3_impl {
         }
     }
 
+    struct Vst3StateHandle {
+        plugin: Arc<Mutex<vst3_host::Plugin>>,
+    }
+
+    impl PluginStateHandle for Vst3StateHandle {
+        fn save(&self) -> Option<Vec<u8>> {
+            self.plugin.lock().ok().and_then(|p| p.save_state().ok())
+        }
+
+        fn load(&self, data: &[u8]) -> bool {
+            self.plugin
+                .lock()
+                .ok()
+                .and_then(|mut p| p.load_state(data).ok())
+                .is_some()
+        }
+    }
+
     impl UniInstance for Vst3PluginInstance {
         fn process(
             &mut self,
@@ -300,16 +319,10 @@ mod vst3_impl {
             &self.params
         }
 
-        fn save_state(&mut self) -> Option<Vec<u8>> {
-            self.plugin.lock().ok().and_then(|p| p.save_state().ok())
-        }
-
-        fn load_state(&mut self, data: &[u8]) -> bool {
-            self.plugin
-                .lock()
-                .ok()
-                .and_then(|mut p| p.load_state(data).ok())
-                .is_some()
+        fn state_handle(&self) -> Option<Arc<dyn PluginStateHandle>> {
+            Some(Arc::new(Vst3StateHandle {
+                plugin: Arc::clone(&self.plugin),
+            }))
         }
 
         fn open_editor(&mut self) -> Result<()> {
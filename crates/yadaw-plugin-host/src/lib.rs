@@ -1,3 +1,6 @@
+pub mod backend_native;
+mod native_dynamics;
+mod native_eq;
 pub mod plugin_facade;
 
 #[cfg(unix)]
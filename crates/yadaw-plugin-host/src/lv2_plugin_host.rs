@@ -1,5 +1,6 @@
 use anyhow::{Result, anyhow};
 use dashmap::DashMap;
+use parking_lot::Mutex;
 use std::collections::HashMap;
 use std::sync::Arc;
 use yeli::{
@@ -7,6 +8,9 @@ use yeli::{
     UiInstance, World,
 };
 
+/// Length of the silent block run on instantiation to fill the latency port
+const LATENCY_PROBE_FRAMES: usize = 256;
+
 #[derive(Clone, Debug)]
 pub struct PluginInfo {
     pub uri: String,
@@ -102,11 +106,14 @@ impl LV2PluginHost {
 
         let params = Arc::new(DashMap::new());
         let mut control_port_indices = HashMap::new();
+        let mut latency_port = None;
 
         for port in plugin.ports() {
             if port.port_type() == PortType::ControlInput {
                 params.insert(port.symbol.clone(), port.default.unwrap_or(0.0));
                 control_port_indices.insert(port.symbol.clone(), PortIndex(port.index));
+            } else if port.port_type() == PortType::ControlOutput && port.symbol == "latency" {
+                latency_port = Some(PortIndex(port.index));
             }
         }
 
@@ -121,13 +128,15 @@ impl LV2PluginHost {
             );
         }
 
-        Ok(LV2PluginInstance {
+        let mut instance = LV2PluginInstance {
             instance,
             features: self.features.clone(),
             port_counts: plugin.port_counts(),
             params,
             midi_sequence: Some(AtomSequence::with_features(4096, &self.features, true)),
             control_port_indices,
+            latency_port,
+            pending_state: Arc::new(Mutex::new(None)),
             empty_atom_in: AtomSequence::with_features(1024, &self.features, true),
             atom_outputs: (0..plugin.port_counts().atom_sequence_outputs)
                 .map(|_| AtomSequence::with_features(4096, &self.features, false))
@@ -137,7 +146,14 @@ impl LV2PluginHost {
             silent_audio: vec![0.0; self.max_block_size],
             scratch_audio_out: Vec::new(),
             active_ui: None,
-        })
+        };
+        // A latency port only holds the plugin's value once it has run, so run
+        // one silent block before anyone reads it
+        if instance.latency_port.is_some() {
+            let frames = LATENCY_PROBE_FRAMES.min(self.max_block_size);
+            instance.process_multi(&[], &mut [], false, frames)?;
+        }
+        Ok(instance)
     }
 
     pub fn sample_rate(&self) -> f64 {
@@ -156,6 +172,10 @@ pub struct LV2PluginInstance {
     params: Arc<DashMap<String, f32>>,
     pub midi_sequence: Option<AtomSequence>,
     control_port_indices: HashMap<String, PortIndex>,
+    // Control output the plugin reports its latency on (lv2:reportsLatency)
+    latency_port: Option<PortIndex>,
+    // Port values a state load queued from another thread, written at the next run
+    pending_state: Arc<Mutex<Option<Vec<(String, f32)>>>>,
 
     empty_atom_in: AtomSequence,
     atom_outputs: Vec<AtomSequence>,
@@ -212,6 +232,14 @@ impl LV2PluginInstance {
         use_midi: bool,
         samples: usize,
     ) -> Result<()> {
+        // try_lock: never wait on a load in progress, it lands next block
+        let restored = self.pending_state.try_lock().and_then(|mut p| p.take());
+        for (symbol, value) in restored.into_iter().flatten() {
+            if let Some(&pi) = self.control_port_indices.get(&symbol) {
+                self.instance.set_control_input(pi, value);
+            }
+        }
+
         let len = samples.min(self.max_block_size);
         let need_ai = self.port_counts.audio_inputs;
         let need_ao = self.port_counts.audio_outputs;
@@ -309,6 +337,12 @@ impl LV2PluginInstance {
     }
 
     pub fn set_parameter(&mut self, symbol: &str, value: f32) {
+        // Params set after a state load still win over it, as with the other backends
+        if let Some(mut pending) = self.pending_state.try_lock()
+            && let Some(ports) = pending.as_mut()
+        {
+            ports.retain(|(s, _)| s != symbol);
+        }
         self.params.insert(symbol.to_string(), value);
         if let Some(&pi) = self.control_port_indices.get(symbol) {
             self.instance.set_control_input(pi, value);
@@ -319,6 +353,12 @@ impl LV2PluginInstance {
         self.params.get(symbol).map(|v| *v)
     }
 
+    pub fn latency_samples(&self) -> u32 {
+        self.latency_port
+            .and_then(|pi| self.instance.control_output(pi))
+            .map_or(0, |v| v.max(0.0).round() as u32)
+    }
+
     pub fn prepare_midi_raw_events(&mut self, events: &[(u8, u8, u8, i64)]) {
         let mut sequence = self
             .midi_sequence
@@ -359,6 +399,11 @@ impl LV2PluginInstance {
         self.params.clone()
     }
 
+    /// Slot for port values to write at the next run, see `process_multi`
+    pub fn pending_state(&self) -> Arc<Mutex<Option<Vec<(String, f32)>>>> {
+        self.pending_state.clone()
+    }
+
     pub fn set_params_arc(&mut self, params: Arc<DashMap<String, f32>>) {
         self.params = params;
     }
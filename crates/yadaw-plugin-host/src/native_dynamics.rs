@@ -0,0 +1,250 @@
+use anyhow::Result;
+use yadaw_plugin_api::{
+    MidiEvent, ParamKey, ParamKind, PluginInstance, ProcessCtx, UnifiedParamInfo,
+};
+
+pub const URI: &str = "urn:yadaw:native:dynamics";
+pub const NAME: &str = "Compressor / Gate";
+
+const MODE: u32 = 0;
+const THRESHOLD: u32 = 1;
+const RATIO: u32 = 2;
+const ATTACK: u32 = 3;
+const RELEASE: u32 = 4;
+const KNEE: u32 = 5;
+const MAKEUP: u32 = 6;
+const RANGE: u32 = 7;
+const PARAM_COUNT: usize = 8;
+
+const MODE_GATE: f32 = 1.0;
+/// Detector floor; anything quieter counts as silence
+const FLOOR_DB: f32 = -120.0;
+
+/// (id, name, (min, max, default), unit); Mode is the only enum
+const PARAMS: [(u32, &str, (f32, f32, f32), Option<&str>); PARAM_COUNT] = [
+    (MODE, "Mode", (0.0, 1.0, 0.0), None),
+    (THRESHOLD, "Threshold", (-60.0, 0.0, -18.0), Some("dB")),
+    (RATIO, "Ratio", (1.0, 20.0, 4.0), None),
+    (ATTACK, "Attack", (0.1, 100.0, 10.0), Some("ms")),
+    (RELEASE, "Release", (5.0, 1000.0, 100.0), Some("ms")),
+    (KNEE, "Knee", (0.0, 24.0, 6.0), Some("dB")),
+    (MAKEUP, "Makeup", (0.0, 24.0, 0.0), Some("dB")),
+    (RANGE, "Gate Range", (-80.0, 0.0, -60.0), Some("dB")),
+];
+
+/// Feed-forward compressor, or downward expander in gate mode. The detector
+/// follows the sidechain when one is routed, otherwise the input.
+pub struct Dynamics {
+    sample_rate: f64,
+    params: Vec<UnifiedParamInfo>,
+    values: [f32; PARAM_COUNT],
+    /// Smoothed gain change, in dB (0 or below)
+    gain_db: f32,
+    /// Deepest reduction during the last block, in dB (0 or above)
+    block_reduction: f32,
+}
+
+impl Dynamics {
+    pub fn new(sample_rate: f64) -> Self {
+        let params = PARAMS
+            .iter()
+            .map(|&(id, name, (min, max, default), unit)| {
+                let is_mode = id == MODE;
+                UnifiedParamInfo {
+                    key: ParamKey::Native(id),
+                    name: name.to_string(),
+                    min,
+                    max,
+                    default,
+                    stepped: is_mode,
+                    enum_labels: is_mode
+                        .then(|| vec!["Compressor".to_string(), "Gate".to_string()]),
+                    kind: if is_mode {
+                        ParamKind::Enum
+                    } else {
+                        ParamKind::Float
+                    },
+                    group: None,
+                    is_hidden: false,
+                    is_readonly: false,
+                    is_automatable: true,
+                    is_bypass: false,
+                    unit: unit.map(str::to_string),
+                    value_to_text: None,
+                }
+            })
+            .collect();
+
+        Self {
+            sample_rate,
+            params,
+            values: PARAMS.map(|p| p.2.2),
+            gain_db: 0.0,
+            block_reduction: 0.0,
+        }
+    }
+
+    fn value(&self, id: u32) -> f32 {
+        self.values[id as usize]
+    }
+
+    /// Static curve: gain change in dB for a detector level in dB
+    fn gain_computer(&self, level_db: f32, gate: bool) -> f32 {
+        let threshold = self.value(THRESHOLD);
+        let ratio = self.value(RATIO).max(1.0);
+        let knee = self.value(KNEE).max(0.0);
+        let over = level_db - threshold;
+        let half = knee * 0.5;
+
+        if gate {
+            let gain = if over >= half {
+                0.0
+            } else if over > -half {
+                -(ratio - 1.0) * (over - half).powi(2) / (2.0 * knee)
+            } else {
+                over * (ratio - 1.0)
+            };
+            gain.max(self.value(RANGE))
+        } else if over <= -half {
+            0.0
+        } else if over < half {
+            (1.0 / ratio - 1.0) * (over + half).powi(2) / (2.0 * knee)
+        } else {
+            over * (1.0 / ratio - 1.0)
+        }
+    }
+
+    fn coeff(&self, ms: f32) -> f32 {
+        (-1.0 / (ms.max(0.01) as f64 * 0.001 * self.sample_rate)).exp() as f32
+    }
+}
+
+impl PluginInstance for Dynamics {
+    fn process(
+        &mut self,
+        ctx: &ProcessCtx,
+        audio_in: &[&[f32]],
+        audio_out: &mut [&mut [f32]],
+        _events: &[MidiEvent],
+    ) -> Result<()> {
+        let gate = self.value(MODE) >= MODE_GATE - 0.5;
+        let attack = self.coeff(self.value(ATTACK));
+        let release = self.coeff(self.value(RELEASE));
+        let makeup = self.value(MAKEUP);
+        let detector = if ctx.sidechain.is_empty() {
+            audio_in
+        } else {
+            ctx.sidechain
+        };
+
+        let frames = audio_out
+            .iter()
+            .map(|c| c.len())
+            .chain(audio_in.iter().map(|c| c.len()))
+            .fold(ctx.frames, usize::min);
+        self.block_reduction = 0.0;
+
+        for i in 0..frames {
+            let level = detector
+                .iter()
+                .filter_map(|c| c.get(i))
+                .fold(0.0f32, |m, s| m.max(s.abs()));
+            let level_db = if level > 0.0 {
+                (20.0 * level.log10()).max(FLOOR_DB)
+            } else {
+                FLOOR_DB
+            };
+
+            let target = self.gain_computer(level_db, gate);
+            // A compressor attacks as it clamps down, a gate as it opens
+            let closing = target < self.gain_db;
+            let coeff = if closing != gate { attack } else { release };
+            self.gain_db = target + (self.gain_db - target) * coeff;
+            self.block_reduction = self.block_reduction.max(-self.gain_db);
+
+            let gain = 10f32.powf((self.gain_db + makeup) / 20.0);
+            for (ch, out) in audio_out.iter_mut().enumerate() {
+                let input = audio_in.get(ch).or(audio_in.first());
+                out[i] = input.map_or(0.0, |c| c[i]) * gain;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_param(&mut self, key: &ParamKey, value: f32) {
+        if let ParamKey::Native(id) = key
+            && let Some(v) = self.values.get_mut(*id as usize)
+        {
+            *v = value;
+        }
+    }
+
+    fn get_param(&self, key: &ParamKey) -> Option<f32> {
+        match key {
+            ParamKey::Native(id) => self.values.get(*id as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn params(&self) -> &[UnifiedParamInfo] {
+        &self.params
+    }
+
+    fn gain_reduction_db(&self) -> Option<f32> {
+        Some(self.block_reduction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dynamics(threshold: f32, ratio: f32, knee: f32) -> Dynamics {
+        let mut d = Dynamics::new(48000.0);
+        d.set_param(&ParamKey::Native(THRESHOLD), threshold);
+        d.set_param(&ParamKey::Native(RATIO), ratio);
+        d.set_param(&ParamKey::Native(KNEE), knee);
+        d
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn compressor_applies_the_ratio_above_threshold() {
+        let d = dynamics(-20.0, 4.0, 6.0);
+        assert_eq!(d.gain_computer(-40.0, false), 0.0);
+        // 12 dB over comes out 3 dB over
+        assert!(close(d.gain_computer(-8.0, false), -9.0));
+        assert!(close(d.gain_computer(0.0, false), -15.0));
+    }
+
+    #[test]
+    fn gate_expands_below_threshold_down_to_its_range() {
+        let mut d = dynamics(-40.0, 2.0, 0.0);
+        d.set_param(&ParamKey::Native(RANGE), -30.0);
+        assert_eq!(d.gain_computer(-20.0, true), 0.0);
+        assert!(close(d.gain_computer(-45.0, true), -5.0));
+        assert!(close(d.gain_computer(-100.0, true), -30.0));
+        assert!(close(d.gain_computer(FLOOR_DB, true), -30.0));
+    }
+
+    #[test]
+    fn knee_meets_both_straight_segments() {
+        let d = dynamics(-20.0, 4.0, 6.0);
+        for gate in [false, true] {
+            for edge in [-23.0, -17.0] {
+                let below = d.gain_computer(edge - 1e-4, gate);
+                let above = d.gain_computer(edge + 1e-4, gate);
+                assert!(
+                    close(below, above),
+                    "gate {gate} at {edge}: {below} vs {above}"
+                );
+            }
+        }
+        // Halfway round the knee, at threshold
+        assert!(close(d.gain_computer(-20.0, false), -0.5625));
+        assert!(close(d.gain_computer(-20.0, true), -2.25));
+    }
+}
@@ -0,0 +1,409 @@
+use anyhow::Result;
+use yadaw_plugin_api::{
+    MidiEvent, ParamKey, ParamKind, PluginInstance, ProcessCtx, UnifiedParamInfo,
+};
+
+pub const URI: &str = "urn:yadaw:native:parametric-eq";
+pub const NAME: &str = "Parametric EQ";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    HighPass,
+    LowShelf,
+    Peak,
+    HighShelf,
+    LowPass,
+}
+
+impl Shape {
+    fn has_gain(self) -> bool {
+        !matches!(self, Shape::HighPass | Shape::LowPass)
+    }
+}
+
+/// (name, shape, default frequency, on by default)
+const BANDS: [(&str, Shape, f32, bool); 8] = [
+    ("High-Pass", Shape::HighPass, 30.0, false),
+    ("Low Shelf", Shape::LowShelf, 100.0, true),
+    ("Peak 1", Shape::Peak, 250.0, true),
+    ("Peak 2", Shape::Peak, 1000.0, true),
+    ("Peak 3", Shape::Peak, 3000.0, true),
+    ("Peak 4", Shape::Peak, 6000.0, true),
+    ("High Shelf", Shape::HighShelf, 8000.0, true),
+    ("Low-Pass", Shape::LowPass, 18000.0, false),
+];
+
+// Each band owns four parameter slots, whether or not it uses them all
+const SLOT_ON: u32 = 0;
+const SLOT_FREQ: u32 = 1;
+const SLOT_GAIN: u32 = 2;
+const SLOT_Q: u32 = 3;
+const SLOTS: u32 = 4;
+const OUTPUT_GAIN: u32 = BANDS.len() as u32 * SLOTS;
+
+#[derive(Clone, Copy)]
+struct Coeffs {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+}
+
+impl Coeffs {
+    const IDENTITY: Coeffs = Coeffs {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// RBJ audio EQ cookbook biquads
+    fn design(shape: Shape, sample_rate: f64, freq: f64, gain_db: f64, q: f64) -> Self {
+        let freq = freq.clamp(10.0, sample_rate * 0.49);
+        let w0 = std::f64::consts::TAU * freq / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q.max(0.05));
+        let a = 10f64.powf(gain_db / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match shape {
+            Shape::HighPass => (
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            Shape::LowPass => (
+                (1.0 - cos) / 2.0,
+                1.0 - cos,
+                (1.0 - cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            Shape::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            Shape::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ),
+            Shape::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ),
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// Transposed direct form II state for one band on one channel
+#[derive(Clone, Copy, Default)]
+struct BiquadState {
+    z1: f64,
+    z2: f64,
+}
+
+impl BiquadState {
+    #[inline]
+    fn tick(&mut self, c: &Coeffs, x: f64) -> f64 {
+        let y = c.b0 * x + self.z1;
+        self.z1 = c.b1 * x - c.a1 * y + self.z2;
+        self.z2 = c.b2 * x - c.a2 * y;
+        y
+    }
+}
+
+/// Stereo EQ with high/low-pass, low/high shelves and four peaking bands
+pub struct ParametricEq {
+    sample_rate: f64,
+    params: Vec<UnifiedParamInfo>,
+    /// Indexed by `ParamKey::Native` id
+    values: Vec<f32>,
+    coeffs: [Coeffs; BANDS.len()],
+    active: [bool; BANDS.len()],
+    state: [[BiquadState; BANDS.len()]; 2],
+    dirty: bool,
+}
+
+fn param(
+    id: u32,
+    name: String,
+    group: &str,
+    (min, max, default): (f32, f32, f32),
+    kind: ParamKind,
+    unit: Option<&str>,
+) -> UnifiedParamInfo {
+    UnifiedParamInfo {
+        key: ParamKey::Native(id),
+        name,
+        min,
+        max,
+        default,
+        stepped: kind == ParamKind::Bool,
+        enum_labels: None,
+        kind,
+        group: Some(group.to_string()),
+        is_hidden: false,
+        is_readonly: false,
+        is_automatable: true,
+        is_bypass: false,
+        unit: unit.map(str::to_string),
+        value_to_text: None,
+    }
+}
+
+impl ParametricEq {
+    pub fn new(sample_rate: f64) -> Self {
+        let mut params = Vec::new();
+        for (band, &(name, shape, freq, on)) in BANDS.iter().enumerate() {
+            let base = band as u32 * SLOTS;
+            let default_q = if shape == Shape::Peak { 1.0 } else { 0.707 };
+            params.push(param(
+                base + SLOT_ON,
+                format!("{name} On"),
+                name,
+                (0.0, 1.0, if on { 1.0 } else { 0.0 }),
+                ParamKind::Bool,
+                None,
+            ));
+            params.push(param(
+                base + SLOT_FREQ,
+                format!("{name} Freq"),
+                name,
+                (20.0, 20000.0, freq),
+                ParamKind::Float,
+                Some("Hz"),
+            ));
+            if shape.has_gain() {
+                params.push(param(
+                    base + SLOT_GAIN,
+                    format!("{name} Gain"),
+                    name,
+                    (-18.0, 18.0, 0.0),
+                    ParamKind::Float,
+                    Some("dB"),
+                ));
+            }
+            params.push(param(
+                base + SLOT_Q,
+                format!("{name} Q"),
+                name,
+                (0.1, 10.0, default_q),
+                ParamKind::Float,
+                None,
+            ));
+        }
+        params.push(param(
+            OUTPUT_GAIN,
+            "Output Gain".to_string(),
+            "Output",
+            (-24.0, 24.0, 0.0),
+            ParamKind::Float,
+            Some("dB"),
+        ));
+
+        let mut values = vec![0.0; OUTPUT_GAIN as usize + 1];
+        for p in &params {
+            if let ParamKey::Native(id) = p.key {
+                values[id as usize] = p.default;
+            }
+        }
+
+        Self {
+            sample_rate,
+            params,
+            values,
+            coeffs: [Coeffs::IDENTITY; BANDS.len()],
+            active: [false; BANDS.len()],
+            state: Default::default(),
+            dirty: true,
+        }
+    }
+
+    fn value(&self, band: usize, slot: u32) -> f64 {
+        self.values[(band as u32 * SLOTS + slot) as usize] as f64
+    }
+
+    fn update_coeffs(&mut self) {
+        for (band, &(_, shape, _, _)) in BANDS.iter().enumerate() {
+            let on = self.value(band, SLOT_ON) > 0.5;
+            let gain = self.value(band, SLOT_GAIN);
+            // A 0 dB bell or shelf is a no-op; skip it rather than run it
+            let active = on && (!shape.has_gain() || gain.abs() > 1e-3);
+            if active && !self.active[band] {
+                for ch in &mut self.state {
+                    ch[band] = BiquadState::default();
+                }
+            }
+            self.active[band] = active;
+            if active {
+                self.coeffs[band] = Coeffs::design(
+                    shape,
+                    self.sample_rate,
+                    self.value(band, SLOT_FREQ),
+                    gain,
+                    self.value(band, SLOT_Q),
+                );
+            }
+        }
+        self.dirty = false;
+    }
+}
+
+impl PluginInstance for ParametricEq {
+    fn process(
+        &mut self,
+        ctx: &ProcessCtx,
+        audio_in: &[&[f32]],
+        audio_out: &mut [&mut [f32]],
+        _events: &[MidiEvent],
+    ) -> Result<()> {
+        if self.dirty {
+            self.update_coeffs();
+        }
+        let out_gain = 10f64.powf(self.values[OUTPUT_GAIN as usize] as f64 / 20.0);
+
+        for (ch, out) in audio_out.iter_mut().enumerate().take(2) {
+            let Some(input) = audio_in.get(ch).or(audio_in.first()) else {
+                out.fill(0.0);
+                continue;
+            };
+            let state = &mut self.state[ch];
+            let frames = ctx.frames.min(input.len()).min(out.len());
+            for i in 0..frames {
+                let mut x = input[i] as f64;
+                for band in 0..BANDS.len() {
+                    if self.active[band] {
+                        x = state[band].tick(&self.coeffs[band], x);
+                    }
+                }
+                out[i] = (x * out_gain) as f32;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_param(&mut self, key: &ParamKey, value: f32) {
+        if let ParamKey::Native(id) = key
+            && let Some(v) = self.values.get_mut(*id as usize)
+        {
+            *v = value;
+            self.dirty = true;
+        }
+    }
+
+    fn get_param(&self, key: &ParamKey) -> Option<f32> {
+        match key {
+            ParamKey::Native(id) => self.values.get(*id as usize).copied(),
+            _ => None,
+        }
+    }
+
+    fn params(&self) -> &[UnifiedParamInfo] {
+        &self.params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: f64 = 48000.0;
+
+    fn band(name: &str) -> u32 {
+        BANDS.iter().position(|b| b.0 == name).unwrap() as u32 * SLOTS
+    }
+
+    /// Steady-state gain in dB of `eq` for a sine at `freq`
+    fn response_db(eq: &mut ParametricEq, freq: f64) -> f64 {
+        let frames = RATE as usize;
+        let input: Vec<f32> = (0..frames)
+            .map(|n| (std::f64::consts::TAU * freq * n as f64 / RATE).sin() as f32 * 0.25)
+            .collect();
+        let mut left = vec![0.0; frames];
+        let mut right = vec![0.0; frames];
+        let ctx = ProcessCtx {
+            frames,
+            bpm: 120.0,
+            time_samples: 0.0,
+            loop_active: false,
+            sidechain: &[],
+        };
+        eq.process(&ctx, &[&input, &input], &mut [&mut left, &mut right], &[])
+            .unwrap();
+
+        // Skip the first half while the filters settle
+        let rms = |s: &[f32]| {
+            (s[frames / 2..]
+                .iter()
+                .map(|&x| (x as f64).powi(2))
+                .sum::<f64>()
+                / (frames / 2) as f64)
+                .sqrt()
+        };
+        20.0 * (rms(&left) / rms(&input)).log10()
+    }
+
+    #[test]
+    fn peak_band_reaches_its_gain_at_the_centre() {
+        let mut eq = ParametricEq::new(RATE);
+        let peak = band("Peak 2");
+        eq.set_param(&ParamKey::Native(peak + SLOT_FREQ), 1000.0);
+        eq.set_param(&ParamKey::Native(peak + SLOT_GAIN), 6.0);
+        assert!((response_db(&mut eq, 1000.0) - 6.0).abs() < 0.05);
+
+        eq.set_param(&ParamKey::Native(peak + SLOT_GAIN), -9.0);
+        assert!((response_db(&mut eq, 1000.0) + 9.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn pass_filters_are_3_db_down_at_cutoff() {
+        for name in ["High-Pass", "Low-Pass"] {
+            let mut eq = ParametricEq::new(RATE);
+            let b = band(name);
+            eq.set_param(&ParamKey::Native(b + SLOT_ON), 1.0);
+            eq.set_param(&ParamKey::Native(b + SLOT_FREQ), 1000.0);
+            let db = response_db(&mut eq, 1000.0);
+            assert!((db + 3.01).abs() < 0.05, "{name}: {db} dB");
+        }
+    }
+
+    #[test]
+    fn zero_db_bands_leave_the_signal_alone() {
+        let mut eq = ParametricEq::new(RATE);
+        for freq in [60.0, 1000.0, 12000.0] {
+            assert!(response_db(&mut eq, freq).abs() < 1e-4);
+        }
+        // Boosted and then reset, the band switches back off
+        let peak = band("Peak 1");
+        eq.set_param(&ParamKey::Native(peak + SLOT_GAIN), 12.0);
+        assert!(response_db(&mut eq, 250.0) > 11.0);
+        eq.set_param(&ParamKey::Native(peak + SLOT_GAIN), 0.0);
+        assert!(response_db(&mut eq, 250.0).abs() < 1e-4);
+    }
+}
@@ -8,7 +8,8 @@ pub struct HostFacade {
 impl HostFacade {
     #[allow(unused_variables)]
     pub fn new(cfg: HostConfig) -> Result<Self> {
-        let mut backs: Vec<Box<dyn PluginBackend>> = Vec::new();
+        let mut backs: Vec<Box<dyn PluginBackend>> =
+            vec![Box::new(crate::backend_native::Backend::new(&cfg))];
 
         #[cfg(feature = "clap-host")]
         {
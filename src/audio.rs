@@ -1,19 +1,30 @@
 use crate::audio_state::{
     AudioGraphSnapshot, AudioState, MidiClipSnapshot, PluginDescriptorSnapshot, RealtimeCommand,
-    RtAutomationLaneSnapshot, RtAutomationTarget, RtCurveType, TrackSnapshot,
+    RtAutomationLaneSnapshot, RtAutomationTarget, TrackSnapshot, VcaGroupSnapshot,
+};
+use crate::audio_utils::{
+    DelayLine, calculate_stereo_gains, db_to_linear, pitch_stretch, soft_clip,
 };
-use crate::audio_utils::{calculate_stereo_gains, soft_clip};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::constants::RECORDING_BUFFER_SIZE;
-use crate::constants::{DEBUG_PLUGIN_AUDIO, MAX_BUFFER_SIZE, PREVIEW_NOTE_DURATION};
-use crate::messages::{PluginParamInfo, UIUpdate};
+use crate::constants::{
+    DEBUG_PLUGIN_AUDIO, MAX_BUFFER_SIZE, MIN_PLUGIN_CONTROL_BLOCK, PREVIEW_NOTE_DURATION,
+};
+use crate::messages::{AudioDeviceSelection, PluginParamInfo, UIUpdate};
+use crate::metering::{CorrelationMeter, LoudnessMeter, TruePeakDetector};
+use crate::metronome::{ClickPattern, ClickPlayer};
 use crate::midi_utils::generate_sine_for_note;
 use crate::mixer::ChannelStrip;
-use crate::model::clip::AudioClip;
+use crate::model::MidiNote;
+use crate::model::clip::{AudioClip, MidiClip, loop_pass_position};
 use crate::model::track::TrackType;
+use crate::plugin_state::{StateRestore, StateRestorer};
+use crate::spectrum::{SPECTRUM_MASTER, SpectrumTap};
 use crate::time_utils::TimeConverter;
 use wasm_safe_mutex::mpsc::{Receiver, channel};
-use yadaw_plugin_api::{BackendKind, HostConfig, ParamKey, ProcessCtx, RtMidiEvent};
+use yadaw_plugin_api::{
+    BackendKind, HostConfig, ParamKey, PluginStateHandle, ProcessCtx, RtMidiEvent,
+};
 use yadaw_plugin_host::HostFacade;
 
 use crate::messages::UiTx;
@@ -21,10 +32,11 @@ use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use dashmap::DashMap;
 use rtrb::{Consumer, RingBuffer};
 use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
 use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 use std::sync::OnceLock;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// (`globalThis.performance` is unavailable in AudioWorkletGlobalScope).
 fn now_secs() -> f64 {
@@ -55,6 +67,14 @@ impl PluginCell {
 
 unsafe impl Send for PluginCell {}
 
+/// A state load the restore worker hasn't finished yet
+struct PendingRestore {
+    instance: PluginInstanceHandle,
+    /// Saved params, put back on top of the loaded state
+    params: Vec<(ParamKey, f32)>,
+    done: Arc<AtomicBool>,
+}
+
 static PLUGIN_GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
 static PLUGIN_ID_COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(1);
 
@@ -74,12 +94,47 @@ pub struct AudioEngine {
     sample_rate: f64,
     updates: UiTx,
     channel_strips: HashMap<u64, ChannelStrip>,
+    // Channels the current solos keep audible, rebuilt by `refresh_solo_audible`
+    solo_audible: Option<std::collections::HashSet<u64>>,
+    // The solo-in-place setting `solo_audible` was built with
+    solo_audible_in_place: bool,
     xrun_count: u64,
     paused_last: bool,
     host_facade: HostFacade,
     last_ui_meter_update: f64,
+    last_track_cpu_update: f64,
+    // Callbacks since the last TrackCpu report
+    cpu_report_blocks: u32,
 
     free_running_samples: f64,
+    // Samples between automation evaluations, 0 = once per block
+    automation_interval: usize,
+    // Interleaved stereo cue mix for the current callback
+    cue_buffer: Vec<f32>,
+    master_true_peak: [TruePeakDetector; 2],
+    master_correlation: CorrelationMeter,
+    master_loudness: LoudnessMeter,
+    // Loop wraps since playback started, for the transport loop count
+    loop_passes: usize,
+    // Frames left before chased MIDI timecode counts as stopped
+    timecode_timeout: Option<usize>,
+    // Post-fader L/R of tracks that key a sidechain, from the latest sub-block.
+    // Allocated by `refresh_processing_order` for every source.
+    sidechain_taps: HashMap<u64, [Vec<f32>; 2]>,
+    // A sidechain key padded to the block, reused by every keyed plugin
+    sidechain_keys: [Vec<f32>; 2],
+    // `track_order` with sidechain sources first, see `refresh_processing_order`
+    track_processing_order: Vec<u64>,
+    // Buses in the order they sum, see `bus_processing_order`
+    bus_processing_order: Vec<u64>,
+    // Largest compensated plugin latency across tracks, in frames
+    output_latency: usize,
+    // Feeds the spectrum analyzer worker, when one is running
+    spectrum_tap: Option<SpectrumTap>,
+    click: ClickPlayer,
+    // Queues plugin state loads for the restore worker; None loads them in place
+    state_restorer: Option<StateRestorer>,
+    pending_restores: Vec<PendingRestore>,
 }
 
 struct TrackProcessor {
@@ -101,6 +156,13 @@ struct TrackProcessor {
     automation_sample_buffers: HashMap<String, Vec<f32>>,
     pending_note_offs: Vec<(u8 /*ch*/, u8 /*key*/, f64 /*abs_beat*/)>,
     rt_midi_events: Vec<RtMidiEvent>,
+    // Last value each CC lane sent, so holds don't repeat it
+    sent_cc: [Option<u8>; 128],
+    // Summed latency of the active plugins, and the L/R delay that lines the track up
+    plugin_latency: u32,
+    pdc: [DelayLine; 2],
+    // Plugin time since the last TrackCpu report, in ms
+    cpu_ms: f32,
 }
 
 impl TrackProcessor {
@@ -122,6 +184,10 @@ impl TrackProcessor {
             automation_sample_buffers: HashMap::new(),
             pending_note_offs: Vec::new(),
             rt_midi_events: Vec::new(),
+            sent_cc: [None; 128],
+            plugin_latency: 0,
+            pdc: Default::default(),
+            cpu_ms: 0.0,
         };
         s.ensure_channels(2);
         s
@@ -158,13 +224,146 @@ struct PreviewNote {
     start_position: f64,
 }
 
+/// Interleaved channels carried by the input ring; mono interfaces are doubled
+const INPUT_CHANNELS: usize = 2;
+const TRACK_CPU_INTERVAL_SECS: f64 = 0.25;
+/// Timecode drift tolerated before jumping to it
+const TIMECODE_RELOCATE_SECS: f64 = 0.1;
+/// Playback stops when timecode has been silent this long
+const TIMECODE_TIMEOUT_SECS: f64 = 0.25;
+
+/// One input frame as a track hears it: the chosen channel, or both mixed
+#[inline]
+fn input_sample(frame: &[f32], channel: Option<usize>) -> f32 {
+    match channel {
+        Some(ch) => frame[ch.min(INPUT_CHANNELS - 1)],
+        None => frame.iter().sum::<f32>() / INPUT_CHANNELS as f32,
+    }
+}
+
 struct RecordingState {
     is_recording: bool,
     recording_track: Option<u64>,
     recording_consumer: Consumer<f32>,
     recording_start_position: f64,
+    // Input samples still to drop so the take starts exactly on the count-in target
+    discard_input: usize,
+    /// Samples to capture before a punch recording ends itself
+    punch_len: Option<usize>,
     accumulated_samples: Vec<f32>,
+    /// Input frames not yet monitored, `INPUT_CHANNELS` interleaved
     monitor_queue: Vec<f32>,
+    /// Armed MIDI track that incoming notes are captured for
+    midi_track: Option<u64>,
+    /// (frame since the take started, status, data1, data2)
+    midi_events: Vec<(f64, u8, u8, u8)>,
+    /// Transport frames covered since the take started, at the current block
+    elapsed_frames: f64,
+}
+
+/// Pairs captured note-ons with their note-offs; notes still held when the
+/// take ended run to its end. None when nothing was played. The clip is
+/// left unnamed; the UI names it when it stores the take.
+fn build_recorded_midi_clip(
+    events: &[(f64, u8, u8, u8)],
+    start_beat: f64,
+    length_frames: f64,
+    converter: &TimeConverter,
+) -> Option<MidiClip> {
+    let length_beats = converter.samples_to_beats(length_frames.max(0.0));
+    let mut held: HashMap<(u8, u8), (f64, u8)> = HashMap::new();
+    let mut notes = Vec::new();
+    let mut push_note = |pitch: u8, velocity: u8, start: f64, end: f64| {
+        let start = converter.samples_to_beats(start.max(0.0));
+        let end = converter.samples_to_beats(end.clamp(0.0, length_frames));
+        notes.push(MidiNote {
+            id: 0,
+            pitch,
+            velocity,
+            start,
+            duration: (end - start).max(0.01),
+        });
+    };
+
+    for &(frame, status, data1, data2) in events {
+        let key = (status & 0x0F, data1);
+        match status & 0xF0 {
+            0x90 if data2 > 0 => {
+                // Retriggering a held key closes the previous note first
+                if let Some((start, velocity)) = held.insert(key, (frame, data2)) {
+                    push_note(data1, velocity, start, frame);
+                }
+            }
+            0x80 | 0x90 => {
+                if let Some((start, velocity)) = held.remove(&key) {
+                    push_note(data1, velocity, start, frame);
+                }
+            }
+            _ => {}
+        }
+    }
+    for ((_, pitch), (start, velocity)) in held {
+        push_note(pitch, velocity, start, length_frames);
+    }
+
+    if notes.is_empty() {
+        return None;
+    }
+    notes.sort_by(|a, b| a.start.total_cmp(&b.start).then(a.pitch.cmp(&b.pitch)));
+    let length_beats = notes
+        .iter()
+        .map(|n| n.start + n.duration)
+        .fold(length_beats, f64::max);
+
+    Some(MidiClip {
+        name: String::new(),
+        start_beat,
+        length_beats,
+        content_len_beats: length_beats,
+        notes,
+        ..Default::default()
+    })
+}
+
+/// Cuts a recording that ran past the loop end into one clip per pass. Empty
+/// when it never wrapped; a sliver recorded after the last wrap is dropped.
+fn split_loop_passes(
+    clip: &AudioClip,
+    loop_start: f64,
+    loop_end: f64,
+    converter: &TimeConverter,
+) -> Vec<AudioClip> {
+    let loop_len = loop_end - loop_start;
+    if loop_len <= 0.0
+        || clip.start_beat >= loop_end
+        || clip.start_beat + clip.length_beats <= loop_end
+    {
+        return Vec::new();
+    }
+
+    let min_pass = converter.beats_to_samples(loop_len * 0.1).round() as usize;
+    let mut takes = Vec::new();
+    let mut start_beat = clip.start_beat;
+    let mut offset = 0usize;
+    while offset < clip.samples.len() {
+        let remaining = clip.samples.len() - offset;
+        let pass_len = (converter.beats_to_samples(loop_end - start_beat).round() as usize)
+            .clamp(1, remaining);
+        if !takes.is_empty() && pass_len < min_pass {
+            break;
+        }
+        takes.push(AudioClip {
+            name: format!("{} Take {}", clip.name, takes.len() + 1),
+            start_beat,
+            length_beats: converter.samples_to_beats(pass_len as f64),
+            samples: clip.samples[offset..offset + pass_len].to_vec(),
+            sample_rate: clip.sample_rate,
+            ..Default::default()
+        });
+        offset += pass_len;
+        start_beat = loop_start;
+    }
+    takes
 }
 
 fn choose_output_stream_config(
@@ -218,23 +417,134 @@ fn choose_output_stream_config(
         .unwrap_or(default_config)
 }
 
-pub fn resolve_output_sample_rate(preferred_sample_rate: f32) -> f32 {
+pub fn resolve_output_sample_rate(selection: &AudioDeviceSelection) -> f32 {
+    let host = cpal::default_host();
+    match resolve_output(&host, selection) {
+        Some((_, config)) => config.sample_rate as f32,
+        None => selection.sample_rate,
+    }
+}
+
+fn device_name(device: &cpal::Device) -> Option<String> {
+    device.description().ok().map(|d| d.name().to_string())
+}
+
+const COMMON_SAMPLE_RATES: [u32; 7] = [22050, 44100, 48000, 88200, 96000, 176400, 192000];
+
+/// An output device with the settings it accepts, for the audio setup dialog
+#[derive(Debug, Clone)]
+pub struct OutputDeviceInfo {
+    pub name: String,
+    pub sample_rates: Vec<u32>,
+    /// Frames per callback the driver allows; None when it doesn't say
+    pub buffer_range: Option<(u32, u32)>,
+}
+
+pub fn list_output_devices() -> Vec<OutputDeviceInfo> {
     let host = cpal::default_host();
-    let Some(device) = host.default_output_device() else {
-        return preferred_sample_rate;
+    let Ok(devices) = host.output_devices() else {
+        return Vec::new();
     };
+    devices
+        .filter_map(|device| {
+            let name = device_name(&device)?;
+            let ranges: Vec<_> = device.supported_output_configs().ok()?.collect();
+            let sample_rates = COMMON_SAMPLE_RATES
+                .into_iter()
+                .filter(|rate| {
+                    ranges
+                        .iter()
+                        .any(|r| (r.min_sample_rate()..=r.max_sample_rate()).contains(rate))
+                })
+                .collect();
+            let buffer_range = ranges
+                .iter()
+                .filter_map(|r| match *r.buffer_size() {
+                    cpal::SupportedBufferSize::Range { min, max } => Some((min, max)),
+                    cpal::SupportedBufferSize::Unknown => None,
+                })
+                .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)));
+            Some(OutputDeviceInfo {
+                name,
+                sample_rates,
+                buffer_range,
+            })
+        })
+        .collect()
+}
 
-    choose_output_stream_config(&device, preferred_sample_rate).sample_rate() as f32
+/// The named output, or the default one when it's gone or none is named
+fn find_output_device(host: &cpal::Host, name: Option<&str>) -> Option<cpal::Device> {
+    if let Some(name) = name {
+        let found = host
+            .output_devices()
+            .ok()
+            .and_then(|mut devices| devices.find(|d| device_name(d).as_deref() == Some(name)));
+        if found.is_some() {
+            return found;
+        }
+        log::warn!(
+            "Output device '{}' not found; using the default output",
+            name
+        );
+    }
+    host.default_output_device()
 }
 
-fn build_audio_callback(
-    mut engine: AudioEngine,
-    channels: usize,
+fn resolve_output(
+    host: &cpal::Host,
+    selection: &AudioDeviceSelection,
+) -> Option<(cpal::Device, cpal::StreamConfig)> {
+    let device = find_output_device(host, selection.output_device.as_deref())?;
+    let supported = choose_output_stream_config(&device, selection.sample_rate);
+    let requested = (selection.buffer_size.min(MAX_BUFFER_SIZE)) as u32;
+    let buffer_size = match (requested, *supported.buffer_size()) {
+        (0, _) => cpal::BufferSize::Default,
+        (frames, cpal::SupportedBufferSize::Range { min, max }) => {
+            let max = max.min(MAX_BUFFER_SIZE as u32).max(min);
+            cpal::BufferSize::Fixed(frames.clamp(min, max))
+        }
+        (frames, cpal::SupportedBufferSize::Unknown) => cpal::BufferSize::Fixed(frames),
+    };
+
+    if selection.sample_rate.round() as u32 != supported.sample_rate() {
+        log::warn!(
+            "Requested sample rate {} Hz is unavailable on this output; using {} Hz instead",
+            selection.sample_rate,
+            supported.sample_rate()
+        );
+    }
+
+    let mut config: cpal::StreamConfig = supported.into();
+    config.buffer_size = buffer_size;
+    Some((device, config))
+}
+
+/// Everything the output callback drives. It outlives any one stream, so
+/// switching devices keeps the engine, its plugins and the command channels.
+struct StreamParts {
+    engine: AudioEngine,
     realtime_commands: Receiver<RealtimeCommand>,
     snapshot_rx: Receiver<AudioGraphSnapshot>,
+}
+
+fn build_audio_callback(
+    parts: Arc<parking_lot::Mutex<StreamParts>>,
+    channels: usize,
     updates: UiTx,
 ) -> impl FnMut(&mut [f32], &cpal::OutputCallbackInfo) {
     move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+        // Only contended while the audio thread is switching streams
+        let Some(mut guard) = parts.try_lock() else {
+            data.fill(0.0);
+            return;
+        };
+        let StreamParts {
+            engine,
+            realtime_commands,
+            snapshot_rx,
+        } = &mut *guard;
+
         if let Err(panic) = std::panic::catch_unwind(AssertUnwindSafe(|| {
             let num_frames = data.len() / channels;
             let cb_start = now_secs();
@@ -253,22 +563,69 @@ fn build_audio_callback(
                 engine.apply_new_snapshot(new_snapshot);
             }
 
-            if engine.recording_state.monitor_queue.len() > 2 * MAX_BUFFER_SIZE {
-                let drop_n = engine.recording_state.monitor_queue.len() - 2 * MAX_BUFFER_SIZE;
+            let monitor_cap = 2 * MAX_BUFFER_SIZE * INPUT_CHANNELS;
+            if engine.recording_state.monitor_queue.len() > monitor_cap {
+                let drop_n = engine.recording_state.monitor_queue.len() - monitor_cap;
                 engine.recording_state.monitor_queue.drain(0..drop_n);
             }
 
-            if is_playing && should_be_recording && !is_actually_recording {
-                if engine.recording_state.recording_track.is_some() {
+            // While counting in, hold off until the block that reaches the target
+            let position = engine.audio_state.get_position();
+            let count_in_target = engine.audio_state.count_in_target.load();
+            let count_in_done =
+                count_in_target.is_nan() || position + num_frames as f64 > count_in_target;
+
+            // Punching in waits the same way; playback carries on either side
+            let punch = engine.audio_state.punch_range().map(|(start, end)| {
+                let conv =
+                    TimeConverter::new(engine.sample_rate as f32, engine.audio_state.bpm.load());
+                (conv.beats_to_samples(start), conv.beats_to_samples(end))
+            });
+            let punch_reached = punch.is_none_or(|(punch_in, punch_out)| {
+                position + num_frames as f64 > punch_in && position < punch_out
+            });
+            // Audio takes end once their samples are in; MIDI-only takes follow the transport
+            let punch_done = punch.is_some()
+                && engine.recording_state.punch_len.is_some_and(|len| {
+                    if engine.recording_state.recording_track.is_some() {
+                        engine.recording_state.accumulated_samples.len() >= len
+                    } else {
+                        engine.recording_state.elapsed_frames >= len as f64
+                    }
+                });
+
+            if is_playing
+                && should_be_recording
+                && !is_actually_recording
+                && count_in_done
+                && punch_reached
+            {
+                engine.audio_state.count_in_target.store(f64::NAN);
+                if engine.recording_state.recording_track.is_some()
+                    || engine.recording_state.midi_track.is_some()
+                {
+                    let mut start = if count_in_target.is_nan() {
+                        position
+                    } else {
+                        count_in_target.max(position)
+                    };
+                    engine.recording_state.punch_len = None;
+                    if let Some((punch_in, punch_out)) = punch {
+                        start = start.max(punch_in);
+                        engine.recording_state.punch_len =
+                            Some((punch_out - start).round().max(1.0) as usize);
+                    }
                     engine.recording_state.is_recording = true;
-                    engine.recording_state.recording_start_position =
-                        engine.audio_state.get_position();
+                    engine.recording_state.recording_start_position = start;
+                    engine.recording_state.discard_input = (start - position).round() as usize;
                     engine.recording_state.accumulated_samples.clear();
+                    engine.recording_state.midi_events.clear();
+                    engine.recording_state.elapsed_frames = position - start;
                     let _ = engine
                         .updates
                         .send_sync(UIUpdate::RecordingStateChanged(true));
                 }
-            } else if (!is_playing || !should_be_recording) && is_actually_recording {
+            } else if (!is_playing || !should_be_recording || punch_done) && is_actually_recording {
                 engine.recording_state.is_recording = false;
                 engine.audio_state.recording.store(false, Ordering::Relaxed);
                 let _ = engine
@@ -297,34 +654,103 @@ fn build_audio_callback(
                             ..Default::default()
                         };
 
+                        // Recording through a loop wrap leaves one take per pass
+                        let takes = if engine.audio_state.loop_enabled.load(Ordering::Relaxed) {
+                            split_loop_passes(
+                                &clip,
+                                engine.audio_state.loop_start.load(),
+                                engine.audio_state.loop_end.load(),
+                                &converter,
+                            )
+                        } else {
+                            Vec::new()
+                        };
+                        let update = if takes.len() > 1 {
+                            UIUpdate::TakesRecorded(track_id, takes)
+                        } else {
+                            UIUpdate::RecordingFinished(track_id, clip)
+                        };
+                        let _ = engine.updates.send_sync(update);
+                        engine.recording_state.accumulated_samples.clear();
+                    }
+                }
+
+                if let Some(track_id) = engine.recording_state.midi_track {
+                    let converter = TimeConverter::new(
+                        engine.sample_rate as f32,
+                        engine.audio_state.bpm.load(),
+                    );
+                    let rec = &engine.recording_state;
+                    let length_frames = match rec.punch_len {
+                        Some(len) => rec.elapsed_frames.min(len as f64),
+                        None => rec.elapsed_frames,
+                    };
+                    if let Some(clip) = build_recorded_midi_clip(
+                        &rec.midi_events,
+                        converter.samples_to_beats(rec.recording_start_position),
+                        length_frames,
+                        &converter,
+                    ) {
                         let _ = engine
                             .updates
-                            .send_sync(UIUpdate::RecordingFinished(track_id, clip));
-                        engine.recording_state.accumulated_samples.clear();
+                            .send_sync(UIUpdate::MidiRecordingFinished(track_id, clip));
                     }
+                    engine.recording_state.midi_events.clear();
                 }
             }
 
-            if engine.recording_state.is_recording {
-                while let Ok(sample) = engine.recording_state.recording_consumer.pop() {
-                    engine.recording_state.accumulated_samples.push(sample);
-                    engine.recording_state.monitor_queue.push(sample);
+            let rec_channel = engine
+                .recording_state
+                .recording_track
+                .and_then(|id| {
+                    engine
+                        .graph_snapshot
+                        .tracks
+                        .iter()
+                        .find(|t| t.track_id == id)
+                })
+                .and_then(|t| t.input_channel);
+            let rec = &mut engine.recording_state;
+            let capture = rec.is_recording && rec.recording_track.is_some();
+            let cap = rec.punch_len.unwrap_or(usize::MAX);
+            // Whole frames only, so a half-written one waits for the next block
+            for _ in 0..rec.recording_consumer.slots() / INPUT_CHANNELS {
+                let mut frame = [0.0f32; INPUT_CHANNELS];
+                for s in &mut frame {
+                    *s = rec.recording_consumer.pop().unwrap_or(0.0);
                 }
-            } else {
-                while let Ok(sample) = engine.recording_state.recording_consumer.pop() {
-                    engine.recording_state.monitor_queue.push(sample);
+                rec.monitor_queue.extend_from_slice(&frame);
+                if !rec.is_recording {
+                    continue;
+                }
+                if rec.discard_input > 0 {
+                    rec.discard_input -= 1;
+                } else if capture && rec.accumulated_samples.len() < cap {
+                    rec.accumulated_samples
+                        .push(input_sample(&frame, rec_channel));
                 }
             }
+            if rec.is_recording {
+                rec.elapsed_frames += num_frames as f64;
+            }
 
             if !is_playing {
                 if !engine.paused_last {
                     engine.midi_panic();
+                    engine.click.stop();
                     engine.paused_last = true;
-                    for processor in engine.track_processors.values_mut() {
+                    engine.loop_passes = 0;
+                    engine.cpu_report_blocks = 0;
+                    for (&track_id, processor) in engine.track_processors.iter_mut() {
                         processor.last_pattern_position = 0.0;
                         processor.pattern_loop_count = 0;
                         processor.notes_triggered_this_loop.clear();
                         processor.plugin_active_notes.clear();
+                        // Nothing runs while stopped
+                        processor.cpu_ms = 0.0;
+                        let _ = engine
+                            .updates
+                            .send_sync(UIUpdate::TrackCpu { track_id, ms: 0.0 });
                     }
                 }
 
@@ -343,6 +769,9 @@ fn build_audio_callback(
                 });
                 return;
             } else {
+                if engine.paused_last {
+                    engine.master_loudness.reset();
+                }
                 engine.paused_last = false;
             }
 
@@ -357,6 +786,23 @@ fn build_audio_callback(
                 &mut plugin_time_ms_accum,
             );
             engine.audio_state.set_position(next_position);
+            route_master_output(
+                data,
+                channels,
+                engine
+                    .audio_state
+                    .master_output_channel
+                    .load(Ordering::Relaxed),
+            );
+            mix_cue_output(
+                data,
+                channels,
+                engine
+                    .audio_state
+                    .cue_output_channel
+                    .load(Ordering::Relaxed),
+                &engine.cue_buffer[..num_frames * 2],
+            );
 
             let elapsed = now_secs() - cb_start;
             let budget = (num_frames as f64 / engine.sample_rate).max(1e-6);
@@ -403,36 +849,12 @@ pub fn run_audio_thread(
     realtime_commands: Receiver<RealtimeCommand>,
     updates: UiTx,
     snapshot_rx: Receiver<AudioGraphSnapshot>,
-    preferred_sample_rate: f32,
+    selection: AudioDeviceSelection,
+    reopen_rx: flume::Receiver<AudioDeviceSelection>,
 ) {
     let host = cpal::default_host();
-    let device = host.default_output_device().expect("No output device");
-    let config = choose_output_stream_config(&device, preferred_sample_rate);
-
-    let requested_rate = if preferred_sample_rate.is_finite() && preferred_sample_rate > 0.0 {
-        preferred_sample_rate.round() as u32
-    } else {
-        config.sample_rate()
-    };
-
-    if requested_rate != config.sample_rate() {
-        log::warn!(
-            "Requested sample rate {} Hz is unavailable on the default output; using {} Hz instead",
-            requested_rate,
-            config.sample_rate()
-        );
-    }
-
-    log::info!(
-        "Audio output configured: {} channels @ {} Hz",
-        config.channels(),
-        config.sample_rate()
-    );
-
-    let sample_rate = config.sample_rate() as f64;
-    let channels = config.channels() as usize;
-
-    audio_state.sample_rate.store(sample_rate as f32);
+    // Opening the stream settles the real rate; plugins follow it if it differs
+    let sample_rate = audio_state.sample_rate.load() as f64;
 
     let host_cfg = HostConfig {
         sample_rate,
@@ -442,11 +864,20 @@ pub fn run_audio_thread(
     let host_facade = HostFacade::new(host_cfg).expect("HostFacade init failed");
 
     // Create recording buffer
-    let (recording_producer, recording_consumer) = RingBuffer::<f32>::new(RECORDING_BUFFER_SIZE);
+    let (recording_producer, recording_consumer) =
+        RingBuffer::<f32>::new(RECORDING_BUFFER_SIZE * INPUT_CHANNELS);
+
+    let (spectrum_producer, spectrum_consumer) =
+        RingBuffer::<f32>::new(crate::spectrum::SPECTRUM_RING_SIZE);
+    crate::spectrum::spawn_analyzer(spectrum_consumer, audio_state.clone(), updates.clone());
+
+    let (restore_producer, restore_consumer) =
+        RingBuffer::<StateRestore>::new(crate::plugin_state::RESTORE_QUEUE_SIZE);
+    crate::plugin_state::spawn_restorer(restore_consumer);
 
     // Initialize engine
 
-    let mut engine = AudioEngine {
+    let engine = AudioEngine {
         graph_snapshot: AudioGraphSnapshot::default(),
         audio_state: audio_state.clone(),
         track_processors: HashMap::new(),
@@ -456,18 +887,43 @@ pub fn run_audio_thread(
             recording_track: None,
             recording_consumer,
             recording_start_position: 0.0,
+            discard_input: 0,
+            punch_len: None,
             accumulated_samples: Vec::new(),
             monitor_queue: Vec::new(),
+            midi_track: None,
+            midi_events: Vec::new(),
+            elapsed_frames: 0.0,
         },
         preview_note: None,
         sample_rate,
         updates: updates.clone(),
         channel_strips: HashMap::new(),
+        solo_audible: None,
+        solo_audible_in_place: false,
         xrun_count: 0,
         paused_last: false,
         host_facade,
         last_ui_meter_update: now_secs(),
+        last_track_cpu_update: now_secs(),
+        cpu_report_blocks: 0,
         free_running_samples: 0.0,
+        automation_interval: 0,
+        cue_buffer: vec![0.0; MAX_BUFFER_SIZE * 2],
+        sidechain_taps: HashMap::new(),
+        sidechain_keys: std::array::from_fn(|_| Vec::with_capacity(MAX_BUFFER_SIZE)),
+        track_processing_order: Vec::new(),
+        bus_processing_order: Vec::new(),
+        output_latency: 0,
+        master_true_peak: Default::default(),
+        master_correlation: CorrelationMeter::default(),
+        master_loudness: LoudnessMeter::new(sample_rate),
+        loop_passes: 0,
+        timecode_timeout: None,
+        spectrum_tap: Some(SpectrumTap::new(spectrum_producer)),
+        click: ClickPlayer::new(sample_rate),
+        state_restorer: Some(StateRestorer::new(restore_producer)),
+        pending_restores: Vec::new(),
     };
 
     // Start recording input thread (native only — wasm CPAL doesn't support input)
@@ -488,9 +944,14 @@ pub fn run_audio_thread(
             let input_callback = move |data: &[f32], _: &cpal::InputCallbackInfo| {
                 let mut producer = recording_producer.lock();
                 for frame in data.chunks(channels) {
-                    let mono_sample = frame.iter().sum::<f32>() / channels as f32;
-                    let _ = producer.push(mono_sample);
-                    peak_acc = peak_acc.max(mono_sample.abs());
+                    let left = frame[0];
+                    let right = frame.get(1).copied().unwrap_or(left);
+                    // Keep frames whole when the ring is full
+                    if producer.slots() >= INPUT_CHANNELS {
+                        let _ = producer.push(left);
+                        let _ = producer.push(right);
+                    }
+                    peak_acc = peak_acc.max(left.abs()).max(right.abs());
                 }
 
                 let elapsed = last_meter.elapsed();
@@ -516,26 +977,93 @@ pub fn run_audio_thread(
         }
     });
 
-    // Audio callback
-    let audio_callback = build_audio_callback(
+    let parts = Arc::new(parking_lot::Mutex::new(StreamParts {
         engine,
-        channels,
         realtime_commands,
         snapshot_rx,
-        updates.clone(),
-    );
+    }));
+    // The stream lives on this thread; a new selection replaces it in place
+    let mut selection = selection;
+    loop {
+        let stream = open_stream(&host, &selection, &parts, &updates).or_else(|| {
+            log::warn!("Falling back to the default output device");
+            let fallback = AudioDeviceSelection {
+                output_device: None,
+                buffer_size: 0,
+                ..selection.clone()
+            };
+            open_stream(&host, &fallback, &parts, &updates)
+        });
+        if stream.is_none() {
+            let _ = updates.send_sync(UIUpdate::Error(
+                "No audio output could be opened. Pick another device in Audio Setup.".into(),
+            ));
+        }
 
-    let stream = device
-        .build_output_stream(
-            config.into(),
-            audio_callback,
-            |err| log::error!("Audio stream error: {}", err),
-            None,
-        )
-        .expect("Failed to create audio stream");
+        let Ok(next) = reopen_rx.recv() else {
+            break;
+        };
+        drop(stream);
+        selection = next;
+    }
+}
+
+/// Opens `selection` and runs the engine on it; None if the device refuses
+#[cfg(not(target_arch = "wasm32"))]
+fn open_stream(
+    host: &cpal::Host,
+    selection: &AudioDeviceSelection,
+    parts: &Arc<parking_lot::Mutex<StreamParts>>,
+    updates: &UiTx,
+) -> Option<cpal::Stream> {
+    let (device, config) = resolve_output(host, selection)?;
+    let channels = config.channels as usize;
+    {
+        let mut parts = parts.lock();
+        parts.engine.set_sample_rate(config.sample_rate as f64);
+        parts
+            .engine
+            .audio_state
+            .output_channels
+            .store(channels, Ordering::Relaxed);
+    }
+
+    let callback = build_audio_callback(parts.clone(), channels, updates.clone());
+    let stream = match device.build_output_stream(
+        config.clone(),
+        callback,
+        |err| log::error!("Audio stream error: {}", err),
+        None,
+    ) {
+        Ok(stream) => stream,
+        Err(e) => {
+            log::error!("Failed to create audio stream: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = stream.play() {
+        log::error!("Failed to start audio stream: {}", e);
+        return None;
+    }
 
-    stream.play().expect("Failed to start audio stream");
-    std::thread::park();
+    let device = device_name(&device).unwrap_or_default();
+    let buffer_size = match config.buffer_size {
+        cpal::BufferSize::Fixed(frames) => frames,
+        cpal::BufferSize::Default => 0,
+    };
+    log::info!(
+        "Audio output '{}' configured: {} channels @ {} Hz, buffer {}",
+        device,
+        channels,
+        config.sample_rate,
+        buffer_size
+    );
+    let _ = updates.send_sync(UIUpdate::AudioDeviceOpened {
+        device,
+        sample_rate: config.sample_rate as f32,
+        buffer_size,
+    });
+    Some(stream)
 }
 
 /// Wrapper to make `cpal::Stream` `Sync` for wasm storage.
@@ -605,6 +1133,9 @@ pub fn run_audio_wasm(
     let channels = config.channels() as usize;
 
     audio_state.sample_rate.store(sample_rate as f32);
+    audio_state
+        .output_channels
+        .store(channels, Ordering::Relaxed);
 
     let host_cfg = HostConfig {
         sample_rate,
@@ -616,7 +1147,7 @@ pub fn run_audio_wasm(
     // Create a dummy recording consumer (CPAL wasm backend doesn't support input)
     let (_, recording_consumer) = RingBuffer::<f32>::new(1);
 
-    let mut engine = AudioEngine {
+    let engine = AudioEngine {
         graph_snapshot: AudioGraphSnapshot::default(),
         audio_state: audio_state.clone(),
         track_processors: HashMap::new(),
@@ -626,22 +1157,52 @@ pub fn run_audio_wasm(
             recording_track: None,
             recording_consumer,
             recording_start_position: 0.0,
+            discard_input: 0,
+            punch_len: None,
             accumulated_samples: Vec::new(),
             monitor_queue: Vec::new(),
+            midi_track: None,
+            midi_events: Vec::new(),
+            elapsed_frames: 0.0,
         },
         preview_note: None,
         sample_rate,
         updates: updates.clone(),
         channel_strips: HashMap::new(),
+        solo_audible: None,
+        solo_audible_in_place: false,
         xrun_count: 0,
         paused_last: false,
         host_facade,
         last_ui_meter_update: now_secs(),
+        last_track_cpu_update: now_secs(),
+        cpu_report_blocks: 0,
         free_running_samples: 0.0,
+        automation_interval: 0,
+        cue_buffer: vec![0.0; MAX_BUFFER_SIZE * 2],
+        sidechain_taps: HashMap::new(),
+        sidechain_keys: std::array::from_fn(|_| Vec::with_capacity(MAX_BUFFER_SIZE)),
+        track_processing_order: Vec::new(),
+        bus_processing_order: Vec::new(),
+        output_latency: 0,
+        master_true_peak: Default::default(),
+        master_correlation: CorrelationMeter::default(),
+        master_loudness: LoudnessMeter::new(sample_rate),
+        loop_passes: 0,
+        timecode_timeout: None,
+        spectrum_tap: None,
+        click: ClickPlayer::new(sample_rate),
+        state_restorer: None,
+        pending_restores: Vec::new(),
     };
 
-    let audio_callback =
-        build_audio_callback(engine, channels, realtime_commands, snapshot_rx, updates);
+    let parts = Arc::new(parking_lot::Mutex::new(StreamParts {
+        engine,
+        realtime_commands,
+        snapshot_rx,
+    }));
+    // The page keeps a single stream, so device changes aren't handed back
+    let audio_callback = build_audio_callback(parts, channels, updates);
 
     let stream = device
         .build_output_stream(
@@ -719,18 +1280,43 @@ impl AudioEngine {
                 // Use dummy ring buffer for offline mode
                 recording_consumer: rtrb::RingBuffer::<f32>::new(1).1,
                 recording_start_position: 0.0,
+                discard_input: 0,
+                punch_len: None,
                 accumulated_samples: Vec::new(),
                 monitor_queue: Vec::new(),
+                midi_track: None,
+                midi_events: Vec::new(),
+                elapsed_frames: 0.0,
             },
             preview_note: None,
             sample_rate: export_sample_rate as f64,
             updates: dummy_tx,
             channel_strips: HashMap::new(),
+            solo_audible: None,
+            solo_audible_in_place: false,
             xrun_count: 0,
             paused_last: false,
             host_facade,
             last_ui_meter_update: now_secs(),
+            last_track_cpu_update: now_secs(),
+            cpu_report_blocks: 0,
             free_running_samples: 0.0,
+            automation_interval: 0,
+            cue_buffer: vec![0.0; MAX_BUFFER_SIZE * 2],
+            sidechain_taps: HashMap::new(),
+            sidechain_keys: std::array::from_fn(|_| Vec::with_capacity(MAX_BUFFER_SIZE)),
+            track_processing_order: Vec::new(),
+            bus_processing_order: Vec::new(),
+            output_latency: 0,
+            master_true_peak: Default::default(),
+            master_correlation: CorrelationMeter::default(),
+            master_loudness: LoudnessMeter::new(export_sample_rate as f64),
+            loop_passes: 0,
+            timecode_timeout: None,
+            spectrum_tap: None,
+            click: ClickPlayer::new(export_sample_rate as f64),
+            state_restorer: None,
+            pending_restores: Vec::new(),
         };
 
         engine.full_sync_for_offline_setup(initial_tracks);
@@ -738,10 +1324,68 @@ impl AudioEngine {
         Ok(engine)
     }
 
+    /// Evaluate automation every `samples` frames (0 = once per block)
+    pub fn set_automation_interval(&mut self, samples: usize) {
+        self.automation_interval = samples;
+    }
+
+    pub fn set_master_clip_bypass(&mut self, bypass: bool) {
+        self.audio_state
+            .master_clip_bypass
+            .store(bypass, Ordering::Relaxed);
+    }
+
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.audio_state.master_volume.store(volume);
+    }
+
+    pub fn set_vca_groups(&mut self, groups: HashMap<u64, VcaGroupSnapshot>) {
+        self.graph_snapshot.groups = groups;
+        self.refresh_solo_audible();
+    }
+
+    /// Rebuilds the set of channels the solos keep. Runs when the graph, a
+    /// solo or the solo-in-place setting changes, not every block.
+    fn refresh_solo_audible(&mut self) {
+        self.solo_audible_in_place = self.audio_state.solo_in_place.load(Ordering::Relaxed);
+        self.solo_audible = solo_audible(
+            &self.graph_snapshot,
+            &self.channel_strips,
+            self.solo_audible_in_place,
+        );
+    }
+
+    /// Orders the tracks and buses for processing and sets up the sidechain
+    /// taps. Runs when the graph changes, so the callback itself doesn't
+    /// allocate for them.
+    fn refresh_processing_order(&mut self) {
+        // Sidechain sources go first so their keys are current when the
+        // plugins listening to them run
+        let sources: std::collections::HashSet<u64> = self
+            .graph_snapshot
+            .tracks
+            .iter()
+            .flat_map(|t| t.plugin_chain.iter().filter_map(|p| p.sidechain_source))
+            .collect();
+        self.track_processing_order
+            .clone_from(&self.graph_snapshot.track_order);
+        self.track_processing_order
+            .sort_by_key(|id| !sources.contains(id));
+        self.bus_processing_order = bus_processing_order(&self.graph_snapshot.tracks);
+
+        self.sidechain_taps.retain(|id, _| sources.contains(id));
+        for id in sources {
+            self.sidechain_taps
+                .entry(id)
+                .or_insert_with(|| std::array::from_fn(|_| Vec::with_capacity(MAX_BUFFER_SIZE)));
+        }
+    }
+
     fn full_sync_for_offline_setup(&mut self, tracks: &[TrackSnapshot]) {
         // 1. Clear any existing state
         self.track_processors.clear();
         self.channel_strips.clear();
+        self.audio_state.plugin_state_handles.clear();
 
         // 2. Build new processors and instantiate all plugins
         for track_snapshot in tracks {
@@ -760,6 +1404,7 @@ impl AudioEngine {
                 {
                     Ok(mut inst) => {
                         // Apply all saved parameters to the new instance
+                        let mut applied = Vec::new();
                         for param_entry in plugin_snapshot.params.iter() {
                             let param_name = param_entry.key();
                             let param_value = *param_entry.value();
@@ -770,6 +1415,7 @@ impl AudioEngine {
                                 .map(|p| p.key.clone());
                             if let Some(key) = maybe_key {
                                 inst.set_param(&key, param_value);
+                                applied.push((key, param_value));
                             }
                         }
 
@@ -778,6 +1424,13 @@ impl AudioEngine {
                             handle,
                             PluginCell(Arc::new(parking_lot::Mutex::new(Box::from(inst)))),
                         );
+                        self.attach_plugin_state(
+                            plugin_id,
+                            handle,
+                            plugin_snapshot.state.clone(),
+                            applied,
+                            &plugin_snapshot.uri,
+                        );
 
                         let param_name_to_key: HashMap<String, ParamKey> =
                             if let Some(cell) = self.plugin_instances.get(&handle) {
@@ -834,13 +1487,23 @@ impl AudioEngine {
         self.graph_snapshot = AudioGraphSnapshot {
             tracks: tracks.to_vec(),
             track_order: tracks.iter().map(|t| t.track_id).collect(),
+            groups: std::mem::take(&mut self.graph_snapshot.groups),
+            time_signature: self.graph_snapshot.time_signature,
         };
 
-        // 4. Update the recording track reference (though it won't be used)
+        // 4. Update the tracks a recording captures audio and MIDI for
         self.recording_state.recording_track = tracks
             .iter()
             .find(|t| t.armed && !matches!(t.track_type, TrackType::Midi))
             .map(|t| t.track_id);
+        self.recording_state.midi_track = tracks
+            .iter()
+            .find(|t| t.armed && matches!(t.track_type, TrackType::Midi))
+            .map(|t| t.track_id);
+
+        self.refresh_solo_audible();
+        self.refresh_processing_order();
+        self.update_latency_compensation();
     }
 
     fn process_realtime_command(&mut self, cmd: RealtimeCommand) {
@@ -864,6 +1527,28 @@ impl AudioEngine {
                 if let Some(strip) = self.channel_strips.get_mut(&track_id) {
                     strip.solo = solo;
                 }
+                self.refresh_solo_audible();
+            }
+            RealtimeCommand::UpdateSendAmount(track_id, index, amount) => {
+                if let Some(send) = self
+                    .graph_snapshot
+                    .tracks
+                    .iter_mut()
+                    .find(|t| t.track_id == track_id)
+                    .and_then(|t| t.sends.get_mut(index))
+                {
+                    // Opening or closing a send changes what soloing in place keeps
+                    let toggled = (send.amount > 0.0) != (amount > 0.0);
+                    send.amount = amount;
+                    if toggled {
+                        self.refresh_solo_audible();
+                    }
+                }
+            }
+            RealtimeCommand::UpdateGroupVca(group_id, gain) => {
+                if let Some(group) = self.graph_snapshot.groups.get_mut(&group_id) {
+                    group.gain = gain;
+                }
             }
 
             RealtimeCommand::UpdatePluginBypass(track_id, plugin_id, bypass) => {
@@ -872,6 +1557,7 @@ impl AudioEngine {
                         plugin.bypass = bypass;
                     }
                 }
+                self.update_latency_compensation();
             }
 
             RealtimeCommand::PreviewNote(track_id, pitch, start_position) => {
@@ -893,11 +1579,16 @@ impl AudioEngine {
                 self.audio_state.loop_start.store(start);
                 self.audio_state.loop_end.store(end);
             }
+            RealtimeCommand::ResetLoopPasses => {
+                self.loop_passes = 0;
+            }
             RealtimeCommand::AddUnifiedPlugin {
                 track_id,
                 plugin_id,
                 backend,
                 uri,
+                state,
+                params,
             } => {
                 let proc = self
                     .track_processors
@@ -905,12 +1596,23 @@ impl AudioEngine {
                     .or_insert_with(|| TrackProcessor::new());
 
                 match self.host_facade.instantiate(backend, &uri) {
-                    Ok(inst) => {
+                    Ok(mut inst) => {
                         let mut name_to_key = HashMap::new();
                         for p in inst.params() {
                             name_to_key.insert(p.name.clone(), p.key.clone());
                         }
 
+                        let mut applied = Vec::with_capacity(params.len());
+                        for (name, value) in params {
+                            let key = match name_to_key.get(&name) {
+                                Some(key) => key.clone(),
+                                None if backend == BackendKind::Lv2 => ParamKey::Lv2(name),
+                                None => continue,
+                            };
+                            inst.set_param(&key, value);
+                            applied.push((key, value));
+                        }
+
                         let params_for_ui: Vec<PluginParamInfo> = inst
                             .params()
                             .iter()
@@ -962,6 +1664,8 @@ impl AudioEngine {
                             has_editor,
                             params: params_for_ui,
                         });
+                        self.attach_plugin_state(plugin_id, handle, state, applied, &uri);
+                        self.update_latency_compensation();
                     }
                     Err(e) => {
                         let msg = format!("Failed to instantiate plugin {}: {}", uri, e);
@@ -983,6 +1687,8 @@ impl AudioEngine {
                     }
                     proc.plugin_order.retain(|&id| id != plugin_id);
                 }
+                self.audio_state.plugin_state_handles.remove(&plugin_id);
+                self.update_latency_compensation();
             }
 
             RealtimeCommand::UpdatePluginParam(track_id, plugin_id, param_name, value) => {
@@ -998,6 +1704,7 @@ impl AudioEngine {
                                     BackendKind::Lv2 => ParamKey::Lv2(param_name.clone()),
                                     BackendKind::Clap => ParamKey::Clap(0),
                                     BackendKind::Vst3 => ParamKey::Vst3(0),
+                                    BackendKind::Native => ParamKey::Native(0),
                                 });
 
                             if let Some(cell) = self.plugin_instances.get(&handle) {
@@ -1012,7 +1719,26 @@ impl AudioEngine {
                 status,
                 data1,
                 data2,
+                timestamp_us,
             } => {
+                if self.recording_state.is_recording
+                    && self.recording_state.midi_track == Some(track_id)
+                {
+                    // Place the event where the transport was when it arrived, not at this block
+                    let age_us = (now_secs() * 1_000_000.0 - timestamp_us as f64).max(0.0);
+                    let age = (age_us * self.sample_rate / 1_000_000.0).min(MAX_BUFFER_SIZE as f64);
+                    let frame = self.recording_state.elapsed_frames - age;
+                    let in_punch = self
+                        .recording_state
+                        .punch_len
+                        .is_none_or(|len| frame < len as f64);
+                    if frame >= 0.0 && in_punch {
+                        self.recording_state
+                            .midi_events
+                            .push((frame, status, data1, data2));
+                    }
+                }
+
                 let proc = self
                     .track_processors
                     .entry(track_id)
@@ -1058,6 +1784,15 @@ impl AudioEngine {
             }
             RealtimeCommand::RebuildTrackChain { track_id, chain } => {
                 self.rebuild_track_chain_rt(track_id, &chain);
+                self.update_latency_compensation();
+            }
+            RealtimeCommand::ReorderPlugins { track_id, order } => {
+                // Instances are kept, so plugin state and held notes carry over
+                if let Some(proc) = self.track_processors.get_mut(&track_id) {
+                    proc.plugin_order.sort_unstable_by_key(|id| {
+                        order.iter().position(|o| o == id).unwrap_or(usize::MAX)
+                    });
+                }
             }
             RealtimeCommand::OpenPluginEditor(track_id, plugin_id) => {
                 if let Some(proc) = self.track_processors.get(&track_id) {
@@ -1075,6 +1810,36 @@ impl AudioEngine {
                     }
                 }
             }
+            RealtimeCommand::SetClickSounds(sounds) => {
+                self.click.set_sounds(sounds);
+            }
+            RealtimeCommand::SyncTempo(bpm) => {
+                // Position is stored in samples, so rescale it to keep the beat in place
+                let old_bpm = self.audio_state.bpm.load();
+                self.audio_state.bpm.store(bpm);
+                if old_bpm > 0.0 && (old_bpm - bpm).abs() > f32::EPSILON {
+                    let position = self.audio_state.get_position();
+                    self.audio_state
+                        .set_position(position * old_bpm as f64 / bpm as f64);
+                }
+            }
+            RealtimeCommand::SyncLocate(beat) => {
+                let converter =
+                    TimeConverter::new(self.sample_rate as f32, self.audio_state.bpm.load());
+                self.audio_state
+                    .set_position(converter.beats_to_samples(beat.max(0.0)));
+                self.loop_passes = 0;
+            }
+            RealtimeCommand::SyncTimecode(seconds) => {
+                // At a fixed tempo, timeline seconds map straight to samples
+                let target = seconds * self.sample_rate;
+                let position = self.audio_state.get_position();
+                if (target - position).abs() > TIMECODE_RELOCATE_SECS * self.sample_rate {
+                    self.audio_state.set_position(target);
+                    self.loop_passes = 0;
+                }
+                self.timecode_timeout = Some((TIMECODE_TIMEOUT_SECS * self.sample_rate) as usize);
+            }
             _ => {}
         }
     }
@@ -1089,6 +1854,32 @@ impl AudioEngine {
     ) -> f64 {
         let bpm = self.audio_state.bpm.load();
         let master_volume = self.audio_state.master_volume.load();
+        let clip_bypass = self.audio_state.master_clip_bypass.load(Ordering::Relaxed);
+        let master_clip = |s: f32| if clip_bypass { s } else { soft_clip(s) };
+        let cue_level = self.audio_state.cue_level.load();
+        let cue_click = self.audio_state.cue_click.load(Ordering::Relaxed);
+        let true_peak = self.audio_state.true_peak_metering.load(Ordering::Relaxed);
+        let spectrum_source = self.audio_state.spectrum_source.load(Ordering::Relaxed);
+
+        self.finish_state_restores();
+
+        // Timecode stopped arriving: the master has stopped
+        if let Some(left) = self.timecode_timeout {
+            self.timecode_timeout = left.checked_sub(num_frames).filter(|&l| l > 0);
+            if self.timecode_timeout.is_none() {
+                self.audio_state.playing.store(false, Ordering::Relaxed);
+            }
+        }
+
+        // Sized for MAX_BUFFER_SIZE up front; only an oversized callback grows it
+        if self.cue_buffer.len() < num_frames * 2 {
+            self.cue_buffer.resize(num_frames * 2, 0.0);
+        }
+        self.cue_buffer[..num_frames * 2].fill(0.0);
+
+        if self.audio_state.solo_in_place.load(Ordering::Relaxed) != self.solo_audible_in_place {
+            self.refresh_solo_audible();
+        }
 
         let loop_enabled = self.audio_state.loop_enabled.load(Ordering::Relaxed);
         let loop_start_beats = self.audio_state.loop_start.load();
@@ -1099,15 +1890,31 @@ impl AudioEngine {
         let loop_end_samp = converter.beats_to_samples(loop_end_beats);
 
         let loop_active = loop_enabled && (loop_end_samp - loop_start_samp) >= 1.0;
+        let loop_count = self.audio_state.loop_count.load(Ordering::Relaxed);
 
-        // snapshot track order once (avoid borrowing self later)
-        let track_order_ids: Vec<u64> = self.graph_snapshot.track_order.clone();
+        // Taken for the callback so iterating them doesn't borrow self, put back at the end
+        let track_order_ids = std::mem::take(&mut self.track_processing_order);
+        let bus_ids = std::mem::take(&mut self.bus_processing_order);
 
         // Meters
         let mut track_peaks: HashMap<u64, (f32, f32)> = HashMap::new();
         let mut master_peak_l = 0.0f32;
         let mut master_peak_r = 0.0f32;
 
+        // Plugin params are pushed once per sub-block, so a finer control rate
+        // means shorter sub-blocks while any automation exists
+        let automation_block_cap = if self.automation_interval > 0
+            && self
+                .graph_snapshot
+                .tracks
+                .iter()
+                .any(|t| !t.automation_lanes.is_empty())
+        {
+            self.automation_interval.max(MIN_PLUGIN_CONTROL_BLOCK)
+        } else {
+            MAX_BUFFER_SIZE
+        };
+
         let mut frames_processed = 0usize;
 
         while frames_processed < num_frames {
@@ -1127,6 +1934,7 @@ impl AudioEngine {
 
             let mut frames_to_process = (num_frames - frames_processed)
                 .min(MAX_BUFFER_SIZE)
+                .min(automation_block_cap)
                 .min(frames_to_loop_end);
 
             if loop_active && frames_to_process == 0 {
@@ -1134,29 +1942,24 @@ impl AudioEngine {
                 for processor in self.track_processors.values_mut() {
                     processor.active_notes.clear();
                 }
+                self.loop_passes += 1;
+                if loop_count > 0 && self.loop_passes >= loop_count {
+                    // Rest of the callback stays silent; the next one sees the stop
+                    self.audio_state.playing.store(false, Ordering::Relaxed);
+                    break;
+                }
                 continue;
             }
             if frames_to_process == 0 {
                 frames_to_process = 1;
             }
 
-            // Solo/mute state (short immutable borrow; ends at statement)
-            let any_track_soloed = self.channel_strips.values().any(|s| s.solo);
-
             // Snapshots used for this block
             let preview_opt = self.preview_note.clone();
             let is_recording_now = self.audio_state.recording.load(Ordering::Relaxed);
             let rec_track_id = self.recording_state.recording_track;
 
             // Build Bus accumulators for this sub-block (track_id -> L/R buffers)
-            let bus_ids: Vec<u64> = self
-                .graph_snapshot
-                .tracks
-                .iter()
-                .filter(|t| matches!(t.track_type, TrackType::Bus))
-                .map(|t| t.track_id)
-                .collect();
-
             let mut bus_accum_l: HashMap<u64, Vec<f32>> = HashMap::new();
             let mut bus_accum_r: HashMap<u64, Vec<f32>> = HashMap::new();
             for bid in &bus_ids {
@@ -1178,18 +1981,24 @@ impl AudioEngine {
                 if matches!(track.track_type, TrackType::Bus) {
                     continue; // handled in second pass
                 }
+                let vca = self.graph_snapshot.vca(track.group_id);
 
                 // Compute solo/mute flags without binding a long-lived reference
-                let strip_mute = self
-                    .channel_strips
-                    .get(&track_id)
-                    .map_or(track.muted, |s| s.mute);
-                let strip_solo = self
-                    .channel_strips
-                    .get(&track_id)
-                    .map_or(track.solo, |s| s.solo);
-
-                if strip_mute || (any_track_soloed && !strip_solo) {
+                let strip_mute = vca.muted
+                    || self
+                        .channel_strips
+                        .get(&track_id)
+                        .map_or(track.muted, |s| s.mute);
+                let soloed_out = self
+                    .solo_audible
+                    .as_ref()
+                    .is_some_and(|audible| !audible.contains(&track_id));
+
+                if strip_mute || soloed_out {
+                    // Silenced tracks key nothing
+                    if let Some(tap) = self.sidechain_taps.get_mut(&track_id) {
+                        tap.iter_mut().for_each(Vec::clear);
+                    }
                     continue;
                 }
 
@@ -1203,10 +2012,20 @@ impl AudioEngine {
                             block_start_samples,
                             frames_to_process,
                             &converter,
+                            self.automation_interval,
                         );
 
-                        // Build pre-plugin buffers from clips
-                        if matches!(track.track_type, TrackType::Midi) {
+                        // Build pre-plugin buffers from clips (or the frozen render)
+                        if let Some(frozen) = &track.frozen_buffer {
+                            process_frozen_track(
+                                frozen,
+                                track.frozen_sample_rate,
+                                processor,
+                                frames_to_process,
+                                block_start_samples,
+                                self.sample_rate,
+                            );
+                        } else if matches!(track.track_type, TrackType::Midi) {
                             process_midi_track(
                                 &track,
                                 processor,
@@ -1246,55 +2065,68 @@ impl AudioEngine {
                         if track.monitor_enabled
                             || (is_recording_now && Some(track_id) == rec_track_id)
                         {
-                            let take = self
+                            let gain = db_to_linear(track.monitor_gain_db);
+                            let frames = self
                                 .recording_state
                                 .monitor_queue
-                                .len()
-                                .min(frames_to_process);
-                            for i in 0..take {
-                                let s = self.recording_state.monitor_queue[i];
+                                .chunks_exact(INPUT_CHANNELS)
+                                .take(frames_to_process);
+                            for (i, frame) in frames.enumerate() {
+                                let s = input_sample(frame, track.input_channel) * gain;
                                 processor.input_buffers[0][i] += s;
                                 processor.input_buffers[1][i] += s;
                             }
-                            if take > 0 {
-                                self.recording_state.monitor_queue.drain(..take);
-                            }
                         }
                     }
                 }
 
                 // Plugin chain with no outstanding &mut borrow on processor
-                self.run_plugin_chain(
-                    &track,
-                    track_id,
-                    frames_to_process,
-                    block_start_samples,
-                    bpm,
-                    self.sample_rate,
-                    loop_active,
-                    loop_start_beats,
-                    loop_end_beats,
-                    plugin_time_ms_accum,
-                    true,
-                );
+                if track.frozen_buffer.is_none() {
+                    self.run_plugin_chain(
+                        &track,
+                        track_id,
+                        frames_to_process,
+                        block_start_samples,
+                        bpm,
+                        self.sample_rate,
+                        loop_active,
+                        loop_start_beats,
+                        loop_end_beats,
+                        plugin_time_ms_accum,
+                        true,
+                    );
+                }
 
                 // Mix to master, with per-sample automation fallback (re-borrow briefly)
                 // First, compute strip vol/pan in a tiny scope so the borrow ends before we borrow processor mutably.
                 let (strip_volume, strip_pan) = {
                     let strip = self.channel_strips.get(&track_id);
                     (
-                        strip.map_or(track.volume, |s| s.gain),
+                        strip.map_or(track.volume, |s| s.gain) * vca.gain,
                         strip.map_or(track.pan, |s| s.pan),
                     )
                 };
 
                 if let Some(processor) = self.track_processors.get_mut(&track_id) {
+                    for (line, buf) in processor.pdc.iter_mut().zip(&mut processor.input_buffers) {
+                        line.process(&mut buf[..frames_to_process]);
+                    }
+
                     let vol_automation = processor.automation_sample_buffers.get("volume");
                     let pan_automation = processor.automation_sample_buffers.get("pan");
 
                     let mut tp_l = 0.0f32;
                     let mut tp_r = 0.0f32;
 
+                    let mut tap = self.sidechain_taps.get_mut(&track_id);
+                    if let Some(tap) = tap.as_mut() {
+                        tap.iter_mut().for_each(Vec::clear);
+                    }
+                    let mut spectrum = self
+                        .spectrum_tap
+                        .as_mut()
+                        .filter(|_| spectrum_source == track_id);
+
                     for i in 0..frames_to_process {
                         // Determine gain/pan per sample
                         let vol = vol_automation.map_or_else(
@@ -1321,21 +2153,47 @@ impl AudioEngine {
 
                         let (left_gain, right_gain) = calculate_stereo_gains(vol, pan);
 
-                        let l_src = processor.input_buffers[0][i]; // post-plugins, pre-track strip
-                        let r_src = processor.input_buffers[1][i];
+                        // post-plugins, pre-track strip
+                        let (l_src, r_src) = channel_utility(
+                            &track,
+                            processor.input_buffers[0][i],
+                            processor.input_buffers[1][i],
+                        );
 
                         let l = l_src * left_gain;
                         let r = r_src * right_gain;
 
-                        let out_idx = (frames_processed + i) * channels;
-                        output[out_idx] += l;
-                        if channels > 1 {
-                            output[out_idx + 1] += r;
+                        if let Some(bus) = track.output_bus
+                            && let (Some(acc_l), Some(acc_r)) =
+                                (bus_accum_l.get_mut(&bus), bus_accum_r.get_mut(&bus))
+                        {
+                            acc_l[i] += l;
+                            acc_r[i] += r;
+                        } else {
+                            let out_idx = (frames_processed + i) * channels;
+                            output[out_idx] += l;
+                            if channels > 1 {
+                                output[out_idx + 1] += r;
+                            }
+                        }
+
+                        if track.cue {
+                            let cue_idx = (frames_processed + i) * 2;
+                            self.cue_buffer[cue_idx] += l_src * cue_level;
+                            self.cue_buffer[cue_idx + 1] += r_src * cue_level;
                         }
 
                         tp_l = tp_l.max(l.abs());
                         tp_r = tp_r.max(r.abs());
 
+                        if let Some([tap_l, tap_r]) = tap.as_mut() {
+                            tap_l.push(l);
+                            tap_r.push(r);
+                        }
+                        if let Some(spectrum) = spectrum.as_mut() {
+                            spectrum.push(l, r);
+                        }
+
                         // Route sends to Bus accumulators
                         for s in &track.sends {
                             if s.muted || s.amount <= 0.0 {
@@ -1374,6 +2232,23 @@ impl AudioEngine {
                 let Some(bus_track) = bus_track_opt else {
                     continue;
                 };
+                let vca = self.graph_snapshot.vca(bus_track.group_id);
+
+                let bus_mute = vca.muted
+                    || self
+                        .channel_strips
+                        .get(&bus_id)
+                        .map_or(bus_track.muted, |s| s.mute);
+                let soloed_out = self
+                    .solo_audible
+                    .as_ref()
+                    .is_some_and(|audible| !audible.contains(&bus_id));
+                if bus_mute || soloed_out {
+                    if let Some(tap) = self.sidechain_taps.get_mut(&bus_id) {
+                        tap.iter_mut().for_each(Vec::clear);
+                    }
+                    continue;
+                }
 
                 // Feed accumulators and apply automation (short borrow)
                 {
@@ -1399,6 +2274,7 @@ impl AudioEngine {
                             block_start_samples,
                             frames_to_process,
                             &converter,
+                            self.automation_interval,
                         );
                     }
                 }
@@ -1418,11 +2294,11 @@ impl AudioEngine {
                     true,
                 );
 
-                // Mix bus to master (re-borrow briefly)
+                // Mix bus to its output (re-borrow briefly)
                 let (strip_volume, strip_pan) = {
                     let strip = self.channel_strips.get(&bus_id);
                     (
-                        strip.map_or(bus_track.volume, |s| s.gain),
+                        strip.map_or(bus_track.volume, |s| s.gain) * vca.gain,
                         strip.map_or(bus_track.pan, |s| s.pan),
                     )
                 };
@@ -1432,61 +2308,143 @@ impl AudioEngine {
                     let mut tp_l = 0.0f32;
                     let mut tp_r = 0.0f32;
 
+                    // Buses run after every track, so a bus key reaches track plugins a block late
+                    let mut tap = self.sidechain_taps.get_mut(&bus_id);
+                    if let Some(tap) = tap.as_mut() {
+                        tap.iter_mut().for_each(Vec::clear);
+                    }
+                    let mut spectrum = self
+                        .spectrum_tap
+                        .as_mut()
+                        .filter(|_| spectrum_source == bus_id);
+
                     for i in 0..frames_to_process {
-                        let l = proc.input_buffers[0][i] * left_gain;
-                        let r = proc.input_buffers[1][i] * right_gain;
-                        let out_idx = (frames_processed + i) * channels;
-                        output[out_idx] += l;
-                        if channels > 1 {
-                            output[out_idx + 1] += r;
+                        let (l_src, r_src) = channel_utility(
+                            &bus_track,
+                            proc.input_buffers[0][i],
+                            proc.input_buffers[1][i],
+                        );
+                        let l = l_src * left_gain;
+                        let r = r_src * right_gain;
+                        if let Some(bus) = bus_track.output_bus
+                            && let (Some(acc_l), Some(acc_r)) =
+                                (bus_accum_l.get_mut(&bus), bus_accum_r.get_mut(&bus))
+                        {
+                            acc_l[i] += l;
+                            acc_r[i] += r;
+                        } else {
+                            let out_idx = (frames_processed + i) * channels;
+                            output[out_idx] += l;
+                            if channels > 1 {
+                                output[out_idx + 1] += r;
+                            }
+                        }
+                        if bus_track.cue {
+                            let cue_idx = (frames_processed + i) * 2;
+                            self.cue_buffer[cue_idx] += l_src * cue_level;
+                            self.cue_buffer[cue_idx + 1] += r_src * cue_level;
                         }
                         tp_l = tp_l.max(l.abs());
                         tp_r = tp_r.max(r.abs());
+                        if let Some([tap_l, tap_r]) = tap.as_mut() {
+                            tap_l.push(l);
+                            tap_r.push(r);
+                        }
+                        if let Some(spectrum) = spectrum.as_mut() {
+                            spectrum.push(l, r);
+                        }
                     }
                     track_peaks.insert(bus_id, (tp_l, tp_r));
                 }
             }
 
-            // Metronome (write interleaved, absolute frame index)
-            if self.audio_state.metronome_enabled.load(Ordering::Relaxed) {
+            // Every monitoring track heard the same input; move past it once
+            let heard =
+                (self.recording_state.monitor_queue.len() / INPUT_CHANNELS).min(frames_to_process);
+            self.recording_state
+                .monitor_queue
+                .drain(..heard * INPUT_CHANNELS);
+
+            // Metronome (write interleaved, absolute frame index); always clicks during a count-in
+            let counting_in = block_start_samples < self.audio_state.count_in_target.load();
+            if counting_in || self.audio_state.metronome_enabled.load(Ordering::Relaxed) {
+                let pattern = ClickPattern::new(
+                    self.graph_snapshot.time_signature,
+                    self.audio_state.click_eighths.load(Ordering::Relaxed),
+                );
                 let block_start_beat = converter.samples_to_beats(block_start_samples);
                 let block_end_beat =
                     converter.samples_to_beats(block_start_samples + frames_to_process as f64);
-                let beats_per_bar = 4.0;
-
-                let mut next_beat_idx = block_start_beat.ceil() as i64;
-                while (next_beat_idx as f64) < block_end_beat {
-                    let beat_time_samples = converter.beats_to_samples(next_beat_idx as f64);
-                    let start_in_block = (beat_time_samples - block_start_samples).round() as i64;
+                let block_end = frames_processed + frames_to_process;
+
+                // Ring out the previous click up to each new one, then the rest of the block
+                let mut rendered = frames_processed;
+                let mut tick = (block_start_beat / pattern.step()).ceil() as i64;
+                while (tick as f64 * pattern.step()) < block_end_beat {
+                    let tick_samples = converter.beats_to_samples(tick as f64 * pattern.step());
+                    let start_in_block = (tick_samples - block_start_samples).round() as i64;
                     if start_in_block >= 0 && start_in_block < frames_to_process as i64 {
-                        let accent = (next_beat_idx % beats_per_bar as i64) == 0;
                         let start_idx_abs = frames_processed + (start_in_block as usize);
-                        write_click_interleaved(
+                        self.click.render(
                             output,
                             channels,
+                            cue_click.then_some(&mut self.cue_buffer[..num_frames * 2]),
+                            rendered,
                             start_idx_abs,
-                            num_frames,
-                            self.sample_rate,
-                            accent,
                         );
+                        self.click.trigger(pattern.kind(tick));
+                        rendered = start_idx_abs;
                     }
-                    next_beat_idx += 1;
+                    tick += 1;
                 }
+                self.click.render(
+                    output,
+                    channels,
+                    cue_click.then_some(&mut self.cue_buffer[..num_frames * 2]),
+                    rendered,
+                    block_end,
+                );
+            } else {
+                self.click.stop();
             }
 
             // Apply master gain and soft clip; track master peaks
             for i in frames_processed..(frames_processed + frames_to_process) {
                 let out_idx = i * channels;
-                let l = soft_clip(output[out_idx] * master_volume);
+                let l = master_clip(output[out_idx] * master_volume);
                 output[out_idx] = l;
-                master_peak_l = master_peak_l.max(l.abs());
+                let peak_l = if true_peak {
+                    self.master_true_peak[0].process(l)
+                } else {
+                    l.abs()
+                };
+                master_peak_l = master_peak_l.max(peak_l);
 
                 if channels > 1 {
-                    let r = soft_clip(output[out_idx + 1] * master_volume);
+                    let r = master_clip(output[out_idx + 1] * master_volume);
                     output[out_idx + 1] = r;
-                    master_peak_r = master_peak_r.max(r.abs());
+                    let peak_r = if true_peak {
+                        self.master_true_peak[1].process(r)
+                    } else {
+                        r.abs()
+                    };
+                    master_peak_r = master_peak_r.max(peak_r);
+                    self.master_correlation.process(l, r);
+                    self.master_loudness.process(l, r);
+                    if spectrum_source == SPECTRUM_MASTER
+                        && let Some(spectrum) = self.spectrum_tap.as_mut()
+                    {
+                        spectrum.push(l, r);
+                    }
                 } else {
-                    master_peak_r = master_peak_r.max(l.abs());
+                    master_peak_r = master_peak_r.max(peak_l);
+                    self.master_correlation.process(l, l);
+                    self.master_loudness.process(l, l);
+                    if spectrum_source == SPECTRUM_MASTER
+                        && let Some(spectrum) = self.spectrum_tap.as_mut()
+                    {
+                        spectrum.push(l, l);
+                    }
                 }
             }
 
@@ -1501,6 +2459,8 @@ impl AudioEngine {
                 }
             }
         }
+        self.track_processing_order = track_order_ids;
+        self.bus_processing_order = bus_ids;
 
         // Send meters at ~60 FPS
         let now = now_secs();
@@ -1515,11 +2475,75 @@ impl AudioEngine {
                     master_peak_l,
                     master_peak_r,
                 ));
+            let (correlation, mono_peak) = self.master_correlation.take();
+            let _ = self
+                .updates
+                .send_sync(crate::messages::UIUpdate::MasterCorrelation(
+                    correlation,
+                    mono_peak,
+                ));
+            let _ = self
+                .updates
+                .send_sync(crate::messages::UIUpdate::LoudnessMeter {
+                    momentary: self.master_loudness.momentary(),
+                    short_term: self.master_loudness.short_term(),
+                    integrated: self.master_loudness.integrated(),
+                });
+
+            // try_lock: never wait on a plugin the UI thread is holding
+            let reduction: HashMap<u64, f32> = self
+                .track_processors
+                .values()
+                .flat_map(|proc| proc.plugins.iter())
+                .filter_map(|(&plugin_id, plugin)| {
+                    let cell = self.plugin_instances.get(&plugin.rt_instance_id?)?;
+                    let gr = cell.0.try_lock()?.gain_reduction_db()?;
+                    Some((plugin_id, gr))
+                })
+                .collect();
+            if !reduction.is_empty() {
+                let _ = self
+                    .updates
+                    .send_sync(crate::messages::UIUpdate::PluginGainReduction(reduction));
+            }
+        }
+
+        // Per-track plugin time, averaged per callback; coarser than the meters
+        self.cpu_report_blocks += 1;
+        if now - self.last_track_cpu_update >= TRACK_CPU_INTERVAL_SECS {
+            self.last_track_cpu_update = now;
+            let blocks = self.cpu_report_blocks as f32;
+            self.cpu_report_blocks = 0;
+            for (&track_id, proc) in self.track_processors.iter_mut() {
+                let ms = std::mem::take(&mut proc.cpu_ms) / blocks;
+                let _ = self
+                    .updates
+                    .send_sync(crate::messages::UIUpdate::TrackCpu { track_id, ms });
+            }
         }
 
         current_position
     }
 
+    /// Stops clips, clip MIDI and frozen renders at `position` (in samples)
+    /// while the plugin chains keep running, so an export tail carries only
+    /// what rings out. Clip notes still held are released there.
+    pub fn silence_sources(&mut self, position: f64) {
+        for track in &mut self.graph_snapshot.tracks {
+            track.audio_clips.clear();
+            track.midi_clips.clear();
+            // Its chain's tail is baked in with whatever follows, so it goes too
+            track.frozen_buffer = None;
+        }
+        let converter = TimeConverter::new(self.sample_rate as f32, self.audio_state.bpm.load());
+        let beat = converter.samples_to_beats(position);
+        for proc in self.track_processors.values_mut() {
+            for note_off in &mut proc.pending_note_offs {
+                note_off.2 = beat;
+            }
+        }
+    }
+
     fn midi_panic(&mut self) {
         // Build All Notes Off + All Sound Off for channels 0..15
         for proc in self.track_processors.values_mut() {
@@ -1535,6 +2559,7 @@ impl AudioEngine {
                         bpm: self.audio_state.bpm.load(),
                         time_samples: 0.0,
                         loop_active: false,
+                        sidechain: &[],
                     };
                     let panic_events: Vec<RtMidiEvent> = (0..16)
                         .flat_map(|ch| {
@@ -1603,6 +2628,140 @@ impl AudioEngine {
             .iter()
             .find(|t| t.armed && !matches!(t.track_type, TrackType::Midi))
             .map(|t| t.track_id);
+
+        self.refresh_solo_audible();
+        self.refresh_processing_order();
+
+        // Routing may have changed which bus a track waits on
+        self.update_latency_compensation();
+    }
+
+    /// Follows a stream opened at another rate: the transport keeps its place in
+    /// time and plugins are re-instantiated at the new rate
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if (sample_rate - self.sample_rate).abs() < 0.5 {
+            return;
+        }
+        let ratio = sample_rate / self.sample_rate;
+        self.audio_state
+            .set_position(self.audio_state.get_position() * ratio);
+        self.audio_state.sample_rate.store(sample_rate as f32);
+        self.sample_rate = sample_rate;
+        self.master_loudness = LoudnessMeter::new(sample_rate);
+        // Built-in ticks at the new rate; the UI re-sends configured samples
+        self.click = ClickPlayer::new(sample_rate);
+
+        match HostFacade::new(HostConfig {
+            sample_rate,
+            max_block: MAX_BUFFER_SIZE,
+            plugin_scan_paths: Vec::new(),
+        }) {
+            Ok(facade) => self.host_facade = facade,
+            Err(e) => log::error!("Plugin host reinit at {} Hz failed: {}", sample_rate, e),
+        }
+        for proc in self.track_processors.values() {
+            for plugin in proc.plugins.values() {
+                if let Some(handle) = plugin.rt_instance_id {
+                    self.plugin_instances.remove(&handle);
+                }
+            }
+        }
+        let chains: Vec<_> = self
+            .graph_snapshot
+            .tracks
+            .iter()
+            .map(|t| (t.track_id, t.plugin_chain.clone()))
+            .collect();
+        for (track_id, chain) in chains {
+            self.rebuild_track_chain_rt(track_id, &chain);
+        }
+        self.update_latency_compensation();
+    }
+
+    /// Plugin delay compensation: sums each track's plugin latency and delays every
+    /// track so all paths reach the master together. A track routed to a bus also
+    /// waits on that bus's plugins; sends into a bus are not compensated.
+    fn update_latency_compensation(&mut self) {
+        for proc in self.track_processors.values_mut() {
+            proc.plugin_latency = proc
+                .plugin_order
+                .iter()
+                .filter_map(|id| proc.plugins.get(id))
+                .filter(|p| !p.bypass)
+                .filter_map(|p| p.rt_instance_id)
+                .filter_map(|handle| self.plugin_instances.get(&handle))
+                .map(|cell| cell.lock().latency_samples())
+                .sum();
+        }
+
+        let own = |id: u64| {
+            self.track_processors
+                .get(&id)
+                .map_or(0, |p| p.plugin_latency)
+        };
+        let bus_outputs: HashMap<u64, Option<u64>> = self
+            .graph_snapshot
+            .tracks
+            .iter()
+            .filter(|t| matches!(t.track_type, TrackType::Bus))
+            .map(|t| (t.track_id, t.output_bus))
+            .collect();
+        // Latency of every bus from `first` down the submix chain to the master
+        let chain_latency = |first: Option<u64>| {
+            let mut total = 0;
+            let mut next = first.filter(|id| bus_outputs.contains_key(id));
+            let mut hops = 0;
+            while let Some(bus) = next
+                && hops <= bus_outputs.len()
+            {
+                total += own(bus);
+                hops += 1;
+                next = bus_outputs.get(&bus).copied().flatten();
+            }
+            total
+        };
+        // (track, own latency, latency to the master, is bus)
+        let paths: Vec<(u64, u32, u32, bool)> = self
+            .graph_snapshot
+            .tracks
+            .iter()
+            .map(|t| {
+                let is_bus = matches!(t.track_type, TrackType::Bus);
+                // A frozen render already has its chain's latency trimmed off
+                let latency = if t.frozen_buffer.is_some() {
+                    0
+                } else {
+                    own(t.track_id)
+                };
+                (
+                    t.track_id,
+                    latency,
+                    latency + chain_latency(t.output_bus),
+                    is_bus,
+                )
+            })
+            .collect();
+        let max_path = paths.iter().map(|&(_, _, path, _)| path).max().unwrap_or(0);
+
+        let mut report = HashMap::new();
+        for (track_id, latency, path, is_bus) in paths {
+            let Some(proc) = self.track_processors.get_mut(&track_id) else {
+                continue;
+            };
+            // Buses are lined up by the tracks feeding them
+            let delay = if is_bus { 0 } else { max_path - path };
+            for line in &mut proc.pdc {
+                line.set_delay(delay as usize);
+            }
+            report.insert(track_id, (latency, delay));
+        }
+        self.output_latency = max_path as usize;
+        let _ = self.updates.send_sync(UIUpdate::TrackLatency(report));
+    }
+
+    /// Frames the whole mix trails the timeline by once delay compensation is applied
+    pub fn output_latency(&self) -> usize {
+        self.output_latency
     }
 
     fn rebuild_track_chain_rt(&mut self, track_id: u64, chain: &[PluginDescriptorSnapshot]) {
@@ -1611,12 +2770,20 @@ impl AudioEngine {
             .entry(track_id)
             .or_insert_with(|| TrackProcessor::new());
 
+        for plugin_id in proc.plugins.keys() {
+            self.audio_state.plugin_state_handles.remove(plugin_id);
+        }
         proc.plugins.clear();
         proc.plugin_order.clear();
 
+        // New instances and the params they got, to attach state to once the chain is built
+        let mut created = Vec::with_capacity(chain.len());
+
         for (plugin_idx, pdesc) in chain.iter().enumerate() {
             match self.host_facade.instantiate(pdesc.backend, &pdesc.uri) {
                 Ok(mut inst) => {
+                    let mut applied = Vec::new();
+
                     // Build param name -> key map once
                     let param_map: std::collections::HashMap<String, ParamKey> = inst
                         .params()
@@ -1631,11 +2798,14 @@ impl AudioEngine {
 
                         match pdesc.backend {
                             BackendKind::Lv2 => {
-                                inst.set_param(&ParamKey::Lv2(name.clone()), val);
+                                let key = ParamKey::Lv2(name.clone());
+                                inst.set_param(&key, val);
+                                applied.push((key, val));
                             }
                             BackendKind::Clap => {
                                 if let Some(actual_key) = param_map.get(&name) {
                                     inst.set_param(actual_key, val);
+                                    applied.push((actual_key.clone(), val));
                                 } else {
                                     log::warn!(
                                         "CLAP param '{}' not found for plugin {} when rebuilding chain",
@@ -1644,12 +2814,14 @@ impl AudioEngine {
                                     );
                                 }
                             }
-                            BackendKind::Vst3 => {
+                            BackendKind::Vst3 | BackendKind::Native => {
                                 if let Some(actual_key) = param_map.get(&name) {
                                     inst.set_param(actual_key, val);
+                                    applied.push((actual_key.clone(), val));
                                 } else {
                                     log::warn!(
-                                        "VST3 param '{}' not found for plugin {} when rebuilding chain",
+                                        "{:?} param '{}' not found for plugin {} when rebuilding chain",
+                                        pdesc.backend,
                                         name,
                                         pdesc.uri
                                     );
@@ -1705,6 +2877,7 @@ impl AudioEngine {
 
                     proc.plugins.insert(pdesc.plugin_id, pp);
                     proc.plugin_order.push(pdesc.plugin_id);
+                    created.push((pdesc, handle, applied));
                 }
                 Err(e) => {
                     log::error!("RebuildChain: instantiate failed {}: {}", pdesc.uri, e);
@@ -1726,6 +2899,97 @@ impl AudioEngine {
                 }
             }
         }
+
+        for (pdesc, handle, applied) in created {
+            self.attach_plugin_state(
+                pdesc.plugin_id,
+                handle,
+                pdesc.state.clone(),
+                applied,
+                &pdesc.uri,
+            );
+        }
+    }
+
+    /// Publishes a new instance's state handle for saving, and loads its saved
+    /// blob. The live engine hands the load to the restore worker and puts
+    /// `params` back once it lands, so the callback never waits on the plugin;
+    /// offline renders load it in place.
+    fn attach_plugin_state(
+        &mut self,
+        plugin_id: u64,
+        instance: PluginInstanceHandle,
+        state: Option<Arc<[u8]>>,
+        params: Vec<(ParamKey, f32)>,
+        uri: &str,
+    ) {
+        let Some(handle) = self
+            .plugin_instances
+            .get(&instance)
+            .and_then(|cell| cell.lock().state_handle())
+        else {
+            return;
+        };
+        self.audio_state
+            .plugin_state_handles
+            .insert(plugin_id, Arc::clone(&handle));
+        if let Some(state) = state {
+            self.restore_plugin_state(instance, handle, state, params, uri);
+        }
+    }
+
+    fn restore_plugin_state(
+        &mut self,
+        instance: PluginInstanceHandle,
+        handle: Arc<dyn PluginStateHandle>,
+        state: Arc<[u8]>,
+        params: Vec<(ParamKey, f32)>,
+        uri: &str,
+    ) {
+        let Some(restorer) = self.state_restorer.as_mut() else {
+            crate::plugin_state::load(handle.as_ref(), &state, uri);
+            self.apply_params(instance, &params);
+            return;
+        };
+
+        let done = Arc::new(AtomicBool::new(false));
+        let queued = restorer.push(StateRestore {
+            handle,
+            state,
+            uri: uri.to_string(),
+            done: Arc::clone(&done),
+        });
+        if queued {
+            self.pending_restores.push(PendingRestore {
+                instance,
+                params,
+                done,
+            });
+        } else {
+            log::warn!("Plugin state restore queue full, {uri} keeps its parameters only");
+        }
+    }
+
+    /// Puts saved params back on plugins whose state the worker has loaded
+    fn finish_state_restores(&mut self) {
+        let mut i = 0;
+        while i < self.pending_restores.len() {
+            if !self.pending_restores[i].done.load(Ordering::Acquire) {
+                i += 1;
+                continue;
+            }
+            let restore = self.pending_restores.swap_remove(i);
+            self.apply_params(restore.instance, &restore.params);
+        }
+    }
+
+    fn apply_params(&self, instance: PluginInstanceHandle, params: &[(ParamKey, f32)]) {
+        if let Some(cell) = self.plugin_instances.get(&instance) {
+            let mut inst = cell.lock();
+            for (key, value) in params {
+                inst.set_param(key, *value);
+            }
+        }
     }
 
     fn run_plugin_chain(
@@ -1816,6 +3080,33 @@ impl AudioEngine {
                         }));
                     }
                 }
+
+                // CC lanes send their value whenever it changes
+                if let Some(proc) = self.track_processors.get_mut(&track_id) {
+                    if transport_jump {
+                        proc.sent_cc = [None; 128];
+                    }
+                    for lane in track.automation_lanes.iter().filter(|l| l.read) {
+                        if let RtAutomationTarget::MidiCc(cc) = lane.parameter
+                            && cc < 128
+                        {
+                            let value = (value_at_beat_snapshot(lane, block_start_beat)
+                                .clamp(0.0, 1.0)
+                                * 127.0)
+                                .round() as u8;
+                            let sent = &mut proc.sent_cc[cc as usize];
+                            if *sent != Some(value) {
+                                *sent = Some(value);
+                                all_midi_events.push(RtMidiEvent {
+                                    status: 0xB0,
+                                    data1: cc,
+                                    data2: value,
+                                    time_frames: 0,
+                                });
+                            }
+                        }
+                    }
+                }
             }
 
             all_midi_events.sort_by_key(|e| e.time_frames);
@@ -1841,6 +3132,7 @@ impl AudioEngine {
         // 3) Iterate plugins; for each plugin, stage data with a short &mut borrow, process (borrowing self
         //    immutably), then write outputs back with another short &mut borrow.
         let mut first_active_plugin = true;
+        let mut chain_ms = 0.0f32;
 
         for plugin_id in plugin_order {
             // Stage-per-plugin data from processor: handle, bypass, param updates, input copies, uri
@@ -1871,6 +3163,7 @@ impl AudioEngine {
                                     BackendKind::Lv2 => ParamKey::Lv2(param_name.clone()),
                                     BackendKind::Clap => ParamKey::Clap(0),
                                     BackendKind::Vst3 => ParamKey::Vst3(0),
+                                    BackendKind::Native => ParamKey::Native(0),
                                 });
                             up.push((key, value));
                         }
@@ -1926,11 +3219,33 @@ impl AudioEngine {
                     &[]
                 };
 
+            let sidechain_source = track
+                .plugin_chain
+                .iter()
+                .find(|p| p.plugin_id == plugin_id)
+                .and_then(|p| p.sidechain_source);
+            // Taken so the key can be read while the plugin borrows self
+            let mut key_bufs = std::mem::take(&mut self.sidechain_keys);
+            let keyed = match sidechain_source.and_then(|s| self.sidechain_taps.get(&s)) {
+                Some(tap) => {
+                    for (key, src) in key_bufs.iter_mut().zip(tap) {
+                        key.clear();
+                        key.extend_from_slice(&src[..num_frames.min(src.len())]);
+                        key.resize(num_frames, 0.0);
+                    }
+                    true
+                }
+                None => false,
+            };
+            let keys: [&[f32]; 2] = [&key_bufs[0][..], &key_bufs[1][..]];
+            let sidechain: &[&[f32]] = if keyed { &keys } else { &[] };
+
             let ctx = ProcessCtx {
                 frames: num_frames,
                 bpm,
                 time_samples: block_start_samples,
                 loop_active,
+                sidechain,
             };
 
             let t0 = web_time::Instant::now();
@@ -1942,7 +3257,10 @@ impl AudioEngine {
                 })
                 .map(|res| res.is_err())
                 .unwrap_or(false);
-            *plugin_time_ms_accum += t0.elapsed().as_secs_f32() * 1000.0;
+            self.sidechain_keys = key_bufs;
+            let elapsed_ms = t0.elapsed().as_secs_f32() * 1000.0;
+            *plugin_time_ms_accum += elapsed_ms;
+            chain_ms += elapsed_ms;
 
             if panicked {
                 if let Some(proc) = self.track_processors.get_mut(&track_id) {
@@ -1978,6 +3296,7 @@ impl AudioEngine {
         // Update last_block_end_samples
         if let Some(proc) = self.track_processors.get_mut(&track_id) {
             proc.last_block_end_samples = block_start_samples + num_frames as f64;
+            proc.cpu_ms += chain_ms;
         }
     }
 
@@ -1992,6 +3311,133 @@ impl AudioEngine {
     }
 }
 
+/// Channels the current solos leave audible, or None when nothing is soloed.
+/// A soloed or solo-safe channel keeps the buses on its way to the master,
+/// plus the buses its sends reach when soloing in place; a soloed bus keeps
+/// every channel feeding it.
+fn solo_audible(
+    graph: &AudioGraphSnapshot,
+    strips: &HashMap<u64, ChannelStrip>,
+    in_place: bool,
+) -> Option<std::collections::HashSet<u64>> {
+    let soloed: Vec<u64> = graph
+        .tracks
+        .iter()
+        .filter(|t| {
+            graph.vca(t.group_id).solo || strips.get(&t.track_id).map_or(t.solo, |s| s.solo)
+        })
+        .map(|t| t.track_id)
+        .collect();
+    if soloed.is_empty() {
+        return None;
+    }
+
+    let is_bus = |id: u64| {
+        graph
+            .tracks
+            .iter()
+            .any(|t| t.track_id == id && matches!(t.track_type, TrackType::Bus))
+    };
+    // Buses a channel feeds: its output, and its live sends if asked
+    let feeds = |t: &TrackSnapshot, with_sends: bool| -> Vec<u64> {
+        let sends = t
+            .sends
+            .iter()
+            .filter(|s| with_sends && !s.muted && s.amount > 0.0)
+            .map(|s| s.destination_track);
+        t.output_bus
+            .into_iter()
+            .chain(sends)
+            .filter(|&id| is_bus(id))
+            .collect()
+    };
+
+    let mut audible: std::collections::HashSet<u64> = graph
+        .tracks
+        .iter()
+        .filter(|t| t.solo_safe)
+        .map(|t| t.track_id)
+        .collect();
+
+    // Downstream: what carries a soloed or solo-safe channel to the master
+    let mut seen = std::collections::HashSet::new();
+    let mut stack: Vec<u64> = soloed.iter().chain(&audible).copied().collect();
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        audible.insert(id);
+        if let Some(t) = graph.tracks.iter().find(|t| t.track_id == id) {
+            stack.extend(feeds(t, in_place));
+        }
+    }
+
+    // Upstream: what feeds a soloed bus
+    let mut seen = std::collections::HashSet::new();
+    let mut stack: Vec<u64> = soloed.into_iter().filter(|&id| is_bus(id)).collect();
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        audible.insert(id);
+        stack.extend(
+            graph
+                .tracks
+                .iter()
+                .filter(|t| feeds(t, true).contains(&id))
+                .map(|t| t.track_id),
+        );
+    }
+
+    Some(audible)
+}
+
+/// Mono fold-down, then polarity flip, of a channel's post-plugin signal
+#[inline]
+fn channel_utility(track: &TrackSnapshot, l: f32, r: f32) -> (f32, f32) {
+    let (l, r) = if track.mono {
+        let m = 0.5 * (l + r);
+        (m, m)
+    } else {
+        (l, r)
+    };
+    if track.phase_inverted {
+        (-l, -r)
+    } else {
+        (l, r)
+    }
+}
+
+/// Buses deepest in a submix chain first, so each one has summed into the bus
+/// it feeds before that bus runs; track order breaks ties
+fn bus_processing_order(tracks: &[TrackSnapshot]) -> Vec<u64> {
+    let outputs: HashMap<u64, Option<u64>> = tracks
+        .iter()
+        .filter(|t| matches!(t.track_type, TrackType::Bus))
+        .map(|t| (t.track_id, t.output_bus))
+        .collect();
+    let depth = |id: u64| {
+        let mut depth = 0;
+        let mut next = outputs.get(&id).copied().flatten();
+        // Routing is kept acyclic; the bound only guards a stale snapshot
+        while let Some(bus) = next
+            && depth < outputs.len()
+        {
+            depth += 1;
+            next = outputs.get(&bus).copied().flatten();
+        }
+        depth
+    };
+
+    let mut order: Vec<(usize, u64)> = tracks
+        .iter()
+        .filter(|t| outputs.contains_key(&t.track_id))
+        .map(|t| (depth(t.track_id), t.track_id))
+        .collect();
+    order.sort_by_key(|&(depth, _)| std::cmp::Reverse(depth));
+    order.into_iter().map(|(_, id)| id).collect()
+}
+
 #[allow(dead_code)] // later centralize strip + automation gain logic
 #[inline]
 fn effective_gains(track: &TrackSnapshot, processor: &TrackProcessor) -> (f32, f32) {
@@ -2114,14 +3560,25 @@ fn process_audio_track(
         let clip_start_samples = converter.beats_to_samples(clip.start_beat);
 
         let audio_duration_seconds = clip.samples.len() as f64 / clip.sample_rate as f64;
-        let audio_length_samples = if clip.warp_mode {
+        // Tempo-warped clips keep the source's musical length and stretch it in time
+        let tempo_warp = clip.warp_mode && clip.original_bpm > 0.0;
+        let audio_length_samples = if tempo_warp {
+            converter.beats_to_samples(audio_duration_seconds * clip.original_bpm as f64 / 60.0)
+        } else if clip.warp_mode {
             converter.beats_to_samples(clip.length_beats.max(0.0))
         } else {
             audio_duration_seconds * sample_rate
         };
 
         let visual_length_samples = converter.beats_to_samples(clip.length_beats);
-        let clip_length_samples = audio_length_samples.min(visual_length_samples);
+        // Looped clips repeat the source for the whole instance length
+        let src_len = clip.samples.len() as f64;
+        let looping = clip.loop_enabled && src_len > 1.0;
+        let clip_length_samples = if looping {
+            visual_length_samples
+        } else {
+            audio_length_samples.min(visual_length_samples)
+        };
 
         let clip_end_samples = clip_start_samples + clip_length_samples;
 
@@ -2148,9 +3605,56 @@ fn process_audio_track(
         } else {
             clip.sample_rate as f64 / sample_rate
         }; // src_per_dst
+        // Crossfade length in source samples
+        let loop_xfade = if looping {
+            let requested = converter.beats_to_samples(clip.loop_crossfade.max(0.0)) * ratio;
+            clip.loop_mode.crossfade_len(requested, src_len)
+        } else {
+            0.0
+        };
         let clip_length_beats = clip.length_beats;
         let fade_in_beats = clip.fade_in.unwrap_or(0.0).max(0.0);
         let fade_out_beats = clip.fade_out.unwrap_or(0.0).max(0.0);
+        // Source samples per output sample at the recorded pitch
+        let native_ratio = clip.sample_rate as f64 / sample_rate;
+        let grain = clip.sample_rate as f64 * 0.04;
+        let pitch = 2f64.powf(clip.pitch_semitones as f64 / 12.0);
+        let pitched = clip.pitch_semitones != 0.0;
+
+        // Mono clips feed both sides from `samples`
+        let stereo = !clip.samples_right.is_empty();
+        let read_src = |data: &[f32], src_pos: f64| {
+            if looping {
+                let (rep, local) = loop_pass_position(src_pos, src_len, loop_xfade);
+                // A reversed clip flips every pass, including ping-pong's backward ones
+                let reversed = clip.loop_mode.is_reversed(rep) != clip.reversed;
+                let read = |pos: f64| {
+                    let pos = if reversed {
+                        (src_len - 1.0 - pos).max(0.0)
+                    } else {
+                        pos
+                    };
+                    sample_linear(data, pos)
+                };
+                let xfade_start = src_len - loop_xfade;
+                if loop_xfade > 0.0 && local >= xfade_start {
+                    // Equal-power blend of the tail into the next pass's head
+                    let t = ((local - xfade_start) / loop_xfade) as f32 * FRAC_PI_2;
+                    read(local) * t.cos() + read(local - xfade_start) * t.sin()
+                } else {
+                    read(local)
+                }
+            } else if clip.reversed {
+                let pos = src_len - 1.0 - src_pos;
+                if pos < 0.0 {
+                    0.0
+                } else {
+                    sample_linear(data, pos)
+                }
+            } else {
+                sample_linear(data, src_pos)
+            }
+        };
 
         for i in 0..frames {
             let buf_idx = start_in_buffer + i;
@@ -2162,37 +3666,97 @@ fn process_audio_track(
             let proj_off = (overlap_start - clip_start_samples) + i as f64;
             // Source float index (clip domain)
             let src_pos = (proj_off + offset_samples) * ratio;
-            let src_idx = src_pos.floor() as usize;
-            let frac = (src_pos - src_idx as f64) as f32;
-
-            // Linear interpolation from clip.samples (mono)
-            let s0 = clip.samples.get(src_idx).copied().unwrap_or(0.0);
-            let s1 = clip.samples.get(src_idx + 1).copied().unwrap_or(s0);
-            let mut s = s0 * (1.0 - frac) + s1 * frac;
+            let read_channel = |data: &[f32]| {
+                let read = |pos: f64| read_src(data, pos);
+                if tempo_warp {
+                    let native_pos = (proj_off + offset_samples) * native_ratio;
+                    pitch_stretch(native_pos, ratio / native_ratio, pitch, grain, read)
+                } else if pitched {
+                    pitch_stretch(src_pos, 1.0, pitch, grain, read)
+                } else {
+                    read(src_pos)
+                }
+            };
+            let left = read_channel(&clip.samples);
+            let right = if stereo {
+                read_channel(&clip.samples_right)
+            } else {
+                left
+            };
 
             // Apply clip gain
-            s *= clip.gain;
+            let mut gain = clip.gain;
 
             // Apply fades (in beats, relative to clip start)
             let clip_pos_beats = converter.samples_to_beats(proj_off);
             // Fade in
             if fade_in_beats > 0.0 && clip_pos_beats < fade_in_beats {
                 let f = (clip_pos_beats / fade_in_beats) as f32;
-                s *= f.clamp(0.0, 1.0);
+                gain *= clip.fade_in_curve.gain(f);
             }
             // Fade out
             if fade_out_beats > 0.0 && clip_pos_beats > (clip_length_beats - fade_out_beats) {
                 let rem = (clip_length_beats - clip_pos_beats).max(0.0);
                 let f = (rem / fade_out_beats) as f32;
-                s *= f.clamp(0.0, 1.0);
+                gain *= clip.fade_out_curve.gain(f);
+            }
+            // Crossfades: silent before the incoming region, gone after the outgoing one
+            let beat = clip.start_beat + clip_pos_beats;
+            if let Some(xf) = &clip.crossfade_in {
+                gain *= xf.gain(beat, true);
+            }
+            if let Some(xf) = &clip.crossfade_out {
+                gain *= xf.gain(beat, false);
             }
 
-            processor.input_buffers[0][buf_idx] += s;
-            processor.input_buffers[1][buf_idx] += s;
+            processor.input_buffers[0][buf_idx] += left * gain;
+            processor.input_buffers[1][buf_idx] += right * gain;
         }
     }
 }
 
+/// Plays a frozen track's interleaved stereo render into its input buffers
+fn process_frozen_track(
+    frozen: &[f32],
+    frozen_sample_rate: f32,
+    processor: &mut TrackProcessor,
+    num_frames: usize,
+    current_position: f64,
+    sample_rate: f64,
+) {
+    let ratio = if frozen_sample_rate > 0.0 {
+        frozen_sample_rate as f64 / sample_rate
+    } else {
+        1.0
+    };
+    let frame_at = |idx: usize| {
+        (
+            frozen.get(idx * 2).copied().unwrap_or(0.0),
+            frozen.get(idx * 2 + 1).copied().unwrap_or(0.0),
+        )
+    };
+
+    for i in 0..num_frames {
+        let pos = (current_position + i as f64) * ratio;
+        let idx = pos.floor() as usize;
+        let frac = (pos - idx as f64) as f32;
+        let (l0, r0) = frame_at(idx);
+        let (l1, r1) = frame_at(idx + 1);
+        processor.input_buffers[0][i] = l0 + (l1 - l0) * frac;
+        processor.input_buffers[1][i] = r0 + (r1 - r0) * frac;
+    }
+}
+
+/// Linear interpolation into a mono sample buffer
+#[inline]
+fn sample_linear(samples: &[f32], pos: f64) -> f32 {
+    let idx = pos.floor() as usize;
+    let frac = (pos - idx as f64) as f32;
+    let s0 = samples.get(idx).copied().unwrap_or(0.0);
+    let s1 = samples.get(idx + 1).copied().unwrap_or(s0);
+    s0 * (1.0 - frac) + s1 * frac
+}
+
 fn process_preview_note(
     processor: &mut TrackProcessor,
     preview: &PreviewNote,
@@ -2261,6 +3825,7 @@ fn build_block_midi_events(
         }
 
         let offset = clip.content_offset_beats.rem_euclid(content_len);
+        let reversed = clip.loop_enabled && clip.loop_mode.is_reversed(k as i64);
 
         for n in &clip.notes {
             let s_loc = (n.start + offset).rem_euclid(content_len);
@@ -2275,6 +3840,12 @@ fn build_block_midi_events(
             }
 
             for (s_local, e_local) in segs {
+                // Reversed repetitions mirror each note inside the content window
+                let (s_local, e_local) = if reversed {
+                    (content_len - e_local, content_len - s_local)
+                } else {
+                    (s_local, e_local)
+                };
                 let s_raw = rep_off + s_local;
 
                 let e_raw_full = rep_off + e_local;
@@ -2319,20 +3890,30 @@ fn build_block_midi_events(
 }
 
 #[inline]
-fn quantize_beat(beat: f64, clip: &MidiClipSnapshot) -> f64 {
-    if !clip.quantize_enabled || clip.quantize_grid <= 0.0 {
+pub(crate) fn quantize_beat(beat: f64, clip: &MidiClipSnapshot) -> f64 {
+    if !clip.quantize_enabled {
         return beat;
     }
-    let g = clip.quantize_grid as f64;
-    let q = (beat / g).round() * g;
-    let mut q_swing = q;
-    if clip.swing.abs() > 0.0001 {
-        let idx = (q_swing / (g * 0.5)).round() as i64;
-        if idx % 2 != 0 {
-            q_swing += (clip.swing as f64) * 0.5 * g;
-        }
+    quantize_to_grid(
+        beat,
+        clip.quantize_grid as f64,
+        clip.quantize_strength as f64,
+        clip.swing as f64,
+    )
+}
+
+/// Pulls `beat` towards the nearest grid line by `strength`; `swing` delays
+/// every other grid step by up to half a step
+pub(crate) fn quantize_to_grid(beat: f64, grid: f64, strength: f64, swing: f64) -> f64 {
+    if grid <= 0.0 {
+        return beat;
     }
-    beat + (q_swing - beat) * (clip.quantize_strength as f64).clamp(0.0, 1.0)
+    let step = (beat / grid).round();
+    let mut q = step * grid;
+    if swing.abs() > 0.0001 && (step as i64) % 2 != 0 {
+        q += swing * 0.5 * grid;
+    }
+    beat + (q - beat) * strength.clamp(0.0, 1.0)
 }
 
 fn update_active_notes(events: &[(u8, u8, u8, i64)], active: &mut Vec<(u8, u8)>) {
@@ -2372,15 +3953,8 @@ fn value_at_beat_snapshot(lane: &RtAutomationLaneSnapshot, beat: f64) -> f32 {
     if (next.beat - prev.beat).abs() < f64::EPSILON {
         return next.value;
     }
-    let t = ((beat - prev.beat) / (next.beat - prev.beat)).clamp(0.0, 1.0);
-    match next.curve_type {
-        RtCurveType::Step => prev.value,
-        RtCurveType::Linear => prev.value + ((next.value - prev.value) * t as f32),
-        RtCurveType::Exponential => {
-            let t2 = (t as f32).powf(2.0);
-            prev.value + (next.value - prev.value) * t2
-        }
-    }
+    let t = ((beat - prev.beat) / (next.beat - prev.beat)).clamp(0.0, 1.0) as f32;
+    prev.value + (next.value - prev.value) * prev.curve.shape(t, prev.tension)
 }
 
 fn apply_automation_smooth(
@@ -2389,6 +3963,7 @@ fn apply_automation_smooth(
     block_start_samples: f64,
     num_frames: usize,
     converter: &TimeConverter,
+    control_interval: usize,
 ) {
     // Reset per-block automation state
     processor.automated_volume = f32::NAN;
@@ -2396,15 +3971,20 @@ fn apply_automation_smooth(
     processor.automated_plugin_params.clear();
 
     let block_start_beat = converter.samples_to_beats(block_start_samples);
+    let block_end_beat = converter.samples_to_beats(block_start_samples + num_frames as f64);
 
-    for lane in &track.automation_lanes {
+    for lane in track.automation_lanes.iter().filter(|l| l.read) {
         let has_point_in_block = lane.points.iter().any(|p| {
             let beat = p.beat;
-            beat >= block_start_beat
-                && beat < converter.samples_to_beats(block_start_samples + num_frames as f64)
+            beat >= block_start_beat && beat < block_end_beat
         });
 
-        if has_point_in_block {
+        // With a finer control rate, ramps between points are sampled too
+        let ramps_in_block = control_interval > 0
+            && value_at_beat_snapshot(lane, block_start_beat)
+                != value_at_beat_snapshot(lane, block_end_beat);
+
+        if has_point_in_block || ramps_in_block {
             // Per-sample automation path
             let param_key = match &lane.parameter {
                 RtAutomationTarget::TrackVolume => "volume".to_string(),
@@ -2413,6 +3993,11 @@ fn apply_automation_smooth(
                     plugin_id,
                     param_name,
                 } => {
+                    // Plugins only take one value per sub-block
+                    processor.automated_plugin_params.insert(
+                        (*plugin_id, param_name.clone()),
+                        value_at_beat_snapshot(lane, block_start_beat),
+                    );
                     format!("plugin_{}_{}", plugin_id, param_name)
                 }
                 _ => continue,
@@ -2427,10 +4012,15 @@ fn apply_automation_smooth(
                 buf.resize(num_frames, 0.0);
             }
 
-            // Sample automation curve for each frame in the block
+            // Sample automation curve every `step` frames, holding in between
+            let step = control_interval.max(1);
+            let mut held = 0.0;
             for i in 0..num_frames {
-                let beat = converter.samples_to_beats(block_start_samples + i as f64);
-                buf[i] = value_at_beat_snapshot(lane, beat);
+                if i % step == 0 {
+                    let beat = converter.samples_to_beats(block_start_samples + i as f64);
+                    held = value_at_beat_snapshot(lane, beat);
+                }
+                buf[i] = held;
             }
         } else {
             // Per-block automation path
@@ -2507,32 +4097,351 @@ impl Drop for AudioEngine {
     }
 }
 
-#[inline]
-fn write_click_interleaved(
-    out: &mut [f32],
-    channels: usize,
-    start_frame: usize,  // absolute frame index inside this callback
-    total_frames: usize, // num_frames of this callback
-    sr: f64,
-    accent: bool,
-) {
-    // short decaying cosine tick
-    let len_ms = if accent { 25.0 } else { 15.0 };
-    let len_frames = ((len_ms / 1000.0) * sr) as usize;
-    let end_frame = (start_frame + len_frames).min(total_frames);
-    let f_hz = if accent { 3000.0 } else { 2000.0 };
-    let amp = if accent { 0.35 } else { 0.25 };
-
-    for fidx in start_frame..end_frame {
-        let i = fidx - start_frame;
-        let t = i as f64 / sr;
-        let env = (-t * 60.0).exp() as f32;
-        let s = (2.0 * std::f64::consts::PI * f_hz * t).cos() as f32 * amp * env;
-
-        let base = fidx * channels;
-        out[base] += s;
-        if channels > 1 {
-            out[base + 1] += s;
+/// The engine mixes the master into channels 0/1; move it to the configured pair.
+fn route_master_output(data: &mut [f32], channels: usize, first_channel: usize) {
+    if first_channel == 0 || first_channel + 1 >= channels {
+        return;
+    }
+    for frame in data.chunks_exact_mut(channels) {
+        frame[first_channel] = frame[0];
+        frame[first_channel + 1] = frame[1];
+        frame[0] = 0.0;
+        frame[1] = 0.0;
+    }
+}
+
+/// Adds the cue mix on top of whatever is already on its output pair.
+fn mix_cue_output(data: &mut [f32], channels: usize, first_channel: usize, cue: &[f32]) {
+    if first_channel + 1 >= channels {
+        return;
+    }
+    for (frame, cue_frame) in data.chunks_exact_mut(channels).zip(cue.chunks_exact(2)) {
+        frame[first_channel] += cue_frame[0];
+        frame[first_channel + 1] += cue_frame[1];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_state::RtAutomationPoint;
+    use crate::model::track::Track;
+    use crate::project::AppState;
+
+    fn track(id: u64, track_type: TrackType, output_bus: Option<u64>) -> Track {
+        Track {
+            id,
+            track_type,
+            output_bus,
+            ..Default::default()
+        }
+    }
+
+    fn graph(tracks: Vec<Track>) -> AudioGraphSnapshot {
+        let mut state = AppState::default();
+        for t in tracks {
+            state.track_order.push(t.id);
+            state.tracks.insert(t.id, t);
+        }
+        AudioGraphSnapshot {
+            tracks: crate::audio_snapshot::build_track_snapshots(&state),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn submix_buses_run_before_the_bus_they_feed() {
+        let graph = graph(vec![
+            track(1, TrackType::Audio, Some(3)),
+            track(2, TrackType::Bus, None),
+            track(3, TrackType::Bus, Some(4)),
+            track(4, TrackType::Bus, Some(2)),
+        ]);
+        assert_eq!(bus_processing_order(&graph.tracks), vec![3, 4, 2]);
+    }
+
+    #[test]
+    fn bus_order_ends_on_a_stale_cycle() {
+        let graph = graph(vec![
+            track(2, TrackType::Bus, Some(3)),
+            track(3, TrackType::Bus, Some(2)),
+        ]);
+        assert_eq!(bus_processing_order(&graph.tracks).len(), 2);
+    }
+
+    fn send(destination_track: u64, amount: f32) -> crate::model::track::Send {
+        crate::model::track::Send {
+            destination_track,
+            amount,
+            pre_fader: false,
+            muted: false,
+        }
+    }
+
+    fn sorted(audible: Option<std::collections::HashSet<u64>>) -> Option<Vec<u64>> {
+        audible.map(|set| {
+            let mut ids: Vec<u64> = set.into_iter().collect();
+            ids.sort_unstable();
+            ids
+        })
+    }
+
+    /// 1 and 2 play into bus 3, which feeds bus 4; 1 also sends to bus 5
+    fn solo_graph(soloed: &[u64], in_place: bool) -> Option<Vec<u64>> {
+        let mut tracks = vec![
+            track(1, TrackType::Audio, Some(3)),
+            track(2, TrackType::Audio, Some(3)),
+            track(3, TrackType::Bus, Some(4)),
+            track(4, TrackType::Bus, None),
+            track(5, TrackType::Bus, None),
+            track(6, TrackType::Audio, None),
+        ];
+        tracks[0].sends = vec![send(5, 0.5)];
+        for t in &mut tracks {
+            t.solo = soloed.contains(&t.id);
+        }
+        sorted(solo_audible(&graph(tracks), &HashMap::new(), in_place))
+    }
+
+    #[test]
+    fn nothing_soloed_keeps_everything() {
+        assert_eq!(solo_graph(&[], true), None);
+    }
+
+    #[test]
+    fn soloed_channel_keeps_its_way_to_the_master() {
+        assert_eq!(solo_graph(&[1], false), Some(vec![1, 3, 4]));
+    }
+
+    #[test]
+    fn solo_in_place_keeps_the_buses_sends_reach() {
+        assert_eq!(solo_graph(&[1], true), Some(vec![1, 3, 4, 5]));
+    }
+
+    #[test]
+    fn soloed_bus_keeps_what_feeds_it() {
+        assert_eq!(solo_graph(&[3], false), Some(vec![1, 2, 3, 4]));
+        // Only the send reaches bus 5, so 1's own output path stays silent
+        assert_eq!(solo_graph(&[5], false), Some(vec![1, 5]));
+    }
+
+    #[test]
+    fn solo_safe_return_keeps_its_buses() {
+        // Return 2 is solo-safe and plays through bus 3, which 4 also feeds
+        let mut tracks = vec![
+            track(1, TrackType::Audio, None),
+            track(2, TrackType::Bus, Some(3)),
+            track(3, TrackType::Bus, None),
+            track(4, TrackType::Audio, Some(3)),
+        ];
+        tracks[0].solo = true;
+        tracks[1].solo_safe = true;
+        let audible = solo_audible(&graph(tracks), &HashMap::new(), false);
+        assert_eq!(sorted(audible), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn closed_or_muted_sends_are_not_followed() {
+        let mut tracks = vec![
+            track(1, TrackType::Audio, None),
+            track(2, TrackType::Bus, None),
+            track(3, TrackType::Bus, None),
+        ];
+        tracks[0].solo = true;
+        tracks[0].sends = vec![send(2, 0.0), send(3, 1.0)];
+        tracks[0].sends[1].muted = true;
+        let audible = solo_audible(&graph(tracks), &HashMap::new(), true);
+        assert_eq!(sorted(audible), Some(vec![1]));
+    }
+
+    #[test]
+    fn solo_follows_strips_groups_and_solo_safe() {
+        let mut tracks = vec![
+            track(1, TrackType::Audio, None),
+            track(2, TrackType::Audio, None),
+            track(3, TrackType::Audio, None),
+        ];
+        tracks[0].solo = true;
+        tracks[2].solo_safe = true;
+        let mut graph = graph(tracks);
+
+        // The live strip overrides the snapshot's solo
+        let strips = HashMap::from([(
+            1,
+            ChannelStrip {
+                solo: false,
+                ..Default::default()
+            },
+        )]);
+        assert_eq!(solo_audible(&graph, &strips, true), None);
+
+        graph.tracks[1].group_id = Some(7);
+        graph.groups.insert(
+            7,
+            VcaGroupSnapshot {
+                solo: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            sorted(solo_audible(&graph, &strips, true)),
+            Some(vec![2, 3])
+        );
+    }
+
+    fn lane(parameter: RtAutomationTarget, points: &[(f64, f32)]) -> RtAutomationLaneSnapshot {
+        RtAutomationLaneSnapshot {
+            parameter,
+            points: points
+                .iter()
+                .map(|&(beat, value)| RtAutomationPoint {
+                    beat,
+                    value,
+                    curve: crate::model::CurveType::Linear,
+                    tension: 0.0,
+                })
+                .collect(),
+            visible: true,
+            height: 30.0,
+            color: None,
+            read: true,
         }
     }
+
+    fn automated(lanes: Vec<RtAutomationLaneSnapshot>) -> TrackSnapshot {
+        let mut snapshot = graph(vec![track(1, TrackType::Audio, None)]).tracks[0].clone();
+        snapshot.automation_lanes = lanes;
+        snapshot
+    }
+
+    // 120 BPM at 48 kHz: one beat is 24000 samples
+    fn converter() -> TimeConverter {
+        TimeConverter::new(48000.0, 120.0)
+    }
+
+    #[test]
+    fn smooth_automation_holds_one_value_per_block_between_points() {
+        let track = automated(vec![
+            lane(RtAutomationTarget::TrackVolume, &[(0.0, 0.0), (4.0, 1.0)]),
+            lane(RtAutomationTarget::TrackPan, &[(0.0, 0.75)]),
+        ]);
+        let mut processor = TrackProcessor::new();
+        apply_automation_smooth(&track, &mut processor, 24000.0, 480, &converter(), 0);
+
+        assert!((processor.automated_volume - 0.25).abs() < 1e-6);
+        assert!((processor.automated_pan - 0.5).abs() < 1e-6);
+        assert!(processor.automation_sample_buffers.is_empty());
+    }
+
+    #[test]
+    fn smooth_automation_ramps_per_sample_over_a_point() {
+        let track = automated(vec![lane(
+            RtAutomationTarget::TrackVolume,
+            &[(0.0, 0.0), (4.0, 1.0)],
+        )]);
+        let mut processor = TrackProcessor::new();
+        apply_automation_smooth(&track, &mut processor, 0.0, 480, &converter(), 0);
+
+        assert!(processor.automated_volume.is_nan());
+        let buf = &processor.automation_sample_buffers["volume"];
+        assert_eq!(buf.len(), 480);
+        assert_eq!(buf[0], 0.0);
+        assert!((buf[240] - 240.0 / 96000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn smooth_automation_steps_ramps_at_the_control_rate() {
+        let track = automated(vec![lane(
+            RtAutomationTarget::TrackVolume,
+            &[(0.0, 0.0), (4.0, 1.0)],
+        )]);
+        let mut processor = TrackProcessor::new();
+        apply_automation_smooth(&track, &mut processor, 24000.0, 128, &converter(), 64);
+
+        let buf = &processor.automation_sample_buffers["volume"];
+        assert!(buf[..64].iter().all(|&v| v == buf[0]));
+        assert!((buf[0] - 0.25).abs() < 1e-6);
+        assert!((buf[64] - 24064.0 / 96000.0).abs() < 1e-6);
+        assert!(buf[64..].iter().all(|&v| v == buf[64]));
+    }
+
+    #[test]
+    fn smooth_automation_is_continuous_across_blocks() {
+        let track = automated(vec![lane(
+            RtAutomationTarget::TrackVolume,
+            &[(0.0, 0.0), (4.0, 1.0)],
+        )]);
+        // One beat rises by 0.25 over 24000 samples
+        let slope = 0.25 / 24000.0;
+        for interval in [1, 64] {
+            let mut processor = TrackProcessor::new();
+            let mut curve = Vec::new();
+            for block in 0..4 {
+                let start = 24000.0 + (block * 256) as f64;
+                apply_automation_smooth(&track, &mut processor, start, 256, &converter(), interval);
+                curve.extend_from_slice(&processor.automation_sample_buffers["volume"][..256]);
+            }
+
+            let max_step = curve.windows(2).map(|w| w[1] - w[0]).fold(0.0f32, f32::max);
+            assert!(curve.windows(2).all(|w| w[1] >= w[0]));
+            assert!(
+                max_step <= slope * interval as f32 + 1e-6,
+                "interval {interval}: step {max_step}"
+            );
+            assert!((curve[0] - 0.25).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn smooth_automation_skips_lanes_not_reading() {
+        let mut volume = lane(RtAutomationTarget::TrackVolume, &[(0.0, 0.5)]);
+        volume.read = false;
+        let track = automated(vec![volume]);
+        let mut processor = TrackProcessor::new();
+        processor.automated_volume = 1.0;
+        apply_automation_smooth(&track, &mut processor, 0.0, 480, &converter(), 0);
+
+        assert!(processor.automated_volume.is_nan());
+        assert!(processor.automation_sample_buffers.is_empty());
+    }
+
+    #[test]
+    fn recorded_midi_pairs_notes_and_closes_held_ones_at_the_end() {
+        let events = [
+            (0.0, 0x90, 60, 100),
+            (6000.0, 0x91, 60, 90),
+            (12000.0, 0x80, 60, 0),
+            // Same key on another channel is its own note, released by a zero note-on
+            (18000.0, 0x91, 60, 0),
+            // Retrigger closes the first note, the second is held past the end
+            (24000.0, 0x90, 64, 80),
+            (30000.0, 0x90, 64, 70),
+        ];
+        let clip = build_recorded_midi_clip(&events, 8.0, 36000.0, &converter()).unwrap();
+
+        let notes: Vec<_> = clip
+            .notes
+            .iter()
+            .map(|n| (n.pitch, n.velocity, n.start, n.duration))
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                (60, 100, 0.0, 0.5),
+                (60, 90, 0.25, 0.5),
+                (64, 80, 1.0, 0.25),
+                (64, 70, 1.25, 0.25),
+            ]
+        );
+        assert_eq!(clip.start_beat, 8.0);
+        assert_eq!(clip.length_beats, 1.5);
+        assert_eq!(clip.content_len_beats, 1.5);
+        assert!(clip.name.is_empty());
+    }
+
+    #[test]
+    fn recorded_midi_without_notes_is_dropped() {
+        let events = [(0.0, 0x80, 60, 0), (100.0, 0xB0, 1, 64)];
+        assert!(build_recorded_midi_clip(&events, 0.0, 24000.0, &converter()).is_none());
+    }
 }
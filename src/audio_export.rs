@@ -1,8 +1,11 @@
 use crate::audio::AudioEngine;
-use crate::audio_state::AudioState;
+use crate::audio_state::{AudioState, RtAutomationTarget, TrackSnapshot, VcaGroupSnapshot};
 use crate::constants::MAX_BUFFER_SIZE;
-use crate::messages::{ExportConfig, ExportFormat, ExportState, UIUpdate, UiTx};
-use crate::project::AppState;
+use crate::messages::{
+    ExportConfig, ExportFormat, ExportState, NormalizeTarget, StemOptions, UIUpdate, UiTx,
+};
+use crate::model::track::TrackType;
+use crate::project::{AppState, Marker, ProjectMetadata};
 use crate::time_utils::TimeConverter;
 
 use anyhow::{Result, anyhow, bail};
@@ -15,9 +18,10 @@ use wasm_bindgen::JsCast;
 #[cfg(target_arch = "wasm32")]
 use web_sys::HtmlAnchorElement;
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufWriter, Cursor};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 impl ExportFormat {
@@ -74,41 +78,394 @@ impl AudioExporter {
         audio_state: Arc<AudioState>,
         config: ExportConfig,
         ui_tx: UiTx,
+    ) {
+        Self::export_all(app_state, audio_state, vec![config], ui_tx);
+    }
+
+    /// Render each config one after another, stopping at the first failure
+    pub fn export_all(
+        app_state: AppState,
+        audio_state: Arc<AudioState>,
+        configs: Vec<ExportConfig>,
+        ui_tx: UiTx,
     ) {
         #[cfg(not(target_arch = "wasm32"))]
         crate::runtime::RT.spawn_blocking(move || {
-            let result = run_export(app_state, audio_state, &config, &ui_tx);
-            match result {
-                Ok(path) => {
-                    let _ = ui_tx.send_sync(UIUpdate::ExportStateUpdate(ExportState::Complete(
-                        path.to_string_lossy().into_owned(),
-                    )));
-                }
-                Err(e) => {
-                    let _ = ui_tx.send_sync(UIUpdate::ExportStateUpdate(ExportState::Error(
-                        e.to_string(),
-                    )));
+            let mut last = None;
+            for config in &configs {
+                match run_export(app_state.clone(), audio_state.clone(), config, &ui_tx) {
+                    Ok(path) => last = Some(path.to_string_lossy().into_owned()),
+                    Err(e) => {
+                        send(&ui_tx, ExportState::Error(e.to_string()));
+                        return;
+                    }
                 }
             }
+            if let Some(path) = last {
+                send(
+                    &ui_tx,
+                    ExportState::Complete(completed_label(path, configs.len())),
+                );
+            }
         });
         #[cfg(target_arch = "wasm32")]
         wasm_bindgen_futures::spawn_local(async move {
-            let result = run_export_wasm(app_state, audio_state, &config).await;
-            match result {
-                Ok(filename) => {
-                    let _ = ui_tx
-                        .send_sync(UIUpdate::ExportStateUpdate(ExportState::Complete(filename)));
-                }
-                Err(e) => {
-                    let _ = ui_tx.send_sync(UIUpdate::ExportStateUpdate(ExportState::Error(
-                        e.to_string(),
-                    )));
+            let mut last = None;
+            for config in &configs {
+                match run_export_wasm(app_state.clone(), audio_state.clone(), config).await {
+                    Ok(filename) => last = Some(filename),
+                    Err(e) => {
+                        send(&ui_tx, ExportState::Error(e.to_string()));
+                        return;
+                    }
                 }
             }
+            if let Some(filename) = last {
+                send(
+                    &ui_tx,
+                    ExportState::Complete(completed_label(filename, configs.len())),
+                );
+            }
         });
     }
 }
 
+fn completed_label(last: String, count: usize) -> String {
+    if count > 1 {
+        format!("{last} (and {} more)", count - 1)
+    } else {
+        last
+    }
+}
+
+/// Each marker to the next as (name, start, end); the last one runs to `end_beat`.
+/// Names are numbered ("01 Verse") so markers sharing a name still export to
+/// their own files. Expects markers sorted by beat.
+pub fn marker_sections(markers: &[Marker], end_beat: f64) -> Vec<(String, f64, f64)> {
+    markers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, m)| {
+            let end = markers.get(i + 1).map_or(end_beat, |next| next.beat);
+            (end > m.beat).then_some((m, end))
+        })
+        .enumerate()
+        .map(|(n, (m, end))| (format!("{:02} {}", n + 1, m.name), m.beat, end))
+        .collect()
+}
+
+/// `song.wav` + "Verse 1" -> `song_Verse_1.wav`
+pub fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("export");
+    let suffix: String = suffix
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut name = format!("{stem}_{suffix}");
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        name.push('.');
+        name.push_str(ext);
+    }
+    path.with_file_name(name)
+}
+
+/// `Stems/` + 3 + "Lead Vox" -> `Stems/03_Lead_Vox.wav`; the index keeps
+/// tracks with the same name apart
+pub fn stem_path(folder: &Path, index: usize, track_name: &str) -> PathBuf {
+    suffixed_path(&folder.join(format!("{:02}.wav", index + 1)), track_name)
+}
+
+/// The tracks one stem renders: its own track and, when its sends count, the
+/// buses they reach. Everything else stays out of the render.
+fn stem_snapshots(mut snapshots: Vec<TrackSnapshot>, stem: &StemOptions) -> Vec<TrackSnapshot> {
+    snapshots.retain(|t| {
+        t.track_id == stem.track_id
+            || (stem.include_sends && matches!(t.track_type, TrackType::Bus))
+    });
+    for t in &mut snapshots {
+        t.solo = false;
+        if t.track_id != stem.track_id {
+            continue;
+        }
+        t.muted = false;
+        t.monitor_enabled = false;
+        if !stem.include_sends {
+            t.sends.clear();
+            t.output_bus = None;
+        }
+        if !stem.post_fader {
+            // Unity through the centre-pan law
+            let (law, _) = crate::audio_utils::calculate_stereo_gains(1.0, 0.0);
+            t.volume = 1.0 / law;
+            t.pan = 0.0;
+            t.group_id = None;
+            t.automation_lanes
+                .retain(|l| matches!(l.parameter, RtAutomationTarget::PluginParam { .. }));
+        }
+    }
+    snapshots
+}
+
+fn export_snapshots(app_state: &AppState, config: &ExportConfig) -> Vec<TrackSnapshot> {
+    let snapshots = crate::audio_snapshot::build_track_snapshots(app_state);
+    match &config.stem {
+        Some(stem) => stem_snapshots(snapshots, stem),
+        None => snapshots,
+    }
+}
+
+/// Group faders for an export; a stem plays even when its group is muted or
+/// another group is soloed
+fn export_groups(app_state: &AppState, config: &ExportConfig) -> HashMap<u64, VcaGroupSnapshot> {
+    let mut groups = crate::audio_snapshot::build_group_snapshots(app_state);
+    if config.stem.is_some() {
+        for group in groups.values_mut() {
+            group.muted = false;
+            group.solo = false;
+        }
+    }
+    groups
+}
+
+/// Seconds rendered past a frozen track's last clip so effect tails survive
+const FREEZE_TAIL_SECS: f64 = 4.0;
+
+/// One track on its own with a neutral strip: unity volume, centre pan and
+/// only plugin automation, since the live strip still applies on playback
+fn isolated_track_snapshot(app_state: &AppState, track_id: u64) -> Result<TrackSnapshot> {
+    let mut snapshot = crate::audio_snapshot::build_track_snapshots(app_state)
+        .into_iter()
+        .find(|t| t.track_id == track_id)
+        .ok_or_else(|| anyhow!("Track {track_id} not found"))?;
+
+    snapshot.volume = 1.0;
+    snapshot.pan = 0.0;
+    snapshot.muted = false;
+    snapshot.solo = false;
+    snapshot.phase_inverted = false;
+    snapshot.mono = false;
+    snapshot.cue = false;
+    snapshot.monitor_enabled = false;
+    snapshot.output_bus = None;
+    snapshot.sends.clear();
+    snapshot.frozen_buffer = None;
+    snapshot.group_id = None;
+    snapshot
+        .automation_lanes
+        .retain(|l| matches!(l.parameter, RtAutomationTarget::PluginParam { .. }));
+    Ok(snapshot)
+}
+
+/// Renders `frames` of an isolated track from `start_frame` as interleaved
+/// stereo, latency-compensated and with the centre-pan law undone
+fn render_isolated(
+    snapshot: TrackSnapshot,
+    audio_state: &AudioState,
+    sample_rate: f32,
+    start_frame: usize,
+    frames: usize,
+) -> Result<Vec<f32>> {
+    let mut engine = AudioEngine::new_for_offline_render(&[snapshot], audio_state, sample_rate)?;
+    engine.set_master_clip_bypass(true);
+    engine.set_master_volume(1.0);
+    // Render past the end by the chain's latency, then trim it off the front
+    let latency = engine.output_latency();
+    let total_frames = frames + latency;
+
+    let mut pcm = Vec::<f32>::with_capacity(total_frames * 2);
+    let mut frames_done = 0usize;
+    while frames_done < total_frames {
+        let batch = (total_frames - frames_done).min(MAX_BUFFER_SIZE);
+        let mut buf = vec![0.0f32; batch * 2];
+        let mut plugin_time_ms = 0.0f32;
+        engine.process_audio(
+            &mut buf,
+            batch,
+            2,
+            (start_frame + frames_done) as f64,
+            &mut plugin_time_ms,
+        );
+        pcm.extend_from_slice(&buf);
+        frames_done += batch;
+    }
+    pcm.drain(..latency * 2);
+
+    // Undo the centre-pan law so playback through the strip matches the live chain
+    let (gain_l, gain_r) = crate::audio_utils::calculate_stereo_gains(1.0, 0.0);
+    for frame in pcm.chunks_exact_mut(2) {
+        frame[0] /= gain_l;
+        frame[1] /= gain_r;
+    }
+    Ok(pcm)
+}
+
+/// Renders one track's clips through its plugin chain, pre-fader and pre-pan, as
+/// interleaved stereo from beat 0 at the engine's sample rate.
+pub fn render_track_freeze(
+    app_state: &AppState,
+    audio_state: &AudioState,
+    track_id: u64,
+) -> Result<(Vec<f32>, f32)> {
+    let snapshot = isolated_track_snapshot(app_state, track_id)?;
+    let end_beat = snapshot
+        .audio_clips
+        .iter()
+        .map(|c| c.start_beat + c.length_beats)
+        .chain(
+            snapshot
+                .midi_clips
+                .iter()
+                .map(|c| c.start_beat + c.length_beats),
+        )
+        .fold(0.0f64, f64::max);
+    if end_beat <= 0.0 {
+        bail!("Nothing to freeze on this track.");
+    }
+
+    let sample_rate = audio_state.sample_rate.load();
+    let converter = TimeConverter::new(sample_rate, app_state.bpm);
+    let total_frames =
+        (converter.beats_to_samples(end_beat) + FREEZE_TAIL_SECS * sample_rate as f64) as usize;
+
+    let pcm = render_isolated(snapshot, audio_state, sample_rate, 0, total_frames)?;
+    Ok((pcm, sample_rate))
+}
+
+/// Renders exactly `start_beat..end_beat` of one track the way a freeze does,
+/// for bouncing it. Everything the track plays is rendered, so the caller
+/// leaves only the clips being bounced on it.
+pub fn render_track_range(
+    app_state: &AppState,
+    audio_state: &AudioState,
+    track_id: u64,
+    start_beat: f64,
+    end_beat: f64,
+) -> Result<(Vec<f32>, f32)> {
+    if end_beat <= start_beat {
+        bail!("The bounce range is empty.");
+    }
+    let snapshot = isolated_track_snapshot(app_state, track_id)?;
+
+    let sample_rate = audio_state.sample_rate.load();
+    let converter = TimeConverter::new(sample_rate, app_state.bpm);
+    let start_frame = converter.beats_to_samples(start_beat).round() as usize;
+    let end_frame = converter.beats_to_samples(end_beat).round() as usize;
+    let frames = end_frame.saturating_sub(start_frame);
+
+    let pcm = render_isolated(snapshot, audio_state, sample_rate, start_frame, frames)?;
+    Ok((pcm, sample_rate))
+}
+
+/// Level the auto tail treats as silence
+const TAIL_SILENCE_DB: f32 = -90.0;
+/// How long the output has to stay below it before the tail ends
+const TAIL_SILENCE_WINDOW_SECS: f64 = 0.5;
+/// Cap for the auto tail, for chains that never fall quiet
+const TAIL_AUTO_MAX_SECS: f64 = 30.0;
+
+/// Keeps rendering past the export range so reverb and delay tails ring out.
+/// Clips and MIDI stop at the end of the range, so only the effects are
+/// heard. In auto mode it stops once the output has been silent for a while
+/// and drops that silent run.
+fn render_tail(
+    engine: &mut AudioEngine,
+    config: &ExportConfig,
+    channels: usize,
+    mut pos: f64,
+    pcm: &mut Vec<f32>,
+) {
+    engine.silence_sources(pos);
+    let sample_rate = config.sample_rate as f64;
+    let tail_secs = if config.tail_auto {
+        TAIL_AUTO_MAX_SECS
+    } else {
+        config.tail_secs.max(0.0) as f64
+    };
+    let max_frames = (tail_secs * sample_rate) as usize;
+    let window = (TAIL_SILENCE_WINDOW_SECS * sample_rate) as usize;
+    let threshold = crate::audio_utils::db_to_linear(TAIL_SILENCE_DB);
+
+    let mut quiet = 0usize;
+    let mut done = 0usize;
+    while done < max_frames {
+        let batch = (max_frames - done).min(MAX_BUFFER_SIZE);
+        let mut buf = vec![0.0f32; batch * channels];
+        let mut plugin_time_ms = 0.0f32;
+        engine.process_audio(&mut buf, batch, channels, pos, &mut plugin_time_ms);
+        pcm.extend_from_slice(&buf);
+        pos += batch as f64;
+        done += batch;
+
+        if !config.tail_auto {
+            continue;
+        }
+        if buf.iter().all(|s| s.abs() < threshold) {
+            quiet += batch;
+            if quiet >= window {
+                pcm.truncate(pcm.len() - quiet * channels);
+                break;
+            }
+        } else {
+            quiet = 0;
+        }
+    }
+}
+
+/// Loudness normalization never pushes peaks past this
+const LOUDNESS_PEAK_CEILING_DB: f32 = -1.0;
+
+/// Gain that takes the finished render to the configured peak or loudness.
+/// None for silence.
+fn normalize_gain(pcm: &[f32], channels: usize, config: &ExportConfig) -> Option<f32> {
+    let peak = crate::metering::buffer_peak(pcm, channels, config.true_peak);
+    if peak <= 1e-6 {
+        return None;
+    }
+    let gain_to_peak = |db: f32| crate::audio_utils::db_to_linear(db) / peak;
+
+    match config.normalize_target {
+        NormalizeTarget::Peak(db) => Some(gain_to_peak(db.min(0.0))),
+        NormalizeTarget::Loudness(lufs) => {
+            let mut meter = crate::metering::LoudnessMeter::new(config.sample_rate as f64);
+            for frame in pcm.chunks_exact(channels) {
+                meter.process(frame[0], frame[channels.min(2) - 1]);
+            }
+            let measured = meter.integrated();
+            if !measured.is_finite() {
+                return None;
+            }
+            let gain = crate::audio_utils::db_to_linear(lufs - measured);
+            let ceiling = gain_to_peak(LOUDNESS_PEAK_CEILING_DB);
+            if gain > ceiling {
+                log::warn!(
+                    "{lufs} LUFS would clip; normalizing to a {LOUDNESS_PEAK_CEILING_DB} dBFS peak instead"
+                );
+            }
+            Some(gain.min(ceiling))
+        }
+    }
+}
+
+/// Adds +-1 LSB triangular noise ahead of quantizing to `bits`, so detail
+/// below the last bit turns into steady noise rather than distortion
+fn apply_tpdf_dither(pcm: &mut [f32], bits: u16) {
+    use rand::RngExt;
+    let mut rng = rand::rng();
+    let lsb = 1.0 / (1u32 << (bits - 1)) as f32;
+    for s in pcm {
+        *s += (rng.random::<f32>() - rng.random::<f32>()) * lsb;
+    }
+}
+
 fn run_export(
     app_state: AppState,
     audio_state: Arc<AudioState>,
@@ -135,19 +492,25 @@ fn run_export(
         bail!("Export range is zero length.");
     }
 
-    let snapshots = crate::audio_snapshot::build_track_snapshots(&app_state);
+    let snapshots = export_snapshots(&app_state, &config);
     let mut engine =
         AudioEngine::new_for_offline_render(&snapshots, &audio_state, config.sample_rate)?;
+    engine.set_vca_groups(export_groups(&app_state, &config));
+    engine.set_automation_interval(config.automation_interval);
+    engine.set_master_clip_bypass(config.bypass_master_soft_clipper);
+    // Compensated plugin latency delays the mix; render it extra and drop it below
+    let latency = engine.output_latency();
+    let render_frames = total_frames + latency as u64;
 
     send(ui_tx, ExportState::Rendering(0.0));
 
-    let total_samples = total_frames as usize * channels;
+    let total_samples = render_frames as usize * channels;
     let mut pcm = Vec::<f32>::with_capacity(total_samples);
     let mut current_pos = start_sample as f64;
     let mut frames_done = 0u64;
 
-    while frames_done < total_frames {
-        let batch = ((total_frames - frames_done) as usize).min(MAX_BUFFER_SIZE);
+    while frames_done < render_frames {
+        let batch = ((render_frames - frames_done) as usize).min(MAX_BUFFER_SIZE);
         let mut buf = vec![0.0f32; batch * channels];
         let mut plugin_time_ms = 0.0f32;
 
@@ -159,20 +522,24 @@ fn run_export(
 
         send(
             ui_tx,
-            ExportState::Rendering(frames_done as f32 / total_frames as f32),
+            ExportState::Rendering(frames_done as f32 / render_frames as f32),
         );
     }
+    render_tail(&mut engine, &config, channels, current_pos, &mut pcm);
+    pcm.drain(..latency * channels);
 
     if config.normalize {
         send(ui_tx, ExportState::Normalizing);
-        let peak = pcm.iter().copied().map(f32::abs).fold(0.0f32, f32::max);
-        if peak > 1e-6 {
-            let gain = 0.99 / peak;
+        if let Some(gain) = normalize_gain(&pcm, channels, &config) {
             for s in &mut pcm {
                 *s *= gain;
             }
         }
     }
+    // Float and Opus output keep the full resolution
+    if config.dither && matches!(sample_format, SampleFormat::I16) && format != ExportFormat::Ogg {
+        apply_tpdf_dither(&mut pcm, 16);
+    }
 
     send(ui_tx, ExportState::Finalizing);
 
@@ -191,6 +558,8 @@ fn run_export(
         }
     }
 
+    tag_export_file(&temp_path, format, &app_state.metadata)?;
+
     std::fs::rename(&temp_path, &output_path)
         .map_err(|e| anyhow!("Failed to move temp file: {e}"))?;
 
@@ -232,7 +601,9 @@ fn write_wav(
     ))?;
 
     let mut sink = muxer.track_writer(track);
-    encode_pcm_from_f32(&mut encoder, pcm, sample_format, &mut sink)?;
+    // Float WAV can carry overs, so only clamp when the master clipper is active
+    let clamp_float = !config.bypass_master_soft_clipper;
+    encode_pcm_from_f32(&mut encoder, pcm, sample_format, clamp_float, &mut sink)?;
     encoder.flush(&mut sink)?;
     drop(sink);
 
@@ -259,7 +630,7 @@ fn write_flac(
 
     {
         let mut sink = muxer.track_writer(track);
-        encode_pcm_from_f32(&mut encoder, pcm, sample_format, &mut sink)?;
+        encode_pcm_from_f32(&mut encoder, pcm, sample_format, true, &mut sink)?;
         encoder.flush(&mut sink)?;
     }
 
@@ -314,7 +685,7 @@ fn write_ogg(
 
     {
         let mut sink = muxer.track_writer(track);
-        encode_pcm_from_f32(&mut encoder, &pcm_data, SampleFormat::F32, &mut sink)?;
+        encode_pcm_from_f32(&mut encoder, &pcm_data, SampleFormat::F32, true, &mut sink)?;
         encoder.flush(&mut sink)?;
     }
 
@@ -322,10 +693,126 @@ fn write_ogg(
     Ok(())
 }
 
+/// RIFF INFO id and Vorbis comment name for each non-empty tagged field
+fn tag_fields(metadata: &ProjectMetadata) -> Vec<([u8; 4], &'static str, &str)> {
+    [
+        (*b"INAM", "TITLE", metadata.title.trim()),
+        (*b"IART", "ARTIST", metadata.artist.trim()),
+    ]
+    .into_iter()
+    .filter(|(_, _, value)| !value.is_empty())
+    .collect()
+}
+
+fn tag_export_file(path: &Path, format: ExportFormat, metadata: &ProjectMetadata) -> Result<()> {
+    if tag_fields(metadata).is_empty() {
+        return Ok(());
+    }
+
+    let bytes = std::fs::read(path).map_err(|e| anyhow!("Cannot read export for tagging: {e}"))?;
+    let tagged = match format {
+        ExportFormat::Wav => tag_wav(bytes, metadata)?,
+        ExportFormat::Flac => tag_flac(bytes, metadata)?,
+        ExportFormat::Ogg => return Ok(()),
+    };
+    std::fs::write(path, tagged).map_err(|e| anyhow!("Cannot write export tags: {e}"))?;
+    Ok(())
+}
+
+/// Appends a LIST/INFO chunk and fixes up the RIFF size.
+fn tag_wav(mut bytes: Vec<u8>, metadata: &ProjectMetadata) -> Result<Vec<u8>> {
+    let fields = tag_fields(metadata);
+    if fields.is_empty() {
+        return Ok(bytes);
+    }
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        bail!("Cannot tag export: not a RIFF/WAVE file");
+    }
+
+    let mut info = b"INFO".to_vec();
+    for (id, _, value) in fields {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        info.extend_from_slice(&id);
+        info.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        info.extend_from_slice(&data);
+        if data.len() % 2 == 1 {
+            info.push(0);
+        }
+    }
+
+    // Chunks are word aligned
+    if bytes.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(b"LIST");
+    bytes.extend_from_slice(&(info.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&info);
+
+    let riff_size = (bytes.len() - 8) as u32;
+    bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(bytes)
+}
+
+/// Replaces any VORBIS_COMMENT block with one holding the project tags.
+fn tag_flac(bytes: Vec<u8>, metadata: &ProjectMetadata) -> Result<Vec<u8>> {
+    const VORBIS_COMMENT: u8 = 4;
+
+    if bytes.len() < 4 || &bytes[0..4] != b"fLaC" {
+        bail!("Cannot tag export: not a FLAC file");
+    }
+
+    let fields = tag_fields(metadata);
+    let mut comment = Vec::new();
+    let vendor = b"YADAW";
+    comment.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    comment.extend_from_slice(vendor);
+    comment.extend_from_slice(&(fields.len() as u32).to_le_bytes());
+    for (_, name, value) in fields {
+        let entry = format!("{name}={value}");
+        comment.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+        comment.extend_from_slice(entry.as_bytes());
+    }
+
+    let mut blocks: Vec<(u8, &[u8])> = Vec::new();
+    let mut pos = 4;
+    loop {
+        let truncated = || anyhow!("Cannot tag export: truncated FLAC metadata");
+        let header = bytes.get(pos..pos + 4).ok_or_else(truncated)?;
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7F;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        let data = bytes.get(pos + 4..pos + 4 + len).ok_or_else(truncated)?;
+        if block_type != VORBIS_COMMENT {
+            blocks.push((block_type, data));
+        }
+        pos += 4 + len;
+        if is_last {
+            break;
+        }
+    }
+
+    // STREAMINFO has to stay first
+    blocks.insert(blocks.len().min(1), (VORBIS_COMMENT, comment.as_slice()));
+
+    let mut out = Vec::with_capacity(bytes.len() + comment.len() + 4);
+    out.extend_from_slice(b"fLaC");
+    let count = blocks.len();
+    for (i, (block_type, data)) in blocks.into_iter().enumerate() {
+        let last_flag = if i + 1 == count { 0x80 } else { 0 };
+        out.push(last_flag | block_type);
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+        out.extend_from_slice(data);
+    }
+    out.extend_from_slice(&bytes[pos..]);
+    Ok(out)
+}
+
 fn encode_pcm_from_f32(
     encoder: &mut dyn Encoder,
     pcm: &[f32],
     sample_format: SampleFormat,
+    clamp_float: bool,
     sink: &mut dyn PacketSink,
 ) -> Result<()> {
     match sample_format {
@@ -344,6 +831,9 @@ fn encode_pcm_from_f32(
                 .collect();
             encoder.encode(AudioBufferRef::I24(&samples), sink)?;
         }
+        SampleFormat::F32 if !clamp_float => {
+            encoder.encode(AudioBufferRef::F32(pcm), sink)?;
+        }
         SampleFormat::F32 => {
             let clamped: Vec<f32> = pcm.iter().map(|&s| s.clamp(-1.0, 1.0)).collect();
             encoder.encode(AudioBufferRef::F32(&clamped), sink)?;
@@ -377,9 +867,12 @@ async fn run_export_wasm(
         bail!("Export range is zero length.");
     }
 
-    let snapshots = crate::audio_snapshot::build_track_snapshots(&app_state);
+    let snapshots = export_snapshots(&app_state, &config);
     let mut engine =
         AudioEngine::new_for_offline_render(&snapshots, &audio_state, config.sample_rate)?;
+    engine.set_vca_groups(export_groups(&app_state, &config));
+    engine.set_automation_interval(config.automation_interval);
+    engine.set_master_clip_bypass(config.bypass_master_soft_clipper);
 
     let total_samples = total_frames as usize * channels;
     let mut pcm = Vec::<f32>::with_capacity(total_samples);
@@ -397,14 +890,14 @@ async fn run_export_wasm(
         current_pos += batch as f64;
         frames_done += batch as u64;
     }
+    render_tail(&mut engine, config, channels, current_pos, &mut pcm);
 
-    if config.normalize {
-        let peak = pcm.iter().copied().map(f32::abs).fold(0.0f32, f32::max);
-        if peak > 1e-6 {
-            let gain = 0.99 / peak;
-            for s in &mut pcm {
-                *s *= gain;
-            }
+    // Written as 32-bit float, so there's nothing to dither
+    if config.normalize
+        && let Some(gain) = normalize_gain(&pcm, channels, config)
+    {
+        for s in &mut pcm {
+            *s *= gain;
         }
     }
 
@@ -436,7 +929,7 @@ async fn run_export_wasm(
                 .finalize()
                 .map_err(|e| anyhow!("Failed to finalize WAV: {e}"))?;
         }
-        cursor.into_inner()
+        tag_wav(cursor.into_inner(), &app_state.metadata)?
     };
 
     let uint8 = js_sys::Uint8Array::from(&wav_bytes[..]);
@@ -463,3 +956,275 @@ async fn run_export_wasm(
 
     Ok(filename)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> ProjectMetadata {
+        ProjectMetadata {
+            title: "Night Drive".into(),
+            artist: " Someone ".into(),
+            ..Default::default()
+        }
+    }
+
+    /// (id, payload) of each chunk after the RIFF/WAVE header
+    fn riff_chunks(bytes: &[u8]) -> Vec<([u8; 4], &[u8])> {
+        let mut chunks = Vec::new();
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id = bytes[pos..pos + 4].try_into().unwrap();
+            let len = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            chunks.push((id, &bytes[pos + 8..pos + 8 + len]));
+            pos += 8 + len + len % 2;
+        }
+        chunks
+    }
+
+    /// (type, is_last, payload) of each FLAC metadata block, then the frames
+    fn flac_blocks(bytes: &[u8]) -> (Vec<(u8, bool, &[u8])>, &[u8]) {
+        let mut blocks = Vec::new();
+        let mut pos = 4;
+        loop {
+            let header = &bytes[pos..pos + 4];
+            let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+            let is_last = header[0] & 0x80 != 0;
+            blocks.push((header[0] & 0x7F, is_last, &bytes[pos + 4..pos + 4 + len]));
+            pos += 4 + len;
+            if is_last {
+                return (blocks, &bytes[pos..]);
+            }
+        }
+    }
+
+    fn flac_block(out: &mut Vec<u8>, block_type: u8, last: bool, data: &[u8]) {
+        out.push(if last { 0x80 } else { 0 } | block_type);
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+        out.extend_from_slice(data);
+    }
+
+    #[test]
+    fn wav_tags_read_back_from_an_info_list() {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend_from_slice(b"fmt \x10\0\0\0");
+        wav.extend_from_slice(&[0; 16]);
+        wav.extend_from_slice(b"data\x03\0\0\0\x01\x02\x03");
+        let riff_size = (wav.len() - 8) as u32;
+        wav[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+        let tagged = tag_wav(wav, &metadata()).unwrap();
+        let riff_size = u32::from_le_bytes(tagged[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_size, tagged.len() - 8);
+
+        let chunks = riff_chunks(&tagged);
+        let ids: Vec<_> = chunks.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [b"fmt ", b"data", b"LIST"]);
+        assert_eq!(chunks[1].1, [1, 2, 3]);
+
+        let list = chunks[2].1;
+        assert_eq!(&list[..4], b"INFO");
+        let mut fields = Vec::new();
+        let mut pos = 4;
+        while pos < list.len() {
+            let len = u32::from_le_bytes(list[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let value = &list[pos + 8..pos + 8 + len];
+            fields.push((&list[pos..pos + 4], value));
+            pos += 8 + len + len % 2;
+        }
+        assert_eq!(
+            fields,
+            [
+                (&b"INAM"[..], &b"Night Drive\0"[..]),
+                (&b"IART"[..], &b"Someone\0"[..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn untagged_metadata_leaves_the_wav_alone() {
+        let wav = b"RIFF\x04\0\0\0WAVE".to_vec();
+        assert_eq!(
+            tag_wav(wav.clone(), &ProjectMetadata::default()).unwrap(),
+            wav
+        );
+        assert!(tag_wav(b"OggS".to_vec(), &metadata()).is_err());
+    }
+
+    #[test]
+    fn flac_tags_replace_the_old_comment_after_streaminfo() {
+        let mut flac = b"fLaC".to_vec();
+        flac_block(&mut flac, 0, false, &[7; 34]);
+        flac_block(&mut flac, 4, false, b"stale comment");
+        flac_block(&mut flac, 1, true, &[0; 8]);
+        flac.extend_from_slice(&[0xFF, 0xF8, 0x42]);
+
+        let tagged = tag_flac(flac, &metadata()).unwrap();
+        let (blocks, frames) = flac_blocks(&tagged);
+        let layout: Vec<_> = blocks.iter().map(|&(t, last, _)| (t, last)).collect();
+        assert_eq!(layout, [(0, false), (4, false), (1, true)]);
+        assert_eq!(blocks[0].2, [7; 34]);
+        assert_eq!(frames, [0xFF, 0xF8, 0x42]);
+
+        let comment = blocks[1].2;
+        let vendor_len = u32::from_le_bytes(comment[..4].try_into().unwrap()) as usize;
+        let mut pos = 4 + vendor_len;
+        let count = u32::from_le_bytes(comment[pos..pos + 4].try_into().unwrap());
+        pos += 4;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let len = u32::from_le_bytes(comment[pos..pos + 4].try_into().unwrap()) as usize;
+            entries.push(std::str::from_utf8(&comment[pos + 4..pos + 4 + len]).unwrap());
+            pos += 4 + len;
+        }
+        assert_eq!(pos, comment.len());
+        assert_eq!(entries, ["TITLE=Night Drive", "ARTIST=Someone"]);
+    }
+
+    #[test]
+    fn flac_with_only_streaminfo_gets_the_comment_last() {
+        let mut flac = b"fLaC".to_vec();
+        flac_block(&mut flac, 0, true, &[0; 34]);
+
+        let tagged = tag_flac(flac, &metadata()).unwrap();
+        let (blocks, frames) = flac_blocks(&tagged);
+        let layout: Vec<_> = blocks.iter().map(|&(t, last, _)| (t, last)).collect();
+        assert_eq!(layout, [(0, false), (4, true)]);
+        assert!(frames.is_empty());
+        assert!(tag_flac(b"fLaC\0\0".to_vec(), &metadata()).is_err());
+    }
+
+    fn marker(beat: f64, name: &str) -> Marker {
+        Marker {
+            beat,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn marker_sections_are_numbered_apart() {
+        let markers = [
+            marker(0.0, "Verse"),
+            // Zero length, skipped without using up a number
+            marker(8.0, "Empty"),
+            marker(8.0, "Verse"),
+            marker(16.0, "Chorus"),
+        ];
+        let sections = marker_sections(&markers, 24.0);
+        assert_eq!(
+            sections,
+            vec![
+                ("01 Verse".to_string(), 0.0, 8.0),
+                ("02 Verse".to_string(), 8.0, 16.0),
+                ("03 Chorus".to_string(), 16.0, 24.0),
+            ]
+        );
+        // A marker at or past the end has nothing to export
+        assert!(marker_sections(&[marker(24.0, "Outro")], 24.0).is_empty());
+    }
+
+    #[test]
+    fn same_named_sections_get_their_own_files() {
+        let path = Path::new("/out/song.wav");
+        let markers = [marker(0.0, "Verse"), marker(8.0, "Verse")];
+        let paths: Vec<PathBuf> = marker_sections(&markers, 16.0)
+            .iter()
+            .map(|(name, _, _)| suffixed_path(path, name))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/out/song_01_Verse.wav"),
+                PathBuf::from("/out/song_02_Verse.wav"),
+            ]
+        );
+    }
+
+    #[test]
+    fn suffixed_path_keeps_the_folder_and_extension() {
+        assert_eq!(
+            suffixed_path(Path::new("mix/song.flac"), "Verse 1/2"),
+            PathBuf::from("mix/song_Verse_1_2.flac")
+        );
+        assert_eq!(
+            suffixed_path(Path::new("song"), "Intro"),
+            PathBuf::from("song_Intro")
+        );
+    }
+
+    fn normalize_config(target: NormalizeTarget) -> ExportConfig {
+        ExportConfig {
+            path: PathBuf::new(),
+            export_uri: None,
+            format: None,
+            sample_rate: 48000.0,
+            bit_depth: 24,
+            start_beat: 0.0,
+            end_beat: 0.0,
+            normalize: true,
+            automation_interval: 0,
+            bypass_master_soft_clipper: false,
+            true_peak: false,
+            stem: None,
+            normalize_target: target,
+            dither: false,
+            tail_secs: 0.0,
+            tail_auto: false,
+        }
+    }
+
+    /// Five seconds of a 1 kHz sine peaking at `amp` in both channels
+    fn stereo_sine(amp: f32) -> Vec<f32> {
+        (0..5 * 48000)
+            .flat_map(|n| {
+                let s = amp * (std::f64::consts::TAU * 1000.0 * n as f64 / 48000.0).sin() as f32;
+                [s, s]
+            })
+            .collect()
+    }
+
+    fn gain_db(gain: Option<f32>) -> f32 {
+        crate::audio_utils::linear_to_db(gain.expect("a gain"))
+    }
+
+    #[test]
+    fn peak_normalize_scales_the_loudest_sample_to_the_target() {
+        let pcm = [0.5, -0.25, 0.125, -0.05];
+        let gain = normalize_gain(&pcm, 2, &normalize_config(NormalizeTarget::Peak(-6.0)));
+        assert!((gain_db(gain) - (-6.0 + 6.02)).abs() < 0.01);
+        // Targets above full scale stop at 0 dBFS
+        let gain = normalize_gain(&pcm, 2, &normalize_config(NormalizeTarget::Peak(3.0)));
+        assert!((gain.unwrap() - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn loudness_normalize_reaches_the_target() {
+        // Both channels at -20 dBFS read -20 LUFS
+        let pcm = stereo_sine(0.1);
+        let gain = normalize_gain(&pcm, 2, &normalize_config(NormalizeTarget::Loudness(-23.0)));
+        assert!((gain_db(gain) + 3.0).abs() < 0.05, "{:?}", gain);
+    }
+
+    #[test]
+    fn loudness_normalize_keeps_a_peak_ceiling() {
+        // 0 LUFS would need +20 dB and push the peak to 0 dBFS
+        let pcm = stereo_sine(0.1);
+        let gain = normalize_gain(&pcm, 2, &normalize_config(NormalizeTarget::Loudness(0.0)));
+        assert!(
+            (gain_db(gain) - (LOUDNESS_PEAK_CEILING_DB + 20.0)).abs() < 0.01,
+            "{:?}",
+            gain
+        );
+    }
+
+    #[test]
+    fn silence_is_not_normalized() {
+        let pcm = vec![0.0; 4800];
+        for target in [
+            NormalizeTarget::Peak(-1.0),
+            NormalizeTarget::Loudness(-14.0),
+        ] {
+            assert_eq!(normalize_gain(&pcm, 2, &normalize_config(target)), None);
+        }
+    }
+}
@@ -24,6 +24,26 @@ fn new_audio_clip(
     }
 }
 
+/// Fade lengths offered in the clip menu, in milliseconds
+pub const FADE_PRESETS_MS: [f32; 6] = [1.0, 2.0, 5.0, 10.0, 50.0, 200.0];
+
+/// `fade_ms` in beats, capped so the fades at both ends of `clip` never overlap
+pub fn fade_beats(clip: &AudioClip, fade_ms: f32, bpm: f32) -> f64 {
+    let beats = (fade_ms.max(0.0) as f64 / 1000.0) * (bpm.max(0.0) as f64 / 60.0);
+    beats.min(clip.length_beats.max(0.0) / 2.0)
+}
+
+/// Fade both ends of a new clip by `fade_ms` so it doesn't click.
+/// Fades already set on the clip are kept.
+pub fn apply_default_fades(clip: &mut AudioClip, fade_ms: f32, bpm: f32) {
+    if fade_ms <= 0.0 || bpm <= 0.0 {
+        return;
+    }
+    let beats = fade_beats(clip, fade_ms, bpm);
+    clip.fade_in.get_or_insert(beats);
+    clip.fade_out.get_or_insert(beats);
+}
+
 fn hash_source_bytes(data: &[u8]) -> u64 {
     let mut hasher = std::hash::DefaultHasher::new();
     data.hash(&mut hasher);
@@ -229,3 +249,41 @@ fn trim_silence_end(samples: &[f32], threshold: f32) -> Vec<f32> {
     }
     samples[..last_non_silent].to_vec()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(length_beats: f64) -> AudioClip {
+        AudioClip {
+            length_beats,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fade_converts_milliseconds_at_the_project_tempo() {
+        // 200 ms at 120 bpm is 0.4 beats
+        assert!((fade_beats(&clip(8.0), 200.0, 120.0) - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fade_is_capped_at_half_the_clip() {
+        assert_eq!(fade_beats(&clip(0.2), 200.0, 120.0), 0.1);
+    }
+
+    #[test]
+    fn default_fades_keep_existing_ones() {
+        let mut c = AudioClip {
+            fade_in: Some(1.0),
+            ..clip(8.0)
+        };
+        apply_default_fades(&mut c, 200.0, 120.0);
+        assert_eq!(c.fade_in, Some(1.0));
+        assert!((c.fade_out.unwrap() - 0.4).abs() < 1e-9);
+
+        let mut c = clip(8.0);
+        apply_default_fades(&mut c, 0.0, 120.0);
+        assert_eq!((c.fade_in, c.fade_out), (None, None));
+    }
+}
@@ -1,13 +1,14 @@
 //! Build immutable audio snapshots from UI/project model types.
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use dashmap::DashMap;
 
 use crate::{
     audio_state::{
-        AudioClipSnapshot, MidiClipSnapshot, MidiNoteSnapshot, PluginDescriptorSnapshot,
-        RtAutomationLaneSnapshot, RtAutomationPoint, RtAutomationTarget, RtCurveType,
-        TrackSnapshot,
+        AudioClipSnapshot, CrossfadeEdge, MidiClipSnapshot, MidiNoteSnapshot,
+        PluginDescriptorSnapshot, RtAutomationLaneSnapshot, RtAutomationPoint, RtAutomationTarget,
+        TrackSnapshot, VcaGroupSnapshot,
     },
     model::{
         clip::{AudioClip, MidiClip, MidiNote},
@@ -17,6 +18,11 @@ use crate::{
     project::AppState,
 };
 
+/// Mixer-side state of every track group
+pub fn build_group_snapshots(state: &AppState) -> HashMap<u64, VcaGroupSnapshot> {
+    state.groups.iter().map(|(&id, g)| (id, g.vca())).collect()
+}
+
 /// Build snapshots
 pub fn build_track_snapshots(state: &AppState) -> Vec<TrackSnapshot> {
     state
@@ -35,10 +41,21 @@ fn track_to_snapshot(t: &Track, state: &AppState) -> TrackSnapshot {
         pan: t.pan,
         muted: t.muted,
         solo: t.solo,
+        solo_safe: t.solo_safe,
+        phase_inverted: t.phase_inverted,
+        mono: t.mono,
         armed: t.armed,
         track_type: t.track_type,
         monitor_enabled: t.monitor_enabled,
-        audio_clips: t.audio_clips.iter().map(audio_clip_to_snapshot).collect(),
+        monitor_gain_db: t.monitor_gain_db,
+        input_channel: t.input_channel,
+        cue: t.cue,
+        output_bus: t.output_bus,
+        audio_clips: t
+            .audio_clips
+            .iter()
+            .map(|c| audio_clip_to_snapshot(c, t))
+            .collect(),
         midi_clips: t
             .midi_clips
             .iter()
@@ -51,10 +68,26 @@ fn track_to_snapshot(t: &Track, state: &AppState) -> TrackSnapshot {
             .map(automation_lane_to_snapshot)
             .collect(),
         sends: t.sends.clone(),
+        frozen_buffer: t.frozen_buffer.clone().filter(|_| t.frozen),
+        frozen_sample_rate: t.frozen_sample_rate,
+        group_id: t.group_id.filter(|id| state.groups.contains_key(id)),
     }
 }
 
-fn audio_clip_to_snapshot(c: &AudioClip) -> AudioClipSnapshot {
+fn audio_clip_to_snapshot(c: &AudioClip, t: &Track) -> AudioClipSnapshot {
+    let edge = |incoming: bool| {
+        t.crossfades
+            .iter()
+            .filter(|xf| if incoming { xf.in_clip } else { xf.out_clip } == c.id)
+            .find_map(|xf| {
+                let (start_beat, end_beat) = t.crossfade_span(xf)?;
+                Some(CrossfadeEdge {
+                    start_beat,
+                    end_beat,
+                    curve: xf.curve,
+                })
+            })
+    };
     AudioClipSnapshot {
         clip_id: c.id,
         name: c.name.clone(),
@@ -62,11 +95,22 @@ fn audio_clip_to_snapshot(c: &AudioClip) -> AudioClipSnapshot {
         length_beats: c.length_beats,
         offset_beats: c.offset_beats,
         samples: c.samples.clone(),
+        samples_right: c.samples_right.clone(),
         sample_rate: c.sample_rate,
         warp_mode: c.warp_mode,
+        original_bpm: c.original_bpm,
+        loop_enabled: c.loop_enabled,
+        loop_mode: c.loop_mode,
+        loop_crossfade: c.loop_crossfade,
         fade_in: c.fade_in,
         fade_out: c.fade_out,
+        fade_in_curve: c.fade_in_curve,
+        fade_out_curve: c.fade_out_curve,
         gain: c.gain,
+        pitch_semitones: c.pitch_semitones,
+        reversed: c.reversed,
+        crossfade_in: edge(true),
+        crossfade_out: edge(false),
     }
 }
 
@@ -91,6 +135,7 @@ fn midi_clip_to_snapshot(c: &MidiClip, state: &AppState) -> MidiClipSnapshot {
             c.length_beats.max(0.000001)
         },
         loop_enabled: c.loop_enabled,
+        loop_mode: c.loop_mode,
         notes: base_notes.iter().map(midi_note_to_snapshot).collect(),
         color: c.color,
         transpose: c.transpose,
@@ -130,6 +175,8 @@ fn plugin_desc_to_snapshot(p: &PluginDescriptor) -> PluginDescriptorSnapshot {
         backend: p.backend,
         bypass: p.bypass,
         params,
+        sidechain_source: p.sidechain_source,
+        state: p.state.as_deref().map(Arc::from),
     }
 }
 
@@ -152,6 +199,9 @@ fn automation_lane_to_snapshot(
                 plugin_id: *plugin_id,
                 param_name: param_name.clone(),
             },
+            crate::model::automation::AutomationTarget::MidiCc(cc) => {
+                RtAutomationTarget::MidiCc(*cc)
+            }
         },
         points: l
             .points
@@ -159,11 +209,13 @@ fn automation_lane_to_snapshot(
             .map(|p| RtAutomationPoint {
                 beat: p.beat,
                 value: p.value,
-                curve_type: RtCurveType::Linear,
+                curve: p.curve,
+                tension: p.tension,
             })
             .collect(),
         visible: l.visible,
         height: l.height,
         color: l.color,
+        read: l.plays_back(),
     }
 }
@@ -1,11 +1,15 @@
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 
-use crate::constants::DEFAULT_LOOP_LEN;
+use crate::constants::{DEFAULT_CUE_OUTPUT_CHANNEL, DEFAULT_LOOP_LEN};
+use crate::metronome::ClickSounds;
+use crate::model::clip::{ClipLoopMode, CrossfadeCurve, FadeCurve};
 use crate::model::track::TrackType;
-use yadaw_plugin_api::BackendKind;
+use crate::time_utils::TimeSignature;
+use yadaw_plugin_api::{BackendKind, PluginStateHandle};
 
 pub struct AtomicF64 {
     storage: AtomicU64,
@@ -51,11 +55,50 @@ pub struct AudioState {
     pub bpm: Arc<AtomicF32>,
     pub sample_rate: Arc<AtomicF32>,
     pub master_volume: Arc<AtomicF32>,
+    /// Skip the master soft clipper (output may exceed 0 dBFS)
+    pub master_clip_bypass: Arc<AtomicBool>,
+    /// Master meter reports inter-sample peaks instead of sample peaks
+    pub true_peak_metering: Arc<AtomicBool>,
+    /// Soloing a channel also keeps the buses its sends reach audible
+    pub solo_in_place: Arc<AtomicBool>,
     pub loop_enabled: Arc<AtomicBool>,
     pub loop_start: Arc<AtomicF64>,
     pub loop_end: Arc<AtomicF64>,
+    /// Loop passes before playback stops, 0 loops forever
+    pub loop_count: Arc<AtomicUsize>,
+    /// Recording only captures between `punch_in` and `punch_out` (beats)
+    pub punch_enabled: Arc<AtomicBool>,
+    pub punch_in: Arc<AtomicF64>,
+    pub punch_out: Arc<AtomicF64>,
 
     pub metronome_enabled: Arc<AtomicBool>,
+    /// Click the eighths between beats as well
+    pub click_eighths: Arc<AtomicBool>,
+    /// Bars of click before recording starts, 0 = record immediately
+    pub count_in_bars: Arc<AtomicU32>,
+    /// Position recording waits for while counting in (NaN when not counting in)
+    pub count_in_target: Arc<AtomicF64>,
+    /// Transport and tempo follow incoming MIDI clock
+    pub external_sync: Arc<AtomicBool>,
+
+    /// Channel count of the open output device
+    pub output_channels: Arc<AtomicUsize>,
+    /// First device channel of the stereo pair the master is sent to
+    pub master_output_channel: Arc<AtomicUsize>,
+
+    pub cue_level: Arc<AtomicF32>,
+    pub cue_output_channel: Arc<AtomicUsize>,
+    /// Send the metronome to the cue bus as well
+    pub cue_click: Arc<AtomicBool>,
+
+    /// State handles of the engine's live plugins, keyed by plugin id, so their
+    /// state can be saved off the audio thread
+    pub plugin_state_handles: Arc<DashMap<u64, Arc<dyn PluginStateHandle>>>,
+    /// Bumped each time the command processor finishes a plugin state capture
+    pub plugin_states_epoch: Arc<AtomicU64>,
+
+    /// Track feeding the spectrum analyzer, or one of the `crate::spectrum` sentinels
+    pub spectrum_source: Arc<AtomicU64>,
 }
 
 impl Default for AudioState {
@@ -73,11 +116,32 @@ impl AudioState {
             bpm: Arc::new(AtomicF32::new(120.0)),
             sample_rate: Arc::new(AtomicF32::new(44100.0)),
             master_volume: Arc::new(AtomicF32::new(0.8)),
+            master_clip_bypass: Arc::new(AtomicBool::new(false)),
+            true_peak_metering: Arc::new(AtomicBool::new(false)),
+            solo_in_place: Arc::new(AtomicBool::new(true)),
             loop_enabled: Arc::new(AtomicBool::new(true)),
             loop_start: Arc::new(AtomicF64::new(0.0)),
             loop_end: Arc::new(AtomicF64::new(DEFAULT_LOOP_LEN)),
+            loop_count: Arc::new(AtomicUsize::new(0)),
+            punch_enabled: Arc::new(AtomicBool::new(false)),
+            punch_in: Arc::new(AtomicF64::new(0.0)),
+            punch_out: Arc::new(AtomicF64::new(0.0)),
 
             metronome_enabled: Arc::new(AtomicBool::new(false)),
+            click_eighths: Arc::new(AtomicBool::new(false)),
+            count_in_bars: Arc::new(AtomicU32::new(0)),
+            count_in_target: Arc::new(AtomicF64::new(f64::NAN)),
+            external_sync: Arc::new(AtomicBool::new(false)),
+            output_channels: Arc::new(AtomicUsize::new(2)),
+            master_output_channel: Arc::new(AtomicUsize::new(0)),
+            cue_level: Arc::new(AtomicF32::new(1.0)),
+            cue_output_channel: Arc::new(AtomicUsize::new(DEFAULT_CUE_OUTPUT_CHANNEL)),
+            cue_click: Arc::new(AtomicBool::new(false)),
+
+            plugin_state_handles: Arc::new(DashMap::new()),
+            plugin_states_epoch: Arc::new(AtomicU64::new(0)),
+
+            spectrum_source: Arc::new(AtomicU64::new(crate::spectrum::SPECTRUM_OFF)),
         }
     }
 
@@ -87,6 +151,12 @@ impl AudioState {
     pub fn set_position(&self, pos: f64) {
         self.position.store(pos);
     }
+
+    /// Punch region in beats, when punch recording is on and the region isn't empty
+    pub fn punch_range(&self) -> Option<(f64, f64)> {
+        let (start, end) = (self.punch_in.load(), self.punch_out.load());
+        (self.punch_enabled.load(Ordering::Relaxed) && end > start).then_some((start, end))
+    }
 }
 
 /// Immutable snapshot of track data for audio processing
@@ -98,14 +168,43 @@ pub struct TrackSnapshot {
     pub pan: f32,
     pub muted: bool,
     pub solo: bool,
+    pub solo_safe: bool,
+    pub phase_inverted: bool,
+    pub mono: bool,
     pub armed: bool,
     pub monitor_enabled: bool,
+    pub monitor_gain_db: f32,
+    pub input_channel: Option<usize>,
+    pub cue: bool,
+    pub output_bus: Option<u64>,
     pub audio_clips: Vec<AudioClipSnapshot>,
     pub midi_clips: Vec<MidiClipSnapshot>,
     pub plugin_chain: Vec<PluginDescriptorSnapshot>,
     pub automation_lanes: Vec<RtAutomationLaneSnapshot>,
     pub sends: Vec<crate::model::track::Send>,
     pub track_type: TrackType,
+    /// Set while frozen: plays instead of the clips and plugin chain
+    pub frozen_buffer: Option<Arc<Vec<f32>>>,
+    pub frozen_sample_rate: f32,
+    pub group_id: Option<u64>,
+}
+
+/// Group controls as the mixer applies them to every member
+#[derive(Debug, Clone, Copy)]
+pub struct VcaGroupSnapshot {
+    pub gain: f32,
+    pub muted: bool,
+    pub solo: bool,
+}
+
+impl Default for VcaGroupSnapshot {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            muted: false,
+            solo: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -115,6 +214,7 @@ pub struct MidiClipSnapshot {
     pub length_beats: f64,
     pub content_len_beats: f64, // loop source length
     pub loop_enabled: bool,
+    pub loop_mode: ClipLoopMode,
     pub notes: Vec<MidiNoteSnapshot>,
     pub color: Option<(u8, u8, u8)>,
 
@@ -159,17 +259,25 @@ pub enum RealtimeCommand {
     UpdateTrackPan(u64, f32),                 // Track ID
     UpdateTrackMute(u64, bool),               // Track ID
     UpdateTrackSolo(u64, bool),               // Track ID
+    UpdateGroupVca(u64, f32),                 // Group ID
+    UpdateSendAmount(u64, usize, f32),        // track_id, send index, level
     UpdatePluginBypass(u64, u64, bool),       // track_id, plugin_id, bypass
     UpdatePluginParam(u64, u64, String, f32), // track_id, plugin_id, param, value
     PreviewNote(u64, u8, f64),                // Track ID
     StopPreviewNote,
     SetLoopEnabled(bool),
     SetLoopRegion(f64, f64),
+    /// The playhead was moved, so the loop count starts over
+    ResetLoopPasses,
     AddUnifiedPlugin {
         track_id: u64,
         plugin_id: u64,
         backend: BackendKind,
         uri: String,
+        /// Saved blob to restore, e.g. from a track template
+        state: Option<Arc<[u8]>>,
+        /// Params by name, applied on top of `state`
+        params: Vec<(String, f32)>,
     },
     RemovePluginInstance {
         track_id: u64,
@@ -195,13 +303,27 @@ pub enum RealtimeCommand {
         track_id: u64,
         chain: Vec<PluginDescriptorSnapshot>,
     },
+    /// New rendering order of a track's plugins, by plugin id
+    ReorderPlugins {
+        track_id: u64,
+        order: Vec<u64>,
+    },
     MidiMessage {
         track_id: u64,
         status: u8,
         data1: u8,
         data2: u8,
+        /// Arrival time from the input port, in microseconds since the Unix epoch
+        timestamp_us: u64,
     },
     OpenPluginEditor(u64, u64),
+    SetClickSounds(ClickSounds),
+    /// Tempo followed from external MIDI clock; the beat under the playhead stays
+    SyncTempo(f32),
+    /// Move the playhead to a beat, from external MIDI clock
+    SyncLocate(f64),
+    /// Position read from MIDI timecode, in seconds
+    SyncTimecode(f64),
 }
 
 #[derive(Debug, Clone)]
@@ -212,6 +334,8 @@ pub struct PluginDescriptorSnapshot {
     pub backend: BackendKind,
     pub bypass: bool,
     pub params: Arc<DashMap<String, f32>>,
+    pub sidechain_source: Option<u64>,
+    pub state: Option<Arc<[u8]>>,
 }
 
 #[derive(Debug, Clone)]
@@ -221,20 +345,16 @@ pub struct RtAutomationLaneSnapshot {
     pub visible: bool,
     pub height: f32,
     pub color: Option<(u8, u8, u8)>,
+    /// False when the lane is off or a write pass overrides it
+    pub read: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct RtAutomationPoint {
     pub beat: f64,
     pub value: f32,
-    pub curve_type: RtCurveType,
-}
-
-#[derive(Debug, Clone)]
-pub enum RtCurveType {
-    Linear,
-    Exponential,
-    Step,
+    pub curve: crate::model::CurveType,
+    pub tension: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -243,6 +363,7 @@ pub enum RtAutomationTarget {
     TrackPan,
     TrackSend(u64), // by id
     PluginParam { plugin_id: u64, param_name: String },
+    MidiCc(u8),
 }
 
 #[derive(Debug, Clone)]
@@ -253,15 +374,60 @@ pub struct AudioClipSnapshot {
     pub length_beats: f64,
     pub offset_beats: f64,
     pub samples: Vec<f32>,
+    /// Empty for mono clips, see `AudioClip::samples_right`
+    pub samples_right: Vec<f32>,
     pub sample_rate: f32,
     pub warp_mode: bool,
+    pub original_bpm: f32,
+    pub loop_enabled: bool,
+    pub loop_mode: ClipLoopMode,
+    pub loop_crossfade: f64,
     pub fade_in: Option<f64>,
     pub fade_out: Option<f64>,
+    pub fade_in_curve: FadeCurve,
+    pub fade_out_curve: FadeCurve,
     pub gain: f32,
+    pub pitch_semitones: f32,
+    pub reversed: bool,
+    /// Crossfade with the clip this one overlaps at its start / end
+    pub crossfade_in: Option<CrossfadeEdge>,
+    pub crossfade_out: Option<CrossfadeEdge>,
+}
+
+/// Project-beat region over which a clip fades against its neighbour
+#[derive(Debug, Clone, Copy)]
+pub struct CrossfadeEdge {
+    pub start_beat: f64,
+    pub end_beat: f64,
+    pub curve: CrossfadeCurve,
+}
+
+impl CrossfadeEdge {
+    /// Gain at `beat` for the clip fading out (`incoming == false`) or in
+    #[inline]
+    pub fn gain(&self, beat: f64, incoming: bool) -> f32 {
+        let len = (self.end_beat - self.start_beat).max(1e-9);
+        let t = ((beat - self.start_beat) / len).clamp(0.0, 1.0) as f32;
+        let (out_gain, in_gain) = self.curve.gains(t);
+        if incoming { in_gain } else { out_gain }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct AudioGraphSnapshot {
     pub tracks: Vec<TrackSnapshot>,
     pub track_order: Vec<u64>,
+    pub groups: HashMap<u64, VcaGroupSnapshot>,
+    /// Meter the click follows
+    pub time_signature: TimeSignature,
+}
+
+impl AudioGraphSnapshot {
+    /// Group controls for a track; unity when it has no group
+    pub fn vca(&self, group_id: Option<u64>) -> VcaGroupSnapshot {
+        group_id
+            .and_then(|id| self.groups.get(&id))
+            .copied()
+            .unwrap_or_default()
+    }
 }
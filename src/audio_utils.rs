@@ -40,3 +40,112 @@ pub fn soft_clip(x: f32) -> f32 {
         sign * (0.5 + (x.abs() - 0.5).tanh() * 0.5)
     }
 }
+
+/// Read of a source played `speed` times faster than recorded, transposed by
+/// the `pitch` ratio independently of the speed.
+///
+/// `pos` is the playback position in source samples at native speed. Hann-windowed
+/// grains of `grain` samples are overlap-added at 50%, each reading the source at
+/// `pitch` times its native rate from where the stretched timeline had reached when
+/// the grain started. Stateless, so it can be evaluated per sample from any position.
+pub fn pitch_stretch(
+    pos: f64,
+    speed: f64,
+    pitch: f64,
+    grain: f64,
+    read: impl Fn(f64) -> f32,
+) -> f32 {
+    let grain = grain.max(2.0);
+    let hop = grain * 0.5;
+    let k = (pos / hop).floor();
+
+    let mut out = 0.0f32;
+    for start in [(k - 1.0) * hop, k * hop] {
+        let local = pos - start;
+        if !(0.0..grain).contains(&local) {
+            continue;
+        }
+        let w = (std::f64::consts::PI * local / grain).sin().powi(2) as f32;
+        out += w * read((start * speed + local * pitch).max(0.0));
+    }
+    out
+}
+
+/// Guesses the tempo of a loop by assuming it spans a power-of-two number of beats,
+/// picking the count whose tempo lands closest to `near_bpm`.
+pub fn detect_loop_bpm(duration_secs: f64, near_bpm: f32) -> f32 {
+    if duration_secs <= 0.0 || near_bpm <= 0.0 {
+        return near_bpm;
+    }
+    (0..=8)
+        .map(|p| (1u32 << p) as f64 * 60.0 / duration_secs)
+        .min_by(|a, b| {
+            let da = (a / near_bpm as f64).ln().abs();
+            let db = (b / near_bpm as f64).ln().abs();
+            da.total_cmp(&db)
+        })
+        .map_or(near_bpm, |bpm| bpm as f32)
+}
+
+/// Fixed sample delay, used to line tracks up behind slower plugin chains.
+/// The history is allocated once for `MAX_PDC_SAMPLES`, so changing the delay
+/// on the audio thread only moves the read point.
+#[derive(Debug, Clone)]
+pub struct DelayLine {
+    buf: Vec<f32>,
+    pos: usize,
+    delay: usize,
+}
+
+impl Default for DelayLine {
+    fn default() -> Self {
+        Self {
+            buf: vec![0.0; crate::constants::MAX_PDC_SAMPLES + 1],
+            pos: 0,
+            delay: 0,
+        }
+    }
+}
+
+impl DelayLine {
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// Clamped to `MAX_PDC_SAMPLES`
+    pub fn set_delay(&mut self, samples: usize) {
+        self.delay = samples.min(self.buf.len() - 1);
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let len = self.buf.len();
+        for s in samples {
+            self.buf[self.pos] = *s;
+            *s = self.buf[(self.pos + len - self.delay) % len];
+            self.pos = (self.pos + 1) % len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_line_moves_its_read_point() {
+        let mut line = DelayLine::default();
+        line.set_delay(2);
+        let mut block = [1.0, 2.0, 3.0, 4.0];
+        line.process(&mut block);
+        assert_eq!(block, [0.0, 0.0, 1.0, 2.0]);
+
+        // A longer delay reads further back into the history
+        line.set_delay(3);
+        let mut block = [5.0, 6.0];
+        line.process(&mut block);
+        assert_eq!(block, [2.0, 3.0]);
+
+        line.set_delay(usize::MAX);
+        assert_eq!(line.delay(), crate::constants::MAX_PDC_SAMPLES);
+    }
+}
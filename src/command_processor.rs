@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 
@@ -10,15 +9,19 @@ use crate::audio_export::AudioExporter;
 use crate::audio_state::{AudioGraphSnapshot, AudioState, RealtimeCommand};
 use crate::edit_actions::EditProcessor;
 use crate::idgen;
-use crate::messages::{AudioCommand, UIUpdate, UiTx};
+use crate::messages::{AudioCommand, AudioDeviceSelection, UIUpdate, UiTx};
 use crate::midi_input::MidiInputHandler;
+use crate::midi_utils::MidiVelocity;
+use crate::model::automation::{AutomationLane, AutomationMode, AutomationTarget};
 use crate::model::clip::MidiPattern;
-use crate::model::track::TrackType;
-use crate::model::{AutomationPoint, MidiClip, MidiNote, PluginDescriptor, TrackGroup};
+use crate::model::track::{Track, TrackType};
+use crate::model::{
+    AudioClip, AutomationPoint, GrooveTemplate, MidiClip, MidiNote, PluginDescriptor, TrackGroup,
+};
 use crate::plugin::{create_plugin_instance, get_control_port_info};
 use crate::project::{AppState, ClipLocation, ClipRef};
-use crate::time_utils::quick::samples_to_beats;
-use yadaw_plugin_api::BackendKind;
+use crate::time_utils::TimeConverter;
+use yadaw_plugin_api::{BackendKind, PluginStateHandle};
 
 pub async fn run_command_processor(
     app_state: Arc<Mutex<AppState>>,
@@ -27,13 +30,17 @@ pub async fn run_command_processor(
     realtime_tx: Sender<RealtimeCommand>,
     ui_tx: UiTx,
     snapshot_tx: Sender<AudioGraphSnapshot>,
+    device_tx: flume::Sender<AudioDeviceSelection>,
     midi_input_handler: Option<Arc<MidiInputHandler>>,
 ) {
-    let mut midi_recording_state: Option<MidiRecordingState> = None;
     while let Ok(command) = command_rx.recv_async().await {
+        // The audio thread may be waiting with no stream, so it hears directly
+        if let AudioCommand::SetAudioDevice(selection) = command {
+            let _ = device_tx.send(selection);
+            continue;
+        }
         process_command(
             command, // pass by value so we can move owned fields
-            &mut midi_recording_state,
             &app_state,
             &audio_state,
             &realtime_tx,
@@ -46,7 +53,6 @@ pub async fn run_command_processor(
 
 fn process_command(
     command: AudioCommand, // by value
-    midi_recording_state: &mut Option<MidiRecordingState>,
     app_state: &Arc<Mutex<AppState>>,
     audio_state: &Arc<AudioState>,
     realtime_tx: &Sender<RealtimeCommand>,
@@ -57,21 +63,33 @@ fn process_command(
     match command {
         AudioCommand::Play => {
             audio_state.playing.store(true, Ordering::Relaxed);
+            let mut state = app_state.lock_sync();
+            let beat = playhead_beat(audio_state);
+            if open_write_passes(&mut state, ui_tx, beat, |_, _, lane| {
+                lane.write_mode == AutomationMode::Write
+            }) {
+                send_graph_snapshot(&state, snapshot_tx);
+            }
         }
         AudioCommand::Stop => {
             audio_state.playing.store(false, Ordering::Relaxed);
             audio_state.recording.store(false, Ordering::Relaxed);
-            if midi_recording_state.is_some() {
-                log::info!("Stopping MIDI recording due to transport stop.");
-                *midi_recording_state = None;
-                send_graph_snapshot(&app_state.lock_sync(), snapshot_tx);
+            audio_state.count_in_target.store(f64::NAN);
+            let mut state = app_state.lock_sync();
+            if close_write_passes(&mut state, playhead_beat(audio_state), |_, _, _| true) {
+                send_graph_snapshot(&state, snapshot_tx);
             }
         }
         AudioCommand::Pause => {
             audio_state.playing.store(false, Ordering::Relaxed);
+            let mut state = app_state.lock_sync();
+            if close_write_passes(&mut state, playhead_beat(audio_state), |_, _, _| true) {
+                send_graph_snapshot(&state, snapshot_tx);
+            }
         }
         AudioCommand::SetPosition(position) => {
             audio_state.set_position(position);
+            let _ = realtime_tx.send_sync(RealtimeCommand::ResetLoopPasses);
         }
         AudioCommand::SetBPM(bpm) => {
             let mut state = app_state.lock_sync();
@@ -117,11 +135,20 @@ fn process_command(
         AudioCommand::UpdateTracks => {
             send_graph_snapshot(&app_state.lock_sync(), snapshot_tx);
         }
+        AudioCommand::SetUndoBatch(batch) => {
+            let _ = ui_tx.send_sync(UIUpdate::UndoBatch(batch));
+        }
         AudioCommand::SetTrackVolume(track_id, volume) => {
             let mut state = app_state.lock_sync();
             if let Some(track) = state.tracks.get_mut(&track_id) {
                 track.volume = volume;
             }
+            record_control_change(
+                &mut state,
+                audio_state,
+                track_id,
+                &AutomationTarget::TrackVolume,
+            );
             let _ = realtime_tx.send_sync(RealtimeCommand::UpdateTrackVolume(track_id, volume));
         }
         AudioCommand::SetTrackPan(track_id, pan) => {
@@ -129,6 +156,12 @@ fn process_command(
             if let Some(track) = state.tracks.get_mut(&track_id) {
                 track.pan = pan;
             }
+            record_control_change(
+                &mut state,
+                audio_state,
+                track_id,
+                &AutomationTarget::TrackPan,
+            );
             let _ = realtime_tx.send_sync(RealtimeCommand::UpdateTrackPan(track_id, pan));
         }
         AudioCommand::SetTrackMute(track_id, mute) => {
@@ -145,6 +178,36 @@ fn process_command(
             }
             let _ = realtime_tx.send_sync(RealtimeCommand::UpdateTrackSolo(track_id, solo));
         }
+        AudioCommand::SoloTrack {
+            track_id,
+            solo,
+            exclusive,
+        } => {
+            let mut state = app_state.lock_sync();
+            let mut changed = Vec::new();
+            if solo && exclusive {
+                for (&id, track) in state.tracks.iter_mut() {
+                    if id != track_id && track.solo && !track.solo_safe {
+                        track.solo = false;
+                        changed.push((id, false));
+                    }
+                }
+            }
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.solo = solo;
+                changed.push((track_id, solo));
+            }
+            for (id, solo) in changed {
+                let _ = realtime_tx.send_sync(RealtimeCommand::UpdateTrackSolo(id, solo));
+            }
+        }
+        AudioCommand::SetTrackSoloSafe(track_id, safe) => {
+            let mut state = app_state.lock_sync();
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.solo_safe = safe;
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
         AudioCommand::ArmForRecording(track_id, armed) => {
             let mut state = app_state.lock_sync();
 
@@ -173,41 +236,71 @@ fn process_command(
             log::info!("FinalizeRecording command received.");
         }
         AudioCommand::StartRecording => {
-            audio_state.playing.store(true, Ordering::Relaxed);
-
-            // Resolve armed MIDI track (read-only)
-            let armed_midi_track_id = {
-                let state = app_state.lock_sync();
-                state
-                    .tracks
-                    .values()
-                    .find(|t| matches!(t.track_type, TrackType::Midi) && t.armed)
-                    .map(|t| t.id)
-            };
-
-            let sr = audio_state.sample_rate.load();
-            let bpm = audio_state.bpm.load();
-            let start_beat = samples_to_beats(audio_state.get_position(), sr, bpm);
-
-            if let Some(track_id) = armed_midi_track_id {
-                insert_recording_clip_if_missing(app_state, track_id, start_beat);
-                *midi_recording_state = Some(MidiRecordingState {
-                    track_id,
-                    active_notes: HashMap::new(),
-                });
+            // Count in from a stop: back the playhead up so recording begins where it was
+            let count_in_bars = audio_state.count_in_bars.load(Ordering::Relaxed);
+            if count_in_bars > 0 && !audio_state.playing.load(Ordering::Relaxed) {
+                let bar_len = app_state.lock_sync().time_signature.bar_len();
+                let target = audio_state.get_position();
+                let pre_roll = crate::time_utils::quick::beats_to_samples(
+                    count_in_bars as f64 * bar_len,
+                    audio_state.sample_rate.load(),
+                    audio_state.bpm.load(),
+                );
+                audio_state.count_in_target.store(target);
+                audio_state.set_position(target - pre_roll);
             }
+            audio_state.playing.store(true, Ordering::Relaxed);
 
             audio_state.recording.store(true, Ordering::Relaxed);
         }
         AudioCommand::StopRecording => {
             audio_state.recording.store(false, Ordering::Relaxed);
-            if midi_recording_state.is_some() {
-                *midi_recording_state = None;
-                send_graph_snapshot(&app_state.lock_sync(), snapshot_tx);
-            }
+            audio_state.count_in_target.store(f64::NAN);
         }
-        AudioCommand::SetMetronome(on) => {
-            audio_state.metronome_enabled.store(on, Ordering::Relaxed);
+        AudioCommand::SetMetronome {
+            enabled,
+            count_in_bars,
+        } => {
+            audio_state
+                .metronome_enabled
+                .store(enabled, Ordering::Relaxed);
+            audio_state
+                .count_in_bars
+                .store(count_in_bars, Ordering::Relaxed);
+        }
+        AudioCommand::SetClickSamples { accent, click } => {
+            let sample_rate = audio_state.sample_rate.load() as f64;
+            let bpm = audio_state.bpm.load();
+            let realtime_tx = realtime_tx.clone();
+            let ui_tx = ui_tx.clone();
+            let decode = move || {
+                let load = |path: Option<std::path::PathBuf>| {
+                    let path = path?;
+                    match crate::audio_import::import_audio_file(&path, bpm) {
+                        Ok(clip) => Some(crate::metronome::resample(
+                            &clip.samples,
+                            clip.sample_rate as f64,
+                            sample_rate,
+                        )),
+                        Err(e) => {
+                            let _ = ui_tx.send_sync(UIUpdate::Warning(format!(
+                                "Failed to load click sample {}: {}",
+                                path.display(),
+                                e
+                            )));
+                            None
+                        }
+                    }
+                };
+                let sounds =
+                    crate::metronome::ClickSounds::new(sample_rate, load(accent), load(click));
+                let _ = realtime_tx.send_sync(RealtimeCommand::SetClickSounds(sounds));
+            };
+            // Decoding and resampling a long sample would hold up the queue
+            #[cfg(not(target_arch = "wasm32"))]
+            crate::runtime::RT.spawn_blocking(decode);
+            #[cfg(target_arch = "wasm32")]
+            decode();
         }
         AudioCommand::SetSendDestination(track_id, index, dest_track_id) => {
             let mut state = app_state.lock_sync();
@@ -224,18 +317,21 @@ fn process_command(
             let data1 = raw_message.message[1];
             let data2 = raw_message.message[2];
 
-            let target_track_id = {
+            let (target_track_id, cc_bound) = {
                 let st = app_state.lock_sync();
-                st.tracks
+                // An armed track takes the input so what is heard is what gets recorded
+                let target = st
+                    .tracks
                     .values()
-                    .find(|t| {
-                        matches!(t.track_type, TrackType::Midi) && t.midi_input_port.is_some()
-                    })
+                    .find(|t| matches!(t.track_type, TrackType::Midi) && t.armed)
                     .map(|t| t.id)
                     .or_else(|| {
                         st.tracks
                             .values()
-                            .find(|t| matches!(t.track_type, TrackType::Midi) && t.armed)
+                            .find(|t| {
+                                matches!(t.track_type, TrackType::Midi)
+                                    && t.midi_input_port.is_some()
+                            })
                             .map(|t| t.id)
                     })
                     .or_else(|| {
@@ -243,80 +339,61 @@ fn process_command(
                             .values()
                             .find(|t| matches!(t.track_type, TrackType::Midi))
                             .map(|t| t.id)
-                    })
+                    });
+                // A CC bound to a lane, or being learned by one, drives the
+                // lane's control instead of reaching the instrument
+                let cc_bound = status & 0xF0 == 0xB0
+                    && st
+                        .tracks
+                        .values()
+                        .flat_map(|t| &t.automation_lanes)
+                        .any(|l| l.learning || l.cc_binding == Some(data1));
+                (target, cc_bound)
             };
 
-            if let Some(track_id) = target_track_id {
+            if let Some(track_id) = target_track_id
+                && !cc_bound
+            {
                 let _ = realtime_tx.send_sync(RealtimeCommand::MidiMessage {
                     track_id,
                     status,
                     data1,
                     data2,
+                    timestamp_us: raw_message.timestamp_us,
                 });
             }
-            if let Some(rec) = midi_recording_state {
-                let channel = status & 0x0F;
-                let message_type = status & 0xF0;
-
-                let current_beat = {
-                    let pos_samples = audio_state.get_position();
-                    let sr = audio_state.sample_rate.load();
-                    let bpm = audio_state.bpm.load();
-                    samples_to_beats(pos_samples, sr, bpm)
-                };
 
-                match message_type {
-                    0x90 if data2 > 0 => {
-                        rec.active_notes
-                            .insert((data1, channel), (current_beat, data2));
-                    }
-                    0x80 | 0x90 => {
-                        if let Some((start_beat, velocity)) =
-                            rec.active_notes.remove(&(data1, channel))
-                        {
-                            let duration = (current_beat - start_beat).max(0.01);
-
-                            // 1) Find the target clip/pattern immutably
-                            let (clip_idx, pid_opt, clip_start) = {
-                                let st = app_state.lock_sync();
-                                let Some(track) = st.tracks.get(&rec.track_id) else {
-                                    return;
-                                };
-                                if let Some((idx, clip)) =
-                                    track.midi_clips.iter().enumerate().find(|(_, c)| {
-                                        start_beat >= c.start_beat
-                                            && start_beat < c.start_beat + c.length_beats
-                                    })
-                                {
-                                    (idx, clip.pattern_id, clip.start_beat)
-                                } else {
-                                    (usize::MAX, None, 0.0)
-                                }
-                            };
-                            if clip_idx == usize::MAX {
-                                return;
-                            }
-
-                            // 2) Prepare the note with a global ID
-                            let note = crate::model::MidiNote {
-                                id: idgen::next(),
-                                pitch: data1,
-                                velocity,
-                                start: (start_beat - clip_start).max(0.0),
-                                duration,
-                            };
-
-                            // 3) Insert into pattern in a short mutable scope
-                            if let Some(pid) = pid_opt {
-                                let mut st = app_state.lock_sync();
-                                if let Some(p) = st.patterns.get_mut(&pid) {
-                                    p.notes.push(note);
-                                }
-                            }
-                        }
+            // The piano roll's step input listens for keys
+            match status & 0xF0 {
+                0x90 => {
+                    let _ = ui_tx.send_sync(UIUpdate::MidiNoteInput(data1, data2));
+                }
+                0x80 => {
+                    let _ = ui_tx.send_sync(UIUpdate::MidiNoteInput(data1, 0));
+                }
+                0xB0 => {
+                    let controls = route_midi_cc(
+                        app_state,
+                        audio_state,
+                        ui_tx,
+                        snapshot_tx,
+                        target_track_id,
+                        data1,
+                        data2,
+                    );
+                    for control in controls {
+                        process_command(
+                            control,
+                            app_state,
+                            audio_state,
+                            realtime_tx,
+                            ui_tx,
+                            snapshot_tx,
+                            midi_input_handler,
+                        );
                     }
-                    _ => {}
                 }
+                _ => {}
             }
         }
         AudioCommand::SetTrackMidiInput(track_id, port_name) => {
@@ -372,6 +449,19 @@ fn process_command(
                 track_id, plugin_id, bypass,
             ));
         }
+        AudioCommand::SetPluginSidechain(track_id, plugin_id, source) => {
+            let mut state = app_state.lock_sync();
+            // A track can't key its own plugins
+            let source = source.filter(|&s| s != track_id && state.tracks.contains_key(&s));
+            if let Some(plugin) = state
+                .tracks
+                .get_mut(&track_id)
+                .and_then(|t| t.plugin_chain.iter_mut().find(|p| p.id == plugin_id))
+            {
+                plugin.sidechain_source = source;
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
         AudioCommand::SetPluginParam(track_id, plugin_id, param_name, value) => {
             let (uri_opt, backend) = {
                 let state = app_state.lock_sync();
@@ -393,6 +483,15 @@ fn process_command(
                         plugin.params.insert(param_name.clone(), v);
                     }
                 }
+                record_control_change(
+                    &mut state,
+                    audio_state,
+                    track_id,
+                    &AutomationTarget::PluginParam {
+                        plugin_id,
+                        param_name: param_name.clone(),
+                    },
+                );
                 drop(state);
 
                 let _ = realtime_tx.send_sync(RealtimeCommand::UpdatePluginParam(
@@ -403,12 +502,16 @@ fn process_command(
                 ));
             }
         }
-        AudioCommand::MovePlugin(track_id, from_idx, to_idx) => {
+        AudioCommand::ReorderPlugin { track_id, from, to } => {
             let mut state = app_state.lock_sync();
             if let Some(track) = state.tracks.get_mut(&track_id) {
-                if from_idx < track.plugin_chain.len() && to_idx < track.plugin_chain.len() {
-                    let plugin = track.plugin_chain.remove(from_idx);
-                    track.plugin_chain.insert(to_idx, plugin);
+                let len = track.plugin_chain.len();
+                if from < len && to < len && from != to {
+                    let plugin = track.plugin_chain.remove(from);
+                    track.plugin_chain.insert(to, plugin);
+                    let order = track.plugin_chain.iter().map(|p| p.id).collect();
+                    let _ =
+                        realtime_tx.send_sync(RealtimeCommand::ReorderPlugins { track_id, order });
                 }
             }
             send_graph_snapshot(&state, snapshot_tx);
@@ -417,10 +520,18 @@ fn process_command(
             use crate::presets::{PluginPreset, save_preset};
             let state = app_state.lock_sync();
 
-            let (uri, backend, params_map) = if let Some(track) = state.tracks.get(&track_id) {
+            let (uri, backend, params_map, plugin_id, saved_state) = if let Some(track) =
+                state.tracks.get(&track_id)
+            {
                 if plugin_idx < track.plugin_chain.len() {
                     let desc = &track.plugin_chain[plugin_idx];
-                    (desc.uri.clone(), desc.backend, desc.params.clone())
+                    (
+                        desc.uri.clone(),
+                        desc.backend,
+                        desc.params.clone(),
+                        desc.id,
+                        desc.state.clone(),
+                    )
                 } else {
                     drop(state);
                     let _ = ui_tx.send_sync(UIUpdate::Warning(format!(
@@ -434,12 +545,17 @@ fn process_command(
                 let _ = ui_tx.send_sync(UIUpdate::Warning(format!("Track {} not found", track_id)));
                 return;
             };
+            drop(state);
+
+            // A fresh blob from the live instance, else the one last saved
+            let plugin_state = save_plugin_state(audio_state, plugin_id).or(saved_state);
 
             let preset = PluginPreset {
                 uri: uri.clone(),
                 backend,
                 name: name.clone(),
                 params: params_map,
+                state: plugin_state,
             };
 
             match save_preset(&preset) {
@@ -465,7 +581,7 @@ fn process_command(
                 let _ = ui_tx.send_sync(UIUpdate::PushUndo(snapshot));
             }
 
-            let (_uri, plugin_id, params_to_update) = {
+            let (uri, plugin_id, params_to_update, preset_state) = {
                 let mut state = app_state.lock_sync();
                 let (uri, plugin_id) = if let Some(track) = state.tracks.get_mut(&track_id) {
                     if plugin_idx < track.plugin_chain.len() {
@@ -500,14 +616,22 @@ fn process_command(
                         for (k, v) in &preset.params {
                             desc.params.insert(k.clone(), *v);
                         }
+                        if preset.state.is_some() {
+                            desc.state = preset.state.clone();
+                        }
                         desc.preset_name = Some(name.clone());
                     }
                 }
 
                 let params_to_update = preset.params.clone();
-                (uri, plugin_id, params_to_update)
+                (uri, plugin_id, params_to_update, preset.state)
             };
 
+            // State first, so the preset's params land on top of it
+            if let Some(blob) = preset_state {
+                load_plugin_state(audio_state, plugin_id, &blob, &uri);
+            }
+
             for (param_name, value) in params_to_update {
                 let _ = realtime_tx.send_sync(RealtimeCommand::UpdatePluginParam(
                     track_id, plugin_id, param_name, value,
@@ -516,6 +640,95 @@ fn process_command(
 
             send_graph_snapshot(&app_state.lock_sync(), snapshot_tx);
         }
+        AudioCommand::AddPluginWithState { track_id, mut desc } => {
+            let plugin_id = idgen::next();
+            desc.id = plugin_id;
+            let (backend, uri, params, blob, bypass) = (
+                desc.backend,
+                desc.uri.clone(),
+                desc.params.clone(),
+                desc.state.clone(),
+                desc.bypass,
+            );
+            {
+                let mut state = app_state.lock_sync();
+                let Some(track) = state.tracks.get_mut(&track_id) else {
+                    return;
+                };
+                track.plugin_chain.push(desc);
+            }
+
+            // The engine hands the blob to its restore worker and applies the
+            // params on top once it has loaded
+            let _ = realtime_tx.send_sync(RealtimeCommand::AddUnifiedPlugin {
+                track_id,
+                plugin_id,
+                backend,
+                uri,
+                state: blob.map(Into::into),
+                params: params.into_iter().collect(),
+            });
+            if bypass {
+                let _ = realtime_tx.send_sync(RealtimeCommand::UpdatePluginBypass(
+                    track_id, plugin_id, true,
+                ));
+            }
+
+            send_graph_snapshot(&app_state.lock_sync(), snapshot_tx);
+        }
+        AudioCommand::SaveTrackTemplate(track_id, name) => {
+            use crate::presets::{TemplateSend, TrackTemplate, save_track_template};
+
+            let mut template = {
+                let state = app_state.lock_sync();
+                let Some(track) = state.tracks.get(&track_id) else {
+                    let _ =
+                        ui_tx.send_sync(UIUpdate::Warning(format!("Track {} not found", track_id)));
+                    return;
+                };
+                TrackTemplate {
+                    name: name.clone(),
+                    track_type: track.track_type,
+                    color: track.color,
+                    sends: track
+                        .sends
+                        .iter()
+                        .filter_map(|s| {
+                            let bus = state.tracks.get(&s.destination_track)?;
+                            Some(TemplateSend {
+                                bus: bus.name.clone(),
+                                amount: s.amount,
+                                pre_fader: s.pre_fader,
+                                muted: s.muted,
+                            })
+                        })
+                        .collect(),
+                    plugins: track.plugin_chain.clone(),
+                }
+            };
+
+            // Fresh blobs from the live plugins, else the ones last saved
+            for plugin in &mut template.plugins {
+                if let Some(blob) = save_plugin_state(audio_state, plugin.id) {
+                    plugin.state = Some(blob);
+                }
+                plugin.id = 0;
+                plugin.sidechain_source = None;
+            }
+
+            match save_track_template(&template) {
+                Ok(_) => {
+                    let _ =
+                        ui_tx.send_sync(UIUpdate::Info(format!("Saved track template '{}'", name)));
+                }
+                Err(e) => {
+                    let _ = ui_tx.send_sync(UIUpdate::Error(format!(
+                        "Failed to save track template '{}': {}",
+                        name, e
+                    )));
+                }
+            }
+        }
         AudioCommand::SetLoopEnabled(enabled) => {
             audio_state.loop_enabled.store(enabled, Ordering::Relaxed);
             {
@@ -534,6 +747,15 @@ fn process_command(
             }
             let _ = realtime_tx.send_sync(RealtimeCommand::SetLoopRegion(start, end));
         }
+        AudioCommand::SetPunchEnabled(enabled) => {
+            audio_state.punch_enabled.store(enabled, Ordering::Relaxed);
+        }
+        AudioCommand::SetPunchRegion(start, end) => {
+            audio_state.punch_in.store(start.max(0.0));
+            audio_state.punch_out.store(end.max(start));
+        }
+        // Handled by run_command_processor
+        AudioCommand::SetAudioDevice(_) => {}
         AudioCommand::AddPluginUnified {
             track_id,
             plugin_idx,
@@ -554,8 +776,11 @@ fn process_command(
                         bypass: false,
                         has_editor: false,
                         params: std::collections::HashMap::new(),
+                        param_ranges: std::collections::HashMap::new(),
                         preset_name: None,
                         custom_name: None,
+                        sidechain_source: None,
+                        state: None,
                     });
                 desc.backend = backend;
                 desc.id = plugin_id;
@@ -583,6 +808,8 @@ fn process_command(
                     plugin_id,
                     backend,
                     uri: uri.clone(),
+                    state: None,
+                    params: Vec::new(),
                 });
 
                 let state = app_state.lock_sync();
@@ -601,6 +828,7 @@ fn process_command(
                 new_pid,
                 MidiPattern {
                     id: new_pid,
+                    name: String::new(),
                     notes: Vec::new(),
                 },
             );
@@ -712,6 +940,7 @@ fn process_command(
                     new_pid,
                     MidiPattern {
                         id: new_pid,
+                        name: String::new(),
                         notes: base_notes,
                     },
                 );
@@ -754,6 +983,29 @@ fn process_command(
             }
             send_graph_snapshot(&state, snapshot_tx);
         }
+        AudioCommand::RippleEdit {
+            track_id,
+            from_beat,
+            delta,
+            exclude,
+            markers,
+        } => {
+            let mut state = app_state.lock_sync();
+            for (id, track) in state.tracks.iter_mut() {
+                if track_id.is_none_or(|t| t == *id) {
+                    track.ripple(from_beat, delta, &exclude);
+                }
+            }
+            if markers {
+                for m in &mut state.markers {
+                    if m.beat >= from_beat {
+                        m.beat = (m.beat + delta).max(0.0);
+                    }
+                }
+                state.markers.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
         AudioCommand::ResizeAudioClip {
             clip_id,
             new_start,
@@ -824,8 +1076,7 @@ fn process_command(
             }
             send_graph_snapshot(&state, snapshot_tx);
         }
-        AudioCommand::AddAutomationPoint(track_id, target, beat, value) => {
-            use crate::model::automation::{AutomationLane, AutomationMode, AutomationPoint};
+        AudioCommand::AddAutomationPoint(track_id, target, beat, value, lane_height) => {
             let mut state = app_state.lock_sync();
             if let Some(track) = state.tracks.get_mut(&track_id) {
                 let lane_idx = if let Some(idx) = track
@@ -839,15 +1090,22 @@ fn process_command(
                         parameter: target.clone(),
                         points: Vec::new(),
                         visible: true,
-                        height: 30.0,
+                        height: lane_height,
                         color: None,
                         write_mode: AutomationMode::Read,
                         read_enabled: true,
+                        write_pass: None,
+                        cc_binding: None,
+                        learning: false,
                     });
                     track.automation_lanes.len() - 1
                 };
                 if let Some(lane) = track.automation_lanes.get_mut(lane_idx) {
-                    lane.points.push(AutomationPoint { beat, value });
+                    lane.points.push(AutomationPoint {
+                        beat,
+                        value,
+                        ..Default::default()
+                    });
                     lane.points
                         .sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
                 }
@@ -876,14 +1134,42 @@ fn process_command(
             if let Some(track) = state.tracks.get_mut(&track_id)
                 && let Some(lane) = track.automation_lanes.get_mut(lane_idx)
             {
+                let shape = lane
+                    .points
+                    .iter()
+                    .find(|p| (p.beat - old_beat).abs() <= 0.001)
+                    .map(|p| (p.curve, p.tension))
+                    .unwrap_or_default();
                 lane.points.retain(|p| (p.beat - old_beat).abs() > 0.001);
                 lane.points.push(AutomationPoint {
                     beat: new_beat,
                     value: new_value,
+                    curve: shape.0,
+                    tension: shape.1,
                 });
             }
             send_graph_snapshot(&state, snapshot_tx);
         }
+        AudioCommand::SetAutomationCurve {
+            track_id,
+            lane_idx,
+            beat,
+            curve,
+            tension,
+        } => {
+            let mut state = app_state.lock_sync();
+            if let Some(track) = state.tracks.get_mut(&track_id)
+                && let Some(lane) = track.automation_lanes.get_mut(lane_idx)
+                && let Some(point) = lane
+                    .points
+                    .iter_mut()
+                    .find(|p| (p.beat - beat).abs() <= 0.001)
+            {
+                point.curve = curve;
+                point.tension = tension.clamp(-1.0, 1.0);
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
         AudioCommand::PreviewNote(track_id, pitch) => {
             let current_position = audio_state.get_position();
             let _ = realtime_tx.send_sync(RealtimeCommand::PreviewNote(
@@ -903,6 +1189,52 @@ fn process_command(
             }
             send_graph_snapshot(&state, snapshot_tx);
         }
+        AudioCommand::SetTrackMonitorGain(track_id, gain_db) => {
+            let mut state = app_state.lock_sync();
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.monitor_gain_db = gain_db;
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
+        AudioCommand::SetTrackInputChannel(track_id, channel) => {
+            let mut state = app_state.lock_sync();
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.input_channel = channel;
+                let _ = ui_tx.send_sync(UIUpdate::PushUndo(state.snapshot()));
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
+        AudioCommand::SetTrackCue(track_id, cue) => {
+            let mut state = app_state.lock_sync();
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.cue = cue;
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
+        AudioCommand::SetTrackPhaseInvert(track_id, invert) => {
+            let mut state = app_state.lock_sync();
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.phase_inverted = invert;
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
+        AudioCommand::SetTrackMono(track_id, mono) => {
+            let mut state = app_state.lock_sync();
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.mono = mono;
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
+        AudioCommand::SetTrackOutputBus(track_id, bus_id) => {
+            let mut state = app_state.lock_sync();
+            let bus_ok = bus_id
+                .is_none_or(|b| crate::track_manager::can_route_to_bus(&state.tracks, track_id, b));
+            if bus_ok && let Some(track) = state.tracks.get_mut(&track_id) {
+                track.output_bus = bus_id;
+                let _ = ui_tx.send_sync(UIUpdate::PushUndo(state.snapshot()));
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
         AudioCommand::AddSend(track_id, dest_track_id, amount) => {
             let mut state = app_state.lock_sync();
             if let Some(t) = state.tracks.get_mut(&track_id) {
@@ -929,11 +1261,12 @@ fn process_command(
         AudioCommand::SetSendAmount(track_id, index, value) => {
             let mut state = app_state.lock_sync();
             if let Some(t) = state.tracks.get_mut(&track_id) {
-                if index < t.sends.len() {
-                    t.sends[index].amount = value;
+                if let Some(send) = t.sends.get_mut(index) {
+                    send.amount = value;
+                    let _ = realtime_tx
+                        .send_sync(RealtimeCommand::UpdateSendAmount(track_id, index, value));
                 }
             }
-            send_graph_snapshot(&state, snapshot_tx);
         }
         AudioCommand::SetSendPreFader(track_id, index, pref) => {
             let mut state = app_state.lock_sync();
@@ -985,6 +1318,7 @@ fn process_command(
                         new_pid,
                         MidiPattern {
                             id: new_pid,
+                            name: String::new(),
                             notes: base_notes,
                         },
                     );
@@ -1126,22 +1460,77 @@ fn process_command(
             if let Some(g) = st.groups.get_mut(&group_id) {
                 g.collapsed = !g.collapsed;
             }
+        }
+        AudioCommand::SetGroupVca(group_id, gain) => {
+            let mut st = app_state.lock_sync();
+            if let Some(g) = st.groups.get_mut(&group_id) {
+                g.vca_gain = gain;
+                let _ =
+                    realtime_tx.send_sync(RealtimeCommand::UpdateGroupVca(group_id, g.vca().gain));
+            }
+        }
+        AudioCommand::SetGroupMute(group_id, mute) => {
+            let mut st = app_state.lock_sync();
+            if let Some(g) = st.groups.get_mut(&group_id) {
+                g.muted = mute;
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::SetGroupSolo(group_id, solo) => {
+            let mut st = app_state.lock_sync();
+            if let Some(g) = st.groups.get_mut(&group_id) {
+                g.solo = solo;
+            }
             send_graph_snapshot(&st, snapshot_tx);
         }
         AudioCommand::ToggleClipLoop { clip_id, enabled } => {
             let mut state = app_state.lock_sync();
             if let Some((track, loc)) = state.find_clip_mut(clip_id) {
-                if let ClipLocation::Midi(idx) = loc {
-                    if let Some(clip) = track.midi_clips.get_mut(idx) {
-                        clip.loop_enabled = enabled;
-                        if clip.content_len_beats <= 0.0 {
-                            clip.content_len_beats = clip.length_beats.max(0.000001);
+                match loc {
+                    ClipLocation::Midi(idx) => {
+                        if let Some(clip) = track.midi_clips.get_mut(idx) {
+                            clip.loop_enabled = enabled;
+                            if clip.content_len_beats <= 0.0 {
+                                clip.content_len_beats = clip.length_beats.max(0.000001);
+                            }
+                        }
+                    }
+                    ClipLocation::Audio(idx) => {
+                        if let Some(clip) = track.audio_clips.get_mut(idx) {
+                            clip.loop_enabled = enabled;
+                        }
+                    }
+                }
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
+        AudioCommand::SetClipLoopMode { clip_id, mode } => {
+            let mut state = app_state.lock_sync();
+            if let Some((track, loc)) = state.find_clip_mut(clip_id) {
+                match loc {
+                    ClipLocation::Midi(idx) => {
+                        if let Some(clip) = track.midi_clips.get_mut(idx) {
+                            clip.loop_mode = mode;
+                        }
+                    }
+                    ClipLocation::Audio(idx) => {
+                        if let Some(clip) = track.audio_clips.get_mut(idx) {
+                            clip.loop_mode = mode;
                         }
                     }
                 }
             }
             send_graph_snapshot(&state, snapshot_tx);
         }
+        AudioCommand::SetClipLoopCrossfade { clip_id, beats } => {
+            let mut state = app_state.lock_sync();
+            if let Some((track, ClipLocation::Audio(idx))) = state.find_clip_mut(clip_id)
+                && let Some(clip) = track.audio_clips.get_mut(idx)
+            {
+                clip.loop_crossfade = beats.max(0.0);
+            }
+            send_graph_snapshot(&state, snapshot_tx);
+        }
         AudioCommand::MakeClipAlias { clip_id } => {
             use crate::model::clip::MidiPattern;
 
@@ -1170,9 +1559,14 @@ fn process_command(
 
             let new_pid = idgen::next();
             if let Some(notes) = notes_to_move {
-                state
-                    .patterns
-                    .insert(new_pid, MidiPattern { id: new_pid, notes });
+                state.patterns.insert(
+                    new_pid,
+                    MidiPattern {
+                        id: new_pid,
+                        name: String::new(),
+                        notes,
+                    },
+                );
 
                 if let Some((track, ClipLocation::Midi(idx))) = state.find_clip_mut(clip_id) {
                     if let Some(clip) = track.midi_clips.get_mut(idx) {
@@ -1216,28 +1610,114 @@ fn process_command(
             }
             send_graph_snapshot(&state, snapshot_tx);
         }
-        AudioCommand::DuplicateMidiClipAsAlias { clip_id } => {
-            let mut state = app_state.lock_sync();
-
-            let _ = ui_tx.send_sync(UIUpdate::PushUndo(state.snapshot()));
-
-            let (src_clip, src_pid, track_id) = {
-                let (track, loc) = match state.find_clip(clip_id) {
-                    Some(t) => t,
-                    None => return,
-                };
-                let clip = match loc {
-                    ClipLocation::Midi(idx) => track.midi_clips.get(idx),
-                    _ => None,
-                };
-                (
-                    clip.cloned(),
-                    clip.and_then(|c| c.pattern_id),
-                    Some(track.id),
-                )
+        AudioCommand::PreviewQuantize {
+            clip_id,
+            grid,
+            strength,
+            swing,
+        } => {
+            let st = app_state.lock_sync();
+            let Some((track, ClipLocation::Midi(idx))) = st.find_clip(clip_id) else {
+                return;
             };
-
-            let src_clip = match src_clip {
+            let track_id = track.id;
+            // The live graph, with only this clip's quantize overridden
+            let mut snapshot = graph_snapshot(&st);
+            if let Some(clip) = snapshot
+                .tracks
+                .iter_mut()
+                .find(|t| t.track_id == track_id)
+                .and_then(|t| t.midi_clips.get_mut(idx))
+            {
+                clip.quantize_enabled = true;
+                clip.quantize_grid = grid.max(0.0);
+                clip.quantize_strength = strength.clamp(0.0, 1.0);
+                clip.swing = swing;
+            }
+            let _ = snapshot_tx.send_sync(snapshot);
+        }
+        AudioCommand::EndQuantizePreview => {
+            let st = app_state.lock_sync();
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::SetActiveTake {
+            track_id,
+            region,
+            take_id,
+        } => {
+            let mut st = app_state.lock_sync();
+            if let Some(track) = st.tracks.get_mut(&track_id)
+                && track.set_active_take(region, take_id)
+            {
+                st.rebuild_clip_index();
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::ExtractGroove { clip_id, grid } => {
+            let mut st = app_state.lock_sync();
+            let Some((track, ClipLocation::Midi(idx))) = st.find_clip(clip_id) else {
+                return;
+            };
+            let clip = &track.midi_clips[idx];
+            let notes = clip
+                .pattern_id
+                .and_then(|pid| st.patterns.get(&pid))
+                .map_or(&clip.notes, |p| &p.notes);
+            let groove = GrooveTemplate::extract(
+                idgen::next(),
+                format!("Groove from {}", clip.name),
+                notes,
+                grid,
+                st.time_signature.bar_len(),
+                clip.start_beat + clip.content_offset_beats,
+            );
+            st.grooves.push(groove);
+        }
+        AudioCommand::ApplyGroove {
+            clip_id,
+            groove_id,
+            strength,
+        } => {
+            let (groove, origin) = {
+                let st = app_state.lock_sync();
+                let Some(groove) = st.grooves.iter().find(|g| g.id == groove_id).cloned() else {
+                    return;
+                };
+                let Some((track, ClipLocation::Midi(idx))) = st.find_clip(clip_id) else {
+                    return;
+                };
+                let clip = &track.midi_clips[idx];
+                (groove, clip.start_beat + clip.content_offset_beats)
+            };
+            let content_len = clip_content_len(app_state, clip_id);
+            with_pattern_mut(app_state, clip_id, |pat, _len| {
+                groove.apply(&mut pat.notes, strength, content_len, origin);
+            });
+            let st = app_state.lock_sync();
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::DuplicateMidiClipAsAlias { clip_id } => {
+            let mut state = app_state.lock_sync();
+
+            let _ = ui_tx.send_sync(UIUpdate::PushUndo(state.snapshot()));
+
+            let (src_clip, src_pid, track_id) = {
+                let (track, loc) = match state.find_clip(clip_id) {
+                    Some(t) => t,
+                    None => return,
+                };
+                let clip = match loc {
+                    ClipLocation::Midi(idx) => track.midi_clips.get(idx),
+                    _ => None,
+                };
+                (
+                    clip.cloned(),
+                    clip.and_then(|c| c.pattern_id),
+                    Some(track.id),
+                )
+            };
+
+            let src_clip = match src_clip {
                 Some(c) => c,
                 None => return,
             };
@@ -1262,9 +1742,14 @@ fn process_command(
                         }
                     }
 
-                    state
-                        .patterns
-                        .insert(new_pid, MidiPattern { id: new_pid, notes });
+                    state.patterns.insert(
+                        new_pid,
+                        MidiPattern {
+                            id: new_pid,
+                            name: String::new(),
+                            notes,
+                        },
+                    );
 
                     if let Some((track, loc)) = state.find_clip_mut(clip_id) {
                         if let ClipLocation::Midi(idx) = loc {
@@ -1305,11 +1790,25 @@ fn process_command(
             new_offset,
         } => {
             let mut state = app_state.lock_sync();
+            let bpm = state.bpm;
             if let Some((track, loc)) = state.find_clip_mut(clip_id) {
-                if let ClipLocation::Midi(idx) = loc {
-                    if let Some(clip) = track.midi_clips.get_mut(idx) {
-                        let len = clip.content_len_beats.max(0.000001);
-                        clip.content_offset_beats = ((new_offset % len) + len) % len;
+                match loc {
+                    ClipLocation::Midi(idx) => {
+                        if let Some(clip) = track.midi_clips.get_mut(idx) {
+                            let len = clip.content_len_beats.max(0.000001);
+                            clip.content_offset_beats = ((new_offset % len) + len) % len;
+                        }
+                    }
+                    ClipLocation::Audio(idx) => {
+                        // Audio offset is the read start into the source
+                        if let Some(clip) = track.audio_clips.get_mut(idx) {
+                            let src_len = clip.source_length_beats(bpm);
+                            clip.offset_beats = if clip.loop_enabled && src_len > 0.0 {
+                                new_offset.rem_euclid(src_len)
+                            } else {
+                                new_offset.clamp(0.0, src_len.max(0.0))
+                            };
+                        }
                     }
                 }
             }
@@ -1403,6 +1902,25 @@ fn process_command(
             let st = app_state.lock_sync();
             send_graph_snapshot(&st, snapshot_tx);
         }
+        AudioCommand::MoveSelectedNotes {
+            clip_id,
+            note_ids,
+            delta_beats,
+            delta_semitones,
+        } => {
+            with_pattern_mut(app_state, clip_id, |pat, clip_len| {
+                for note in &mut pat.notes {
+                    if note_ids.contains(&note.id) {
+                        let new_start = (note.start + delta_beats).max(0.0);
+                        let max_start = (clip_len - note.duration).max(0.0);
+                        note.start = new_start.min(max_start);
+                        note.pitch = (note.pitch as i32 + delta_semitones).clamp(0, 127) as u8;
+                    }
+                }
+            });
+            let st = app_state.lock_sync();
+            send_graph_snapshot(&st, snapshot_tx);
+        }
         AudioCommand::QuantizeSelectedNotes {
             clip_id,
             note_ids,
@@ -1432,8 +1950,10 @@ fn process_command(
         AudioCommand::HumanizeSelectedNotes {
             clip_id,
             note_ids,
-            amount,
+            timing_beats,
+            velocity,
         } => {
+            let content_len = clip_content_len(app_state, clip_id);
             with_pattern_mut(app_state, clip_id, |pat, _len| {
                 let mut sub: Vec<_> = pat
                     .notes
@@ -1441,7 +1961,7 @@ fn process_command(
                     .filter(|n| note_ids.contains(&n.id))
                     .cloned()
                     .collect();
-                EditProcessor::humanize_notes(&mut sub, amount);
+                EditProcessor::humanize_notes(&mut sub, timing_beats, velocity, content_len);
                 let mut sub_iter = sub.into_iter();
                 for note in &mut pat.notes {
                     if note_ids.contains(&note.id) {
@@ -1454,6 +1974,55 @@ fn process_command(
             let st = app_state.lock_sync();
             send_graph_snapshot(&st, snapshot_tx);
         }
+        AudioCommand::LegatoSelectedNotes { clip_id, note_ids } => {
+            let content_len = clip_content_len(app_state, clip_id);
+            with_pattern_mut(app_state, clip_id, |pat, _len| {
+                EditProcessor::legato_notes(&mut pat.notes, &note_ids, content_len);
+            });
+            let st = app_state.lock_sync();
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::ScaleSelectedNoteLengths {
+            clip_id,
+            note_ids,
+            factor,
+        } => {
+            let content_len = clip_content_len(app_state, clip_id);
+            with_pattern_mut(app_state, clip_id, |pat, _len| {
+                let mut sub: Vec<_> = pat
+                    .notes
+                    .iter()
+                    .filter(|n| note_ids.contains(&n.id))
+                    .cloned()
+                    .collect();
+                EditProcessor::scale_note_lengths(&mut sub, factor, content_len);
+                let mut sub_iter = sub.into_iter();
+                for note in &mut pat.notes {
+                    if note_ids.contains(&note.id) {
+                        if let Some(scaled) = sub_iter.next() {
+                            *note = scaled;
+                        }
+                    }
+                }
+            });
+            let st = app_state.lock_sync();
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::NormalizeClipVelocities {
+            clip_id,
+            target,
+            flatten,
+        } => {
+            with_pattern_mut(app_state, clip_id, |pat, _len| {
+                if flatten {
+                    MidiVelocity::flatten(&mut pat.notes, target);
+                } else {
+                    MidiVelocity::normalize(&mut pat.notes, target);
+                }
+            });
+            let st = app_state.lock_sync();
+            send_graph_snapshot(&st, snapshot_tx);
+        }
         AudioCommand::ExportAudio(config) => {
             let app_state_clone = app_state.lock_sync().clone();
             let audio_state_clone = audio_state.clone();
@@ -1466,6 +2035,10 @@ fn process_command(
                 ui_tx_clone,
             );
         }
+        AudioCommand::ExportAudioBatch(configs) => {
+            let app_state_clone = app_state.lock_sync().clone();
+            AudioExporter::export_all(app_state_clone, audio_state.clone(), configs, ui_tx.clone());
+        }
         AudioCommand::RebuildAllRtChains => {
             let state = app_state.lock_sync();
             let track_snapshots = crate::audio_snapshot::build_track_snapshots(&state);
@@ -1715,6 +2288,15 @@ fn process_command(
             }
             send_graph_snapshot(&st, snapshot_tx);
         }
+        AudioCommand::SetAudioClipPitch(clip_id, semitones) => {
+            let mut st = app_state.lock_sync();
+            if let Some((track, ClipLocation::Audio(idx))) = st.find_clip_mut(clip_id)
+                && let Some(ac) = track.audio_clips.get_mut(idx)
+            {
+                ac.pitch_semitones = semitones.clamp(-24.0, 24.0);
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
         AudioCommand::SetAudioClipFadeIn(clip_id, dur) => {
             let mut st = app_state.lock_sync();
             if let Some((track, loc)) = st.find_clip_mut(clip_id) {
@@ -1737,6 +2319,69 @@ fn process_command(
             }
             send_graph_snapshot(&st, snapshot_tx);
         }
+        AudioCommand::CrossfadeOverlaps { clip_id } => {
+            let mut st = app_state.lock_sync();
+            if let Some((track, ClipLocation::Audio(_))) = st.find_clip_mut(clip_id) {
+                track.crossfade_overlaps(clip_id, idgen::next);
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::UpdateCrossfade {
+            track_id,
+            crossfade_id,
+            center,
+            length,
+            curve,
+        } => {
+            let mut st = app_state.lock_sync();
+            if let Some(track) = st.tracks.get_mut(&track_id) {
+                let overlap = track
+                    .crossfades
+                    .iter()
+                    .find(|xf| xf.id == crossfade_id)
+                    .and_then(|xf| {
+                        let find = |id| track.audio_clips.iter().find(|c| c.id == id);
+                        crate::model::clip::clip_overlap(find(xf.out_clip)?, find(xf.in_clip)?)
+                    });
+                if let Some((lo, hi)) = overlap
+                    && let Some(xf) = track.crossfades.iter_mut().find(|xf| xf.id == crossfade_id)
+                {
+                    let center = center.clamp(lo, hi);
+                    let max_len = 2.0 * (center - lo).min(hi - center);
+                    xf.center = center;
+                    xf.length = length.clamp(0.0, max_len);
+                    xf.curve = curve;
+                }
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::RemoveCrossfade {
+            track_id,
+            crossfade_id,
+        } => {
+            let mut st = app_state.lock_sync();
+            if let Some(track) = st.tracks.get_mut(&track_id) {
+                track.crossfades.retain(|xf| xf.id != crossfade_id);
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::SetAudioClipFadeCurve {
+            clip_id,
+            fade_in,
+            curve,
+        } => {
+            let mut st = app_state.lock_sync();
+            if let Some((track, ClipLocation::Audio(idx))) = st.find_clip_mut(clip_id)
+                && let Some(ac) = track.audio_clips.get_mut(idx)
+            {
+                if fade_in {
+                    ac.fade_in_curve = curve;
+                } else {
+                    ac.fade_out_curve = curve;
+                }
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
         AudioCommand::SetAudioClipWarpMode(clip_id, warp_mode) => {
             let mut st = app_state.lock_sync();
             if let Some((track, loc)) = st.find_clip_mut(clip_id) {
@@ -1751,6 +2396,33 @@ fn process_command(
             }
             send_graph_snapshot(&st, snapshot_tx);
         }
+        AudioCommand::WarpAudioClipToTempo(clip_id, original_bpm) => {
+            if !(original_bpm.is_finite() && original_bpm > 0.0) {
+                return;
+            }
+            let mut st = app_state.lock_sync();
+            let bpm = st.bpm;
+            if let Some((track, ClipLocation::Audio(idx))) = st.find_clip_mut(clip_id)
+                && let Some(ac) = track.audio_clips.get_mut(idx)
+            {
+                // Keep the same stretch of source in view at its new musical length
+                let before = ac.source_length_beats(bpm);
+                ac.warp_mode = true;
+                ac.original_bpm = original_bpm;
+                let after = ac.source_length_beats(bpm);
+                if before > 0.0 {
+                    let ratio = after / before;
+                    if !ac.loop_enabled {
+                        ac.length_beats *= ratio;
+                    }
+                    ac.offset_beats *= ratio;
+                    for fade in [&mut ac.fade_in, &mut ac.fade_out].into_iter().flatten() {
+                        *fade *= ratio;
+                    }
+                }
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
         AudioCommand::CreateMidiClipWithData { track_id, mut clip } => {
             let mut st = app_state.lock_sync();
 
@@ -1881,6 +2553,7 @@ fn process_command(
                     left_pid,
                     crate::model::clip::MidiPattern {
                         id: left_pid,
+                        name: String::new(),
                         notes: left_notes,
                     },
                 );
@@ -1888,6 +2561,7 @@ fn process_command(
                     right_pid,
                     crate::model::clip::MidiPattern {
                         id: right_pid,
+                        name: String::new(),
                         notes: right_notes,
                     },
                 );
@@ -1980,12 +2654,176 @@ fn process_command(
             send_graph_snapshot(&st, snapshot_tx);
         }
         AudioCommand::FreezeTrack(track_id) => {
-            let mut st = app_state.lock_sync();
-            if let Some(t) = st.tracks.get_mut(&track_id) {
-                t.frozen = true;
-                t.frozen_buffer = None;
+            let app_state = app_state.clone();
+            let audio_state = audio_state.clone();
+            let ui_tx = ui_tx.clone();
+            let snapshot_tx = snapshot_tx.clone();
+            let freeze = move || {
+                let st_clone = app_state.lock_sync().clone();
+                match crate::audio_export::render_track_freeze(&st_clone, &audio_state, track_id) {
+                    Ok((buffer, sample_rate)) => {
+                        let mut st = app_state.lock_sync();
+                        if let Some(t) = st.tracks.get_mut(&track_id) {
+                            t.frozen = true;
+                            t.frozen_buffer = Some(Arc::new(buffer));
+                            t.frozen_sample_rate = sample_rate;
+                        }
+                        send_graph_snapshot(&st, &snapshot_tx);
+                    }
+                    Err(e) => {
+                        let _ = ui_tx.send_sync(UIUpdate::Error(format!(
+                            "Failed to freeze track {track_id}: {e}"
+                        )));
+                    }
+                }
+            };
+            // Rendering can take a while with heavy plugins; keep commands flowing
+            #[cfg(not(target_arch = "wasm32"))]
+            crate::runtime::RT.spawn_blocking(freeze);
+            #[cfg(target_arch = "wasm32")]
+            freeze();
+        }
+        AudioCommand::BounceRange {
+            track_id,
+            start_beat,
+            end_beat,
+        } => {
+            // Cut the clips at the range edges, so only what lies inside is
+            // bounced and muted
+            for position in [start_beat, end_beat] {
+                let splits = app_state
+                    .lock_sync()
+                    .tracks
+                    .get(&track_id)
+                    .map(|t| clips_split_at(t, position))
+                    .unwrap_or_default();
+                for split in splits {
+                    process_command(
+                        split,
+                        app_state,
+                        audio_state,
+                        realtime_tx,
+                        ui_tx,
+                        snapshot_tx,
+                        midi_input_handler,
+                    );
+                }
             }
-            send_graph_snapshot(&st, snapshot_tx);
+
+            let app_state = app_state.clone();
+            let audio_state = audio_state.clone();
+            let ui_tx = ui_tx.clone();
+            let snapshot_tx = snapshot_tx.clone();
+            let bounce = move || {
+                let mut st_clone = app_state.lock_sync().clone();
+                let Some(source) = st_clone.tracks.get_mut(&track_id) else {
+                    return;
+                };
+                let sources = clips_within(source, start_beat, end_beat);
+                if sources.is_empty() {
+                    let _ = ui_tx.send_sync(UIUpdate::Warning(
+                        "There are no clips to bounce in that range.".to_string(),
+                    ));
+                    return;
+                }
+                // Render only what is being bounced; the rest stays live on the track
+                source.audio_clips.retain(|c| sources.contains(&c.id));
+                source.midi_clips.retain(|c| sources.contains(&c.id));
+
+                let rendered = crate::audio_export::render_track_range(
+                    &st_clone,
+                    &audio_state,
+                    track_id,
+                    start_beat,
+                    end_beat,
+                );
+                let (pcm, sample_rate) = match rendered {
+                    Ok(r) => r,
+                    Err(e) => {
+                        let _ = ui_tx.send_sync(UIUpdate::Error(format!("Bounce failed: {e}")));
+                        return;
+                    }
+                };
+
+                let (samples, samples_right): (Vec<f32>, Vec<f32>) =
+                    pcm.chunks_exact(2).map(|f| (f[0], f[1])).unzip();
+                let clip = AudioClip {
+                    id: idgen::next(),
+                    name: "Bounce".to_string(),
+                    start_beat,
+                    length_beats: end_beat - start_beat,
+                    samples,
+                    samples_right,
+                    sample_rate,
+                    ..Default::default()
+                };
+
+                let mut st = app_state.lock_sync();
+                let Some(source) = st.tracks.get_mut(&track_id) else {
+                    return;
+                };
+                // The originals and the chain stay for later tweaks, muted so
+                // the bounce isn't heard twice
+                for clip in source
+                    .audio_clips
+                    .iter_mut()
+                    .filter(|c| sources.contains(&c.id))
+                {
+                    clip.muted = true;
+                }
+                for clip in source
+                    .midi_clips
+                    .iter_mut()
+                    .filter(|c| sources.contains(&c.id))
+                {
+                    clip.muted = true;
+                }
+
+                // The bounce gets the source's strip, minus its plugins
+                let source = &st.tracks[&track_id];
+                let mut bounce_track = Track {
+                    name: format!("{} Bounce", source.name),
+                    track_type: TrackType::Audio,
+                    volume: source.volume,
+                    pan: source.pan,
+                    phase_inverted: source.phase_inverted,
+                    mono: source.mono,
+                    output_bus: source.output_bus,
+                    sends: source.sends.clone(),
+                    group_id: source.group_id,
+                    color: source.color,
+                    automation_lanes: source
+                        .automation_lanes
+                        .iter()
+                        .filter(|l| {
+                            matches!(
+                                l.parameter,
+                                AutomationTarget::TrackVolume
+                                    | AutomationTarget::TrackPan
+                                    | AutomationTarget::TrackSend(_)
+                            )
+                        })
+                        .cloned()
+                        .collect(),
+                    audio_clips: vec![clip],
+                    ..Default::default()
+                };
+                bounce_track.id = st.fresh_id();
+                let pos = st
+                    .track_order
+                    .iter()
+                    .position(|&id| id == track_id)
+                    .map_or(st.track_order.len(), |i| i + 1);
+                st.track_order.insert(pos, bounce_track.id);
+                st.tracks.insert(bounce_track.id, bounce_track);
+                st.ensure_ids();
+                st.rebuild_clip_index();
+                send_graph_snapshot(&st, &snapshot_tx);
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            crate::runtime::RT.spawn_blocking(bounce);
+            #[cfg(target_arch = "wasm32")]
+            bounce();
         }
         AudioCommand::UnfreezeTrack(track_id) => {
             let mut st = app_state.lock_sync();
@@ -1997,13 +2835,115 @@ fn process_command(
         }
         AudioCommand::SetAutomationMode(track_id, lane_idx, automation_mode) => {
             let mut st = app_state.lock_sync();
+            let beat = playhead_beat(audio_state);
+            // Leaving a writing mode ends its pass where the playhead is
+            let this_lane = |id: u64, idx: usize| id == track_id && idx == lane_idx;
+            close_write_passes(&mut st, beat, |id, idx, lane| {
+                this_lane(id, idx) && lane.write_mode != automation_mode
+            });
             if let Some(t) = st.tracks.get_mut(&track_id) {
                 if let Some(lane) = t.automation_lanes.get_mut(lane_idx) {
                     lane.write_mode = automation_mode;
                 }
             }
+            // Switching to Write mid-playback starts writing straight away
+            if automation_mode == AutomationMode::Write
+                && audio_state.playing.load(Ordering::Relaxed)
+            {
+                open_write_passes(&mut st, ui_tx, beat, |id, idx, _| this_lane(id, idx));
+            }
+            send_graph_snapshot(&st, snapshot_tx);
+        }
+        AudioCommand::PasteAutomation {
+            track_id,
+            lane_idx,
+            mut points,
+            at_beat,
+        } => {
+            if points.is_empty() {
+                return;
+            }
+            let first = points.iter().map(|p| p.beat).fold(f64::INFINITY, f64::min);
+            let last = points
+                .iter()
+                .map(|p| p.beat)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let (from, to) = (at_beat, at_beat + (last - first));
+
+            let mut st = app_state.lock_sync();
+            if let Some(track) = st.tracks.get_mut(&track_id)
+                && let Some(lane) = track.automation_lanes.get_mut(lane_idx)
+            {
+                lane.points
+                    .retain(|p| p.beat < from - 0.001 || p.beat > to + 0.001);
+                for p in &mut points {
+                    p.beat = at_beat + (p.beat - first);
+                }
+                lane.points.extend(points);
+                lane.points
+                    .sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
+            }
             send_graph_snapshot(&st, snapshot_tx);
         }
+        AudioCommand::MidiLearnAutomation(track_id, lane_idx, learn) => {
+            let mut st = app_state.lock_sync();
+            for lane in st
+                .tracks
+                .values_mut()
+                .flat_map(|t| t.automation_lanes.iter_mut())
+            {
+                lane.learning = false;
+            }
+            if let Some(lane) = st
+                .tracks
+                .get_mut(&track_id)
+                .and_then(|t| t.automation_lanes.get_mut(lane_idx))
+            {
+                lane.learning = learn;
+            }
+        }
+        AudioCommand::SetAutomationCc(track_id, lane_idx, cc) => {
+            let mut st = app_state.lock_sync();
+            if let Some(lane) = st
+                .tracks
+                .get_mut(&track_id)
+                .and_then(|t| t.automation_lanes.get_mut(lane_idx))
+            {
+                lane.cc_binding = cc;
+                lane.learning = false;
+            }
+        }
+        AudioCommand::TouchAutomation {
+            track_id,
+            target,
+            touched,
+        } => {
+            if !audio_state.playing.load(Ordering::Relaxed) {
+                return;
+            }
+            let mut st = app_state.lock_sync();
+            let beat = playhead_beat(audio_state);
+            let changed = if touched {
+                open_write_passes(&mut st, ui_tx, beat, |id, _, lane| {
+                    id == track_id
+                        && lane.parameter == target
+                        && matches!(
+                            lane.write_mode,
+                            AutomationMode::Touch | AutomationMode::Latch
+                        )
+                })
+            } else {
+                // Latch keeps writing until the transport stops
+                close_write_passes(&mut st, beat, |id, _, lane| {
+                    id == track_id
+                        && lane.parameter == target
+                        && lane.write_mode == AutomationMode::Touch
+                })
+            };
+            if changed {
+                send_graph_snapshot(&st, snapshot_tx);
+            }
+        }
         AudioCommand::ClearAutomationLane(track_id, lane_idx) => {
             let mut st = app_state.lock_sync();
             if let Some(t) = st.tracks.get_mut(&track_id) {
@@ -2022,6 +2962,26 @@ fn process_command(
             }
             send_graph_snapshot(&st, snapshot_tx);
         }
+        AudioCommand::SetAutomationLaneVisible(track_id, lane_idx, visible) => {
+            let mut st = app_state.lock_sync();
+            if let Some(lane) = st
+                .tracks
+                .get_mut(&track_id)
+                .and_then(|t| t.automation_lanes.get_mut(lane_idx))
+            {
+                lane.visible = visible;
+            }
+        }
+        AudioCommand::SetAllAutomationLanesVisible(visible) => {
+            let mut st = app_state.lock_sync();
+            for lane in st
+                .tracks
+                .values_mut()
+                .flat_map(|t| t.automation_lanes.iter_mut())
+            {
+                lane.visible = visible;
+            }
+        }
         AudioCommand::PunchOutAudioClip {
             clip_id,
             start_beat,
@@ -2077,6 +3037,9 @@ fn process_command(
         AudioCommand::OpenPluginEditor(track_id, plugin_id) => {
             let _ = realtime_tx.send_sync(RealtimeCommand::OpenPluginEditor(track_id, plugin_id));
         }
+        AudioCommand::CapturePluginStates => {
+            capture_plugin_states(app_state, audio_state);
+        }
         AudioCommand::PunchOutMidiClip {
             clip_id,
             start_beat,
@@ -2129,59 +3092,323 @@ fn process_command(
 }
 
 pub fn send_graph_snapshot(state: &AppState, snapshot_tx: &Sender<AudioGraphSnapshot>) {
-    let snapshot = AudioGraphSnapshot {
+    let _ = snapshot_tx.send_sync(graph_snapshot(state));
+}
+
+fn graph_snapshot(state: &AppState) -> AudioGraphSnapshot {
+    AudioGraphSnapshot {
         tracks: crate::audio_snapshot::build_track_snapshots(state),
         track_order: state.track_order.clone(),
+        groups: crate::audio_snapshot::build_group_snapshots(state),
+        time_signature: state.time_signature,
+    }
+}
+
+/// Splits for the unmuted clips of `track` that straddle `position`
+fn clips_split_at(track: &Track, position: f64) -> Vec<AudioCommand> {
+    let straddles = |start: f64, length: f64| start < position && position < start + length;
+    let audio = track
+        .audio_clips
+        .iter()
+        .filter(|c| !c.muted && straddles(c.start_beat, c.length_beats))
+        .map(|c| AudioCommand::SplitAudioClip {
+            clip_id: c.id,
+            position,
+        });
+    let midi = track
+        .midi_clips
+        .iter()
+        .filter(|c| !c.muted && straddles(c.start_beat, c.length_beats))
+        .map(|c| AudioCommand::SplitMidiClip {
+            clip_id: c.id,
+            position,
+        });
+    audio.chain(midi).collect()
+}
+
+/// Unmuted clips of `track` that lie inside `start..end`
+fn clips_within(track: &Track, start: f64, end: f64) -> std::collections::HashSet<u64> {
+    // Split edges can land a rounding error off the range
+    const EPS: f64 = 1e-9;
+    let inside = |clip_start: f64, length: f64| {
+        clip_start >= start - EPS && clip_start + length <= end + EPS && length > 0.0
     };
+    let audio = track
+        .audio_clips
+        .iter()
+        .filter(|c| !c.muted && inside(c.start_beat, c.length_beats))
+        .map(|c| c.id);
+    let midi = track
+        .midi_clips
+        .iter()
+        .filter(|c| !c.muted && inside(c.start_beat, c.length_beats))
+        .map(|c| c.id);
+    audio.chain(midi).collect()
+}
+
+/// Saves every live plugin's state blob onto its descriptor, then bumps
+/// `AudioState::plugin_states_epoch`. Runs here rather than in the engine
+/// because plugins may block while saving. Plugins without a live instance
+/// keep the blob from the last load or capture.
+fn capture_plugin_states(app_state: &Arc<Mutex<AppState>>, audio_state: &AudioState) {
+    let plugin_ids: Vec<u64> = app_state
+        .lock_sync()
+        .tracks
+        .values()
+        .flat_map(|t| t.plugin_chain.iter().map(|p| p.id))
+        .collect();
 
-    let _ = snapshot_tx.send_sync(snapshot);
+    let mut blobs: std::collections::HashMap<u64, Vec<u8>> = plugin_ids
+        .into_iter()
+        .filter_map(|id| Some((id, save_plugin_state(audio_state, id)?)))
+        .collect();
+
+    {
+        let mut state = app_state.lock_sync();
+        for track in state.tracks.values_mut() {
+            for plugin in &mut track.plugin_chain {
+                if let Some(blob) = blobs.remove(&plugin.id) {
+                    plugin.state = Some(blob);
+                }
+            }
+        }
+    }
+    audio_state
+        .plugin_states_epoch
+        .fetch_add(1, Ordering::Release);
 }
 
-// Create a recording MIDI clip at start_beat if none spans that beat.
-fn insert_recording_clip_if_missing(
-    app_state: &Arc<Mutex<AppState>>,
-    track_id: u64,
-    start_beat: f64,
-) {
-    let need_insert = {
-        let st = app_state.lock_sync();
-        match st.tracks.get(&track_id) {
-            Some(t) => !t
-                .midi_clips
+fn plugin_state_handle(
+    audio_state: &AudioState,
+    plugin_id: u64,
+) -> Option<Arc<dyn PluginStateHandle>> {
+    // Cloned out so no map shard stays locked while the plugin works
+    audio_state
+        .plugin_state_handles
+        .get(&plugin_id)
+        .map(|h| Arc::clone(h.value()))
+}
+
+/// State blob of a live plugin, or None if it has no instance or can't save
+fn save_plugin_state(audio_state: &AudioState, plugin_id: u64) -> Option<Vec<u8>> {
+    plugin_state_handle(audio_state, plugin_id)?.save()
+}
+
+/// Loads a blob into a live plugin, waiting until it has been applied
+fn load_plugin_state(audio_state: &AudioState, plugin_id: u64, state: &[u8], uri: &str) {
+    if let Some(handle) = plugin_state_handle(audio_state, plugin_id) {
+        crate::plugin_state::load(handle.as_ref(), state, uri);
+    }
+}
+
+/// Playhead in beats, for stamping live control changes
+fn playhead_beat(audio_state: &AudioState) -> f64 {
+    let sample_rate = audio_state.sample_rate.load();
+    let bpm = audio_state.bpm.load();
+    if sample_rate > 0.0 && bpm > 0.0 {
+        TimeConverter::new(sample_rate, bpm).samples_to_beats(audio_state.get_position())
+    } else {
+        0.0
+    }
+}
+
+/// A control's current value in the units its lane stores
+fn control_value(track: &Track, target: &AutomationTarget) -> Option<f32> {
+    match target {
+        AutomationTarget::TrackVolume => Some(track.volume),
+        AutomationTarget::TrackPan => Some((track.pan + 1.0) * 0.5),
+        AutomationTarget::PluginParam {
+            plugin_id,
+            param_name,
+        } => track
+            .plugin_chain
+            .iter()
+            .find(|p| p.id == *plugin_id)
+            .and_then(|p| p.params.get(param_name).copied()),
+        AutomationTarget::TrackSend(_) | AutomationTarget::MidiCc(_) => None,
+    }
+}
+
+/// Starts a write pass on each picked lane, seeding it with the control's
+/// current value. Pushes one undo step first; returns whether any opened.
+fn open_write_passes(
+    state: &mut AppState,
+    ui_tx: &UiTx,
+    beat: f64,
+    mut pick: impl FnMut(u64, usize, &AutomationLane) -> bool,
+) -> bool {
+    let lanes: Vec<(u64, usize, f32)> = state
+        .tracks
+        .iter()
+        .flat_map(|(&id, track)| {
+            track
+                .automation_lanes
                 .iter()
-                .any(|c| start_beat >= c.start_beat && start_beat < c.start_beat + c.length_beats),
-            None => false,
+                .enumerate()
+                .filter(|(_, l)| l.write_pass.is_none())
+                .filter_map(move |(idx, l)| {
+                    control_value(track, &l.parameter).map(|v| (id, idx, l, v))
+                })
+        })
+        .filter(|&(id, idx, l, _)| pick(id, idx, l))
+        .map(|(id, idx, _, v)| (id, idx, v))
+        .collect();
+    if lanes.is_empty() {
+        return false;
+    }
+
+    let _ = ui_tx.send_sync(UIUpdate::PushUndo(state.snapshot()));
+    for (id, idx, value) in lanes {
+        if let Some(lane) = state
+            .tracks
+            .get_mut(&id)
+            .and_then(|t| t.automation_lanes.get_mut(idx))
+        {
+            lane.write_point(beat, value);
+        }
+    }
+    true
+}
+
+/// Ends the write pass on each picked lane with a point at `beat`
+fn close_write_passes(
+    state: &mut AppState,
+    beat: f64,
+    mut pick: impl FnMut(u64, usize, &AutomationLane) -> bool,
+) -> bool {
+    let mut closed = false;
+    for (&id, track) in state.tracks.iter_mut() {
+        for idx in 0..track.automation_lanes.len() {
+            let lane = &track.automation_lanes[idx];
+            if lane.write_pass.is_none() || !pick(id, idx, lane) {
+                continue;
+            }
+            let value = control_value(track, &lane.parameter);
+            let lane = &mut track.automation_lanes[idx];
+            if let Some(value) = value {
+                lane.write_point(beat, value);
+            }
+            lane.write_pass = None;
+            closed = true;
         }
-    };
-    if !need_insert {
-        return;
     }
+    closed
+}
 
+/// Feeds an incoming controller CC to the lanes listening for it: a lane
+/// in MIDI learn takes the binding, bound lanes get the commands that move
+/// their control, and CC lanes on `cc_track` record it. A CC move counts as
+/// grabbing the control, so it opens a write pass on writing lanes.
+fn route_midi_cc(
+    app_state: &Arc<Mutex<AppState>>,
+    audio_state: &AudioState,
+    ui_tx: &UiTx,
+    snapshot_tx: &Sender<AudioGraphSnapshot>,
+    cc_track: Option<u64>,
+    cc: u8,
+    data: u8,
+) -> Vec<AudioCommand> {
     let mut st = app_state.lock_sync();
-    let new_clip_id = idgen::next();
-    let new_pid = idgen::next();
-    st.patterns.insert(
-        new_pid,
-        MidiPattern {
-            id: new_pid,
-            notes: Vec::new(),
-        },
-    );
-    if let Some(t) = st.tracks.get_mut(&track_id) {
-        t.midi_clips.push(MidiClip {
-            id: new_clip_id,
-            name: format!("Rec @ Beat {:.1}", start_beat),
-            start_beat: start_beat.floor(),
-            length_beats: 64.0,
-            pattern_id: Some(new_pid),
-            ..Default::default()
+    if let Some(lane) = st
+        .tracks
+        .values_mut()
+        .flat_map(|t| t.automation_lanes.iter_mut())
+        .find(|l| l.learning)
+    {
+        lane.cc_binding = Some(cc);
+        lane.learning = false;
+        return Vec::new();
+    }
+
+    let value = data as f32 / 127.0;
+    let playing = audio_state.playing.load(Ordering::Relaxed);
+    let beat = playhead_beat(audio_state);
+    let listens = |id: u64, lane: &AutomationLane| {
+        lane.cc_binding == Some(cc)
+            || (lane.parameter == AutomationTarget::MidiCc(cc) && Some(id) == cc_track)
+    };
+
+    let opens = playing
+        && st.tracks.iter().any(|(&id, t)| {
+            t.automation_lanes
+                .iter()
+                .any(|l| listens(id, l) && l.write_mode.writes() && l.write_pass.is_none())
         });
-        t.midi_clips
-            .sort_by(|a, b| a.start_beat.partial_cmp(&b.start_beat).unwrap());
+    if opens {
+        let _ = ui_tx.send_sync(UIUpdate::PushUndo(st.snapshot()));
+    }
+
+    let mut controls = Vec::new();
+    for (&id, track) in st.tracks.iter_mut() {
+        for lane in track.automation_lanes.iter_mut() {
+            if !listens(id, lane) {
+                continue;
+            }
+            if playing && lane.write_mode.writes() && lane.write_pass.is_none() {
+                lane.write_pass = Some(beat);
+            }
+            match &lane.parameter {
+                AutomationTarget::TrackVolume => {
+                    controls.push(AudioCommand::SetTrackVolume(id, value));
+                }
+                AutomationTarget::TrackPan => {
+                    controls.push(AudioCommand::SetTrackPan(id, value * 2.0 - 1.0));
+                }
+                AutomationTarget::PluginParam {
+                    plugin_id,
+                    param_name,
+                } => {
+                    if let Some(plugin) = track.plugin_chain.iter().find(|p| p.id == *plugin_id) {
+                        controls.push(AudioCommand::SetPluginParam(
+                            id,
+                            *plugin_id,
+                            param_name.clone(),
+                            plugin.param_from_unit(param_name, value),
+                        ));
+                    }
+                }
+                AutomationTarget::MidiCc(_) => {
+                    if lane.write_pass.is_some() {
+                        lane.write_point(beat, value);
+                    }
+                }
+                AutomationTarget::TrackSend(_) => {}
+            }
+        }
+    }
+    if opens {
+        send_graph_snapshot(&st, snapshot_tx);
+    }
+    controls
+}
+
+/// Records a control change into the lanes of `target` that are mid-pass
+fn record_control_change(
+    state: &mut AppState,
+    audio_state: &AudioState,
+    track_id: u64,
+    target: &AutomationTarget,
+) {
+    if !audio_state.playing.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(track) = state.tracks.get_mut(&track_id) else {
+        return;
+    };
+    let Some(value) = control_value(track, target) else {
+        return;
+    };
+    let beat = playhead_beat(audio_state);
+    for lane in track
+        .automation_lanes
+        .iter_mut()
+        .filter(|l| l.write_pass.is_some() && l.parameter == *target)
+    {
+        lane.write_point(beat, value);
     }
-    st.ensure_ids();
 }
 
+// Create a recording MIDI clip at start_beat if none spans that beat.
 // Borrow-safe helper: resolve a clip's pattern and length, then mutate in place.
 fn with_pattern_mut<T>(
     app_state: &Arc<Mutex<AppState>>,
@@ -2207,9 +3434,18 @@ fn with_pattern_mut<T>(
     Some(f(pat, clip_len))
 }
 
-/// State for an in-progress MIDI recording.
-struct MidiRecordingState {
-    /// The track ID we are recording to.
-    track_id: u64,
-    active_notes: HashMap<(u8, u8), (f64, u8)>,
+/// One pass of a MIDI clip's content, the span looped notes wrap within
+fn clip_content_len(app_state: &Arc<Mutex<AppState>>, clip_id: u64) -> f64 {
+    let st = app_state.lock_sync();
+    match st.find_clip(clip_id) {
+        Some((track, ClipLocation::Midi(idx))) => {
+            let c = &track.midi_clips[idx];
+            if c.content_len_beats > 0.0 {
+                c.content_len_beats
+            } else {
+                c.length_beats
+            }
+        }
+        _ => 0.0,
+    }
 }
@@ -18,6 +18,10 @@ pub struct Config {
     pub ui: UIConfig,
     pub paths: PathConfig,
     pub behavior: BehaviorConfig,
+    #[serde(default)]
+    pub midi: MidiConfig,
+    #[serde(default)]
+    pub metronome: MetronomeConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +31,27 @@ pub struct AudioConfig {
     pub auto_detect_audio_device: bool,
     pub preferred_output_device: Option<String>,
     pub preferred_input_device: Option<String>,
+    /// First output channel of the master stereo pair (0 = channels 1/2)
+    #[serde(default)]
+    pub master_output_channel: usize,
+    /// First output channel of the cue/headphone pair
+    #[serde(default = "default_cue_output_channel")]
+    pub cue_output_channel: usize,
+    /// Level of the cue/headphone mix
+    #[serde(default = "default_cue_level")]
+    pub cue_level: f32,
+    /// Send the metronome to the cue output
+    #[serde(default)]
+    pub cue_click: bool,
+    /// Skip the master soft clipper, the only limiting on the master bus
+    #[serde(default, alias = "bypass_master_clipper")]
+    pub bypass_master_soft_clipper: bool,
+    /// Meter and normalize on 4x oversampled (inter-sample) peaks
+    #[serde(default)]
+    pub true_peak_metering: bool,
+    /// Soloing a channel keeps the buses its sends reach audible
+    #[serde(default = "default_solo_in_place")]
+    pub solo_in_place: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +60,55 @@ pub struct UIConfig {
     pub show_tooltips: bool,
     pub auto_scroll_on_playback: bool,
     pub smooth_scrolling: bool,
+    #[serde(default)]
+    pub bars_start_at_zero: bool,
+    #[serde(default)]
+    pub track_filter: TrackFilter,
+    /// Curve height of newly created automation lanes
+    #[serde(default = "default_automation_lane_height")]
+    pub automation_lane_height: f32,
+}
+
+/// Which tracks the track list and timeline show. Display only:
+/// hidden tracks keep their order, routing and playback.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum TrackFilter {
+    #[default]
+    All,
+    Armed,
+    Soloed,
+    HideMuted,
+    HideEmpty,
+}
+
+impl TrackFilter {
+    pub const ALL: [TrackFilter; 5] = [
+        TrackFilter::All,
+        TrackFilter::Armed,
+        TrackFilter::Soloed,
+        TrackFilter::HideMuted,
+        TrackFilter::HideEmpty,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TrackFilter::All => "All Tracks",
+            TrackFilter::Armed => "Armed",
+            TrackFilter::Soloed => "Soloed",
+            TrackFilter::HideMuted => "Hide Muted",
+            TrackFilter::HideEmpty => "Hide Empty",
+        }
+    }
+
+    pub fn shows(self, track: &crate::model::Track) -> bool {
+        match self {
+            TrackFilter::All => true,
+            TrackFilter::Armed => track.armed,
+            TrackFilter::Soloed => track.solo,
+            TrackFilter::HideMuted => !track.muted,
+            TrackFilter::HideEmpty => !track.audio_clips.is_empty() || !track.midi_clips.is_empty(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -51,6 +125,36 @@ pub struct PathConfig {
     pub audio_import_dir: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MidiConfig {
+    #[serde(default)]
+    pub clock_output_enabled: bool,
+    #[serde(default)]
+    pub clock_output_port: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetronomeConfig {
+    #[serde(default)]
+    pub subdivision: ClickSubdivision,
+    /// Sound for the first beat of each bar, the built-in tick when unset
+    #[serde(default)]
+    pub accent_sample: Option<PathBuf>,
+    /// Sound for the other beats and their subdivisions
+    #[serde(default)]
+    pub click_sample: Option<PathBuf>,
+}
+
+/// Finest note value the metronome clicks
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum ClickSubdivision {
+    /// Beats only (dotted quarters in 6/8, 9/8 and 12/8)
+    #[default]
+    Quarter,
+    /// Beats plus the eighths between them
+    Eighth,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BehaviorConfig {
     pub auto_save: bool,
@@ -58,6 +162,90 @@ pub struct BehaviorConfig {
     pub create_backup_on_save: bool,
     pub stop_on_track_selection: bool,
     pub follow_playhead: bool,
+    #[serde(default)]
+    pub lock_playhead_to_grid: bool,
+    /// Pointer travel (px) before a pressed clip starts moving
+    #[serde(default = "default_clip_drag_threshold")]
+    pub clip_drag_threshold_px: f32,
+    #[serde(default)]
+    pub incompatible_clip_drop: IncompatibleClipDrop,
+    #[serde(default)]
+    pub clip_overlap_drop: ClipOverlapDrop,
+    #[serde(default)]
+    pub note_preview: NotePreview,
+    #[serde(default)]
+    pub solo_mode: SoloMode,
+    /// Micro-fade (ms) put on both ends of imported and recorded audio clips
+    #[serde(default = "default_clip_fade_ms")]
+    pub default_clip_fade_ms: f32,
+}
+
+/// What happens when clips are dropped on a track that can't hold them
+/// (audio on MIDI, MIDI on audio, anything on a bus)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum IncompatibleClipDrop {
+    /// Leave the clips where they were and say why
+    Reject,
+    /// Move them onto a new track of the right type below the target
+    #[default]
+    CreateTrack,
+}
+
+/// What a dropped clip does to the clips it lands on.
+/// Holding Alt while dropping layers instead (or punches out when this is Layer).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum ClipOverlapDrop {
+    /// Cut the covered region out of the existing clips
+    #[default]
+    PunchOut,
+    /// Keep both and fade across the overlap (audio only; MIDI layers)
+    Crossfade,
+    /// Keep both and let them play together
+    Layer,
+}
+
+/// When the piano roll auditions pitches through the track
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum NotePreview {
+    Off,
+    /// While a note is held or dragged
+    #[default]
+    OnClick,
+    /// Whatever row is under the pointer
+    OnHover,
+}
+
+/// What a plain click on a solo button does to the other solos.
+/// Holding Ctrl/Cmd while clicking does the opposite.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum SoloMode {
+    #[default]
+    Additive,
+    Exclusive,
+}
+
+fn default_clip_drag_threshold() -> f32 {
+    4.0
+}
+
+fn default_automation_lane_height() -> f32 {
+    crate::constants::AUTOMATION_LANE_HEIGHT
+}
+
+fn default_clip_fade_ms() -> f32 {
+    3.0
+}
+
+fn default_cue_output_channel() -> usize {
+    crate::constants::DEFAULT_CUE_OUTPUT_CHANNEL
+}
+
+fn default_cue_level() -> f32 {
+    1.0
+}
+
+fn default_solo_in_place() -> bool {
+    true
 }
 
 impl Default for Config {
@@ -69,12 +257,22 @@ impl Default for Config {
                 auto_detect_audio_device: true,
                 preferred_output_device: None,
                 preferred_input_device: None,
+                master_output_channel: 0,
+                cue_output_channel: default_cue_output_channel(),
+                cue_level: default_cue_level(),
+                cue_click: false,
+                bypass_master_soft_clipper: false,
+                true_peak_metering: false,
+                solo_in_place: true,
             },
             ui: UIConfig {
                 theme: Theme::Dark,
                 show_tooltips: true,
                 auto_scroll_on_playback: true,
                 smooth_scrolling: true,
+                bars_start_at_zero: false,
+                track_filter: TrackFilter::default(),
+                automation_lane_height: default_automation_lane_height(),
             },
             paths: PathConfig {
                 last_project_dir: None,
@@ -88,12 +286,25 @@ impl Default for Config {
                 create_backup_on_save: true,
                 stop_on_track_selection: false,
                 follow_playhead: true,
+                lock_playhead_to_grid: false,
+                clip_drag_threshold_px: default_clip_drag_threshold(),
+                incompatible_clip_drop: IncompatibleClipDrop::default(),
+                clip_overlap_drop: ClipOverlapDrop::default(),
+                note_preview: NotePreview::default(),
+                solo_mode: SoloMode::default(),
+                default_clip_fade_ms: default_clip_fade_ms(),
             },
+            midi: MidiConfig::default(),
+            metronome: MetronomeConfig::default(),
         }
     }
 }
 
 impl Config {
+    pub fn bar_numbering(&self) -> crate::time_utils::BarNumbering {
+        crate::time_utils::BarNumbering::new(self.ui.bars_start_at_zero)
+    }
+
     pub fn load() -> Result<Self> {
         if let Some(data) = crate::wasm_persist::read_config_string(
             crate::paths::opfs::FILE_CONFIG,
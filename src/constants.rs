@@ -7,6 +7,10 @@ pub const DEFAULT_SAMPLE_RATE: f64 = 44100.0;
 pub const DEFAULT_BPM: f32 = 120.0;
 pub const DEFAULT_MASTER_VOLUME: f32 = 0.8;
 pub const DEFAULT_TRACK_VOLUME: f32 = 0.7;
+pub const DEFAULT_EXPORT_AUTOMATION_INTERVAL: usize = 32; // samples, 0 = once per block
+pub const MIN_PLUGIN_CONTROL_BLOCK: usize = 16;
+pub const DEFAULT_CUE_OUTPUT_CHANNEL: usize = 2; // channels 3/4
+pub const MAX_PDC_SAMPLES: usize = 1 << 15; // longest delay compensation, ~0.7 s at 48kHz
 
 // UI Layout Constants
 pub const PIANO_KEY_WIDTH: f32 = 60.0;
@@ -43,6 +47,7 @@ pub const SINE_WAVE_AMPLITUDE: f32 = 0.1;
 pub const NORMALIZE_TARGET_DB: f32 = -0.1; // dB
 pub const NORMALIZE_TARGET_LINEAR: f32 = 0.989;
 pub const SILENCE_THRESHOLD: f32 = 0.001; // -60dB
+pub const PUNCH_CROSSFADE_MS: f32 = 10.0;
 
 // Channel Configuration
 pub const CHANNEL_QUEUE_SIZE: usize = 256;
@@ -131,15 +131,35 @@ impl EditProcessor {
         if split_sample >= clip.samples.len() {
             return None;
         }
+        // A reversed clip plays its samples from the end, so its head is the tail
+        let split = |samples: &[f32]| {
+            if clip.reversed {
+                let (tail, head) = samples.split_at(samples.len() - split_sample);
+                (head.to_vec(), tail.to_vec())
+            } else {
+                let (head, tail) = samples.split_at(split_sample);
+                (head.to_vec(), tail.to_vec())
+            }
+        };
+        let (head, tail) = split(&clip.samples);
+        let (head_right, tail_right) = if clip.samples_right.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            split(&clip.samples_right)
+        };
         let mut first = clip.clone();
         first.length_beats = split_offset;
-        first.samples = clip.samples[..split_sample].to_vec();
+        first.samples = head;
+        first.samples_right = head_right;
+        first.peaks.invalidate();
 
         let mut second = clip.clone();
         second.name = format!("{} (2)", clip.name);
         second.start_beat = position_beats;
         second.length_beats = clip.length_beats - split_offset;
-        second.samples = clip.samples[split_sample..].to_vec();
+        second.samples = tail;
+        second.samples_right = tail_right;
+        second.peaks.invalidate();
         Some((first, second))
     }
 
@@ -149,8 +169,12 @@ impl EditProcessor {
             .clamp(0.0, clip.samples.len() as f64) as usize;
         for i in 0..fade_samples {
             let f = i as f32 / fade_samples.max(1) as f32;
-            clip.samples[i] *= f;
+            let idx = clip.played_index(i);
+            for channel in clip.channels_mut() {
+                channel[idx] *= f;
+            }
         }
+        clip.peaks.invalidate();
     }
 
     pub fn apply_fade_out(clip: &mut AudioClip, duration_beats: f64, bpm: f32) {
@@ -160,8 +184,12 @@ impl EditProcessor {
         let start = clip.samples.len().saturating_sub(fade_samples);
         for i in 0..fade_samples {
             let f = 1.0 - (i as f32 / fade_samples.max(1) as f32);
-            clip.samples[start + i] *= f;
+            let idx = clip.played_index(start + i);
+            for channel in clip.channels_mut() {
+                channel[idx] *= f;
+            }
         }
+        clip.peaks.invalidate();
     }
 
     pub fn quantize_notes(notes: &mut Vec<MidiNote>, grid: f64, strength: f32) {
@@ -178,15 +206,98 @@ impl EditProcessor {
         }
     }
 
-    pub fn humanize_notes(notes: &mut Vec<MidiNote>, amount: f32) {
+    /// Random offsets of up to ±`timing` beats and ±`velocity`; starts pushed past
+    /// either end of the clip content wrap around to the other end
+    pub fn humanize_notes(notes: &mut [MidiNote], timing: f64, velocity: u8, content_len: f64) {
         use rand::RngExt;
         let mut rng = rand::rng();
+        let len = content_len.max(1e-6);
         for n in notes.iter_mut() {
-            let dt = (rng.random::<f64>() - 0.5) * amount as f64 * 0.1;
-            n.start = (n.start + dt).max(0.0);
-            let dv = (rng.random::<f32>() - 0.5) * amount * 20.0;
-            let v = (n.velocity as f32 + dv).clamp(1.0, 127.0);
-            n.velocity = v as u8;
+            let dt = (rng.random::<f64>() * 2.0 - 1.0) * timing;
+            n.start = (n.start + dt).rem_euclid(len);
+            let dv = (rng.random::<f32>() * 2.0 - 1.0) * velocity as f32;
+            n.velocity = (n.velocity as f32 + dv).round().clamp(1.0, 127.0) as u8;
         }
     }
+
+    /// Extends each selected note up to the next note start. The last notes reach
+    /// the first start of the next pass through the clip content.
+    pub fn legato_notes(notes: &mut [MidiNote], selected: &[u64], content_len: f64) {
+        let len = content_len.max(1e-6);
+        let mut starts: Vec<f64> = notes.iter().map(|n| n.start).collect();
+        starts.sort_by(f64::total_cmp);
+        starts.dedup();
+        let Some(&first) = starts.first() else {
+            return;
+        };
+        for n in notes.iter_mut().filter(|n| selected.contains(&n.id)) {
+            let next = starts
+                .iter()
+                .copied()
+                .find(|&s| s > n.start + 1e-9)
+                .unwrap_or(first + len);
+            n.duration = (next - n.start).max(1e-6);
+        }
+    }
+
+    /// Multiplies durations by `factor`, never past one pass of the clip content
+    pub fn scale_note_lengths(notes: &mut [MidiNote], factor: f64, content_len: f64) {
+        let len = content_len.max(1e-6);
+        for n in notes.iter_mut() {
+            n.duration = (n.duration * factor).clamp(1e-6, len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One second at 120 BPM, two beats, with the right channel negated
+    fn stereo(reversed: bool) -> AudioClip {
+        let samples: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+        AudioClip {
+            start_beat: 4.0,
+            length_beats: 2.0,
+            samples_right: samples.iter().map(|s| -s).collect(),
+            samples,
+            sample_rate: 1000.0,
+            reversed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn split_keeps_stereo_channels_together() {
+        let (first, second) = EditProcessor::split_clip(&stereo(false), 5.0, 120.0).unwrap();
+        assert_eq!(first.samples.len(), 500);
+        assert_eq!(first.samples_right.len(), 500);
+        assert_eq!(second.samples[0], 500.0);
+        assert_eq!(second.samples_right[0], -500.0);
+
+        // Reversed, the head is cut from the end of both channels
+        let (first, second) = EditProcessor::split_clip(&stereo(true), 5.5, 120.0).unwrap();
+        assert_eq!(first.samples_right.len(), 750);
+        assert_eq!(first.samples_right[0], -250.0);
+        assert_eq!(second.samples_right.len(), 250);
+        assert_eq!(second.samples_right[249], -249.0);
+    }
+
+    #[test]
+    fn split_leaves_mono_clips_mono() {
+        let mut clip = stereo(false);
+        clip.samples_right.clear();
+        let (first, second) = EditProcessor::split_clip(&clip, 5.0, 120.0).unwrap();
+        assert!(first.samples_right.is_empty());
+        assert!(second.samples_right.is_empty());
+    }
+
+    #[test]
+    fn fades_reach_both_channels() {
+        let mut clip = stereo(false);
+        EditProcessor::apply_fade_in(&mut clip, 1.0, 120.0);
+        assert_eq!(clip.samples[0], 0.0);
+        assert_eq!(clip.samples_right[0], 0.0);
+        assert_eq!(clip.samples_right[250], -125.0);
+    }
 }
@@ -1,12 +1,13 @@
 use crate::audio;
 use crate::audio_state::{AudioGraphSnapshot, AudioState, RealtimeCommand};
 use crate::config::Config;
-use crate::messages::{AudioCommand, UiRx, UiTx};
+use crate::messages::{AudioCommand, AudioDeviceSelection, UiRx, UiTx};
 use crate::midi_input::MidiInputHandler;
 use crate::spawn_detached;
 use crate::{project, ui};
 use flume::{self, Sender};
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use wasm_safe_mutex::mpsc::{Receiver, channel};
 use wasm_safe_mutex::{self, Mutex};
 
@@ -34,22 +35,30 @@ struct AppChannels {
 fn setup_channels_and_start_audio(
     app_state: &Arc<Mutex<project::AppState>>,
     audio_state: &Arc<AudioState>,
-    start_audio: impl FnOnce(Receiver<RealtimeCommand>, Receiver<AudioGraphSnapshot>, UiTx),
+    start_audio: impl FnOnce(
+        Receiver<RealtimeCommand>,
+        Receiver<AudioGraphSnapshot>,
+        UiTx,
+        flume::Receiver<AudioDeviceSelection>,
+    ),
 ) -> AppChannels {
     let (command_tx, command_rx) = flume::unbounded::<AudioCommand>();
     let (realtime_tx, realtime_rx) = channel::<RealtimeCommand>();
     let (snapshot_tx, snapshot_rx) = channel::<AudioGraphSnapshot>();
     let (ui_tx, ui_rx) = channel();
+    // Device changes bypass the stream, which may not be running at all
+    let (device_tx, device_rx) = flume::unbounded::<AudioDeviceSelection>();
 
-    start_audio(realtime_rx, snapshot_rx, ui_tx.clone());
+    start_audio(realtime_rx, snapshot_rx, ui_tx.clone(), device_rx);
 
-    let midi_handler = match MidiInputHandler::new(command_tx.clone()) {
-        Ok(handler) => Some(Arc::new(handler)),
-        Err(e) => {
-            log::warn!("Could not create MIDI Input handler: {}", e);
-            None
-        }
-    };
+    let midi_handler =
+        match MidiInputHandler::new(command_tx.clone(), realtime_tx.clone(), audio_state.clone()) {
+            Ok(handler) => Some(Arc::new(handler)),
+            Err(e) => {
+                log::warn!("Could not create MIDI Input handler: {}", e);
+                None
+            }
+        };
 
     spawn_detached!(crate::command_processor::run_command_processor(
         app_state.clone(),
@@ -58,6 +67,7 @@ fn setup_channels_and_start_audio(
         realtime_tx,
         ui_tx.clone(),
         snapshot_tx,
+        device_tx,
         midi_handler.clone(),
     ));
 
@@ -101,9 +111,28 @@ pub fn run_app() -> Result<(), Box<dyn std::error::Error>> {
     let app_state = Arc::new(Mutex::new(project::AppState::default()));
     let audio_state = Arc::new(AudioState::new());
 
-    let preferred_sample_rate = config.audio.sample_rate;
-    let host_sample_rate = audio::resolve_output_sample_rate(preferred_sample_rate);
+    let device_selection = AudioDeviceSelection::from(&config.audio);
+    let host_sample_rate = audio::resolve_output_sample_rate(&device_selection);
     audio_state.sample_rate.store(host_sample_rate);
+    audio_state
+        .master_output_channel
+        .store(config.audio.master_output_channel, Ordering::Relaxed);
+    audio_state
+        .cue_output_channel
+        .store(config.audio.cue_output_channel, Ordering::Relaxed);
+    audio_state.cue_level.store(config.audio.cue_level);
+    audio_state
+        .cue_click
+        .store(config.audio.cue_click, Ordering::Relaxed);
+    audio_state
+        .master_clip_bypass
+        .store(config.audio.bypass_master_soft_clipper, Ordering::Relaxed);
+    audio_state
+        .true_peak_metering
+        .store(config.audio.true_peak_metering, Ordering::Relaxed);
+    audio_state
+        .solo_in_place
+        .store(config.audio.solo_in_place, Ordering::Relaxed);
     {
         let mut state = app_state.lock_sync();
         state.sample_rate = host_sample_rate;
@@ -126,7 +155,7 @@ pub fn run_app() -> Result<(), Box<dyn std::error::Error>> {
     let channels = setup_channels_and_start_audio(
         &app_state,
         &audio_state,
-        |realtime_rx, snapshot_rx, ui_tx_audio| {
+        |realtime_rx, snapshot_rx, ui_tx_audio, device_rx| {
             let audio_state_audio = audio_state_audio.clone();
             std::thread::spawn(move || {
                 audio::run_audio_thread(
@@ -134,7 +163,8 @@ pub fn run_app() -> Result<(), Box<dyn std::error::Error>> {
                     realtime_rx,
                     ui_tx_audio,
                     snapshot_rx,
-                    host_sample_rate,
+                    device_selection,
+                    device_rx,
                 );
             });
         },
@@ -197,9 +227,28 @@ pub fn run_app_android(app: AndroidApp) -> Result<(), Box<dyn std::error::Error>
     let app_state = Arc::new(Mutex::new(crate::project::AppState::default()));
     let audio_state = Arc::new(AudioState::new());
 
-    let preferred_sample_rate = config.audio.sample_rate;
-    let host_sample_rate = audio::resolve_output_sample_rate(preferred_sample_rate);
+    let device_selection = AudioDeviceSelection::from(&config.audio);
+    let host_sample_rate = audio::resolve_output_sample_rate(&device_selection);
     audio_state.sample_rate.store(host_sample_rate);
+    audio_state
+        .master_output_channel
+        .store(config.audio.master_output_channel, Ordering::Relaxed);
+    audio_state
+        .cue_output_channel
+        .store(config.audio.cue_output_channel, Ordering::Relaxed);
+    audio_state.cue_level.store(config.audio.cue_level);
+    audio_state
+        .cue_click
+        .store(config.audio.cue_click, Ordering::Relaxed);
+    audio_state
+        .master_clip_bypass
+        .store(config.audio.bypass_master_soft_clipper, Ordering::Relaxed);
+    audio_state
+        .true_peak_metering
+        .store(config.audio.true_peak_metering, Ordering::Relaxed);
+    audio_state
+        .solo_in_place
+        .store(config.audio.solo_in_place, Ordering::Relaxed);
     {
         let mut state = app_state.lock_sync();
         state.sample_rate = host_sample_rate;
@@ -222,7 +271,7 @@ pub fn run_app_android(app: AndroidApp) -> Result<(), Box<dyn std::error::Error>
     let channels = setup_channels_and_start_audio(
         &app_state,
         &audio_state,
-        |realtime_rx, snapshot_rx, ui_tx_audio| {
+        |realtime_rx, snapshot_rx, ui_tx_audio, device_rx| {
             let audio_state_audio = audio_state_audio.clone();
             std::thread::spawn(move || {
                 audio::run_audio_thread(
@@ -230,7 +279,8 @@ pub fn run_app_android(app: AndroidApp) -> Result<(), Box<dyn std::error::Error>
                     realtime_rx,
                     ui_tx_audio,
                     snapshot_rx,
-                    host_sample_rate,
+                    device_selection,
+                    device_rx,
                 );
             });
         },
@@ -271,11 +321,31 @@ pub fn create_app() -> ui::YadawApp {
     let config = Config::default();
     let app_state = Arc::new(Mutex::new(project::AppState::default()));
     let audio_state = Arc::new(AudioState::new());
+    audio_state
+        .master_output_channel
+        .store(config.audio.master_output_channel, Ordering::Relaxed);
+    audio_state
+        .cue_output_channel
+        .store(config.audio.cue_output_channel, Ordering::Relaxed);
+    audio_state.cue_level.store(config.audio.cue_level);
+    audio_state
+        .cue_click
+        .store(config.audio.cue_click, Ordering::Relaxed);
+    audio_state
+        .master_clip_bypass
+        .store(config.audio.bypass_master_soft_clipper, Ordering::Relaxed);
+    audio_state
+        .true_peak_metering
+        .store(config.audio.true_peak_metering, Ordering::Relaxed);
+    audio_state
+        .solo_in_place
+        .store(config.audio.solo_in_place, Ordering::Relaxed);
 
     let channels = setup_channels_and_start_audio(
         &app_state,
         &audio_state,
-        |realtime_rx, snapshot_rx, ui_tx_audio| {
+        // The browser picks the output device
+        |realtime_rx, snapshot_rx, ui_tx_audio, _device_rx| {
             audio::run_audio_wasm(
                 audio_state.clone(),
                 realtime_rx,
@@ -10,6 +10,9 @@ pub enum AppAction {
     GoToStart,
     Rewind,
     FastForward,
+    PlayheadLeft,
+    PlayheadRight,
+    TapTempo,
 
     // Edit
     Undo,
@@ -70,6 +73,7 @@ pub enum AppAction {
     HumanizeDialog,
 
     // Other
+    ToggleVirtualKeyboard,
     Escape,
 }
 
@@ -82,6 +86,21 @@ pub enum ActionContext {
     Mixer,
     NoteSelection,
     ClipSelection,
+    Automation,
+}
+
+impl ActionContext {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Global => "Global",
+            Self::PianoRoll => "Piano Roll",
+            Self::Timeline => "Timeline",
+            Self::Mixer => "Mixer",
+            Self::NoteSelection => "Note Selection",
+            Self::ClipSelection => "Clip Selection",
+            Self::Automation => "Automation",
+        }
+    }
 }
 
 impl AppAction {
@@ -95,6 +114,9 @@ impl AppAction {
             GoToStart,
             Rewind,
             FastForward,
+            PlayheadLeft,
+            PlayheadRight,
+            TapTempo,
             Undo,
             Redo,
             Cut,
@@ -139,6 +161,7 @@ impl AppAction {
             QuantizeDialog,
             TransposeDialog,
             HumanizeDialog,
+            ToggleVirtualKeyboard,
             Escape,
         ]
     }
@@ -152,7 +175,11 @@ impl AppAction {
             | Self::Record
             | Self::GoToStart
             | Self::Rewind
-            | Self::FastForward => &[Global],
+            | Self::FastForward
+            | Self::TapTempo => &[Global],
+
+            // Arrow keys nudge notes in the piano roll, so the playhead only moves here
+            Self::PlayheadLeft | Self::PlayheadRight => &[Timeline],
 
             // Global edit
             Self::Undo
@@ -207,11 +234,20 @@ impl AppAction {
             // Dialogs
             Self::QuantizeDialog | Self::TransposeDialog | Self::HumanizeDialog => &[PianoRoll],
 
-            Self::Delete => &[Global, PianoRoll, Timeline],
-            Self::Escape => &[Global],
+            Self::Delete => &[Global, PianoRoll, Timeline, Automation],
+            Self::ToggleVirtualKeyboard | Self::Escape => &[Global],
         }
     }
 
+    /// Whether both actions can fire in the same context; Global actions are
+    /// live everywhere
+    pub fn shares_context(&self, other: AppAction) -> bool {
+        let (a, b) = (self.contexts(), other.contexts());
+        a.contains(&ActionContext::Global)
+            || b.contains(&ActionContext::Global)
+            || a.iter().any(|c| b.contains(c))
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Self::PlayPause => "Play/Pause",
@@ -220,6 +256,9 @@ impl AppAction {
             Self::GoToStart => "Go to Start",
             Self::Rewind => "Rewind",
             Self::FastForward => "Fast Forward",
+            Self::PlayheadLeft => "Move Playhead Left (Grid)",
+            Self::PlayheadRight => "Move Playhead Right (Grid)",
+            Self::TapTempo => "Tap Tempo",
 
             Self::Undo => "Undo",
             Self::Redo => "Redo",
@@ -274,6 +313,7 @@ impl AppAction {
             Self::TransposeDialog => "Transpose...",
             Self::HumanizeDialog => "Humanize...",
 
+            Self::ToggleVirtualKeyboard => "Toggle Computer Keyboard Piano",
             Self::Escape => "Escape",
         }
     }
@@ -285,7 +325,10 @@ impl AppAction {
             | Self::Record
             | Self::GoToStart
             | Self::Rewind
-            | Self::FastForward => "Transport",
+            | Self::FastForward
+            | Self::PlayheadLeft
+            | Self::PlayheadRight
+            | Self::TapTempo => "Transport",
 
             Self::Undo
             | Self::Redo
@@ -335,7 +378,7 @@ impl AppAction {
             | Self::FadeIn
             | Self::FadeOut => "Timeline",
 
-            Self::Escape => "Other",
+            Self::ToggleVirtualKeyboard | Self::Escape => "Other",
         }
     }
 }
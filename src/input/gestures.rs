@@ -3,11 +3,26 @@ use web_time::Instant;
 
 #[derive(Debug, Clone, Copy)]
 pub enum GestureAction {
-    Pan { delta: Vec2 },
-    PinchZoom { scale: f32, center: Pos2 },
-    Tap { pos: Pos2 },
-    DoubleTap { pos: Pos2 },
-    LongPress { pos: Pos2 },
+    Pan {
+        delta: Vec2,
+        center: Pos2,
+        fingers: usize,
+    },
+    /// `vertical` when the fingers are spread more up-down than sideways
+    PinchZoom {
+        scale: f32,
+        center: Pos2,
+        vertical: bool,
+    },
+    Tap {
+        pos: Pos2,
+    },
+    DoubleTap {
+        pos: Pos2,
+    },
+    LongPress {
+        pos: Pos2,
+    },
 }
 
 pub struct GestureRecognizer {
@@ -160,7 +175,8 @@ impl GestureRecognizer {
         }
 
         let centroid = self.compute_centroid();
-        let distance = (self.touch_points[0].pos - self.touch_points[1].pos).length();
+        let spread = self.touch_points[0].pos - self.touch_points[1].pos;
+        let distance = spread.length();
 
         if let (Some(_last_c), Some(last_d)) = (self.last_centroid, self.last_distance) {
             let scale = (distance / last_d).clamp(0.5, 2.0);
@@ -171,6 +187,7 @@ impl GestureRecognizer {
                 return Some(GestureAction::PinchZoom {
                     scale,
                     center: centroid,
+                    vertical: spread.y.abs() > spread.x.abs(),
                 });
             }
         }
@@ -192,7 +209,11 @@ impl GestureRecognizer {
 
             if delta.length() > self.config.pan_min_distance {
                 self.last_centroid = Some(centroid);
-                return Some(GestureAction::Pan { delta });
+                return Some(GestureAction::Pan {
+                    delta,
+                    center: centroid,
+                    fingers: self.touch_points.len(),
+                });
             }
         }
 
@@ -1,17 +1,25 @@
 pub mod actions;
 pub mod gestures;
+pub mod profiles;
 pub mod shortcuts;
+pub mod virtual_keyboard;
 
 use actions::{ActionContext, AppAction};
 use gestures::{GestureAction, GestureRecognizer};
-use shortcuts::ShortcutRegistry;
+use shortcuts::{ModifierSet, ShortcutRegistry};
+use virtual_keyboard::VirtualKeyboard;
 
-use egui::{Context, Key};
+use egui::{Context, Key, Pos2, Rect};
 
 pub struct InputManager {
     shortcuts: ShortcutRegistry,
     gestures: GestureRecognizer,
     current_context: ActionContext,
+    /// Long press from the latest poll, until a view claims it
+    long_press: Option<Pos2>,
+    /// Two-finger pans and pinches from the latest poll
+    view_gestures: Vec<GestureAction>,
+    pub virtual_keyboard: VirtualKeyboard,
 }
 
 impl InputManager {
@@ -20,6 +28,9 @@ impl InputManager {
             shortcuts: ShortcutRegistry::default(),
             gestures: GestureRecognizer::new(),
             current_context: ActionContext::Global,
+            long_press: None,
+            view_gestures: Vec::new(),
+            virtual_keyboard: VirtualKeyboard::default(),
         }
     }
 
@@ -40,13 +51,47 @@ impl InputManager {
         self.shortcuts.save(path)
     }
 
+    /// Switch to a built-in or saved shortcut profile
+    pub fn load_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        match profiles::builtin_profile(name) {
+            Some(registry) => self.shortcuts = registry,
+            None => {
+                self.load_shortcuts(&profiles::profile_path(name))?;
+                self.shortcuts.profile = Some(name.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Store the current bindings as a named profile in the config dir
+    pub fn save_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        self.shortcuts.profile = Some(name.to_string());
+        let json = serde_json::to_string_pretty(&self.shortcuts)?;
+        std::fs::write(profiles::profile_path(name), json)?;
+        Ok(())
+    }
+
+    pub fn profile_name(&self) -> &str {
+        self.shortcuts
+            .profile
+            .as_deref()
+            .unwrap_or(profiles::DEFAULT_PROFILE)
+    }
+
     /// Set current UI context (affects which shortcuts are active)
     pub fn set_context(&mut self, context: ActionContext) {
         self.current_context = context;
     }
 
+    pub fn context(&self) -> ActionContext {
+        self.current_context
+    }
+
     /// Process input and return triggered actions
     pub fn poll_actions(&mut self, ctx: &Context) -> Vec<AppAction> {
+        self.long_press = None;
+        self.view_gestures.clear();
+
         // Don't process shortcuts when text input has focus (dialogs, BPM field, etc.)
         if ctx.egui_wants_keyboard_input() {
             if ctx.input(|i| i.key_pressed(Key::Escape)) {
@@ -69,6 +114,9 @@ impl InputManager {
 
             for bind in bindings {
                 let key: Key = bind.key.into();
+                if bind.modifiers == ModifierSet::NONE && self.virtual_keyboard.claims(key) {
+                    continue;
+                }
 
                 // Check if key was pressed this frame
                 let key_pressed = ctx.input(|i| i.key_pressed(key));
@@ -94,16 +142,43 @@ impl InputManager {
                         _ => {}
                     }
                 }
-                GestureAction::LongPress { .. } => {
-                    // TODO: Show context menu in far future
+                GestureAction::LongPress { pos } => {
+                    // Views open their context menu via `take_long_press_in`
+                    self.long_press = Some(pos);
+                }
+                GestureAction::Pan { fingers, .. } if fingers >= 2 => {
+                    self.view_gestures.push(gesture);
                 }
-                _ => {} // Pan/Pinch handled separately in views
+                GestureAction::PinchZoom { .. } => self.view_gestures.push(gesture),
+                // One-finger drags stay with egui's pointer (clip moves, selection)
+                _ => {}
             }
         }
 
         actions
     }
 
+    /// Claim this frame's long press if it landed inside `rect`
+    pub fn take_long_press_in(&mut self, rect: Rect) -> Option<Pos2> {
+        let pos = self.long_press.filter(|p| rect.contains(*p))?;
+        self.long_press = None;
+        Some(pos)
+    }
+
+    /// Claim this frame's two-finger gestures centered inside `rect`
+    pub fn take_view_gestures_in(&mut self, rect: Rect) -> Vec<GestureAction> {
+        let (taken, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut self.view_gestures)
+            .into_iter()
+            .partition(|g| match g {
+                GestureAction::Pan { center, .. } | GestureAction::PinchZoom { center, .. } => {
+                    rect.contains(*center)
+                }
+                _ => false,
+            });
+        self.view_gestures = rest;
+        taken
+    }
+
     /// Get reference to shortcut registry (for UI editing)
     pub fn shortcuts(&self) -> &ShortcutRegistry {
         &self.shortcuts
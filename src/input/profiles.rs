@@ -0,0 +1,107 @@
+use super::actions::AppAction;
+use super::shortcuts::{KeyCode, Keybind, ShortcutRegistry};
+use std::path::PathBuf;
+
+pub const DEFAULT_PROFILE: &str = "Default";
+pub const BUILTIN_PROFILES: [&str; 3] = [DEFAULT_PROFILE, "Ableton-like", "Logic-like"];
+
+pub fn is_builtin(name: &str) -> bool {
+    BUILTIN_PROFILES.contains(&name)
+}
+
+/// Keybinding set shipped with the app, or None for a user profile
+pub fn builtin_profile(name: &str) -> Option<ShortcutRegistry> {
+    use AppAction::*;
+    use KeyCode::*;
+
+    let mut reg = ShortcutRegistry::default_bindings();
+    match name {
+        DEFAULT_PROFILE => {}
+        "Ableton-like" => {
+            reg.set_bindings(Record, &[Keybind::none(F9)]);
+            reg.set_bindings(Rewind, &[]);
+            reg.set_bindings(FastForward, &[]);
+            reg.set_bindings(ToggleLoop, &[Keybind::cmd_shift(L)]);
+            reg.set_bindings(ToggleTimeline, &[Keybind::none(Tab)]);
+            reg.set_bindings(TogglePianoRoll, &[Keybind::shift(Tab)]);
+            reg.set_bindings(ToggleMixer, &[Keybind::cmd_alt(M)]);
+            reg.set_bindings(QuantizeDialog, &[Keybind::cmd(U)]);
+            reg.set_bindings(FadeIn, &[Keybind::cmd_alt(F)]);
+            reg.set_bindings(FadeOut, &[Keybind::cmd_alt(G)]);
+        }
+        "Logic-like" => {
+            reg.set_bindings(Stop, &[Keybind::none(Num0)]);
+            reg.set_bindings(Rewind, &[Keybind::none(Comma)]);
+            reg.set_bindings(FastForward, &[Keybind::none(Period)]);
+            reg.set_bindings(ToggleLoop, &[Keybind::none(C)]);
+            reg.set_bindings(SetLoopToSelection, &[Keybind::cmd(U)]);
+            reg.set_bindings(SplitAtPlayhead, &[Keybind::cmd(T)]);
+            reg.set_bindings(ToggleMixer, &[Keybind::none(X)]);
+            reg.set_bindings(ToggleVirtualKeyboard, &[Keybind::cmd(K)]);
+        }
+        _ => return None,
+    }
+    reg.profile = Some(name.to_string());
+    Some(reg)
+}
+
+pub fn profiles_dir() -> PathBuf {
+    let dir = crate::paths::config_root_dir().join("shortcut_profiles");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// Keep names readable as file stems
+pub fn sanitize_profile_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+pub fn profile_path(name: &str) -> PathBuf {
+    profiles_dir().join(format!("{}.json", name))
+}
+
+/// Profiles saved by the user, sorted by name
+pub fn saved_profiles() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(profiles_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+        .filter(|name| !is_builtin(name))
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn delete_profile(name: &str) -> anyhow::Result<()> {
+    std::fs::remove_file(profile_path(name))
+        .map_err(|e| anyhow::anyhow!("Failed to delete shortcut profile '{}': {}", name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_profiles_have_no_conflicts() {
+        for name in BUILTIN_PROFILES {
+            let reg = builtin_profile(name).unwrap();
+            for (&action, binds) in &reg.bindings {
+                for bind in binds {
+                    let clashes = reg.conflicts(bind, action);
+                    assert!(
+                        clashes.is_empty(),
+                        "{name}: {} on {bind:?} clashes with {clashes:?}",
+                        action.name()
+                    );
+                }
+            }
+        }
+    }
+}
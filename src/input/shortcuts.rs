@@ -568,6 +568,10 @@ pub struct ShortcutRegistry {
     /// Action -> List of keybinds (allows multiple binds per action)
     pub bindings: HashMap<AppAction, Vec<Keybind>>,
 
+    /// Profile these bindings were loaded from or last saved as
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
     /// Reverse lookup for conflict detection
     #[serde(skip)]
     keybind_to_action: HashMap<Keybind, AppAction>,
@@ -584,6 +588,7 @@ impl ShortcutRegistry {
     pub fn default_bindings() -> Self {
         let mut reg = Self {
             bindings: HashMap::new(),
+            profile: None,
             keybind_to_action: HashMap::new(),
         };
 
@@ -596,9 +601,11 @@ impl ShortcutRegistry {
         reg.bind(Record, Keybind::none(R));
         reg.bind(GoToStart, Keybind::none(Home));
         reg.bind(Rewind, Keybind::none(J));
-        reg.bind(FastForward, Keybind::none(L));
         reg.bind(Rewind, Keybind::none(Comma));
         reg.bind(FastForward, Keybind::none(K));
+        reg.bind(PlayheadLeft, Keybind::none(ArrowLeft));
+        reg.bind(PlayheadRight, Keybind::none(ArrowRight));
+        reg.bind(TapTempo, Keybind::shift(T));
 
         reg.bind(Undo, Keybind::cmd(Z));
         reg.bind(Redo, Keybind::cmd_shift(Z));
@@ -666,6 +673,7 @@ impl ShortcutRegistry {
         reg.bind(HumanizeDialog, Keybind::none(H));
         reg.bind(HumanizeDialog, Keybind::cmd(H));
 
+        reg.bind(ToggleVirtualKeyboard, Keybind::cmd_shift(K));
         reg.bind(AppAction::Escape, Keybind::none(KeyCode::Escape));
 
         reg
@@ -677,12 +685,29 @@ impl ShortcutRegistry {
         self.keybind_to_action.insert(keybind, action);
     }
 
-    /// Remove a specific binding
-    pub fn unbind(&mut self, keybind: &Keybind) {
-        self.keybind_to_action.remove(keybind);
-        for binds in self.bindings.values_mut() {
+    /// Remove a binding from one action; other actions keep the same keybind
+    pub fn unbind(&mut self, action: AppAction, keybind: &Keybind) {
+        if let Some(binds) = self.bindings.get_mut(&action) {
             binds.retain(|b| b != keybind);
         }
+        self.rebuild_reverse_index();
+    }
+
+    /// Replace every binding of an action
+    pub fn set_bindings(&mut self, action: AppAction, keybinds: &[Keybind]) {
+        self.bindings.insert(action, keybinds.to_vec());
+        self.rebuild_reverse_index();
+    }
+
+    /// Swap one of an action's bindings for another, keeping its position
+    pub fn rebind(&mut self, action: AppAction, old: &Keybind, new: Keybind) {
+        let binds = self.bindings.entry(action).or_default();
+        match binds.iter().position(|b| b == old) {
+            Some(i) => binds[i] = new,
+            None => binds.push(new),
+        }
+        binds.dedup();
+        self.rebuild_reverse_index();
     }
 
     /// Get all keybinds for an action
@@ -698,16 +723,18 @@ impl ShortcutRegistry {
         self.keybind_to_action.get(keybind).copied()
     }
 
-    /// Check if a keybind is already used
-    pub fn has_conflict(
-        &self,
-        keybind: &Keybind,
-        exclude_action: Option<AppAction>,
-    ) -> Option<AppAction> {
-        self.keybind_to_action
-            .get(keybind)
-            .copied()
-            .filter(|&a| exclude_action.map_or(true, |ex| ex != a))
+    /// Other actions bound to `keybind` that can fire alongside `action`
+    pub fn conflicts(&self, keybind: &Keybind, action: AppAction) -> Vec<AppAction> {
+        let mut found: Vec<AppAction> = self
+            .bindings
+            .iter()
+            .filter(|&(&other, binds)| {
+                other != action && action.shares_context(other) && binds.contains(keybind)
+            })
+            .map(|(&other, _)| other)
+            .collect();
+        found.sort_by_key(|a| a.name());
+        found
     }
 
     /// Save to file
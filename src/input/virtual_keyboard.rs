@@ -0,0 +1,120 @@
+use egui::{Context, Event, Key};
+
+/// Piano layout on a QWERTY row: A..K are the white keys from C, W..U the black keys
+const NOTE_KEYS: [(Key, u8); 13] = [
+    (Key::A, 0),
+    (Key::W, 1),
+    (Key::S, 2),
+    (Key::E, 3),
+    (Key::D, 4),
+    (Key::F, 5),
+    (Key::T, 6),
+    (Key::G, 7),
+    (Key::Y, 8),
+    (Key::H, 9),
+    (Key::U, 10),
+    (Key::J, 11),
+    (Key::K, 12),
+];
+
+const OCTAVE_DOWN: Key = Key::Z;
+const OCTAVE_UP: Key = Key::X;
+const MIN_OCTAVE: i8 = -1;
+const MAX_OCTAVE: i8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualKeyEvent {
+    NoteOn(u8),
+    NoteOff,
+}
+
+/// Plays notes from the computer keyboard. Previews are monophonic, so the
+/// last key pressed sounds and releasing it stops the note.
+pub struct VirtualKeyboard {
+    pub enabled: bool,
+    /// Octave of the A key, C4 = middle C
+    pub octave: i8,
+    sounding: Option<Key>,
+}
+
+impl Default for VirtualKeyboard {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            octave: 4,
+            sounding: None,
+        }
+    }
+}
+
+impl VirtualKeyboard {
+    /// Keys the keyboard claims while enabled, so shortcuts on them stay quiet
+    pub fn claims(&self, key: Key) -> bool {
+        self.enabled
+            && (key == OCTAVE_DOWN || key == OCTAVE_UP || NOTE_KEYS.iter().any(|&(k, _)| k == key))
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) -> Option<VirtualKeyEvent> {
+        self.enabled = enabled;
+        self.release()
+    }
+
+    fn release(&mut self) -> Option<VirtualKeyEvent> {
+        self.sounding.take().map(|_| VirtualKeyEvent::NoteOff)
+    }
+
+    fn pitch(&self, semitone: u8) -> Option<u8> {
+        let pitch = (self.octave as i32 + 1) * 12 + semitone as i32;
+        (0..=127).contains(&pitch).then_some(pitch as u8)
+    }
+
+    /// Note events for this frame's unmodified key presses and releases
+    pub fn poll(&mut self, ctx: &Context) -> Vec<VirtualKeyEvent> {
+        let mut out = Vec::new();
+        if !self.enabled {
+            return out;
+        }
+        // A focused text field gets the keys; don't leave a note hanging
+        if ctx.egui_wants_keyboard_input() {
+            out.extend(self.release());
+            return out;
+        }
+
+        let keys: Vec<(Key, bool)> = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|e| match e {
+                    Event::Key {
+                        key,
+                        pressed,
+                        repeat: false,
+                        modifiers,
+                        ..
+                    } if modifiers.is_none() || !pressed => Some((*key, *pressed)),
+                    _ => None,
+                })
+                .collect()
+        });
+
+        for (key, pressed) in keys {
+            match key {
+                OCTAVE_DOWN if pressed => self.octave = (self.octave - 1).max(MIN_OCTAVE),
+                OCTAVE_UP if pressed => self.octave = (self.octave + 1).min(MAX_OCTAVE),
+                _ => {
+                    let Some(&(_, semitone)) = NOTE_KEYS.iter().find(|&&(k, _)| k == key) else {
+                        continue;
+                    };
+                    if pressed {
+                        if let Some(pitch) = self.pitch(semitone) {
+                            self.sounding = Some(key);
+                            out.push(VirtualKeyEvent::NoteOn(pitch));
+                        }
+                    } else if self.sounding == Some(key) {
+                        out.extend(self.release());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
@@ -20,8 +20,11 @@ pub mod input;
 pub mod level_meter;
 pub mod messages;
 pub mod metering;
+pub mod metronome;
+pub mod midi_export;
 pub mod midi_import;
 pub mod midi_input;
+pub mod midi_output;
 pub mod midi_utils;
 pub mod mixer;
 pub mod model;
@@ -29,10 +32,12 @@ pub mod paths;
 pub mod wasm_persist;
 pub mod performance;
 pub mod plugin;
+pub mod plugin_state;
 pub mod presets;
 pub mod project;
 pub mod project_manager;
 pub mod runtime;
+pub mod spectrum;
 pub mod time_utils;
 pub mod track_manager;
 pub mod transport;
@@ -4,9 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     model::{
-        MidiNote,
-        automation::{AutomationMode, AutomationTarget},
-        clip::{AudioClip, MidiClip},
+        MidiNote, PluginDescriptor,
+        automation::{AutomationMode, AutomationPoint, AutomationTarget, CurveType},
+        clip::{AudioClip, ClipLoopMode, CrossfadeCurve, FadeCurve, MidiClip},
     },
     project::AppStateSnapshot,
 };
@@ -35,6 +35,77 @@ pub struct ExportConfig {
     pub start_beat: f64,
     pub end_beat: f64,
     pub normalize: bool,
+    /// Samples between automation evaluations, 0 = once per block
+    #[serde(default = "default_export_automation_interval")]
+    pub automation_interval: usize,
+    /// Skip the master soft clipper; float output may exceed 0 dBFS
+    #[serde(default, alias = "bypass_master_clipper")]
+    pub bypass_master_soft_clipper: bool,
+    /// Normalize against inter-sample peaks
+    #[serde(default)]
+    pub true_peak: bool,
+    /// Render a single track instead of the full mix
+    #[serde(default)]
+    pub stem: Option<StemOptions>,
+    /// Level `normalize` aims for
+    #[serde(default)]
+    pub normalize_target: NormalizeTarget,
+    /// TPDF dither ahead of quantizing below 24 bits
+    #[serde(default)]
+    pub dither: bool,
+    /// Seconds rendered past `end_beat` so effect tails ring out
+    #[serde(default)]
+    pub tail_secs: f32,
+    /// Stop the tail once the output has fallen silent instead
+    #[serde(default)]
+    pub tail_auto: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NormalizeTarget {
+    /// Peak level, dBFS
+    Peak(f32),
+    /// Integrated loudness, LUFS
+    Loudness(f32),
+}
+
+impl Default for NormalizeTarget {
+    fn default() -> Self {
+        Self::Peak(-0.1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StemOptions {
+    pub track_id: u64,
+    /// Keep the track's fader, pan and their automation
+    pub post_fader: bool,
+    /// Route through the track's sends and output bus instead of straight to master
+    pub include_sends: bool,
+}
+
+fn default_export_automation_interval() -> usize {
+    crate::constants::DEFAULT_EXPORT_AUTOMATION_INTERVAL
+}
+
+/// Output device and stream settings for the audio thread to open
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioDeviceSelection {
+    /// None opens the host's default output
+    pub output_device: Option<String>,
+    pub sample_rate: f32,
+    /// Frames per callback, 0 leaves it to the driver
+    pub buffer_size: usize,
+}
+
+impl From<&crate::config::AudioConfig> for AudioDeviceSelection {
+    fn from(config: &crate::config::AudioConfig) -> Self {
+        Self {
+            output_device: config.preferred_output_device.clone(),
+            sample_rate: config.sample_rate,
+            buffer_size: config.buffer_size,
+        }
+    }
 }
 
 use yadaw_plugin_api::{BackendKind, ParamKind};
@@ -78,20 +149,49 @@ pub enum AudioCommand {
     SetMasterVolume(f32),
 
     UpdateTracks,
+    /// Opens (Some) or closes (None) an undo batch; echoed back as
+    /// [`UIUpdate::UndoBatch`] so the undo entries pushed in between collapse
+    SetUndoBatch(Option<u64>),
 
     SetTrackVolume(u64, f32),
     SetTrackPan(u64, f32),
     SetTrackMute(u64, bool),
     SetTrackSolo(u64, bool),
+    /// Solo button press; exclusive clears the other (non-safe) solos first
+    SoloTrack {
+        track_id: u64,
+        solo: bool,
+        exclusive: bool,
+    },
+    SetTrackSoloSafe(u64, bool),
     ArmForRecording(u64, bool),
     FinalizeRecording,
     SetTrackInput(u64, Option<String>),
     SetTrackOutput(u64, Option<String>),
     SetTrackMonitor(u64, bool),
+    SetTrackMonitorGain(u64, f32), // dB
+    SetTrackInputChannel(u64, Option<usize>),
+    SetTrackCue(u64, bool),
+    SetTrackPhaseInvert(u64, bool),
+    SetTrackMono(u64, bool),
+    SetTrackOutputBus(u64, Option<u64>),
     FreezeTrack(u64),
     UnfreezeTrack(u64),
+    /// Renders exactly the range of the track through its chain, in stereo,
+    /// onto a new audio track below it. Clips straddling the range edges are
+    /// split there and the ones inside are muted, so the bounce replaces them
+    /// while they stay around for later tweaks. The new track has no plugins,
+    /// which stands in for bypassing them: plugins live on tracks, so a single
+    /// clip can't bypass them.
+    BounceRange {
+        track_id: u64,
+        start_beat: f64,
+        end_beat: f64,
+    },
 
     OpenPluginEditor(u64, u64),
+    /// Save every live plugin's state onto its descriptor, off the audio thread
+    CapturePluginStates,
 
     AddPluginUnified {
         track_id: u64,
@@ -103,13 +203,29 @@ pub enum AudioCommand {
 
     RemovePlugin(u64, u64),
     SetPluginBypass(u64, u64, bool),
+    SetPluginSidechain(u64, u64, Option<u64>), // track_id, plugin_id, source track
     SetPluginParam(u64, u64, String, f32),
-    MovePlugin(u64, usize, usize),
+    /// Moves a plugin within its chain without reinstantiating anything
+    ReorderPlugin {
+        track_id: u64,
+        from: usize,
+        to: usize,
+    },
     LoadPluginPreset(u64, usize, String),
     SavePluginPreset(u64, usize, String),
+    /// Appends a plugin with the params, state and bypass it was saved with
+    AddPluginWithState {
+        track_id: u64,
+        desc: PluginDescriptor,
+    },
+    SaveTrackTemplate(u64, String),
 
     SetLoopEnabled(bool),
     SetLoopRegion(f64, f64),
+    SetPunchEnabled(bool),
+    SetPunchRegion(f64, f64),
+    /// Reopen the output stream on another device or with other settings
+    SetAudioDevice(AudioDeviceSelection),
 
     CreateMidiClip {
         track_id: u64,
@@ -149,6 +265,15 @@ pub enum AudioCommand {
         clip_id: u64,
         new_start: f64,
     },
+    /// Shifts clips, automation and optionally markers from `from_beat` on by
+    /// `delta`, on one track or all of them (None)
+    RippleEdit {
+        track_id: Option<u64>,
+        from_beat: f64,
+        delta: f64,
+        exclude: Vec<u64>,
+        markers: bool,
+    },
     ResizeAudioClip {
         clip_id: u64,
         new_start: f64,
@@ -170,12 +295,36 @@ pub enum AudioCommand {
         clip_id: u64,
     },
     SetAudioClipGain(u64, f32),
+    /// Transposition in semitones, keeping the clip's length
+    SetAudioClipPitch(u64, f32),
     SetAudioClipFadeIn(u64, Option<f64>),
     SetAudioClipFadeOut(u64, Option<f64>),
+    SetAudioClipFadeCurve {
+        clip_id: u64,
+        fade_in: bool,
+        curve: FadeCurve,
+    },
+    /// Crossfades the clip with every audio clip it overlaps on its track
+    CrossfadeOverlaps {
+        clip_id: u64,
+    },
+    UpdateCrossfade {
+        track_id: u64,
+        crossfade_id: u64,
+        center: f64,
+        length: f64,
+        curve: CrossfadeCurve,
+    },
+    RemoveCrossfade {
+        track_id: u64,
+        crossfade_id: u64,
+    },
     SetAudioClipWarpMode(u64, bool),
+    WarpAudioClipToTempo(u64, f32), // original BPM
 
     // Automation (track ID + lane index)
-    AddAutomationPoint(u64, AutomationTarget, f64, f32),
+    /// Track, target, beat, value, and the height of the lane if this creates it
+    AddAutomationPoint(u64, AutomationTarget, f64, f32, f32),
     RemoveAutomationPoint(u64, usize, f64),
     UpdateAutomationPoint {
         track_id: u64,
@@ -184,9 +333,38 @@ pub enum AudioCommand {
         new_beat: f64,
         new_value: f32,
     },
+    /// Shape of the segment leaving the point at `beat`
+    SetAutomationCurve {
+        track_id: u64,
+        lane_idx: usize,
+        beat: f64,
+        curve: CurveType,
+        tension: f32,
+    },
     SetAutomationMode(u64, usize, AutomationMode),
+    /// Places `points` so the earliest lands on `at_beat`, replacing the
+    /// lane's points over the span they cover
+    PasteAutomation {
+        track_id: u64,
+        lane_idx: usize,
+        points: Vec<AutomationPoint>,
+        at_beat: f64,
+    },
+    /// Arms or disarms MIDI learn on a lane; the next incoming CC binds to it
+    MidiLearnAutomation(u64, usize, bool),
+    SetAutomationCc(u64, usize, Option<u8>),
+    /// A control bound to `target` was grabbed or let go, for Touch and
+    /// Latch writing
+    TouchAutomation {
+        track_id: u64,
+        target: AutomationTarget,
+        touched: bool,
+    },
     ClearAutomationLane(u64, usize),
     RemoveAutomationLane(u64, usize),
+    SetAutomationLaneVisible(u64, usize, bool),
+    /// Show or hide every automation lane on every track
+    SetAllAutomationLanesVisible(bool),
 
     // Preview (track ID)
     PreviewNote(u64, u8),
@@ -206,6 +384,14 @@ pub enum AudioCommand {
         clip_id: u64,
         enabled: bool,
     },
+    SetClipLoopMode {
+        clip_id: u64,
+        mode: ClipLoopMode,
+    },
+    SetClipLoopCrossfade {
+        clip_id: u64,
+        beats: f64,
+    },
     MakeClipAlias {
         clip_id: u64,
     },
@@ -219,6 +405,30 @@ pub enum AudioCommand {
         swing: f32,
         enabled: bool,
     },
+    /// Plays the clip with these quantize settings without storing them
+    PreviewQuantize {
+        clip_id: u64,
+        grid: f32,
+        strength: f32,
+        swing: f32,
+    },
+    EndQuantizePreview,
+    /// Choose which take of a loop-recording region plays
+    SetActiveTake {
+        track_id: u64,
+        region: u64,
+        take_id: u64,
+    },
+    /// Store the clip's timing feel on a `grid` spaced grid as a new groove template
+    ExtractGroove {
+        clip_id: u64,
+        grid: f32,
+    },
+    ApplyGroove {
+        clip_id: u64,
+        groove_id: u64,
+        strength: f32,
+    },
     DuplicateMidiClipAsAlias {
         clip_id: u64,
     },
@@ -239,6 +449,8 @@ pub enum AudioCommand {
         note_ids: Vec<u64>,
     },
     ExportAudio(ExportConfig),
+    /// Several renders run back to back, e.g. one file per marker section
+    ExportAudioBatch(Vec<ExportConfig>),
     SetTrackMidiInput(u64, Option<String>),
     MidiInput(RawMidiMessage),
     RebuildAllRtChains,
@@ -262,7 +474,15 @@ pub enum AudioCommand {
         dest_track_id: u64,
         new_start: f64,
     },
-    SetMetronome(bool),
+    SetMetronome {
+        enabled: bool,
+        count_in_bars: u32,
+    },
+    /// Load the metronome's accent and beat samples; `None` keeps the built-in tick
+    SetClickSamples {
+        accent: Option<std::path::PathBuf>,
+        click: Option<std::path::PathBuf>,
+    },
     SetSendDestination(
         u64,   /*track_id*/
         usize, /*send index*/
@@ -278,16 +498,39 @@ pub enum AudioCommand {
         note_ids: Vec<u64>,
         delta_beats: f64,
     },
+    MoveSelectedNotes {
+        clip_id: u64,
+        note_ids: Vec<u64>,
+        delta_beats: f64,
+        delta_semitones: i32,
+    },
     QuantizeSelectedNotes {
         clip_id: u64,
         note_ids: Vec<u64>,
         strength: f32,
         grid: f32,
     },
+    /// Random offsets of up to ±`timing_beats` and ±`velocity`
     HumanizeSelectedNotes {
         clip_id: u64,
         note_ids: Vec<u64>,
-        amount: f32,
+        timing_beats: f64,
+        velocity: u8,
+    },
+    LegatoSelectedNotes {
+        clip_id: u64,
+        note_ids: Vec<u64>,
+    },
+    ScaleSelectedNoteLengths {
+        clip_id: u64,
+        note_ids: Vec<u64>,
+        factor: f64,
+    },
+    /// Normalize the clip's loudest note to `target`, or set every note to it when `flatten`
+    NormalizeClipVelocities {
+        clip_id: u64,
+        target: u8,
+        flatten: bool,
     },
     AddNotesToClip {
         clip_id: u64,
@@ -313,6 +556,10 @@ pub enum AudioCommand {
     SetGroupLinkMute(u64, bool),
     SetGroupLinkSolo(u64, bool),
     ToggleGroupCollapsed(u64),
+    /// Group fader gain, applied on top of each member's volume
+    SetGroupVca(u64, f32),
+    SetGroupMute(u64, bool),
+    SetGroupSolo(u64, bool),
     SetTrackColor(u64, u8, u8, u8),
 }
 
@@ -320,10 +567,44 @@ pub enum AudioCommand {
 pub enum UIUpdate {
     Position(f64),
     TrackLevels(HashMap<u64, (f32, f32)>), // indexed for meters
+    TrackLatency(HashMap<u64, (u32, u32)>), // plugin latency, compensation delay (samples)
     RecordingFinished(u64, AudioClip),     // Track ID
+    /// One clip per pass of a loop recording, in recording order
+    TakesRecorded(u64, Vec<AudioClip>),
+    /// Notes captured from MIDI input; the clip and its notes still need ids
+    MidiRecordingFinished(u64, MidiClip),
+    /// Key from the MIDI input (pitch, velocity), velocity 0 for a release
+    MidiNoteInput(u8, u8),
+    /// The output stream was (re)opened: device name, sample rate, frames per
+    /// callback (0 when the driver decides)
+    AudioDeviceOpened {
+        device: String,
+        sample_rate: f32,
+        buffer_size: u32,
+    },
     RecordingLevel(f32),
     MasterLevel(f32, f32),
+    MasterCorrelation(f32, f32), // correlation (-1..=1), mono-sum peak
+    LoudnessMeter {
+        momentary: f32,
+        short_term: f32,
+        integrated: f32,
+    },
+    /// Plugin time of a track, in ms per audio callback
+    TrackCpu {
+        track_id: u64,
+        ms: f32,
+    },
+    /// Gain reduction in dB by plugin id, for plugins that meter it
+    PluginGainReduction(HashMap<u64, f32>),
+    /// Linear FFT magnitudes from the analyzer, `track_id` None for master
+    Spectrum {
+        track_id: Option<u64>,
+        bins: Vec<f32>,
+    },
     PushUndo(AppStateSnapshot),
+    /// Undo entries pushed until the next `UndoBatch` belong to this batch
+    UndoBatch(Option<u64>),
 
     PerformanceMetric {
         cpu_usage: f32,
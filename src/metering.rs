@@ -122,6 +122,354 @@ impl MeterData {
     }
 }
 
+const TP_OVERSAMPLE: usize = 4;
+const TP_TAPS_PER_PHASE: usize = 12;
+
+/// Inter-sample peak detector: 4x polyphase windowed-sinc interpolation,
+/// reporting the largest reconstructed value (as in ITU-R BS.1770).
+#[derive(Clone, Debug)]
+pub struct TruePeakDetector {
+    phases: [[f32; TP_TAPS_PER_PHASE]; TP_OVERSAMPLE],
+    history: [f32; TP_TAPS_PER_PHASE],
+    pos: usize,
+}
+
+impl Default for TruePeakDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TruePeakDetector {
+    pub fn new() -> Self {
+        let taps = TP_OVERSAMPLE * TP_TAPS_PER_PHASE;
+        let center = (taps - 1) as f64 / 2.0;
+        let mut phases = [[0.0f32; TP_TAPS_PER_PHASE]; TP_OVERSAMPLE];
+
+        for (p, phase) in phases.iter_mut().enumerate() {
+            let mut sum = 0.0f64;
+            let mut coeffs = [0.0f64; TP_TAPS_PER_PHASE];
+            for (j, c) in coeffs.iter_mut().enumerate() {
+                let k = p + j * TP_OVERSAMPLE;
+                let x = (k as f64 - center) / TP_OVERSAMPLE as f64;
+                let sinc = if x.abs() < 1e-9 {
+                    1.0
+                } else {
+                    (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+                };
+                // Blackman window
+                let w = 2.0 * std::f64::consts::PI * k as f64 / (taps - 1) as f64;
+                let window = 0.42 - 0.5 * w.cos() + 0.08 * (2.0 * w).cos();
+                *c = sinc * window;
+                sum += *c;
+            }
+            // Unity DC gain per phase
+            for (dst, c) in phase.iter_mut().zip(coeffs) {
+                *dst = (c / sum) as f32;
+            }
+        }
+
+        Self {
+            phases,
+            history: [0.0; TP_TAPS_PER_PHASE],
+            pos: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.history = [0.0; TP_TAPS_PER_PHASE];
+        self.pos = 0;
+    }
+
+    /// Feeds one sample and returns the absolute peak around it
+    #[inline]
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.history[self.pos] = sample;
+
+        let mut peak = sample.abs();
+        for phase in &self.phases {
+            let mut acc = 0.0f32;
+            let mut idx = self.pos;
+            for &c in phase {
+                acc += c * self.history[idx];
+                idx = (idx + TP_TAPS_PER_PHASE - 1) % TP_TAPS_PER_PHASE;
+            }
+            peak = peak.max(acc.abs());
+        }
+
+        self.pos = (self.pos + 1) % TP_TAPS_PER_PHASE;
+        peak
+    }
+}
+
+/// Stereo phase correlation and mono-sum peak, accumulated between reads
+#[derive(Clone, Debug, Default)]
+pub struct CorrelationMeter {
+    sum_lr: f64,
+    sum_ll: f64,
+    sum_rr: f64,
+    mono_peak: f32,
+}
+
+impl CorrelationMeter {
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) {
+        self.sum_lr += (l * r) as f64;
+        self.sum_ll += (l * l) as f64;
+        self.sum_rr += (r * r) as f64;
+        self.mono_peak = self.mono_peak.max(((l + r) * 0.5).abs());
+    }
+
+    /// Returns `(correlation, mono_peak)` since the last call and starts a new window.
+    /// Correlation runs from -1 (out of phase) to +1 (mono); silence reads as +1.
+    pub fn take(&mut self) -> (f32, f32) {
+        let energy = (self.sum_ll * self.sum_rr).sqrt();
+        let correlation = if energy > 1e-12 {
+            (self.sum_lr / energy).clamp(-1.0, 1.0) as f32
+        } else {
+            1.0
+        };
+        let mono_peak = self.mono_peak;
+        *self = Self::default();
+        (correlation, mono_peak)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    #[inline]
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+/// BS.1770 K-weighting (high shelf then RLB high-pass) for one channel
+#[derive(Clone, Copy, Debug)]
+struct KWeighting {
+    shelf: Biquad,
+    highpass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: f64) -> Self {
+        // Stage 1: +4 dB shelf, designed for any rate (matches the 48 kHz table)
+        let f0 = 1681.974450955533;
+        let gain_db = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad {
+            b0: (vh + vb * k / q + k * k) / a0,
+            b1: 2.0 * (k * k - vh) / a0,
+            b2: (vh - vb * k / q + k * k) / a0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Default::default()
+        };
+
+        // Stage 2: ~38 Hz high-pass
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let highpass = Biquad {
+            b0: 1.0,
+            b1: -2.0,
+            b2: 1.0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Default::default()
+        };
+
+        Self { shelf, highpass }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f64 {
+        self.highpass.process(self.shelf.process(x as f64))
+    }
+}
+
+const LUFS_SUB_BLOCKS: usize = 30; // 3 s of 100 ms steps
+const LUFS_HIST_MIN: f64 = -70.0;
+const LUFS_HIST_STEP: f64 = 0.1;
+const LUFS_HIST_BINS: usize = 800; // -70..+10 LUFS
+
+#[inline]
+fn energy_to_lufs(energy: f64) -> f32 {
+    if energy > 0.0 {
+        (-0.691 + 10.0 * energy.log10()) as f32
+    } else {
+        f32::NEG_INFINITY
+    }
+}
+
+/// ITU-R BS.1770-4 loudness of a stereo signal: momentary (400 ms), short-term (3 s)
+/// and gated integrated LUFS. Gating blocks are binned at 0.1 LU so memory stays fixed
+/// however long it runs.
+#[derive(Clone, Debug)]
+pub struct LoudnessMeter {
+    filters: [KWeighting; 2],
+    sub_block_len: usize,
+    sub_block_pos: usize,
+    sub_block_sum: f64,
+    // Mean-square energy of the most recent 100 ms steps
+    sub_blocks: [f64; LUFS_SUB_BLOCKS],
+    sub_block_count: usize,
+    sub_block_head: usize,
+    hist_count: Box<[u32]>,
+    hist_energy: Box<[f64]>,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: f64) -> Self {
+        let filter = KWeighting::new(sample_rate);
+        Self {
+            filters: [filter; 2],
+            sub_block_len: ((sample_rate * 0.1).round() as usize).max(1),
+            sub_block_pos: 0,
+            sub_block_sum: 0.0,
+            sub_blocks: [0.0; LUFS_SUB_BLOCKS],
+            sub_block_count: 0,
+            sub_block_head: 0,
+            hist_count: vec![0; LUFS_HIST_BINS].into_boxed_slice(),
+            hist_energy: vec![0.0; LUFS_HIST_BINS].into_boxed_slice(),
+        }
+    }
+
+    /// Clears all history, e.g. when playback restarts
+    pub fn reset(&mut self) {
+        for f in &mut self.filters {
+            f.shelf.reset();
+            f.highpass.reset();
+        }
+        self.sub_block_pos = 0;
+        self.sub_block_sum = 0.0;
+        self.sub_blocks = [0.0; LUFS_SUB_BLOCKS];
+        self.sub_block_count = 0;
+        self.sub_block_head = 0;
+        self.hist_count.fill(0);
+        self.hist_energy.fill(0.0);
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) {
+        let kl = self.filters[0].process(l);
+        let kr = self.filters[1].process(r);
+        self.sub_block_sum += kl * kl + kr * kr;
+        self.sub_block_pos += 1;
+
+        if self.sub_block_pos >= self.sub_block_len {
+            self.sub_blocks[self.sub_block_head] = self.sub_block_sum / self.sub_block_len as f64;
+            self.sub_block_head = (self.sub_block_head + 1) % LUFS_SUB_BLOCKS;
+            self.sub_block_count = (self.sub_block_count + 1).min(LUFS_SUB_BLOCKS);
+            self.sub_block_pos = 0;
+            self.sub_block_sum = 0.0;
+
+            // Each completed step closes a 400 ms gating block (75% overlap)
+            if self.sub_block_count >= 4 {
+                self.add_gating_block(self.recent_energy(4));
+            }
+        }
+    }
+
+    fn recent_energy(&self, steps: usize) -> f64 {
+        let n = steps.min(self.sub_block_count);
+        if n == 0 {
+            return 0.0;
+        }
+        let sum: f64 = (1..=n)
+            .map(|i| self.sub_blocks[(self.sub_block_head + LUFS_SUB_BLOCKS - i) % LUFS_SUB_BLOCKS])
+            .sum();
+        sum / n as f64
+    }
+
+    fn add_gating_block(&mut self, energy: f64) {
+        let lufs = energy_to_lufs(energy) as f64;
+        // Absolute gate
+        if lufs < LUFS_HIST_MIN {
+            return;
+        }
+        let bin = (((lufs - LUFS_HIST_MIN) / LUFS_HIST_STEP) as usize).min(LUFS_HIST_BINS - 1);
+        self.hist_count[bin] += 1;
+        self.hist_energy[bin] += energy;
+    }
+
+    pub fn momentary(&self) -> f32 {
+        energy_to_lufs(self.recent_energy(4))
+    }
+
+    pub fn short_term(&self) -> f32 {
+        energy_to_lufs(self.recent_energy(LUFS_SUB_BLOCKS))
+    }
+
+    /// Gated integrated loudness: -70 LUFS absolute, then -10 LU relative gate
+    pub fn integrated(&self) -> f32 {
+        let (count, energy) = self
+            .hist_count
+            .iter()
+            .zip(self.hist_energy.iter())
+            .fold((0u64, 0.0f64), |(c, e), (&n, &s)| (c + n as u64, e + s));
+        if count == 0 {
+            return f32::NEG_INFINITY;
+        }
+
+        let relative_gate = energy_to_lufs(energy / count as f64) as f64 - 10.0;
+        let first_bin = ((relative_gate - LUFS_HIST_MIN) / LUFS_HIST_STEP)
+            .ceil()
+            .clamp(0.0, LUFS_HIST_BINS as f64) as usize;
+        let (count, energy) = self.hist_count[first_bin..]
+            .iter()
+            .zip(self.hist_energy[first_bin..].iter())
+            .fold((0u64, 0.0f64), |(c, e), (&n, &s)| (c + n as u64, e + s));
+        if count == 0 {
+            return f32::NEG_INFINITY;
+        }
+        energy_to_lufs(energy / count as f64)
+    }
+}
+
+/// Largest sample (or inter-sample, when `true_peak`) magnitude of an interleaved buffer
+pub fn buffer_peak(samples: &[f32], channels: usize, true_peak: bool) -> f32 {
+    if !true_peak {
+        return samples.iter().copied().map(f32::abs).fold(0.0f32, f32::max);
+    }
+
+    let channels = channels.max(1);
+    let mut peak = 0.0f32;
+    for ch in 0..channels {
+        let mut detector = TruePeakDetector::new();
+        for &s in samples.iter().skip(ch).step_by(channels) {
+            peak = peak.max(detector.process(s));
+        }
+        // Flush the filter delay so the tail is measured too
+        for _ in 0..TP_TAPS_PER_PHASE {
+            peak = peak.max(detector.process(0.0));
+        }
+    }
+    peak
+}
+
 /// Draw a meter bar (can be used by both LevelMeter and VuMeter)
 pub fn draw_meter_bar(painter: &egui::Painter, rect: egui::Rect, data: &MeterData, vertical: bool) {
     // Background
@@ -211,3 +559,133 @@ pub fn draw_meter_bar(painter: &egui::Painter, rect: egui::Rect, data: &MeterDat
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Full-scale sine at fs/4, 45° off the sample grid: every sample sits
+    /// at ±0.707 while the waveform peaks at 1.0 between them
+    fn quarter_rate_sine(frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|n| {
+                let phase = std::f64::consts::FRAC_PI_2 * n as f64 + std::f64::consts::FRAC_PI_4;
+                phase.sin() as f32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sample_peak_misses_the_inter_sample_peak() {
+        let peak = buffer_peak(&quarter_rate_sine(4800), 1, false);
+        assert!((peak - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn true_peak_finds_the_inter_sample_peak() {
+        let peak = buffer_peak(&quarter_rate_sine(4800), 1, true);
+        assert!(peak > 0.98 && peak < 1.01, "true peak {peak}");
+    }
+
+    #[test]
+    fn true_peak_measures_each_channel() {
+        let interleaved: Vec<f32> = quarter_rate_sine(4800)
+            .into_iter()
+            .flat_map(|s| [s * 0.5, s])
+            .collect();
+        let peak = buffer_peak(&interleaved, 2, true);
+        assert!(peak > 0.98 && peak < 1.01, "true peak {peak}");
+    }
+
+    #[test]
+    fn true_peak_never_reads_below_the_sample_peak() {
+        let mut impulse = vec![0.0; 64];
+        impulse[10] = -0.8;
+        assert_eq!(buffer_peak(&impulse, 1, true), 0.8);
+
+        let mut detector = TruePeakDetector::new();
+        assert_eq!(detector.process(0.3), 0.3);
+        detector.reset();
+        assert_eq!(detector.process(0.0), 0.0);
+    }
+
+    const RATE: f64 = 48000.0;
+    /// One 100 ms loudness step at [`RATE`]
+    const STEP: usize = 4800;
+
+    /// Feeds `frames` of a 1 kHz sine at `amp` to the left channel, and to
+    /// the right as well when `stereo`
+    fn feed_sine(meter: &mut LoudnessMeter, amp: f32, frames: usize, stereo: bool) {
+        for n in 0..frames {
+            let s = amp * (std::f64::consts::TAU * 1000.0 * n as f64 / RATE).sin() as f32;
+            meter.process(s, if stereo { s } else { 0.0 });
+        }
+    }
+
+    fn feed_silence(meter: &mut LoudnessMeter, frames: usize) {
+        for _ in 0..frames {
+            meter.process(0.0, 0.0);
+        }
+    }
+
+    fn assert_lufs(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{actual} LUFS, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn full_scale_sine_reads_its_reference_loudness() {
+        // BS.1770: a 0 dBFS 1 kHz sine in one channel is -3.01 LUFS, and the
+        // channels' energies add, so in both it is 3.01 LU louder
+        let mut meter = LoudnessMeter::new(RATE);
+        feed_sine(&mut meter, 1.0, 50 * STEP, false);
+        assert_lufs(meter.integrated(), -3.01, 0.05);
+        assert_lufs(meter.momentary(), -3.01, 0.05);
+        assert_lufs(meter.short_term(), -3.01, 0.05);
+
+        let mut meter = LoudnessMeter::new(RATE);
+        feed_sine(&mut meter, 1.0, 50 * STEP, true);
+        assert_lufs(meter.integrated(), 0.0, 0.05);
+    }
+
+    #[test]
+    fn absolute_gate_ignores_blocks_below_minus_70() {
+        let mut meter = LoudnessMeter::new(RATE);
+        feed_sine(&mut meter, 1.0, 30 * STEP, false);
+        // -83 LUFS, three times as long: ungated it would pull the result
+        // down by about 6 LU
+        feed_sine(&mut meter, 1e-4, 90 * STEP, false);
+        // Only the blocks straddling the change count against the tone
+        assert_lufs(meter.integrated(), -3.01, 0.3);
+    }
+
+    #[test]
+    fn relative_gate_drops_blocks_10_lu_under_the_mean() {
+        let mut meter = LoudnessMeter::new(RATE);
+        feed_sine(&mut meter, 1.0, 50 * STEP, false);
+        // -33 LUFS passes the absolute gate but not the relative one
+        feed_sine(&mut meter, 10f32.powf(-30.0 / 20.0), 50 * STEP, false);
+        assert_lufs(meter.integrated(), -3.01, 0.3);
+    }
+
+    #[test]
+    fn momentary_spans_400_ms_and_short_term_3_s() {
+        let mut meter = LoudnessMeter::new(RATE);
+        feed_sine(&mut meter, 1.0, 40 * STEP, false);
+
+        // One of four momentary steps still holds the tone: 6.02 LU down.
+        // Once it has left, only the filters' ring-out is left.
+        feed_silence(&mut meter, 3 * STEP);
+        assert_lufs(meter.momentary(), -9.03, 0.1);
+        feed_silence(&mut meter, STEP);
+        assert!(meter.momentary() < -40.0);
+
+        // Ten of thirty short-term steps hold it: 4.77 LU down
+        feed_silence(&mut meter, 16 * STEP);
+        assert_lufs(meter.short_term(), -7.78, 0.1);
+        feed_silence(&mut meter, 10 * STEP);
+        assert!(meter.short_term() < -40.0);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::time_utils::TimeSignature;
+
+/// An eighth note, in quarter-note beats
+const EIGHTH: f64 = 0.5;
+/// Level of the in-between clicks relative to the beat click
+const SUBDIVISION_GAIN: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickKind {
+    /// First beat of the bar
+    Accent,
+    Beat,
+    /// Eighths between the beats
+    Subdivision,
+}
+
+/// Where the click falls for one meter. Beats follow the signature's count,
+/// except in compound meters (6/8, 9/8, 12/8) where each beat is a dotted
+/// quarter and the counts in between are its subdivisions.
+#[derive(Debug, Clone, Copy)]
+pub struct ClickPattern {
+    /// Grid spacing, in quarter-note beats
+    step: f64,
+    ticks_per_bar: i64,
+    ticks_per_beat: i64,
+}
+
+impl ClickPattern {
+    pub fn new(sig: TimeSignature, eighths: bool) -> Self {
+        let compound = sig.denominator == 8 && sig.numerator > 3 && sig.numerator % 3 == 0;
+        let beat_len = if compound {
+            3.0 * sig.beat_len()
+        } else {
+            sig.beat_len()
+        };
+        let halves = beat_len / EIGHTH;
+        let step = if eighths && halves > 1.0 && halves.fract() == 0.0 {
+            EIGHTH
+        } else {
+            beat_len
+        };
+
+        Self {
+            step,
+            ticks_per_bar: ((sig.bar_len() / step).round() as i64).max(1),
+            ticks_per_beat: ((beat_len / step).round() as i64).max(1),
+        }
+    }
+
+    /// Grid spacing, in quarter-note beats
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// What the `tick`th grid position (counted from beat 0) plays
+    pub fn kind(&self, tick: i64) -> ClickKind {
+        let in_bar = tick.rem_euclid(self.ticks_per_bar);
+        if in_bar == 0 {
+            ClickKind::Accent
+        } else if in_bar % self.ticks_per_beat == 0 {
+            ClickKind::Beat
+        } else {
+            ClickKind::Subdivision
+        }
+    }
+}
+
+/// Mono click sounds at the engine sample rate
+#[derive(Debug, Clone)]
+pub struct ClickSounds {
+    accent: Vec<f32>,
+    click: Vec<f32>,
+}
+
+impl ClickSounds {
+    /// User samples where given, the built-in ticks otherwise
+    pub fn new(sample_rate: f64, accent: Option<Vec<f32>>, click: Option<Vec<f32>>) -> Self {
+        Self {
+            accent: accent.unwrap_or_else(|| synth_tick(sample_rate, 3000.0, 0.35, 25.0)),
+            click: click.unwrap_or_else(|| synth_tick(sample_rate, 2000.0, 0.25, 15.0)),
+        }
+    }
+
+    fn sound(&self, kind: ClickKind) -> (&[f32], f32) {
+        match kind {
+            ClickKind::Accent => (&self.accent, 1.0),
+            ClickKind::Beat => (&self.click, 1.0),
+            ClickKind::Subdivision => (&self.click, SUBDIVISION_GAIN),
+        }
+    }
+}
+
+/// Short decaying cosine tick
+fn synth_tick(sample_rate: f64, freq_hz: f64, amp: f32, len_ms: f64) -> Vec<f32> {
+    let len = (len_ms / 1000.0 * sample_rate) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f64 / sample_rate;
+            let env = (-t * 60.0).exp() as f32;
+            (std::f64::consts::TAU * freq_hz * t).cos() as f32 * amp * env
+        })
+        .collect()
+}
+
+/// Linear resample of a mono buffer, for user click samples
+pub fn resample(samples: &[f32], from_rate: f64, to_rate: f64) -> Vec<f32> {
+    if samples.is_empty() || from_rate <= 0.0 || (from_rate - to_rate).abs() < 1e-6 {
+        return samples.to_vec();
+    }
+    let ratio = from_rate / to_rate;
+    let len = (samples.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let idx = pos as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = samples[idx];
+            let b = samples.get(idx + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Plays one click at a time, carrying its tail across callbacks
+pub struct ClickPlayer {
+    sounds: ClickSounds,
+    voice: Option<(ClickKind, usize)>,
+}
+
+impl ClickPlayer {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sounds: ClickSounds::new(sample_rate, None, None),
+            voice: None,
+        }
+    }
+
+    pub fn set_sounds(&mut self, sounds: ClickSounds) {
+        self.sounds = sounds;
+        self.voice = None;
+    }
+
+    /// Start a click, cutting off the one still ringing
+    pub fn trigger(&mut self, kind: ClickKind) {
+        self.voice = Some((kind, 0));
+    }
+
+    pub fn stop(&mut self) {
+        self.voice = None;
+    }
+
+    /// Mix the ringing click into interleaved `out` over frames
+    /// `start..end`, and into the stereo `cue` buffer when given
+    pub fn render(
+        &mut self,
+        out: &mut [f32],
+        channels: usize,
+        mut cue: Option<&mut [f32]>,
+        start: usize,
+        end: usize,
+    ) {
+        let Some((kind, pos)) = self.voice else {
+            return;
+        };
+        let (sound, gain) = self.sounds.sound(kind);
+        let frames = end
+            .saturating_sub(start)
+            .min(sound.len().saturating_sub(pos));
+
+        for i in 0..frames {
+            let s = sound[pos + i] * gain;
+            let base = (start + i) * channels;
+            out[base] += s;
+            if channels > 1 {
+                out[base + 1] += s;
+            }
+            if let Some(cue) = cue.as_deref_mut() {
+                cue[(start + i) * 2] += s;
+                cue[(start + i) * 2 + 1] += s;
+            }
+        }
+
+        let pos = pos + frames;
+        self.voice = (pos < sound.len()).then_some((kind, pos));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ClickKind::{Accent as A, Beat as B, Subdivision as S};
+
+    fn pattern(numerator: u32, denominator: u32, eighths: bool) -> ClickPattern {
+        ClickPattern::new(
+            TimeSignature {
+                numerator,
+                denominator,
+            },
+            eighths,
+        )
+    }
+
+    fn kinds(pattern: &ClickPattern, ticks: i64) -> Vec<ClickKind> {
+        (0..ticks).map(|t| pattern.kind(t)).collect()
+    }
+
+    #[test]
+    fn four_four() {
+        let p = pattern(4, 4, false);
+        assert_eq!(p.step(), 1.0);
+        assert_eq!(kinds(&p, 5), [A, B, B, B, A]);
+
+        let p = pattern(4, 4, true);
+        assert_eq!(p.step(), 0.5);
+        assert_eq!(kinds(&p, 9), [A, S, B, S, B, S, B, S, A]);
+    }
+
+    #[test]
+    fn three_four() {
+        let p = pattern(3, 4, false);
+        assert_eq!(p.step(), 1.0);
+        assert_eq!(kinds(&p, 4), [A, B, B, A]);
+
+        let p = pattern(3, 4, true);
+        assert_eq!(p.step(), 0.5);
+        assert_eq!(kinds(&p, 7), [A, S, B, S, B, S, A]);
+    }
+
+    #[test]
+    fn six_eight_counts_dotted_quarters() {
+        let p = pattern(6, 8, false);
+        assert_eq!(p.step(), 1.5);
+        assert_eq!(kinds(&p, 3), [A, B, A]);
+
+        let p = pattern(6, 8, true);
+        assert_eq!(p.step(), 0.5);
+        assert_eq!(kinds(&p, 7), [A, S, S, B, S, S, A]);
+    }
+
+    #[test]
+    fn twelve_eight_counts_dotted_quarters() {
+        let p = pattern(12, 8, false);
+        assert_eq!(p.step(), 1.5);
+        assert_eq!(kinds(&p, 5), [A, B, B, B, A]);
+
+        let p = pattern(12, 8, true);
+        assert_eq!(p.step(), 0.5);
+        assert_eq!(kinds(&p, 13), [A, S, S, B, S, S, B, S, S, B, S, S, A]);
+    }
+
+    #[test]
+    fn ticks_before_zero_follow_the_bar() {
+        let p = pattern(4, 4, false);
+        assert_eq!(p.kind(-4), A);
+        assert_eq!(p.kind(-1), B);
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::{Result, anyhow, bail};
+use std::path::Path;
+
+use midly::num::{u4, u7, u15, u24, u28};
+use midly::{Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+
+use crate::audio_state::MidiClipSnapshot;
+use crate::model::track::TrackType;
+use crate::project::AppState;
+
+pub const TICKS_PER_QUARTER: u16 = 960;
+
+/// (tick, is_note_on, key, velocity)
+type NoteEvent = (u64, bool, u8, u8);
+
+pub fn export_midi_file(state: &AppState, path: &Path) -> Result<()> {
+    let data = export_midi_data(state)?;
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+/// Type-1 SMF: a conductor track with tempo and meter, then one track per MIDI track
+pub fn export_midi_data(state: &AppState) -> Result<Vec<u8>> {
+    let tracks: Vec<_> = crate::audio_snapshot::build_track_snapshots(state)
+        .into_iter()
+        .filter(|t| matches!(t.track_type, TrackType::Midi))
+        .collect();
+    if tracks.is_empty() {
+        bail!("The project has no MIDI tracks to export.");
+    }
+
+    let mut smf = Smf::new(Header::new(
+        Format::Parallel,
+        Timing::Metrical(u15::new(TICKS_PER_QUARTER)),
+    ));
+
+    let tempo = (60_000_000.0 / state.bpm.max(1.0) as f64).round() as u32;
+    let sig = state.time_signature;
+    smf.tracks.push(vec![
+        meta(MetaMessage::Tempo(u24::new(tempo.min(0xFF_FFFF)))),
+        meta(MetaMessage::TimeSignature(
+            sig.numerator.clamp(1, 255) as u8,
+            sig.denominator.max(1).ilog2() as u8,
+            24,
+            8,
+        )),
+        meta(MetaMessage::EndOfTrack),
+    ]);
+
+    for track in &tracks {
+        let mut notes: Vec<NoteEvent> = Vec::new();
+        for clip in &track.midi_clips {
+            clip_note_events(clip, &mut notes);
+        }
+        // Note-offs before note-ons on the same tick so repeated notes retrigger
+        notes.sort_by_key(|&(tick, on, key, _)| (tick, on, key));
+
+        let mut events = Vec::with_capacity(notes.len() + 2);
+        events.push(meta(MetaMessage::TrackName(track.name.as_bytes())));
+        let mut last_tick = 0u64;
+        for (tick, on, key, vel) in notes {
+            let delta = (tick - last_tick).min(0x0FFF_FFFF) as u32;
+            last_tick = tick;
+            let key = u7::new(key);
+            let message = if on {
+                MidiMessage::NoteOn {
+                    key,
+                    vel: u7::new(vel),
+                }
+            } else {
+                MidiMessage::NoteOff {
+                    key,
+                    vel: u7::new(0),
+                }
+            };
+            events.push(TrackEvent {
+                delta: u28::new(delta),
+                kind: TrackEventKind::Midi {
+                    channel: u4::new(0),
+                    message,
+                },
+            });
+        }
+        events.push(meta(MetaMessage::EndOfTrack));
+        smf.tracks.push(events);
+    }
+
+    let mut out = Vec::new();
+    smf.write_std(&mut out)
+        .map_err(|e| anyhow!("MIDI write failed: {e}"))?;
+    Ok(out)
+}
+
+fn meta(message: MetaMessage<'_>) -> TrackEvent<'_> {
+    TrackEvent {
+        delta: u28::new(0),
+        kind: TrackEventKind::Meta(message),
+    }
+}
+
+#[inline]
+fn beat_to_tick(beat: f64) -> u64 {
+    (beat.max(0.0) * TICKS_PER_QUARTER as f64).round() as u64
+}
+
+/// Expands a clip's notes the way playback does: content offset, loop repeats
+/// (wrapping notes that cross the loop end), reversed passes, transpose,
+/// velocity offset and, when enabled, the clip's quantize settings.
+fn clip_note_events(clip: &MidiClipSnapshot, out: &mut Vec<NoteEvent>) {
+    let clip_start = clip.start_beat;
+    let clip_end = clip.start_beat + clip.length_beats.max(0.0);
+    let content_len = clip.content_len_beats.max(0.000001);
+    let repeats = if clip.loop_enabled {
+        (clip.length_beats / content_len).ceil().max(1.0) as i32
+    } else {
+        1
+    };
+    let offset = clip.content_offset_beats.rem_euclid(content_len);
+
+    for k in 0..repeats {
+        let rep_off = clip_start + k as f64 * content_len;
+        let rep_end = (rep_off + content_len).min(clip_end);
+        let reversed = clip.loop_enabled && clip.loop_mode.is_reversed(k as i64);
+
+        for n in &clip.notes {
+            let s_loc = (n.start + offset).rem_euclid(content_len);
+            let e_loc_raw = s_loc + n.duration;
+
+            let segs = if e_loc_raw <= content_len {
+                [(s_loc, e_loc_raw), (0.0, 0.0)]
+            } else {
+                [(s_loc, content_len), (0.0, e_loc_raw - content_len)]
+            };
+
+            for (s_local, e_local) in segs {
+                if e_local <= s_local {
+                    continue;
+                }
+                let (s_local, e_local) = if reversed {
+                    (content_len - e_local, content_len - s_local)
+                } else {
+                    (s_local, e_local)
+                };
+                let start = rep_off + s_local;
+                let end = (rep_off + e_local).min(rep_end);
+                if start >= rep_end || end <= start {
+                    continue;
+                }
+
+                let pitch = (n.pitch as i16 + clip.transpose as i16).clamp(0, 127) as u8;
+                let vel = (n.velocity as i16 + clip.velocity_offset as i16).clamp(1, 127) as u8;
+
+                let start = crate::audio::quantize_beat(start, clip);
+                let end = crate::audio::quantize_beat(end, clip).max(start + 1e-6);
+
+                let start_tick = beat_to_tick(start);
+                let end_tick = beat_to_tick(end).max(start_tick + 1);
+                out.push((start_tick, true, pitch, vel));
+                out.push((end_tick, false, pitch, 0));
+            }
+        }
+    }
+}
+
+/// Browsers can't write files directly, so hand the bytes to a download link
+#[cfg(target_arch = "wasm32")]
+pub fn download_midi(state: &AppState, filename: &str) -> Result<()> {
+    use wasm_bindgen::JsCast;
+    use web_sys::HtmlAnchorElement;
+
+    let data = export_midi_data(state)?;
+    let uint8 = js_sys::Uint8Array::from(&data[..]);
+    let parts = js_sys::Array::new();
+    parts.push(&uint8.into());
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)
+        .map_err(|e| anyhow!("Failed to create Blob: {:?}", e))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|e| anyhow!("Failed to create object URL: {:?}", e))?;
+
+    let window = web_sys::window().ok_or_else(|| anyhow!("No window"))?;
+    let document = window.document().ok_or_else(|| anyhow!("No document"))?;
+    let anchor = document
+        .create_element("a")
+        .map_err(|e| anyhow!("Failed to create anchor: {:?}", e))?
+        .dyn_into::<HtmlAnchorElement>()
+        .map_err(|e| anyhow!("Failed to cast anchor: {:?}", e))?;
+
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
+}
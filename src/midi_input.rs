@@ -1,24 +1,57 @@
 use anyhow::{Result, anyhow};
 use flume::Sender;
 use midir::{Ignore, MidiInput, MidiInputConnection, MidiInputPort};
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 
-use crate::messages::RawMidiMessage;
+use crate::audio_state::{AudioState, RealtimeCommand};
+use crate::messages::{AudioCommand, RawMidiMessage};
+use crate::time_utils::quick::samples_to_beats;
+
+type RealtimeTx = wasm_safe_mutex::mpsc::Sender<RealtimeCommand>;
+
+const CLOCK_PPQN: f64 = 24.0;
+const MIDI_CLOCK: u8 = 0xF8;
+const MIDI_START: u8 = 0xFA;
+const MIDI_CONTINUE: u8 = 0xFB;
+const MIDI_STOP: u8 = 0xFC;
+const MIDI_SONG_POSITION: u8 = 0xF2;
+const MIDI_QUARTER_FRAME: u8 = 0xF1;
+/// Timecode frame rates by the rate bits of quarter-frame piece 7
+const MTC_FRAME_RATES: [f64; 4] = [24.0, 25.0, 29.97, 30.0];
+
+// Clock intervals outside 20..=400 BPM are treated as dropouts
+const MIN_CLOCK_INTERVAL_US: f64 = 60_000_000.0 / (400.0 * CLOCK_PPQN);
+const MAX_CLOCK_INTERVAL_US: f64 = 60_000_000.0 / (20.0 * CLOCK_PPQN);
+// Loop filter gains: period tracking and phase correction
+const PERIOD_GAIN: f64 = 0.05;
+const PHASE_GAIN: f64 = 0.1;
+const MAX_PHASE_CORRECTION: f64 = 0.05;
+// Beyond this phase error we relocate instead of chasing
+const RELOCATE_BEATS: f64 = 0.5;
 
 pub struct MidiInputHandler {
     midi_in: Arc<Mutex<MidiInput>>,
     connection: Arc<Mutex<Option<MidiInputConnection<()>>>>,
     command_tx: Sender<crate::messages::AudioCommand>,
+    realtime_tx: RealtimeTx,
+    audio_state: Arc<AudioState>,
     pub connected_port_name: Arc<Mutex<Option<String>>>,
 }
 
 impl MidiInputHandler {
-    pub fn new(command_tx: Sender<crate::messages::AudioCommand>) -> Result<Self> {
+    pub fn new(
+        command_tx: Sender<crate::messages::AudioCommand>,
+        realtime_tx: RealtimeTx,
+        audio_state: Arc<AudioState>,
+    ) -> Result<Self> {
         let midi_in = MidiInput::new("YADAW-MIDI-Input")?;
         Ok(Self {
             midi_in: Arc::new(Mutex::new(midi_in)),
             connection: Arc::new(Mutex::new(None)),
             command_tx,
+            realtime_tx,
+            audio_state,
             connected_port_name: Arc::new(Mutex::new(None)),
         })
     }
@@ -38,17 +71,19 @@ impl MidiInputHandler {
         };
 
         let mut midi_in_for_connection = MidiInput::new(&format!("YADAW-conn-{}", port_name))?;
-        // Disable unnecessary logging from this temporary instance.
-        midi_in_for_connection.ignore(Ignore::All);
+        // Timing messages are needed for external sync.
+        midi_in_for_connection.ignore(Ignore::SysexAndActiveSense);
 
         let port_name_clone = port_name.to_string();
         let command_tx_clone = self.command_tx.clone();
         let connected_port_name_clone = self.connected_port_name.clone();
+        let mut clock_sync = ClockSync::new(
+            self.audio_state.clone(),
+            self.command_tx.clone(),
+            self.realtime_tx.clone(),
+        );
 
-        let initial_time = web_time::SystemTime::now()
-            .duration_since(web_time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+        let mut stamp_clock = StampClock::default();
 
         log::info!("Attempting to connect to MIDI port: {}", port_name_clone);
 
@@ -57,9 +92,16 @@ impl MidiInputHandler {
                 &port_to_connect,
                 &port_name_clone,
                 move |stamp, message, _| {
+                    if message.first().is_some_and(|&status| status >= 0xF0) {
+                        clock_sync.handle(stamp, message);
+                        return;
+                    }
                     if message.len() == 3 {
+                        let now_us = web_time::SystemTime::now()
+                            .duration_since(web_time::UNIX_EPOCH)
+                            .map_or(0, |d| d.as_micros() as u64);
                         let raw_message = RawMidiMessage {
-                            timestamp_us: initial_time + stamp,
+                            timestamp_us: stamp_clock.to_wall(stamp, now_us),
                             message: [message[0], message[1], message[2]],
                         };
                         let _ = command_tx_clone
@@ -99,3 +141,365 @@ impl MidiInputHandler {
             .collect()
     }
 }
+
+/// Slaves the transport to incoming MIDI clock, or to MIDI timecode, when
+/// external sync is on. Tempo and position changes go to the engine through
+/// the realtime queue, so it applies them between callbacks.
+///
+/// Clock drives tempo through [`ClockPll`]; song position and start/stop
+/// messages place and run the transport. Timecode carries no tempo, so the
+/// project tempo stays and the engine only chases the position, stopping
+/// when the frames stop arriving.
+struct ClockSync {
+    audio_state: Arc<AudioState>,
+    command_tx: Sender<AudioCommand>,
+    realtime_tx: RealtimeTx,
+    pll: ClockPll,
+    timecode: TimecodeReader,
+    running: bool,
+    base_beat: f64,
+    clocks: u64,
+}
+
+impl ClockSync {
+    fn new(
+        audio_state: Arc<AudioState>,
+        command_tx: Sender<AudioCommand>,
+        realtime_tx: RealtimeTx,
+    ) -> Self {
+        Self {
+            audio_state,
+            command_tx,
+            realtime_tx,
+            pll: ClockPll::default(),
+            timecode: TimecodeReader::default(),
+            running: false,
+            base_beat: 0.0,
+            clocks: 0,
+        }
+    }
+
+    fn handle(&mut self, stamp_us: u64, message: &[u8]) {
+        if !self.audio_state.external_sync.load(Ordering::Relaxed) {
+            self.running = false;
+            self.pll = ClockPll::default();
+            self.timecode = TimecodeReader::default();
+            return;
+        }
+
+        match message[0] {
+            MIDI_CLOCK => self.on_clock(stamp_us),
+            MIDI_START => {
+                self.relocate(0.0);
+                self.running = true;
+                let _ = self.command_tx.send(AudioCommand::Play);
+            }
+            MIDI_CONTINUE => {
+                self.base_beat = self.current_beat();
+                self.clocks = 0;
+                self.running = true;
+                let _ = self.command_tx.send(AudioCommand::Play);
+            }
+            MIDI_STOP => {
+                self.running = false;
+                let _ = self.command_tx.send(AudioCommand::Stop);
+                // Make the followed tempo the project tempo
+                let bpm = (self.audio_state.bpm.load() * 10.0).round() / 10.0;
+                let _ = self.command_tx.send(AudioCommand::SetBPM(bpm));
+            }
+            MIDI_SONG_POSITION if message.len() >= 3 => {
+                let sixteenths = message[1] as u32 | ((message[2] as u32) << 7);
+                self.relocate(sixteenths as f64 / 4.0);
+            }
+            MIDI_QUARTER_FRAME if message.len() >= 2 => {
+                if let Some(seconds) = self.timecode.quarter_frame(message[1]) {
+                    let _ = self
+                        .realtime_tx
+                        .send_sync(RealtimeCommand::SyncTimecode(seconds));
+                    if !self.audio_state.playing.load(Ordering::Relaxed) {
+                        let _ = self.command_tx.send(AudioCommand::Play);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_clock(&mut self, stamp_us: u64) {
+        if self.running {
+            self.clocks += 1;
+        }
+        let Some(tempo) = self.pll.tick(stamp_us) else {
+            return;
+        };
+
+        if !self.running {
+            // Follow tempo while stopped so playback starts at the right speed
+            self.set_tempo(tempo as f32);
+            return;
+        }
+
+        let expected = self.base_beat + self.clocks as f64 / CLOCK_PPQN;
+        match phase_corrected_tempo(tempo, expected - self.current_beat()) {
+            Some(bpm) => self.set_tempo(bpm as f32),
+            None => {
+                self.set_tempo(tempo as f32);
+                let _ = self
+                    .realtime_tx
+                    .send_sync(RealtimeCommand::SyncLocate(expected));
+            }
+        }
+    }
+
+    fn relocate(&mut self, beat: f64) {
+        self.base_beat = beat;
+        self.clocks = 0;
+        let _ = self
+            .realtime_tx
+            .send_sync(RealtimeCommand::SyncLocate(beat));
+    }
+
+    fn set_tempo(&self, bpm: f32) {
+        if bpm.is_finite() && bpm > 0.0 {
+            let _ = self.realtime_tx.send_sync(RealtimeCommand::SyncTempo(bpm));
+        }
+    }
+
+    fn current_beat(&self) -> f64 {
+        samples_to_beats(
+            self.audio_state.get_position(),
+            self.audio_state.sample_rate.load(),
+            self.audio_state.bpm.load(),
+        )
+    }
+}
+
+/// Maps midir port timestamps onto wall-clock microseconds. Each backend
+/// counts from its own origin (port open on ALSA and Windows, host boot on
+/// CoreMIDI), so the origin is estimated as the smallest `now - stamp` seen:
+/// the message delivered with the least delay.
+#[derive(Debug, Default)]
+struct StampClock {
+    origin_us: Option<u64>,
+}
+
+impl StampClock {
+    fn to_wall(&mut self, stamp_us: u64, now_us: u64) -> u64 {
+        let origin = now_us.saturating_sub(stamp_us);
+        let origin = self.origin_us.map_or(origin, |o| o.min(origin));
+        self.origin_us = Some(origin);
+        origin + stamp_us
+    }
+}
+
+/// Tracks the tempo of a MIDI clock: the interval between clocks is
+/// low-pass filtered, and intervals outside 20..=400 BPM count as dropouts.
+#[derive(Debug, Default)]
+struct ClockPll {
+    last_stamp_us: Option<u64>,
+    period_us: Option<f64>,
+}
+
+impl ClockPll {
+    /// Feeds one clock, returning the filtered tempo once there is one
+    fn tick(&mut self, stamp_us: u64) -> Option<f64> {
+        if let Some(last) = self.last_stamp_us {
+            let interval = stamp_us.saturating_sub(last) as f64;
+            if (MIN_CLOCK_INTERVAL_US..=MAX_CLOCK_INTERVAL_US).contains(&interval) {
+                let period = self.period_us.get_or_insert(interval);
+                *period += PERIOD_GAIN * (interval - *period);
+            } else {
+                self.period_us = None;
+            }
+        }
+        self.last_stamp_us = Some(stamp_us);
+        self.period_us
+            .map(|period| 60_000_000.0 / (period * CLOCK_PPQN))
+    }
+}
+
+/// Bends `tempo` slightly so the playhead closes a phase `error` (in beats,
+/// positive when behind) without jumping; None when it is too far off to
+/// chase and should relocate instead.
+fn phase_corrected_tempo(tempo: f64, error: f64) -> Option<f64> {
+    if error.abs() > RELOCATE_BEATS {
+        return None;
+    }
+    let correction = (error * PHASE_GAIN).clamp(-MAX_PHASE_CORRECTION, MAX_PHASE_CORRECTION);
+    Some(tempo * (1.0 + correction))
+}
+
+/// Assembles MIDI timecode from quarter-frame messages
+#[derive(Debug, Default)]
+struct TimecodeReader {
+    pieces: [u8; 8],
+    /// Bit per piece received since piece 0
+    seen: u8,
+}
+
+impl TimecodeReader {
+    /// Feeds the data byte of a quarter frame, returning the time in seconds
+    /// once all eight pieces have arrived in order
+    fn quarter_frame(&mut self, data: u8) -> Option<f64> {
+        let piece = ((data >> 4) & 0x07) as usize;
+        if piece == 0 {
+            self.seen = 0;
+        }
+        self.pieces[piece] = data & 0x0F;
+        self.seen |= 1 << piece;
+        if piece != 7 || self.seen != 0xFF {
+            return None;
+        }
+        self.seen = 0;
+
+        let p = &self.pieces;
+        let frames = p[0] | (p[1] << 4);
+        let seconds = p[2] | (p[3] << 4);
+        let minutes = p[4] | (p[5] << 4);
+        let hours = p[6] | ((p[7] & 0x01) << 4);
+        let fps = MTC_FRAME_RATES[((p[7] >> 1) & 0x03) as usize];
+        // The time was sent from piece 0, two frames ago
+        Some(
+            hours as f64 * 3600.0
+                + minutes as f64 * 60.0
+                + seconds as f64
+                + (frames as f64 + 2.0) / fps,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period_us(bpm: f64) -> f64 {
+        60_000_000.0 / (bpm * CLOCK_PPQN)
+    }
+
+    /// Feeds `count` clocks from `start_us`, with `jitter(i)` added to each
+    fn run(
+        pll: &mut ClockPll,
+        start_us: f64,
+        bpm: f64,
+        count: usize,
+        jitter: impl Fn(usize) -> f64,
+    ) -> (f64, Option<f64>) {
+        let mut t = start_us;
+        let mut tempo = None;
+        for i in 0..count {
+            t += period_us(bpm);
+            tempo = pll.tick((t + jitter(i)) as u64);
+        }
+        (t, tempo)
+    }
+
+    #[test]
+    fn steady_clock_locks_to_its_tempo() {
+        let mut pll = ClockPll::default();
+        assert_eq!(pll.tick(0), None);
+        let (_, tempo) = run(&mut pll, 0.0, 120.0, 100, |_| 0.0);
+        assert!((tempo.unwrap() - 120.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn jitter_is_smoothed() {
+        let mut pll = ClockPll::default();
+        let jitter = |i: usize| if i % 2 == 0 { 2000.0 } else { -2000.0 };
+        let (t, _) = run(&mut pll, 0.0, 120.0, 100, jitter);
+        // Each raw interval is off by about 19 BPM; the filtered tempo stays close
+        for i in 0..48 {
+            let stamp = t + (i + 1) as f64 * period_us(120.0) + jitter(i);
+            let tempo = pll.tick(stamp as u64).unwrap();
+            assert!((tempo - 120.0).abs() < 1.0, "{tempo}");
+        }
+    }
+
+    #[test]
+    fn tempo_change_is_followed() {
+        let mut pll = ClockPll::default();
+        let (t, _) = run(&mut pll, 0.0, 120.0, 100, |_| 0.0);
+        let (_, tempo) = run(&mut pll, t, 140.0, 200, |_| 0.0);
+        assert!((tempo.unwrap() - 140.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn dropout_restarts_tracking() {
+        let mut pll = ClockPll::default();
+        let (t, _) = run(&mut pll, 0.0, 120.0, 50, |_| 0.0);
+        assert_eq!(pll.tick(t as u64 + 1_000_000), None);
+        let (_, tempo) = run(&mut pll, t + 1_000_000.0, 90.0, 1, |_| 0.0);
+        assert!((tempo.unwrap() - 90.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn phase_error_bends_tempo_or_relocates() {
+        assert_eq!(phase_corrected_tempo(120.0, 0.0), Some(120.0));
+        assert!(phase_corrected_tempo(120.0, 0.1).unwrap() > 120.0);
+        assert!(phase_corrected_tempo(120.0, -0.1).unwrap() < 120.0);
+        let max = 120.0 * (1.0 + MAX_PHASE_CORRECTION);
+        assert!(phase_corrected_tempo(120.0, RELOCATE_BEATS).unwrap() <= max);
+        assert_eq!(phase_corrected_tempo(120.0, RELOCATE_BEATS + 0.01), None);
+    }
+
+    /// Quarter-frame data bytes for hh:mm:ss:ff at rate code `rate`
+    fn quarter_frames(h: u8, m: u8, s: u8, f: u8, rate: u8) -> [u8; 8] {
+        let pieces = [
+            f & 0x0F,
+            f >> 4,
+            s & 0x0F,
+            s >> 4,
+            m & 0x0F,
+            m >> 4,
+            h & 0x0F,
+            (h >> 4) | (rate << 1),
+        ];
+        std::array::from_fn(|i| ((i as u8) << 4) | pieces[i])
+    }
+
+    #[test]
+    fn timecode_is_read_after_eight_quarter_frames() {
+        let mut reader = TimecodeReader::default();
+        let data = quarter_frames(1, 2, 3, 4, 1);
+        for &byte in &data[..7] {
+            assert_eq!(reader.quarter_frame(byte), None);
+        }
+        let seconds = reader.quarter_frame(data[7]).unwrap();
+        assert!((seconds - (3723.0 + 6.0 / 25.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn timecode_waits_for_a_full_sequence() {
+        let mut reader = TimecodeReader::default();
+        let data = quarter_frames(0, 0, 10, 0, 3);
+        // Joined halfway through: nothing until a sequence runs from piece 0
+        for &byte in &data[4..] {
+            assert_eq!(reader.quarter_frame(byte), None);
+        }
+        let mut last = None;
+        for &byte in &data {
+            last = reader.quarter_frame(byte);
+        }
+        assert!((last.unwrap() - (10.0 + 2.0 / 30.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn port_stamps_map_onto_the_wall_clock() {
+        // CoreMIDI-style stamps: host time, nowhere near the epoch
+        let origin = 1_700_000_000_000_000;
+        let mut clock = StampClock::default();
+        // First message arrives 2 ms late, the next one on time
+        assert_eq!(
+            clock.to_wall(5_000_000, origin + 5_002_000),
+            origin + 5_000_000 + 2_000
+        );
+        assert_eq!(
+            clock.to_wall(6_000_000, origin + 6_000_000),
+            origin + 6_000_000
+        );
+        // A message delivered late keeps the time it was stamped with
+        assert_eq!(
+            clock.to_wall(7_000_000, origin + 7_003_000),
+            origin + 7_000_000
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::{Result, anyhow};
+use midir::{MidiOutput, MidiOutputConnection, MidiOutputPort};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use web_time::Instant;
+
+use crate::audio_state::AudioState;
+
+const CLOCK_PPQN: f64 = 24.0;
+const MIDI_CLOCK: u8 = 0xF8;
+const MIDI_START: u8 = 0xFA;
+const MIDI_CONTINUE: u8 = 0xFB;
+const MIDI_STOP: u8 = 0xFC;
+const MIDI_SONG_POSITION: u8 = 0xF2;
+
+/// Sends MIDI clock (24 ppqn) and start/stop/continue following the transport,
+/// so external gear can use YADAW as tempo master.
+pub struct MidiClockOutput {
+    midi_out: Arc<Mutex<MidiOutput>>,
+    connection: Arc<Mutex<Option<MidiOutputConnection>>>,
+    enabled: Arc<AtomicBool>,
+    shutdown: Arc<AtomicBool>,
+    pub connected_port_name: Arc<Mutex<Option<String>>>,
+}
+
+impl MidiClockOutput {
+    pub fn new(audio_state: Arc<AudioState>) -> Result<Self> {
+        let midi_out = MidiOutput::new("YADAW-MIDI-Output")?;
+        let output = Self {
+            midi_out: Arc::new(Mutex::new(midi_out)),
+            connection: Arc::new(Mutex::new(None)),
+            enabled: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            connected_port_name: Arc::new(Mutex::new(None)),
+        };
+        output.spawn_clock_thread(audio_state);
+        Ok(output)
+    }
+
+    pub fn connect(&self, port_name: &str) -> Result<()> {
+        self.disconnect();
+
+        let port: MidiOutputPort = {
+            let midi_out_guard = self.midi_out.lock().unwrap();
+            midi_out_guard
+                .ports()
+                .iter()
+                .find(|p| midi_out_guard.port_name(p).as_deref() == Ok(port_name))
+                .ok_or_else(|| anyhow!("MIDI output port not found: {}", port_name))?
+                .clone()
+        };
+
+        let midi_out_for_connection = MidiOutput::new(&format!("YADAW-out-{}", port_name))?;
+        let conn = midi_out_for_connection
+            .connect(&port, port_name)
+            .map_err(|e| anyhow!("Failed to connect to MIDI output '{}': {}", port_name, e))?;
+
+        *self.connection.lock().unwrap() = Some(conn);
+        *self.connected_port_name.lock().unwrap() = Some(port_name.to_string());
+        log::info!("MIDI clock output connected to {}", port_name);
+
+        Ok(())
+    }
+
+    pub fn disconnect(&self) {
+        if let Some(mut conn) = self.connection.lock().unwrap().take() {
+            // Don't leave slaves running
+            let _ = conn.send(&[MIDI_STOP]);
+            conn.close();
+            *self.connected_port_name.lock().unwrap() = None;
+            log::info!("MIDI output connection closed.");
+        }
+    }
+
+    pub fn list_ports(&self) -> Vec<String> {
+        let midi_out_guard = self.midi_out.lock().unwrap();
+        midi_out_guard
+            .ports()
+            .iter()
+            .filter_map(|p| midi_out_guard.port_name(p).ok())
+            .collect()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_clock_thread(&self, audio_state: Arc<AudioState>) {
+        let connection = self.connection.clone();
+        let enabled = self.enabled.clone();
+        let shutdown = self.shutdown.clone();
+
+        std::thread::spawn(move || {
+            let mut clock = ClockState::default();
+            while !shutdown.load(Ordering::Relaxed) {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+
+                let mut guard = connection.lock().unwrap();
+                match guard.as_mut() {
+                    Some(conn) if enabled.load(Ordering::Relaxed) => {
+                        clock.tick(conn, &audio_state);
+                    }
+                    _ => {
+                        if clock.was_playing
+                            && let Some(conn) = guard.as_mut()
+                        {
+                            let _ = conn.send(&[MIDI_STOP]);
+                        }
+                        clock = ClockState::default();
+                    }
+                }
+            }
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn spawn_clock_thread(&self, _audio_state: Arc<AudioState>) {
+        log::warn!("MIDI clock output is not supported on wasm");
+    }
+}
+
+impl Drop for MidiClockOutput {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        self.disconnect();
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+#[derive(Default)]
+struct ClockState {
+    was_playing: bool,
+    last_tick: i64,
+    // Audio position only advances once per audio block, so extrapolate between updates
+    last_position: f64,
+    last_position_at: Option<Instant>,
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+impl ClockState {
+    fn tick(&mut self, conn: &mut MidiOutputConnection, audio_state: &AudioState) {
+        let playing = audio_state.playing.load(Ordering::Relaxed);
+
+        if !playing {
+            if self.was_playing {
+                let _ = conn.send(&[MIDI_STOP]);
+                self.was_playing = false;
+            }
+            self.last_position_at = None;
+            return;
+        }
+
+        let sample_rate = audio_state.sample_rate.load();
+        let bpm = audio_state.bpm.load();
+        if sample_rate <= 0.0 || bpm <= 0.0 {
+            return;
+        }
+
+        let position = self.estimated_position(audio_state.get_position(), sample_rate as f64);
+        let beats = crate::time_utils::quick::samples_to_beats(position, sample_rate, bpm);
+        let current_tick = (beats * CLOCK_PPQN).floor() as i64;
+
+        if !self.was_playing {
+            if current_tick <= 0 {
+                let _ = conn.send(&[MIDI_START]);
+            } else {
+                Self::send_song_position(conn, beats);
+                let _ = conn.send(&[MIDI_CONTINUE]);
+            }
+            self.was_playing = true;
+            self.last_tick = current_tick - 1;
+        } else if current_tick < self.last_tick || current_tick > self.last_tick + CLOCK_PPQN as i64
+        {
+            // Loop wrap or seek while playing: relocate the slaves
+            let _ = conn.send(&[MIDI_STOP]);
+            Self::send_song_position(conn, beats);
+            let _ = conn.send(&[MIDI_CONTINUE]);
+            self.last_tick = current_tick - 1;
+        }
+
+        while self.last_tick < current_tick {
+            let _ = conn.send(&[MIDI_CLOCK]);
+            self.last_tick += 1;
+        }
+    }
+
+    fn estimated_position(&mut self, reported: f64, sample_rate: f64) -> f64 {
+        let now = Instant::now();
+        match self.last_position_at {
+            Some(at) if reported == self.last_position => {
+                let elapsed = now.duration_since(at).as_secs_f64().min(0.05);
+                reported + elapsed * sample_rate
+            }
+            _ => {
+                self.last_position = reported;
+                self.last_position_at = Some(now);
+                reported
+            }
+        }
+    }
+
+    /// Song Position Pointer counts sixteenth notes
+    fn send_song_position(conn: &mut MidiOutputConnection, beats: f64) {
+        let sixteenths = ((beats * 4.0).floor().max(0.0) as u32).min(0x3FFF);
+        let _ = conn.send(&[
+            MIDI_SONG_POSITION,
+            (sixteenths & 0x7F) as u8,
+            ((sixteenths >> 7) & 0x7F) as u8,
+        ]);
+    }
+}
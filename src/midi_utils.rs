@@ -1,3 +1,5 @@
+use crate::model::MidiNote;
+
 /// MIDI note utilities and conversions
 pub struct MidiNoteUtils;
 
@@ -115,6 +117,31 @@ impl MidiVelocity {
 
         (curved * 127.0).round().clamp(0.0, 127.0) as u8
     }
+
+    /// Scale velocities so the loudest note hits `target`, keeping relative dynamics.
+    /// Never drops a note below 1 so nothing goes silent.
+    pub fn normalize(notes: &mut [MidiNote], target: u8) {
+        let target = target.clamp(1, 127);
+        let Some(peak) = notes.iter().map(|n| n.velocity).max() else {
+            return;
+        };
+        if peak == 0 {
+            Self::flatten(notes, target);
+            return;
+        }
+        let gain = target as f32 / peak as f32;
+        for note in notes {
+            note.velocity = (note.velocity as f32 * gain).round().clamp(1.0, 127.0) as u8;
+        }
+    }
+
+    /// Set every note to the same velocity (clamped to 1..=127)
+    pub fn flatten(notes: &mut [MidiNote], velocity: u8) {
+        let velocity = velocity.clamp(1, 127);
+        for note in notes {
+            note.velocity = velocity;
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1,9 +1,77 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AutomationPoint {
     pub beat: f64,
     pub value: f32,
+    /// Shape of the segment from this point to the next
+    #[serde(default)]
+    pub curve: CurveType,
+    /// Bend of a curved segment, -1..=1
+    #[serde(default)]
+    pub tension: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CurveType {
+    #[default]
+    Linear,
+    /// Holds the value until the next point
+    Hold,
+    SCurve,
+    Exponential,
+}
+
+impl CurveType {
+    pub const ALL: [CurveType; 4] = [
+        CurveType::Linear,
+        CurveType::Hold,
+        CurveType::SCurve,
+        CurveType::Exponential,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CurveType::Linear => "Linear",
+            CurveType::Hold => "Hold",
+            CurveType::SCurve => "S-Curve",
+            CurveType::Exponential => "Exponential",
+        }
+    }
+
+    /// Whether `tension` changes the shape
+    pub fn has_tension(self) -> bool {
+        matches!(self, CurveType::SCurve | CurveType::Exponential)
+    }
+
+    /// Progress `t` (0..=1) along a segment mapped through the curve. Zero
+    /// tension gives a quadratic ease; +-1 bends it up to 4x either way.
+    pub fn shape(self, t: f32, tension: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        let k = 2.0f32.powf(1.0 + 2.0 * tension.clamp(-1.0, 1.0));
+        match self {
+            CurveType::Linear => t,
+            CurveType::Hold => 0.0,
+            CurveType::Exponential => t.powf(k),
+            CurveType::SCurve => {
+                let (a, b) = (t.powf(k), (1.0 - t).powf(k));
+                if a + b > 0.0 { a / (a + b) } else { t }
+            }
+        }
+    }
+
+    /// Tension that makes `shape(t, _)` pass through `s`, for dragging a
+    /// handle sitting at `t` along the segment
+    pub fn tension_through(self, t: f32, s: f32) -> Option<f32> {
+        let t = t.clamp(0.01, 0.99);
+        let s = s.clamp(0.001, 0.999);
+        let k = match self {
+            CurveType::Exponential => s.ln() / t.ln(),
+            CurveType::SCurve => (s / (1.0 - s)).ln() / (t / (1.0 - t)).ln(),
+            _ => return None,
+        };
+        (k.is_finite() && k > 0.0).then(|| ((k.log2() - 1.0) / 2.0).clamp(-1.0, 1.0))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -15,6 +83,34 @@ pub enum AutomationMode {
     Latch,
 }
 
+impl AutomationMode {
+    pub const ALL: [AutomationMode; 5] = [
+        AutomationMode::Read,
+        AutomationMode::Write,
+        AutomationMode::Touch,
+        AutomationMode::Latch,
+        AutomationMode::Off,
+    ];
+
+    /// Whether the mode records control moves during playback
+    pub fn writes(self) -> bool {
+        matches!(
+            self,
+            AutomationMode::Write | AutomationMode::Touch | AutomationMode::Latch
+        )
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AutomationMode::Off => "Off",
+            AutomationMode::Read => "Read",
+            AutomationMode::Write => "Write",
+            AutomationMode::Touch => "Touch",
+            AutomationMode::Latch => "Latch",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutomationLane {
     pub parameter: AutomationTarget,
@@ -24,6 +120,40 @@ pub struct AutomationLane {
     pub color: Option<(u8, u8, u8)>,
     pub write_mode: AutomationMode,
     pub read_enabled: bool,
+    /// Beat of the last point the current write pass recorded, None when
+    /// the lane isn't being written
+    #[serde(skip)]
+    pub write_pass: Option<f64>,
+    /// Controller CC that drives the lane's target
+    #[serde(default)]
+    pub cc_binding: Option<u8>,
+    /// Waiting for the next incoming CC to become `cc_binding`
+    #[serde(skip)]
+    pub learning: bool,
+}
+
+impl AutomationLane {
+    /// Whether playback should follow the lane's points
+    pub fn plays_back(&self) -> bool {
+        self.read_enabled && self.write_mode != AutomationMode::Off && self.write_pass.is_none()
+    }
+
+    /// Records `value` at `beat` during a write pass, replacing whatever the
+    /// pass swept over since its last point
+    pub fn write_point(&mut self, beat: f64, value: f32) {
+        // A loop wrap starts sweeping again from `beat`
+        let from = self.write_pass.filter(|&f| f <= beat).unwrap_or(beat);
+        self.points
+            .retain(|p| (p.beat <= from || p.beat > beat) && (p.beat - beat).abs() > 0.001);
+        self.points.push(AutomationPoint {
+            beat,
+            value,
+            ..Default::default()
+        });
+        self.points
+            .sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
+        self.write_pass = Some(beat);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -31,5 +161,120 @@ pub enum AutomationTarget {
     TrackVolume,
     TrackPan,
     TrackSend(u64),
-    PluginParam { plugin_id: u64, param_name: String },
+    PluginParam {
+        plugin_id: u64,
+        param_name: String,
+    },
+    /// Controller messages sent to the track's instrument
+    MidiCc(u8),
+}
+
+/// Lane values for -inf, -12, -6, -3 and 0 dB
+const VOLUME_SNAPS: [f32; 5] = [0.0, 0.251_189, 0.501_187, 0.707_946, 1.0];
+
+impl AutomationTarget {
+    /// Lane values drawn points stick to
+    pub fn snap_values(&self) -> &'static [f32] {
+        match self {
+            AutomationTarget::TrackVolume => &VOLUME_SNAPS,
+            // Hard left, centre, hard right
+            AutomationTarget::TrackPan => &[0.0, 0.5, 1.0],
+            _ => &[0.0, 0.25, 0.5, 0.75, 1.0],
+        }
+    }
+
+    /// Value a new lane starts at. Controllers start at their power-on value
+    /// (volume 100, pan and balance centred, expression full, the rest off),
+    /// so a new sustain lane doesn't hold every note.
+    pub fn seed_value(&self) -> f32 {
+        match self {
+            AutomationTarget::MidiCc(7) => 100.0 / 127.0,
+            AutomationTarget::MidiCc(8 | 10) => 64.0 / 127.0,
+            AutomationTarget::MidiCc(11) => 1.0,
+            AutomationTarget::MidiCc(_) => 0.0,
+            _ => 0.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controller_lanes_start_at_their_power_on_value() {
+        assert_eq!(AutomationTarget::MidiCc(64).seed_value(), 0.0);
+        assert_eq!(AutomationTarget::MidiCc(1).seed_value(), 0.0);
+        assert_eq!(AutomationTarget::MidiCc(7).seed_value(), 100.0 / 127.0);
+        assert_eq!(AutomationTarget::MidiCc(10).seed_value(), 64.0 / 127.0);
+        assert_eq!(AutomationTarget::MidiCc(11).seed_value(), 1.0);
+        assert_eq!(AutomationTarget::TrackPan.seed_value(), 0.5);
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn curves_run_from_zero_to_one_and_never_turn_back() {
+        for curve in [CurveType::Linear, CurveType::SCurve, CurveType::Exponential] {
+            for tension in [-1.0, -0.5, 0.0, 0.5, 1.0] {
+                assert_eq!(curve.shape(0.0, tension), 0.0);
+                assert!(close(curve.shape(1.0, tension), 1.0));
+                let samples: Vec<f32> = (0..=100)
+                    .map(|i| curve.shape(i as f32 / 100.0, tension))
+                    .collect();
+                assert!(
+                    samples.windows(2).all(|w| w[1] >= w[0]),
+                    "{curve:?} {tension}"
+                );
+            }
+        }
+        // Out-of-range progress is clamped
+        assert_eq!(CurveType::Linear.shape(-0.5, 0.0), 0.0);
+        assert_eq!(CurveType::Linear.shape(1.5, 0.0), 1.0);
+    }
+
+    #[test]
+    fn hold_stays_on_the_first_value() {
+        assert_eq!(CurveType::Hold.shape(0.0, 0.0), 0.0);
+        assert_eq!(CurveType::Hold.shape(0.99, 1.0), 0.0);
+    }
+
+    #[test]
+    fn tension_bends_the_exponential() {
+        let exp = CurveType::Exponential;
+        assert!(close(exp.shape(0.5, 0.0), 0.25));
+        assert!(close(exp.shape(0.5, 1.0), 0.5f32.powi(8)));
+        assert!(close(exp.shape(0.25, -1.0), 0.5));
+        // Tension past the range acts like its end
+        assert!(close(exp.shape(0.5, 3.0), exp.shape(0.5, 1.0)));
+    }
+
+    #[test]
+    fn s_curve_is_symmetric_about_its_middle() {
+        let s = CurveType::SCurve;
+        for tension in [-1.0, 0.0, 1.0] {
+            assert!(close(s.shape(0.5, tension), 0.5));
+            for t in [0.1, 0.25, 0.4] {
+                assert!(close(s.shape(t, tension) + s.shape(1.0 - t, tension), 1.0));
+            }
+        }
+        assert!(close(s.shape(0.25, 0.0), 0.1));
+    }
+
+    #[test]
+    fn tension_through_inverts_shape() {
+        for curve in [CurveType::Exponential, CurveType::SCurve] {
+            for tension in [-0.8, -0.2, 0.3, 0.9] {
+                let t = 0.4;
+                let found = curve.tension_through(t, curve.shape(t, tension)).unwrap();
+                assert!(
+                    (found - tension).abs() < 1e-3,
+                    "{curve:?}: {found} vs {tension}"
+                );
+            }
+        }
+        assert_eq!(CurveType::Linear.tension_through(0.5, 0.3), None);
+    }
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use super::peaks::PeakCache;
 use crate::constants::DEFAULT_MIN_PROJECT_BEATS;
 
 #[inline]
@@ -25,9 +26,21 @@ fn default_false() -> bool {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MidiPattern {
     pub id: u64,
+    #[serde(default)]
+    pub name: String,
     pub notes: Vec<MidiNote>,
 }
 
+impl MidiPattern {
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            format!("Pattern {}", self.id)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct MidiNote {
     #[serde(default = "zero_u64")]
@@ -38,6 +51,60 @@ pub struct MidiNote {
     pub duration: f64,
 }
 
+/// How a looped clip plays its content on each repetition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClipLoopMode {
+    #[default]
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+impl ClipLoopMode {
+    pub const ALL: [ClipLoopMode; 3] = [Self::Forward, Self::Reverse, Self::PingPong];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Forward => "Forward",
+            Self::Reverse => "Reverse",
+            Self::PingPong => "Ping-Pong",
+        }
+    }
+
+    /// Whether repetition `rep` (0-based) plays backwards.
+    pub fn is_reversed(self, rep: i64) -> bool {
+        match self {
+            Self::Forward => false,
+            Self::Reverse => true,
+            Self::PingPong => rep.rem_euclid(2) == 1,
+        }
+    }
+
+    /// Crossfade actually applied between passes of a source `src_len`
+    /// long. Ping-pong passes meet end to end, so they never need one.
+    pub fn crossfade_len(self, requested: f64, src_len: f64) -> f64 {
+        match self {
+            Self::PingPong => 0.0,
+            _ => requested.clamp(0.0, (src_len * 0.5).max(0.0)),
+        }
+    }
+}
+
+/// Splits a position into a looped source into the pass it falls in and the
+/// position within the source. With a crossfade, passes after the first
+/// resume `xfade` in, since their head was blended under the previous tail.
+pub fn loop_pass_position(pos: f64, src_len: f64, xfade: f64) -> (i64, f64) {
+    if pos < src_len {
+        return (0, pos.max(0.0));
+    }
+    let period = (src_len - xfade).max(1e-9);
+    let q = pos - src_len;
+    (
+        1 + (q / period).floor() as i64,
+        xfade + q.rem_euclid(period),
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MidiClip {
     #[serde(default = "zero_u64")]
@@ -51,6 +118,8 @@ pub struct MidiClip {
     pub transpose: i8,
     #[serde(default = "default_false")]
     pub loop_enabled: bool,
+    #[serde(default)]
+    pub loop_mode: ClipLoopMode,
 
     #[serde(default = "default_zero_f64")]
     pub content_len_beats: f64,
@@ -88,6 +157,7 @@ impl Default for MidiClip {
             velocity_offset: 0,
             transpose: 0,
             loop_enabled: false,
+            loop_mode: ClipLoopMode::Forward,
             content_len_beats: length,
             pattern_id: None,
             quantize_grid: default_quantize_grid(),
@@ -103,6 +173,39 @@ impl Default for MidiClip {
     }
 }
 
+/// Shape of a clip fade, from silence (t = 0) to full level (t = 1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FadeCurve {
+    #[default]
+    Linear,
+    /// Holds the summed power of overlapping fades constant
+    EqualPower,
+    /// Rises quickly, then eases into full level
+    Logarithmic,
+}
+
+impl FadeCurve {
+    pub const ALL: [FadeCurve; 3] = [Self::Linear, Self::EqualPower, Self::Logarithmic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::EqualPower => "Equal Power",
+            Self::Logarithmic => "Logarithmic",
+        }
+    }
+
+    #[inline]
+    pub fn gain(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EqualPower => (t * std::f32::consts::FRAC_PI_2).sin(),
+            Self::Logarithmic => (1.0 + 9.0 * t).log10(),
+        }
+    }
+}
+
 fn default_opt_u64_none() -> Option<u64> {
     None
 }
@@ -117,23 +220,58 @@ pub struct AudioClip {
     #[serde(default = "default_zero_f64")]
     pub offset_beats: f64,
     pub samples: Vec<f32>,
+    /// Right channel of a stereo clip, as long as `samples`, which then holds
+    /// the left. Empty for mono clips.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples_right: Vec<f32>,
     pub sample_rate: f32,
     #[serde(default = "default_opt_u64_none")]
     pub source_hash: Option<u64>,
+    /// Copy of `samples` under the project folder, written by "Collect and
+    /// Save". While it still matches, saves leave the samples out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_path: Option<String>,
     pub fade_in: Option<f64>,
     pub fade_out: Option<f64>,
+    #[serde(default)]
+    pub fade_in_curve: FadeCurve,
+    #[serde(default)]
+    pub fade_out_curve: FadeCurve,
     pub gain: f32,
-    pub pitch_shift: f32,
+    /// Transposition in semitones (fractions are cents), length unchanged
+    #[serde(default, alias = "pitch_shift")]
+    pub pitch_semitones: f32,
     pub time_stretch: f32,
     #[serde(default = "default_false")]
     pub warp_mode: bool,
-    pub reverse: bool,
+    /// Tempo the source was played at. When set, a warped clip keeps its musical
+    /// length at any project tempo (pitch preserved); 0 fits the source to `length_beats`.
+    #[serde(default)]
+    pub original_bpm: f32,
+    /// Plays the source backwards; `samples` stay as recorded
+    #[serde(default)]
+    pub reversed: bool,
+    /// Older projects stored reversed clips with their samples flipped, see
+    /// `migrate_reversed_samples`
+    #[serde(default, rename = "reverse", skip_serializing)]
+    pub legacy_reversed_samples: bool,
     pub loop_enabled: bool,
+    #[serde(default)]
+    pub loop_mode: ClipLoopMode,
+    /// Beats of the loop tail blended into its head on each wrap
+    #[serde(default = "default_zero_f64")]
+    pub loop_crossfade: f64,
     pub color: Option<(u8, u8, u8)>,
     pub muted: bool,
     pub locked: bool,
     pub crossfade_in: Option<f64>,
     pub crossfade_out: Option<f64>,
+    /// Loop-recording region this clip is a take of, see `Track::takes`
+    #[serde(default)]
+    pub take_region: Option<u64>,
+    /// Waveform peaks of `samples`, invalidate after changing them
+    #[serde(skip)]
+    pub peaks: PeakCache,
 }
 
 impl Default for AudioClip {
@@ -145,21 +283,306 @@ impl Default for AudioClip {
             length_beats: DEFAULT_MIN_PROJECT_BEATS,
             offset_beats: 0.0,
             samples: Vec::new(),
+            samples_right: Vec::new(),
             sample_rate: 44100.0,
             source_hash: None,
+            media_path: None,
             fade_in: None,
             fade_out: None,
+            fade_in_curve: FadeCurve::Linear,
+            fade_out_curve: FadeCurve::Linear,
             gain: 1.0,
-            pitch_shift: 0.0,
+            pitch_semitones: 0.0,
             time_stretch: 1.0,
             warp_mode: false,
-            reverse: false,
+            original_bpm: 0.0,
+            reversed: false,
+            legacy_reversed_samples: false,
             loop_enabled: false,
+            loop_mode: ClipLoopMode::Forward,
+            loop_crossfade: 0.0,
             color: None,
             muted: false,
             locked: false,
             crossfade_in: None,
             crossfade_out: None,
+            take_region: None,
+            peaks: PeakCache::default(),
+        }
+    }
+}
+
+impl AudioClip {
+    /// Loaded from a project whose media file for it couldn't be found
+    pub fn is_missing_media(&self) -> bool {
+        self.samples.is_empty() && self.media_path.is_some()
+    }
+
+    /// Source length in project beats at the given tempo.
+    pub fn source_length_beats(&self, bpm: f32) -> f64 {
+        if self.warp_mode && self.original_bpm > 0.0 {
+            (self.samples.len() as f64 / self.sample_rate as f64)
+                * (self.original_bpm as f64 / 60.0)
+        } else if self.warp_mode {
+            self.length_beats
+        } else {
+            (self.samples.len() as f64 / self.sample_rate as f64) * (bpm as f64 / 60.0)
+        }
+    }
+
+    /// Flips the playback direction and mirrors everything positioned against
+    /// it: the fades swap ends and the offset is taken from the other end of
+    /// the source. The samples are left untouched.
+    pub fn toggle_reversed(&mut self, bpm: f32) {
+        let src_len = self.source_length_beats(bpm);
+        let played = self.length_beats.min(src_len);
+        let mirrored = src_len - self.offset_beats - played;
+        self.offset_beats = if self.loop_enabled && src_len > 0.0 {
+            mirrored.rem_euclid(src_len)
+        } else {
+            mirrored.max(0.0)
+        };
+
+        std::mem::swap(&mut self.fade_in, &mut self.fade_out);
+        std::mem::swap(&mut self.fade_in_curve, &mut self.fade_out_curve);
+        self.reversed = !self.reversed;
+    }
+
+    /// `samples`, then `samples_right` for a stereo clip
+    pub fn channels(&self) -> impl Iterator<Item = &Vec<f32>> {
+        std::iter::once(&self.samples)
+            .chain((!self.samples_right.is_empty()).then_some(&self.samples_right))
+    }
+
+    pub fn channels_mut(&mut self) -> impl Iterator<Item = &mut Vec<f32>> {
+        let stereo = !self.samples_right.is_empty();
+        std::iter::once(&mut self.samples).chain(stereo.then_some(&mut self.samples_right))
+    }
+
+    /// Index in `samples` of the `i`th sample in playback order
+    #[inline]
+    pub fn played_index(&self, i: usize) -> usize {
+        if self.reversed {
+            self.samples.len() - 1 - i
+        } else {
+            i
+        }
+    }
+
+    /// Restores the recorded sample order of a clip reversed by an older
+    /// version, keeping it reversed through the flag instead
+    pub fn migrate_reversed_samples(&mut self) {
+        if std::mem::take(&mut self.legacy_reversed_samples) {
+            self.samples.reverse();
+            self.samples_right.reverse();
+            self.peaks.invalidate();
+            self.reversed = !self.reversed;
+        }
+    }
+}
+
+/// Gain law of a crossfade between two overlapping clips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CrossfadeCurve {
+    #[default]
+    EqualPower,
+    Linear,
+}
+
+impl CrossfadeCurve {
+    pub const ALL: [CrossfadeCurve; 2] = [Self::EqualPower, Self::Linear];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EqualPower => "Equal Power",
+            Self::Linear => "Linear",
+        }
+    }
+
+    /// (outgoing, incoming) gains at `t` (0..1) through the fade.
+    #[inline]
+    pub fn gains(self, t: f32) -> (f32, f32) {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::EqualPower => {
+                let a = t * std::f32::consts::FRAC_PI_2;
+                (a.cos(), a.sin())
+            }
+            Self::Linear => (1.0 - t, t),
+        }
+    }
+}
+
+/// Fade between two overlapping audio clips on one track: `out_clip` fades
+/// down while `in_clip` fades up across `length` beats around `center`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Crossfade {
+    pub id: u64,
+    pub out_clip: u64,
+    pub in_clip: u64,
+    pub center: f64,
+    pub length: f64,
+    #[serde(default)]
+    pub curve: CrossfadeCurve,
+}
+
+impl Crossfade {
+    pub fn start(&self) -> f64 {
+        self.center - self.length * 0.5
+    }
+
+    pub fn end(&self) -> f64 {
+        self.center + self.length * 0.5
+    }
+
+    pub fn links(&self, clip_id: u64) -> bool {
+        self.out_clip == clip_id || self.in_clip == clip_id
+    }
+}
+
+/// Overlap of an earlier-starting clip with a later one, if any. A clip that
+/// ends inside the other has none: fading the outer clip out there would
+/// silence the rest of it.
+pub fn clip_overlap(out_clip: &AudioClip, in_clip: &AudioClip) -> Option<(f64, f64)> {
+    let start = in_clip.start_beat;
+    let out_end = out_clip.start_beat + out_clip.length_beats;
+    let in_end = in_clip.start_beat + in_clip.length_beats;
+    (out_clip.start_beat < start && out_end > start && in_end >= out_end)
+        .then_some((start, out_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(start_beat: f64, length_beats: f64) -> AudioClip {
+        AudioClip {
+            start_beat,
+            length_beats,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn overlap_runs_from_the_later_start_to_the_earlier_end() {
+        assert_eq!(
+            clip_overlap(&clip(0.0, 4.0), &clip(3.0, 4.0)),
+            Some((3.0, 4.0))
+        );
+        assert_eq!(
+            clip_overlap(&clip(0.0, 4.0), &clip(2.0, 2.0)),
+            Some((2.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn clips_apart_or_in_the_wrong_order_have_no_overlap() {
+        assert_eq!(clip_overlap(&clip(0.0, 2.0), &clip(2.0, 2.0)), None);
+        assert_eq!(clip_overlap(&clip(3.0, 4.0), &clip(0.0, 4.0)), None);
+    }
+
+    #[test]
+    fn contained_clip_has_no_overlap() {
+        assert_eq!(clip_overlap(&clip(0.0, 8.0), &clip(2.0, 2.0)), None);
+    }
+
+    // Two seconds of source: 4 beats at 120 BPM
+    fn sourced(length_beats: f64, offset_beats: f64) -> AudioClip {
+        AudioClip {
+            length_beats,
+            offset_beats,
+            samples: vec![0.0; 88200],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reversing_mirrors_offset_and_fades() {
+        let mut c = sourced(2.0, 0.5);
+        c.fade_in = Some(0.25);
+        c.fade_out = Some(1.0);
+        c.fade_out_curve = FadeCurve::EqualPower;
+        c.toggle_reversed(120.0);
+
+        assert!(c.reversed);
+        assert_eq!(c.offset_beats, 1.5);
+        assert_eq!(c.fade_in, Some(1.0));
+        assert_eq!(c.fade_out, Some(0.25));
+        assert_eq!(c.fade_in_curve, FadeCurve::EqualPower);
+        assert_eq!(c.fade_out_curve, FadeCurve::Linear);
+        assert_eq!(c.samples.len(), 88200);
+    }
+
+    #[test]
+    fn reversing_twice_restores_the_clip() {
+        let mut c = sourced(2.0, 0.5);
+        c.fade_in = Some(0.25);
+        c.toggle_reversed(120.0);
+        c.toggle_reversed(120.0);
+
+        assert!(!c.reversed);
+        assert_eq!(c.offset_beats, 0.5);
+        assert_eq!(c.fade_in, Some(0.25));
+        assert_eq!(c.fade_out, None);
+    }
+
+    #[test]
+    fn reversing_a_looped_clip_wraps_the_offset() {
+        let mut c = sourced(6.0, 1.0);
+        c.loop_enabled = true;
+        c.toggle_reversed(120.0);
+        assert_eq!(c.offset_beats, 3.0);
+
+        let mut c = sourced(6.0, 1.0);
+        c.toggle_reversed(120.0);
+        assert_eq!(c.offset_beats, 0.0);
+    }
+
+    #[test]
+    fn played_index_counts_from_the_end_when_reversed() {
+        let mut c = sourced(4.0, 0.0);
+        assert_eq!(c.played_index(0), 0);
+        c.toggle_reversed(120.0);
+        assert_eq!(c.played_index(0), 88199);
+        assert_eq!(c.played_index(88199), 0);
+    }
+
+    #[test]
+    fn fade_curves_run_from_silence_to_unity() {
+        for curve in FadeCurve::ALL {
+            assert_eq!(curve.gain(0.0), 0.0, "{curve:?}");
+            assert!((curve.gain(1.0) - 1.0).abs() < 1e-6, "{curve:?}");
+            assert_eq!(curve.gain(-0.5), curve.gain(0.0), "{curve:?}");
+            assert_eq!(curve.gain(1.5), curve.gain(1.0), "{curve:?}");
+        }
+    }
+
+    #[test]
+    fn fade_curves_rise_monotonically() {
+        for curve in FadeCurve::ALL {
+            let gains: Vec<f32> = (0..=20).map(|i| curve.gain(i as f32 / 20.0)).collect();
+            assert!(gains.windows(2).all(|w| w[0] < w[1]), "{curve:?}");
+        }
+    }
+
+    #[test]
+    fn fade_curve_midpoints() {
+        assert_eq!(FadeCurve::Linear.gain(0.5), 0.5);
+        assert!((FadeCurve::EqualPower.gain(0.5) - 0.5f32.sqrt()).abs() < 1e-6);
+        assert!((FadeCurve::Logarithmic.gain(0.5) - 5.5f32.log10()).abs() < 1e-6);
+        // The faster curves are already past halfway
+        assert!(FadeCurve::Logarithmic.gain(0.5) > FadeCurve::EqualPower.gain(0.5));
+    }
+
+    #[test]
+    fn equal_power_fade_keeps_power_against_its_mirror() {
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            let (a, b) = (
+                FadeCurve::EqualPower.gain(t),
+                FadeCurve::EqualPower.gain(1.0 - t),
+            );
+            assert!((a * a + b * b - 1.0).abs() < 1e-5, "t = {t}");
         }
     }
 }
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+use super::MidiNote;
+
+/// Timing feel captured from a clip: how far notes sit from each grid step
+/// over one cycle, in fractions of a step so it holds at any tempo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrooveTemplate {
+    pub id: u64,
+    pub name: String,
+    /// Step length in beats
+    pub grid: f32,
+    /// Mean offset of the notes on each step of the cycle, in steps
+    pub offsets: Vec<f32>,
+}
+
+impl GrooveTemplate {
+    /// Averages the offsets of `notes` from a `grid` spaced grid, folded over
+    /// a cycle of `cycle_beats`. `origin` is the timeline beat a note start of
+    /// zero plays at (clip start plus content offset), so steps line up with
+    /// the bars. Steps without notes keep a zero offset.
+    pub fn extract(
+        id: u64,
+        name: String,
+        notes: &[MidiNote],
+        grid: f32,
+        cycle_beats: f64,
+        origin: f64,
+    ) -> Self {
+        let grid = grid.max(1e-3);
+        let g = grid as f64;
+        let steps = ((cycle_beats / g).round() as usize).max(1);
+
+        let mut sums = vec![0.0f64; steps];
+        let mut counts = vec![0u32; steps];
+        for n in notes {
+            let pos = origin + n.start;
+            let step = (pos / g).round();
+            let idx = (step as i64).rem_euclid(steps as i64) as usize;
+            sums[idx] += (pos - step * g) / g;
+            counts[idx] += 1;
+        }
+
+        let offsets = sums
+            .iter()
+            .zip(&counts)
+            .map(|(&sum, &count)| {
+                if count > 0 {
+                    (sum / count as f64) as f32
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        Self {
+            id,
+            name,
+            grid,
+            offsets,
+        }
+    }
+
+    /// Moves each note start `strength` of the way towards its step's
+    /// groove position, keeping it inside `0..content_len`. `origin` is as
+    /// for [`GrooveTemplate::extract`].
+    pub fn apply(&self, notes: &mut [MidiNote], strength: f32, content_len: f64, origin: f64) {
+        if self.offsets.is_empty() {
+            return;
+        }
+        let g = self.grid.max(1e-3) as f64;
+        let strength = strength.clamp(0.0, 1.0) as f64;
+        for n in notes.iter_mut() {
+            let pos = origin + n.start;
+            let step = (pos / g).round();
+            let idx = (step as i64).rem_euclid(self.offsets.len() as i64) as usize;
+            let target = (step + self.offsets[idx] as f64) * g;
+            let start = n.start + (target - pos) * strength;
+            n.start = start.clamp(0.0, (content_len - n.duration).max(0.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(start: f64) -> MidiNote {
+        MidiNote {
+            id: 0,
+            pitch: 60,
+            velocity: 100,
+            start,
+            duration: 0.25,
+        }
+    }
+
+    fn starts(notes: &[MidiNote]) -> Vec<f64> {
+        notes
+            .iter()
+            .map(|n| (n.start * 1000.0).round() / 1000.0)
+            .collect()
+    }
+
+    #[test]
+    fn extract_averages_offsets_per_step() {
+        // Eighths over a 2-beat cycle; the offbeats sit a fifth of a step late
+        let notes = [
+            note(0.0),
+            note(0.6),
+            note(1.0),
+            note(1.6),
+            note(2.0),
+            note(2.6),
+        ];
+        let groove = GrooveTemplate::extract(1, "g".into(), &notes, 0.5, 2.0, 0.0);
+        assert_eq!(groove.offsets.len(), 4);
+        for (got, want) in groove.offsets.iter().zip([0.0, 0.2, 0.0, 0.2]) {
+            assert!((got - want).abs() < 1e-5, "{:?}", groove.offsets);
+        }
+    }
+
+    #[test]
+    fn extract_counts_steps_from_the_timeline_position() {
+        // Content shifted by a beat: note 0.1 plays at 1.1, the second step
+        let groove = GrooveTemplate::extract(1, "g".into(), &[note(0.1)], 1.0, 2.0, 1.0);
+        assert!(groove.offsets[0].abs() < 1e-5);
+        assert!((groove.offsets[1] - 0.1).abs() < 1e-5);
+    }
+
+    #[test]
+    fn apply_pulls_notes_by_strength() {
+        let groove = GrooveTemplate {
+            id: 1,
+            name: "g".into(),
+            grid: 0.5,
+            offsets: vec![0.0, 0.2],
+        };
+        let mut notes = vec![note(0.0), note(0.5), note(1.5)];
+        groove.apply(&mut notes, 1.0, 4.0, 0.0);
+        assert_eq!(starts(&notes), vec![0.0, 0.6, 1.6]);
+
+        let mut notes = vec![note(0.5)];
+        groove.apply(&mut notes, 0.5, 4.0, 0.0);
+        assert_eq!(starts(&notes), vec![0.55]);
+    }
+
+    #[test]
+    fn apply_follows_the_origin() {
+        let groove = GrooveTemplate {
+            id: 1,
+            name: "g".into(),
+            grid: 0.5,
+            offsets: vec![0.0, 0.2],
+        };
+        // Offset by half a step, content 0.0 plays on an offbeat
+        let mut notes = vec![note(0.0), note(0.5)];
+        groove.apply(&mut notes, 1.0, 4.0, 0.5);
+        assert_eq!(starts(&notes), vec![0.1, 0.5]);
+    }
+
+    #[test]
+    fn apply_keeps_notes_inside_the_content() {
+        let groove = GrooveTemplate {
+            id: 1,
+            name: "g".into(),
+            grid: 1.0,
+            offsets: vec![-0.3, 0.4],
+        };
+        let mut notes = vec![note(0.0), note(3.0)];
+        groove.apply(&mut notes, 1.0, 3.5, 0.0);
+        assert_eq!(starts(&notes), vec![0.0, 3.25]);
+    }
+}
@@ -14,6 +14,10 @@ pub const COLOR_PALETTE: &[(u8, u8, u8)] = &[
     (44, 62, 80),    // Dark
 ];
 
+fn default_vca_gain() -> f32 {
+    1.0
+}
+
 /// A track group for organizational and control-linking purposes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackGroup {
@@ -26,6 +30,14 @@ pub struct TrackGroup {
     pub link_volume: bool,
     pub link_mute: bool,
     pub link_solo: bool,
+
+    /// Group fader; scales members on top of their own volume
+    #[serde(default = "default_vca_gain")]
+    pub vca_gain: f32,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub solo: bool,
 }
 
 impl Default for TrackGroup {
@@ -38,6 +50,9 @@ impl Default for TrackGroup {
             link_volume: true,
             link_mute: true,
             link_solo: true,
+            vca_gain: 1.0,
+            muted: false,
+            solo: false,
         }
     }
 }
@@ -54,6 +69,15 @@ impl TrackGroup {
         }
     }
 
+    /// What the mixer applies to members, with unlinked controls left neutral
+    pub fn vca(&self) -> crate::audio_state::VcaGroupSnapshot {
+        crate::audio_state::VcaGroupSnapshot {
+            gain: if self.link_volume { self.vca_gain } else { 1.0 },
+            muted: self.link_mute && self.muted,
+            solo: self.link_solo && self.solo,
+        }
+    }
+
     pub fn color_egui(&self) -> egui::Color32 {
         egui::Color32::from_rgb(self.color.0, self.color.1, self.color.2)
     }
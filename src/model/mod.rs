@@ -1,11 +1,18 @@
 pub mod automation;
 pub mod clip;
+pub mod groove;
 pub mod group;
+pub mod peaks;
 pub mod plugin;
+pub mod scale;
 pub mod track;
 
-pub use automation::{AutomationLane, AutomationMode, AutomationPoint, AutomationTarget};
-pub use clip::{AudioClip, MidiClip, MidiNote};
+pub use automation::{
+    AutomationLane, AutomationMode, AutomationPoint, AutomationTarget, CurveType,
+};
+pub use clip::{AudioClip, ClipLoopMode, Crossfade, CrossfadeCurve, FadeCurve, MidiClip, MidiNote};
+pub use groove::GrooveTemplate;
 pub use group::{COLOR_PALETTE, TrackGroup};
 pub use plugin::{PluginDescriptor, PluginParam};
+pub use scale::{ChordKind, Scale, ScaleMode};
 pub use track::{Send, Track};
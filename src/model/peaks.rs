@@ -0,0 +1,165 @@
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+/// Samples per block on the finest level; spans shorter than this are
+/// scanned directly
+pub const BASE_BLOCK: usize = 128;
+/// Blocks merged into one on each coarser level
+const LEVEL_FACTOR: usize = 4;
+
+/// Extremes and energy of a run of samples
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Peak {
+    pub min: f32,
+    pub max: f32,
+    /// Mean of the squared samples
+    pub mean_square: f32,
+    /// Samples covered, to weight the energy when merging
+    len: usize,
+}
+
+impl Peak {
+    fn scan(samples: &[f32]) -> Self {
+        let (min, max, sum_sq) = samples
+            .iter()
+            .fold((0.0f32, 0.0f32, 0.0f32), |(lo, hi, sq), &s| {
+                (lo.min(s), hi.max(s), sq + s * s)
+            });
+        Self {
+            min,
+            max,
+            mean_square: sum_sq / samples.len().max(1) as f32,
+            len: samples.len(),
+        }
+    }
+
+    /// Combines adjacent runs; a short final block counts for what it covers
+    fn merge(peaks: &[Peak]) -> Self {
+        let mut out = peaks.iter().fold(Peak::default(), |acc, p| Peak {
+            min: acc.min.min(p.min),
+            max: acc.max.max(p.max),
+            mean_square: acc.mean_square + p.mean_square * p.len as f32,
+            len: acc.len + p.len,
+        });
+        out.mean_square /= out.len.max(1) as f32;
+        out
+    }
+
+    pub fn rms(&self) -> f32 {
+        self.mean_square.sqrt()
+    }
+}
+
+/// Peaks of a sample buffer at several block sizes, so drawing costs
+/// O(pixels) at any zoom
+pub struct PeakPyramid {
+    /// Level `k` holds one peak per `BASE_BLOCK * LEVEL_FACTOR^k` samples
+    levels: Vec<Vec<Peak>>,
+}
+
+impl PeakPyramid {
+    pub fn build(samples: &[f32]) -> Self {
+        let mut levels = Vec::new();
+        let mut level: Vec<Peak> = samples.chunks(BASE_BLOCK).map(Peak::scan).collect();
+        while level.len() > 1 {
+            let coarser = level.chunks(LEVEL_FACTOR).map(Peak::merge).collect();
+            levels.push(level);
+            level = coarser;
+        }
+        levels.push(level);
+        Self { levels }
+    }
+
+    /// Peak over `samples[start..end]`, read from the coarsest level whose
+    /// blocks still fit in the span
+    pub fn range(&self, samples: &[f32], start: usize, end: usize) -> Peak {
+        let end = end.min(samples.len());
+        if start >= end {
+            return Peak::default();
+        }
+        if end - start < BASE_BLOCK {
+            return Peak::scan(&samples[start..end]);
+        }
+
+        let mut block = BASE_BLOCK;
+        let mut k = 0;
+        while k + 1 < self.levels.len() && block * LEVEL_FACTOR <= end - start {
+            block *= LEVEL_FACTOR;
+            k += 1;
+        }
+        let level = &self.levels[k];
+        let first = start / block;
+        let last = end.div_ceil(block).min(level.len());
+        Peak::merge(&level[first..last])
+    }
+}
+
+/// Lazily built peaks of a clip's samples. Clones share the cell until one
+/// of them changes its samples and calls [`PeakCache::invalidate`].
+#[derive(Clone, Default)]
+pub struct PeakCache(Arc<OnceLock<PeakPyramid>>);
+
+impl PeakCache {
+    pub fn get_or_build(&self, samples: &[f32]) -> &PeakPyramid {
+        self.0.get_or_init(|| PeakPyramid::build(samples))
+    }
+
+    /// Call after changing the samples the peaks were built from
+    pub fn invalidate(&mut self) {
+        self.0 = Arc::default();
+    }
+}
+
+impl fmt::Debug for PeakCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let levels = self.0.get().map_or(0, |p| p.levels.len());
+        write!(f, "PeakCache({levels} levels)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn coarse_levels_keep_extremes_and_rms() {
+        let samples: Vec<f32> = (0..BASE_BLOCK * 64)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        let pyramid = PeakPyramid::build(&samples);
+        let peak = pyramid.range(&samples, 0, samples.len());
+        assert_eq!((peak.min, peak.max), (-0.5, 0.5));
+        assert!(close(peak.rms(), 0.5));
+    }
+
+    #[test]
+    fn merging_weights_a_short_final_block() {
+        let mut samples = vec![1.0; BASE_BLOCK];
+        samples.extend(vec![0.0; BASE_BLOCK / 2]);
+        let pyramid = PeakPyramid::build(&samples);
+        let peak = pyramid.range(&samples, 0, samples.len());
+        assert!(close(peak.mean_square, 2.0 / 3.0), "{}", peak.mean_square);
+    }
+
+    #[test]
+    fn merging_unequal_energies_averages_the_squares() {
+        let mut samples = vec![1.0; BASE_BLOCK * 2];
+        samples.extend(vec![0.5; BASE_BLOCK * 2]);
+        let pyramid = PeakPyramid::build(&samples);
+        let peak = pyramid.range(&samples, 0, samples.len());
+        assert!(close(peak.rms(), ((1.0 + 0.25) / 2.0f32).sqrt()));
+    }
+
+    #[test]
+    fn short_spans_are_scanned_directly() {
+        let samples: Vec<f32> = (0..BASE_BLOCK * 4).map(|i| i as f32 - 15.0).collect();
+        let pyramid = PeakPyramid::build(&samples);
+        let peak = pyramid.range(&samples, 10, 20);
+        assert_eq!((peak.min, peak.max), (-5.0, 4.0));
+        assert_eq!(pyramid.range(&samples, 5, 5), Peak::default());
+    }
+}
@@ -12,8 +12,63 @@ pub struct PluginDescriptor {
     pub bypass: bool,
     pub has_editor: bool,
     pub params: HashMap<String, f32>,
+    /// Each parameter's (min, max), filled in once the plugin reports them
+    #[serde(skip)]
+    pub param_ranges: HashMap<String, (f32, f32)>,
     pub preset_name: Option<String>,
     pub custom_name: Option<String>,
+    /// Track whose post-fader output feeds the plugin's sidechain input
+    #[serde(default)]
+    pub sidechain_source: Option<u64>,
+    /// Opaque state from the plugin itself, restored before `params`
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "state_blob")]
+    pub state: Option<Vec<u8>>,
+}
+
+impl PluginDescriptor {
+    /// Maps `unit` (0..1) onto the parameter's range; unit-range values pass
+    /// through for parameters whose range hasn't been reported
+    pub fn param_from_unit(&self, param_name: &str, unit: f32) -> f32 {
+        let (min, max) = self
+            .param_ranges
+            .get(param_name)
+            .copied()
+            .unwrap_or((0.0, 1.0));
+        min + unit.clamp(0.0, 1.0) * (max - min)
+    }
+}
+
+/// Stores plugin state blobs as hex strings so project files stay readable
+pub(crate) mod state_blob {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::fmt::Write;
+
+    pub fn serialize<S: Serializer>(blob: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        match blob {
+            Some(bytes) => {
+                let mut hex = String::with_capacity(bytes.len() * 2);
+                for b in bytes {
+                    let _ = write!(hex, "{b:02x}");
+                }
+                s.serialize_some(&hex)
+            }
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+        let Some(hex) = Option::<String>::deserialize(d)? else {
+            return Ok(None);
+        };
+        if hex.len() % 2 != 0 || !hex.is_ascii() {
+            return Err(D::Error::custom("plugin state is not a hex string"));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(D::Error::custom))
+            .collect::<Result<Vec<u8>, _>>()
+            .map(Some)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,3 +80,65 @@ pub struct PluginParam {
     pub max: f32,
     pub default: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptor(state: Option<Vec<u8>>) -> PluginDescriptor {
+        PluginDescriptor {
+            id: 1,
+            uri: "urn:test".to_string(),
+            name: "Test".to_string(),
+            backend: BackendKind::Clap,
+            bypass: false,
+            has_editor: false,
+            params: HashMap::new(),
+            param_ranges: HashMap::new(),
+            preset_name: None,
+            custom_name: None,
+            sidechain_source: None,
+            state,
+        }
+    }
+
+    #[test]
+    fn state_blob_round_trips_as_hex() {
+        let blob = vec![0x00, 0x0f, 0xa5, 0xff, 0x10];
+        let json = serde_json::to_value(descriptor(Some(blob.clone()))).unwrap();
+        assert_eq!(json["state"], "000fa5ff10");
+
+        let back: PluginDescriptor = serde_json::from_value(json).unwrap();
+        assert_eq!(back.state, Some(blob));
+    }
+
+    #[test]
+    fn missing_state_stays_none() {
+        let json = serde_json::to_value(descriptor(None)).unwrap();
+        assert!(json.get("state").is_none());
+        let back: PluginDescriptor = serde_json::from_value(json).unwrap();
+        assert_eq!(back.state, None);
+    }
+
+    #[test]
+    fn malformed_state_is_rejected() {
+        let mut json = serde_json::to_value(descriptor(None)).unwrap();
+        for bad in ["abc", "zz", "é"] {
+            json["state"] = bad.into();
+            assert!(serde_json::from_value::<PluginDescriptor>(json.clone()).is_err());
+        }
+    }
+
+    #[test]
+    fn unit_values_span_the_parameter_range() {
+        let mut desc = descriptor(None);
+        desc.param_ranges
+            .insert("cutoff".to_string(), (20.0, 20000.0));
+        assert_eq!(desc.param_from_unit("cutoff", 0.0), 20.0);
+        assert_eq!(desc.param_from_unit("cutoff", 1.0), 20000.0);
+        assert_eq!(desc.param_from_unit("cutoff", 0.5), 10010.0);
+        assert_eq!(desc.param_from_unit("cutoff", 2.0), 20000.0);
+        // Unknown ranges are taken as 0..1
+        assert_eq!(desc.param_from_unit("mix", 0.25), 0.25);
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScaleMode {
+    #[default]
+    Major,
+    Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    HarmonicMinor,
+    MelodicMinor,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+}
+
+impl ScaleMode {
+    pub const ALL: [ScaleMode; 12] = [
+        ScaleMode::Major,
+        ScaleMode::Minor,
+        ScaleMode::Dorian,
+        ScaleMode::Phrygian,
+        ScaleMode::Lydian,
+        ScaleMode::Mixolydian,
+        ScaleMode::Locrian,
+        ScaleMode::HarmonicMinor,
+        ScaleMode::MelodicMinor,
+        ScaleMode::MajorPentatonic,
+        ScaleMode::MinorPentatonic,
+        ScaleMode::Blues,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScaleMode::Major => "Major",
+            ScaleMode::Minor => "Minor",
+            ScaleMode::Dorian => "Dorian",
+            ScaleMode::Phrygian => "Phrygian",
+            ScaleMode::Lydian => "Lydian",
+            ScaleMode::Mixolydian => "Mixolydian",
+            ScaleMode::Locrian => "Locrian",
+            ScaleMode::HarmonicMinor => "Harmonic Minor",
+            ScaleMode::MelodicMinor => "Melodic Minor",
+            ScaleMode::MajorPentatonic => "Major Pentatonic",
+            ScaleMode::MinorPentatonic => "Minor Pentatonic",
+            ScaleMode::Blues => "Blues",
+        }
+    }
+
+    /// Semitones above the root, ascending
+    pub fn intervals(self) -> &'static [u8] {
+        match self {
+            ScaleMode::Major => &[0, 2, 4, 5, 7, 9, 11],
+            ScaleMode::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            ScaleMode::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            ScaleMode::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            ScaleMode::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            ScaleMode::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            ScaleMode::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            ScaleMode::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            ScaleMode::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            ScaleMode::MajorPentatonic => &[0, 2, 4, 7, 9],
+            ScaleMode::MinorPentatonic => &[0, 3, 5, 7, 10],
+            ScaleMode::Blues => &[0, 3, 5, 6, 7, 10],
+        }
+    }
+}
+
+/// Chords the piano roll's chord tool stacks from scale thirds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChordKind {
+    #[default]
+    Triad,
+    Seventh,
+}
+
+impl ChordKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ChordKind::Triad => "Triad",
+            ChordKind::Seventh => "7th",
+        }
+    }
+
+    fn note_count(self) -> usize {
+        match self {
+            ChordKind::Triad => 3,
+            ChordKind::Seventh => 4,
+        }
+    }
+}
+
+/// Project key used to highlight rows, snap drawn notes and build chords
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Scale {
+    /// Pitch class of the tonic, 0 = C
+    pub root: u8,
+    pub mode: ScaleMode,
+}
+
+impl Scale {
+    pub fn new(root: u8, mode: ScaleMode) -> Self {
+        Self {
+            root: root % 12,
+            mode,
+        }
+    }
+
+    fn degree_offset(&self, pitch: u8) -> u8 {
+        ((pitch as i32 - self.root as i32).rem_euclid(12)) as u8
+    }
+
+    pub fn contains(&self, pitch: u8) -> bool {
+        self.mode.intervals().contains(&self.degree_offset(pitch))
+    }
+
+    /// Nearest in-scale pitch; ties resolve downwards
+    pub fn snap(&self, pitch: u8) -> u8 {
+        (0..12i32)
+            .flat_map(|d| [pitch as i32 - d, pitch as i32 + d])
+            .filter(|p| (0..=127).contains(p))
+            .map(|p| p as u8)
+            .find(|&p| self.contains(p))
+            .unwrap_or(pitch)
+    }
+
+    /// Pitches of the chord built on the scale degree nearest `pitch`, stacking
+    /// every other scale note upwards
+    pub fn chord(&self, pitch: u8, kind: ChordKind) -> Vec<u8> {
+        let root = self.snap(pitch);
+        let steps = self.mode.intervals();
+        let offset = self.degree_offset(root);
+        let degree = steps.iter().position(|&s| s == offset).unwrap_or(0);
+        let tonic = root as i32 - offset as i32;
+
+        (0..kind.note_count())
+            .map(|i| {
+                let d = degree + i * 2;
+                tonic + 12 * (d / steps.len()) as i32 + steps[d % steps.len()] as i32
+            })
+            .filter(|p| (0..=127).contains(p))
+            .map(|p| p as u8)
+            .collect()
+    }
+}
+
+impl fmt::Display for Scale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            NOTE_NAMES[self.root as usize % 12],
+            self.mode.name()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_moves_to_the_nearest_degree_and_ties_go_down() {
+        let c_major = Scale::new(0, ScaleMode::Major);
+        assert_eq!(c_major.snap(60), 60);
+        assert_eq!(c_major.snap(61), 60);
+        assert_eq!(c_major.snap(66), 65);
+        assert_eq!(c_major.snap(70), 69);
+
+        // A minor pentatonic: A C D E G
+        let a_penta = Scale::new(9, ScaleMode::MinorPentatonic);
+        assert_eq!(a_penta.snap(65), 64);
+        assert_eq!(a_penta.snap(66), 67);
+        assert_eq!(a_penta.snap(70), 69);
+    }
+
+    #[test]
+    fn snap_stays_in_midi_range() {
+        let b_major = Scale::new(11, ScaleMode::Major);
+        assert_eq!(b_major.snap(0), 1);
+        let c_major = Scale::new(0, ScaleMode::Major);
+        assert_eq!(c_major.snap(127), 127);
+    }
+
+    #[test]
+    fn chords_stack_scale_thirds() {
+        let c_major = Scale::new(0, ScaleMode::Major);
+        assert_eq!(c_major.chord(60, ChordKind::Triad), vec![60, 64, 67]);
+        assert_eq!(c_major.chord(62, ChordKind::Triad), vec![62, 65, 69]);
+        assert_eq!(c_major.chord(71, ChordKind::Seventh), vec![71, 74, 77, 81]);
+        // Off-scale pitches build on the snapped degree
+        assert_eq!(c_major.chord(61, ChordKind::Seventh), vec![60, 64, 67, 71]);
+
+        let a_minor = Scale::new(9, ScaleMode::Minor);
+        assert_eq!(a_minor.chord(57, ChordKind::Triad), vec![57, 60, 64]);
+    }
+
+    #[test]
+    fn chords_drop_notes_above_127() {
+        let c_major = Scale::new(0, ScaleMode::Major);
+        assert_eq!(c_major.chord(124, ChordKind::Seventh), vec![124, 127]);
+    }
+}
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
 use super::{automation::AutomationLane, plugin::PluginDescriptor};
-use crate::model::clip::{AudioClip, MidiClip};
+use crate::model::clip::{AudioClip, Crossfade, CrossfadeCurve, MidiClip, clip_overlap};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TrackType {
@@ -29,13 +30,30 @@ pub struct Track {
     pub pan: f32,
     pub muted: bool,
     pub solo: bool,
+    /// Keeps playing while other tracks are soloed
+    #[serde(default)]
+    pub solo_safe: bool,
     pub armed: bool,
     pub track_type: TrackType,
     pub midi_input_port: Option<String>,
     pub input_device: Option<String>,
     pub output_device: Option<String>,
+    /// Bus track that receives this track's post-fader output (None = master)
+    #[serde(default)]
+    pub output_bus: Option<u64>,
     pub midi_clips: Vec<MidiClip>,
     pub audio_clips: Vec<AudioClip>,
+    /// Passes recorded over a loop that aren't playing, grouped by
+    /// `AudioClip::take_region`
+    #[serde(default)]
+    pub takes: Vec<AudioClip>,
+    /// Take region -> id of its playing take, which lives in `audio_clips`
+    /// like any other clip
+    #[serde(default)]
+    pub active_takes: HashMap<u64, u64>,
+    /// Fades between overlapping `audio_clips`
+    #[serde(default)]
+    pub crossfades: Vec<Crossfade>,
     pub plugin_chain: Vec<PluginDescriptor>,
     pub automation_lanes: Vec<AutomationLane>,
     pub sends: Vec<Send>,
@@ -45,10 +63,26 @@ pub struct Track {
     pub minimized: bool,
     pub record_enabled: bool,
     pub monitor_enabled: bool,
+    /// Level of the monitored input, applied before the plugin chain
+    #[serde(default)]
+    pub monitor_gain_db: f32,
+    /// Interface input the track listens to and records; None mixes both
+    #[serde(default)]
+    pub input_channel: Option<usize>,
+    /// Feeds the cue bus (pre-fader) independently of the main mix
+    #[serde(default)]
+    pub cue: bool,
     pub input_gain: f32,
     pub phase_inverted: bool,
+    /// Folds the track to mono after its plugins, for checking phase
+    #[serde(default)]
+    pub mono: bool,
     pub frozen: bool,
-    pub frozen_buffer: Option<Vec<f32>>,
+    /// Interleaved stereo render of the plugin chain, pre-fader, from beat 0.
+    /// Shared with the graph snapshots rather than copied into each
+    pub frozen_buffer: Option<Arc<Vec<f32>>>,
+    #[serde(default)]
+    pub frozen_sample_rate: f32,
 
     #[serde(skip)]
     pub plugin_by_id: HashMap<u64, usize>,
@@ -63,13 +97,18 @@ impl Default for Track {
             pan: 0.0,
             muted: false,
             solo: false,
+            solo_safe: false,
             armed: false,
             track_type: TrackType::Audio,
             midi_input_port: None,
             input_device: None,
             output_device: None,
+            output_bus: None,
             midi_clips: Vec::new(),
             audio_clips: Vec::new(),
+            takes: Vec::new(),
+            active_takes: HashMap::new(),
+            crossfades: Vec::new(),
             plugin_chain: Vec::new(),
             automation_lanes: Vec::new(),
             sends: Vec::new(),
@@ -79,16 +118,142 @@ impl Default for Track {
             minimized: false,
             record_enabled: false,
             monitor_enabled: false,
+            monitor_gain_db: 0.0,
+            input_channel: None,
+            cue: false,
             input_gain: 1.0,
             phase_inverted: false,
+            mono: false,
             frozen: false,
             frozen_buffer: None,
+            frozen_sample_rate: 0.0,
             plugin_by_id: HashMap::new(),
         }
     }
 }
 
 impl Track {
+    /// Every take, the playing ones included, in recording order
+    pub fn all_takes(&self) -> Vec<&AudioClip> {
+        let mut takes: Vec<&AudioClip> = self
+            .audio_clips
+            .iter()
+            .filter(|c| {
+                c.take_region
+                    .is_some_and(|region| self.active_takes.get(&region) == Some(&c.id))
+            })
+            .chain(&self.takes)
+            .collect();
+        takes.sort_by_key(|t| t.id);
+        takes
+    }
+
+    /// Plays `take_id` in its region: it moves into `audio_clips`, and the
+    /// take playing there until now goes back to `takes`. False if it isn't
+    /// one of the region's takes.
+    pub fn set_active_take(&mut self, region: u64, take_id: u64) -> bool {
+        let Some(idx) = self
+            .takes
+            .iter()
+            .position(|t| t.id == take_id && t.take_region == Some(region))
+        else {
+            return false;
+        };
+        let take = self.takes.remove(idx);
+        // The old take may have been deleted or moved off the track meanwhile
+        if let Some(old) = self.active_takes.insert(region, take_id)
+            && let Some(pos) = self.audio_clips.iter().position(|c| c.id == old)
+        {
+            let old = self.audio_clips.remove(pos);
+            self.takes.push(old);
+        }
+        self.audio_clips.push(take);
+        true
+    }
+
+    /// Region a crossfade actually covers: its span clamped to the clips'
+    /// current overlap. None once the clips are gone or apart.
+    pub fn crossfade_span(&self, xf: &Crossfade) -> Option<(f64, f64)> {
+        let find = |id| self.audio_clips.iter().find(|c| c.id == id);
+        let (lo, hi) = clip_overlap(find(xf.out_clip)?, find(xf.in_clip)?)?;
+        let start = xf.start().max(lo);
+        let end = xf.end().min(hi);
+        (end > start).then_some((start, end))
+    }
+
+    /// Drops crossfades whose clips no longer overlap, then adds one over
+    /// the whole overlap between `clip_id` and each clip it now overlaps.
+    pub fn crossfade_overlaps(&mut self, clip_id: u64, mut next_id: impl FnMut() -> u64) {
+        let clips = &self.audio_clips;
+        let find = |id| clips.iter().find(|c| c.id == id);
+        self.crossfades.retain(|xf| {
+            find(xf.out_clip)
+                .zip(find(xf.in_clip))
+                .and_then(|(a, b)| clip_overlap(a, b))
+                .is_some()
+        });
+
+        let Some(clip) = find(clip_id) else {
+            return;
+        };
+        for other in clips.iter().filter(|c| c.id != clip_id) {
+            let (out_clip, in_clip) = if other.start_beat <= clip.start_beat {
+                (other, clip)
+            } else {
+                (clip, other)
+            };
+            let Some((start, end)) = clip_overlap(out_clip, in_clip) else {
+                continue;
+            };
+            let exists = self
+                .crossfades
+                .iter()
+                .any(|xf| xf.out_clip == out_clip.id && xf.in_clip == in_clip.id);
+            if !exists {
+                self.crossfades.push(Crossfade {
+                    id: next_id(),
+                    out_clip: out_clip.id,
+                    in_clip: in_clip.id,
+                    center: (start + end) * 0.5,
+                    length: end - start,
+                    curve: CrossfadeCurve::default(),
+                });
+            }
+        }
+    }
+
+    /// Shifts everything starting at or after `from` by `delta`, except the
+    /// clips in `exclude`. Closing a gap drops the automation left inside it.
+    pub fn ripple(&mut self, from: f64, delta: f64, exclude: &[u64]) {
+        let shift = |beat: &mut f64| {
+            if *beat >= from {
+                *beat = (*beat + delta).max(0.0);
+            }
+        };
+        for clip in self.audio_clips.iter_mut().chain(self.takes.iter_mut()) {
+            if !exclude.contains(&clip.id) {
+                shift(&mut clip.start_beat);
+            }
+        }
+        for clip in &mut self.midi_clips {
+            if !exclude.contains(&clip.id) {
+                shift(&mut clip.start_beat);
+            }
+        }
+        for xf in &mut self.crossfades {
+            shift(&mut xf.center);
+        }
+        for lane in &mut self.automation_lanes {
+            if delta < 0.0 {
+                lane.points
+                    .retain(|p| p.beat < from + delta || p.beat >= from);
+            }
+            for point in &mut lane.points {
+                shift(&mut point.beat);
+            }
+        }
+    }
+
     pub fn rebuild_plugin_index(&mut self) {
         self.plugin_by_id.clear();
         for (idx, plugin) in self.plugin_chain.iter().enumerate() {
@@ -109,3 +274,151 @@ impl Track {
         self.plugin_chain.get_mut(idx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(id: u64, region: u64) -> AudioClip {
+        AudioClip {
+            id,
+            take_region: Some(region),
+            ..Default::default()
+        }
+    }
+
+    fn ids<'a>(clips: impl IntoIterator<Item = &'a AudioClip>) -> Vec<u64> {
+        clips.into_iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn active_take_swaps_with_the_playing_one() {
+        let mut track = Track {
+            audio_clips: vec![take(3, 1)],
+            takes: vec![take(2, 1), take(4, 1)],
+            active_takes: HashMap::from([(1, 3)]),
+            ..Default::default()
+        };
+        assert!(track.set_active_take(1, 2));
+        assert_eq!(ids(&track.audio_clips), [2]);
+        assert_eq!(ids(&track.takes), [4, 3]);
+        assert_eq!(ids(track.all_takes()), [2, 3, 4]);
+        assert!(!track.set_active_take(1, 2));
+        assert!(!track.set_active_take(9, 4));
+    }
+
+    #[test]
+    fn deleted_active_take_is_not_brought_back() {
+        let mut track = Track {
+            audio_clips: vec![AudioClip {
+                id: 5,
+                ..Default::default()
+            }],
+            takes: vec![take(2, 1)],
+            active_takes: HashMap::from([(1, 3)]),
+            ..Default::default()
+        };
+        assert!(track.set_active_take(1, 2));
+        assert_eq!(ids(&track.audio_clips), [5, 2]);
+        assert!(track.takes.is_empty());
+    }
+
+    fn clip_at(id: u64, start_beat: f64) -> AudioClip {
+        AudioClip {
+            id,
+            start_beat,
+            ..Default::default()
+        }
+    }
+
+    fn starts<'a>(clips: impl IntoIterator<Item = &'a AudioClip>) -> Vec<f64> {
+        clips.into_iter().map(|c| c.start_beat).collect()
+    }
+
+    fn ripple_track() -> Track {
+        use super::super::automation::{AutomationMode, AutomationPoint, AutomationTarget};
+        Track {
+            audio_clips: vec![clip_at(1, 0.0), clip_at(2, 4.0), clip_at(3, 8.0)],
+            takes: vec![take(4, 1)],
+            midi_clips: vec![MidiClip {
+                id: 10,
+                start_beat: 6.0,
+                ..Default::default()
+            }],
+            crossfades: vec![Crossfade {
+                id: 20,
+                out_clip: 2,
+                in_clip: 3,
+                center: 8.0,
+                length: 0.5,
+                curve: CrossfadeCurve::default(),
+            }],
+            automation_lanes: vec![AutomationLane {
+                parameter: AutomationTarget::TrackVolume,
+                points: [2.0, 5.0, 7.0, 9.0]
+                    .map(|beat| AutomationPoint {
+                        beat,
+                        ..Default::default()
+                    })
+                    .to_vec(),
+                visible: true,
+                height: 60.0,
+                color: None,
+                write_mode: AutomationMode::Read,
+                read_enabled: true,
+                write_pass: None,
+                cc_binding: None,
+                learning: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn lane_beats(track: &Track) -> Vec<f64> {
+        track.automation_lanes[0]
+            .points
+            .iter()
+            .map(|p| p.beat)
+            .collect()
+    }
+
+    #[test]
+    fn ripple_shifts_everything_from_the_edit_point() {
+        let mut track = ripple_track();
+        track.takes[0].start_beat = 4.0;
+        track.ripple(4.0, 2.0, &[]);
+        assert_eq!(starts(&track.audio_clips), [0.0, 6.0, 10.0]);
+        assert_eq!(starts(&track.takes), [6.0]);
+        assert_eq!(track.midi_clips[0].start_beat, 8.0);
+        assert_eq!(track.crossfades[0].center, 10.0);
+        assert_eq!(lane_beats(&track), [2.0, 7.0, 9.0, 11.0]);
+    }
+
+    #[test]
+    fn ripple_leaves_excluded_clips_in_place() {
+        let mut track = ripple_track();
+        track.ripple(4.0, 2.0, &[3, 10]);
+        assert_eq!(starts(&track.audio_clips), [0.0, 6.0, 8.0]);
+        assert_eq!(track.midi_clips[0].start_beat, 6.0);
+        // Crossfades and automation follow the timeline, not the clips
+        assert_eq!(track.crossfades[0].center, 10.0);
+    }
+
+    #[test]
+    fn closing_a_gap_drops_the_automation_inside_it() {
+        let mut track = ripple_track();
+        track.ripple(6.0, -2.0, &[]);
+        assert_eq!(starts(&track.audio_clips), [0.0, 4.0, 6.0]);
+        assert_eq!(track.midi_clips[0].start_beat, 4.0);
+        assert_eq!(lane_beats(&track), [2.0, 5.0, 7.0]);
+    }
+
+    #[test]
+    fn ripple_never_moves_anything_before_zero() {
+        let mut track = ripple_track();
+        track.ripple(4.0, -6.0, &[]);
+        assert_eq!(starts(&track.audio_clips), [0.0, 0.0, 2.0]);
+        assert_eq!(track.midi_clips[0].start_beat, 0.0);
+        assert_eq!(lane_beats(&track), [0.0, 1.0, 3.0]);
+    }
+}
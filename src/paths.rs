@@ -169,6 +169,12 @@ pub fn current_theme_path() -> PathBuf {
     dir.join("current_theme.json")
 }
 
+pub fn templates_dir() -> PathBuf {
+    let dir = config_root_dir().join("templates");
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
 pub fn set_executable(path: &Path) {
     #[cfg(unix)]
     {
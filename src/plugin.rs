@@ -2,9 +2,7 @@ use anyhow::{Result, anyhow};
 
 use crate::messages::AudioCommand;
 use crate::model::plugin::PluginDescriptor;
-#[cfg(feature = "lv2-legacy")]
-use yadaw_plugin_api::BackendKind;
-use yadaw_plugin_api::UnifiedPluginInfo;
+use yadaw_plugin_api::{BackendKind, UnifiedPluginInfo};
 #[cfg(feature = "lv2-legacy")]
 use yadaw_plugin_host::legacy::{PluginInfo, get_available_plugins, with_host};
 
@@ -21,6 +19,10 @@ pub trait PluginCategorizationInfo {
     fn audio_inputs(&self) -> usize;
     fn audio_outputs(&self) -> usize;
     fn has_midi(&self) -> bool;
+    /// Built into the host rather than loaded from a plugin file
+    fn is_native(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(feature = "lv2-legacy")]
@@ -64,6 +66,9 @@ impl PluginCategorizationInfo for UnifiedPluginInfo {
     fn has_midi(&self) -> bool {
         self.has_midi
     }
+    fn is_native(&self) -> bool {
+        self.backend == BackendKind::Native
+    }
 }
 
 #[allow(unused_variables)]
@@ -77,8 +82,10 @@ pub fn create_plugin_instance(uri: &str, sample_rate: f32) -> Result<PluginDescr
             .ok_or_else(|| anyhow!("Plugin not found: {}", uri))?;
 
         let mut params = std::collections::HashMap::new();
+        let mut param_ranges = std::collections::HashMap::new();
         for port in &plugin_info.control_ports {
             params.insert(port.symbol.clone(), port.default);
+            param_ranges.insert(port.symbol.clone(), (port.min, port.max));
         }
 
         Ok(PluginDescriptor {
@@ -89,8 +96,11 @@ pub fn create_plugin_instance(uri: &str, sample_rate: f32) -> Result<PluginDescr
             bypass: Default::default(),
             has_editor: Default::default(),
             params,
+            param_ranges,
             preset_name: Default::default(),
             custom_name: Default::default(),
+            sidechain_source: None,
+            state: None,
         })
     }
     #[cfg(not(feature = "lv2-legacy"))]
@@ -166,6 +176,9 @@ pub fn classify_plugin_uri(uri: &str) -> Option<PluginKind> {
 /// Categorizes plugin (based on name for effect subtypes)
 pub fn categorize_plugin(p: &impl PluginCategorizationInfo) -> Vec<String> {
     let mut categories = vec!["All".to_string()];
+    if p.is_native() {
+        categories.push("Native".to_string());
+    }
     if p.is_instrument() || (p.has_midi() && p.audio_outputs() > 0 && p.audio_inputs() == 0) {
         categories.push("Instruments".to_string());
     } else if p.audio_inputs() > 0 && p.audio_outputs() > 0 {
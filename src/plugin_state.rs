@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use rtrb::{Producer, PushError};
+use yadaw_plugin_api::PluginStateHandle;
+
+/// Restores the audio thread can queue before the worker picks them up
+pub const RESTORE_QUEUE_SIZE: usize = 256;
+
+/// A saved blob waiting to be loaded into a freshly created instance
+pub struct StateRestore {
+    pub handle: Arc<dyn PluginStateHandle>,
+    pub state: Arc<[u8]>,
+    pub uri: String,
+    /// Set once the load has finished, whether or not the plugin took it
+    pub done: Arc<AtomicBool>,
+}
+
+/// Loads the blob, falling back to parameters only if the plugin rejects it
+pub fn load(handle: &dyn PluginStateHandle, state: &[u8], uri: &str) {
+    if !handle.load(state) {
+        log::warn!("Plugin {uri} did not restore its saved state, using parameters only");
+    }
+}
+
+/// Audio-thread end of the restore worker. Loading state can block (CLAP
+/// waits on the plugin's main thread), so the callback only queues it.
+pub struct StateRestorer {
+    producer: Producer<StateRestore>,
+}
+
+impl StateRestorer {
+    pub fn new(producer: Producer<StateRestore>) -> Self {
+        Self { producer }
+    }
+
+    /// Queues a restore; false if the worker is too far behind to take it
+    pub fn push(&mut self, restore: StateRestore) -> bool {
+        match self.producer.push(restore) {
+            Ok(()) => true,
+            Err(PushError::Full(_)) => false,
+        }
+    }
+}
+
+/// Loads queued state blobs on their own thread, flagging each as done
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_restorer(mut consumer: rtrb::Consumer<StateRestore>) {
+    use std::sync::atomic::Ordering;
+
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(5);
+
+    std::thread::spawn(move || {
+        loop {
+            while let Ok(restore) = consumer.pop() {
+                load(restore.handle.as_ref(), &restore.state, &restore.uri);
+                restore.done.store(true, Ordering::Release);
+            }
+            if consumer.is_abandoned() {
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
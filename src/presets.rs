@@ -4,12 +4,22 @@ use std::collections::HashMap;
 
 use yadaw_plugin_api::BackendKind;
 
+use crate::model::PluginDescriptor;
+use crate::model::track::TrackType;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginPreset {
     pub uri: String,
     pub backend: BackendKind,
     pub name: String,
     pub params: HashMap<String, f32>,
+    /// The plugin's own state blob, restored before `params`
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::model::plugin::state_blob"
+    )]
+    pub state: Option<Vec<u8>>,
 }
 
 fn sanitize(input: &str) -> String {
@@ -46,6 +56,11 @@ pub fn load_preset(uri: &str, name: &str) -> Result<PluginPreset> {
     Ok(preset)
 }
 
+pub fn delete_preset(uri: &str, name: &str) -> Result<()> {
+    std::fs::remove_file(preset_path(uri, name))
+        .map_err(|e| anyhow!("Failed to delete preset '{}': {}", name, e))
+}
+
 pub fn list_presets_for(uri: &str) -> Vec<String> {
     let dir = preset_dir_for_uri(uri);
     let mut out = Vec::new();
@@ -62,3 +77,63 @@ pub fn list_presets_for(uri: &str) -> Vec<String> {
     out.sort();
     out
 }
+
+/// A reusable track setup: its type, color, sends and plugin chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackTemplate {
+    pub name: String,
+    pub track_type: TrackType,
+    pub color: Option<(u8, u8, u8)>,
+    /// Sends to buses by name; ones with no such bus in the project are dropped on load
+    pub sends: Vec<TemplateSend>,
+    /// Plugins with params and state; ids are reassigned on load
+    pub plugins: Vec<PluginDescriptor>,
+}
+
+/// A template's send, naming its bus since track ids don't carry over
+/// between projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSend {
+    #[serde(default)]
+    pub bus: String,
+    pub amount: f32,
+    pub pre_fader: bool,
+    pub muted: bool,
+}
+
+fn template_path(name: &str) -> std::path::PathBuf {
+    crate::paths::templates_dir().join(format!("{}.json", sanitize(name)))
+}
+
+pub fn save_track_template(template: &TrackTemplate) -> Result<()> {
+    let json = serde_json::to_string_pretty(template)?;
+    std::fs::write(template_path(&template.name), json)?;
+    Ok(())
+}
+
+pub fn load_track_template(name: &str) -> Result<TrackTemplate> {
+    let path = template_path(name);
+    if !path.exists() {
+        return Err(anyhow!("Track template not found: {}", name));
+    }
+    let txt = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&txt)?)
+}
+
+pub fn delete_track_template(name: &str) -> Result<()> {
+    std::fs::remove_file(template_path(name))
+        .map_err(|e| anyhow!("Failed to delete track template '{}': {}", name, e))
+}
+
+pub fn list_track_templates() -> Vec<String> {
+    let mut out: Vec<String> = std::fs::read_dir(crate::paths::templates_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+        .collect();
+    out.sort();
+    out
+}
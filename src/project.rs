@@ -4,8 +4,9 @@ use std::collections::HashMap;
 
 use crate::constants::DEFAULT_LOOP_LEN;
 use crate::model::clip::MidiPattern;
-use crate::model::{Track, TrackGroup};
-use crate::time_utils::TimeConverter;
+use crate::model::track::TrackType;
+use crate::model::{AudioClip, GrooveTemplate, Scale, Track, TrackGroup};
+use crate::time_utils::{TimeConverter, TimeSignature};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppState {
@@ -29,8 +30,19 @@ pub struct AppState {
     pub loop_start: f64,
     pub loop_end: f64,
     pub loop_enabled: bool,
-    pub time_signature: (i32, i32),
+    pub time_signature: TimeSignature,
     pub next_id: u64,
+    #[serde(default)]
+    pub metadata: ProjectMetadata,
+    /// Kept sorted by beat
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    /// Key the piano roll highlights and snaps to, None when off
+    #[serde(default)]
+    pub scale: Option<Scale>,
+    /// Timing templates extracted from clips
+    #[serde(default)]
+    pub grooves: Vec<GrooveTemplate>,
 }
 
 /// Reference to where a clip lives
@@ -58,8 +70,12 @@ impl Default for AppState {
             loop_start: 0.0,
             loop_end: DEFAULT_LOOP_LEN,
             loop_enabled: false,
-            time_signature: (4, 4),
+            time_signature: TimeSignature::default(),
             next_id: 1,
+            metadata: ProjectMetadata::default(),
+            markers: Vec::new(),
+            scale: None,
+            grooves: Vec::new(),
         }
     }
 }
@@ -76,9 +92,17 @@ pub struct AppStateSnapshot {
     pub loop_end: f64,
     pub loop_enabled: bool,
     pub sample_rate: f32,
-    pub time_signature: (i32, i32),
+    pub time_signature: TimeSignature,
     pub playing: bool,
     pub recording: bool,
+    pub markers: Vec<Marker>,
+    pub scale: Option<Scale>,
+    pub grooves: Vec<GrooveTemplate>,
+    /// Editing context, filled in by the UI; `restore` leaves it to the caller
+    #[serde(default)]
+    pub selected_clips: Vec<u64>,
+    #[serde(default)]
+    pub playhead_beat: Option<f64>,
 }
 
 impl AppState {
@@ -97,6 +121,11 @@ impl AppState {
             loop_start: self.loop_start,
             loop_end: self.loop_end,
             loop_enabled: self.loop_enabled,
+            markers: self.markers.clone(),
+            scale: self.scale,
+            grooves: self.grooves.clone(),
+            selected_clips: Vec::new(),
+            playhead_beat: None,
         }
     }
 
@@ -114,6 +143,9 @@ impl AppState {
         self.loop_start = snapshot.loop_start;
         self.loop_end = snapshot.loop_end;
         self.loop_enabled = snapshot.loop_enabled;
+        self.markers = snapshot.markers;
+        self.scale = snapshot.scale;
+        self.grooves = snapshot.grooves;
         self.rebuild_clip_index();
         crate::idgen::seed_from_max(self.max_id_in_project());
         self.ensure_ids();
@@ -186,6 +218,9 @@ impl AppState {
         self.track_order.clear();
 
         for mut track in project.tracks {
+            for clip in track.audio_clips.iter_mut().chain(track.takes.iter_mut()) {
+                clip.migrate_reversed_samples();
+            }
             let track_id = if track.id == 0 {
                 self.fresh_id()
             } else {
@@ -213,6 +248,12 @@ impl AppState {
         self.loop_start = project.loop_start;
         self.loop_end = project.loop_end;
         self.loop_enabled = project.loop_enabled;
+        self.metadata = project.metadata;
+        self.markers = project.markers;
+        self.scale = project.scale;
+        self.grooves = project.grooves;
+        self.markers
+            .sort_by(|a, b| a.beat.partial_cmp(&b.beat).unwrap());
         self.rebuild_clip_index();
         crate::idgen::seed_from_max(self.max_id_in_project());
         self.ensure_ids();
@@ -239,11 +280,30 @@ impl AppState {
             loop_start: self.loop_start,
             loop_end: self.loop_end,
             loop_enabled: self.loop_enabled,
+            metadata: self.metadata.clone(),
+            markers: self.markers.clone(),
+            scale: self.scale,
+            grooves: self.grooves.clone(),
             created_at: chrono::Utc::now(),
             modified_at: chrono::Utc::now(),
         }
     }
 
+    /// Insert a marker, keeping `markers` sorted by beat
+    pub fn add_marker(&mut self, beat: f64, name: String) {
+        let idx = self.markers.partition_point(|m| m.beat <= beat);
+        self.markers.insert(idx, Marker { beat, name });
+    }
+
+    /// First "Marker N" no marker is named yet, so deleting one never leads
+    /// to a repeat
+    pub fn next_marker_name(&self) -> String {
+        (1..)
+            .map(|n| format!("Marker {n}"))
+            .find(|name| self.markers.iter().all(|m| &m.name != name))
+            .unwrap_or_default()
+    }
+
     #[inline]
     pub fn fresh_id(&self) -> u64 {
         crate::idgen::next()
@@ -306,6 +366,7 @@ impl AppState {
         for np in staged {
             self.patterns.entry(np.pid).or_insert(MidiPattern {
                 id: np.pid,
+                name: String::new(),
                 notes: np.notes,
             });
         }
@@ -394,6 +455,62 @@ impl AppState {
         let idx = track.plugin_chain.iter().position(|p| p.id == plugin_id)?;
         Some((track, idx))
     }
+
+    /// Drops a punched-in recording onto an audio track. Clips under it are
+    /// trimmed, split or removed so they overlap it by `crossfade` beats at
+    /// each edge, with matching fades on both sides.
+    pub fn insert_punch_clip(
+        &mut self,
+        track_id: u64,
+        mut clip: AudioClip,
+        crossfade: f64,
+    ) -> bool {
+        let Some(track) = self.tracks.get_mut(&track_id) else {
+            return false;
+        };
+        if matches!(track.track_type, TrackType::Midi) {
+            return false;
+        }
+
+        let start = clip.start_beat;
+        let end = start + clip.length_beats;
+        let xf = crossfade.clamp(0.0, clip.length_beats / 2.0);
+
+        let mut kept = Vec::with_capacity(track.audio_clips.len() + 2);
+        for old in track.audio_clips.drain(..) {
+            let old_end = old.start_beat + old.length_beats;
+            if old_end <= start || old.start_beat >= end {
+                kept.push(old);
+                continue;
+            }
+            if old_end > end {
+                let mut right = old.clone();
+                let new_start = (end - xf).max(old.start_beat);
+                if old.start_beat < start {
+                    right.id = crate::idgen::next();
+                }
+                right.offset_beats += new_start - old.start_beat;
+                right.start_beat = new_start;
+                right.length_beats = old_end - new_start;
+                right.fade_in = Some(xf);
+                kept.push(right);
+            }
+            if old.start_beat < start {
+                let mut left = old;
+                left.length_beats = (start + xf).min(old_end) - left.start_beat;
+                left.fade_out = Some(xf);
+                kept.push(left);
+            }
+        }
+
+        clip.fade_in = Some(xf);
+        clip.fade_out = Some(xf);
+        kept.push(clip);
+        track.audio_clips = kept;
+        self.rebuild_clip_index();
+        true
+    }
+
     fn max_id_in_project(&self) -> u64 {
         let mut max_id = 0u64;
         for t in self.tracks.values() {
@@ -414,6 +531,12 @@ impl AppState {
             for p in &t.plugin_chain {
                 max_id = max_id.max(p.id);
             }
+            for take in &t.takes {
+                max_id = max_id.max(take.id).max(take.take_region.unwrap_or(0));
+            }
+        }
+        for g in &self.grooves {
+            max_id = max_id.max(g.id);
         }
         max_id
     }
@@ -441,18 +564,149 @@ pub struct Project {
     pub patterns: Vec<MidiPattern>,
     pub groups: Vec<TrackGroup>,
     pub bpm: f32,
-    pub time_signature: (i32, i32),
+    pub time_signature: TimeSignature,
     pub sample_rate: f32,
     pub master_volume: f32,
     pub loop_start: f64,
     pub loop_end: f64,
     pub loop_enabled: bool,
+    #[serde(default)]
+    pub metadata: ProjectMetadata,
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    #[serde(default)]
+    pub scale: Option<Scale>,
+    #[serde(default)]
+    pub grooves: Vec<GrooveTemplate>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub modified_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Named position on the timeline; consecutive markers delimit sections
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub beat: f64,
+    pub name: String,
+}
+
+/// Free-form info stored with the project; title/artist are also written to export tags
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectMetadata {
+    pub title: String,
+    pub artist: String,
+    pub key: String,
+    pub comments: String,
+}
+
 impl From<&AppState> for Project {
     fn from(state: &AppState) -> Self {
         state.to_project()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(id: u64, start_beat: f64, length_beats: f64) -> AudioClip {
+        AudioClip {
+            id,
+            start_beat,
+            length_beats,
+            ..Default::default()
+        }
+    }
+
+    fn state_with(clips: Vec<AudioClip>) -> AppState {
+        let mut state = AppState::default();
+        let track = Track {
+            id: 1,
+            track_type: TrackType::Audio,
+            audio_clips: clips,
+            ..Default::default()
+        };
+        state.track_order.push(1);
+        state.tracks.insert(1, track);
+        state.rebuild_clip_index();
+        state
+    }
+
+    /// (id, start, length, offset, fade in, fade out), in timeline order
+    fn layout(state: &AppState) -> Vec<(u64, f64, f64, f64, Option<f64>, Option<f64>)> {
+        let mut clips: Vec<_> = state.tracks[&1]
+            .audio_clips
+            .iter()
+            .map(|c| {
+                (
+                    c.id,
+                    c.start_beat,
+                    c.length_beats,
+                    c.offset_beats,
+                    c.fade_in,
+                    c.fade_out,
+                )
+            })
+            .collect();
+        clips.sort_by(|a, b| a.1.total_cmp(&b.1));
+        clips
+    }
+
+    #[test]
+    fn punch_inside_a_clip_splits_it_with_crossfades() {
+        let mut state = state_with(vec![clip(10, 0.0, 8.0)]);
+        assert!(state.insert_punch_clip(1, clip(20, 2.0, 2.0), 0.25));
+
+        let clips = layout(&state);
+        assert_eq!(clips.len(), 3);
+        assert_eq!(clips[0], (10, 0.0, 2.25, 0.0, None, Some(0.25)));
+        assert_eq!(clips[1], (20, 2.0, 2.0, 0.0, Some(0.25), Some(0.25)));
+        let (right_id, start, length, offset, fade_in, fade_out) = clips[2];
+        assert_ne!(right_id, 10);
+        assert_eq!(
+            (start, length, offset, fade_in, fade_out),
+            (3.75, 4.25, 3.75, Some(0.25), None)
+        );
+        assert!(state.clips_by_id.contains_key(&right_id));
+        assert!(state.clips_by_id.contains_key(&20));
+    }
+
+    #[test]
+    fn punch_trims_clips_at_its_edges_and_drops_covered_ones() {
+        let mut state = state_with(vec![
+            clip(10, 0.0, 3.0),
+            clip(11, 4.5, 1.0),
+            clip(12, 5.0, 4.0),
+        ]);
+        assert!(state.insert_punch_clip(1, clip(20, 2.0, 4.0), 0.5));
+
+        assert_eq!(
+            layout(&state),
+            vec![
+                (10, 0.0, 2.5, 0.0, None, Some(0.5)),
+                (20, 2.0, 4.0, 0.0, Some(0.5), Some(0.5)),
+                (12, 5.5, 3.5, 0.5, Some(0.5), None),
+            ]
+        );
+        assert!(!state.clips_by_id.contains_key(&11));
+    }
+
+    #[test]
+    fn punch_crossfade_is_at_most_half_the_take() {
+        let mut state = state_with(vec![]);
+        assert!(state.insert_punch_clip(1, clip(20, 2.0, 1.0), 4.0));
+        assert_eq!(
+            layout(&state),
+            vec![(20, 2.0, 1.0, 0.0, Some(0.5), Some(0.5))]
+        );
+    }
+
+    #[test]
+    fn punch_skips_midi_and_missing_tracks() {
+        let mut state = state_with(vec![]);
+        state.tracks.get_mut(&1).unwrap().track_type = TrackType::Midi;
+        assert!(!state.insert_punch_clip(1, clip(20, 0.0, 1.0), 0.1));
+        assert!(!state.insert_punch_clip(2, clip(21, 0.0, 1.0), 0.1));
+        assert!(state.tracks[&1].audio_clips.is_empty());
+    }
+}
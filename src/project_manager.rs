@@ -4,10 +4,12 @@ use std::path::{Path, PathBuf};
 use web_time::{Duration, Instant, SystemTime};
 
 use crate::constants::PROJECT_EXTENSION;
+use crate::model::AudioClip;
 use crate::paths::cache_dir;
 use crate::project::{AppState, Project};
 use crate::wasm_persist::{read_config_string, save_config_string};
 use std::fs;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone)]
 pub struct ProjectInfo {
@@ -63,7 +65,8 @@ impl ProjectManager {
         }
 
         // Save actual project
-        let project = Project::from(state);
+        let mut project = Project::from(state);
+        detach_collected_media(&mut project, project_dir(path));
         let json = serde_json::to_string_pretty(&project)?;
         fs::write(path, json)?;
 
@@ -149,7 +152,8 @@ impl ProjectManager {
         }
 
         let contents = fs::read_to_string(path)?;
-        let project: Project = serde_json::from_str(&contents)?;
+        let mut project: Project = serde_json::from_str(&contents)?;
+        attach_collected_media(&mut project, project_dir(path));
 
         let std_system_time = path.metadata()?.modified()?;
 
@@ -368,4 +372,306 @@ impl ProjectManager {
     pub fn set_auto_save(&mut self, enabled: bool) {
         self.auto_save_enabled = enabled;
     }
+
+    /// Writes every audio clip to `media/` next to `path`, points the clips at
+    /// those files and saves. Returns how many new files were written.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn collect_and_save(&mut self, state: &mut AppState, path: &Path) -> Result<usize> {
+        let dir = project_dir(path);
+        fs::create_dir_all(dir.join(MEDIA_DIR))?;
+
+        let mut written = 0;
+        for track in state.tracks.values_mut() {
+            for clip in track.audio_clips.iter_mut().chain(track.takes.iter_mut()) {
+                if clip.samples.is_empty() {
+                    continue;
+                }
+                let safe_name: String = clip
+                    .name
+                    .chars()
+                    .map(|c| {
+                        if c.is_alphanumeric() || c == '-' {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                // Always '/', so the project opens on any platform
+                let rel = format!("{}/{}_{:016x}.wav", MEDIA_DIR, safe_name, media_hash(clip));
+                let file = dir.join(&rel);
+                if !file.is_file() {
+                    write_media_wav(&file, clip)?;
+                    written += 1;
+                }
+                clip.media_path = Some(rel);
+            }
+        }
+
+        self.save_project(state, path)?;
+        Ok(written)
+    }
+}
+
+/// Subfolder of the project folder that "Collect and Save" copies audio into
+const MEDIA_DIR: &str = "media";
+
+fn project_dir(path: &Path) -> &Path {
+    path.parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+fn media_hash(clip: &AudioClip) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    clip.sample_rate.to_bits().hash(&mut hasher);
+    for s in clip.channels().flatten() {
+        s.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// The hash a collected file was named with, see `collect_and_save`
+fn media_file_hash(rel: &str) -> Option<u64> {
+    let stem = Path::new(rel).file_stem()?.to_str()?;
+    u64::from_str_radix(stem.rsplit_once('_')?.1, 16).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_media_wav(path: &Path, clip: &AudioClip) -> Result<()> {
+    let stereo = !clip.samples_right.is_empty();
+    let spec = hound::WavSpec {
+        channels: if stereo { 2 } else { 1 },
+        sample_rate: clip.sample_rate.round() as u32,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for (i, &s) in clip.samples.iter().enumerate() {
+        writer.write_sample(s)?;
+        if stereo {
+            writer.write_sample(clip.samples_right.get(i).copied().unwrap_or(0.0))?;
+        }
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+fn project_clips_mut(project: &mut Project) -> impl Iterator<Item = &mut AudioClip> {
+    project
+        .tracks
+        .iter_mut()
+        .flat_map(|t| t.audio_clips.iter_mut().chain(t.takes.iter_mut()))
+}
+
+/// Leaves out the samples of clips whose collected file still holds them.
+/// Clips edited since the collect keep their samples in the project.
+fn detach_collected_media(project: &mut Project, dir: &Path) {
+    for clip in project_clips_mut(project) {
+        let Some(rel) = &clip.media_path else {
+            continue;
+        };
+        if dir.join(rel).is_file() && media_file_hash(rel) == Some(media_hash(clip)) {
+            clip.samples = Vec::new();
+            clip.samples_right = Vec::new();
+        }
+    }
+}
+
+/// Reads back the samples `detach_collected_media` left out. A missing file
+/// leaves its clip as a placeholder to relink, see `AudioClip::is_missing_media`.
+fn attach_collected_media(project: &mut Project, dir: &Path) {
+    for clip in project_clips_mut(project) {
+        let Some(rel) = &clip.media_path else {
+            continue;
+        };
+        if !clip.samples.is_empty() {
+            continue;
+        }
+        let path = dir.join(rel);
+        match read_media_file(&path) {
+            Ok((samples, samples_right, _)) => {
+                clip.samples = samples;
+                clip.samples_right = samples_right;
+            }
+            Err(e) => log::warn!("Missing media {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Samples, right channel (empty unless stereo, see `AudioClip::samples_right`)
+/// and sample rate of a media file. Collected files are read back exactly;
+/// anything else is decoded like an import.
+pub fn read_media_file(path: &Path) -> Result<(Vec<f32>, Vec<f32>, f32)> {
+    if let Ok(mut reader) = hound::WavReader::open(path) {
+        let spec = reader.spec();
+        if spec.channels <= 2 && spec.sample_format == hound::SampleFormat::Float {
+            let samples = reader.samples::<f32>().collect::<Result<Vec<_>, _>>()?;
+            let sample_rate = spec.sample_rate as f32;
+            if spec.channels == 1 {
+                return Ok((samples, Vec::new(), sample_rate));
+            }
+            let (left, right) = samples.chunks_exact(2).map(|f| (f[0], f[1])).unzip();
+            return Ok((left, right, sample_rate));
+        }
+    }
+    let clip = crate::audio_import::import_audio_file(path, 120.0)?;
+    Ok((clip.samples, Vec::new(), clip.sample_rate))
+}
+
+/// `AudioClip::media_path` for `file`: relative when it sits inside the
+/// project's folder, absolute otherwise
+pub fn media_path_for(project_path: Option<&Path>, file: &Path) -> String {
+    project_path
+        .and_then(|p| file.strip_prefix(project_dir(p)).ok())
+        .map(|rel| {
+            rel.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_else(|| file.to_string_lossy().into_owned())
+}
+
+/// Searches `root` and its subfolders for files named like any of `names`.
+/// The first match for each name wins.
+pub fn find_files_named(
+    root: &Path,
+    names: &std::collections::HashSet<String>,
+) -> std::collections::HashMap<String, PathBuf> {
+    const MAX_DEPTH: usize = 8;
+    let mut found = std::collections::HashMap::new();
+    let mut stack = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if depth < MAX_DEPTH {
+                    stack.push((path, depth + 1));
+                }
+            } else if let Some(name) = path.file_name().and_then(|n| n.to_str())
+                && names.contains(name)
+            {
+                found.entry(name.to_string()).or_insert(path);
+            }
+        }
+    }
+    found
+}
+
+/// `song.v3` -> ("song", Some(3)), `song` -> ("song", None). Only the
+/// suffix [`next_version_path`] writes counts, so `mix.2024` is a plain name.
+fn split_version(stem: &str) -> (&str, Option<u32>) {
+    let parsed = stem.rsplit_once('.').and_then(|(base, suffix)| {
+        let digits = suffix.strip_prefix('v')?;
+        if base.is_empty() || digits.starts_with('0') || !digits.bytes().all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        Some((base, digits.parse().ok()?))
+    });
+    match parsed {
+        Some((base, v)) => (base, Some(v)),
+        None => (stem, None),
+    }
+}
+
+/// Every saved version of the project at `path`, found next to it as
+/// `<name>.v<n>.yadaw`, oldest first. The unnumbered file counts as version 0.
+pub fn project_versions(path: &Path) -> Vec<(u32, PathBuf)> {
+    let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+        return Vec::new();
+    };
+    let base = split_version(stem).0;
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let mut versions: Vec<(u32, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(PROJECT_EXTENSION))
+        .filter_map(|p| {
+            let (b, version) = split_version(p.file_stem()?.to_str()?);
+            (b == base).then(|| (version.unwrap_or(0), p.clone()))
+        })
+        .collect();
+    versions.sort_by_key(|(v, _)| *v);
+    versions
+}
+
+/// Path for the version after the newest one on disk
+pub fn next_version_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("project");
+    let base = split_version(stem).0;
+    let next = project_versions(path).last().map_or(1, |(v, _)| v + 1);
+    path.with_file_name(format!("{}.v{}.{}", base, next, PROJECT_EXTENSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_written_suffix_is_a_version() {
+        assert_eq!(split_version("mix"), ("mix", None));
+        assert_eq!(split_version("mix.v3"), ("mix", Some(3)));
+        assert_eq!(split_version("mix.2024"), ("mix.2024", None));
+        assert_eq!(split_version("mix.2024.v12"), ("mix.2024", Some(12)));
+        assert_eq!(split_version("mix.v03"), ("mix.v03", None));
+        assert_eq!(split_version("mix.v"), ("mix.v", None));
+        assert_eq!(split_version(".v2"), (".v2", None));
+    }
+
+    #[test]
+    fn next_version_keeps_dotted_names_whole() {
+        let dir = Path::new("/nonexistent/yadaw");
+        assert_eq!(
+            next_version_path(&dir.join("mix.yadaw")),
+            dir.join("mix.v1.yadaw")
+        );
+        assert_eq!(
+            next_version_path(&dir.join("mix.2024.yadaw")),
+            dir.join("mix.2024.v1.yadaw")
+        );
+    }
+
+    #[test]
+    fn versions_are_found_next_to_the_project() {
+        let dir = std::env::temp_dir().join(format!("yadaw_versions_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "mix.2024.yadaw",
+            "mix.2024.v1.yadaw",
+            "mix.2024.v2.yadaw",
+            "mix.yadaw",
+        ] {
+            fs::write(dir.join(name), "{}").unwrap();
+        }
+
+        let versions = project_versions(&dir.join("mix.2024.v1.yadaw"));
+        let next = next_version_path(&dir.join("mix.2024.yadaw"));
+        let plain = project_versions(&dir.join("mix.yadaw"));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            versions,
+            vec![
+                (0, dir.join("mix.2024.yadaw")),
+                (1, dir.join("mix.2024.v1.yadaw")),
+                (2, dir.join("mix.2024.v2.yadaw")),
+            ]
+        );
+        assert_eq!(next, dir.join("mix.2024.v3.yadaw"));
+        assert_eq!(plain, vec![(0, dir.join("mix.yadaw"))]);
+    }
 }
@@ -0,0 +1,112 @@
+use rtrb::Producer;
+
+/// `AudioState::spectrum_source` while no analyzer is listening
+pub const SPECTRUM_OFF: u64 = u64::MAX;
+/// `AudioState::spectrum_source` for the master output
+pub const SPECTRUM_MASTER: u64 = u64::MAX - 1;
+
+pub const FFT_SIZE: usize = 4096;
+/// Mono samples the audio thread can queue before the worker drains them
+pub const SPECTRUM_RING_SIZE: usize = FFT_SIZE * 4;
+
+/// Audio-thread end of the analyzer: queues a mono copy of the selected
+/// signal, dropping samples while the worker is behind
+pub struct SpectrumTap {
+    producer: Producer<f32>,
+}
+
+impl SpectrumTap {
+    pub fn new(producer: Producer<f32>) -> Self {
+        Self { producer }
+    }
+
+    #[inline]
+    pub fn push(&mut self, l: f32, r: f32) {
+        let _ = self.producer.push(0.5 * (l + r));
+    }
+}
+
+/// The analyzer source as the UI sees it: a track id, or `None` for master
+pub fn source_to_track(source: u64) -> Option<Option<u64>> {
+    match source {
+        SPECTRUM_OFF => None,
+        SPECTRUM_MASTER => Some(None),
+        id => Some(Some(id)),
+    }
+}
+
+pub fn track_to_source(track_id: Option<u64>) -> u64 {
+    track_id.unwrap_or(SPECTRUM_MASTER)
+}
+
+/// Runs the FFT on its own thread, sending `UIUpdate::Spectrum` at roughly
+/// 30 fps while a source is selected
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_analyzer(
+    mut consumer: rtrb::Consumer<f32>,
+    audio_state: std::sync::Arc<crate::audio_state::AudioState>,
+    updates: crate::messages::UiTx,
+) {
+    use rustfft::{FftPlanner, num_complex::Complex};
+    use std::sync::atomic::Ordering;
+
+    const FRAME_INTERVAL: std::time::Duration = std::time::Duration::from_millis(33);
+
+    std::thread::spawn(move || {
+        let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| {
+                let phase = std::f32::consts::TAU * i as f32 / FFT_SIZE as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        // A full-scale sine reads 1.0 through the Hann window
+        let scale = 4.0 / FFT_SIZE as f32;
+
+        let mut history = vec![0.0f32; FFT_SIZE];
+        let mut write = 0usize;
+        let mut buffer = vec![Complex::new(0.0f32, 0.0); FFT_SIZE];
+        let mut source = SPECTRUM_OFF;
+
+        loop {
+            std::thread::sleep(FRAME_INTERVAL);
+
+            let current = audio_state.spectrum_source.load(Ordering::Relaxed);
+            if current != source {
+                source = current;
+                history.fill(0.0);
+            }
+
+            let fresh = consumer.slots();
+            if let Ok(chunk) = consumer.read_chunk(fresh) {
+                for s in chunk {
+                    history[write] = s;
+                    write = (write + 1) % FFT_SIZE;
+                }
+            }
+            let Some(track_id) = source_to_track(source) else {
+                continue;
+            };
+            if fresh == 0 {
+                continue;
+            }
+
+            // Oldest sample first
+            for (k, c) in buffer.iter_mut().enumerate() {
+                *c = Complex::new(history[(write + k) % FFT_SIZE] * window[k], 0.0);
+            }
+            fft.process(&mut buffer);
+            let bins = buffer[..FFT_SIZE / 2]
+                .iter()
+                .map(|c| c.norm() * scale)
+                .collect();
+
+            if updates
+                .send_sync(crate::messages::UIUpdate::Spectrum { track_id, bins })
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 /// Time conversion utilities for the DAW
 pub struct TimeConverter {
     sample_rate: f32,
@@ -63,11 +65,117 @@ impl TimeConverter {
     }
 }
 
+/// Project meter. Positions elsewhere are in quarter-note beats, so a bar of
+/// 6/8 spans 3.0 beats and each of its six counts is 0.5 beats long.
+/// Stored as a `(numerator, denominator)` pair to stay compatible with older project files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "(i32, i32)", into = "(i32, i32)")]
+pub struct TimeSignature {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        Self {
+            numerator: 4,
+            denominator: 4,
+        }
+    }
+}
+
+impl TimeSignature {
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        Self {
+            numerator: numerator.max(1),
+            denominator: denominator.max(1),
+        }
+    }
+
+    /// Length of one count (the denominator's note value) in quarter-note beats
+    #[inline]
+    pub fn beat_len(&self) -> f64 {
+        4.0 / self.denominator.max(1) as f64
+    }
+
+    /// Length of one bar in quarter-note beats
+    #[inline]
+    pub fn bar_len(&self) -> f64 {
+        self.numerator.max(1) as f64 * self.beat_len()
+    }
+}
+
+impl From<(i32, i32)> for TimeSignature {
+    fn from((numerator, denominator): (i32, i32)) -> Self {
+        Self::new(numerator.max(1) as u32, denominator.max(1) as u32)
+    }
+}
+
+impl From<TimeSignature> for (i32, i32) {
+    fn from(sig: TimeSignature) -> Self {
+        (sig.numerator as i32, sig.denominator as i32)
+    }
+}
+
+impl std::fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+/// How bars are numbered in the ruler, piano roll and position display.
+/// Beats within a bar are always counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarNumbering {
+    pub first_bar: i64,
+}
+
+impl Default for BarNumbering {
+    fn default() -> Self {
+        Self { first_bar: 1 }
+    }
+}
+
+impl BarNumbering {
+    pub fn new(bars_start_at_zero: bool) -> Self {
+        Self {
+            first_bar: if bars_start_at_zero { 0 } else { 1 },
+        }
+    }
+
+    /// Display number of the bar that starts at `bar_index` (0-based)
+    #[inline]
+    pub fn bar_number(&self, bar_index: i64) -> i64 {
+        bar_index + self.first_bar
+    }
+
+    /// Split a beat position into (bar, count, fraction of count) for display.
+    /// Counts follow the signature's denominator, so 6/8 counts eighths.
+    pub fn position(&self, beats: f64, sig: TimeSignature) -> (i64, u32, f64) {
+        let bar_len = sig.bar_len();
+        let beats = beats.max(0.0);
+        let bar_index = (beats / bar_len).floor();
+        let in_bar = (beats - bar_index * bar_len) / sig.beat_len();
+        let beat = (in_bar.floor() as u32).min(sig.numerator.max(1) - 1) + 1;
+        (self.bar_number(bar_index as i64), beat, in_bar.fract())
+    }
+
+    /// "bar.beat.tick", e.g. "1.1.000"
+    pub fn format_position(&self, beats: f64, sig: TimeSignature, ticks_per_beat: u32) -> String {
+        let (bar, beat, frac) = self.position(beats, sig);
+        let tick = (frac * ticks_per_beat as f64) as u32;
+        format!("{}.{}.{:03}", bar, beat, tick)
+    }
+}
+
 /// Format time in bars:beats:sixteenths
-pub fn format_bars_beats_sixteenths(beats: f64, beats_per_bar: u32) -> String {
-    let bars = (beats / beats_per_bar as f64) as i32 + 1;
-    let beat = (beats % beats_per_bar as f64) as i32 + 1;
-    let sixteenth = ((beats % 1.0) * 4.0) as i32 + 1;
+pub fn format_bars_beats_sixteenths(
+    beats: f64,
+    sig: TimeSignature,
+    numbering: BarNumbering,
+) -> String {
+    let (bars, beat, frac) = numbering.position(beats, sig);
+    let sixteenth = (frac * sig.beat_len() * 4.0) as i32 + 1;
     format!("{:03}:{:02}:{:02}", bars, beat, sixteenth)
 }
 
@@ -111,3 +219,52 @@ pub mod quick {
         (beats * 60.0 / bpm as f64) * sample_rate as f64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_count_from_the_chosen_first_bar() {
+        let sig = TimeSignature::default();
+        assert_eq!(BarNumbering::new(false).position(0.0, sig), (1, 1, 0.0));
+        assert_eq!(BarNumbering::new(true).position(0.0, sig), (0, 1, 0.0));
+        assert_eq!(BarNumbering::new(false).position(9.5, sig), (3, 2, 0.5));
+        assert_eq!(BarNumbering::new(true).position(9.5, sig), (2, 2, 0.5));
+    }
+
+    #[test]
+    fn counts_follow_the_denominator() {
+        let six_eight = TimeSignature::new(6, 8);
+        let numbering = BarNumbering::default();
+        // A 6/8 bar is three quarter beats of six eighth counts
+        assert_eq!(numbering.position(2.5, six_eight), (1, 6, 0.0));
+        assert_eq!(numbering.position(3.0, six_eight), (2, 1, 0.0));
+        assert_eq!(numbering.position(3.75, six_eight), (2, 2, 0.5));
+    }
+
+    #[test]
+    fn negative_positions_read_as_the_start() {
+        let numbering = BarNumbering::default();
+        assert_eq!(
+            numbering.position(-2.0, TimeSignature::default()),
+            (1, 1, 0.0)
+        );
+    }
+
+    #[test]
+    fn formats_follow_the_numbering() {
+        let sig = TimeSignature::default();
+        let zero = BarNumbering::new(true);
+        assert_eq!(zero.format_position(4.25, sig, 960), "1.1.240");
+        assert_eq!(
+            BarNumbering::default().format_position(4.25, sig, 960),
+            "2.1.240"
+        );
+        assert_eq!(format_bars_beats_sixteenths(4.25, sig, zero), "001:01:02");
+        assert_eq!(
+            format_bars_beats_sixteenths(0.0, sig, BarNumbering::default()),
+            "001:01:01"
+        );
+    }
+}
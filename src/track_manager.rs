@@ -96,10 +96,12 @@ impl TrackBuilder {
             pan: self.pan.unwrap_or(0.0),
             muted: false,
             solo: false,
+            solo_safe: false,
             armed: false,
             track_type,
             input_device: None,
             output_device: None,
+            output_bus: None,
             midi_clips: self.midi_clips,
             audio_clips: vec![],
             plugin_chain: vec![],
@@ -111,10 +113,15 @@ impl TrackBuilder {
             minimized: false,
             record_enabled: false,
             monitor_enabled: false,
+            monitor_gain_db: 0.0,
+            input_channel: None,
+            cue: false,
             input_gain: 1.0,
             phase_inverted: false,
+            mono: false,
             frozen: false,
             frozen_buffer: None,
+            frozen_sample_rate: 0.0,
             plugin_by_id: HashMap::new(),
             midi_input_port: None,
         }
@@ -361,6 +368,61 @@ pub fn delete_track(
     tracks.remove(&track_id)
 }
 
+/// Any track may feed a bus, as long as that bus doesn't already lead back
+/// to it on the way to the master.
+pub fn can_route_to_bus(tracks: &HashMap<u64, Track>, source_id: u64, bus_id: u64) -> bool {
+    let source_ok = tracks.contains_key(&source_id);
+    let bus_ok = tracks
+        .get(&bus_id)
+        .is_some_and(|t| matches!(t.track_type, TrackType::Bus));
+    if !(source_ok && bus_ok) {
+        return false;
+    }
+
+    let mut next = Some(bus_id);
+    let mut hops = 0;
+    while let Some(id) = next {
+        if id == source_id || hops > tracks.len() {
+            return false;
+        }
+        hops += 1;
+        next = tracks.get(&id).and_then(|t| t.output_bus);
+    }
+    true
+}
+
+/// Inserts `bus` after the last of `sources` and points their outputs at it.
+pub fn route_to_new_bus(
+    tracks: &mut HashMap<u64, Track>,
+    track_order: &mut Vec<u64>,
+    mut bus: Track,
+    bus_id: u64,
+    sources: &[u64],
+) -> anyhow::Result<()> {
+    if sources.is_empty() {
+        return Err(anyhow::anyhow!("No tracks selected"));
+    }
+    // A fresh bus feeds the master, so routing into it can't close a loop
+    if let Some(tid) = sources.iter().find(|tid| !tracks.contains_key(tid)) {
+        return Err(anyhow::anyhow!("Track {} not found", tid));
+    }
+
+    bus.id = bus_id;
+    let insert_at = track_order
+        .iter()
+        .rposition(|id| sources.contains(id))
+        .map_or(track_order.len(), |i| i + 1);
+    track_order.insert(insert_at, bus_id);
+    tracks.insert(bus_id, bus);
+
+    for tid in sources {
+        if let Some(t) = tracks.get_mut(tid) {
+            t.output_bus = Some(bus_id);
+        }
+    }
+    Ok(())
+}
+
 pub fn move_track(track_order: &mut Vec<u64>, from_idx: usize, to_idx: usize) {
     if from_idx < track_order.len() && to_idx < track_order.len() && from_idx != to_idx {
         let track_id = track_order.remove(from_idx);
@@ -391,3 +453,65 @@ pub fn create_master_track() -> Track {
         .with_volume(0.8)
         .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: u64, track_type: TrackType, output_bus: Option<u64>) -> (u64, Track) {
+        (
+            id,
+            Track {
+                id,
+                track_type,
+                output_bus,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn tracks_and_buses_can_feed_a_bus() {
+        let tracks = HashMap::from([
+            track(1, TrackType::Audio, None),
+            track(2, TrackType::Bus, None),
+            track(3, TrackType::Bus, None),
+        ]);
+        assert!(can_route_to_bus(&tracks, 1, 2));
+        assert!(can_route_to_bus(&tracks, 3, 2));
+    }
+
+    #[test]
+    fn only_buses_are_destinations() {
+        let tracks = HashMap::from([
+            track(1, TrackType::Audio, None),
+            track(2, TrackType::Audio, None),
+        ]);
+        assert!(!can_route_to_bus(&tracks, 1, 2));
+        assert!(!can_route_to_bus(&tracks, 1, 9));
+    }
+
+    #[test]
+    fn routing_that_closes_a_loop_is_refused() {
+        // 2 -> 3 -> 4 -> master
+        let tracks = HashMap::from([
+            track(2, TrackType::Bus, Some(3)),
+            track(3, TrackType::Bus, Some(4)),
+            track(4, TrackType::Bus, None),
+        ]);
+        assert!(!can_route_to_bus(&tracks, 4, 2));
+        assert!(!can_route_to_bus(&tracks, 3, 2));
+        assert!(!can_route_to_bus(&tracks, 2, 2));
+        assert!(can_route_to_bus(&tracks, 2, 4));
+    }
+
+    #[test]
+    fn a_stale_cycle_does_not_hang() {
+        let tracks = HashMap::from([
+            track(1, TrackType::Audio, None),
+            track(2, TrackType::Bus, Some(3)),
+            track(3, TrackType::Bus, Some(2)),
+        ]);
+        assert!(!can_route_to_bus(&tracks, 1, 2));
+    }
+}
@@ -68,10 +68,6 @@ impl Transport {
         }
     }
 
-    pub fn set_position(&self, position: f64) {
-        let _ = self.command_tx.send(AudioCommand::SetPosition(position));
-    }
-
     pub fn set_bpm(&self, bpm: f32) {
         self.audio_state.bpm.store(bpm);
         let _ = self.command_tx.send(AudioCommand::SetBPM(bpm));
@@ -92,24 +88,4 @@ impl Transport {
     pub fn is_recording(&self) -> bool {
         self.audio_state.recording.load(Ordering::Relaxed)
     }
-
-    pub fn rewind(&self) {
-        self.set_position(0.0);
-    }
-
-    pub fn fast_forward(&self, beats: f64) {
-        let current = self.get_position();
-        let sample_rate = self.audio_state.sample_rate.load();
-        let bpm = self.get_bpm();
-        let samples_per_beat = (60.0 / bpm) * sample_rate;
-        self.set_position(current + beats * samples_per_beat as f64);
-    }
-
-    pub fn rewind_beats(&self, beats: f64) {
-        let current = self.get_position();
-        let sample_rate = self.audio_state.sample_rate.load();
-        let bpm = self.get_bpm();
-        let samples_per_beat = (60.0 / bpm) * sample_rate;
-        self.set_position((current - beats * samples_per_beat as f64).max(0.0));
-    }
 }
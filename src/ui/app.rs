@@ -6,9 +6,11 @@ use crate::error::{ResultExt, UserNotification, common};
 use crate::input::InputManager;
 use crate::midi_import::ImportedTrack;
 use crate::input::actions::{ActionContext, AppAction};
+use crate::input::virtual_keyboard::VirtualKeyEvent;
 use crate::messages::{AudioCommand, PluginParamInfo, UiRx, UIUpdate};
 use crate::midi_input::MidiInputHandler;
-use crate::model::automation::AutomationTarget;
+use crate::midi_output::MidiClockOutput;
+use crate::model::automation::{AutomationPoint, AutomationTarget};
 use crate::model::clip::MidiPattern;
 use crate::model::track::TrackType;
 use crate::model::{AudioClip, MidiClip, MidiNote, Track};
@@ -20,6 +22,8 @@ use yadaw_plugin_api::UnifiedPluginInfo;
 
 use crate::track_manager::{TrackManager, UITrackType};
 use crate::transport::Transport;
+use crate::ui::piano_roll::PianoRollAction;
+use crate::ui::timeline::AutomationSelection;
 use flume::Sender;
 
 use eframe::egui;
@@ -35,6 +39,83 @@ use web_time::{Duration, Instant};
 pub enum ActiveEditTarget {
     Clips,
     Notes,
+    Automation,
+}
+
+/// A row of the track list and timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TrackRow {
+    /// A group's folder; its members follow unless it is collapsed
+    Folder(u64),
+    Track {
+        id: u64,
+        nested: bool,
+    },
+}
+
+/// Edits within this long of each other on one control share an undo entry
+const UNDO_COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Collapses the undo entries of one user action. The action's commands
+/// push their own entries from the command processor, after the batch has
+/// closed on the UI side, so the batch id travels with them and is echoed
+/// back around those entries.
+#[derive(Debug, Default)]
+struct UndoBatches {
+    /// Nesting depth of `with_undo_batch` calls
+    depth: usize,
+    /// Id of the open (or last) batch
+    current: u64,
+    /// Batch the command processor's entries currently belong to
+    remote: Option<u64>,
+    /// Last batch that has its entry on the stack
+    recorded: Option<u64>,
+}
+
+impl UndoBatches {
+    /// Opens a batch, returning its id when it is the outermost one
+    fn begin(&mut self) -> Option<u64> {
+        self.depth += 1;
+        (self.depth == 1).then(|| {
+            self.current += 1;
+            self.current
+        })
+    }
+
+    /// Closes a batch, true when it was the outermost one
+    fn end(&mut self) -> bool {
+        self.depth = self.depth.saturating_sub(1);
+        self.depth == 0
+    }
+
+    /// Whether an entry pushed from the UI should be recorded
+    fn claim_local(&mut self) -> bool {
+        let batch = (self.depth > 0).then_some(self.current);
+        self.claim(batch)
+    }
+
+    /// Whether an entry pushed by the command processor should be recorded
+    fn claim_remote(&mut self) -> bool {
+        self.claim(self.remote)
+    }
+
+    fn claim(&mut self, batch: Option<u64>) -> bool {
+        match batch {
+            Some(id) if self.recorded == Some(id) => false,
+            Some(id) => {
+                self.recorded = Some(id);
+                true
+            }
+            None => true,
+        }
+    }
+}
+
+/// Continuous control (fader, knob) whose undo entry is on the stack
+struct UndoGesture {
+    key: egui::Id,
+    dragging: bool,
+    last_change: Instant,
 }
 
 pub struct YadawApp {
@@ -53,6 +134,7 @@ pub struct YadawApp {
     pub(super) tracks_ui: super::tracks::TracksPanel,
     pub(super) timeline_ui: super::timeline::TimelineView,
     pub(super) mixer_ui: super::mixer::MixerWindow,
+    pub(super) spectrum_ui: super::spectrum::SpectrumWindow,
     pub(super) menu_bar: super::menu_bar::MenuBar,
     pub(super) piano_roll_view: super::piano_roll_view::PianoRollView,
 
@@ -63,6 +145,12 @@ pub struct YadawApp {
     pub(super) available_plugins: HashMap<String, UnifiedPluginInfo>,
     pub(super) selected_track_for_plugin: Option<u64>,
     pub(super) clap_param_meta: std::collections::HashMap<(u64, usize), Vec<PluginParamInfo>>,
+    /// Latest gain reduction (dB) of plugins that meter it, by plugin id
+    pub(super) plugin_gain_reduction: std::collections::HashMap<u64, f32>,
+    /// Latest plugin time per audio callback (ms), by track id
+    pub(super) track_cpu_ms: std::collections::HashMap<u64, f32>,
+    /// Duration of one audio callback (ms), what a track's plugin time is measured against
+    pub(super) callback_budget_ms: f32,
 
     // Selection state
     pub(super) selected_track: u64,
@@ -72,6 +160,8 @@ pub struct YadawApp {
     // Undo/Redo
     pub(super) undo_stack: VecDeque<AppStateSnapshot>,
     pub(super) redo_stack: VecDeque<AppStateSnapshot>,
+    undo_batches: UndoBatches,
+    undo_gesture: Option<UndoGesture>,
 
     // Other state
     pub(super) project_path: Option<String>,
@@ -79,8 +169,12 @@ pub struct YadawApp {
     pub(super) midi_clipboard: Option<Vec<MidiClip>>,
     pub(super) show_performance: bool,
     pub(super) performance_monitor: PerformanceMonitor,
+    /// Output the engine is running on: device, sample rate, frames per callback
+    pub(super) audio_device: Option<(String, f32, u32)>,
     pub(super) track_manager: TrackManager,
     pub(super) project_manager: ProjectManager,
+    // Plugin state epoch a capture timed out at, see `check_late_plugin_states`
+    late_plugin_states: Option<u64>,
 
     // Touch support
     touch_state: TouchState,
@@ -88,6 +182,7 @@ pub struct YadawApp {
     pub input_manager: InputManager,
 
     pub(super) note_clipboard: Option<Vec<MidiNote>>,
+    pub(super) automation_clipboard: Option<Vec<AutomationPoint>>,
     pub(super) active_edit_target: ActiveEditTarget,
     pub last_real_metrics_at: Option<Instant>,
 
@@ -99,6 +194,7 @@ pub struct YadawApp {
 
     pub midi_input_handler: Option<Arc<MidiInputHandler>>,
     pub available_midi_ports: Vec<String>,
+    pub midi_clock_output: Option<MidiClockOutput>,
 
     pub last_active_clip_per_track: HashMap<u64, u64>,
 }
@@ -158,6 +254,14 @@ impl YadawApp {
                 .unwrap_or_default()
         };
 
+        let midi_clock_output = match MidiClockOutput::new(audio_state.clone()) {
+            Ok(output) => Some(output),
+            Err(e) => {
+                log::warn!("Could not create MIDI clock output: {}", e);
+                None
+            }
+        };
+
         let mut input_manager = InputManager::new();
 
         let mut project_manager = ProjectManager::new();
@@ -187,11 +291,12 @@ impl YadawApp {
 
         project_manager.set_auto_save(config.behavior.auto_save);
 
-        Self {
+        let mut app = Self {
             transport_ui: super::transport::TransportUI::new(transport),
             tracks_ui: super::tracks::TracksPanel::new(),
             timeline_ui: super::timeline::TimelineView::new(),
             mixer_ui: super::mixer::MixerWindow::new(),
+            spectrum_ui: super::spectrum::SpectrumWindow::new(),
             menu_bar: super::menu_bar::MenuBar::new(),
             piano_roll_view: super::piano_roll_view::PianoRollView::new(),
             dialogs: super::dialogs::DialogManager::new(),
@@ -204,6 +309,9 @@ impl YadawApp {
             config: config.clone(),
             available_plugins: available_plugins_map,
             clap_param_meta: std::collections::HashMap::new(),
+            plugin_gain_reduction: std::collections::HashMap::new(),
+            track_cpu_ms: std::collections::HashMap::new(),
+            callback_budget_ms: 0.0,
 
             selected_track: initial_track_id,
             selected_pattern: 0,
@@ -212,18 +320,23 @@ impl YadawApp {
 
             undo_stack: VecDeque::new(),
             redo_stack: VecDeque::new(),
+            undo_batches: UndoBatches::default(),
+            undo_gesture: None,
 
             project_path: None,
             clipboard: None,
             midi_clipboard: None,
             note_clipboard: None,
+            automation_clipboard: None,
 
             active_edit_target: ActiveEditTarget::Clips,
 
             show_performance: false,
             performance_monitor: PerformanceMonitor::new(),
+            audio_device: None,
             track_manager: TrackManager::new(),
             project_manager,
+            late_plugin_states: None,
 
             touch_state: TouchState {
                 last_touch_pos: None,
@@ -244,15 +357,63 @@ impl YadawApp {
 
             midi_input_handler,
             available_midi_ports,
+            midi_clock_output,
 
             last_active_clip_per_track: HashMap::default(),
+        };
+        app.apply_midi_clock_config();
+        app.apply_metronome_config();
+        app
+    }
+
+    /// Hands the click subdivision and samples to the engine
+    pub fn apply_metronome_config(&self) {
+        use crate::config::ClickSubdivision;
+
+        let metronome = &self.config.metronome;
+        self.audio_state.click_eighths.store(
+            metronome.subdivision == ClickSubdivision::Eighth,
+            Ordering::Relaxed,
+        );
+        let _ = self.command_tx.send(AudioCommand::SetClickSamples {
+            accent: metronome.accent_sample.clone(),
+            click: metronome.click_sample.clone(),
+        });
+    }
+
+    /// Connects the clock output to the configured port and enables/disables sending.
+    pub fn apply_midi_clock_config(&mut self) {
+        let Some(output) = &self.midi_clock_output else {
+            return;
+        };
+        let midi = &self.config.midi;
+
+        let wanted = midi.clock_output_port.as_deref().filter(|_| midi.clock_output_enabled);
+        let current = output.connected_port_name.lock().unwrap().clone();
+        if wanted != current.as_deref() {
+            match wanted {
+                Some(port) => {
+                    if let Err(e) = output.connect(port) {
+                        log::warn!("{}", e);
+                        self.dialogs
+                            .show_message(&format!("MIDI clock output unavailable: {e}"));
+                    }
+                }
+                None => output.disconnect(),
+            }
         }
+
+        output.set_enabled(midi.clock_output_enabled);
     }
 
     // Core functionality methods
     pub fn push_undo(&mut self) {
-        let state = self.state.lock_sync();
-        self.undo_stack.push_back(state.snapshot());
+        if !self.undo_batches.claim_local() {
+            return;
+        }
+
+        let snapshot = self.undo_snapshot();
+        self.undo_stack.push_back(snapshot);
         self.redo_stack.clear();
 
         if self.undo_stack.len() > 100 {
@@ -260,34 +421,180 @@ impl YadawApp {
         }
 
         self.project_manager.mark_dirty();
+        self.undo_gesture = None;
+    }
+
+    /// Undo for a continuous control, keyed by track and parameter. Call with
+    /// the widget's response before sending its change: a whole drag, or quick
+    /// repeated edits of the same control, become one undo entry.
+    pub fn undo_gesture(&mut self, key: egui::Id, resp: &egui::Response) {
+        let now = Instant::now();
+        let ongoing = self.undo_gesture.as_ref().filter(|g| g.key == key);
+
+        // A fresh drag is a new move even if it follows the last one closely
+        if resp.drag_started() && ongoing.is_some_and(|g| !g.dragging) {
+            self.undo_gesture = None;
+        }
+
+        if resp.changed() {
+            let continues = self.undo_gesture.as_ref().is_some_and(|g| {
+                g.key == key
+                    && (g.dragging || now.duration_since(g.last_change) < UNDO_COALESCE_WINDOW)
+            });
+            if !continues {
+                self.push_undo();
+            }
+            self.undo_gesture = Some(UndoGesture {
+                key,
+                dragging: resp.dragged(),
+                last_change: now,
+            });
+        }
+
+        if resp.drag_stopped()
+            && let Some(g) = self.undo_gesture.as_mut().filter(|g| g.key == key)
+        {
+            g.dragging = false;
+            g.last_change = now;
+        }
+    }
+
+    /// Run `f` as one user action: every `push_undo` inside, and every undo
+    /// entry the commands it sends push, collapses into a single entry.
+    pub fn with_undo_batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.begin_undo_batch();
+        let result = f(self);
+        self.end_undo_batch();
+        result
+    }
+
+    /// Opens a batch for code that can't run inside [`Self::with_undo_batch`];
+    /// pair every call with [`Self::end_undo_batch`]
+    pub fn begin_undo_batch(&mut self) {
+        if let Some(id) = self.undo_batches.begin() {
+            let _ = self.command_tx.send(AudioCommand::SetUndoBatch(Some(id)));
+        }
+    }
+
+    pub fn end_undo_batch(&mut self) {
+        if self.undo_batches.end() {
+            let _ = self.command_tx.send(AudioCommand::SetUndoBatch(None));
+        }
     }
 
     pub fn undo(&mut self) {
+        self.undo_gesture = None;
         if let Some(snapshot) = self.undo_stack.pop_back() {
-            let mut state = self.state.lock_sync();
-            let current = state.snapshot();
+            let current = self.undo_snapshot();
             self.redo_stack.push_back(current);
-            state.restore(snapshot);
-            drop(state);
-
-            self.sync_views_after_model_change();
+            self.restore_undo_snapshot(snapshot);
             let _ = self.command_tx.send(AudioCommand::UpdateTracks);
         }
     }
 
     pub fn redo(&mut self) {
+        self.undo_gesture = None;
         if let Some(snapshot) = self.redo_stack.pop_back() {
-            let mut state = self.state.lock_sync();
-            let current = state.snapshot();
+            let current = self.undo_snapshot();
             self.undo_stack.push_back(current);
-            state.restore(snapshot);
-            drop(state);
-
-            self.sync_views_after_model_change();
+            self.restore_undo_snapshot(snapshot);
             let _ = self.command_tx.send(AudioCommand::UpdateTracks);
         }
     }
 
+    /// Model snapshot plus the clip selection and playhead it was taken with
+    fn undo_snapshot(&self) -> AppStateSnapshot {
+        let mut snapshot = self.state.lock_sync().snapshot();
+        self.stamp_editing_context(&mut snapshot);
+        snapshot
+    }
+
+    fn stamp_editing_context(&self, snapshot: &mut AppStateSnapshot) {
+        snapshot.selected_clips = self.selected_clips.clone();
+        snapshot.playhead_beat = Some(self.playhead_beat());
+    }
+
+    /// Restore the model, then the selection (minus clips that no longer
+    /// exist) and, while stopped, the playhead
+    fn restore_undo_snapshot(&mut self, mut snapshot: AppStateSnapshot) {
+        let selected = std::mem::take(&mut snapshot.selected_clips);
+        let playhead = snapshot.playhead_beat.take();
+        let bpm = snapshot.bpm;
+
+        let mut state = self.state.lock_sync();
+        state.restore(snapshot);
+        self.selected_clips = selected
+            .into_iter()
+            .filter(|id| state.clips_by_id.contains_key(id))
+            .collect();
+        drop(state);
+
+        self.sync_views_after_model_change();
+
+        let sample_rate = self.audio_state.sample_rate.load();
+        if let Some(beat) = playhead
+            && !self.audio_state.playing.load(Ordering::Relaxed)
+            && sample_rate > 0.0
+            && bpm > 0.0
+        {
+            let samples = crate::time_utils::quick::beats_to_samples(beat, sample_rate, bpm);
+            let _ = self.command_tx.send(AudioCommand::SetPosition(samples));
+        }
+    }
+
+    /// `track_order` narrowed by the track list's view filter
+    pub(super) fn visible_track_ids(&self) -> Vec<u64> {
+        let filter = self.config.ui.track_filter;
+        let state = self.state.lock_sync();
+        state
+            .track_order
+            .iter()
+            .copied()
+            .filter(|tid| state.tracks.get(tid).is_some_and(|t| filter.shows(t)))
+            .collect()
+    }
+
+    /// Visible tracks under their group folders. A folder takes the place of
+    /// its first member and gathers the rest below it; display only, the
+    /// track order is left alone.
+    pub(super) fn track_rows(&self) -> Vec<TrackRow> {
+        let visible = self.visible_track_ids();
+        let state = self.state.lock_sync();
+        let group_of = |tid: u64| {
+            state
+                .tracks
+                .get(&tid)
+                .and_then(|t| t.group_id)
+                .filter(|gid| state.groups.contains_key(gid))
+        };
+
+        let mut rows = Vec::new();
+        let mut placed = std::collections::HashSet::new();
+        for &tid in &visible {
+            let Some(gid) = group_of(tid) else {
+                rows.push(TrackRow::Track {
+                    id: tid,
+                    nested: false,
+                });
+                continue;
+            };
+            if !placed.insert(gid) {
+                continue;
+            }
+            rows.push(TrackRow::Folder(gid));
+            if state.groups[&gid].collapsed {
+                continue;
+            }
+            rows.extend(
+                visible
+                    .iter()
+                    .filter(|&&m| group_of(m) == Some(gid))
+                    .map(|&id| TrackRow::Track { id, nested: true }),
+            );
+        }
+        rows
+    }
+
     // Track management
     pub fn add_audio_track(&mut self) {
         self.push_undo();
@@ -321,6 +628,28 @@ impl YadawApp {
         let _ = self.command_tx.send(AudioCommand::UpdateTracks);
     }
 
+    /// Inserts a fresh track right below `after_id` and returns its id.
+    /// The caller owns undo and the engine sync.
+    pub(super) fn insert_track_after(&mut self, track_type: UITrackType, after_id: u64) -> u64 {
+        let mut state = self.state.lock_sync();
+        let track_id = state.fresh_id();
+        let mut track = self.track_manager.create_track(track_type, None);
+        track.id = track_id;
+        if track_type == UITrackType::Midi {
+            // Not the demo pattern, the dropped clips are the content
+            track.midi_clips.clear();
+        }
+        let pos = state
+            .track_order
+            .iter()
+            .position(|&id| id == after_id)
+            .map_or(state.track_order.len(), |i| i + 1);
+        state.track_order.insert(pos, track_id);
+        state.tracks.insert(track_id, track);
+        state.ensure_ids();
+        track_id
+    }
+
     pub fn add_bus_track(&mut self) {
         self.push_undo();
         let mut state = self.state.lock_sync();
@@ -334,11 +663,65 @@ impl YadawApp {
         let _ = self.command_tx.send(AudioCommand::UpdateTracks);
     }
 
-    pub fn add_automation_lane_by_id(&mut self, track_id: u64, target: AutomationTarget) {
+    /// Solo button click. `invert` flips the configured additive/exclusive mode.
+    pub(super) fn solo_track(&self, track_id: u64, solo: bool, invert: bool) {
+        let exclusive =
+            (self.config.behavior.solo_mode == crate::config::SoloMode::Exclusive) != invert;
+        let _ = self.command_tx.send(AudioCommand::SoloTrack {
+            track_id,
+            solo,
+            exclusive,
+        });
+    }
+
+    /// Creates a bus fed by `track_ids`, optionally grouping them under the bus name.
+    pub fn route_tracks_to_new_bus(&mut self, track_ids: &[u64], name: &str, group: bool) {
         self.push_undo();
-        let _ = self
-            .command_tx
-            .send(AudioCommand::AddAutomationPoint(track_id, target, 0.0, 0.5));
+        let name = (!name.trim().is_empty()).then(|| name.trim().to_string());
+        let bus = self.track_manager.create_track(UITrackType::Bus, name);
+        let bus_name = bus.name.clone();
+        let result = {
+            let mut guard = self.state.lock_sync();
+            let state = &mut *guard;
+            let bus_id = state.fresh_id();
+            crate::track_manager::route_to_new_bus(
+                &mut state.tracks,
+                &mut state.track_order,
+                bus,
+                bus_id,
+                track_ids,
+            )
+        };
+
+        match result {
+            Ok(()) => {
+                if group {
+                    let _ = self
+                        .command_tx
+                        .send(AudioCommand::CreateGroup(bus_name, track_ids.to_vec()));
+                }
+                let _ = self.command_tx.send(AudioCommand::UpdateTracks);
+                self.project_manager.mark_dirty();
+            }
+            Err(e) => {
+                self.undo_stack.pop_back();
+                self.dialogs.show_error(&e.to_string());
+            }
+        }
+    }
+
+    pub fn add_automation_lane_by_id(&mut self, track_id: u64, target: AutomationTarget) {
+        let seed = target.seed_value();
+        self.with_undo_batch(|app| {
+            app.push_undo();
+            let _ = app.command_tx.send(AudioCommand::AddAutomationPoint(
+                track_id,
+                target,
+                0.0,
+                seed,
+                app.config.ui.automation_lane_height,
+            ));
+        });
     }
 
     pub fn show_plugin_browser_for_track(&mut self, track_id: u64) {
@@ -359,8 +742,10 @@ impl YadawApp {
 
     // Update clipboard operations to use IDs
     pub fn cut_selected(&mut self) {
-        self.copy_selected();
-        self.delete_selected();
+        self.with_undo_batch(|app| {
+            app.copy_selected();
+            app.delete_selected();
+        });
     }
 
     /// Copy selected clips (ID-based)
@@ -580,6 +965,99 @@ impl YadawApp {
         let _ = self.command_tx.send(AudioCommand::UpdateTracks);
     }
 
+    fn delete_selected_automation_points(&mut self) {
+        let Some(selection) = self.timeline_ui.take_automation_selection() else {
+            return;
+        };
+        if selection.beats.is_empty() {
+            return;
+        }
+
+        self.with_undo_batch(|app| {
+            app.push_undo();
+            for beat in selection.beats {
+                let _ = app.command_tx.send(AudioCommand::RemoveAutomationPoint(
+                    selection.track_id,
+                    selection.lane_idx,
+                    beat,
+                ));
+            }
+        });
+    }
+
+    /// The selected automation points with the selection they came from
+    fn selected_automation_points(&self) -> Option<(AutomationSelection, Vec<AutomationPoint>)> {
+        let selection = self.timeline_ui.automation_selection()?.clone();
+        let state = self.state.lock_sync();
+        let lane = state
+            .tracks
+            .get(&selection.track_id)?
+            .automation_lanes
+            .get(selection.lane_idx)?;
+        let points: Vec<AutomationPoint> = lane
+            .points
+            .iter()
+            .filter(|p| selection.beats.iter().any(|b| (b - p.beat).abs() < 0.001))
+            .cloned()
+            .collect();
+        drop(state);
+        (!points.is_empty()).then_some((selection, points))
+    }
+
+    fn copy_automation_selection(&mut self) {
+        if let Some((_, points)) = self.selected_automation_points() {
+            self.automation_clipboard = Some(points);
+        }
+    }
+
+    /// Pastes copied automation into the lane last under the pointer
+    fn paste_automation_at_playhead(&mut self) {
+        let (Some(points), Some((track_id, lane_idx))) = (
+            self.automation_clipboard.clone(),
+            self.timeline_ui.automation_focus(),
+        ) else {
+            return;
+        };
+        self.push_undo();
+        let _ = self.command_tx.send(AudioCommand::PasteAutomation {
+            track_id,
+            lane_idx,
+            points,
+            at_beat: self.playhead_beat(),
+        });
+    }
+
+    /// Repeats the selected points right after the selection, then selects
+    /// the copy so duplicating again keeps extending the pattern
+    fn duplicate_automation_selection(&mut self) {
+        let Some((selection, points)) = self.selected_automation_points() else {
+            return;
+        };
+        let first = points.iter().map(|p| p.beat).fold(f64::INFINITY, f64::min);
+        let last = points
+            .iter()
+            .map(|p| p.beat)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let (start, end) = selection.range.unwrap_or((first, last));
+        let span = end - start;
+        if span <= 0.0 {
+            return;
+        }
+
+        self.push_undo();
+        let _ = self.command_tx.send(AudioCommand::PasteAutomation {
+            track_id: selection.track_id,
+            lane_idx: selection.lane_idx,
+            points,
+            at_beat: first + span,
+        });
+        self.timeline_ui.select_automation(AutomationSelection {
+            beats: selection.beats.iter().map(|b| b + span).collect(),
+            range: selection.range.map(|(s, e)| (s + span, e + span)),
+            ..selection
+        });
+    }
+
     /// Delete selected clips (ID-based)
     pub fn delete_selected(&mut self) {
         if self.selected_clips.is_empty() {
@@ -599,17 +1077,21 @@ impl YadawApp {
 
         let clip_ids = self.selected_clips.clone();
         let mut state = self.state.lock_sync();
+        let mut spans = Vec::new();
 
         for clip_id in clip_ids {
             if let Some((track, loc)) = state.find_clip_mut(clip_id) {
-                match loc {
+                let (start, len) = match loc {
                     crate::project::ClipLocation::Midi(idx) => {
-                        track.midi_clips.remove(idx);
+                        let clip = track.midi_clips.remove(idx);
+                        (clip.start_beat, clip.length_beats)
                     }
                     crate::project::ClipLocation::Audio(idx) => {
-                        track.audio_clips.remove(idx);
+                        let clip = track.audio_clips.remove(idx);
+                        (clip.start_beat, clip.length_beats)
                     }
-                }
+                };
+                spans.push((track.id, start, start + len));
                 state.clips_by_id.remove(&clip_id);
             }
         }
@@ -617,6 +1099,9 @@ impl YadawApp {
         self.selected_clips.clear();
         drop(state);
         let _ = self.command_tx.send(AudioCommand::UpdateTracks);
+        for cmd in self.timeline_ui.ripple_delete_commands(&spans) {
+            let _ = self.command_tx.send(cmd);
+        }
     }
 
     // Selection
@@ -667,19 +1152,153 @@ impl YadawApp {
         }
     }
 
+    /// Saves a plugin's params as a named preset. The command processor saves a
+    /// fresh state blob from the live plugin along with them.
+    pub(super) fn save_plugin_preset(&self, track_id: u64, plugin_idx: usize, name: String) {
+        let _ = self
+            .command_tx
+            .send(AudioCommand::SavePluginPreset(track_id, plugin_idx, name));
+    }
+
+    /// Saves a track's type, color, sends and plugins as a named template. The
+    /// command processor saves fresh state blobs from the live plugins.
+    pub(super) fn save_track_template(&self, track_id: u64, name: String) {
+        let _ = self
+            .command_tx
+            .send(AudioCommand::SaveTrackTemplate(track_id, name));
+    }
+
+    /// Adds a track below the selected one from a saved template. The engine
+    /// instantiates its plugins one by one, as if they were added by hand.
+    pub fn add_track_from_template(&mut self, name: &str) {
+        let template = match crate::presets::load_track_template(name) {
+            Ok(t) => t,
+            Err(e) => {
+                self.dialogs.show_message(&e.to_string());
+                return;
+            }
+        };
+        let track_type = match template.track_type {
+            TrackType::Audio => UITrackType::Audio,
+            TrackType::Midi => UITrackType::Midi,
+            TrackType::Bus => UITrackType::Bus,
+        };
+
+        self.push_undo();
+        let track_id = self.insert_track_after(track_type, self.selected_track);
+        {
+            let mut state = self.state.lock_sync();
+            // Sends only make sense if a bus of that name exists in this project
+            let sends: Vec<_> = template
+                .sends
+                .into_iter()
+                .filter_map(|s| {
+                    let bus = state.track_order.iter().copied().find(|&id| {
+                        id != track_id
+                            && state
+                                .tracks
+                                .get(&id)
+                                .is_some_and(|t| t.track_type == TrackType::Bus && t.name == s.bus)
+                    })?;
+                    Some(crate::model::track::Send {
+                        destination_track: bus,
+                        amount: s.amount,
+                        pre_fader: s.pre_fader,
+                        muted: s.muted,
+                    })
+                })
+                .collect();
+            if let Some(track) = state.tracks.get_mut(&track_id) {
+                track.name = template.name;
+                track.color = template.color;
+                track.sends = sends;
+            }
+        }
+        self.select_track(track_id);
+
+        let _ = self.command_tx.send(AudioCommand::UpdateTracks);
+        for desc in template.plugins {
+            let _ = self
+                .command_tx
+                .send(AudioCommand::AddPluginWithState { track_id, desc });
+        }
+    }
+
+    /// Has the command processor save every plugin's state blob onto the plugin
+    /// descriptors, and waits for it. If it doesn't finish in time, the blobs
+    /// from the last load or capture are saved, and the project is marked
+    /// dirty once the late ones arrive, see `check_late_plugin_states`.
+    pub(super) fn capture_plugin_states(&mut self) {
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        let has_plugins = self
+            .state
+            .lock_sync()
+            .tracks
+            .values()
+            .any(|t| !t.plugin_chain.is_empty());
+        if !has_plugins {
+            return;
+        }
+
+        let epoch = &self.audio_state.plugin_states_epoch;
+        let before = epoch.load(Ordering::Acquire);
+        if self
+            .command_tx
+            .send(AudioCommand::CapturePluginStates)
+            .is_err()
+        {
+            return;
+        }
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while epoch.load(Ordering::Acquire) == before {
+            if Instant::now() >= deadline {
+                log::warn!("Plugin state capture timed out, keeping previously saved states");
+                self.late_plugin_states = Some(before);
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        self.late_plugin_states = None;
+    }
+
+    /// Marks the project dirty once a capture that timed out has finished, so
+    /// the states it missed get saved next time
+    fn check_late_plugin_states(&mut self) {
+        if let Some(before) = self.late_plugin_states
+            && self.audio_state.plugin_states_epoch.load(Ordering::Acquire) != before
+        {
+            self.late_plugin_states = None;
+            self.project_manager.mark_dirty();
+        }
+    }
+
+    /// Saves to `<name>.v<n+1>.yadaw` next to the current project and keeps
+    /// working in the new file
+    pub fn save_new_version(&mut self) {
+        let Some(current) = self.project_path.clone() else {
+            self.dialogs.show_save_dialog();
+            return;
+        };
+        let next = crate::project_manager::next_version_path(Path::new(&current));
+        self.save_project_to_path(&next);
+    }
+
+    /// Copies the engine's live tempo and loop settings into `state` before a save
+    fn store_live_transport(&self, state: &mut AppState) {
+        state.bpm = self.audio_state.bpm.load();
+        state.loop_start = self.audio_state.loop_start.load();
+        state.loop_end = self.audio_state.loop_end.load();
+        state.loop_enabled = self.audio_state.loop_enabled.load(Ordering::Relaxed);
+    }
+
     pub fn save_project_to_path(&mut self, path: &Path) {
-        let live_bpm = self.audio_state.bpm.load();
-        let live_loop_start = self.audio_state.loop_start.load();
-        let live_loop_end = self.audio_state.loop_end.load();
-        let live_loop_enabled = self.audio_state.loop_enabled.load(Ordering::Relaxed);
+        self.capture_plugin_states();
 
         let save_result = {
             let mut state = self.state.lock_sync();
-            state.bpm = live_bpm;
-            state.loop_start = live_loop_start;
-            state.loop_end = live_loop_end;
-            state.loop_enabled = live_loop_enabled;
-
+            self.store_live_transport(&mut state);
             self.project_manager.save_project(&state, path)
         };
 
@@ -692,6 +1311,98 @@ impl YadawApp {
             .notify_user(&mut self.dialogs);
     }
 
+    /// Saves the project with its audio written to a `media/` folder beside it
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn collect_and_save(&mut self) {
+        let Some(current) = self.project_path.clone() else {
+            self.dialogs
+                .show_message("Save the project once before collecting its audio");
+            return;
+        };
+        self.capture_plugin_states();
+
+        let result = {
+            let mut state = self.state.lock_sync();
+            self.store_live_transport(&mut state);
+            self.project_manager
+                .collect_and_save(&mut state, Path::new(&current))
+        };
+
+        result
+            .map_err(common::project_save_failed)
+            .map(|written| {
+                self.dialogs.show_success(&format!(
+                    "Project saved, {} audio file(s) written to media/",
+                    written
+                ));
+            })
+            .notify_user(&mut self.dialogs);
+    }
+
+    /// Media paths of clips whose audio couldn't be found, one entry per file
+    pub fn missing_media(&self) -> Vec<String> {
+        let state = self.state.lock_sync();
+        let mut missing: Vec<String> = state
+            .tracks
+            .values()
+            .flat_map(|t| t.audio_clips.iter().chain(t.takes.iter()))
+            .filter(|c| c.is_missing_media())
+            .filter_map(|c| c.media_path.clone())
+            .collect();
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+
+    /// Points every clip that used `media_path` at `file` and loads its audio
+    pub fn relink_media(&mut self, media_path: &str, file: &Path) -> anyhow::Result<()> {
+        let (samples, samples_right, sample_rate) = crate::project_manager::read_media_file(file)?;
+        let new_path = crate::project_manager::media_path_for(
+            self.project_path.as_deref().map(Path::new),
+            file,
+        );
+        {
+            let mut state = self.state.lock_sync();
+            for track in state.tracks.values_mut() {
+                for clip in track.audio_clips.iter_mut().chain(track.takes.iter_mut()) {
+                    if clip.media_path.as_deref() == Some(media_path) {
+                        clip.samples = samples.clone();
+                        clip.samples_right = samples_right.clone();
+                        clip.sample_rate = sample_rate;
+                        clip.media_path = Some(new_path.clone());
+                        clip.peaks.invalidate();
+                    }
+                }
+            }
+        }
+        self.project_manager.mark_dirty();
+        let _ = self.command_tx.send(AudioCommand::UpdateTracks);
+        Ok(())
+    }
+
+    /// Relinks missing media to same-named files under `folder`. Returns how
+    /// many files were found.
+    pub fn relink_media_from_folder(&mut self, folder: &Path) -> usize {
+        let missing = self.missing_media();
+        let by_name: std::collections::HashMap<String, String> = missing
+            .into_iter()
+            .filter_map(|m| {
+                let name = Path::new(&m).file_name()?.to_str()?.to_string();
+                Some((name, m))
+            })
+            .collect();
+        let names = by_name.keys().cloned().collect();
+
+        let mut relinked = 0;
+        for (name, file) in crate::project_manager::find_files_named(folder, &names) {
+            match self.relink_media(&by_name[&name], &file) {
+                Ok(()) => relinked += 1,
+                Err(e) => log::warn!("Failed to relink {}: {}", file.display(), e),
+            }
+        }
+        relinked
+    }
+
     pub fn load_project_from_path(&mut self, path: &Path) {
         self.project_manager
             .load_project(path)
@@ -724,49 +1435,45 @@ impl YadawApp {
                 let _ = self.command_tx.send(AudioCommand::RebuildAllRtChains);
 
                 self.hydrate_audio_cache();
+
+                if !self.missing_media().is_empty() {
+                    self.dialogs.show_missing_media();
+                }
             })
             .notify_user(&mut self.dialogs);
     }
 
     // Audio operations
+    fn has_selected_audio_clips(&self) -> bool {
+        let state = self.state.lock_sync();
+        self.selected_clips
+            .iter()
+            .any(|&id| matches!(state.find_clip(id), Some((_, ClipLocation::Audio(_)))))
+    }
+
     pub fn normalize_selected(&mut self) {
-        if self.selected_clips.is_empty() {
+        if !self.has_selected_audio_clips() {
             return;
         }
         self.push_undo();
 
-        let mut state = self.state.lock_sync();
-        for &clip_id in &self.selected_clips {
-            if let Some((track, loc)) = state.find_clip_mut(clip_id) {
-                if let crate::project::ClipLocation::Audio(idx) = loc {
-                    if let Some(clip) = track.audio_clips.get_mut(idx) {
-                        let peak = clip.samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
-                        if peak > 0.0 {
-                            let gain = crate::constants::NORMALIZE_TARGET_LINEAR / peak;
-                            for s in &mut clip.samples {
-                                *s *= gain;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        drop(state);
+        normalize_clips(&mut self.state.lock_sync(), &self.selected_clips);
         let _ = self.command_tx.send(AudioCommand::UpdateTracks);
     }
 
     pub fn reverse_selected(&mut self) {
-        if self.selected_clips.is_empty() {
+        if !self.has_selected_audio_clips() {
             return;
         }
         self.push_undo();
 
+        let bpm = self.audio_state.bpm.load();
         let mut state = self.state.lock_sync();
         for &clip_id in &self.selected_clips {
             if let Some((track, loc)) = state.find_clip_mut(clip_id) {
                 if let crate::project::ClipLocation::Audio(idx) = loc {
                     if let Some(clip) = track.audio_clips.get_mut(idx) {
-                        clip.samples.reverse();
+                        clip.toggle_reversed(bpm);
                     }
                 }
             }
@@ -845,11 +1552,80 @@ impl YadawApp {
                 .send(AudioCommand::SetAudioClipWarpMode(*clip_id, enabled));
         }
 
-        targets.len()
+        targets.len()
+    }
+
+    /// Warps an audio clip at the tempo its loop length implies
+    pub fn warp_audio_clip_to_tempo(&mut self, clip_id: u64) {
+        let bpm = self.audio_state.bpm.load();
+        let original_bpm = {
+            let state = self.state.lock_sync();
+            let Some((track, ClipLocation::Audio(idx))) = state.find_clip(clip_id) else {
+                return;
+            };
+            let Some(clip) = track.audio_clips.get(idx) else {
+                return;
+            };
+            let duration = clip.samples.len() as f64 / clip.sample_rate as f64;
+            crate::audio_utils::detect_loop_bpm(duration, bpm)
+        };
+
+        self.push_undo();
+        let _ = self
+            .command_tx
+            .send(AudioCommand::WarpAudioClipToTempo(clip_id, original_bpm));
+    }
+
+    /// Bounces the span of the selected clips on the first one's track
+    pub fn bounce_selected_clips(&mut self) {
+        let range = {
+            let state = self.state.lock_sync();
+            let Some(track_id) = self
+                .selected_clips
+                .first()
+                .and_then(|id| state.clips_by_id.get(id))
+                .map(|r| r.track_id)
+            else {
+                return;
+            };
+            state.tracks.get(&track_id).and_then(|track| {
+                let audio = track
+                    .audio_clips
+                    .iter()
+                    .map(|c| (c.id, c.start_beat, c.length_beats));
+                let midi = track
+                    .midi_clips
+                    .iter()
+                    .map(|c| (c.id, c.start_beat, c.length_beats));
+                audio
+                    .chain(midi)
+                    .filter(|(id, _, _)| self.selected_clips.contains(id))
+                    .map(|(_, start, len)| (start, start + len))
+                    .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+                    .map(|(start, end)| (track_id, start, end))
+            })
+        };
+        if let Some((track_id, start_beat, end_beat)) = range {
+            self.bounce_to_track(track_id, start_beat, end_beat);
+        }
+    }
+
+    /// Renders a range of a track through its plugins onto a new audio track
+    /// below it, and mutes the clips inside it, see `AudioCommand::BounceRange`
+    pub fn bounce_to_track(&mut self, track_id: u64, start_beat: f64, end_beat: f64) {
+        if end_beat <= start_beat {
+            return;
+        }
+        self.push_undo();
+        let _ = self.command_tx.send(AudioCommand::BounceRange {
+            track_id,
+            start_beat,
+            end_beat,
+        });
     }
 
     pub fn apply_fade_in(&mut self) {
-        if self.selected_clips.is_empty() {
+        if !self.has_selected_audio_clips() {
             return;
         }
         self.push_undo();
@@ -870,7 +1646,7 @@ impl YadawApp {
     }
 
     pub fn apply_fade_out(&mut self) {
-        if self.selected_clips.is_empty() {
+        if !self.has_selected_audio_clips() {
             return;
         }
         self.push_undo();
@@ -890,6 +1666,29 @@ impl YadawApp {
         let _ = self.command_tx.send(AudioCommand::UpdateTracks);
     }
 
+    /// Sets both fades of the selected audio clips to `fade_ms`; 0 removes them
+    pub fn set_fades_for_selected(&mut self, fade_ms: f32) {
+        if !self.has_selected_audio_clips() {
+            return;
+        }
+        self.push_undo();
+
+        let mut state = self.state.lock_sync();
+        let bpm = state.bpm;
+        for &clip_id in &self.selected_clips {
+            if let Some((track, ClipLocation::Audio(idx))) = state.find_clip_mut(clip_id)
+                && let Some(clip) = track.audio_clips.get_mut(idx)
+            {
+                let beats = crate::audio_import::fade_beats(clip, fade_ms, bpm);
+                let fade = (beats > 0.0).then_some(beats);
+                clip.fade_in = fade;
+                clip.fade_out = fade;
+            }
+        }
+        drop(state);
+        let _ = self.command_tx.send(AudioCommand::UpdateTracks);
+    }
+
     pub fn split_selected_at_playhead(&mut self) {
         if self.selected_clips.is_empty() {
             return;
@@ -990,24 +1789,19 @@ impl YadawApp {
     }
 
     pub fn transpose_selected_notes(&mut self, semitones: i32) {
-        self.push_undo();
-        let Some(clip_id) = self.piano_roll_view.selected_clip else {
-            return;
-        };
-        let note_ids = self.piano_roll_view.piano_roll.selected_note_ids.clone();
-        if note_ids.is_empty() {
+        if self.piano_roll_view.selected_clip.is_none()
+            || self.piano_roll_view.piano_roll.selected_note_ids.is_empty()
+        {
             return;
         }
+        self.push_undo();
 
-        let _ = self.command_tx.send(AudioCommand::TransposeSelectedNotes {
-            clip_id,
-            note_ids,
-            semitones,
-        });
+        let action = PianoRollAction::TransposeSelection(semitones.clamp(-127, 127) as i8);
+        self.piano_roll_view
+            .transform_selection(action, &self.command_tx);
     }
 
     fn nudge_notes(&mut self, direction: f32, fine: bool, coarse: bool) {
-        self.push_undo();
         let Some(clip_id) = self.piano_roll_view.selected_clip else {
             return;
         };
@@ -1015,6 +1809,7 @@ impl YadawApp {
         if note_ids.is_empty() {
             return;
         }
+        self.push_undo();
 
         let grid = self.piano_roll_view.piano_roll.grid_snap as f64;
         let delta_beats = if fine {
@@ -1033,7 +1828,6 @@ impl YadawApp {
     }
 
     pub fn quantize_selected_notes_with_params(&mut self, strength: f32, grid: f32, _swing: f32) {
-        self.push_undo();
         let Some(clip_id) = self.piano_roll_view.selected_clip else {
             return;
         };
@@ -1041,6 +1835,7 @@ impl YadawApp {
         if note_ids.is_empty() {
             return;
         }
+        self.push_undo();
 
         let _ = self.command_tx.send(AudioCommand::QuantizeSelectedNotes {
             clip_id,
@@ -1050,8 +1845,7 @@ impl YadawApp {
         });
     }
 
-    pub fn humanize_selected_notes(&mut self, amount: f32) {
-        self.push_undo();
+    pub fn humanize_selected_notes(&mut self, timing_ms: f32, velocity: u8) {
         let Some(clip_id) = self.piano_roll_view.selected_clip else {
             return;
         };
@@ -1059,19 +1853,81 @@ impl YadawApp {
         if note_ids.is_empty() {
             return;
         }
+        self.push_undo();
 
+        let bpm = self.audio_state.bpm.load() as f64;
+        let timing_beats = timing_ms as f64 / 1000.0 * bpm / 60.0;
         let _ = self.command_tx.send(AudioCommand::HumanizeSelectedNotes {
             clip_id,
             note_ids,
-            amount,
+            timing_beats,
+            velocity,
         });
     }
 
-    pub fn add_automation_lane(&mut self, track_id: u64, target: AutomationTarget) {
+    pub fn legato_selected_notes(&mut self) {
+        let Some(clip_id) = self.piano_roll_view.selected_clip else {
+            return;
+        };
+        let note_ids = self.piano_roll_view.piano_roll.selected_note_ids.clone();
+        if note_ids.is_empty() {
+            return;
+        }
         self.push_undo();
+
+        let _ = self
+            .command_tx
+            .send(AudioCommand::LegatoSelectedNotes { clip_id, note_ids });
+    }
+
+    pub fn scale_selected_note_lengths(&mut self, factor: f64) {
+        let Some(clip_id) = self.piano_roll_view.selected_clip else {
+            return;
+        };
+        let note_ids = self.piano_roll_view.piano_roll.selected_note_ids.clone();
+        if note_ids.is_empty() || !factor.is_finite() || factor <= 0.0 {
+            return;
+        }
+        self.push_undo();
+
         let _ = self
             .command_tx
-            .send(AudioCommand::AddAutomationPoint(track_id, target, 0.0, 0.5));
+            .send(AudioCommand::ScaleSelectedNoteLengths {
+                clip_id,
+                note_ids,
+                factor,
+            });
+    }
+
+    pub fn add_automation_lane(&mut self, track_id: u64, target: AutomationTarget) {
+        let seed = target.seed_value();
+        self.with_undo_batch(|app| {
+            app.push_undo();
+            let _ = app.command_tx.send(AudioCommand::AddAutomationPoint(
+                track_id,
+                target,
+                0.0,
+                seed,
+                app.config.ui.automation_lane_height,
+            ));
+        });
+    }
+
+    /// Reports a control bound to `target` being grabbed or released, which
+    /// is when Touch and Latch lanes start and stop writing
+    pub fn touch_automation(&self, track_id: u64, target: AutomationTarget, resp: &egui::Response) {
+        let touched = if resp.drag_started() {
+            true
+        } else if resp.drag_stopped() {
+            false
+        } else {
+            return;
+        };
+        let _ = self.command_tx.send(AudioCommand::TouchAutomation {
+            track_id,
+            target,
+            touched,
+        });
     }
 
     pub fn zoom_to_fit(&mut self) {
@@ -1099,12 +1955,19 @@ impl YadawApp {
     }
 
     pub fn tap_tempo(&mut self) {
-        self.push_undo();
+        if self.audio_state.external_sync.load(Ordering::Relaxed) {
+            return;
+        }
         let now = Instant::now();
-        let taps = &mut self.touch_state.tap_times;
 
-        // Keep only taps within the last 2 seconds
-        taps.retain(|t| now.duration_since(*t).as_secs_f64() < 2.0);
+        // Keep only taps within the last 2 seconds; a fresh run of taps is one undo step
+        self.touch_state
+            .tap_times
+            .retain(|t| now.duration_since(*t).as_secs_f64() < 2.0);
+        if self.touch_state.tap_times.is_empty() {
+            self.push_undo();
+        }
+        let taps = &mut self.touch_state.tap_times;
         taps.push(now);
 
         if taps.len() >= 2 {
@@ -1129,6 +1992,83 @@ impl YadawApp {
         }
     }
 
+    /// Playhead position in beats
+    pub fn playhead_beat(&self) -> f64 {
+        let position = self.audio_state.get_position();
+        let sample_rate = self.audio_state.sample_rate.load();
+        let bpm = self.audio_state.bpm.load();
+        if sample_rate > 0.0 && bpm > 0.0 {
+            crate::time_utils::quick::samples_to_beats(position, sample_rate, bpm)
+        } else {
+            0.0
+        }
+    }
+
+    /// Grid size of the editor currently shown, in beats
+    fn active_grid_beats(&self) -> f64 {
+        if self.is_selected_track_midi() {
+            self.piano_roll_view.piano_roll.grid_snap as f64
+        } else {
+            self.timeline_ui.grid_snap as f64
+        }
+    }
+
+    /// Add a numbered marker at `beat`, as one undo step
+    pub fn add_marker(&mut self, beat: f64) {
+        self.push_undo();
+        let mut state = self.state.lock_sync();
+        let name = state.next_marker_name();
+        state.add_marker(beat, name);
+    }
+
+    pub fn remove_marker(&mut self, index: usize) {
+        self.push_undo();
+        let mut state = self.state.lock_sync();
+        if index < state.markers.len() {
+            state.markers.remove(index);
+        }
+    }
+
+    /// Move the playhead, snapping to the grid while stopped if the preference is set
+    pub fn seek_to_beat(&mut self, beat: f64) {
+        let mut beat = beat.max(0.0);
+        if self.config.behavior.lock_playhead_to_grid
+            && !self.audio_state.playing.load(Ordering::Relaxed)
+        {
+            beat = crate::time_utils::quantize_to_grid(beat, self.active_grid_beats());
+        }
+
+        let sample_rate = self.audio_state.sample_rate.load();
+        let bpm = self.audio_state.bpm.load();
+        if sample_rate > 0.0 && bpm > 0.0 {
+            let samples = crate::time_utils::quick::beats_to_samples(beat, sample_rate, bpm);
+            let _ = self.command_tx.send(AudioCommand::SetPosition(samples));
+        }
+    }
+
+    /// Step the playhead to the previous/next grid line
+    fn nudge_playhead(&mut self, direction: f64) {
+        // Arrow keys move selected clips instead (see TimelineView::handle_keyboard_nudge)
+        if !self.selected_clips.is_empty() {
+            return;
+        }
+
+        let grid = self.active_grid_beats().max(1e-6);
+        let pos = self.playhead_beat() / grid;
+        // Treat positions within rounding error of a grid line as on it
+        let pos = if (pos - pos.round()).abs() < 1e-6 {
+            pos.round()
+        } else {
+            pos
+        };
+        let target = if direction > 0.0 {
+            (pos.floor() + 1.0) * grid
+        } else {
+            (pos.ceil() - 1.0) * grid
+        };
+        self.seek_to_beat(target);
+    }
+
     pub fn import_audio_dialog(&mut self) {
         self.dialogs.open_import_audio();
     }
@@ -1182,6 +2122,16 @@ impl YadawApp {
             self.mixer_ui = mixer;
         }
 
+        // Spectrum analyzer; the engine only taps a source while it's open
+        self.audio_state
+            .spectrum_source
+            .store(self.spectrum_ui.source_id(), Ordering::Relaxed);
+        if self.spectrum_ui.is_visible() {
+            let mut spectrum = std::mem::take(&mut self.spectrum_ui);
+            spectrum.show(ctx, self);
+            self.spectrum_ui = spectrum;
+        }
+
         // Dialogs
         let mut dialogs = std::mem::take(&mut self.dialogs);
         dialogs.show_all(ctx, self);
@@ -1202,12 +2152,33 @@ impl YadawApp {
                 self.tracks_ui.update_levels(levels);
             }
             UIUpdate::RecordingFinished(track_id, mut clip) => {
+                crate::audio_import::apply_default_fades(
+                    &mut clip,
+                    self.config.behavior.default_clip_fade_ms,
+                    self.audio_state.bpm.load(),
+                );
                 self.push_undo();
                 let mut state = self.state.lock_sync();
                 clip.id = state.fresh_id();
                 let clip_id = clip.id;
 
-                let added = if let Some(track) = state.tracks.get_mut(&track_id) {
+                // Punched takes start inside the punch region; blend them into what's there
+                let punched = self
+                    .audio_state
+                    .punch_range()
+                    .is_some_and(|(start, end)| (start - 1e-3..end).contains(&clip.start_beat));
+                let added = if punched {
+                    let bpm = self.audio_state.bpm.load() as f64;
+                    let fade_ms = self
+                        .config
+                        .behavior
+                        .default_clip_fade_ms
+                        .max(crate::constants::PUNCH_CROSSFADE_MS);
+                    let crossfade = fade_ms as f64 / 1000.0 * bpm / 60.0;
+                    // Indexes the clip itself
+                    state.insert_punch_clip(track_id, clip, crossfade);
+                    false
+                } else if let Some(track) = state.tracks.get_mut(&track_id) {
                     if !matches!(track.track_type, crate::model::track::TrackType::Midi) {
                         track.audio_clips.push(clip);
                         true
@@ -1234,18 +2205,149 @@ impl YadawApp {
                     .send(crate::messages::AudioCommand::UpdateTracks);
                 self.project_manager.mark_dirty();
             }
+            UIUpdate::TakesRecorded(track_id, mut takes) => {
+                self.push_undo();
+                let fade_ms = self.config.behavior.default_clip_fade_ms;
+                let bpm = self.audio_state.bpm.load();
+                let mut state = self.state.lock_sync();
+                let region = state.fresh_id();
+                for take in &mut takes {
+                    crate::audio_import::apply_default_fades(take, fade_ms, bpm);
+                    take.id = state.fresh_id();
+                    take.take_region = Some(region);
+                }
+
+                if let Some(track) = state.tracks.get_mut(&track_id)
+                    && !matches!(track.track_type, crate::model::track::TrackType::Midi)
+                    && let Some(last) = takes.pop()
+                {
+                    // The latest pass plays until another take is picked
+                    track.active_takes.insert(region, last.id);
+                    track.audio_clips.push(last);
+                    track.takes.extend(takes);
+                    state.rebuild_clip_index();
+                }
+
+                drop(state);
+                let _ = self
+                    .command_tx
+                    .send(crate::messages::AudioCommand::UpdateTracks);
+                self.project_manager.mark_dirty();
+            }
+            UIUpdate::MidiRecordingFinished(track_id, mut clip) => {
+                clip.name = format!("Rec {}", chrono::Local::now().format("%H:%M:%S"));
+                self.push_undo();
+                let mut state = self.state.lock_sync();
+                let mut notes = std::mem::take(&mut clip.notes);
+                for note in &mut notes {
+                    note.id = state.fresh_id();
+                }
+                let pattern_id = state.fresh_id();
+                state.patterns.insert(
+                    pattern_id,
+                    crate::model::clip::MidiPattern {
+                        id: pattern_id,
+                        name: String::new(),
+                        notes,
+                    },
+                );
+                clip.id = state.fresh_id();
+                clip.pattern_id = Some(pattern_id);
+                let clip_id = clip.id;
+
+                if let Some(track) = state.tracks.get_mut(&track_id)
+                    && matches!(track.track_type, crate::model::track::TrackType::Midi)
+                {
+                    track.midi_clips.push(clip);
+                    state.clips_by_id.insert(
+                        clip_id,
+                        crate::project::ClipRef {
+                            track_id,
+                            is_midi: true,
+                        },
+                    );
+                }
+
+                drop(state);
+                let _ = self
+                    .command_tx
+                    .send(crate::messages::AudioCommand::UpdateTracks);
+                self.project_manager.mark_dirty();
+            }
+            UIUpdate::MidiNoteInput(pitch, velocity) => {
+                let roll = &self.piano_roll_view.piano_roll;
+                if roll.step_input {
+                    // One undo step per chord
+                    if velocity > 0 && !roll.step_chord_open() {
+                        self.push_undo();
+                    }
+                    self.piano_roll_view
+                        .piano_roll
+                        .queue_step_note(pitch, velocity);
+                }
+            }
             UIUpdate::RecordingStateChanged(on) => {
                 self.is_recording_ui = on;
             }
             UIUpdate::RecordingLevel(_) => {}
-            UIUpdate::MasterLevel(_, _) => {}
-            UIUpdate::PushUndo(snapshot) => {
-                self.undo_stack.push_back(snapshot);
-                self.redo_stack.clear();
-                if self.undo_stack.len() > 100 {
-                    self.undo_stack.remove(0);
+            UIUpdate::MasterLevel(left, right) => {
+                self.mixer_ui.update_master_level(left, right);
+            }
+            UIUpdate::AudioDeviceOpened {
+                device,
+                sample_rate,
+                buffer_size,
+            } => {
+                self.state.lock_sync().sample_rate = sample_rate;
+                // The engine dropped the click samples loaded at the old rate
+                let rate_changed = self
+                    .audio_device
+                    .as_ref()
+                    .is_some_and(|(_, old_rate, _)| *old_rate != sample_rate);
+                self.audio_device = Some((device, sample_rate, buffer_size));
+                if rate_changed {
+                    self.apply_metronome_config();
+                }
+            }
+            UIUpdate::TrackLatency(latency) => {
+                self.mixer_ui.update_track_latency(latency);
+            }
+            UIUpdate::MasterCorrelation(correlation, mono_peak) => {
+                self.mixer_ui.update_master_correlation(correlation, mono_peak);
+            }
+            UIUpdate::LoudnessMeter {
+                momentary,
+                short_term,
+                integrated,
+            } => {
+                self.mixer_ui
+                    .update_master_loudness(momentary, short_term, integrated);
+            }
+            UIUpdate::PluginGainReduction(reduction) => {
+                self.plugin_gain_reduction = reduction;
+            }
+            UIUpdate::TrackCpu { track_id, ms } => {
+                self.track_cpu_ms.insert(track_id, ms);
+            }
+            UIUpdate::Spectrum { track_id, bins } => {
+                self.spectrum_ui.update_spectrum(track_id, &bins);
+            }
+            UIUpdate::PushUndo(mut snapshot) => {
+                if self.undo_batches.claim_remote() {
+                    self.undo_gesture = None;
+                    if snapshot.playhead_beat.is_none() {
+                        self.stamp_editing_context(&mut snapshot);
+                    }
+                    self.undo_stack.push_back(snapshot);
+                    self.redo_stack.clear();
+                    if self.undo_stack.len() > 100 {
+                        self.undo_stack.remove(0);
+                    }
                 }
             }
+            UIUpdate::UndoBatch(batch) => {
+                self.undo_batches.remote = batch;
+            }
             UIUpdate::PerformanceMetric {
                 cpu_usage,
                 buffer_fill,
@@ -1265,12 +2367,16 @@ impl YadawApp {
                     latency_ms,
                 };
                 self.performance_monitor.update_metrics(metrics);
+                self.callback_budget_ms = latency_ms;
                 self.last_real_metrics_at = Some(web_time::Instant::now());
             }
             UIUpdate::NotesCutToClipboard(notes) => {
                 self.note_clipboard = Some(notes);
             }
             UIUpdate::ExportStateUpdate(state) => {
+                if let Some(dialog) = &mut self.dialogs.export_stems {
+                    dialog.set_state(state.clone());
+                }
                 if let Some(dialog) = &mut self.dialogs.export_dialog {
                     dialog.set_state(state);
                 }
@@ -1290,11 +2396,16 @@ impl YadawApp {
                     if let Some(track) = state.tracks.get_mut(&track_id) {
                         if let Some(plugin) = track.plugin_chain.get_mut(plugin_idx) {
                             plugin.params.clear();
+                            plugin.param_ranges.clear();
                             plugin.has_editor = has_editor;
                             for param_info in &params {
                                 plugin
                                     .params
                                     .insert(param_info.name.clone(), param_info.current);
+                                plugin.param_ranges.insert(
+                                    param_info.name.clone(),
+                                    (param_info.min, param_info.max),
+                                );
                             }
                         }
                     }
@@ -1395,29 +2506,20 @@ impl YadawApp {
                 }
             }
 
-            GoToStart => {
-                if let Some(transport) = &self.transport_ui.transport {
-                    transport.set_position(0.0);
-                }
-            }
-
-            Rewind => {
-                if let Some(transport) = &self.transport_ui.transport {
-                    transport.rewind_beats(4.0);
-                }
-            }
-
-            FastForward => {
-                if let Some(transport) = &self.transport_ui.transport {
-                    transport.fast_forward(4.0);
-                }
-            }
+            GoToStart => self.seek_to_beat(0.0),
+            Rewind => self.seek_to_beat(self.playhead_beat() - 4.0),
+            FastForward => self.seek_to_beat(self.playhead_beat() + 4.0),
+            PlayheadLeft => self.nudge_playhead(-1.0),
+            PlayheadRight => self.nudge_playhead(1.0),
+            TapTempo => self.tap_tempo(),
 
             Undo => self.undo(),
             Redo => self.redo(),
 
             Copy => {
-                if self.is_selected_track_midi() {
+                if self.input_manager.context() == ActionContext::Automation {
+                    self.copy_automation_selection();
+                } else if self.is_selected_track_midi() {
                     // Piano roll copy
                     let clipboard = self
                         .piano_roll_view
@@ -1432,7 +2534,10 @@ impl YadawApp {
             }
 
             Cut => {
-                if self.is_selected_track_midi() {
+                if self.input_manager.context() == ActionContext::Automation {
+                    self.copy_automation_selection();
+                    self.delete_selected_automation_points();
+                } else if self.is_selected_track_midi() {
                     self.push_undo();
                     self.piano_roll_view.cut_selected_notes(&self.command_tx);
                 } else {
@@ -1440,7 +2545,11 @@ impl YadawApp {
                 }
             }
             Paste => {
-                if self.is_selected_track_midi() {
+                if self.input_manager.context() == ActionContext::Automation
+                    && self.automation_clipboard.is_some()
+                {
+                    self.paste_automation_at_playhead();
+                } else if self.is_selected_track_midi() {
                     if let Some(ref clipboard) = self.note_clipboard.clone() {
                         self.push_undo();
                         self.piano_roll_view.paste_notes(
@@ -1453,14 +2562,19 @@ impl YadawApp {
                     self.paste_at_playhead();
                 }
             }
-            Delete => {
-                if self.is_selected_track_midi() {
+            Delete => match self.input_manager.context() {
+                ActionContext::Automation => self.delete_selected_automation_points(),
+                ActionContext::PianoRoll if self.piano_roll_view.piano_roll.step_input => {
+                    self.push_undo();
+                    self.piano_roll_view
+                        .step_back(&self.state, &self.command_tx);
+                }
+                ActionContext::PianoRoll => {
                     self.push_undo();
                     self.piano_roll_view.delete_selected_notes(&self.command_tx);
-                } else {
-                    self.delete_selected();
                 }
-            }
+                _ => self.delete_selected(),
+            },
 
             SelectAll => {
                 if self.is_selected_track_midi() {
@@ -1484,7 +2598,9 @@ impl YadawApp {
             }
 
             Duplicate => {
-                if self.is_selected_track_midi() {
+                if self.input_manager.context() == ActionContext::Automation {
+                    self.duplicate_automation_selection();
+                } else if self.is_selected_track_midi() {
                     self.push_undo();
                     self.piano_roll_view.duplicate_selected_notes_smart(
                         &self.state,
@@ -1528,7 +2644,8 @@ impl YadawApp {
                     self.piano_roll_view.piano_roll.zoom_x =
                         (self.piano_roll_view.piano_roll.zoom_x * 1.25).min(500.0);
                 } else {
-                    self.timeline_ui.zoom_x = (self.timeline_ui.zoom_x * 1.25).min(500.0);
+                    let playhead = self.playhead_beat();
+                    self.timeline_ui.zoom_around_playhead(1.25, playhead);
                 }
             }
 
@@ -1537,7 +2654,8 @@ impl YadawApp {
                     self.piano_roll_view.piano_roll.zoom_x =
                         (self.piano_roll_view.piano_roll.zoom_x * 0.8).max(10.0);
                 } else {
-                    self.timeline_ui.zoom_x = (self.timeline_ui.zoom_x * 0.8).max(10.0);
+                    let playhead = self.playhead_beat();
+                    self.timeline_ui.zoom_around_playhead(0.8, playhead);
                 }
             }
 
@@ -1592,6 +2710,11 @@ impl YadawApp {
             TransposeDialog => self.dialogs.show_transpose_dialog(),
             HumanizeDialog => self.dialogs.show_humanize_dialog(),
 
+            ToggleVirtualKeyboard => {
+                let enabled = !self.input_manager.virtual_keyboard.enabled;
+                self.set_virtual_keyboard(enabled);
+            }
+
             Escape => {
                 // Close dialogs or deselect
                 self.deselect_all();
@@ -1600,9 +2723,37 @@ impl YadawApp {
         }
     }
 
-    fn adjust_velocity(&mut self, delta: i8) {
-        self.push_undo();
+    pub fn set_virtual_keyboard(&mut self, enabled: bool) {
+        if self
+            .input_manager
+            .virtual_keyboard
+            .set_enabled(enabled)
+            .is_some()
+        {
+            let _ = self.command_tx.send(AudioCommand::StopPreviewNote);
+        }
+    }
+
+    /// Computer-keyboard notes play the selected instrument track through the preview voice
+    fn play_virtual_keyboard(&mut self, ctx: &egui::Context) {
+        let events = self.input_manager.virtual_keyboard.poll(ctx);
+        if events.is_empty() {
+            return;
+        }
+        let playable = self.is_selected_track_midi();
+        for event in events {
+            let command = match event {
+                VirtualKeyEvent::NoteOn(pitch) if playable => {
+                    AudioCommand::PreviewNote(self.selected_track, pitch)
+                }
+                VirtualKeyEvent::NoteOn(_) => continue,
+                VirtualKeyEvent::NoteOff => AudioCommand::StopPreviewNote,
+            };
+            let _ = self.command_tx.send(command);
+        }
+    }
 
+    fn adjust_velocity(&mut self, delta: i8) {
         let clip_id = match self.piano_roll_view.selected_clip {
             Some(id) => id,
             None => return,
@@ -1612,6 +2763,7 @@ impl YadawApp {
         if selected_ids.is_empty() {
             return;
         }
+        self.push_undo();
 
         let pattern_notes: Vec<MidiNote> = {
             let state = self.state.lock_sync();
@@ -1803,6 +2955,32 @@ impl YadawApp {
         self.clap_param_meta.retain(|(tid, _), _| *tid != track_id);
     }
 
+    /// Moves discovered param metadata along with a plugin moved within its chain
+    pub fn reorder_clap_params_for_track(
+        &mut self,
+        track_id: u64,
+        chain_len: usize,
+        from: usize,
+        to: usize,
+    ) {
+        if from >= chain_len || to >= chain_len {
+            return;
+        }
+        let mut order: Vec<usize> = (0..chain_len).collect();
+        let moved = order.remove(from);
+        order.insert(to, moved);
+
+        let metas: Vec<_> = order
+            .iter()
+            .map(|old_idx| self.clap_param_meta.remove(&(track_id, *old_idx)))
+            .collect();
+        for (new_idx, meta) in metas.into_iter().enumerate() {
+            if let Some(meta) = meta {
+                self.clap_param_meta.insert((track_id, new_idx), meta);
+            }
+        }
+    }
+
     /// Opens a file based on its extension, called when app is launched with a file argument
     pub fn open_file_from_path(&mut self, path: &Path) {
         let extension = path
@@ -1911,6 +3089,7 @@ impl YadawApp {
                 let pattern_id = state.fresh_id();
                 let pattern = MidiPattern {
                     id: pattern_id,
+                    name: String::new(),
                     notes: notes.clone(),
                 };
                 state.patterns.insert(pattern_id, pattern);
@@ -1925,6 +3104,7 @@ impl YadawApp {
                     velocity_offset: 0,
                     transpose: 0,
                     loop_enabled: false,
+                    loop_mode: Default::default(),
                     content_len_beats: length_beats,
                     pattern_id: Some(pattern_id),
                     quantize_grid: 0.25,
@@ -1976,6 +3156,11 @@ impl YadawApp {
         let bpm = self.audio_state.bpm.load();
         match crate::audio_import::import_audio_file(path, bpm) {
             Ok(mut clip) => {
+                crate::audio_import::apply_default_fades(
+                    &mut clip,
+                    self.config.behavior.default_clip_fade_ms,
+                    bpm,
+                );
                 self.push_undo();
                 let mut state = self.state.lock_sync();
                 clip.id = state.fresh_id();
@@ -2017,6 +3202,11 @@ impl YadawApp {
         let decode = || crate::audio_import::import_audio_data(name, data, extension, bpm);
         match decode() {
             Ok(mut clip) => {
+                crate::audio_import::apply_default_fades(
+                    &mut clip,
+                    self.config.behavior.default_clip_fade_ms,
+                    bpm,
+                );
                 self.push_undo();
                 let mut state = self.state.lock_sync();
                 clip.id = state.fresh_id();
@@ -2087,6 +3277,7 @@ impl YadawApp {
                             if let Some(clip) = track.audio_clips.get_mut(idx) {
                                 if clip.source_hash == Some(hash) {
                                     clip.samples = cached;
+                                    clip.peaks.invalidate();
                                 }
                             }
                         }
@@ -2148,17 +3339,25 @@ impl eframe::App for YadawApp {
         while let Ok(update) = self.ui_rx.try_recv() {
             self.process_ui_update(update);
         }
+        self.check_late_plugin_states();
 
-        if self.is_selected_track_midi() {
-            self.input_manager.set_context(ActionContext::PianoRoll);
-        } else {
-            self.input_manager.set_context(ActionContext::Timeline);
-        }
+        // Edit shortcuts only ever read this context, so they agree on the target
+        let context = match self.active_edit_target {
+            ActiveEditTarget::Automation => ActionContext::Automation,
+            ActiveEditTarget::Clips => ActionContext::Timeline,
+            _ if self.is_selected_track_midi() => ActionContext::PianoRoll,
+            _ => ActionContext::Timeline,
+        };
+        self.input_manager.set_context(context);
 
-        let actions = self.input_manager.poll_actions(ctx);
+        // Key presses belong to the shortcut editor while it captures a binding
+        if !self.dialogs.is_capturing_shortcut() {
+            let actions = self.input_manager.poll_actions(ctx);
 
-        for action in actions {
-            self.handle_action(action);
+            for action in actions {
+                self.handle_action(action);
+            }
+            self.play_virtual_keyboard(ctx);
         }
 
         {
@@ -2288,6 +3487,7 @@ impl eframe::App for YadawApp {
         if self.project_manager.get_current_project().is_some()
             && self.last_autosave.elapsed() > self.autosave_interval
         {
+            self.capture_plugin_states();
             let state_guard = self.state.lock_sync();
             if let Err(e) = self.project_manager.auto_save(&state_guard) {
                 log::error!("Auto-save failed: {}", e);
@@ -2297,6 +3497,28 @@ impl eframe::App for YadawApp {
     }
 }
 
+/// Scales each audio clip in `clip_ids` to the normalize target peak
+fn normalize_clips(state: &mut AppState, clip_ids: &[u64]) {
+    for &clip_id in clip_ids {
+        if let Some((track, ClipLocation::Audio(idx))) = state.find_clip_mut(clip_id)
+            && let Some(clip) = track.audio_clips.get_mut(idx)
+        {
+            let peak = clip
+                .channels()
+                .flatten()
+                .map(|s| s.abs())
+                .fold(0.0f32, f32::max);
+            if peak > 0.0 {
+                let gain = crate::constants::NORMALIZE_TARGET_LINEAR / peak;
+                for s in clip.channels_mut().flatten() {
+                    *s *= gain;
+                }
+                clip.peaks.invalidate();
+            }
+        }
+    }
+}
+
 impl Drop for YadawApp {
     fn drop(&mut self) {
         let _ = self.input_manager.save_shortcuts(&shortcuts_path());
@@ -2304,3 +3526,101 @@ impl Drop for YadawApp {
         let _ = self.theme_manager.save_current_theme(&current_theme_path());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_batch_records_one_undo_entry() {
+        let mut batches = UndoBatches::default();
+        let id = batches.begin();
+        assert!(batches.claim_local());
+        assert!(!batches.claim_local());
+        assert!(batches.end());
+
+        // The batch's commands push their own entries once it has closed here
+        batches.remote = id;
+        assert!(!batches.claim_remote());
+        assert!(!batches.claim_remote());
+        batches.remote = None;
+        assert!(batches.claim_remote());
+    }
+
+    #[test]
+    fn first_remote_entry_records_a_batch_without_a_local_one() {
+        let mut batches = UndoBatches::default();
+        let id = batches.begin();
+        assert!(batches.end());
+        batches.remote = id;
+        assert!(batches.claim_remote());
+        assert!(!batches.claim_remote());
+    }
+
+    #[test]
+    fn nested_batches_share_the_outer_entry() {
+        let mut batches = UndoBatches::default();
+        let outer = batches.begin();
+        assert!(outer.is_some());
+        assert_eq!(batches.begin(), None);
+        assert!(batches.claim_local());
+        assert!(!batches.end());
+        assert!(!batches.claim_local());
+        assert!(batches.end());
+
+        // The next batch gets an entry of its own
+        let next = batches.begin();
+        assert_ne!(next, outer);
+        assert!(batches.claim_local());
+        batches.end();
+        assert!(batches.claim_local());
+    }
+
+    #[test]
+    fn undoing_a_batched_normalize_restores_every_clip() {
+        let mut state = AppState::default();
+        let mut track = Track {
+            id: 1,
+            ..Default::default()
+        };
+        track.audio_clips = (1..=3)
+            .map(|n| AudioClip {
+                start_beat: n as f64 * 4.0,
+                length_beats: 4.0,
+                samples: vec![0.1 * n as f32, -0.05],
+                sample_rate: 44100.0,
+                ..Default::default()
+            })
+            .collect();
+        state.track_order.push(1);
+        state.tracks.insert(1, track);
+        state.ensure_ids();
+        let clip_ids: Vec<u64> = state.tracks[&1].audio_clips.iter().map(|c| c.id).collect();
+        let samples = |state: &AppState| -> Vec<Vec<f32>> {
+            state.tracks[&1]
+                .audio_clips
+                .iter()
+                .map(|c| c.samples.clone())
+                .collect()
+        };
+        let before = samples(&state);
+
+        // One clip at a time inside a batch, each step asking for an entry
+        let mut batches = UndoBatches::default();
+        let mut undo_stack = Vec::new();
+        batches.begin();
+        for &cid in &clip_ids {
+            if batches.claim_local() {
+                undo_stack.push(state.snapshot());
+            }
+            normalize_clips(&mut state, &[cid]);
+        }
+        batches.end();
+        assert_eq!(undo_stack.len(), 1);
+        let normalized = samples(&state);
+        assert!(normalized.iter().zip(&before).all(|(n, b)| n != b));
+
+        state.restore(undo_stack.pop().unwrap());
+        assert_eq!(samples(&state), before);
+    }
+}
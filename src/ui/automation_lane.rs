@@ -1,6 +1,11 @@
 use eframe::egui;
 
-use crate::model::automation::AutomationLane;
+use crate::model::automation::{AutomationLane, AutomationPoint, CurveType};
+
+/// Where along a curved segment its tension handle sits
+const TENSION_HANDLE_T: f32 = 0.25;
+/// How close in pixels a value must come to a snap value to stick
+const VALUE_SNAP_PX: f32 = 6.0;
 
 #[derive(Debug, Clone)]
 pub enum AutomationAction {
@@ -9,15 +14,36 @@ pub enum AutomationAction {
         value: f32,
     },
     RemovePoint(f64),
+    /// Alt-drag across the lane, from press to pointer
+    SelectRange {
+        from: f64,
+        to: f64,
+    },
+    /// Click on a point; `additive` toggles it in the current selection
+    SelectPoint {
+        beat: f64,
+        additive: bool,
+    },
     MovePoint {
         old_beat: f64,
         new_beat: f64,
         new_value: f32,
     },
+    /// Reshape the segment leaving the point at `beat`; `started` marks the
+    /// first action of a gesture so undo is pushed once
+    SetCurve {
+        beat: f64,
+        curve: CurveType,
+        tension: f32,
+        started: bool,
+    },
 }
 
 #[derive(Default, Clone)]
-pub struct AutomationLaneWidget;
+pub struct AutomationLaneWidget {
+    /// Point menu opened by a long press: lane id, point beat, screen position
+    touch_menu: Option<(egui::Id, f64, egui::Pos2)>,
+}
 
 impl AutomationLaneWidget {
     pub fn ui(
@@ -28,6 +54,9 @@ impl AutomationLaneWidget {
         zoom_x: f32,
         scroll_x: f32,
         id_ns: egui::Id,
+        selected_beats: &[f64],
+        snap_values: bool,
+        long_press: Option<egui::Pos2>,
     ) -> Vec<AutomationAction> {
         let mut actions = Vec::new();
         let painter = ui.painter_at(lane_rect);
@@ -63,21 +92,113 @@ impl AutomationLaneWidget {
             pts_screen.push((i, egui::pos2(x, y)));
         }
 
-        // Curve (polyline)
-        if pts_screen.len() >= 2 {
-            let pts_for_line: Vec<_> = pts_screen.iter().map(|(_, p)| *p).collect();
-            ui.painter().add(egui::Shape::line(
-                pts_for_line,
-                egui::Stroke::new(1.5, lane_color),
-            ));
+        let to_x = |beat: f64| lane_rect.left() + (beat as f32 * zoom_x - scroll_x);
+        let to_y = |value: f32| lane_rect.bottom() - value.clamp(0.0, 1.0) * lane_rect.height();
+        let pointer_value = |pointer: egui::Pos2| -> (f32, bool) {
+            let value = ((lane_rect.bottom() - pointer.y) / lane_rect.height()).clamp(0.0, 1.0);
+            let snapped = snap_values
+                .then(|| {
+                    lane.parameter
+                        .snap_values()
+                        .iter()
+                        .copied()
+                        .find(|s| ((s - value) * lane_rect.height()).abs() <= VALUE_SNAP_PX)
+                })
+                .flatten();
+            (snapped.unwrap_or(value), snapped.is_some())
+        };
+        let value_guide = |value: f32| {
+            let y = to_y(value);
+            painter.line_segment(
+                [
+                    egui::pos2(lane_rect.left(), y),
+                    egui::pos2(lane_rect.right(), y),
+                ],
+                egui::Stroke::new(1.0, egui::Color32::from_rgb(100, 150, 255)),
+            );
+        };
+
+        // Curve, sampled per segment so shaped segments bend
+        let stroke = egui::Stroke::new(1.5, lane_color);
+        for pair in lane.points.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            let (xa, xb) = (to_x(a.beat), to_x(b.beat));
+            if xb < lane_rect.left() || xa > lane_rect.right() {
+                continue;
+            }
+            let line = match a.curve {
+                CurveType::Linear => {
+                    vec![egui::pos2(xa, to_y(a.value)), egui::pos2(xb, to_y(b.value))]
+                }
+                CurveType::Hold => vec![
+                    egui::pos2(xa, to_y(a.value)),
+                    egui::pos2(xb, to_y(a.value)),
+                    egui::pos2(xb, to_y(b.value)),
+                ],
+                _ => {
+                    let steps = ((xb - xa) / 3.0).ceil().clamp(1.0, 128.0) as usize;
+                    (0..=steps)
+                        .map(|s| {
+                            let t = s as f32 / steps as f32;
+                            let v = a.value + (b.value - a.value) * a.curve.shape(t, a.tension);
+                            egui::pos2(egui::lerp(xa..=xb, t), to_y(v))
+                        })
+                        .collect()
+                }
+            };
+            painter.add(egui::Shape::line(line, stroke));
+        }
+
+        // Tension handles on curved segments
+        let mut hovered_any = false;
+        for (i, pair) in lane.points.windows(2).enumerate() {
+            let (a, b) = (&pair[0], &pair[1]);
+            if !a.curve.has_tension() {
+                continue;
+            }
+            let (xa, xb) = (to_x(a.beat), to_x(b.beat));
+            if xb - xa < 16.0 || xb < lane_rect.left() || xa > lane_rect.right() {
+                continue;
+            }
+            let s = a.curve.shape(TENSION_HANDLE_T, a.tension);
+            let pos = egui::pos2(
+                egui::lerp(xa..=xb, TENSION_HANDLE_T),
+                to_y(a.value + (b.value - a.value) * s),
+            );
+            let resp = ui
+                .interact(
+                    egui::Rect::from_center_size(pos, egui::vec2(10.0, 10.0)),
+                    id_ns.with("auto_tension").with(i as u64),
+                    egui::Sense::drag(),
+                )
+                .on_hover_cursor(egui::CursorIcon::ResizeVertical);
+            hovered_any |= resp.hovered() || resp.dragged();
+
+            let active = resp.hovered() || resp.dragged();
+            painter.circle_filled(pos, 3.5, if active { egui::Color32::WHITE } else { bg });
+            painter.circle_stroke(pos, 3.5, egui::Stroke::new(1.0, lane_color));
+
+            if resp.dragged()
+                && (b.value - a.value).abs() > 1e-3
+                && let Some(pointer) = resp.interact_pointer_pos()
+            {
+                let value = ((lane_rect.bottom() - pointer.y) / lane_rect.height()).clamp(0.0, 1.0);
+                let target = (value - a.value) / (b.value - a.value);
+                if let Some(tension) = a.curve.tension_through(TENSION_HANDLE_T, target) {
+                    actions.push(AutomationAction::SetCurve {
+                        beat: a.beat,
+                        curve: a.curve,
+                        tension,
+                        started: resp.drag_started(),
+                    });
+                }
+            }
         }
 
         // Point handles
         let handle_r = 5.0;
         let id_base = id_ns.with("auto_pts");
 
-        let mut hovered_any = false;
-
         for (i, pos) in pts_screen.iter().cloned() {
             let handle_rect = egui::Rect::from_center_size(pos, egui::vec2(12.0, 12.0));
             let id = id_base.with(i as u64);
@@ -95,15 +216,34 @@ impl AutomationLaneWidget {
             } else {
                 lane_color
             };
+            let selected = selected_beats
+                .iter()
+                .any(|b| (b - lane.points[i].beat).abs() < 0.001);
+            let outline = if selected {
+                egui::Stroke::new(2.0, egui::Color32::WHITE)
+            } else {
+                egui::Stroke::new(1.0, egui::Color32::BLACK)
+            };
             painter.circle_filled(pos, handle_r, fill);
-            painter.circle_stroke(pos, handle_r, egui::Stroke::new(1.0, egui::Color32::BLACK));
+            painter.circle_stroke(pos, handle_r, outline);
+
+            if resp.clicked() || resp.drag_started() {
+                let additive = ui.input(|i| i.modifiers.shift || i.modifiers.command);
+                actions.push(AutomationAction::SelectPoint {
+                    beat: lane.points[i].beat,
+                    additive: additive && !resp.drag_started(),
+                });
+            }
 
             // Drag to move
             if resp.dragged()
                 && let Some(pointer) = resp.interact_pointer_pos()
             {
                 let beat = ((pointer.x - lane_rect.left()) + scroll_x) / zoom_x;
-                let value = ((lane_rect.bottom() - pointer.y) / lane_rect.height()).clamp(0.0, 1.0);
+                let (value, snapped) = pointer_value(pointer);
+                if snapped {
+                    value_guide(value);
+                }
 
                 let old_beat = lane.points[i].beat;
                 actions.push(AutomationAction::MovePoint {
@@ -113,21 +253,53 @@ impl AutomationLaneWidget {
                 });
             }
 
-            // Right-click to remove
-            if resp.secondary_clicked() {
-                actions.push(AutomationAction::RemovePoint(lane.points[i].beat));
+            let point = &lane.points[i];
+            resp.context_menu(|ui| {
+                if point_menu(ui, point, &mut actions) {
+                    ui.close();
+                }
+            });
+
+            // Touch has no right button; a long press opens the same menu
+            if let Some(press) = long_press
+                && handle_rect.expand(6.0).contains(press)
+            {
+                self.touch_menu = Some((id_ns, point.beat, press));
             }
         }
 
+        if let Some((menu_id, beat, pos)) = self.touch_menu
+            && menu_id == id_ns
+        {
+            self.show_touch_menu(ui, lane, menu_id, beat, pos, &mut actions);
+        }
+
         // Click empty space to add
         let lane_bg_id = id_ns.with("auto_lane_bg");
-        let lane_resp = ui.interact(lane_rect, lane_bg_id, egui::Sense::click());
+        let lane_resp = ui.interact(lane_rect, lane_bg_id, egui::Sense::click_and_drag());
+        if lane_resp.dragged()
+            && ui.input(|i| i.modifiers.alt)
+            && let Some(origin) = ui.input(|i| i.pointer.press_origin())
+            && let Some(pos) = lane_resp.interact_pointer_pos()
+        {
+            let to_beat = |x: f32| (((x - lane_rect.left()) + scroll_x) / zoom_x) as f64;
+            actions.push(AutomationAction::SelectRange {
+                from: to_beat(origin.x),
+                to: to_beat(pos.x),
+            });
+        }
+        if !hovered_any
+            && let Some(pos) = lane_resp.hover_pos()
+            && let (value, true) = pointer_value(pos)
+        {
+            value_guide(value);
+        }
         if lane_resp.clicked()
             && !hovered_any
             && let Some(pos) = lane_resp.interact_pointer_pos()
         {
             let beat = ((pos.x - lane_rect.left()) + scroll_x) / zoom_x;
-            let value = ((lane_rect.bottom() - pos.y) / lane_rect.height()).clamp(0.0, 1.0);
+            let (value, _) = pointer_value(pos);
             actions.push(AutomationAction::AddPoint {
                 beat: beat as f64,
                 value,
@@ -136,4 +308,66 @@ impl AutomationLaneWidget {
 
         actions
     }
+
+    fn show_touch_menu(
+        &mut self,
+        ui: &egui::Ui,
+        lane: &AutomationLane,
+        menu_id: egui::Id,
+        beat: f64,
+        pos: egui::Pos2,
+        actions: &mut Vec<AutomationAction>,
+    ) {
+        let Some(point) = lane.points.iter().find(|p| (p.beat - beat).abs() < 1e-9) else {
+            self.touch_menu = None;
+            return;
+        };
+
+        let mut picked = false;
+        let area = egui::Area::new(menu_id.with("touch_menu"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(pos)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::menu(ui.style()).show(ui, |ui| {
+                    picked = point_menu(ui, point, actions);
+                });
+            });
+
+        let outside_pressed = ui.input(|i| {
+            i.pointer.any_pressed()
+                && i.pointer
+                    .interact_pos()
+                    .is_none_or(|p| !area.response.rect.contains(p))
+        });
+        if picked || outside_pressed {
+            self.touch_menu = None;
+        }
+    }
+}
+
+/// Point menu shared by right-click and long press; true once an item is picked
+fn point_menu(
+    ui: &mut egui::Ui,
+    point: &AutomationPoint,
+    actions: &mut Vec<AutomationAction>,
+) -> bool {
+    let mut picked = false;
+    ui.label("Curve to next point");
+    for curve in CurveType::ALL {
+        if ui.radio(point.curve == curve, curve.name()).clicked() {
+            actions.push(AutomationAction::SetCurve {
+                beat: point.beat,
+                curve,
+                tension: point.tension,
+                started: true,
+            });
+            picked = true;
+        }
+    }
+    ui.separator();
+    if ui.button("Delete Point").clicked() {
+        actions.push(AutomationAction::RemovePoint(point.beat));
+        picked = true;
+    }
+    picked
 }
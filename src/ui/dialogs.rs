@@ -12,12 +12,15 @@ use crate::constants::{PROJECT_ALT_EXTENSION, PROJECT_EXTENSION};
 use crate::error::UserNotification;
 use crate::input::InputManager;
 use crate::input::actions::{ActionContext, AppAction};
+use crate::input::profiles;
 use crate::input::shortcuts::{KeyCode, Keybind};
 use crate::messages::AudioCommand;
 use crate::messages::ExportFormat;
 use crate::messages::ExportState;
+use crate::messages::NormalizeTarget;
 use crate::model::track::TrackType;
 use crate::plugin::categorize_plugin;
+use crate::time_utils::TimeSignature;
 use crate::ui::theme;
 use yadaw_plugin_api::{BackendKind, HostConfig};
 use yadaw_plugin_host::HostFacade;
@@ -111,6 +114,7 @@ fn save_project_to_uri(app: &mut super::app::YadawApp, file: &PlatformFile) {
         .loop_enabled
         .load(std::sync::atomic::Ordering::Relaxed);
 
+    app.capture_plugin_states();
     let save_result = {
         let mut state = app.state.lock_sync();
         state.bpm = live_bpm;
@@ -142,37 +146,6 @@ fn save_project_to_uri(_app: &mut super::app::YadawApp, _file: &PlatformFile) {
     unreachable!("save_project_to_uri should not be called on desktop");
 }
 
-macro_rules! simple_dialog {
-    ($name:ident, $title:expr, $content:expr) => {
-        pub struct $name {
-            closed: bool,
-        }
-
-        impl $name {
-            pub fn new() -> Self {
-                Self { closed: false }
-            }
-
-            pub fn show(&mut self, ctx: &egui::Context, app: &mut super::app::YadawApp) {
-                let mut open = true;
-                egui::Window::new($title)
-                    .open(&mut open)
-                    .resizable(false)
-                    .show(ctx, |ui| {
-                        $content(ui, app, &mut self.closed);
-                    });
-                if !open {
-                    self.closed = true;
-                }
-            }
-
-            pub fn is_closed(&self) -> bool {
-                self.closed
-            }
-        }
-    };
-}
-
 impl UserNotification for DialogManager {
     fn show_error(&mut self, message: &str) {
         self.message_box = Some(DialogWrapper::new(MessageContent::new(message.to_string())));
@@ -355,6 +328,7 @@ pub struct DialogManager {
 
     pub open_dialog: Option<OpenDialog>,
     pub save_dialog: Option<SaveDialog>,
+    pub export_midi: Option<ExportMidiDialog>,
 
     pub audio_setup: Option<AudioSetupDialog>,
     pub plugin_browser: Option<PluginBrowserDialog>,
@@ -364,7 +338,12 @@ pub struct DialogManager {
     pub humanize_dialog: Option<HumanizeDialog>,
 
     pub project_settings: Option<ProjectSettingsDialog>,
+    pub project_metadata: Option<ProjectMetadataDialog>,
+    pub project_versions: Option<ProjectVersionsDialog>,
+    pub missing_media: Option<MissingMediaDialog>,
+    pub pattern_manager: Option<PatternManagerDialog>,
     pub export_dialog: Option<ExportDialog>,
+    pub export_stems: Option<StemExportDialog>,
 
     pub theme_editor: Option<ThemeEditorDialog>,
     pub layout_manager: Option<LayoutManagerDialog>,
@@ -382,6 +361,7 @@ impl DialogManager {
         Self {
             open_dialog: None,
             save_dialog: None,
+            export_midi: None,
             audio_setup: None,
             plugin_browser: None,
             plugin_manager: None,
@@ -389,7 +369,12 @@ impl DialogManager {
             transpose_dialog: None,
             humanize_dialog: None,
             project_settings: None,
+            project_metadata: None,
+            project_versions: None,
+            missing_media: None,
+            pattern_manager: None,
             export_dialog: None,
+            export_stems: None,
             theme_editor: None,
             layout_manager: None,
             message_box: None,
@@ -421,6 +406,12 @@ impl DialogManager {
                 self.save_dialog = Some(d);
             }
         }
+        if let Some(mut d) = self.export_midi.take() {
+            d.show(app);
+            if !d.is_closed() {
+                self.export_midi = Some(d);
+            }
+        }
 
         // Tools / audio dialogs
         if let Some(mut d) = self.audio_setup.take() {
@@ -475,12 +466,42 @@ impl DialogManager {
                 self.project_settings = Some(d);
             }
         }
+        if let Some(mut d) = self.project_metadata.take() {
+            d.show(ctx, app);
+            if !d.is_closed() {
+                self.project_metadata = Some(d);
+            }
+        }
+        if let Some(mut d) = self.project_versions.take() {
+            d.show(ctx, app);
+            if !d.is_closed() {
+                self.project_versions = Some(d);
+            }
+        }
+        if let Some(mut d) = self.missing_media.take() {
+            d.show(ctx, app);
+            if !d.is_closed() {
+                self.missing_media = Some(d);
+            }
+        }
+        if let Some(mut d) = self.pattern_manager.take() {
+            d.show(ctx, app);
+            if !d.is_closed() {
+                self.pattern_manager = Some(d);
+            }
+        }
         if let Some(mut d) = self.export_dialog.take() {
             d.show(ctx, app);
             if !d.is_closed() {
                 self.export_dialog = Some(d);
             }
         }
+        if let Some(mut d) = self.export_stems.take() {
+            d.show(ctx, app);
+            if !d.is_closed() {
+                self.export_stems = Some(d);
+            }
+        }
 
         // UI dialogs
         if let Some(mut d) = self.theme_editor.take() {
@@ -535,6 +556,18 @@ impl DialogManager {
     pub fn show_project_settings(&mut self) {
         self.project_settings = Some(ProjectSettingsDialog::new());
     }
+    pub fn show_project_metadata(&mut self) {
+        self.project_metadata = Some(ProjectMetadataDialog::new());
+    }
+    pub fn show_project_versions(&mut self, project_path: Option<&Path>) {
+        self.project_versions = Some(ProjectVersionsDialog::new(project_path));
+    }
+    pub fn show_missing_media(&mut self) {
+        self.missing_media = Some(MissingMediaDialog::new());
+    }
+    pub fn show_pattern_manager(&mut self) {
+        self.pattern_manager = Some(PatternManagerDialog::new());
+    }
     pub fn show_track_grouping(&mut self) {
         self.track_grouping = Some(TrackGroupingDialog::new());
     }
@@ -570,12 +603,16 @@ impl DialogManager {
         self.save_dialog = Some(SaveDialog::new());
     }
 
+    pub fn show_export_midi(&mut self) {
+        self.export_midi = Some(ExportMidiDialog::new());
+    }
+
     pub fn show_plugin_browser(&mut self) {
         self.plugin_browser = Some(PluginBrowserDialog::new());
     }
 
-    pub fn show_audio_setup(&mut self) {
-        self.audio_setup = Some(AudioSetupDialog::new());
+    pub fn show_audio_setup(&mut self, config: &crate::config::AudioConfig) {
+        self.audio_setup = Some(AudioSetupDialog::new(config));
     }
 
     pub fn show_theme_editor(&mut self) {
@@ -586,6 +623,12 @@ impl DialogManager {
         self.track_rename = Some(TrackRenameDialog::new(track_id, current));
     }
 
+    pub fn is_capturing_shortcut(&self) -> bool {
+        self.shortcuts_editor
+            .as_ref()
+            .is_some_and(|e| e.capturing.is_some())
+    }
+
     pub fn show_shortcuts_editor(&mut self) {
         let mut editor = ShortcutsEditorDialog::new();
         editor.open = true;
@@ -594,6 +637,10 @@ impl DialogManager {
     pub fn show_export_dialog(&mut self) {
         self.export_dialog = Some(ExportDialog::new());
     }
+
+    pub fn show_export_stems(&mut self) {
+        self.export_stems = Some(StemExportDialog::new());
+    }
 }
 
 // Individual dialog implementations
@@ -763,6 +810,88 @@ impl SaveDialog {
     }
 }
 
+pub struct ExportMidiDialog {
+    closed: bool,
+    picker_rx: Option<Picker<PlatformFile>>,
+}
+
+impl ExportMidiDialog {
+    pub fn new() -> Self {
+        Self {
+            closed: false,
+            picker_rx: None,
+        }
+    }
+
+    pub fn show(&mut self, app: &mut super::app::YadawApp) {
+        if self.picker_rx.is_none() {
+            let suggested = app
+                .project_path
+                .as_ref()
+                .and_then(|p| Path::new(p).file_stem().and_then(|s| s.to_str()))
+                .map(|stem| format!("{stem}.mid"))
+                .unwrap_or_else(|| "untitled.mid".to_string());
+
+            self.picker_rx = Some(crate::file_picker::pick_save_file(
+                "Export MIDI",
+                &suggested,
+                "mid",
+            ));
+        }
+
+        let Some(mut picker) = self.picker_rx.take() else {
+            return;
+        };
+        let Some(result) = picker.poll() else {
+            self.picker_rx = Some(picker);
+            return;
+        };
+        self.closed = true;
+
+        let file = match result {
+            Ok(Some(file)) => file,
+            Ok(None) => return,
+            Err(e) => {
+                app.dialogs
+                    .show_error(&format!("Export MIDI picker failed: {e}"));
+                return;
+            }
+        };
+
+        let state = app.state.lock_sync();
+
+        #[cfg(target_arch = "wasm32")]
+        let result = crate::midi_export::download_midi(&state, file.name());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let result = if let Some(uri) = file.uri() {
+            // SAF targets are written through a temp file
+            let temp_path = crate::paths::cache_dir().join("export_midi_temp.mid");
+            crate::midi_export::export_midi_file(&state, &temp_path).and_then(|()| {
+                let written = crate::file_picker::write_file_to_uri(&temp_path, uri);
+                let _ = std::fs::remove_file(&temp_path);
+                written.map_err(|e| anyhow::anyhow!(e))
+            })
+        } else if let Some(path) = file.path() {
+            crate::midi_export::export_midi_file(&state, path)
+        } else {
+            Err(anyhow::anyhow!("No destination selected"))
+        };
+        drop(state);
+
+        match result {
+            Ok(()) => app.dialogs.show_success("MIDI exported"),
+            Err(e) => app
+                .dialogs
+                .show_error(&format!("Failed to export MIDI: {e}")),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
 pub struct PluginBrowserDialog {
     closed: bool,
     search_text: String,
@@ -780,6 +909,7 @@ impl PluginBrowserDialog {
             selected_plugin: None,
             available_categories: vec![
                 "All".to_string(),
+                "Native".to_string(),
                 "Instruments".to_string(),
                 "Effects".to_string(),
                 "Dynamics".to_string(),
@@ -853,12 +983,13 @@ impl PluginBrowserDialog {
                             BackendKind::Clap => "[CLAP]",
                             BackendKind::Vst3 => "[VST3]",
                             BackendKind::Lv2 => "[LV2]",
+                            BackendKind::Native => "[Native]",
                         };
 
                         // Show category hint in "All"
                         let display_name = if self.selected_category == "All" {
                             let cats = categorize_plugin(plugin);
-                            let main_cat = cats.iter().find(|c| *c != "All").map(|c| c.as_str()).unwrap_or("Unknown");
+                            let main_cat = cats.iter().find(|c| *c != "All" && *c != "Native").map(|c| c.as_str()).unwrap_or("Unknown");
                             format!("{} {} [{}]", backend_badge, plugin.name, main_cat)
                         } else {
                             format!("{} {}", backend_badge, plugin.name)
@@ -866,7 +997,9 @@ impl PluginBrowserDialog {
 
                         let resp = ui.selectable_label(selected, display_name);
                         if resp.double_clicked() {
-                            let backend = if plugin.uri.starts_with("file://") {
+                            let backend = if plugin.backend == BackendKind::Native {
+                                BackendKind::Native
+                            } else if plugin.uri.starts_with("file://") {
                                 BackendKind::Clap
                             } else if plugin.uri.ends_with(".vst3") || plugin.uri.contains(".vst3") {
                                 BackendKind::Vst3
@@ -904,7 +1037,11 @@ impl PluginBrowserDialog {
                 if let Some(plugin) = app.available_plugins.get(uri) {
                     ui.heading(&plugin.name);
                     ui.separator();
-                    ui.label(format!("Backend: {}", if plugin.uri.starts_with("file://") { "CLAP" } else { "LV2" }));
+                    ui.label(format!("Backend: {}", match plugin.backend {
+                        BackendKind::Native => "Native",
+                        _ if plugin.uri.starts_with("file://") => "CLAP",
+                        _ => "LV2",
+                    }));
                     ui.label(format!("Type: {}", if plugin.is_instrument { "Instrument" } else { "Effect" }));
                     ui.label(format!("Audio I/O: {} inputs / {} outputs", plugin.audio_inputs, plugin.audio_outputs));
                     ui.label(format!("MIDI: {}", if plugin.has_midi { "Yes" } else { "No" }));
@@ -930,7 +1067,9 @@ impl PluginBrowserDialog {
                                 state.tracks.get(&track_id).map(|t| matches!(t.track_type, TrackType::Midi)).unwrap_or(false)
                             };
 
-                            let backend = if plugin.uri.starts_with("file://") {
+                            let backend = if plugin.backend == BackendKind::Native {
+                                BackendKind::Native
+                            } else if plugin.uri.starts_with("file://") {
                                 BackendKind::Clap
                             } else if plugin.uri.ends_with(".vst3") || plugin.uri.contains(".vst3") {
                                 BackendKind::Vst3
@@ -974,18 +1113,157 @@ impl PluginBrowserDialog {
     }
 }
 
-simple_dialog!(
-    AudioSetupDialog,
-    "Audio Setup",
-    |ui: &mut egui::Ui, _app: &mut super::app::YadawApp, closed: &mut bool| {
-        ui.label("Audio configuration would be shown here");
-        ui.label("(Not implemented yet)");
-        ui.separator();
-        if ui.button("Close").clicked() {
-            *closed = true;
+pub struct AudioSetupDialog {
+    closed: bool,
+    devices: Vec<crate::audio::OutputDeviceInfo>,
+    selection: crate::messages::AudioDeviceSelection,
+}
+
+impl AudioSetupDialog {
+    const BUFFER_SIZES: [usize; 8] = [32, 64, 128, 256, 512, 1024, 2048, 4096];
+
+    pub fn new(config: &crate::config::AudioConfig) -> Self {
+        Self {
+            closed: false,
+            devices: crate::audio::list_output_devices(),
+            selection: config.into(),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, app: &mut super::app::YadawApp) {
+        let mut open = true;
+        egui::Window::new("Audio Setup")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| self.draw(ui, app));
+        if !open {
+            self.closed = true;
+        }
+    }
+
+    fn draw(&mut self, ui: &mut egui::Ui, app: &mut super::app::YadawApp) {
+        let device = self
+            .selection
+            .output_device
+            .as_ref()
+            .and_then(|name| self.devices.iter().find(|d| &d.name == name));
+
+        egui::Grid::new("audio_setup_grid")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("Output Device:");
+                egui::ComboBox::from_id_salt("audio_setup_device")
+                    .width(220.0)
+                    .selected_text(
+                        self.selection
+                            .output_device
+                            .as_deref()
+                            .unwrap_or("System Default"),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut self.selection.output_device,
+                            None,
+                            "System Default",
+                        );
+                        for d in &self.devices {
+                            ui.selectable_value(
+                                &mut self.selection.output_device,
+                                Some(d.name.clone()),
+                                &d.name,
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Sample Rate:");
+                let mut rate = self.selection.sample_rate.round() as u32;
+                let rates = device
+                    .map(|d| d.sample_rates.clone())
+                    .filter(|r| !r.is_empty())
+                    .unwrap_or_else(|| vec![44100, 48000, 88200, 96000]);
+                egui::ComboBox::from_id_salt("audio_setup_rate")
+                    .selected_text(format!("{rate} Hz"))
+                    .show_ui(ui, |ui| {
+                        for r in rates {
+                            ui.selectable_value(&mut rate, r, format!("{r} Hz"));
+                        }
+                    });
+                self.selection.sample_rate = rate as f32;
+                ui.end_row();
+
+                ui.label("Buffer Size:");
+                let (min, max) = device
+                    .and_then(|d| d.buffer_range)
+                    .map(|(lo, hi)| (lo as usize, hi as usize))
+                    .unwrap_or((0, usize::MAX));
+                let buffer_text = |frames: usize| match frames {
+                    0 => "Driver Default".to_string(),
+                    n => format!("{n} samples"),
+                };
+                egui::ComboBox::from_id_salt("audio_setup_buffer")
+                    .selected_text(buffer_text(self.selection.buffer_size))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.selection.buffer_size, 0, buffer_text(0));
+                        for n in Self::BUFFER_SIZES
+                            .into_iter()
+                            .filter(|n| (min..=max).contains(n))
+                        {
+                            ui.selectable_value(&mut self.selection.buffer_size, n, buffer_text(n));
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Latency:");
+                ui.label(match self.selection.buffer_size {
+                    0 => "Set by the driver".to_string(),
+                    n => format!(
+                        "{:.1} ms",
+                        n as f32 / self.selection.sample_rate.max(1.0) * 1000.0
+                    ),
+                });
+                ui.end_row();
+            });
+
+        if let Some((name, rate, frames)) = &app.audio_device {
+            let detail = match *frames {
+                0 => format!("Running: {name} at {rate} Hz"),
+                n => format!(
+                    "Running: {name} at {rate} Hz, {n} samples ({:.1} ms)",
+                    n as f32 / rate.max(1.0) * 1000.0
+                ),
+            };
+            ui.label(egui::RichText::new(detail).weak());
         }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Refresh Devices").clicked() {
+                self.devices = crate::audio::list_output_devices();
+            }
+            if ui.button("Apply").clicked() {
+                let audio = &mut app.config.audio;
+                audio.preferred_output_device = self.selection.output_device.clone();
+                audio.sample_rate = self.selection.sample_rate;
+                audio.buffer_size = self.selection.buffer_size;
+                if let Err(e) = app.config.save() {
+                    log::warn!("Failed to save audio settings: {e}");
+                }
+                let _ = app
+                    .command_tx
+                    .send(AudioCommand::SetAudioDevice(self.selection.clone()));
+            }
+            if ui.button("Close").clicked() {
+                self.closed = true;
+            }
+        });
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
     }
-);
+}
 
 pub struct TransposeContent {
     semitones: i32,
@@ -1035,18 +1313,18 @@ pub type TransposeDialog = DialogWrapper<TransposeContent>;
 
 pub struct HumanizeDialog {
     closed: bool,
-    amount: f32,
-    timing_variation: f32,
-    velocity_variation: f32,
+    /// Largest start offset either way, in milliseconds
+    timing_ms: f32,
+    /// Largest velocity change either way
+    velocity: u8,
 }
 
 impl HumanizeDialog {
     pub fn new() -> Self {
         Self {
             closed: false,
-            amount: 0.1,
-            timing_variation: 0.05,
-            velocity_variation: 0.1,
+            timing_ms: 10.0,
+            velocity: 8,
         }
     }
 
@@ -1058,35 +1336,20 @@ impl HumanizeDialog {
             .resizable(false)
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Overall Amount:");
-                    ui.add(
-                        egui::Slider::new(&mut self.amount, 0.0..=1.0)
-                            .suffix("%")
-                            .custom_formatter(|n, _| format!("{:.0}%", n * 100.0)),
-                    );
-                });
-
-                ui.horizontal(|ui| {
-                    ui.label("Timing Variation:");
-                    ui.add(
-                        egui::Slider::new(&mut self.timing_variation, 0.0..=0.2).suffix(" beats"),
-                    );
+                    ui.label("Timing Variation: ±");
+                    ui.add(egui::Slider::new(&mut self.timing_ms, 0.0..=50.0).suffix(" ms"));
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Velocity Variation:");
-                    ui.add(
-                        egui::Slider::new(&mut self.velocity_variation, 0.0..=0.5)
-                            .suffix("%")
-                            .custom_formatter(|n, _| format!("{:.0}%", n * 100.0)),
-                    );
+                    ui.label("Velocity Variation: ±");
+                    ui.add(egui::Slider::new(&mut self.velocity, 0..=32));
                 });
 
                 ui.separator();
 
                 ui.horizontal(|ui| {
                     if ui.button("Apply").clicked() {
-                        app.humanize_selected_notes(self.amount);
+                        app.humanize_selected_notes(self.timing_ms, self.velocity);
                         self.closed = true;
                     }
 
@@ -1109,7 +1372,7 @@ impl HumanizeDialog {
 pub struct ProjectSettingsDialog {
     closed: bool,
     bpm: f32,
-    time_signature: (u32, u32),
+    time_signature: TimeSignature,
     sample_rate: f32,
     initialized: bool,
 }
@@ -1119,7 +1382,7 @@ impl ProjectSettingsDialog {
         Self {
             closed: false,
             bpm: 120.0,
-            time_signature: (4, 4),
+            time_signature: TimeSignature::default(),
             sample_rate: 44100.0,
             initialized: false,
         }
@@ -1132,6 +1395,7 @@ impl ProjectSettingsDialog {
         // Load current settings
         if !self.initialized {
             self.bpm = app.audio_state.bpm.load();
+            self.time_signature = app.state.lock_sync().time_signature;
             self.sample_rate = app.config.audio.sample_rate;
             self.initialized = true;
         }
@@ -1156,18 +1420,21 @@ impl ProjectSettingsDialog {
                 ui.horizontal(|ui| {
                     ui.label("Time Signature:");
                     ui.add(
-                        egui::DragValue::new(&mut self.time_signature.0)
+                        egui::DragValue::new(&mut self.time_signature.numerator)
                             .speed(1)
                             .range(1..=32),
                     );
                     ui.label("/");
                     egui::ComboBox::from_id_salt("time_sig_denom")
-                        .selected_text(format!("{}", self.time_signature.1))
+                        .selected_text(format!("{}", self.time_signature.denominator))
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut self.time_signature.1, 2, "2");
-                            ui.selectable_value(&mut self.time_signature.1, 4, "4");
-                            ui.selectable_value(&mut self.time_signature.1, 8, "8");
-                            ui.selectable_value(&mut self.time_signature.1, 16, "16");
+                            for den in [2, 4, 8, 16] {
+                                ui.selectable_value(
+                                    &mut self.time_signature.denominator,
+                                    den,
+                                    den.to_string(),
+                                );
+                            }
                         });
                 });
 
@@ -1215,9 +1482,20 @@ impl ProjectSettingsDialog {
                             }
                         }
 
+                        let meter_changed =
+                            app.state.lock_sync().time_signature != self.time_signature;
+                        if meter_changed {
+                            app.push_undo();
+                        }
+
                         {
                             let mut state = app.state.lock_sync();
                             state.sample_rate = selected_rate as f32;
+                            state.time_signature = self.time_signature;
+                        }
+                        if meter_changed {
+                            // The engine's click follows the meter
+                            let _ = app.command_tx.send(AudioCommand::UpdateTracks);
                         }
 
                         app.project_manager.mark_dirty();
@@ -1360,9 +1638,16 @@ impl ThemeEditorDialog {
 pub struct ShortcutsEditorDialog {
     open: bool,
     capturing: Option<AppAction>,
+    /// Binding being changed by the capture; None adds a new one
+    replacing: Option<Keybind>,
     capture_buffer: Option<Keybind>,
     filter_context: Option<ActionContext>,
     search_query: String,
+    /// Profiles saved in the config dir
+    profiles: Vec<String>,
+    profile_name_draft: String,
+    /// Last profile load, save or delete failure, shown under the profile bar
+    profile_error: Option<String>,
 
     import_picker: Option<Picker<PlatformFile>>,
     export_picker: Option<Picker<PlatformFile>>,
@@ -1373,9 +1658,13 @@ impl ShortcutsEditorDialog {
         Self {
             open: false,
             capturing: None,
+            replacing: None,
             capture_buffer: None,
             filter_context: None,
             search_query: String::new(),
+            profiles: profiles::saved_profiles(),
+            profile_name_draft: String::new(),
+            profile_error: None,
             import_picker: None,
             export_picker: None,
         }
@@ -1405,7 +1694,7 @@ impl ShortcutsEditorDialog {
                             if let Err(e) = input_mgr.load_shortcuts(path) {
                                 eprintln!("Shortcuts import failed: {}", e);
                             } else {
-                                let _ = input_mgr.save_shortcuts(&crate::paths::shortcuts_path());
+                                persist_shortcuts(input_mgr);
                             }
                         } else {
                             #[cfg(target_os = "android")]
@@ -1420,7 +1709,7 @@ impl ShortcutsEditorDialog {
                                         if let Err(e) = input_mgr.load_shortcuts(&temp_path) {
                                             eprintln!("Shortcuts import failed: {}", e);
                                         } else {
-                                            let _ = input_mgr.save_shortcuts(&crate::paths::shortcuts_path());
+                                            persist_shortcuts(input_mgr);
                                         }
                                         let _ = std::fs::remove_file(&temp_path);
                                     }
@@ -1477,6 +1766,9 @@ impl ShortcutsEditorDialog {
     }
 
     fn draw_content(&mut self, ui: &mut egui::Ui, input_mgr: &mut InputManager) {
+        self.draw_profile_bar(ui, input_mgr);
+        ui.separator();
+
         // Toolbar
         ui.horizontal(|ui| {
             ui.label("Filter:");
@@ -1487,6 +1779,7 @@ impl ShortcutsEditorDialog {
                     Some(ActionContext::PianoRoll) => "Piano Roll",
                     Some(ActionContext::Timeline) => "Timeline",
                     Some(ActionContext::Mixer) => "Mixer",
+                    Some(ActionContext::Automation) => "Automation",
                     _ => "Other",
                 })
                 .show_ui(ui, |ui| {
@@ -1511,6 +1804,11 @@ impl ShortcutsEditorDialog {
                         Some(ActionContext::Mixer),
                         "Mixer",
                     );
+                    ui.selectable_value(
+                        &mut self.filter_context,
+                        Some(ActionContext::Automation),
+                        "Automation",
+                    );
                 });
 
             ui.separator();
@@ -1570,7 +1868,7 @@ impl ShortcutsEditorDialog {
         ui.horizontal(|ui| {
             if ui.button("Reset to Defaults").clicked() {
                 *input_mgr.shortcuts_mut() = crate::input::shortcuts::ShortcutRegistry::default();
-                let _ = input_mgr.save_shortcuts(&crate::paths::shortcuts_path());
+                persist_shortcuts(input_mgr);
             }
 
             if ui.button("Import...").clicked() {
@@ -1597,6 +1895,83 @@ impl ShortcutsEditorDialog {
         });
     }
 
+    fn draw_profile_bar(&mut self, ui: &mut egui::Ui, input_mgr: &mut InputManager) {
+        ui.horizontal(|ui| {
+            ui.label("Profile:");
+            let current = input_mgr.profile_name().to_string();
+            let mut chosen = None;
+            egui::ComboBox::from_id_salt("shortcut_profile")
+                .selected_text(&current)
+                .show_ui(ui, |ui| {
+                    for name in profiles::BUILTIN_PROFILES {
+                        if ui.selectable_label(current == name, name).clicked() {
+                            chosen = Some(name.to_string());
+                        }
+                    }
+                    if !self.profiles.is_empty() {
+                        ui.separator();
+                    }
+                    for name in &self.profiles {
+                        if ui.selectable_label(current == *name, name).clicked() {
+                            chosen = Some(name.clone());
+                        }
+                    }
+                });
+            if let Some(name) = chosen {
+                self.profile_error = match input_mgr.load_profile(&name) {
+                    Ok(()) => {
+                        persist_shortcuts(input_mgr);
+                        None
+                    }
+                    Err(e) => Some(format!("Failed to load shortcut profile '{name}': {e}")),
+                };
+            }
+
+            let deletable = self.profiles.contains(&current);
+            if ui
+                .add_enabled(deletable, egui::Button::new("🗑"))
+                .on_hover_text("Delete this profile")
+                .clicked()
+            {
+                self.profile_error = profiles::delete_profile(&current)
+                    .err()
+                    .map(|e| e.to_string());
+                self.profiles = profiles::saved_profiles();
+            }
+
+            ui.separator();
+            ui.add(
+                egui::TextEdit::singleline(&mut self.profile_name_draft)
+                    .hint_text("Profile name")
+                    .desired_width(140.0),
+            );
+            let name = profiles::sanitize_profile_name(&self.profile_name_draft);
+            let valid = !name.is_empty() && !profiles::is_builtin(&name);
+            if ui
+                .add_enabled(valid, egui::Button::new("Save as Profile"))
+                .on_hover_text("Overwrites a saved profile of the same name")
+                .clicked()
+            {
+                match input_mgr.save_profile(&name) {
+                    Ok(()) => {
+                        persist_shortcuts(input_mgr);
+                        self.profile_name_draft.clear();
+                        self.profiles = profiles::saved_profiles();
+                        self.profile_error = None;
+                    }
+                    Err(e) => {
+                        self.profile_error =
+                            Some(format!("Failed to save shortcut profile '{name}': {e}"));
+                    }
+                }
+            }
+        });
+
+        if let Some(err) = &self.profile_error {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), err);
+        }
+    }
+
     fn draw_action_row(
         &mut self,
         ui: &mut egui::Ui,
@@ -1606,13 +1981,16 @@ impl ShortcutsEditorDialog {
         ui.horizontal(|ui| {
             ui.set_min_width(ui.available_width());
 
-            // Action name
+            // Action name and where it applies
             ui.label(action.name());
+            let contexts: Vec<&str> = action.contexts().iter().map(|c| c.name()).collect();
+            ui.label(egui::RichText::new(contexts.join(", ")).small().weak());
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 // Add binding button
                 if ui.small_button("➕").on_hover_text("Add Keybind").clicked() {
                     self.capturing = Some(action);
+                    self.replacing = None;
                     self.capture_buffer = None;
                 }
 
@@ -1622,12 +2000,29 @@ impl ShortcutsEditorDialog {
                 for (i, bind) in bindings.iter().enumerate().rev() {
                     // Remove button
                     if ui.small_button("✕").on_hover_text("Remove").clicked() {
-                        input_mgr.shortcuts_mut().unbind(bind);
-                        let _ = input_mgr.save_shortcuts(&crate::paths::shortcuts_path());
+                        input_mgr.shortcuts_mut().unbind(action, bind);
+                        persist_shortcuts(input_mgr);
                     }
 
-                    // Keybind label
-                    ui.label(bind.to_string());
+                    // Keybind, click to change it
+                    let conflicts = input_mgr.shortcuts().conflicts(bind, action);
+                    let (text, hover) = if conflicts.is_empty() {
+                        (
+                            egui::RichText::new(bind.to_string()),
+                            "Click to change".to_string(),
+                        )
+                    } else {
+                        (
+                            egui::RichText::new(format!("⚠ {bind}"))
+                                .color(egui::Color32::from_rgb(255, 100, 100)),
+                            format!("Also triggers: {}", action_names(&conflicts)),
+                        )
+                    };
+                    if ui.small_button(text).on_hover_text(hover).clicked() {
+                        self.capturing = Some(action);
+                        self.replacing = Some(*bind);
+                        self.capture_buffer = None;
+                    }
 
                     if i > 0 {
                         ui.label("/");
@@ -1646,6 +2041,12 @@ impl ShortcutsEditorDialog {
         }
     }
 
+    fn finish_capture(&mut self) {
+        self.capturing = None;
+        self.replacing = None;
+        self.capture_buffer = None;
+    }
+
     fn draw_capture_popup(
         &mut self,
         ctx: &egui::Context,
@@ -1680,8 +2081,7 @@ impl ShortcutsEditorDialog {
                 if let Some(bind) = maybe_bind {
                     self.capture_buffer = Some(bind);
                 } else {
-                    self.capturing = None;
-                    self.capture_buffer = None;
+                    self.finish_capture();
                     return;
                 }
             }
@@ -1695,17 +2095,27 @@ impl ShortcutsEditorDialog {
             .resizable(false)
             .show(ctx, |ui| {
                 ui.label(format!("Press keys for: {}", action.name()));
+                if let Some(old) = self.replacing {
+                    ui.label(format!("Replacing: {old}"));
+                }
                 ui.label(egui::RichText::new("(ESC to cancel)").weak());
 
                 if let Some(bind) = captured_bind_opt {
                     ui.separator();
                     ui.label(format!("Captured: {}", bind.to_string()));
 
-                    if let Some(conflict) = input_mgr.shortcuts().has_conflict(&bind, Some(action))
-                    {
+                    let conflicts = input_mgr.shortcuts().conflicts(&bind, action);
+                    if !conflicts.is_empty() {
                         ui.colored_label(
                             egui::Color32::from_rgb(255, 100, 100),
-                            format!("⚠ Already used by: {}", conflict.name()),
+                            format!(
+                                "⚠ Already used in the same context by: {}",
+                                action_names(&conflicts)
+                            ),
+                        );
+                        ui.label(
+                            egui::RichText::new("Assigning anyway makes the key trigger both.")
+                                .weak(),
                         );
                     }
 
@@ -1713,14 +2123,19 @@ impl ShortcutsEditorDialog {
 
                     ui.horizontal(|ui| {
                         if ui.button("Assign").clicked() {
-                            input_mgr.shortcuts_mut().bind(action, bind);
-                            let _ = input_mgr.save_shortcuts(&crate::paths::shortcuts_path());
-                            self.capturing = None;
-                            self.capture_buffer = None;
+                            let shortcuts = input_mgr.shortcuts_mut();
+                            match self.replacing {
+                                Some(old) => shortcuts.rebind(action, &old, bind),
+                                None if !shortcuts.get_bindings(action).contains(&bind) => {
+                                    shortcuts.bind(action, bind)
+                                }
+                                None => {}
+                            }
+                            persist_shortcuts(input_mgr);
+                            self.finish_capture();
                         }
                         if ui.button("Cancel").clicked() {
-                            self.capturing = None;
-                            self.capture_buffer = None;
+                            self.finish_capture();
                         }
                     });
                 } else {
@@ -1730,6 +2145,20 @@ impl ShortcutsEditorDialog {
     }
 }
 
+fn action_names(actions: &[AppAction]) -> String {
+    actions
+        .iter()
+        .map(|a| a.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn persist_shortcuts(input_mgr: &InputManager) {
+    if let Err(e) = input_mgr.save_shortcuts(&crate::paths::shortcuts_path()) {
+        eprintln!("Failed to save shortcuts: {}", e);
+    }
+}
+
 impl Default for ShortcutsEditorDialog {
     fn default() -> Self {
         Self::new()
@@ -1750,6 +2179,9 @@ enum ExportRange {
     EntireProject,
     LoopRegion,
     Custom,
+    BetweenMarkers,
+    /// One file per marker-to-marker section
+    MarkerSections,
 }
 
 pub struct ExportDialog {
@@ -1762,8 +2194,60 @@ pub struct ExportDialog {
     export_range: ExportRange,
     start_beat_input: String,
     end_beat_input: String,
+    marker_from: usize,
+    /// Index into the markers; one past the end means the project end
+    marker_to: usize,
     state: Option<crate::messages::ExportState>,
     normalize: bool,
+    normalize_target: NormalizeTarget,
+    dither: bool,
+    automation_interval: usize,
+    tail_secs: f32,
+    tail_auto: bool,
+}
+
+fn normalize_controls(ui: &mut egui::Ui, normalize: &mut bool, target: &mut NormalizeTarget) {
+    ui.horizontal(|ui| {
+        ui.checkbox(normalize, "Normalize");
+        ui.add_enabled_ui(*normalize, |ui| {
+            let loudness = matches!(target, NormalizeTarget::Loudness(_));
+            egui::ComboBox::from_id_salt("export_normalize_target")
+                .selected_text(if loudness { "Loudness" } else { "Peak" })
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(!loudness, "Peak").clicked() {
+                        *target = NormalizeTarget::Peak(-0.1);
+                    }
+                    if ui.selectable_label(loudness, "Loudness").clicked() {
+                        *target = NormalizeTarget::Loudness(-14.0);
+                    }
+                });
+            match target {
+                NormalizeTarget::Peak(db) => {
+                    ui.add(egui::Slider::new(db, -12.0..=0.0).suffix(" dBFS"));
+                }
+                NormalizeTarget::Loudness(lufs) => {
+                    ui.add(egui::Slider::new(lufs, -30.0..=-6.0).suffix(" LUFS"))
+                        .on_hover_text("Integrated loudness; peaks are held under -1 dBFS");
+                }
+            }
+        });
+    });
+}
+
+/// Render past the range end so reverb and delay tails aren't cut off
+fn tail_controls(ui: &mut egui::Ui, tail_secs: &mut f32, tail_auto: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Tail:");
+        ui.add_enabled(
+            !*tail_auto,
+            egui::DragValue::new(tail_secs)
+                .range(0.0..=30.0)
+                .speed(0.1)
+                .suffix(" s"),
+        );
+        ui.checkbox(tail_auto, "Until silent")
+            .on_hover_text("Stop once the output stays below -90 dBFS (30 s at most)");
+    });
 }
 
 impl ExportDialog {
@@ -1787,8 +2271,15 @@ impl ExportDialog {
             export_range: ExportRange::LoopRegion,
             start_beat_input: "0.0".to_string(),
             end_beat_input: "16.0".to_string(),
+            marker_from: 0,
+            marker_to: 1,
             state: None,
             normalize: false,
+            normalize_target: NormalizeTarget::default(),
+            dither: true,
+            automation_interval: crate::constants::DEFAULT_EXPORT_AUTOMATION_INTERVAL,
+            tail_secs: 2.0,
+            tail_auto: false,
         }
     }
 
@@ -1937,9 +2428,31 @@ impl ExportDialog {
                             ui.radio_value(&mut self.bit_depth, 32, "32-bit Float");
                         }
                     });
+                    if self.bit_depth < 24 {
+                        ui.checkbox(&mut self.dither, "TPDF Dither");
+                    }
                 }
 
-                ui.checkbox(&mut self.normalize, "Normalize Peak to -0.1 dB");
+                normalize_controls(ui, &mut self.normalize, &mut self.normalize_target);
+                tail_controls(ui, &mut self.tail_secs, &mut self.tail_auto);
+
+                ui.horizontal(|ui| {
+                    ui.label("Automation Rate:");
+                    let label = |n: usize| match n {
+                        0 => "Per block".to_string(),
+                        1 => "Per sample".to_string(),
+                        n => format!("Every {n} samples"),
+                    };
+                    egui::ComboBox::from_id_salt("export_automation_interval")
+                        .selected_text(label(self.automation_interval))
+                        .show_ui(ui, |ui| {
+                            for n in [0, 256, 64, 32, 1] {
+                                ui.selectable_value(&mut self.automation_interval, n, label(n));
+                            }
+                        })
+                        .response
+                        .on_hover_text("Finer rates follow fast automation more closely but render slower");
+                });
 
                 // Export Range
                 ui.separator();
@@ -1969,66 +2482,142 @@ impl ExportDialog {
                     });
                 }
 
+                let markers = app.state.lock_sync().markers.clone();
+                ui.add_enabled_ui(!markers.is_empty(), |ui| {
+                    ui.radio_value(
+                        &mut self.export_range,
+                        ExportRange::BetweenMarkers,
+                        "Between Markers",
+                    );
+                    ui.radio_value(
+                        &mut self.export_range,
+                        ExportRange::MarkerSections,
+                        "Each Marker Section (separate files)",
+                    );
+                })
+                .response
+                .on_disabled_hover_text("Double-click the timeline ruler to add markers");
+
+                if self.export_range == ExportRange::BetweenMarkers && !markers.is_empty() {
+                    self.marker_from = self.marker_from.min(markers.len() - 1);
+                    self.marker_to = self.marker_to.clamp(self.marker_from + 1, markers.len());
+                    let label = |i: usize| {
+                        markers
+                            .get(i)
+                            .map_or("Project End".to_string(), |m| m.name.clone())
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label("From:");
+                        egui::ComboBox::from_id_salt("export_marker_from")
+                            .selected_text(label(self.marker_from))
+                            .show_ui(ui, |ui| {
+                                for i in 0..markers.len() {
+                                    ui.selectable_value(&mut self.marker_from, i, label(i));
+                                }
+                            });
+                        ui.label("To:");
+                        egui::ComboBox::from_id_salt("export_marker_to")
+                            .selected_text(label(self.marker_to))
+                            .show_ui(ui, |ui| {
+                                for i in self.marker_from + 1..=markers.len() {
+                                    ui.selectable_value(&mut self.marker_to, i, label(i));
+                                }
+                            });
+                    });
+                }
+
                 ui.separator();
 
                 // Action buttons
                 ui.horizontal(|ui| {
                     if ui.button("Export").clicked() {
-                        let (start_beat, end_beat) = match self.export_range {
-                            ExportRange::EntireProject => {
-                                let end = app.timeline_ui.compute_project_end_beats(app);
-                                (0.0, end)
-                            }
-                            ExportRange::LoopRegion => (
+                        let project_end = app.timeline_ui.compute_project_end_beats(app);
+                        let ranges: Vec<(Option<String>, f64, f64)> = match self.export_range {
+                            ExportRange::EntireProject => vec![(None, 0.0, project_end)],
+                            ExportRange::LoopRegion => vec![(
+                                None,
                                 app.audio_state.loop_start.load(),
                                 app.audio_state.loop_end.load(),
-                            ),
-                            ExportRange::Custom => (
+                            )],
+                            ExportRange::Custom => vec![(
+                                None,
                                 self.start_beat_input.parse().unwrap_or(0.0),
                                 self.end_beat_input.parse().unwrap_or(0.0),
-                            ),
+                            )],
+                            ExportRange::BetweenMarkers => {
+                                let beat_of = |i: usize| markers.get(i).map(|m| m.beat);
+                                vec![(
+                                    None,
+                                    beat_of(self.marker_from).unwrap_or(0.0),
+                                    beat_of(self.marker_to).unwrap_or(project_end),
+                                )]
+                            }
+                            ExportRange::MarkerSections => {
+                                crate::audio_export::marker_sections(&markers, project_end)
+                                    .into_iter()
+                                    .map(|(name, start, end)| (Some(name), start, end))
+                                    .collect()
+                            }
                         };
 
+                        if ranges.is_empty() {
+                            app.dialogs.show_error("There are no marker sections to export.");
+                            return;
+                        }
+
                         #[cfg(target_os = "android")]
-                        {
+                        let export_uri = {
                             let Some(export_uri) = self.export_uri.clone() else {
                                 app.dialogs
                                     .show_error("Please select an export destination first.");
                                 return;
                             };
-
-                            let config = crate::messages::ExportConfig {
-                                path: self.path.clone(),
-                                export_uri: Some(export_uri),
-                                format: Some(self.format),
-                                sample_rate: app.audio_state.sample_rate.load(),
-                                bit_depth: self.bit_depth,
-                                start_beat,
-                                end_beat,
-                                normalize: self.normalize,
-                            };
-
-                            let _ = app.command_tx.send(AudioCommand::ExportAudio(config));
-                            self.state = Some(ExportState::Rendering(0.0));
-                            return;
-                        }
-
+                            if ranges.len() > 1 {
+                                app.dialogs.show_error(
+                                    "Exporting each marker section to its own file isn't supported on Android yet.",
+                                );
+                                return;
+                            }
+                            Some(export_uri)
+                        };
                         #[cfg(not(target_os = "android"))]
-                        {
-                            let config = crate::messages::ExportConfig {
-                                path: self.path.clone(),
-                                export_uri: None,
+                        let export_uri = None;
+
+                        let bypass_clipper = app.config.audio.bypass_master_soft_clipper;
+                        let mut configs: Vec<_> = ranges
+                            .into_iter()
+                            .map(|(section, start_beat, end_beat)| crate::messages::ExportConfig {
+                                path: match section {
+                                    Some(name) => {
+                                        crate::audio_export::suffixed_path(&self.path, &name)
+                                    }
+                                    None => self.path.clone(),
+                                },
+                                export_uri: export_uri.clone(),
                                 format: Some(self.format),
                                 sample_rate: app.audio_state.sample_rate.load(),
                                 bit_depth: self.bit_depth,
                                 start_beat,
                                 end_beat,
                                 normalize: self.normalize,
-                            };
-
-                            let _ = app.command_tx.send(AudioCommand::ExportAudio(config));
-                            self.state = Some(ExportState::Rendering(0.0));
-                        }
+                                normalize_target: self.normalize_target,
+                                dither: self.dither,
+                                automation_interval: self.automation_interval,
+                                bypass_master_soft_clipper: bypass_clipper,
+                                true_peak: app.config.audio.true_peak_metering,
+                                stem: None,
+                                tail_secs: self.tail_secs,
+                                tail_auto: self.tail_auto,
+                            })
+                            .collect();
+
+                        let cmd = if configs.len() == 1 {
+                            AudioCommand::ExportAudio(configs.remove(0))
+                        } else {
+                            AudioCommand::ExportAudioBatch(configs)
+                        };
+                        let _ = app.command_tx.send(cmd);
+                        self.state = Some(ExportState::Rendering(0.0));
                     }
                     if ui.button("Cancel").clicked() {
                         self.closed = true;
@@ -2046,6 +2635,228 @@ impl ExportDialog {
     }
 }
 
+/// One WAV per track, rendered through the offline engine with the others left out
+pub struct StemExportDialog {
+    closed: bool,
+    folder: Option<PathBuf>,
+    picker_rx: Option<Picker<PlatformFile>>,
+    /// (track id, name, export it); filled from the project on first show
+    tracks: Option<Vec<(u64, String, bool)>>,
+    bit_depth: u16,
+    whole_project: bool,
+    post_fader: bool,
+    include_sends: bool,
+    dither: bool,
+    tail_secs: f32,
+    tail_auto: bool,
+    state: Option<ExportState>,
+}
+
+impl StemExportDialog {
+    pub fn new() -> Self {
+        Self {
+            closed: false,
+            folder: None,
+            picker_rx: None,
+            tracks: None,
+            bit_depth: 24,
+            whole_project: true,
+            post_fader: true,
+            include_sends: false,
+            dither: true,
+            tail_secs: 2.0,
+            tail_auto: false,
+            state: None,
+        }
+    }
+
+    pub fn set_state(&mut self, state: ExportState) {
+        self.state = Some(state);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, app: &mut super::app::YadawApp) {
+        let mut open = true;
+        egui::Window::new("Export Stems")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if let Some(state) = &self.state {
+                    match state {
+                        ExportState::Rendering(progress) => {
+                            ui.label("Rendering stems...");
+                            ui.add(egui::ProgressBar::new(*progress).show_percentage());
+                        }
+                        ExportState::Normalizing | ExportState::Finalizing => {
+                            ui.label("Writing files...");
+                            ui.add(egui::Spinner::new());
+                        }
+                        ExportState::Complete(path) => {
+                            ui.colored_label(egui::Color32::GREEN, "Stems exported!");
+                            ui.label(format!("Last file: {path}"));
+                        }
+                        ExportState::Error(err) => {
+                            ui.colored_label(egui::Color32::RED, "Stem export failed!");
+                            ui.label(err);
+                        }
+                        ExportState::Cancelled => {
+                            ui.label("Export Cancelled.");
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        self.closed = true;
+                    }
+                    return;
+                }
+
+                self.draw_options(ui, app);
+            });
+
+        if !open {
+            self.closed = true;
+        }
+    }
+
+    fn draw_options(&mut self, ui: &mut egui::Ui, app: &mut super::app::YadawApp) {
+        let tracks = self.tracks.get_or_insert_with(|| {
+            let state = app.state.lock_sync();
+            state
+                .track_order
+                .iter()
+                .filter_map(|id| state.tracks.get(id))
+                .filter(|t| !matches!(t.track_type, TrackType::Bus))
+                .map(|t| (t.id, t.name.clone(), true))
+                .collect()
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            ui.label("Folder:");
+            match &self.folder {
+                Some(folder) => ui.label(folder.to_string_lossy()),
+                None => ui.weak("None selected"),
+            };
+            if ui.button("Browse...").clicked() && self.picker_rx.is_none() {
+                self.picker_rx = Some(crate::file_picker::pick_directory("Export Stems To"));
+            }
+        });
+        if let Some(mut picker) = self.picker_rx.take() {
+            match picker.poll() {
+                Some(Ok(Some(file))) => {
+                    if let Some(path) = file.path() {
+                        self.folder = Some(path.to_path_buf());
+                    } else {
+                        app.dialogs
+                            .show_error("Stems can only be exported to a local folder.");
+                    }
+                }
+                Some(Ok(None)) => {}
+                Some(Err(err)) => {
+                    app.dialogs
+                        .show_error(&format!("Folder picker failed: {err}"));
+                }
+                None => self.picker_rx = Some(picker),
+            }
+        }
+
+        ui.separator();
+        ui.label("Tracks:");
+        egui::ScrollArea::vertical()
+            .max_height(160.0)
+            .show(ui, |ui| {
+                for (_, name, enabled) in tracks.iter_mut() {
+                    ui.checkbox(enabled, name.as_str());
+                }
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Bit Depth:");
+            ui.radio_value(&mut self.bit_depth, 16, "16-bit");
+            ui.radio_value(&mut self.bit_depth, 24, "24-bit");
+            ui.radio_value(&mut self.bit_depth, 32, "32-bit Float");
+        });
+        if self.bit_depth < 24 {
+            ui.checkbox(&mut self.dither, "TPDF Dither");
+        }
+        ui.horizontal(|ui| {
+            ui.label("Range:");
+            ui.radio_value(&mut self.whole_project, true, "Entire Project");
+            ui.radio_value(&mut self.whole_project, false, "Loop Region");
+        });
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.post_fader, true, "Post-fader");
+            ui.radio_value(&mut self.post_fader, false, "Pre-fader");
+        })
+        .response
+        .on_hover_text("Pre-fader stems ignore the track's volume, pan and their automation");
+        ui.checkbox(&mut self.include_sends, "Include sends and bus processing")
+            .on_hover_text("Otherwise each stem goes straight to the master");
+        tail_controls(ui, &mut self.tail_secs, &mut self.tail_auto);
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let chosen: Vec<(u64, String)> = tracks
+                .iter()
+                .filter(|(_, _, enabled)| *enabled)
+                .map(|(id, name, _)| (*id, name.clone()))
+                .collect();
+            let has_folder = cfg!(target_arch = "wasm32") || self.folder.is_some();
+            if ui
+                .add_enabled(
+                    has_folder && !chosen.is_empty(),
+                    egui::Button::new("Export"),
+                )
+                .clicked()
+            {
+                let (start_beat, end_beat) = if self.whole_project {
+                    (0.0, app.timeline_ui.compute_project_end_beats(app))
+                } else {
+                    (
+                        app.audio_state.loop_start.load(),
+                        app.audio_state.loop_end.load(),
+                    )
+                };
+                let folder = self.folder.clone().unwrap_or_default();
+                let configs = chosen
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (track_id, name))| crate::messages::ExportConfig {
+                        path: crate::audio_export::stem_path(&folder, i, &name),
+                        export_uri: None,
+                        format: Some(ExportFormat::Wav),
+                        sample_rate: app.audio_state.sample_rate.load(),
+                        bit_depth: self.bit_depth,
+                        start_beat,
+                        end_beat,
+                        normalize: false,
+                        normalize_target: Default::default(),
+                        dither: self.dither,
+                        automation_interval: crate::constants::DEFAULT_EXPORT_AUTOMATION_INTERVAL,
+                        bypass_master_soft_clipper: app.config.audio.bypass_master_soft_clipper,
+                        true_peak: false,
+                        stem: Some(crate::messages::StemOptions {
+                            track_id,
+                            post_fader: self.post_fader,
+                            include_sends: self.include_sends,
+                        }),
+                        tail_secs: self.tail_secs,
+                        tail_auto: self.tail_auto,
+                    })
+                    .collect();
+                let _ = app.command_tx.send(AudioCommand::ExportAudioBatch(configs));
+                self.state = Some(ExportState::Rendering(0.0));
+            }
+            if ui.button("Cancel").clicked() {
+                self.closed = true;
+            }
+        });
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
 pub struct PluginManagerDialog {
     closed: bool,
     scan_paths: Vec<String>,
@@ -2864,6 +3675,7 @@ pub struct TrackGroupingDialog {
     selected_group: Option<u64>,
     editing_group: Option<u64>,
     edit_name: String,
+    bus_also_groups: bool,
 }
 
 impl TrackGroupingDialog {
@@ -2875,6 +3687,7 @@ impl TrackGroupingDialog {
             selected_group: None,
             editing_group: None,
             edit_name: String::new(),
+            bus_also_groups: false,
         }
     }
 
@@ -3095,6 +3908,41 @@ impl TrackGroupingDialog {
                             self.selected_tracks.clear();
                         }
                     });
+
+                    ui.horizontal(|ui| {
+                        let has_bus = {
+                            let st = app.state.lock_sync();
+                            self.selected_tracks.iter().any(|tid| {
+                                st.tracks
+                                    .get(tid)
+                                    .is_some_and(|t| matches!(t.track_type, TrackType::Bus))
+                            })
+                        };
+                        let resp = ui
+                            .add_enabled(
+                                !self.selected_tracks.is_empty() && !has_bus,
+                                egui::Button::new("Route to New Bus"),
+                            )
+                            .on_hover_text(
+                                "Create a bus and send the selected tracks' outputs to it",
+                            )
+                            .on_disabled_hover_text(if has_bus {
+                                "A bus can't be routed into another bus"
+                            } else {
+                                "Select tracks first"
+                            });
+                        ui.checkbox(&mut self.bus_also_groups, "Also group");
+
+                        if resp.clicked() {
+                            let name = if self.bus_also_groups {
+                                self.new_group_name.clone()
+                            } else {
+                                String::new()
+                            };
+                            let tracks = std::mem::take(&mut self.selected_tracks);
+                            app.route_tracks_to_new_bus(&tracks, &name, self.bus_also_groups);
+                        }
+                    });
                 });
 
                 ui.separator();
@@ -3113,6 +3961,532 @@ impl TrackGroupingDialog {
     }
 }
 
+pub struct ProjectMetadataDialog {
+    closed: bool,
+    metadata: crate::project::ProjectMetadata,
+    initialized: bool,
+}
+
+impl ProjectMetadataDialog {
+    pub fn new() -> Self {
+        Self {
+            closed: false,
+            metadata: Default::default(),
+            initialized: false,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, app: &mut super::app::YadawApp) {
+        let mut open = true;
+
+        if !self.initialized {
+            self.metadata = app.state.lock_sync().metadata.clone();
+            self.initialized = true;
+        }
+
+        egui::Window::new("Project Info")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("project_metadata_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Title:");
+                        ui.text_edit_singleline(&mut self.metadata.title);
+                        ui.end_row();
+
+                        ui.label("Artist:");
+                        ui.text_edit_singleline(&mut self.metadata.artist);
+                        ui.end_row();
+
+                        ui.label("Key:");
+                        ui.text_edit_singleline(&mut self.metadata.key);
+                        ui.end_row();
+                    });
+
+                ui.label("Notes:");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.metadata.comments)
+                        .desired_rows(6)
+                        .desired_width(f32::INFINITY),
+                );
+
+                ui.label(
+                    egui::RichText::new("Title and artist are written to WAV/FLAC exports.").weak(),
+                );
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("OK").clicked() {
+                        let changed = {
+                            let mut state = app.state.lock_sync();
+                            let changed = state.metadata != self.metadata;
+                            state.metadata = self.metadata.clone();
+                            changed
+                        };
+                        if changed {
+                            app.project_manager.mark_dirty();
+                        }
+                        self.closed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.closed = true;
+                    }
+                });
+            });
+
+        if !open {
+            self.closed = true;
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+/// Lists the numbered versions saved next to the current project
+pub struct ProjectVersionsDialog {
+    closed: bool,
+    /// (version, path, last modified)
+    versions: Vec<(u32, PathBuf, String)>,
+}
+
+impl ProjectVersionsDialog {
+    pub fn new(project_path: Option<&Path>) -> Self {
+        let versions = project_path
+            .map(crate::project_manager::project_versions)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .map(|(version, path)| {
+                let modified = path
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .map(|t| {
+                        chrono::DateTime::<chrono::Local>::from(t)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                (version, path, modified)
+            })
+            .collect();
+        Self {
+            closed: false,
+            versions,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, app: &mut super::app::YadawApp) {
+        let mut open = true;
+        let mut to_open = None;
+
+        egui::Window::new("Project Versions")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                if self.versions.is_empty() {
+                    ui.label("No saved versions. Use File > Save New Version first.");
+                    return;
+                }
+                let current = app.project_path.as_deref().map(Path::new);
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("project_versions_grid")
+                            .num_columns(3)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (version, path, modified) in &self.versions {
+                                    let name = path
+                                        .file_name()
+                                        .and_then(|s| s.to_str())
+                                        .unwrap_or_default();
+                                    let label = if *version == 0 {
+                                        format!("{name} (original)")
+                                    } else {
+                                        name.to_string()
+                                    };
+                                    if current == Some(path.as_path()) {
+                                        ui.strong(label);
+                                    } else {
+                                        ui.label(label);
+                                    }
+                                    ui.label(egui::RichText::new(modified).weak());
+                                    if ui.button("Open").clicked() {
+                                        to_open = Some(path.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(path) = to_open {
+            app.load_project_from_path(&path);
+            self.closed = true;
+        }
+        if !open {
+            self.closed = true;
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+/// Offers to relink clips whose media files were missing on load
+pub struct MissingMediaDialog {
+    closed: bool,
+    /// Media path being located, and its file picker
+    locate: Option<(String, Picker<PlatformFile>)>,
+    folder_picker: Option<Picker<PlatformFile>>,
+    status: Option<String>,
+}
+
+impl MissingMediaDialog {
+    pub fn new() -> Self {
+        Self {
+            closed: false,
+            locate: None,
+            folder_picker: None,
+            status: None,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, app: &mut super::app::YadawApp) {
+        self.poll_pickers(app);
+
+        let missing = app.missing_media();
+        if missing.is_empty() && self.status.is_none() {
+            self.closed = true;
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("Missing Media")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                if missing.is_empty() {
+                    ui.label("All media relinked.");
+                } else {
+                    ui.label(
+                        "These files could not be found. Their clips keep their place \
+                         and length but stay silent until relinked.",
+                    );
+                }
+                ui.separator();
+
+                let busy = self.locate.is_some() || self.folder_picker.is_some();
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for media_path in &missing {
+                            ui.horizontal(|ui| {
+                                ui.label(media_path).on_hover_text(media_path);
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui
+                                            .add_enabled(!busy, egui::Button::new("Locate…"))
+                                            .clicked()
+                                        {
+                                            self.locate = Some((
+                                                media_path.clone(),
+                                                crate::file_picker::pick_open_file(
+                                                    "Locate Media",
+                                                    crate::constants::AUDIO_IMPORT_EXTENSIONS,
+                                                ),
+                                            ));
+                                        }
+                                    },
+                                );
+                            });
+                        }
+                    });
+
+                if let Some(status) = &self.status {
+                    ui.label(egui::RichText::new(status).weak());
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if !missing.is_empty()
+                        && ui
+                            .add_enabled(!busy, egui::Button::new("Search Folder…"))
+                            .on_hover_text("Relink every missing file found by name in a folder")
+                            .clicked()
+                    {
+                        self.folder_picker =
+                            Some(crate::file_picker::pick_directory("Search For Media"));
+                    }
+                    let close_label = if missing.is_empty() { "Done" } else { "Skip" };
+                    if ui.button(close_label).clicked() {
+                        self.closed = true;
+                    }
+                });
+            });
+
+        if !open {
+            self.closed = true;
+        }
+    }
+
+    fn poll_pickers(&mut self, app: &mut super::app::YadawApp) {
+        if let Some((media_path, mut picker)) = self.locate.take() {
+            match picker.poll() {
+                Some(Ok(Some(file))) => match file.path() {
+                    Some(path) => {
+                        self.status = Some(match app.relink_media(&media_path, path) {
+                            Ok(()) => format!("Relinked {}", media_path),
+                            Err(e) => format!("Could not read {}: {}", path.display(), e),
+                        });
+                    }
+                    None => app
+                        .dialogs
+                        .show_error("Media can only be relinked to a local file."),
+                },
+                Some(Ok(None)) => {}
+                Some(Err(err)) => app
+                    .dialogs
+                    .show_error(&format!("File picker failed: {err}")),
+                None => self.locate = Some((media_path, picker)),
+            }
+        }
+
+        if let Some(mut picker) = self.folder_picker.take() {
+            match picker.poll() {
+                Some(Ok(Some(file))) => match file.path() {
+                    Some(path) => {
+                        let found = app.relink_media_from_folder(path);
+                        self.status =
+                            Some(format!("Found {} file(s) in {}", found, path.display()));
+                    }
+                    None => app
+                        .dialogs
+                        .show_error("Only local folders can be searched."),
+                },
+                Some(Ok(None)) => {}
+                Some(Err(err)) => app
+                    .dialogs
+                    .show_error(&format!("Folder picker failed: {err}")),
+                None => self.folder_picker = Some(picker),
+            }
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+enum PatternEdit {
+    Rename(u64, String),
+    Duplicate(u64),
+    Delete(u64),
+    DeleteUnused,
+}
+
+pub struct PatternManagerDialog {
+    closed: bool,
+    renaming: Option<(u64, String)>,
+}
+
+impl PatternManagerDialog {
+    pub fn new() -> Self {
+        Self {
+            closed: false,
+            renaming: None,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, app: &mut super::app::YadawApp) {
+        let mut open = true;
+        let mut edit: Option<PatternEdit> = None;
+
+        // (id, name, note count, alias clips using it)
+        let rows: Vec<(u64, String, usize, usize)> = {
+            let state = app.state.lock_sync();
+            let mut usage: std::collections::HashMap<u64, usize> = Default::default();
+            for track in state.tracks.values() {
+                for clip in &track.midi_clips {
+                    if let Some(pid) = clip.pattern_id {
+                        *usage.entry(pid).or_default() += 1;
+                    }
+                }
+            }
+            let mut rows: Vec<_> = state
+                .patterns
+                .values()
+                .map(|p| {
+                    (
+                        p.id,
+                        p.display_name(),
+                        p.notes.len(),
+                        usage.get(&p.id).copied().unwrap_or(0),
+                    )
+                })
+                .collect();
+            rows.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+            rows
+        };
+
+        egui::Window::new("Patterns")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                if rows.is_empty() {
+                    ui.label("No MIDI patterns in this project");
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("pattern_manager_grid")
+                            .num_columns(4)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Name");
+                                ui.strong("Notes");
+                                ui.strong("Clips");
+                                ui.label("");
+                                ui.end_row();
+
+                                for (id, name, notes, uses) in &rows {
+                                    match self.renaming.as_mut() {
+                                        Some((rid, text)) if rid == id => {
+                                            let resp = ui.text_edit_singleline(text);
+                                            if resp.lost_focus()
+                                                && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                                            {
+                                                edit = Some(PatternEdit::Rename(*id, text.clone()));
+                                            }
+                                        }
+                                        _ => {
+                                            ui.label(name);
+                                        }
+                                    }
+                                    ui.label(notes.to_string());
+                                    ui.label(uses.to_string());
+
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("Rename").clicked() {
+                                            self.renaming = Some((*id, name.clone()));
+                                        }
+                                        if ui.small_button("Duplicate").clicked() {
+                                            edit = Some(PatternEdit::Duplicate(*id));
+                                        }
+                                        if ui
+                                            .add_enabled(
+                                                *uses == 0,
+                                                egui::Button::new("Delete").small(),
+                                            )
+                                            .on_disabled_hover_text("Pattern is used by clips")
+                                            .clicked()
+                                        {
+                                            edit = Some(PatternEdit::Delete(*id));
+                                        }
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    let unused = rows.iter().filter(|r| r.3 == 0).count();
+                    if ui
+                        .add_enabled(
+                            unused > 0,
+                            egui::Button::new(format!("Delete Unused ({unused})")),
+                        )
+                        .clicked()
+                    {
+                        edit = Some(PatternEdit::DeleteUnused);
+                    }
+                    if ui.button("Close").clicked() {
+                        self.closed = true;
+                    }
+                });
+            });
+
+        if let Some(edit) = edit {
+            self.apply(edit, app);
+        }
+
+        if !open {
+            self.closed = true;
+        }
+    }
+
+    fn apply(&mut self, edit: PatternEdit, app: &mut super::app::YadawApp) {
+        app.push_undo();
+        {
+            let mut state = app.state.lock_sync();
+            match edit {
+                PatternEdit::Rename(id, name) => {
+                    if let Some(p) = state.patterns.get_mut(&id) {
+                        p.name = name.trim().to_string();
+                    }
+                    self.renaming = None;
+                }
+                PatternEdit::Duplicate(id) => {
+                    if let Some(src) = state.patterns.get(&id).cloned() {
+                        let new_id = crate::idgen::next();
+                        let notes = src
+                            .notes
+                            .iter()
+                            .map(|n| crate::model::MidiNote {
+                                id: crate::idgen::next(),
+                                ..*n
+                            })
+                            .collect();
+                        state.patterns.insert(
+                            new_id,
+                            crate::model::clip::MidiPattern {
+                                id: new_id,
+                                name: format!("{} Copy", src.display_name()),
+                                notes,
+                            },
+                        );
+                    }
+                }
+                PatternEdit::Delete(id) => {
+                    let in_use = state
+                        .tracks
+                        .values()
+                        .any(|t| t.midi_clips.iter().any(|c| c.pattern_id == Some(id)));
+                    if !in_use {
+                        state.patterns.remove(&id);
+                    }
+                }
+                PatternEdit::DeleteUnused => {
+                    let used: std::collections::HashSet<u64> = state
+                        .tracks
+                        .values()
+                        .flat_map(|t| t.midi_clips.iter().filter_map(|c| c.pattern_id))
+                        .collect();
+                    state.patterns.retain(|id, _| used.contains(id));
+                }
+            }
+        }
+        app.project_manager.mark_dirty();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
 pub struct TrackRenameDialog {
     closed: bool,
     track_id: u64,
@@ -1,15 +1,27 @@
 use std::sync::atomic::Ordering;
 
 use super::*;
+use crate::error::UserNotification;
+use crate::file_picker::{Picker, PlatformFile};
 use crate::{
     config::Config, constants::DEFAULT_MIN_PROJECT_BEATS, input::actions::AppAction,
     messages::AudioCommand,
 };
 
+/// Which metronome sample a pending file pick is for
+#[derive(Clone, Copy)]
+enum ClickSample {
+    Accent,
+    Beat,
+}
+
 pub struct MenuBar {
     show_about: bool,
     show_preferences: bool,
     preferences_draft: Option<Config>,
+    midi_output_ports: Vec<String>,
+    output_channels: usize,
+    click_sample_picker: Option<(ClickSample, Picker<PlatformFile>)>,
 }
 
 impl MenuBar {
@@ -18,6 +30,9 @@ impl MenuBar {
             show_about: false,
             show_preferences: false,
             preferences_draft: None,
+            midi_output_ports: Vec::new(),
+            output_channels: 2,
+            click_sample_picker: None,
         }
     }
 
@@ -86,6 +101,38 @@ impl MenuBar {
                 ui.close();
             }
 
+            if ui
+                .button("Save New Version")
+                .on_hover_text("Save as the next numbered copy, e.g. song.3.yadaw")
+                .clicked()
+            {
+                app.save_new_version();
+                ui.close();
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .button("Collect and Save")
+                .on_hover_text("Write all clip audio to a media folder beside the project")
+                .clicked()
+            {
+                app.collect_and_save();
+                ui.close();
+            }
+
+            if ui
+                .add_enabled(
+                    app.project_path.is_some(),
+                    egui::Button::new("Project Versions..."),
+                )
+                .clicked()
+            {
+                let path = app.project_path.clone();
+                app.dialogs
+                    .show_project_versions(path.as_deref().map(std::path::Path::new));
+                ui.close();
+            }
+
             ui.separator();
 
             if ui.button("Import Audio...").clicked() {
@@ -98,6 +145,16 @@ impl MenuBar {
                 ui.close();
             }
 
+            if ui.button("Export Stems...").clicked() {
+                app.dialogs.show_export_stems();
+                ui.close();
+            }
+
+            if ui.button("Export MIDI...").clicked() {
+                app.dialogs.show_export_midi();
+                ui.close();
+            }
+
             ui.separator();
 
             if ui.button("Project Settings...").clicked() {
@@ -105,6 +162,11 @@ impl MenuBar {
                 ui.close();
             }
 
+            if ui.button("Project Info...").clicked() {
+                app.dialogs.show_project_metadata();
+                ui.close();
+            }
+
             ui.separator();
 
             if ui.button("Exit").clicked() {
@@ -191,6 +253,13 @@ impl MenuBar {
                 ui.close();
             }
 
+            if ui
+                .checkbox(&mut app.spectrum_ui.visible, "Spectrum Analyzer")
+                .clicked()
+            {
+                ui.close();
+            }
+
             if ui
                 .checkbox(&mut app.timeline_ui.show_automation, "Automation Lanes")
                 .clicked()
@@ -335,9 +404,7 @@ impl MenuBar {
             ui.separator();
 
             if ui.button("Go to Start").clicked() {
-                if let Some(transport) = &mut app.transport_ui.transport {
-                    transport.set_position(0.0);
-                }
+                app.seek_to_beat(0.0);
                 ui.close();
             }
 
@@ -356,13 +423,7 @@ impl MenuBar {
                     }
                     max_beat
                 };
-                // convert beats->samples
-                let sr = app.audio_state.sample_rate.load() as f64;
-                let bpm = app.audio_state.bpm.load() as f64;
-                if bpm > 0.0 && sr > 0.0 {
-                    let samples = end_beats * (60.0 / bpm) * sr;
-                    let _ = app.command_tx.send(AudioCommand::SetPosition(samples));
-                }
+                app.seek_to_beat(end_beats);
                 ui.close();
             }
 
@@ -445,7 +506,12 @@ impl MenuBar {
             }
 
             if ui.button("Audio Setup...").clicked() {
-                app.dialogs.show_audio_setup();
+                app.dialogs.show_audio_setup(&app.config.audio);
+                ui.close();
+            }
+
+            if ui.button("Patterns...").clicked() {
+                app.dialogs.show_pattern_manager();
                 ui.close();
             }
 
@@ -584,10 +650,42 @@ impl MenuBar {
         if self.show_preferences {
             if self.preferences_draft.is_none() {
                 self.preferences_draft = Some(app.config.clone());
+                self.midi_output_ports = app
+                    .midi_clock_output
+                    .as_ref()
+                    .map(|o| o.list_ports())
+                    .unwrap_or_default();
+                self.output_channels = app.audio_state.output_channels.load(Ordering::Relaxed);
             }
 
             let mut show_preferences = true;
             let mut apply_clicked = false;
+            let mut open_shortcuts = false;
+            let mut browse_click_sample = None;
+
+            if let Some((which, mut picker)) = self.click_sample_picker.take() {
+                match picker.poll() {
+                    Some(Ok(Some(file))) => match file.path() {
+                        Some(path) => {
+                            if let Some(config) = self.preferences_draft.as_mut() {
+                                let slot = match which {
+                                    ClickSample::Accent => &mut config.metronome.accent_sample,
+                                    ClickSample::Beat => &mut config.metronome.click_sample,
+                                };
+                                *slot = Some(path.to_path_buf());
+                            }
+                        }
+                        None => app
+                            .dialogs
+                            .show_error("Click samples can only be local files."),
+                    },
+                    Some(Ok(None)) => {}
+                    Some(Err(e)) => app
+                        .dialogs
+                        .show_error(&format!("Failed to pick click sample: {e}")),
+                    None => self.click_sample_picker = Some((which, picker)),
+                }
+            }
 
             if let Some(config) = self.preferences_draft.as_mut() {
                 egui::Window::new("Preferences")
@@ -595,12 +693,56 @@ impl MenuBar {
                     .resizable(true)
                     .default_size(egui::vec2(600.0, 400.0))
                     .show(ctx, |ui| {
-                        apply_clicked = draw_preferences(ui, config);
+                        apply_clicked = draw_preferences(
+                            ui,
+                            config,
+                            &self.midi_output_ports,
+                            self.output_channels,
+                            &mut open_shortcuts,
+                            &mut browse_click_sample,
+                        );
                     });
             }
 
+            if let Some(which) = browse_click_sample
+                && self.click_sample_picker.is_none()
+            {
+                let picker = crate::file_picker::pick_open_file(
+                    "Choose Click Sample",
+                    crate::constants::AUDIO_EXTENSIONS,
+                );
+                self.click_sample_picker = Some((which, picker));
+            }
+
+            if open_shortcuts {
+                app.dialogs.show_shortcuts_editor();
+            }
+
             if apply_clicked && let Some(config) = &self.preferences_draft {
+                // The cue controls live in the mixer, not the draft
+                let (cue_level, cue_click) =
+                    (app.config.audio.cue_level, app.config.audio.cue_click);
                 app.config = config.clone();
+                app.config.audio.cue_level = cue_level;
+                app.config.audio.cue_click = cue_click;
+                app.apply_midi_clock_config();
+                app.apply_metronome_config();
+                app.audio_state
+                    .master_output_channel
+                    .store(app.config.audio.master_output_channel, Ordering::Relaxed);
+                app.audio_state
+                    .cue_output_channel
+                    .store(app.config.audio.cue_output_channel, Ordering::Relaxed);
+                app.audio_state.master_clip_bypass.store(
+                    app.config.audio.bypass_master_soft_clipper,
+                    Ordering::Relaxed,
+                );
+                app.audio_state
+                    .true_peak_metering
+                    .store(app.config.audio.true_peak_metering, Ordering::Relaxed);
+                app.audio_state
+                    .solo_in_place
+                    .store(app.config.audio.solo_in_place, Ordering::Relaxed);
                 match app.config.save() {
                     Ok(()) => app.dialogs.show_message(
                         "Preferences saved. Sample-rate changes apply immediately for new plugin instances after relaunch.",
@@ -619,7 +761,14 @@ impl MenuBar {
     }
 }
 
-fn draw_preferences(ui: &mut egui::Ui, config: &mut crate::config::Config) -> bool {
+fn draw_preferences(
+    ui: &mut egui::Ui,
+    config: &mut crate::config::Config,
+    midi_output_ports: &[String],
+    output_channels: usize,
+    open_shortcuts: &mut bool,
+    browse_click_sample: &mut Option<ClickSample>,
+) -> bool {
     const SAMPLE_RATES: [u32; 6] = [22050, 44100, 48000, 88200, 96000, 192000];
     let mut apply_clicked = false;
 
@@ -630,7 +779,13 @@ fn draw_preferences(ui: &mut egui::Ui, config: &mut crate::config::Config) -> bo
             let _ = ui.selectable_label(true, "Audio"); // TODO for later
             let _ = ui.selectable_label(false, "MIDI");
             let _ = ui.selectable_label(false, "Appearance");
-            let _ = ui.selectable_label(false, "Shortcuts");
+            if ui
+                .selectable_label(false, "Shortcuts")
+                .on_hover_text("Opens the keyboard shortcut editor")
+                .clicked()
+            {
+                *open_shortcuts = true;
+            }
             let _ = ui.selectable_label(false, "Behavior");
             let _ = ui.selectable_label(false, "Plugins");
             let _ = ui.selectable_label(false, "File Paths");
@@ -662,7 +817,224 @@ fn draw_preferences(ui: &mut egui::Ui, config: &mut crate::config::Config) -> bo
                 config.audio.sample_rate = sample_rate as f32;
             });
 
-            ui.label(egui::RichText::new("Takes effect on restart.").weak());
+            ui.label(
+                egui::RichText::new(
+                    "Takes effect on restart; \
+                     Tools > Audio Setup switches live.",
+                )
+                .weak(),
+            );
+
+            ui.horizontal(|ui| {
+                ui.label("Master Output:");
+                output_pair_combo(
+                    ui,
+                    "preferences_master_output",
+                    &mut config.audio.master_output_channel,
+                    output_channels,
+                );
+            })
+            .response
+            .on_hover_text(
+                "Output channel pair of the current device that receives the master mix",
+            );
+
+            ui.horizontal(|ui| {
+                ui.label("Cue Output:");
+                output_pair_combo(
+                    ui,
+                    "preferences_cue_output",
+                    &mut config.audio.cue_output_channel,
+                    output_channels,
+                );
+            })
+            .response
+            .on_hover_text("Output channel pair for the cue/headphone mix");
+
+            ui.checkbox(
+                &mut config.audio.bypass_master_soft_clipper,
+                "Bypass master soft clipper",
+            )
+            .on_hover_text(
+                "Applies to playback and export. The master has no other limiter, \
+                 so this leaves the output unprocessed.",
+            );
+            if config.audio.bypass_master_soft_clipper {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "Output may exceed 0 dBFS. Only 32-bit float WAV export keeps the overs.",
+                );
+            }
+
+            ui.checkbox(&mut config.audio.true_peak_metering, "True-peak metering")
+                .on_hover_text(
+                    "Master meter and export normalization use 4x oversampled peaks, catching overs between samples",
+                );
+
+            ui.checkbox(&mut config.audio.solo_in_place, "Solo in place")
+                .on_hover_text(
+                    "Soloing a track keeps the buses its sends reach audible, so reverb and delay \
+                     returns stay. Right-click a solo button to make a track solo-safe.",
+                );
+
+            ui.separator();
+            ui.heading("Display");
+
+            ui.checkbox(&mut config.ui.bars_start_at_zero, "Number bars from 0")
+                .on_hover_text("Used by the timeline ruler, piano roll and position display");
+
+            ui.horizontal(|ui| {
+                ui.label("Automation lane height:");
+                ui.add(
+                    egui::DragValue::new(&mut config.ui.automation_lane_height)
+                        .range(24.0..=200.0)
+                        .suffix(" px"),
+                );
+            })
+            .response
+            .on_hover_text("Height of newly added automation lanes");
+
+            ui.separator();
+            ui.heading("Behavior");
+
+            ui.checkbox(
+                &mut config.behavior.lock_playhead_to_grid,
+                "Lock playhead to grid while stopped",
+            )
+            .on_hover_text("Seeks snap to the current grid; arrow keys step by one grid line");
+
+            ui.horizontal(|ui| {
+                ui.label("Clip drag threshold:");
+                ui.add(
+                    egui::DragValue::new(&mut config.behavior.clip_drag_threshold_px)
+                        .range(0.0..=32.0)
+                        .suffix(" px"),
+                );
+            })
+            .response
+            .on_hover_text("How far the pointer must move before a clicked clip starts dragging");
+
+            ui.horizontal(|ui| {
+                ui.label("Default clip fade:");
+                ui.add(
+                    egui::DragValue::new(&mut config.behavior.default_clip_fade_ms)
+                        .range(0.0..=50.0)
+                        .speed(0.1)
+                        .suffix(" ms"),
+                );
+            })
+            .response
+            .on_hover_text("Fade applied to both ends of imported and recorded audio clips");
+
+            ui.horizontal(|ui| {
+                use crate::config::IncompatibleClipDrop;
+                ui.label("Dropping clips on the wrong track type:");
+                let drop = &mut config.behavior.incompatible_clip_drop;
+                egui::ComboBox::from_id_salt("preferences_incompatible_drop")
+                    .selected_text(match drop {
+                        IncompatibleClipDrop::Reject => "Cancel the move",
+                        IncompatibleClipDrop::CreateTrack => "Create a new track",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            drop,
+                            IncompatibleClipDrop::CreateTrack,
+                            "Create a new track",
+                        );
+                        ui.selectable_value(drop, IncompatibleClipDrop::Reject, "Cancel the move");
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                use crate::config::ClipOverlapDrop;
+                ui.label("Dropping clips over other clips:");
+                let overlap = &mut config.behavior.clip_overlap_drop;
+                egui::ComboBox::from_id_salt("preferences_overlap_drop")
+                    .selected_text(match overlap {
+                        ClipOverlapDrop::PunchOut => "Punch out",
+                        ClipOverlapDrop::Crossfade => "Crossfade",
+                        ClipOverlapDrop::Layer => "Layer",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(overlap, ClipOverlapDrop::PunchOut, "Punch out");
+                        ui.selectable_value(overlap, ClipOverlapDrop::Crossfade, "Crossfade");
+                        ui.selectable_value(overlap, ClipOverlapDrop::Layer, "Layer");
+                    });
+            })
+            .response
+            .on_hover_text("Hold Alt while dropping to layer instead");
+
+            ui.horizontal(|ui| {
+                use crate::config::NotePreview;
+                ui.label("Piano roll note preview:");
+                let preview = &mut config.behavior.note_preview;
+                egui::ComboBox::from_id_salt("preferences_note_preview")
+                    .selected_text(match preview {
+                        NotePreview::Off => "Off",
+                        NotePreview::OnClick => "On note click",
+                        NotePreview::OnHover => "On row hover",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(preview, NotePreview::OnClick, "On note click");
+                        ui.selectable_value(preview, NotePreview::OnHover, "On row hover");
+                        ui.selectable_value(preview, NotePreview::Off, "Off");
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                use crate::config::SoloMode;
+                ui.label("Solo:");
+                let mode = &mut config.behavior.solo_mode;
+                ui.selectable_value(mode, SoloMode::Additive, "Additive");
+                ui.selectable_value(mode, SoloMode::Exclusive, "Exclusive");
+            })
+            .response
+            .on_hover_text("Ctrl/Cmd-click a solo button for the other behavior");
+
+            ui.separator();
+            ui.heading("MIDI");
+
+            ui.checkbox(&mut config.midi.clock_output_enabled, "Send MIDI clock")
+                .on_hover_text("Sends 24 ppqn clock and start/stop/continue to the chosen port");
+
+            ui.horizontal(|ui| {
+                ui.label("Clock output:");
+                egui::ComboBox::from_id_salt("preferences_midi_clock_port")
+                    .selected_text(config.midi.clock_output_port.as_deref().unwrap_or("None"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut config.midi.clock_output_port, None, "None");
+                        for port in midi_output_ports {
+                            ui.selectable_value(
+                                &mut config.midi.clock_output_port,
+                                Some(port.clone()),
+                                port,
+                            );
+                        }
+                    });
+            });
+
+            ui.separator();
+            ui.heading("Metronome");
+
+            ui.horizontal(|ui| {
+                use crate::config::ClickSubdivision;
+                ui.label("Click:");
+                let subdivision = &mut config.metronome.subdivision;
+                ui.selectable_value(subdivision, ClickSubdivision::Quarter, "Beats");
+                ui.selectable_value(subdivision, ClickSubdivision::Eighth, "Eighths");
+            })
+            .response
+            .on_hover_text(
+                "Follows the project time signature: bar 1 is accented, and 6/8, 9/8 and 12/8 \
+                 count dotted-quarter beats",
+            );
+
+            if click_sample_edit(ui, "Accent sample:", &mut config.metronome.accent_sample) {
+                *browse_click_sample = Some(ClickSample::Accent);
+            }
+            if click_sample_edit(ui, "Beat sample:", &mut config.metronome.click_sample) {
+                *browse_click_sample = Some(ClickSample::Beat);
+            }
 
             ui.separator();
 
@@ -675,6 +1047,54 @@ fn draw_preferences(ui: &mut egui::Ui, config: &mut crate::config::Config) -> bo
     apply_clicked
 }
 
+/// Path to an audio file, empty for the built-in tick. Returns true when
+/// Browse was clicked
+fn click_sample_edit(
+    ui: &mut egui::Ui,
+    label: &str,
+    path: &mut Option<std::path::PathBuf>,
+) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let mut text = path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let edit = egui::TextEdit::singleline(&mut text).hint_text("Built-in");
+        if ui.add(edit).changed() {
+            let trimmed = text.trim();
+            *path = (!trimmed.is_empty()).then(|| trimmed.into());
+        }
+        let browse = ui.button("Browse...").clicked();
+        if path.is_some()
+            && ui
+                .small_button("✕")
+                .on_hover_text("Use the built-in tick")
+                .clicked()
+        {
+            *path = None;
+        }
+        browse
+    })
+    .inner
+}
+
+fn output_pair_combo(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    first_channel: &mut usize,
+    output_channels: usize,
+) {
+    let pair_label = |first: usize| format!("{}/{}", first + 1, first + 2);
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(pair_label(*first_channel))
+        .show_ui(ui, |ui| {
+            for first in (0..output_channels.max(2) - 1).step_by(2) {
+                ui.selectable_value(first_channel, first, pair_label(first));
+            }
+        });
+}
+
 impl Default for MenuBar {
     fn default() -> Self {
         Self::new()
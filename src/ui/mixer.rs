@@ -1,6 +1,14 @@
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 
-use crate::{level_meter::LevelMeter, model::track::TrackType};
+use crate::{
+    level_meter::LevelMeter,
+    metering::draw_meter_bar,
+    model::{automation::AutomationTarget, track::TrackType},
+};
+
+/// Level of a new send, about -6 dB
+const DEFAULT_SEND_LEVEL: f32 = 0.5;
 
 pub struct MixerWindow {
     pub visible: bool,
@@ -10,6 +18,8 @@ pub struct MixerWindow {
     // Mixer state
     channel_strips: HashMap<u64, ChannelStrip>,
     master_strip: MasterStrip,
+    // Plugin latency and compensation delay per track, in samples
+    track_latency: HashMap<u64, (u32, u32)>,
 
     // View options
     show_eq: bool,
@@ -27,10 +37,15 @@ struct ChannelStrip {
     meter: LevelMeter,
     eq_enabled: bool,
     sends: Vec<SendControl>,
+    latency: (u32, u32),
 }
 
 struct MasterStrip {
     meter: LevelMeter,
+    mono_meter: LevelMeter,
+    correlation: f32,
+    // Momentary, short-term, integrated LUFS
+    loudness: (f32, f32, f32),
     limiter_enabled: bool,
 }
 
@@ -48,8 +63,12 @@ impl MixerWindow {
             position: None,
 
             channel_strips: HashMap::new(),
+            track_latency: HashMap::new(),
             master_strip: MasterStrip {
                 meter: LevelMeter::default(),
+                mono_meter: LevelMeter::default(),
+                correlation: 1.0,
+                loudness: (f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
                 limiter_enabled: false,
             },
 
@@ -68,6 +87,27 @@ impl MixerWindow {
         self.visible
     }
 
+    pub fn update_master_level(&mut self, left: f32, right: f32) {
+        self.master_strip
+            .meter
+            .update(&[left.max(right)], 1.0 / 60.0);
+    }
+
+    pub fn update_master_correlation(&mut self, correlation: f32, mono_peak: f32) {
+        // Smooth so the needle is readable at meter rate
+        let strip = &mut self.master_strip;
+        strip.correlation += (correlation - strip.correlation) * 0.2;
+        strip.mono_meter.update(&[mono_peak], 1.0 / 60.0);
+    }
+
+    pub fn update_track_latency(&mut self, latency: HashMap<u64, (u32, u32)>) {
+        self.track_latency = latency;
+    }
+
+    pub fn update_master_loudness(&mut self, momentary: f32, short_term: f32, integrated: f32) {
+        self.master_strip.loudness = (momentary, short_term, integrated);
+    }
+
     pub fn toggle_visibility(&mut self) {
         self.visible = !self.visible;
     }
@@ -172,7 +212,13 @@ impl MixerWindow {
                         meter: LevelMeter::default(),
                         eq_enabled: false,
                         sends: Vec::new(),
+                        latency: (0, 0),
                     });
+                strip.latency = self
+                    .track_latency
+                    .get(track_id)
+                    .copied()
+                    .unwrap_or_default();
 
                 Self::draw_channel_strip_ui(
                     ui,
@@ -195,6 +241,18 @@ impl MixerWindow {
                 self.channel_strips.retain(|id, _| live.contains(id));
             }
 
+            let mut groups: Vec<crate::model::TrackGroup> =
+                app.state.lock_sync().groups.values().cloned().collect();
+            groups.sort_by_key(|g| g.id);
+            for group in &groups {
+                let members = track_data
+                    .iter()
+                    .filter(|(_, t)| t.group_id == Some(group.id))
+                    .count();
+                Self::draw_group_strip(ui, group, members, app, strip_width);
+                ui.separator();
+            }
+
             self.draw_master_strip(ui, app);
         });
     }
@@ -228,8 +286,16 @@ impl MixerWindow {
                 // Channel name
                 ui.group(|ui| {
                     ui.set_min_width(ui.available_width());
-                    ui.label(&track.name);
+                    ui.horizontal(|ui| {
+                        ui.label(&track.name);
+                        if track.frozen {
+                            ui.label("❄").on_hover_text(
+                                "Frozen: playing a render of the clips and plugins",
+                            );
+                        }
+                    });
                 });
+                super::tracks::draw_cpu_bar(ui, app, track_id, ui.available_width());
 
                 // Inserts
                 if show_inserts {
@@ -250,6 +316,18 @@ impl MixerWindow {
                         if ui.small_button("+ Add").clicked() {
                             app.show_plugin_browser_for_track(track_id);
                         }
+
+                        let (latency, compensation) = strip.latency;
+                        if latency > 0 || compensation > 0 {
+                            ui.label(
+                                egui::RichText::new(format!("Lat {latency} / PDC +{compensation}"))
+                                    .small()
+                                    .weak(),
+                            )
+                            .on_hover_text(
+                                "Plugin latency and the delay added to keep this track in time (samples)",
+                            );
+                        }
                     });
                 }
 
@@ -292,77 +370,155 @@ impl MixerWindow {
                     });
                 }
 
-                // Sends
-                if show_sends {
+                // Buses this strip can feed
+                let bus_list: Vec<(u64, String)> = {
+                    let st = app.state.lock_sync();
+                    st.track_order
+                        .iter()
+                        .filter(|&&tid| tid != track_id)
+                        .filter_map(|&tid| {
+                            st.tracks
+                                .get(&tid)
+                                .filter(|t| matches!(t.track_type, TrackType::Bus))
+                                .map(|t| (tid, t.name.clone()))
+                        })
+                        .collect()
+                };
+                let is_bus = matches!(track.track_type, TrackType::Bus);
+
+                // Sends (buses don't feed other buses)
+                if show_sends && !is_bus {
                     ui.group(|ui| {
                         ui.set_min_height(60.0);
                         ui.label("Sends");
 
-                        // Fetch live sends
-                        let (mut sends, _is_midi) = {
-                            let st = app.state.lock_sync();
-                            let t = st.tracks.get(&track_id);
-                            let sends = t.map(|tt| tt.sends.clone()).unwrap_or_default();
-                            (
-                                sends,
-                                t.map(|tt| matches!(tt.track_type, TrackType::Audio))
-                                    .unwrap_or(false),
-                            )
-                        };
-
-                        // List
-                        for (idx, s) in sends.iter_mut().enumerate() {
-                            // Fetch bus list
-                            let bus_list: Vec<(u64, String)> = {
-                                let st = app.state.lock_sync();
-                                st.track_order.iter()
-                                    .filter_map(|&tid| {
-                                        st.tracks.get(&tid).and_then(|t| if matches!(t.track_type, TrackType::Bus) { Some((tid, t.name.clone())) } else { None })
-                                    })
-                                    .collect()
-                            };
-
-                            // Row
+                        for (idx, send) in track.sends.iter().enumerate() {
                             ui.horizontal(|ui| {
-                                // Destination dropdown
-                                let mut chosen = s.destination_track;
-                                let current_name = bus_list.iter().find(|(id,_)| *id == chosen).map(|(_,n)| n.as_str()).unwrap_or("(Select bus)");
+                                // Destination
+                                let mut chosen = send.destination_track;
+                                let current = bus_list
+                                    .iter()
+                                    .find(|(id, _)| *id == chosen)
+                                    .map_or("(Select bus)", |(_, n)| n.as_str());
                                 egui::ComboBox::from_id_salt((track_id, idx, "send_dest"))
-                                    .selected_text(current_name)
+                                    .selected_text(current)
+                                    .width(60.0)
                                     .show_ui(ui, |ui| {
                                         for (bid, name) in &bus_list {
-                                            if ui.selectable_value(&mut chosen, *bid, name).clicked() {
-                                                let _ = app.command_tx.send(crate::messages::AudioCommand::SetSendDestination(track_id, idx, *bid));
-                                            }
+                                            ui.selectable_value(&mut chosen, *bid, name);
                                         }
                                     });
-
-                                // Amount
-                                let mut lvl = s.amount;
-                                if ui.add(egui::Slider::new(&mut lvl, 0.0..=1.0).show_value(false)).changed() {
-                                    let _ = app.command_tx.send(crate::messages::AudioCommand::SetSendAmount(track_id, idx, lvl));
+                                if chosen != send.destination_track {
+                                    let _ = app.command_tx.send(
+                                        crate::messages::AudioCommand::SetSendDestination(
+                                            track_id, idx, chosen,
+                                        ),
+                                    );
                                 }
-                                // Pre
-                                let mut pre = s.pre_fader;
-                                if ui.checkbox(&mut pre, "pre").clicked() {
-                                    let _ = app.command_tx.send(crate::messages::AudioCommand::SetSendPreFader(track_id, idx, pre));
+
+                                // Pre/post fader
+                                if ui
+                                    .selectable_label(
+                                        send.pre_fader,
+                                        if send.pre_fader { "Pre" } else { "Post" },
+                                    )
+                                    .on_hover_text("Tap the signal before or after the fader")
+                                    .clicked()
+                                {
+                                    let _ = app.command_tx.send(
+                                        crate::messages::AudioCommand::SetSendPreFader(
+                                            track_id,
+                                            idx,
+                                            !send.pre_fader,
+                                        ),
+                                    );
                                 }
-                                // Remove
-                                if ui.small_button("✕").clicked() {
-                                    let _ = app.command_tx.send(crate::messages::AudioCommand::RemoveSend(track_id, idx));
+
+                                if ui.small_button("✕").on_hover_text("Remove send").clicked() {
+                                    let _ = app.command_tx.send(
+                                        crate::messages::AudioCommand::RemoveSend(track_id, idx),
+                                    );
                                 }
                             });
-                        }
 
-                        if ui.small_button("+ Add").clicked() {
-                            // destination is not yet used (aux mix). track_id is placeholder for now.
-                            let _ = app.command_tx.send(crate::messages::AudioCommand::AddSend(
-                                track_id, track_id, 0.0,
-                            ));
+                            // Level
+                            let mut level = send.amount;
+                            let knob = ui.horizontal(|ui| {
+                                let resp = ui.add(
+                                    egui::Slider::new(&mut level, 0.0..=1.0).show_value(false),
+                                );
+                                ui.label(format!(
+                                    "{:.1} dB",
+                                    crate::audio_utils::linear_to_db(level)
+                                ));
+                                resp
+                            });
+                            app.undo_gesture(
+                                egui::Id::new(("send_level", track_id, idx)),
+                                &knob.inner,
+                            );
+                            if (level - send.amount).abs() > 0.001 {
+                                let _ = app.command_tx.send(
+                                    crate::messages::AudioCommand::SetSendAmount(
+                                        track_id, idx, level,
+                                    ),
+                                );
+                            }
                         }
+
+                        ui.add_enabled_ui(!bus_list.is_empty(), |ui| {
+                            ui.menu_button("+ Send", |ui| {
+                                for (bid, name) in &bus_list {
+                                    if ui.button(name).clicked() {
+                                        let _ = app.command_tx.send(
+                                            crate::messages::AudioCommand::AddSend(
+                                                track_id,
+                                                *bid,
+                                                DEFAULT_SEND_LEVEL,
+                                            ),
+                                        );
+                                        ui.close();
+                                    }
+                                }
+                            })
+                            .response
+                            .on_disabled_hover_text("Add a bus track to send to");
+                        });
                     });
                 }
 
+                // Output routing; buses that would feed back here aren't offered
+                {
+                    let outputs: Vec<&(u64, String)> = {
+                        let st = app.state.lock_sync();
+                        bus_list
+                            .iter()
+                            .filter(|(bid, _)| {
+                                crate::track_manager::can_route_to_bus(&st.tracks, track_id, *bid)
+                            })
+                            .collect()
+                    };
+                    let current = track
+                        .output_bus
+                        .and_then(|b| bus_list.iter().find(|(id, _)| *id == b))
+                        .map_or("Master", |(_, n)| n.as_str());
+                    let mut chosen = track.output_bus;
+                    egui::ComboBox::from_id_salt((track_id, "output_bus"))
+                        .selected_text(format!("→ {current}"))
+                        .width(ui.available_width())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut chosen, None, "Master");
+                            for (bid, name) in outputs {
+                                ui.selectable_value(&mut chosen, Some(*bid), name);
+                            }
+                        });
+                    if chosen != track.output_bus {
+                        let _ = app.command_tx.send(
+                            crate::messages::AudioCommand::SetTrackOutputBus(track_id, chosen),
+                        );
+                    }
+                }
+
                 // Meter
                 ui.group(|ui| {
                     ui.set_min_height(150.0);
@@ -373,14 +529,17 @@ impl MixerWindow {
                 ui.group(|ui| {
                     // Volume fader
                     let mut volume = track.volume;
-                    ui.vertical_centered(|ui| {
-                        ui.add(
+                    let fader = ui.vertical_centered(|ui| {
+                        let resp = ui.add(
                             egui::Slider::new(&mut volume, 0.0..=1.2)
                                 .vertical()
                                 .show_value(false),
                         );
                         ui.label(format!("{:.1}", crate::audio_utils::linear_to_db(volume)));
+                        resp
                     });
+                    app.touch_automation(track_id, AutomationTarget::TrackVolume, &fader.inner);
+                    app.undo_gesture(egui::Id::new(("track_volume", track_id)), &fader.inner);
                     if (volume - track.volume).abs() > 0.001 {
                         let _ = app
                             .command_tx
@@ -393,16 +552,43 @@ impl MixerWindow {
 
                     // Pan
                     let mut pan = track.pan;
-                    ui.horizontal(|ui| {
+                    let knob = ui.horizontal(|ui| {
                         ui.label("Pan:");
-                        ui.add(egui::Slider::new(&mut pan, -1.0..=1.0).show_value(false));
+                        ui.add(egui::Slider::new(&mut pan, -1.0..=1.0).show_value(false))
                     });
+                    app.touch_automation(track_id, AutomationTarget::TrackPan, &knob.inner);
+                    app.undo_gesture(egui::Id::new(("track_pan", track_id)), &knob.inner);
                     if (pan - track.pan).abs() > 0.001 {
                         let _ = app
                             .command_tx
                             .send(crate::messages::AudioCommand::SetTrackPan(track_id, pan));
                     }
                     ui.label(crate::audio_utils::format_pan(pan));
+
+                    // Polarity and mono check
+                    ui.horizontal(|ui| {
+                        if ui
+                            .selectable_label(track.phase_inverted, "Ø")
+                            .on_hover_text("Invert polarity")
+                            .clicked()
+                        {
+                            let _ = app.command_tx.send(
+                                crate::messages::AudioCommand::SetTrackPhaseInvert(
+                                    track_id,
+                                    !track.phase_inverted,
+                                ),
+                            );
+                        }
+                        if ui
+                            .selectable_label(track.mono, "Mono")
+                            .on_hover_text("Sum to mono")
+                            .clicked()
+                        {
+                            let _ = app.command_tx.send(
+                                crate::messages::AudioCommand::SetTrackMono(track_id, !track.mono),
+                            );
+                        }
+                    });
                 });
 
                 // Buttons (mute/solo/arm)
@@ -421,16 +607,33 @@ impl MixerWindow {
                             ));
                     }
                     // Solo
+                    let solo_resp = ui
+                        .selectable_label(
+                            track.solo,
+                            super::tracks::solo_label(track.solo, track.solo_safe),
+                        )
+                        .on_hover_text(super::tracks::SOLO_HOVER);
+                    if solo_resp.clicked() {
+                        app.solo_track(track_id, !track.solo, ui.input(|i| i.modifiers.command));
+                    }
+                    if solo_resp.secondary_clicked() {
+                        let _ = app.command_tx.send(
+                            crate::messages::AudioCommand::SetTrackSoloSafe(
+                                track_id,
+                                !track.solo_safe,
+                            ),
+                        );
+                    }
+                    // Cue
                     if ui
-                        .selectable_label(track.solo, if track.solo { "S" } else { "s" })
-                        .on_hover_text("Solo")
+                        .selectable_label(track.cue, if track.cue { "C" } else { "c" })
+                        .on_hover_text("Cue (pre-fader to the cue output)")
                         .clicked()
                     {
                         let _ = app
                             .command_tx
-                            .send(crate::messages::AudioCommand::SetTrackSolo(
-                                track_id,
-                                !track.solo,
+                            .send(crate::messages::AudioCommand::SetTrackCue(
+                                track_id, !track.cue,
                             ));
                     }
                     // Record arm
@@ -451,6 +654,96 @@ impl MixerWindow {
         });
     }
 
+    /// VCA strip: scales, mutes and solos every member without touching
+    /// their own faders, so the balance between them holds
+    fn draw_group_strip(
+        ui: &mut egui::Ui,
+        group: &crate::model::TrackGroup,
+        members: usize,
+        app: &mut super::app::YadawApp,
+        strip_width: f32,
+    ) {
+        use crate::messages::AudioCommand;
+
+        ui.allocate_ui(egui::vec2(strip_width, ui.available_height()), |ui| {
+            ui.vertical(|ui| {
+                let (rect, _) = ui.allocate_exact_size(
+                    egui::vec2(ui.available_width(), 4.0),
+                    egui::Sense::hover(),
+                );
+                ui.painter().rect_filled(rect, 0.0, group.color_egui());
+
+                ui.group(|ui| {
+                    ui.set_min_width(ui.available_width());
+                    ui.label(egui::RichText::new(&group.name).strong());
+                    ui.label(
+                        egui::RichText::new(format!("VCA · {members} tracks"))
+                            .small()
+                            .weak(),
+                    );
+                });
+
+                // Fader
+                ui.group(|ui| {
+                    let mut gain = group.vca_gain;
+                    let fader = ui.vertical_centered(|ui| {
+                        let resp = ui.add_enabled(
+                            group.link_volume,
+                            egui::Slider::new(&mut gain, 0.0..=1.2)
+                                .vertical()
+                                .show_value(false),
+                        );
+                        ui.label(format!("{:.1}", crate::audio_utils::linear_to_db(gain)));
+                        resp
+                    });
+                    let fader = fader.inner.on_disabled_hover_text(
+                        "Volume is not linked for this group (Track Groups dialog)",
+                    );
+                    app.undo_gesture(egui::Id::new(("group_vca", group.id)), &fader);
+                    if (gain - group.vca_gain).abs() > 0.001 {
+                        let _ = app
+                            .command_tx
+                            .send(AudioCommand::SetGroupVca(group.id, gain));
+                    }
+                });
+
+                // Buttons (mute/solo)
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            group.link_mute,
+                            egui::Button::selectable(
+                                group.muted,
+                                if group.muted { "M" } else { "m" },
+                            ),
+                        )
+                        .on_hover_text("Mute all members")
+                        .clicked()
+                    {
+                        let _ = app
+                            .command_tx
+                            .send(AudioCommand::SetGroupMute(group.id, !group.muted));
+                    }
+                    if ui
+                        .add_enabled(
+                            group.link_solo,
+                            egui::Button::selectable(
+                                group.solo,
+                                if group.solo { "S" } else { "s" },
+                            ),
+                        )
+                        .on_hover_text("Solo all members")
+                        .clicked()
+                    {
+                        let _ = app
+                            .command_tx
+                            .send(AudioCommand::SetGroupSolo(group.id, !group.solo));
+                    }
+                });
+            });
+        });
+    }
+
     fn draw_master_strip(&mut self, ui: &mut egui::Ui, app: &mut super::app::YadawApp) {
         ui.allocate_ui(
             egui::vec2(self.strip_width * 1.5, ui.available_height()),
@@ -480,6 +773,64 @@ impl MixerWindow {
                     ui.group(|ui| {
                         ui.set_min_height(200.0);
                         self.master_strip.meter.ui(ui, true);
+                        let mode = if app.audio_state.true_peak_metering.load(Ordering::Relaxed) {
+                            "TP"
+                        } else {
+                            "Peak"
+                        };
+                        ui.label(egui::RichText::new(mode).small().weak())
+                            .on_hover_text("Meter mode (Preferences > Audio)");
+                    });
+
+                    // Phase correlation and mono-sum level
+                    ui.group(|ui| {
+                        ui.set_min_width(ui.available_width());
+                        draw_correlation_bar(ui, self.master_strip.correlation);
+
+                        let width = ui.available_width().min(120.0);
+                        let (rect, response) =
+                            ui.allocate_exact_size(egui::vec2(width, 8.0), egui::Sense::hover());
+                        draw_meter_bar(
+                            ui.painter(),
+                            rect,
+                            &self.master_strip.mono_meter.data,
+                            false,
+                        );
+                        response.on_hover_text(format!(
+                            "Mono sum: {:.1} dB",
+                            self.master_strip.mono_meter.data.peak_db()
+                        ));
+                    });
+
+                    // Loudness (BS.1770)
+                    ui.group(|ui| {
+                        ui.set_min_width(ui.available_width());
+                        let (momentary, short_term, integrated) = self.master_strip.loudness;
+                        let fmt = |v: f32| {
+                            if v.is_finite() {
+                                format!("{v:.1}")
+                            } else {
+                                "-∞".to_string()
+                            }
+                        };
+                        egui::Grid::new("master_loudness")
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                ui.label("M");
+                                ui.monospace(fmt(momentary));
+                                ui.end_row();
+                                ui.label("S");
+                                ui.monospace(fmt(short_term));
+                                ui.end_row();
+                                ui.label("I");
+                                ui.monospace(fmt(integrated));
+                                ui.end_row();
+                            })
+                            .response
+                            .on_hover_text(
+                                "Loudness in LUFS: momentary (400 ms), short-term (3 s) and \
+                                 integrated since playback started",
+                            );
                     });
 
                     // Master fader
@@ -502,12 +853,76 @@ impl MixerWindow {
                             app.audio_state.master_volume.store(master_volume);
                         }
                     });
+
+                    // Cue bus
+                    ui.group(|ui| {
+                        ui.label("Cue");
+                        let mut cue_level = app.audio_state.cue_level.load();
+                        let slider =
+                            ui.add(egui::Slider::new(&mut cue_level, 0.0..=1.2).show_value(false));
+                        ui.label(format!("{:.1} dB", 20.0 * cue_level.max(0.0001).log10()));
+                        if (cue_level - app.audio_state.cue_level.load()).abs() > 0.001 {
+                            app.audio_state.cue_level.store(cue_level);
+                            app.config.audio.cue_level = cue_level;
+                        }
+                        // Save once the drag ends rather than every frame of it
+                        if slider.drag_stopped() || (slider.changed() && !slider.dragged()) {
+                            let _ = app.config.save();
+                        }
+
+                        let mut cue_click = app.audio_state.cue_click.load(Ordering::Relaxed);
+                        if ui
+                            .checkbox(&mut cue_click, "Click")
+                            .on_hover_text("Send the metronome to the cue output")
+                            .changed()
+                        {
+                            app.audio_state
+                                .cue_click
+                                .store(cue_click, Ordering::Relaxed);
+                            app.config.audio.cue_click = cue_click;
+                            let _ = app.config.save();
+                        }
+                    });
                 });
             },
         );
     }
 }
 
+/// Horizontal -1..+1 correlation bar; turns red when the mix cancels in mono
+fn draw_correlation_bar(ui: &mut egui::Ui, correlation: f32) {
+    let width = ui.available_width().min(120.0);
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, 12.0), egui::Sense::hover());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, egui::Color32::from_gray(20));
+
+    let center = rect.center().x;
+    let x = center + correlation.clamp(-1.0, 1.0) * rect.width() * 0.5;
+    let color = if correlation < -0.3 {
+        egui::Color32::from_rgb(255, 0, 0)
+    } else if correlation < 0.3 {
+        egui::Color32::from_rgb(255, 200, 0)
+    } else {
+        egui::Color32::from_rgb(0, 200, 0)
+    };
+    painter.rect_filled(
+        egui::Rect::from_x_y_ranges(center.min(x)..=center.max(x), rect.y_range()),
+        0.0,
+        color,
+    );
+    painter.line_segment(
+        [
+            egui::pos2(center, rect.top()),
+            egui::pos2(center, rect.bottom()),
+        ],
+        egui::Stroke::new(1.0, egui::Color32::from_gray(120)),
+    );
+
+    response.on_hover_text(format!(
+        "Phase correlation: {correlation:+.2}\nNear -1 cancels when summed to mono"
+    ));
+}
+
 impl Default for MixerWindow {
     fn default() -> Self {
         Self::new()
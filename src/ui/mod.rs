@@ -6,6 +6,7 @@ mod menu_bar;
 mod mixer;
 mod piano_roll;
 mod piano_roll_view;
+mod spectrum;
 mod theme;
 mod timeline;
 mod tracks;
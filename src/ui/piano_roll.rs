@@ -1,8 +1,10 @@
 use std::vec;
 
 use crate::{
+    config::NotePreview,
     constants::{DEFAULT_NOTE_LENGTH_BEATS, PIANO_KEY_WIDTH},
-    model::{MidiClip, MidiNote},
+    model::{ChordKind, MidiClip, MidiNote, Scale},
+    time_utils::{BarNumbering, TimeSignature},
 };
 use eframe::egui;
 
@@ -14,9 +16,27 @@ pub struct PianoRoll {
     pub selected_note_ids: Vec<u64>,
     pub temp_selected_indices: Vec<usize>,
     pub grid_snap: f32,
+    pub time_signature: TimeSignature,
+    pub bar_numbering: BarNumbering,
+    pub note_preview: NotePreview,
+    /// Project scale; rows outside it are dimmed
+    pub scale: Option<Scale>,
+    /// Move drawn notes onto the nearest scale degree
+    pub snap_to_scale: bool,
+    /// Clicks place a chord built from the scale instead of a single note
+    pub chord_stamp: Option<ChordKind>,
     pub(super) interaction_state: InteractionState,
     hover_note: Option<usize>,
     hover_edge: Option<ResizeEdge>,
+    // Pitch we last asked the engine to preview and haven't stopped yet
+    preview_pitch: Option<u8>,
+    /// Keys from a MIDI controller place notes at `step_cursor`
+    pub step_input: bool,
+    pub step_cursor: f64,
+    // Keys down since the last step; the cursor advances once all are released
+    step_held: Vec<u8>,
+    // (pitch, velocity) from the MIDI input, velocity 0 for a release
+    step_queue: Vec<(u8, u8)>,
 }
 
 impl Default for PianoRoll {
@@ -27,11 +47,22 @@ impl Default for PianoRoll {
             scroll_x: 0.0,
             scroll_y: 60.0 * 20.0,
             grid_snap: 0.25,
+            time_signature: TimeSignature::default(),
+            bar_numbering: BarNumbering::default(),
+            note_preview: NotePreview::default(),
+            scale: None,
+            snap_to_scale: false,
+            chord_stamp: None,
             selected_note_ids: Vec::new(),
             temp_selected_indices: Vec::new(),
             hover_note: None,
             interaction_state: InteractionState::Idle,
             hover_edge: None,
+            preview_pitch: None,
+            step_input: false,
+            step_cursor: 0.0,
+            step_held: Vec::new(),
+            step_queue: Vec::new(),
         }
     }
 }
@@ -51,8 +82,11 @@ pub(super) enum InteractionState {
         edge: ResizeEdge,
         current_delta_beats: f64,
     },
+    /// Marquee over empty grid space; `base_*` is the selection kept when additive
     SelectionBox {
         start_pos: egui::Pos2,
+        base_ids: Vec<u64>,
+        base_indices: Vec<usize>,
     },
 }
 
@@ -84,6 +118,9 @@ impl PianoRoll {
         };
 
         let mut actions = Vec::new();
+        if self.step_input {
+            self.drain_step_input(&mut actions);
+        }
         let available_rect = ui.available_rect_before_wrap();
 
         // Background
@@ -140,6 +177,37 @@ impl PianoRoll {
             }
         }
 
+        // Preview whatever the preference asks for, and stop as soon as the
+        // button is up or the pointer has left so notes can't hang
+        let primary_down = ui.input(|i| i.pointer.primary_down());
+        let pointer_pitch = ui
+            .input(|i| i.pointer.hover_pos())
+            .filter(|pos| available_rect.contains(*pos))
+            .map(|pos| self.pitch_at(pos.y, grid_rect));
+        match self.note_preview {
+            NotePreview::OnHover if !primary_down => {
+                if let Some(pitch) = pointer_pitch {
+                    self.start_preview(&mut actions, pitch);
+                }
+            }
+            NotePreview::OnClick
+                if response.hovered() && ui.input(|i| i.pointer.primary_pressed()) =>
+            {
+                if let Some(note) = self.hover_note.and_then(|idx| pattern.notes.get(idx)) {
+                    self.start_preview(&mut actions, note.pitch);
+                }
+            }
+            _ => {}
+        }
+        let keep_sounding = match self.note_preview {
+            NotePreview::Off => false,
+            NotePreview::OnClick => primary_down,
+            NotePreview::OnHover => primary_down || pointer_pitch.is_some(),
+        };
+        if !keep_sounding {
+            self.end_preview(&mut actions);
+        }
+
         // Begin drag (supports Alt+Drag duplicate)
         if response.drag_started()
             && let Some(pos) = response.interact_pointer_pos()
@@ -185,11 +253,14 @@ impl PianoRoll {
                     is_duplicating: alt_held, // The flag is set here
                 };
             } else {
-                if ui.input(|i| i.modifiers.shift) {
-                    self.interaction_state = InteractionState::SelectionBox { start_pos: pos };
-                } else if !ui.input(|i| i.modifiers.ctrl) {
+                if !ui.input(|i| i.modifiers.shift || i.modifiers.ctrl) {
                     self.clear_selection();
                 }
+                self.interaction_state = InteractionState::SelectionBox {
+                    start_pos: pos,
+                    base_ids: self.selected_note_ids.clone(),
+                    base_indices: self.temp_selected_indices.clone(),
+                };
             }
         }
 
@@ -219,10 +290,15 @@ impl PianoRoll {
                         let beat_delta = snapped_beat as f64 - first_original.start;
                         let pitch_delta = pitch - first_original.pitch as i32;
 
-                        if pitch_delta != *drag_offset_semitones {
-                            let preview_pitch =
+                        if pitch_delta != *drag_offset_semitones
+                            && self.note_preview != NotePreview::Off
+                        {
+                            let pitch =
                                 ((first_original.pitch as i32 + pitch_delta).clamp(0, 127)) as u8;
-                            actions.push(PianoRollAction::PreviewNote(preview_pitch));
+                            if self.preview_pitch != Some(pitch) {
+                                self.preview_pitch = Some(pitch);
+                                actions.push(PianoRollAction::PreviewNote(pitch));
+                            }
                         }
 
                         *drag_offset_beats = beat_delta;
@@ -252,7 +328,11 @@ impl PianoRoll {
                         *current_delta_beats = resize_amount;
                     }
                 }
-                InteractionState::SelectionBox { start_pos } => {
+                InteractionState::SelectionBox {
+                    start_pos,
+                    base_ids,
+                    base_indices,
+                } => {
                     let rect = egui::Rect::from_two_pos(*start_pos, current_pos);
                     ui.painter().rect_filled(
                         rect,
@@ -266,10 +346,9 @@ impl PianoRoll {
                         egui::StrokeKind::Inside,
                     );
 
-                    // Update selection
-                    if !ui.input(|i| i.modifiers.ctrl) {
-                        self.clear_selection();
-                    }
+                    // Rebuild from the pre-drag selection so shrinking the box deselects
+                    self.selected_note_ids = base_ids.clone();
+                    self.temp_selected_indices = base_indices.clone();
                     for (i, note) in pattern.notes.iter().enumerate() {
                         let note_rect = self.note_rect(note, grid_rect);
                         if rect.intersects(note_rect) {
@@ -289,7 +368,7 @@ impl PianoRoll {
 
         // Mouse up
         if response.drag_stopped() {
-            actions.push(PianoRollAction::StopPreview);
+            self.end_preview(&mut actions);
             match &self.interaction_state {
                 InteractionState::DraggingNotes {
                     note_indices,
@@ -311,20 +390,11 @@ impl PianoRoll {
                                 drag_offset_semitones: *drag_offset_semitones,
                             });
                         }
-                    } else {
-                        // Otherwise, we UPDATE the existing notes
-                        for &idx in note_indices {
-                            if let Some(original) = pattern.notes.get(idx) {
-                                let mut updated = *original;
-                                let new_start = (original.start + *drag_offset_beats).max(0.0);
-                                let max_start = (pattern.length_beats - original.duration).max(0.0);
-                                updated.start = new_start.min(max_start);
-                                updated.pitch = ((original.pitch as i32 + *drag_offset_semitones)
-                                    .clamp(0, 127))
-                                    as u8;
-                                actions.push(PianoRollAction::UpdateNote(idx, updated));
-                            }
-                        }
+                    } else if *drag_offset_beats != 0.0 || *drag_offset_semitones != 0 {
+                        actions.push(PianoRollAction::MoveSelection {
+                            dbeat: *drag_offset_beats,
+                            dpitch: *drag_offset_semitones,
+                        });
                     }
                 }
                 InteractionState::ResizingNotes {
@@ -395,13 +465,7 @@ impl PianoRoll {
                         } else {
                             fallback.min(pattern.length_beats - snapped_beat as f64)
                         };
-                        actions.push(PianoRollAction::AddNote(MidiNote {
-                            id: 0,
-                            pitch,
-                            velocity: 100,
-                            start: snapped_beat as f64,
-                            duration: use_dur,
-                        }));
+                        actions.push(self.place_notes(pitch, snapped_beat as f64, use_dur));
                     }
                 }
             }
@@ -459,7 +523,7 @@ impl PianoRoll {
                 // compute pitch with floor (so tap matches visual row)
                 let pitch = {
                     let pf = 127.0 - ((grid_pos.y + self.scroll_y) / self.zoom_y);
-                    pf.floor().clamp(0.0, 127.0) as u8
+                    self.drawn_pitch(pf.floor().clamp(0.0, 127.0) as u8)
                 };
 
                 // New-note duration: selected duration if present, else grid or tiny minimum
@@ -484,13 +548,7 @@ impl PianoRoll {
                     // Select existing
                     self.select_single(&pattern.notes[existing_idx], existing_idx, false);
                 } else {
-                    actions.push(PianoRollAction::AddNote(MidiNote {
-                        id: 0,
-                        pitch,
-                        velocity: 100,
-                        start: snapped_beat,
-                        duration: use_dur,
-                    }));
+                    actions.push(self.place_notes(pitch, snapped_beat, use_dur));
                 }
             }
         }
@@ -620,6 +678,17 @@ impl PianoRoll {
             }
         }
 
+        if self.step_input {
+            let x = grid_rect.min.x + (self.step_cursor as f32 * self.zoom_x - self.scroll_x);
+            if grid_rect.x_range().contains(x) {
+                ui.painter().vline(
+                    x,
+                    grid_rect.y_range(),
+                    egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 170, 60)),
+                );
+            }
+        }
+
         // Context menu delete (ID-aware)
         if response.secondary_clicked()
             && let Some(pos) = response.interact_pointer_pos()
@@ -751,16 +820,34 @@ impl PianoRoll {
     }
 
     fn draw_grid(&self, painter: &egui::Painter, rect: egui::Rect, pattern_length: f64) {
-        // Vertical lines (beats)
-        let visible_beats = (rect.width() / self.zoom_x) as i32 + 2;
-        let start_beat = (self.scroll_x / self.zoom_x) as i32;
+        if let Some(scale) = self.scale {
+            for pitch in (0..128u8).filter(|&p| !scale.contains(p)) {
+                let row = egui::Rect::from_x_y_ranges(
+                    rect.x_range(),
+                    self.pitch_to_y(pitch as f32 + 1.0, rect)..=self.pitch_to_y(pitch as f32, rect),
+                )
+                .intersect(rect);
+                if row.is_positive() {
+                    painter.rect_filled(row, 0.0, egui::Color32::from_black_alpha(70));
+                }
+            }
+        }
+
+        // Vertical lines (one per count of the meter)
+        let sig = self.time_signature;
+        let count_len = sig.beat_len();
+        let count_px = count_len as f32 * self.zoom_x;
+        let visible_counts = (rect.width() / count_px) as i64 + 2;
+        let start_count = (self.scroll_x / count_px) as i64;
 
-        for i in 0..visible_beats {
-            let beat = start_beat + i;
+        for i in 0..visible_counts {
+            let count = start_count + i;
+            let beat = count as f64 * count_len;
             let x = rect.min.x + (beat as f32 * self.zoom_x - self.scroll_x);
 
             if x >= rect.min.x && x <= rect.max.x {
-                let color = if beat % 4 == 0 {
+                let is_bar = count % sig.numerator.max(1) as i64 == 0;
+                let color = if is_bar {
                     egui::Color32::from_gray(60)
                 } else {
                     egui::Color32::from_gray(40)
@@ -770,6 +857,17 @@ impl PianoRoll {
                     [egui::pos2(x, rect.min.y), egui::pos2(x, rect.max.y)],
                     egui::Stroke::new(1.0, color),
                 );
+
+                if is_bar {
+                    let (bar, _, _) = self.bar_numbering.position(beat, sig);
+                    painter.text(
+                        egui::pos2(x + 3.0, rect.min.y + 2.0),
+                        egui::Align2::LEFT_TOP,
+                        bar.to_string(),
+                        egui::FontId::monospace(10.0),
+                        egui::Color32::from_gray(140),
+                    );
+                }
             }
         }
 
@@ -800,6 +898,89 @@ impl PianoRoll {
         }
     }
 
+    fn drawn_pitch(&self, pitch: u8) -> u8 {
+        match self.scale {
+            Some(scale) if self.snap_to_scale => scale.snap(pitch),
+            _ => pitch,
+        }
+    }
+
+    /// Action for a click-placed note, or a chord on that degree with the chord tool.
+    /// Chords follow the project scale, C major when none is set.
+    fn place_notes(&self, pitch: u8, start: f64, duration: f64) -> PianoRollAction {
+        let note = |pitch| MidiNote {
+            id: 0,
+            pitch,
+            velocity: 100,
+            start,
+            duration,
+        };
+        match self.chord_stamp {
+            Some(kind) => {
+                let scale = self.scale.unwrap_or_default();
+                PianoRollAction::AddNotes(scale.chord(pitch, kind).into_iter().map(note).collect())
+            }
+            None => PianoRollAction::AddNote(note(self.drawn_pitch(pitch))),
+        }
+    }
+
+    fn step_len(&self) -> f64 {
+        if self.grid_snap > 0.0 {
+            self.grid_snap as f64
+        } else {
+            DEFAULT_NOTE_LENGTH_BEATS
+        }
+    }
+
+    /// Queues a key from the MIDI input for step entry; ignored while it's off
+    pub fn queue_step_note(&mut self, pitch: u8, velocity: u8) {
+        if self.step_input {
+            self.step_queue.push((pitch, velocity));
+        }
+    }
+
+    /// True while keys of the current step are down or waiting to be placed
+    pub fn step_chord_open(&self) -> bool {
+        !self.step_held.is_empty() || self.step_queue.iter().any(|&(_, vel)| vel > 0)
+    }
+
+    /// Keys pressed together land on the same step, which is left once they're all up
+    fn drain_step_input(&mut self, actions: &mut Vec<PianoRollAction>) {
+        let step = self.step_len();
+        for (pitch, velocity) in std::mem::take(&mut self.step_queue) {
+            if velocity > 0 {
+                if !self.step_held.contains(&pitch) {
+                    self.step_held.push(pitch);
+                    actions.push(PianoRollAction::AddNote(MidiNote {
+                        id: 0,
+                        pitch,
+                        velocity,
+                        start: self.step_cursor,
+                        duration: step,
+                    }));
+                }
+            } else if let Some(i) = self.step_held.iter().position(|&p| p == pitch) {
+                self.step_held.swap_remove(i);
+                if self.step_held.is_empty() {
+                    self.step_cursor += step;
+                }
+            }
+        }
+    }
+
+    /// Moves the step cursor back one step; returns the notes starting there
+    pub fn step_back(&mut self, notes: &[MidiNote]) -> Vec<usize> {
+        let step = self.step_len();
+        self.step_held.clear();
+        self.step_cursor = (self.step_cursor - step).max(0.0);
+        notes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| (n.start - self.step_cursor).abs() < step * 0.5)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     fn note_rect(&self, note: &MidiNote, grid_rect: egui::Rect) -> egui::Rect {
         let x = grid_rect.min.x + (note.start as f32 * self.zoom_x - self.scroll_x);
         let y = self.pitch_to_y(note.pitch as f32 + 0.5, grid_rect);
@@ -814,6 +995,29 @@ impl PianoRoll {
         rect.min.y + (127.0 - pitch) * self.zoom_y - self.scroll_y
     }
 
+    fn pitch_at(&self, y: f32, rect: egui::Rect) -> u8 {
+        let pf = 127.0 - ((y - rect.min.y + self.scroll_y) / self.zoom_y);
+        pf.floor().clamp(0.0, 127.0) as u8
+    }
+
+    fn start_preview(&mut self, actions: &mut Vec<PianoRollAction>, pitch: u8) {
+        if self.preview_pitch != Some(pitch) {
+            self.preview_pitch = Some(pitch);
+            actions.push(PianoRollAction::PreviewNote(pitch));
+        }
+    }
+
+    fn end_preview(&mut self, actions: &mut Vec<PianoRollAction>) {
+        if self.preview_pitch.take().is_some() {
+            actions.push(PianoRollAction::StopPreview);
+        }
+    }
+
+    /// Forgets the sounding preview; true if the caller should stop it.
+    pub fn take_preview(&mut self) -> bool {
+        self.preview_pitch.take().is_some()
+    }
+
     pub fn selected_indices(&self, pattern: &MidiClip) -> Vec<usize> {
         use std::collections::HashMap;
         // Map id -> index for fast lookup
@@ -884,6 +1088,13 @@ pub enum PianoRollAction {
     RemoveNote(usize),
     RemoveNotes(Vec<usize>),
     UpdateNote(usize, MidiNote),
+    /// Shift every selected note by this many semitones
+    TransposeSelection(i8),
+    /// Shift every selected note in time and pitch as one edit
+    MoveSelection {
+        dbeat: f64,
+        dpitch: i32,
+    },
     DuplicateNotesAndSelect {
         original_notes: Vec<MidiNote>,
         drag_offset_beats: f64,
@@ -892,3 +1103,94 @@ pub enum PianoRollAction {
     PreviewNote(u8),
     StopPreview,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step_roll() -> PianoRoll {
+        PianoRoll {
+            step_input: true,
+            grid_snap: 0.5,
+            step_cursor: 2.0,
+            ..Default::default()
+        }
+    }
+
+    /// (pitch, velocity, start, duration) of the notes added
+    fn added(actions: &[PianoRollAction]) -> Vec<(u8, u8, f64, f64)> {
+        actions
+            .iter()
+            .map(|a| match a {
+                PianoRollAction::AddNote(n) => (n.pitch, n.velocity, n.start, n.duration),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn step_input_places_a_note_and_advances_on_release() {
+        let mut roll = step_roll();
+        let mut actions = Vec::new();
+        roll.queue_step_note(60, 100);
+        roll.drain_step_input(&mut actions);
+        assert_eq!(added(&actions), vec![(60, 100, 2.0, 0.5)]);
+        assert_eq!(roll.step_cursor, 2.0);
+        assert!(roll.step_chord_open());
+
+        roll.queue_step_note(60, 0);
+        roll.drain_step_input(&mut actions);
+        assert_eq!(roll.step_cursor, 2.5);
+        assert!(!roll.step_chord_open());
+    }
+
+    #[test]
+    fn keys_held_together_share_a_step() {
+        let mut roll = step_roll();
+        let mut actions = Vec::new();
+        roll.queue_step_note(60, 100);
+        roll.queue_step_note(64, 90);
+        roll.drain_step_input(&mut actions);
+        roll.queue_step_note(60, 0);
+        // A key added while the chord is still held joins it
+        roll.queue_step_note(67, 80);
+        roll.drain_step_input(&mut actions);
+        assert_eq!(roll.step_cursor, 2.0);
+
+        roll.queue_step_note(64, 0);
+        roll.queue_step_note(67, 0);
+        roll.drain_step_input(&mut actions);
+        assert_eq!(
+            added(&actions),
+            vec![(60, 100, 2.0, 0.5), (64, 90, 2.0, 0.5), (67, 80, 2.0, 0.5)]
+        );
+        assert_eq!(roll.step_cursor, 2.5);
+    }
+
+    #[test]
+    fn step_input_ignores_repeats_stray_releases_and_off_mode() {
+        let mut roll = step_roll();
+        let mut actions = Vec::new();
+        roll.queue_step_note(62, 0);
+        roll.queue_step_note(60, 100);
+        roll.queue_step_note(60, 100);
+        roll.drain_step_input(&mut actions);
+        assert_eq!(added(&actions), vec![(60, 100, 2.0, 0.5)]);
+        assert_eq!(roll.step_cursor, 2.0);
+
+        roll.step_input = false;
+        roll.queue_step_note(72, 100);
+        assert!(roll.step_queue.is_empty());
+    }
+
+    #[test]
+    fn step_length_falls_back_without_a_grid() {
+        let mut roll = step_roll();
+        roll.grid_snap = 0.0;
+        let mut actions = Vec::new();
+        roll.queue_step_note(60, 100);
+        roll.queue_step_note(60, 0);
+        roll.drain_step_input(&mut actions);
+        assert_eq!(roll.step_cursor, 2.0 + DEFAULT_NOTE_LENGTH_BEATS);
+    }
+}
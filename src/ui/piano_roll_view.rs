@@ -9,7 +9,8 @@ use flume::Sender;
 use crate::audio_state::AudioState;
 use crate::constants::DEFAULT_MIDI_CLIP_LEN;
 use crate::messages::AudioCommand;
-use crate::model::MidiNote;
+use crate::model::scale::NOTE_NAMES;
+use crate::model::{ChordKind, MidiNote, Scale, ScaleMode};
 use crate::project::AppState;
 use crate::ui::piano_roll::{InteractionState, PianoRoll, PianoRollAction};
 
@@ -23,6 +24,9 @@ pub struct PianoRollView {
 
     // Tool modes
     tool_mode: ToolMode,
+    /// Factor the "Scale Lengths" note tool multiplies durations by
+    length_scale: f64,
+    chord_kind: ChordKind,
 
     // MIDI input
     midi_input_enabled: bool,
@@ -37,6 +41,8 @@ pub struct PianoRollView {
 pub enum ToolMode {
     Select,
     Draw,
+    /// Clicks place a chord built from the project scale
+    Chord,
 }
 
 impl PianoRollView {
@@ -46,6 +52,8 @@ impl PianoRollView {
             show_velocity_lane: false,
             velocity_lane_height: 100.0,
             tool_mode: ToolMode::Select,
+            length_scale: 0.5,
+            chord_kind: ChordKind::default(),
             midi_input_enabled: false,
             midi_octave_offset: 0,
             selected_clip: None,
@@ -169,6 +177,9 @@ impl PianoRollView {
 
     fn draw_piano_roll(&mut self, ui: &mut egui::Ui, app: &mut super::app::YadawApp) {
         if self.selected_clip.is_none() {
+            if self.piano_roll.take_preview() {
+                let _ = app.command_tx.send(AudioCommand::StopPreviewNote);
+            }
             ui.centered_and_justified(|ui| {
                 ui.label("Select or create a MIDI clip to edit");
             });
@@ -213,6 +224,20 @@ impl PianoRollView {
             }
         };
 
+        if ui.rect_contains_pointer(ui.max_rect()) {
+            app.active_edit_target = super::app::ActiveEditTarget::Notes;
+        }
+
+        self.piano_roll.bar_numbering = app.config.bar_numbering();
+        self.piano_roll.note_preview = app.config.behavior.note_preview;
+        {
+            let state = app.state.lock_sync();
+            self.piano_roll.time_signature = state.time_signature;
+            self.piano_roll.scale = state.scale;
+        }
+        self.piano_roll.chord_stamp =
+            (self.tool_mode == ToolMode::Chord).then_some(self.chord_kind);
+
         // Draw and interact
         let actions = self.piano_roll.ui(
             ui,
@@ -222,7 +247,7 @@ impl PianoRollView {
                 color: clip_color,
                 ..Default::default()
             },
-            self.tool_mode == super::piano_roll_view::ToolMode::Draw,
+            matches!(self.tool_mode, ToolMode::Draw | ToolMode::Chord),
         );
 
         // Separate preview and mutations
@@ -288,6 +313,13 @@ impl PianoRollView {
                         });
                     }
                 }
+                action @ (PianoRollAction::TransposeSelection(_)
+                | PianoRollAction::MoveSelection { .. }) => {
+                    if !self.piano_roll.selected_note_ids.is_empty() {
+                        app.push_undo();
+                        self.transform_selection(action, &app.command_tx);
+                    }
+                }
                 PianoRollAction::DuplicateNotesAndSelect {
                     original_notes: _,
                     drag_offset_beats,
@@ -484,6 +516,26 @@ impl PianoRollView {
                         {
                             self.tool_mode = ToolMode::Draw;
                         }
+                        if ui
+                            .selectable_label(self.tool_mode == ToolMode::Chord, "♫")
+                            .on_hover_text("Chord Tool: click to place a chord from the scale")
+                            .clicked()
+                        {
+                            self.tool_mode = ToolMode::Chord;
+                        }
+                        if self.tool_mode == ToolMode::Chord {
+                            egui::ComboBox::from_id_salt("piano_roll_chord_kind")
+                                .selected_text(self.chord_kind.name())
+                                .show_ui(ui, |ui| {
+                                    for kind in [ChordKind::Triad, ChordKind::Seventh] {
+                                        ui.selectable_value(
+                                            &mut self.chord_kind,
+                                            kind,
+                                            kind.name(),
+                                        );
+                                    }
+                                });
+                        }
                     });
 
                     ui.separator();
@@ -502,6 +554,83 @@ impl PianoRollView {
                             ui.selectable_value(&mut self.piano_roll.grid_snap, 0.0, "Off");
                         });
 
+                    if ui
+                        .toggle_value(&mut self.piano_roll.step_input, "Step Input")
+                        .on_hover_text(
+                            "Keys from the MIDI input place notes at the cursor and advance by the snap; Backspace steps back",
+                        )
+                        .changed()
+                        && self.piano_roll.step_input
+                    {
+                        self.piano_roll.step_cursor = 0.0;
+                    }
+
+                    ui.separator();
+
+                    // Scale highlight, stored with the project
+                    ui.label("Scale:");
+                    let current_scale = app.state.lock_sync().scale;
+                    let mut root = current_scale.map_or(0, |s| s.root);
+                    let mut mode = current_scale.map(|s| s.mode);
+                    ui.add_enabled_ui(mode.is_some(), |ui| {
+                        egui::ComboBox::from_id_salt("piano_roll_scale_root")
+                            .width(40.0)
+                            .selected_text(NOTE_NAMES[root as usize % 12])
+                            .show_ui(ui, |ui| {
+                                for (i, name) in NOTE_NAMES.iter().enumerate() {
+                                    ui.selectable_value(&mut root, i as u8, *name);
+                                }
+                            });
+                    });
+                    egui::ComboBox::from_id_salt("piano_roll_scale_mode")
+                        .selected_text(mode.map_or("Off", |m| m.name()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut mode, None, "Off");
+                            for m in ScaleMode::ALL {
+                                ui.selectable_value(&mut mode, Some(m), m.name());
+                            }
+                        });
+                    let new_scale = mode.map(|m| Scale::new(root, m));
+                    if new_scale != current_scale {
+                        app.push_undo();
+                        app.state.lock_sync().scale = new_scale;
+                        app.project_manager.mark_dirty();
+                    }
+                    ui.add_enabled(
+                        new_scale.is_some(),
+                        egui::Checkbox::new(&mut self.piano_roll.snap_to_scale, "Snap to scale"),
+                    )
+                    .on_hover_text("Move drawn notes to the nearest scale degree");
+
+                    ui.separator();
+
+                    ui.menu_button("Notes", |ui| {
+                        if ui.button("Humanize...").clicked() {
+                            app.dialogs.show_humanize_dialog();
+                            ui.close();
+                        }
+                        if ui
+                            .button("Legato")
+                            .on_hover_text("Extend selected notes to the next note")
+                            .clicked()
+                        {
+                            app.legato_selected_notes();
+                            ui.close();
+                        }
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut self.length_scale)
+                                    .range(0.05..=8.0)
+                                    .speed(0.01)
+                                    .prefix("×"),
+                            );
+                            if ui.button("Scale Lengths").clicked() {
+                                app.scale_selected_note_lengths(self.length_scale);
+                                ui.close();
+                            }
+                        });
+                    });
+
                     ui.separator();
 
                     // View options
@@ -854,6 +983,73 @@ impl PianoRollView {
         true
     }
 
+    /// Applies a `TransposeSelection`/`MoveSelection` to every selected note in one command
+    pub fn transform_selection(
+        &mut self,
+        action: PianoRollAction,
+        command_tx: &Sender<AudioCommand>,
+    ) -> bool {
+        let Some(clip_id) = self.selected_clip else {
+            return false;
+        };
+        let note_ids = self.piano_roll.selected_note_ids.clone();
+        if note_ids.is_empty() {
+            return false;
+        }
+
+        let command = match action {
+            PianoRollAction::TransposeSelection(semitones) => {
+                AudioCommand::TransposeSelectedNotes {
+                    clip_id,
+                    note_ids,
+                    semitones: semitones as i32,
+                }
+            }
+            PianoRollAction::MoveSelection { dbeat, dpitch } => AudioCommand::MoveSelectedNotes {
+                clip_id,
+                note_ids,
+                delta_beats: dbeat,
+                delta_semitones: dpitch,
+            },
+            _ => return false,
+        };
+        let _ = command_tx.send(command);
+        true
+    }
+
+    /// Step input's backspace: moves the cursor back a step and clears the notes there
+    pub fn step_back(&mut self, state: &Arc<Mutex<AppState>>, command_tx: &Sender<AudioCommand>) {
+        let Some(clip_id) = self.selected_clip else {
+            return;
+        };
+        let notes = {
+            let st = state.lock_sync();
+            match st.find_clip(clip_id) {
+                Some((track, crate::project::ClipLocation::Midi(idx))) => track
+                    .midi_clips
+                    .get(idx)
+                    .map(
+                        |clip| match clip.pattern_id.and_then(|pid| st.patterns.get(&pid)) {
+                            Some(p) => p.notes.clone(),
+                            None => clip.notes.clone(),
+                        },
+                    )
+                    .unwrap_or_default(),
+                _ => return,
+            }
+        };
+
+        let note_ids: Vec<u64> = self
+            .piano_roll
+            .step_back(&notes)
+            .into_iter()
+            .filter_map(|i| notes.get(i).map(|n| n.id))
+            .collect();
+        if !note_ids.is_empty() {
+            let _ = command_tx.send(AudioCommand::RemoveNotesById { clip_id, note_ids });
+        }
+    }
+
     pub fn select_all_notes(&mut self, state: &Arc<Mutex<AppState>>, selected_track: u64) {
         let clip_id = match self.selected_clip {
             Some(id) => id,
@@ -0,0 +1,189 @@
+use crate::spectrum::{FFT_SIZE, SPECTRUM_OFF, track_to_source};
+
+const MIN_FREQ: f32 = 20.0;
+const MAX_FREQ: f32 = 20_000.0;
+const FLOOR_DB: f32 = -90.0;
+const GRID_FREQS: [f32; 9] = [
+    50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0, 10000.0, 20000.0,
+];
+
+pub struct SpectrumWindow {
+    pub visible: bool,
+    /// Track being analyzed, None for master
+    source: Option<u64>,
+    /// Smoothed level of each FFT bin, in dB
+    levels: Vec<f32>,
+}
+
+impl Default for SpectrumWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpectrumWindow {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            source: None,
+            levels: Vec::new(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Value for `AudioState::spectrum_source`; the analyzer idles while hidden
+    pub fn source_id(&self) -> u64 {
+        if self.visible {
+            track_to_source(self.source)
+        } else {
+            SPECTRUM_OFF
+        }
+    }
+
+    pub fn update_spectrum(&mut self, track_id: Option<u64>, bins: &[f32]) {
+        if track_id != self.source {
+            return;
+        }
+        self.levels.resize(bins.len(), FLOOR_DB);
+        for (level, &mag) in self.levels.iter_mut().zip(bins) {
+            let db = (20.0 * mag.max(1e-9).log10()).max(FLOOR_DB);
+            // Rise at once, fall slowly so transients stay readable
+            *level = if db > *level {
+                db
+            } else {
+                *level + (db - *level) * 0.2
+            };
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, app: &super::app::YadawApp) {
+        let mut visible = self.visible;
+        egui::Window::new("Spectrum")
+            .open(&mut visible)
+            .default_size(egui::vec2(520.0, 260.0))
+            .resizable(true)
+            .show(ctx, |ui| {
+                self.draw_source_picker(ui, app);
+                ui.separator();
+                self.draw_graph(ui, app.audio_state.sample_rate.load());
+            });
+        self.visible = visible;
+    }
+
+    fn draw_source_picker(&mut self, ui: &mut egui::Ui, app: &super::app::YadawApp) {
+        let tracks: Vec<(u64, String)> = {
+            let state = app.state.lock_sync();
+            state
+                .track_order
+                .iter()
+                .filter_map(|id| state.tracks.get(id).map(|t| (*id, t.name.clone())))
+                .collect()
+        };
+        if let Some(id) = self.source
+            && !tracks.iter().any(|(t, _)| *t == id)
+        {
+            self.source = None;
+        }
+
+        let selected = self
+            .source
+            .and_then(|id| tracks.iter().find(|(t, _)| *t == id))
+            .map_or("Master", |(_, name)| name.as_str());
+        let before = self.source;
+        ui.horizontal(|ui| {
+            ui.label("Source:");
+            egui::ComboBox::from_id_salt("spectrum_source")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.source, None, "Master");
+                    for (id, name) in &tracks {
+                        ui.selectable_value(&mut self.source, Some(*id), name);
+                    }
+                });
+        });
+        if self.source != before {
+            self.levels.clear();
+        }
+    }
+
+    fn draw_graph(&self, ui: &mut egui::Ui, sample_rate: f32) {
+        let size = ui.available_size().max(egui::vec2(200.0, 120.0));
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, egui::Color32::from_gray(18));
+
+        let log_span = (MAX_FREQ / MIN_FREQ).ln();
+        let x_for_freq = |f: f32| rect.left() + rect.width() * (f / MIN_FREQ).ln() / log_span;
+        let freq_for_x = |x: f32| MIN_FREQ * ((x - rect.left()) / rect.width() * log_span).exp();
+        let y_for_db = |db: f32| rect.top() + rect.height() * (db / FLOOR_DB).clamp(0.0, 1.0);
+
+        let grid = egui::Stroke::new(1.0, egui::Color32::from_gray(45));
+        let label_color = egui::Color32::from_gray(120);
+        let font = egui::FontId::proportional(10.0);
+        for f in GRID_FREQS {
+            let x = x_for_freq(f);
+            painter.vline(x, rect.y_range(), grid);
+            let text = if f >= 1000.0 {
+                format!("{}k", f / 1000.0)
+            } else {
+                format!("{f}")
+            };
+            painter.text(
+                egui::pos2(x + 2.0, rect.bottom() - 2.0),
+                egui::Align2::LEFT_BOTTOM,
+                text,
+                font.clone(),
+                label_color,
+            );
+        }
+        for db in (-80..0).step_by(20) {
+            let y = y_for_db(db as f32);
+            painter.hline(rect.x_range(), y, grid);
+            painter.text(
+                egui::pos2(rect.left() + 2.0, y - 1.0),
+                egui::Align2::LEFT_BOTTOM,
+                format!("{db} dB"),
+                font.clone(),
+                label_color,
+            );
+        }
+
+        if !self.levels.is_empty() && sample_rate > 0.0 {
+            let bin_hz = sample_rate / FFT_SIZE as f32;
+            let last = self.levels.len() - 1;
+            let bin_at = |x: f32| ((freq_for_x(x) / bin_hz) as usize).min(last);
+
+            // Highs pack many bins into a pixel; keep the loudest
+            let points: Vec<egui::Pos2> = (0..rect.width() as usize)
+                .map(|px| {
+                    let x = rect.left() + px as f32;
+                    let (b0, b1) = (bin_at(x), bin_at(x + 1.0));
+                    let db = self.levels[b0..=b1.max(b0)]
+                        .iter()
+                        .copied()
+                        .fold(FLOOR_DB, f32::max);
+                    egui::pos2(x, y_for_db(db))
+                })
+                .collect();
+            painter.add(egui::Shape::line(
+                points,
+                egui::Stroke::new(1.5, ui.visuals().selection.bg_fill),
+            ));
+        }
+
+        if let Some(pos) = response.hover_pos() {
+            let freq = freq_for_x(pos.x);
+            let db = FLOOR_DB * (pos.y - rect.top()) / rect.height();
+            painter.text(
+                rect.right_top() + egui::vec2(-4.0, 4.0),
+                egui::Align2::RIGHT_TOP,
+                format!("{freq:.0} Hz  {db:.1} dB"),
+                font,
+                egui::Color32::from_gray(200),
+            );
+        }
+    }
+}
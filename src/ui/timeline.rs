@@ -1,10 +1,17 @@
+use std::collections::HashSet;
 use std::sync::atomic::Ordering;
 
+use super::app::TrackRow;
 use crate::constants::{DEFAULT_MIDI_CLIP_LEN, DEFAULT_MIN_PROJECT_BEATS};
+use crate::input::gestures::GestureAction;
 use crate::messages::AudioCommand;
 use crate::model::track::TrackType;
-use crate::model::{AudioClip, AutomationTarget, MidiClip, MidiNote, Track};
+use crate::model::{
+    AudioClip, AutomationTarget, ClipLoopMode, CrossfadeCurve, FadeCurve, MidiClip, MidiNote,
+    Track, TrackGroup,
+};
 use crate::project::ClipLocation;
+use crate::time_utils::{BarNumbering, TimeSignature};
 use crate::ui::automation_lane::{AutomationAction, AutomationLaneWidget};
 use crate::ui::waveform::draw_waveform;
 use egui::scroll_area::ScrollSource;
@@ -17,17 +24,26 @@ pub struct TimelineView {
     pub grid_snap: f32,
     pub show_automation: bool,
     pub auto_scroll: bool,
+    /// Overlay the RMS level on audio waveforms
+    pub show_rms: bool,
 
     snap_enabled: bool,
     snap_to_grid: bool,
     snap_to_clips: bool,
     snap_to_loop: bool,
+    snap_to_markers: bool,
     snap_px_threshold: f32, // in pixels, default ~10
 
     // marquee
     selection_box: Option<(egui::Pos2, egui::Pos2)>,
 
     auto_crossfade_on_overlap: bool,
+    tool: TimelineTool,
+    ripple_mode: RippleMode,
+    /// Markers after a rippled edit shift with the clips
+    ripple_markers: bool,
+    /// Crossfade center minus the pointer beat when its drag began
+    crossfade_grab: f64,
 
     snap_preview_beat: Option<f64>,
 
@@ -38,6 +54,10 @@ pub struct TimelineView {
     automation_widgets: Vec<AutomationLaneWidget>,
     pub show_clip_menu: bool,
     clip_menu_pos: egui::Pos2,
+    velocity_target: u8,
+    quantize_preview: Option<QuantizePreview>,
+    groove_choice: Option<u64>,
+    groove_strength: f32,
 
     track_height: f32,
     min_track_height: f32,
@@ -47,9 +67,124 @@ pub struct TimelineView {
     pending_clip_undo: bool,
 
     automation_hit_regions: Vec<egui::Rect>,
+    automation_selection: Option<AutomationSelection>,
+    /// Lane last under the pointer, where automation pastes land
+    automation_focus: Option<(u64, usize)>,
     last_track_blocks: Vec<(u64, egui::Rect)>,
 
     drag_target_track: Option<u64>,
+    /// Tracks showing their loop-recording takes under the clip lane
+    expanded_takes: HashSet<u64>,
+}
+
+/// Quantize settings being dialled in from the clip menu, heard and drawn
+/// before they are applied
+#[derive(Clone, Copy, Debug)]
+struct QuantizePreview {
+    clip_id: u64,
+    grid: f32,
+    strength: f32,
+    swing: f32,
+}
+
+impl QuantizePreview {
+    const GRIDS: [(&'static str, f32); 6] = [
+        ("1/4", 1.0),
+        ("1/8", 0.5),
+        ("1/8T", 1.0 / 3.0),
+        ("1/16", 0.25),
+        ("1/16T", 1.0 / 6.0),
+        ("1/32", 0.125),
+    ];
+
+    fn from_clip(clip: &MidiClip) -> Self {
+        Self {
+            clip_id: clip.id,
+            grid: if clip.quantize_grid > 0.0 {
+                clip.quantize_grid
+            } else {
+                0.25
+            },
+            strength: if clip.quantize_enabled {
+                clip.quantize_strength
+            } else {
+                1.0
+            },
+            swing: clip.swing,
+        }
+    }
+
+    fn quantize(&self, beat: f64) -> f64 {
+        crate::audio::quantize_to_grid(
+            beat,
+            self.grid as f64,
+            self.strength as f64,
+            self.swing as f64,
+        )
+    }
+}
+
+/// What a click on the clip area does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimelineTool {
+    #[default]
+    Pointer,
+    /// Splits the clip under the cursor; Alt splits every track
+    Razor,
+}
+
+/// Whether deleting or moving clips shifts the material after them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RippleMode {
+    #[default]
+    Off,
+    /// Only the edited clip's track closes up
+    Track,
+    /// Every track closes up together
+    All,
+}
+
+impl RippleMode {
+    pub const ALL: [RippleMode; 3] = [Self::Off, Self::Track, Self::All];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Track => "Track",
+            Self::All => "All Tracks",
+        }
+    }
+}
+
+/// Selected points, all within a single automation lane
+#[derive(Clone, Debug)]
+pub struct AutomationSelection {
+    pub track_id: u64,
+    pub lane_idx: usize,
+    pub beats: Vec<f64>,
+    /// Beat range swept with Alt-drag, when the selection came from one
+    pub range: Option<(f64, f64)>,
+}
+
+/// A row of the timeline with the model data it draws
+enum TimelineRow {
+    Folder {
+        group: TrackGroup,
+        members: Vec<Track>,
+    },
+    Track {
+        id: u64,
+        track: Track,
+        nested: bool,
+    },
+}
+
+/// Color of the folder a nested track sits in
+fn folder_color(rows: &[TimelineRow], group_id: u64) -> Option<egui::Color32> {
+    rows.iter().find_map(|row| match row {
+        TimelineRow::Folder { group, .. } if group.id == group_id => Some(group.color_egui()),
+        _ => None,
+    })
 }
 
 #[derive(Clone)]
@@ -58,6 +193,10 @@ enum TimelineInteraction {
         clip_ids_and_starts: Vec<(u64, f64)>,
         start_drag_beat: f64,
         duplicate_on_drop: bool,
+        press_pos: egui::Pos2,
+        /// Set once the pointer has travelled the drag threshold from
+        /// `press_pos`; coming back near it afterwards doesn't undo the drag
+        moved: bool,
     },
     ResizeClipLeft {
         clip_id: u64,
@@ -84,6 +223,8 @@ enum TimelineInteraction {
         clip_id: u64,
         start_offset: f64,
         start_mouse_beat: f64,
+        // Audio offsets count into the source, so they move against the mouse
+        is_midi: bool,
     },
 }
 
@@ -97,15 +238,21 @@ impl TimelineView {
             grid_snap: 0.25,
             show_automation: false,
             auto_scroll: true,
+            show_rms: true,
 
             snap_enabled: true,
             snap_to_grid: true,
             snap_to_clips: true,
             snap_to_loop: true,
+            snap_to_markers: true,
             snap_px_threshold: 10.0,
 
             selection_box: None,
             auto_crossfade_on_overlap: false,
+            crossfade_grab: 0.0,
+            tool: TimelineTool::Pointer,
+            ripple_mode: RippleMode::Off,
+            ripple_markers: false,
             snap_preview_beat: None,
             last_pointer_pos: None,
 
@@ -113,13 +260,20 @@ impl TimelineView {
             automation_widgets: Vec::new(),
             show_clip_menu: false,
             clip_menu_pos: egui::Pos2::ZERO,
+            velocity_target: 100,
+            quantize_preview: None,
+            groove_choice: None,
+            groove_strength: 1.0,
             track_height: 80.0,
             min_track_height: 40.0,
             max_track_height: 200.0,
             last_view_width: 800.0,
             pending_clip_undo: false,
             automation_hit_regions: Vec::new(),
+            automation_selection: None,
+            automation_focus: None,
             last_track_blocks: Vec::new(),
+            expanded_takes: HashSet::new(),
             drag_target_track: None,
         }
     }
@@ -143,7 +297,7 @@ impl TimelineView {
         }
     }
 
-    fn draw_toolbar(&mut self, ui: &mut egui::Ui, _app: &super::app::YadawApp) {
+    fn draw_toolbar(&mut self, ui: &mut egui::Ui, app: &super::app::YadawApp) {
         egui::ScrollArea::horizontal()
             .id_salt("tl_tool_strip")
             .scroll_source(ScrollSource::MOUSE_WHEEL)
@@ -171,13 +325,58 @@ impl TimelineView {
 
                     ui.separator();
                     ui.checkbox(&mut self.show_automation, "Show Automation");
+                    ui.add_enabled_ui(self.show_automation, |ui| {
+                        if ui
+                            .small_button("Collapse All")
+                            .on_hover_text("Hide every automation lane")
+                            .clicked()
+                        {
+                            let _ = app
+                                .command_tx
+                                .send(AudioCommand::SetAllAutomationLanesVisible(false));
+                        }
+                        if ui
+                            .small_button("Expand All")
+                            .on_hover_text("Show every automation lane")
+                            .clicked()
+                        {
+                            let _ = app
+                                .command_tx
+                                .send(AudioCommand::SetAllAutomationLanesVisible(true));
+                        }
+                    });
                     ui.checkbox(&mut self.auto_scroll, "Auto-scroll");
+                    ui.checkbox(&mut self.show_rms, "RMS")
+                        .on_hover_text("Overlay the RMS level on audio waveforms");
+
+                    ui.separator();
+                    ui.selectable_value(&mut self.tool, TimelineTool::Pointer, "Pointer");
+                    ui.selectable_value(&mut self.tool, TimelineTool::Razor, "Razor")
+                        .on_hover_text(
+                            "Click a clip to split it there; Alt-click splits every track",
+                        );
 
                     ui.separator();
                     ui.checkbox(
                         &mut self.auto_crossfade_on_overlap,
                         "Auto crossfade on overlap",
                     );
+                    egui::ComboBox::from_id_salt("tl_ripple_mode")
+                        .selected_text(format!("Ripple: {}", self.ripple_mode.name()))
+                        .show_ui(ui, |ui| {
+                            for mode in RippleMode::ALL {
+                                ui.selectable_value(&mut self.ripple_mode, mode, mode.name());
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Deleting or moving clips shifts everything after them \
+                             on the track, or on every track",
+                        );
+                    ui.add_enabled(
+                        self.ripple_mode != RippleMode::Off,
+                        egui::Checkbox::new(&mut self.ripple_markers, "Ripple markers"),
+                    );
                     egui::ComboBox::from_label("")
                         .selected_text(format!("1/{}", (1.0 / self.grid_snap) as i32))
                         .show_ui(ui, |ui| {
@@ -197,6 +396,7 @@ impl TimelineView {
                     ui.toggle_value(&mut self.snap_to_grid, "Grid");
                     ui.toggle_value(&mut self.snap_to_clips, "Clips");
                     ui.toggle_value(&mut self.snap_to_loop, "Loop");
+                    ui.toggle_value(&mut self.snap_to_markers, "Markers");
                     ui.add(
                         egui::Slider::new(&mut self.snap_px_threshold, 4.0..=24.0)
                             .text("Thresh px"),
@@ -213,12 +413,27 @@ impl TimelineView {
             self.pending_clip_undo = false;
         }
 
-        let track_data: Vec<(u64, Track)> = {
+        let rows: Vec<TimelineRow> = {
+            let rows = app.track_rows();
             let state = app.state.lock_sync();
-            state
-                .track_order
-                .iter()
-                .filter_map(|&tid| state.tracks.get(&tid).cloned().map(|t| (tid, t)))
+            rows.into_iter()
+                .filter_map(|row| match row {
+                    TrackRow::Folder(gid) => {
+                        let group = state.groups.get(&gid)?.clone();
+                        let members = state
+                            .track_order
+                            .iter()
+                            .filter_map(|tid| state.tracks.get(tid))
+                            .filter(|t| t.group_id == Some(gid))
+                            .cloned()
+                            .collect();
+                        Some(TimelineRow::Folder { group, members })
+                    }
+                    TrackRow::Track { id, nested } => {
+                        let track = state.tracks.get(&id)?.clone();
+                        Some(TimelineRow::Track { id, track, nested })
+                    }
+                })
                 .collect()
         };
 
@@ -237,10 +452,14 @@ impl TimelineView {
         // Compute dynamic per-track height: base track + visible lanes (if show_automation)
         const LANE_HEADER_H: f32 = 22.0;
 
-        let track_heights: Vec<f32> = track_data
+        let track_heights: Vec<f32> = rows
             .iter()
-            .map(|(_, t)| {
-                if self.show_automation {
+            .map(|row| {
+                let (tid, t) = match row {
+                    TimelineRow::Folder { .. } => return self.folder_lane_height(),
+                    TimelineRow::Track { id, track, .. } => (id, track),
+                };
+                let lanes_h = if self.show_automation {
                     let extra: f32 = t
                         .automation_lanes
                         .iter()
@@ -253,7 +472,8 @@ impl TimelineView {
                     self.track_height + extra
                 } else {
                     self.track_height
-                }
+                };
+                lanes_h + self.take_lanes_height(*tid, t)
             })
             .collect();
 
@@ -270,6 +490,8 @@ impl TimelineView {
             app.active_edit_target = super::app::ActiveEditTarget::Clips;
         }
 
+        self.handle_touch_gestures(ui, &response, app);
+
         // wheel zoom (Ctrl/Cmd + wheel zooms around cursor)
         if response.hovered() {
             let modifiers = ui.input(|i| i.modifiers);
@@ -299,7 +521,8 @@ impl TimelineView {
 
         // Draw the grid and horizontal ruler
         let rect = response.rect;
-        self.draw_grid(&painter, rect, app.state.lock_sync().bpm);
+        let time_signature = app.state.lock_sync().time_signature;
+        self.draw_grid(&painter, rect, time_signature, app.config.bar_numbering());
 
         // loop/seek
         let ruler_h = 18.0;
@@ -313,7 +536,20 @@ impl TimelineView {
 
         // Place each track block at cumulative Y positions
         let mut y_cursor = rect.top();
-        for ((track_id, track), block_h) in track_data.iter().zip(track_heights.iter()) {
+        for (row, block_h) in rows.iter().zip(track_heights.iter()) {
+            let (track_id, track, nested) = match row {
+                TimelineRow::Folder { group, members } => {
+                    let lane = egui::Rect::from_min_size(
+                        egui::pos2(rect.left(), y_cursor),
+                        egui::vec2(rect.width(), *block_h),
+                    );
+                    self.draw_folder_lane(&painter, ui, lane, group, members, app);
+                    y_cursor += *block_h;
+                    continue;
+                }
+                TimelineRow::Track { id, track, nested } => (id, track, *nested),
+            };
+
             // The main clip area is the top self.track_height of the block
             let clip_area = egui::Rect::from_min_size(
                 egui::pos2(rect.left(), y_cursor),
@@ -340,9 +576,18 @@ impl TimelineView {
             // Cache it for hit mapping (Ctrl+click etc.)
             self.last_track_blocks.push((*track_id, block_rect));
 
+            if nested && let Some(color) = track.group_id.and_then(|g| folder_color(&rows, g)) {
+                let guide = egui::Rect::from_min_size(
+                    block_rect.min + egui::vec2(4.0, 0.0),
+                    egui::vec2(3.0, block_rect.height()),
+                );
+                painter.rect_filled(guide, 0.0, color);
+            }
+
             if self.show_automation {
                 self.draw_automation_lanes(ui, block_rect, &track, *track_id, app);
             }
+            self.draw_take_lanes(ui, block_rect, &track, *track_id, app);
 
             // Draw separator at the BOTTOM of the block (not the top)
             let sep_y = y_cursor + *block_h;
@@ -360,10 +605,13 @@ impl TimelineView {
 
         self.draw_drag_ghosts(ui, app, rect);
         self.draw_resize_previews(ui, app, rect);
-        self.handle_keyboard_nudge(ui, app);
+        // One key press may move, resize and slip at once; keep it a single undo step
+        app.with_undo_batch(|app| self.handle_keyboard_nudge(ui, app));
 
         // Draw loop region overlay
         self.draw_loop_region(&painter, rect, app);
+        self.draw_punch_region(&painter, rect, app);
+        self.draw_markers(&painter, rect, app);
 
         // Draw playhead
         let position = app.audio_state.get_position();
@@ -400,7 +648,13 @@ impl TimelineView {
         }
     }
 
-    fn draw_grid(&self, painter: &egui::Painter, rect: egui::Rect, _bpm: f32) {
+    fn draw_grid(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        sig: TimeSignature,
+        numbering: BarNumbering,
+    ) {
         let ruler_h = 18.0;
         let visuals = painter.ctx().global_style().visuals.clone();
         let bg = visuals.widgets.noninteractive.bg_fill;
@@ -415,16 +669,19 @@ impl TimelineView {
             bg,
         );
 
-        // Vertical beat lines and bar markers
-        let beats_visible = (rect.width() / self.zoom_x) as i32 + 2;
-        let start_beat = (self.scroll_x / self.zoom_x) as i32;
+        // Vertical lines on each count of the meter, heavier on bar starts
+        let count_len = sig.beat_len();
+        let count_px = count_len as f32 * self.zoom_x;
+        let counts_visible = (rect.width() / count_px) as i64 + 2;
+        let start_count = (self.scroll_x / count_px) as i64;
 
-        for beat in start_beat..(start_beat + beats_visible) {
+        for count in start_count..(start_count + counts_visible) {
+            let beat = count as f64 * count_len;
             let x = rect.left() + (beat as f32 * self.zoom_x - self.scroll_x);
             if x < rect.left() || x > rect.right() {
                 continue;
             }
-            let is_bar = beat % 4 == 0;
+            let is_bar = count % sig.numerator.max(1) as i64 == 0;
             let color = if is_bar { bar_fg } else { grid_fg };
             let stroke = egui::Stroke::new(if is_bar { 1.5 } else { 1.0 }, color);
             painter.line_segment(
@@ -434,6 +691,16 @@ impl TimelineView {
                 ],
                 stroke,
             );
+            if is_bar {
+                let (bar, _, _) = numbering.position(beat, sig);
+                painter.text(
+                    egui::pos2(x + 3.0, rect.top() + 2.0),
+                    egui::Align2::LEFT_TOP,
+                    bar.to_string(),
+                    egui::FontId::monospace(10.0),
+                    grid_fg,
+                );
+            }
             painter.line_segment(
                 [
                     egui::pos2(x, rect.top() + ruler_h),
@@ -477,7 +744,7 @@ impl TimelineView {
             painter.rect_filled(strip_rect, 0.0, egui::Color32::from_rgb(r, g, b));
         }
 
-        painter.text(
+        let name_rect = painter.text(
             rect.min + egui::vec2(8.0, 5.0),
             egui::Align2::LEFT_TOP,
             &track.name,
@@ -493,6 +760,356 @@ impl TimelineView {
             for clip in &track.audio_clips {
                 self.draw_audio_clip(painter, ui, rect, clip, track_id, app, track_color);
             }
+            self.draw_crossfades(painter, ui, rect, track, track_id, app);
+        }
+
+        if !track.takes.is_empty() {
+            let expanded = self.expanded_takes.contains(&track_id);
+            let toggle_rect = egui::Rect::from_min_size(
+                egui::pos2(name_rect.right() + 6.0, name_rect.top()),
+                egui::vec2(56.0, name_rect.height()),
+            );
+            let label = if expanded { "Takes ▾" } else { "Takes ▸" };
+            if ui
+                .put(toggle_rect, egui::Button::new(label).small())
+                .on_hover_text("Show loop-recording takes to pick the one that plays")
+                .clicked()
+                && !self.expanded_takes.remove(&track_id)
+            {
+                self.expanded_takes.insert(track_id);
+            }
+        }
+    }
+
+    /// X-shaped fade curves over each crossfade, with a handle band along the
+    /// top: drag the middle to move it, the ends to widen it, right-click for
+    /// the curve
+    fn draw_crossfades(
+        &mut self,
+        painter: &egui::Painter,
+        ui: &mut egui::Ui,
+        track_rect: egui::Rect,
+        track: &Track,
+        track_id: u64,
+        app: &mut super::app::YadawApp,
+    ) {
+        const HANDLE_H: f32 = 12.0;
+        const EDGE_W: f32 = 6.0;
+        const STEPS: usize = 24;
+
+        for xf in &track.crossfades {
+            let Some((start, end)) = track.crossfade_span(xf) else {
+                continue;
+            };
+            let top = track_rect.top() + 20.0;
+            let rect = egui::Rect::from_x_y_ranges(
+                self.beat_to_x(track_rect, start)..=self.beat_to_x(track_rect, end),
+                top..=top + self.track_height - 25.0,
+            );
+            if !rect.intersects(track_rect) {
+                continue;
+            }
+
+            painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(60));
+            let curve_points = |incoming: bool| -> Vec<egui::Pos2> {
+                (0..=STEPS)
+                    .map(|i| {
+                        let t = i as f32 / STEPS as f32;
+                        let (out_gain, in_gain) = xf.curve.gains(t);
+                        let g = if incoming { in_gain } else { out_gain };
+                        egui::pos2(
+                            rect.left() + t * rect.width(),
+                            rect.bottom() - g * rect.height(),
+                        )
+                    })
+                    .collect()
+            };
+            let stroke = egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 200, 90));
+            painter.add(egui::Shape::line(curve_points(false), stroke));
+            painter.add(egui::Shape::line(curve_points(true), stroke));
+
+            let band = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), HANDLE_H));
+            painter.rect_filled(
+                band,
+                2.0,
+                egui::Color32::from_rgb(255, 200, 90).gamma_multiply(0.35),
+            );
+
+            let body = ui.interact(
+                band.shrink2(egui::vec2(EDGE_W, 0.0)),
+                ui.id().with(("crossfade", xf.id)),
+                egui::Sense::click_and_drag(),
+            );
+            let left = ui.interact(
+                egui::Rect::from_center_size(
+                    band.left_center(),
+                    egui::vec2(EDGE_W * 2.0, HANDLE_H),
+                ),
+                ui.id().with(("crossfade_left", xf.id)),
+                egui::Sense::drag(),
+            );
+            let right = ui.interact(
+                egui::Rect::from_center_size(
+                    band.right_center(),
+                    egui::vec2(EDGE_W * 2.0, HANDLE_H),
+                ),
+                ui.id().with(("crossfade_right", xf.id)),
+                egui::Sense::drag(),
+            );
+
+            let update = |center: f64, length: f64, curve| AudioCommand::UpdateCrossfade {
+                track_id,
+                crossfade_id: xf.id,
+                center,
+                length,
+                curve,
+            };
+            let [body_beat, left_beat, right_beat] = [&body, &left, &right].map(|resp| {
+                resp.interact_pointer_pos()
+                    .map(|p| self.x_to_beat(track_rect, p.x))
+            });
+
+            if body.drag_started() || left.drag_started() || right.drag_started() {
+                app.push_undo();
+                if let Some(beat) = body_beat {
+                    self.crossfade_grab = xf.center - beat;
+                }
+            }
+            if body.dragged()
+                && let Some(beat) = body_beat
+            {
+                let _ =
+                    app.command_tx
+                        .send(update(beat + self.crossfade_grab, xf.length, xf.curve));
+            }
+            for (edge, edge_beat) in [(&left, left_beat), (&right, right_beat)] {
+                if edge.dragged()
+                    && let Some(beat) = edge_beat
+                {
+                    let length = (2.0 * (beat - xf.center).abs()).max(1.0 / 64.0);
+                    let _ = app.command_tx.send(update(xf.center, length, xf.curve));
+                }
+                if edge.hovered() || edge.dragged() {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+                }
+            }
+            if body.hovered() || body.dragged() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+            }
+
+            body.on_hover_text(format!("Crossfade ({})", xf.curve.name()))
+                .context_menu(|ui| {
+                    for curve in CrossfadeCurve::ALL {
+                        if ui.radio(xf.curve == curve, curve.name()).clicked() && xf.curve != curve
+                        {
+                            app.push_undo();
+                            let _ = app.command_tx.send(update(xf.center, xf.length, curve));
+                            ui.close();
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("Remove Crossfade").clicked() {
+                        app.push_undo();
+                        let _ = app.command_tx.send(AudioCommand::RemoveCrossfade {
+                            track_id,
+                            crossfade_id: xf.id,
+                        });
+                        ui.close();
+                    }
+                });
+        }
+    }
+
+    /// Summary lane of a group folder: its members' clips as thin bars, one
+    /// row per member, and a toggle to fold them away
+    fn draw_folder_lane(
+        &self,
+        painter: &egui::Painter,
+        ui: &mut egui::Ui,
+        rect: egui::Rect,
+        group: &TrackGroup,
+        members: &[Track],
+        app: &mut super::app::YadawApp,
+    ) {
+        let color = group.color_egui();
+        painter.rect_filled(rect, 0.0, color.gamma_multiply(0.12));
+        painter.rect_filled(
+            egui::Rect::from_min_size(rect.min, egui::vec2(4.0, rect.height())),
+            0.0,
+            color,
+        );
+
+        let toggle_rect =
+            egui::Rect::from_min_size(rect.min + egui::vec2(8.0, 3.0), egui::vec2(18.0, 18.0));
+        let arrow = if group.collapsed { "▸" } else { "▾" };
+        if ui
+            .put(toggle_rect, egui::Button::new(arrow).small().frame(false))
+            .on_hover_text(if group.collapsed {
+                "Show the folder's tracks"
+            } else {
+                "Fold the tracks into this lane"
+            })
+            .clicked()
+        {
+            let _ = app
+                .command_tx
+                .send(AudioCommand::ToggleGroupCollapsed(group.id));
+        }
+        painter.text(
+            egui::pos2(toggle_rect.right() + 4.0, rect.top() + 5.0),
+            egui::Align2::LEFT_TOP,
+            format!("{} ({})", group.name, members.len()),
+            egui::FontId::proportional(12.0),
+            ui.visuals().text_color().gamma_multiply(0.7),
+        );
+
+        if members.is_empty() {
+            return;
+        }
+        let band = egui::Rect::from_min_max(
+            egui::pos2(rect.left(), rect.top() + 22.0),
+            egui::pos2(rect.right(), rect.bottom() - 2.0),
+        );
+        if band.height() <= 0.0 {
+            return;
+        }
+        let row_h = band.height() / members.len() as f32;
+        for (i, track) in members.iter().enumerate() {
+            let top = band.top() + i as f32 * row_h;
+            let fill = track
+                .color
+                .map_or(color, |(r, g, b)| egui::Color32::from_rgb(r, g, b))
+                .gamma_multiply(0.8);
+            let spans = track
+                .audio_clips
+                .iter()
+                .map(|c| (c.start_beat, c.length_beats))
+                .chain(
+                    track
+                        .midi_clips
+                        .iter()
+                        .map(|c| (c.start_beat, c.length_beats)),
+                );
+            for (start, len) in spans {
+                let bar = egui::Rect::from_x_y_ranges(
+                    self.beat_to_x(rect, start)..=self.beat_to_x(rect, start + len),
+                    top..=top + (row_h - 1.0).max(1.0),
+                );
+                if bar.intersects(rect) {
+                    painter.rect_filled(bar.intersect(rect), 1.0, fill);
+                }
+            }
+        }
+    }
+
+    /// Height of the take lanes under a track: one row per pass of its busiest region
+    fn take_lanes_height(&self, track_id: u64, track: &Track) -> f32 {
+        if !self.expanded_takes.contains(&track_id) {
+            return 0.0;
+        }
+        take_lane_rows(track).len() as f32 * TAKE_LANE_H
+    }
+
+    /// Height of a group folder's summary lane
+    fn folder_lane_height(&self) -> f32 {
+        (self.track_height * 0.5).max(24.0)
+    }
+
+    fn draw_take_lanes(
+        &mut self,
+        ui: &mut egui::Ui,
+        block_rect: egui::Rect,
+        track: &Track,
+        track_id: u64,
+        app: &mut super::app::YadawApp,
+    ) {
+        let rows = take_lane_rows(track);
+        if rows.is_empty() || !self.expanded_takes.contains(&track_id) {
+            return;
+        }
+
+        let painter = ui.painter().clone();
+        let mut y = block_rect.bottom() - rows.len() as f32 * TAKE_LANE_H;
+        for (row, takes) in rows.iter().enumerate() {
+            let lane_rect = egui::Rect::from_min_size(
+                egui::pos2(block_rect.left(), y),
+                egui::vec2(block_rect.width(), TAKE_LANE_H),
+            );
+            painter.rect_filled(lane_rect, 0.0, egui::Color32::from_gray(24));
+            painter.text(
+                lane_rect.left_center() + egui::vec2(8.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                format!("Take {}", row + 1),
+                egui::FontId::proportional(11.0),
+                egui::Color32::from_gray(140),
+            );
+
+            for take in takes {
+                let region = take.take_region.unwrap_or_default();
+                let active = track.active_takes.get(&region) == Some(&take.id);
+                let x = self.beat_to_x(lane_rect, take.start_beat);
+                let take_rect = egui::Rect::from_min_size(
+                    egui::pos2(x, lane_rect.top() + 2.0),
+                    egui::vec2(take.length_beats as f32 * self.zoom_x, TAKE_LANE_H - 4.0),
+                );
+                if !take_rect.intersects(lane_rect) {
+                    continue;
+                }
+                self.draw_take(&painter, take_rect, take, active, track.color);
+
+                let resp = ui.interact(
+                    take_rect.intersect(lane_rect),
+                    ui.id().with(("take", take.id)),
+                    egui::Sense::click(),
+                );
+                if resp.clicked() && !active {
+                    app.push_undo();
+                    let _ = app.command_tx.send(AudioCommand::SetActiveTake {
+                        track_id,
+                        region,
+                        take_id: take.id,
+                    });
+                }
+                resp.on_hover_text(if active {
+                    "Playing"
+                } else {
+                    "Click to play this take"
+                });
+            }
+            y += TAKE_LANE_H;
+        }
+    }
+
+    fn draw_take(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        take: &AudioClip,
+        active: bool,
+        track_color: Option<(u8, u8, u8)>,
+    ) {
+        let (r, g, b) = track_color.unwrap_or((70, 75, 80));
+        let fill = egui::Color32::from_rgb(r, g, b);
+        let fill = if active {
+            fill
+        } else {
+            fill.gamma_multiply(0.35)
+        };
+        painter.rect_filled(rect, 3.0, fill);
+        draw_waveform(
+            painter,
+            rect,
+            take,
+            egui::Color32::WHITE.gamma_multiply(if active { 0.7 } else { 0.3 }),
+            self.show_rms,
+        );
+        if active {
+            painter.rect_stroke(
+                rect,
+                3.0,
+                egui::Stroke::new(1.0, egui::Color32::WHITE.gamma_multiply(0.6)),
+                egui::StrokeKind::Inside,
+            );
         }
     }
 
@@ -510,12 +1127,13 @@ impl TimelineView {
 
         let bpm = app.audio_state.bpm.load();
         let audio_duration_seconds = clip.samples.len() as f64 / clip.sample_rate as f64;
-        let audio_length_beats = audio_duration_seconds * (bpm as f64 / 60.0);
-
-        let effective_length_beats = if clip.warp_mode {
+        let missing = clip.is_missing_media();
+        // Warped clips without a source tempo are stretched to fill their length.
+        // A clip with missing media keeps its saved length as a placeholder.
+        let effective_length_beats = if missing {
             clip.length_beats as f32
         } else {
-            (audio_length_beats as f32).min(clip.length_beats as f32)
+            (clip.source_length_beats(bpm) as f32).min(clip.length_beats as f32)
         };
         let clip_width = effective_length_beats * self.zoom_x;
 
@@ -550,37 +1168,59 @@ impl TimelineView {
         // Fill Background
         painter.rect_filled(clip_rect, 3.0, base_color);
 
-        draw_waveform(
-            painter,
-            clip_rect,
-            clip,
-            self.zoom_x,
-            self.scroll_x,
-            fg_color.gamma_multiply(0.6),
-        );
+        if missing {
+            draw_missing_media(painter, clip_rect, clip, fg_color);
+        } else {
+            draw_waveform(
+                painter,
+                clip_rect,
+                clip,
+                fg_color.gamma_multiply(0.6),
+                self.show_rms,
+            );
+        }
 
-        // Audio Looping Indicators (Visual only)
+        // Audio Looping Indicators
         if clip.loop_enabled {
-            let src_len_beats =
-                (clip.samples.len() as f64 / clip.sample_rate as f64) * (bpm as f64 / 60.0);
-            if src_len_beats > 0.0 && src_len_beats < clip.length_beats {
-                let reps = (clip.length_beats / src_len_beats).ceil() as i32;
-                for k in 1..reps {
-                    let offset_x = (k as f64 * src_len_beats * self.zoom_x as f64) as f32;
-                    let line_x = clip_rect.left() + offset_x;
-                    if line_x < clip_rect.right() {
-                        painter.line_segment(
-                            [
-                                egui::pos2(line_x, clip_rect.top()),
-                                egui::pos2(line_x, clip_rect.bottom()),
-                            ],
-                            egui::Stroke::new(1.0, fg_color.gamma_multiply(0.3)),
-                        );
+            let src_len_beats = clip.source_length_beats(bpm);
+            let xfade = clip
+                .loop_mode
+                .crossfade_len(clip.loop_crossfade, src_len_beats);
+            // Each pass after the first starts where the crossfade into it begins
+            let period = src_len_beats - xfade;
+            if period > 0.0 {
+                let reps = ((clip.length_beats + clip.offset_beats) / period).ceil() as i64;
+                for k in 1..=reps {
+                    let rel = k as f64 * period - clip.offset_beats;
+                    if rel <= 0.0 || rel >= clip.length_beats {
+                        continue;
                     }
+                    let line_x = clip_rect.left() + (rel * self.zoom_x as f64) as f32;
+                    let color = fg_color.gamma_multiply(0.3);
+                    if xfade > 0.0 {
+                        let xfade_right =
+                            (line_x + (xfade * self.zoom_x as f64) as f32).min(clip_rect.right());
+                        draw_loop_crossfade(painter, clip_rect, line_x, xfade_right, color);
+                    }
+                    draw_loop_boundary(painter, clip_rect, line_x, clip.loop_mode, k, color);
                 }
             }
         }
 
+        // Slipped clips get a notch on the left edge: source audio is hidden before the start
+        if clip.offset_beats > 1e-6 {
+            let (left, bottom) = (clip_rect.left(), clip_rect.bottom());
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    egui::pos2(left, bottom - 12.0),
+                    egui::pos2(left + 6.0, bottom - 8.0),
+                    egui::pos2(left, bottom - 4.0),
+                ],
+                fg_color.gamma_multiply(0.6),
+                egui::Stroke::NONE,
+            ));
+        }
+
         let bar_color = if is_light {
             base_color.gamma_multiply(0.8)
         } else {
@@ -598,10 +1238,15 @@ impl TimelineView {
         );
 
         if clip.warp_mode {
+            let badge = if clip.original_bpm > 0.0 {
+                format!("WARP {:.1}", clip.original_bpm)
+            } else {
+                "WARP".to_string()
+            };
             painter.text(
                 clip_rect.right_top() + egui::vec2(-6.0, 5.0),
                 egui::Align2::RIGHT_TOP,
-                "WARP",
+                badge,
                 egui::FontId::proportional(10.0),
                 fg_color.gamma_multiply(0.8),
             );
@@ -632,6 +1277,14 @@ impl TimelineView {
             );
         }
 
+        let response = self.clip_tooltip(
+            response,
+            app,
+            &clip.name,
+            clip.start_beat,
+            clip.length_beats,
+            Some((audio_duration_seconds, clip.sample_rate)),
+        );
         self.handle_clip_interaction(response, clip.id, ui, clip_rect, app);
 
         // 6. Draw Fades (Visual feedback)
@@ -656,20 +1309,24 @@ impl TimelineView {
             mesh.colored_vertex(p3, egui::Color32::from_black_alpha(100)); // Darken top-left
             painter.add(mesh);
 
-            painter.line_segment(
-                [p1, p2],
+            painter.add(egui::Shape::line(
+                fade_curve_points(clip_rect, p1.x, p2.x, clip.fade_in_curve, true),
                 egui::Stroke::new(1.0, fg_color.gamma_multiply(0.5)),
-            );
+            ));
         }
 
         if out_px > 1.0 {
             // Fade-out line
-            let p1 = clip_rect.right_top() - egui::vec2(out_px, 0.0);
-            let p2 = clip_rect.right_bottom();
-            painter.line_segment(
-                [p1, p2],
+            painter.add(egui::Shape::line(
+                fade_curve_points(
+                    clip_rect,
+                    clip_rect.right() - out_px,
+                    clip_rect.right(),
+                    clip.fade_out_curve,
+                    false,
+                ),
                 egui::Stroke::new(1.0, fg_color.gamma_multiply(0.5)),
-            );
+            ));
         }
 
         // Fade handles
@@ -703,6 +1360,7 @@ impl TimelineView {
                         .send(AudioCommand::SetAudioClipFadeIn(clip.id, Some(new_len)));
                 }
             }
+            resp.context_menu(|ui| fade_curve_menu(ui, app, clip.id, true, clip.fade_in_curve));
         }
 
         {
@@ -731,6 +1389,63 @@ impl TimelineView {
                         .send(AudioCommand::SetAudioClipFadeOut(clip.id, Some(new_len)));
                 }
             }
+            resp.context_menu(|ui| fade_curve_menu(ui, app, clip.id, false, clip.fade_out_curve));
+        }
+
+        // Clip gain handle: drag up/down, double-click resets to 0 dB
+        {
+            let gain_db = crate::audio_utils::linear_to_db(clip.gain);
+            let center = egui::pos2(clip_rect.center().x, clip_rect.top() + 6.0);
+            let dot_id = ui.id().with(("clip_gain_dot", clip.id));
+            let dot_rect = egui::Rect::from_center_size(center, egui::vec2(14.0, 14.0));
+            let resp = ui.interact(dot_rect, dot_id, egui::Sense::click_and_drag());
+            let unity = gain_db.abs() < 0.05;
+
+            if resp.hovered() || resp.dragged() || !unity {
+                let fill = if resp.hovered() || resp.dragged() {
+                    egui::Color32::from_gray(220)
+                } else {
+                    fg_color.gamma_multiply(0.6)
+                };
+                ui.painter().rect_filled(
+                    egui::Rect::from_center_size(center, egui::vec2(dot_r * 2.0, dot_r * 1.2)),
+                    2.0,
+                    fill,
+                );
+            }
+            if !unity {
+                painter.text(
+                    center + egui::vec2(0.0, 6.0),
+                    egui::Align2::CENTER_TOP,
+                    format!("{gain_db:+.1} dB"),
+                    egui::FontId::proportional(10.0),
+                    fg_color.gamma_multiply(0.8),
+                );
+            }
+
+            if resp.drag_started() || resp.double_clicked() {
+                app.push_undo();
+            }
+            if resp.double_clicked() {
+                let _ = app
+                    .command_tx
+                    .send(AudioCommand::SetAudioClipGain(clip.id, 1.0));
+            } else if resp.dragged() {
+                // Shift for fine adjustment
+                let per_px = if ui.input(|i| i.modifiers.shift) {
+                    0.02
+                } else {
+                    0.2
+                };
+                let new_db = (gain_db - resp.drag_delta().y * per_px).clamp(-48.0, 12.0);
+                let _ = app.command_tx.send(AudioCommand::SetAudioClipGain(
+                    clip.id,
+                    crate::audio_utils::db_to_linear(new_db),
+                ));
+            }
+            resp.on_hover_text(format!(
+                "Clip gain {gain_db:+.1} dB\nDrag to adjust (Shift: fine), double-click to reset"
+            ));
         }
     }
 
@@ -773,6 +1488,105 @@ impl TimelineView {
         );
     }
 
+    /// Red band along the ruler over the punch region
+    fn draw_punch_region(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        app: &super::app::YadawApp,
+    ) {
+        let Some((punch_in, punch_out)) = app.audio_state.punch_range() else {
+            return;
+        };
+        let start_x = self.beat_to_x(rect, punch_in).max(rect.left());
+        let end_x = self.beat_to_x(rect, punch_out).min(rect.right());
+        if end_x <= start_x {
+            return;
+        }
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(start_x..=end_x, rect.top()..=rect.top() + 4.0),
+            0.0,
+            egui::Color32::from_rgb(220, 60, 60),
+        );
+    }
+
+    fn draw_markers(&self, painter: &egui::Painter, rect: egui::Rect, app: &super::app::YadawApp) {
+        let color = egui::Color32::from_rgb(240, 180, 60);
+        let state = app.state.lock_sync();
+        for marker in &state.markers {
+            let x = self.beat_to_x(rect, marker.beat);
+            if x < rect.left() || x > rect.right() {
+                continue;
+            }
+            painter.line_segment(
+                [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                egui::Stroke::new(1.0, color.gamma_multiply(0.5)),
+            );
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    egui::pos2(x, rect.top()),
+                    egui::pos2(x + 6.0, rect.top() + 5.0),
+                    egui::pos2(x, rect.top() + 10.0),
+                ],
+                color,
+                egui::Stroke::NONE,
+            ));
+            painter.text(
+                egui::pos2(x + 8.0, rect.top() + 2.0),
+                egui::Align2::LEFT_TOP,
+                &marker.name,
+                egui::FontId::proportional(10.0),
+                color,
+            );
+        }
+    }
+
+    /// Index of the marker within a few pixels of `x`
+    fn marker_near(&self, rect: egui::Rect, x: f32, app: &super::app::YadawApp) -> Option<usize> {
+        let state = app.state.lock_sync();
+        state
+            .markers
+            .iter()
+            .position(|m| (self.beat_to_x(rect, m.beat) - x).abs() <= 6.0)
+    }
+
+    /// Hover summary: name, start as bar.beat.tick, length in beats and seconds,
+    /// and for audio the source duration and rate
+    fn clip_tooltip(
+        &self,
+        response: egui::Response,
+        app: &super::app::YadawApp,
+        name: &str,
+        start_beat: f64,
+        length_beats: f64,
+        source: Option<(f64, f32)>,
+    ) -> egui::Response {
+        if !app.config.ui.show_tooltips || self.timeline_interaction.is_some() {
+            return response;
+        }
+        response.on_hover_ui(|ui| {
+            let time_signature = app.state.lock_sync().time_signature;
+            let bpm = app.audio_state.bpm.load().max(1.0) as f64;
+            let start = app
+                .config
+                .bar_numbering()
+                .format_position(start_beat, time_signature, 480);
+            ui.strong(name);
+            ui.label(format!("Start: {start}"));
+            ui.label(format!(
+                "Length: {:.2} beats ({:.3} s)",
+                length_beats,
+                length_beats * 60.0 / bpm
+            ));
+            if let Some((seconds, sample_rate)) = source {
+                ui.label(format!(
+                    "Source: {:.3} s @ {} Hz",
+                    seconds, sample_rate as u32
+                ));
+            }
+        })
+    }
+
     fn draw_midi_clip(
         &mut self,
         painter: &egui::Painter,
@@ -864,19 +1678,17 @@ impl TimelineView {
             for k in 1..reps {
                 let x = clip_rect.left() + (k as f32 * content_len as f32 * self.zoom_x);
                 if x >= track_rect.left() && x <= track_rect.right() {
-                    painter.line_segment(
-                        [
-                            egui::pos2(x, clip_rect.top()),
-                            egui::pos2(x, clip_rect.bottom()),
-                        ],
-                        egui::Stroke::new(
-                            1.0,
-                            if is_light {
-                                egui::Color32::from_rgba_premultiplied(0, 0, 0, 40)
-                            } else {
-                                egui::Color32::from_rgba_premultiplied(255, 255, 255, 40)
-                            },
-                        ),
+                    draw_loop_boundary(
+                        painter,
+                        clip_rect,
+                        x,
+                        clip.loop_mode,
+                        k as i64,
+                        if is_light {
+                            egui::Color32::from_rgba_premultiplied(0, 0, 0, 40)
+                        } else {
+                            egui::Color32::from_rgba_premultiplied(255, 255, 255, 40)
+                        },
                     );
                 }
             }
@@ -885,12 +1697,14 @@ impl TimelineView {
         let offset = clip
             .content_offset_beats
             .rem_euclid(clip.content_len_beats.max(0.000001));
+        let quantize_preview = self.quantize_preview.filter(|p| p.clip_id == clip.id);
 
         for k in first_rep..=last_rep {
             let rep_start = k as f64 * content_len;
             if rep_start >= inst_len {
                 break;
             }
+            let reversed = clip.loop_enabled && clip.loop_mode.is_reversed(k as i64);
 
             for note in &base_notes {
                 let s_loc = (note.start + offset).rem_euclid(content_len);
@@ -906,6 +1720,11 @@ impl TimelineView {
                 }
 
                 for (s_local, e_local) in segs {
+                    let (s_local, e_local) = if reversed {
+                        (content_len - e_local, content_len - s_local)
+                    } else {
+                        (s_local, e_local)
+                    };
                     let s = rep_start + s_local;
                     if s >= inst_len {
                         continue;
@@ -930,6 +1749,24 @@ impl TimelineView {
                         1.0,
                         note_color,
                     );
+
+                    // Where the note would land once the previewed quantize is applied
+                    if let Some(preview) = quantize_preview {
+                        let q_s = preview.quantize(clip.start_beat + s) - clip.start_beat;
+                        let q_e = (preview.quantize(clip.start_beat + e) - clip.start_beat)
+                            .max(q_s + 1e-6);
+                        let ghost_left = clip_rect.left() + (q_s as f32 * self.zoom_x);
+                        let ghost_right = clip_rect.left() + (q_e as f32 * self.zoom_x);
+                        painter.rect_stroke(
+                            egui::Rect::from_min_size(
+                                egui::pos2(ghost_left, note_y - 3.0),
+                                egui::vec2((ghost_right - ghost_left).max(2.0), 5.0),
+                            ),
+                            1.0,
+                            egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 200, 80)),
+                            egui::StrokeKind::Outside,
+                        );
+                    }
                 }
             }
         }
@@ -976,9 +1813,66 @@ impl TimelineView {
             app.open_midi_clip_in_piano_roll(clip.id);
         }
 
+        let response = self.clip_tooltip(
+            response,
+            app,
+            &clip.name,
+            clip.start_beat,
+            clip.length_beats,
+            None,
+        );
         self.handle_clip_interaction(response, clip.id, ui, clip_rect, app);
     }
 
+    /// Semitone steps and a cents fine-tune for an audio clip's pitch
+    fn clip_pitch_controls(
+        &self,
+        ui: &mut egui::Ui,
+        app: &mut super::app::YadawApp,
+        clip_id: u64,
+        pitch: f32,
+    ) {
+        let semis = pitch.round();
+        let mut cents = ((pitch - semis) * 100.0).round();
+        let set_pitch = |app: &mut super::app::YadawApp, value: f32| {
+            let _ = app
+                .command_tx
+                .send(AudioCommand::SetAudioClipPitch(clip_id, value));
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Pitch:");
+            if ui.small_button("-").clicked() {
+                app.push_undo();
+                set_pitch(app, pitch - 1.0);
+            }
+            ui.label(format!("{:+} st", semis as i32));
+            if ui.small_button("+").clicked() {
+                app.push_undo();
+                set_pitch(app, pitch + 1.0);
+            }
+            let resp = ui.add(
+                egui::DragValue::new(&mut cents)
+                    .range(-50.0..=50.0)
+                    .speed(0.5)
+                    .suffix(" ct"),
+            );
+            if resp.drag_started() || resp.gained_focus() {
+                app.push_undo();
+            }
+            if resp.changed() {
+                set_pitch(app, semis + cents / 100.0);
+            }
+            if ui
+                .add_enabled(pitch != 0.0, egui::Button::new("Reset").small())
+                .clicked()
+            {
+                app.push_undo();
+                set_pitch(app, 0.0);
+            }
+        });
+    }
+
     fn handle_clip_interaction(
         &mut self,
         response: egui::Response,
@@ -987,7 +1881,7 @@ impl TimelineView {
         clip_rect: egui::Rect,
         app: &mut super::app::YadawApp,
     ) {
-        if ui.input(|i| i.key_down(egui::Key::Space)) {
+        if ui.input(|i| i.key_down(egui::Key::Space)) || self.tool == TimelineTool::Razor {
             return;
         }
         // Select on click
@@ -999,10 +1893,13 @@ impl TimelineView {
             app.selected_clips.push(clip_id);
         }
 
-        // Context menu
-        if response.secondary_clicked() {
+        // Context menu, from right-click or a long press on touch screens
+        let long_press = app.input_manager.take_long_press_in(clip_rect);
+        if response.secondary_clicked() || long_press.is_some() {
             self.show_clip_menu = true;
-            self.clip_menu_pos = response.interact_pointer_pos().unwrap_or_default();
+            self.clip_menu_pos = long_press
+                .or_else(|| response.interact_pointer_pos())
+                .unwrap_or_default();
             if !app.selected_clips.contains(&clip_id) {
                 app.selected_clips.clear();
                 app.selected_clips.push(clip_id);
@@ -1057,33 +1954,27 @@ impl TimelineView {
 
             // Slip content (Alt+drag)
             if alt && !hover_left && !hover_right {
-                let (start_offset, content_len, is_midi) = {
+                let slip = {
                     let state = app.state.lock_sync();
-                    if let Some((track, loc)) = state.find_clip(clip_id) {
-                        if let ClipLocation::Midi(idx) = loc {
+                    state.find_clip(clip_id).map(|(track, loc)| match loc {
+                        ClipLocation::Midi(idx) => {
                             let c = &track.midi_clips[idx];
-                            (
-                                c.content_offset_beats,
-                                c.content_len_beats.max(0.000001),
-                                true,
-                            )
-                        } else {
-                            (0.0, 1.0, false)
+                            let content_len = c.content_len_beats.max(0.000001);
+                            (c.content_offset_beats.rem_euclid(content_len), true)
                         }
-                    } else {
-                        (0.0, 1.0, false)
-                    }
+                        ClipLocation::Audio(idx) => (track.audio_clips[idx].offset_beats, false),
+                    })
                 };
 
-                if is_midi {
+                if let Some((start_offset, is_midi)) = slip {
                     self.timeline_interaction = Some(TimelineInteraction::SlipContent {
                         clip_id,
-                        start_offset: start_offset.rem_euclid(content_len),
+                        start_offset,
                         start_mouse_beat: start_beat_under_mouse,
+                        is_midi,
                     });
                     return;
                 }
-                // only for midi
             }
 
             // Resize edges
@@ -1132,15 +2023,93 @@ impl TimelineView {
                     .map(|(_, start)| *start)
                     .unwrap_or(start_beat_under_mouse);
 
+                let press_pos = ui
+                    .input(|i| i.pointer.press_origin())
+                    .or(response.interact_pointer_pos())
+                    .unwrap_or(clip_rect.center());
+
                 self.timeline_interaction = Some(TimelineInteraction::DragClip {
                     clip_ids_and_starts: clips_and_starts,
                     start_drag_beat: clicked_clip_start,
                     duplicate_on_drop,
+                    press_pos,
+                    moved: false,
                 });
             }
         }
     }
 
+    /// Razor guide at the snapped cursor beat; a click splits the clips it
+    /// crosses on the hovered track, or on every track with Alt held
+    fn handle_razor(
+        &mut self,
+        pos: egui::Pos2,
+        rect: egui::Rect,
+        ui: &mut egui::Ui,
+        app: &mut super::app::YadawApp,
+    ) {
+        let all_tracks = ui.input(|i| i.modifiers.alt);
+        let hovered = self
+            .last_track_blocks
+            .iter()
+            .find(|(_, r)| r.contains(pos) && pos.y < r.top() + self.track_height)
+            .map(|(id, r)| (*id, *r));
+        if hovered.is_none() && !all_tracks {
+            return;
+        }
+
+        let (beat, _) = self.snap_beat(ui, rect, self.x_to_beat(rect, pos.x), app, None);
+        let x = self.beat_to_x(rect, beat);
+        let (top, bottom) = match hovered {
+            Some((_, r)) if !all_tracks => (r.top(), r.top() + self.track_height),
+            _ => (rect.top(), rect.bottom()),
+        };
+        ui.painter().line_segment(
+            [egui::pos2(x, top), egui::pos2(x, bottom)],
+            egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 90, 90)),
+        );
+        ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+
+        if !ui.input(|i| i.pointer.primary_clicked()) {
+            return;
+        }
+        let splits: Vec<AudioCommand> = {
+            let state = app.state.lock_sync();
+            let inside = |start: f64, len: f64| beat > start && beat < start + len;
+            state
+                .tracks
+                .iter()
+                .filter(|(id, _)| all_tracks || hovered.is_some_and(|(h, _)| h == **id))
+                .flat_map(|(_, t)| {
+                    let audio = t
+                        .audio_clips
+                        .iter()
+                        .filter(|c| inside(c.start_beat, c.length_beats))
+                        .map(|c| AudioCommand::SplitAudioClip {
+                            clip_id: c.id,
+                            position: beat,
+                        });
+                    let midi = t
+                        .midi_clips
+                        .iter()
+                        .filter(|c| inside(c.start_beat, c.length_beats))
+                        .map(|c| AudioCommand::SplitMidiClip {
+                            clip_id: c.id,
+                            position: beat,
+                        });
+                    audio.chain(midi).collect::<Vec<_>>()
+                })
+                .collect()
+        };
+        if splits.is_empty() {
+            return;
+        }
+        app.push_undo();
+        for cmd in splits {
+            let _ = app.command_tx.send(cmd);
+        }
+    }
+
     fn handle_timeline_interaction(
         &mut self,
         response: &egui::Response,
@@ -1160,6 +2129,16 @@ impl TimelineView {
         let ruler_h = 18.0;
         let min_len = (self.grid_snap.max(0.03125)) as f64;
 
+        if self.tool == TimelineTool::Razor
+            && self.timeline_interaction.is_none()
+            && let Some(pos) = ui.input(|i| i.pointer.hover_pos())
+            && rect.contains(pos)
+            && pos.y > rect.top() + ruler_h
+        {
+            self.handle_razor(pos, rect, ui, app);
+            return;
+        }
+
         // Start marquee selection when dragging over clip area (not ruler/automation)
         if response.drag_started() && self.timeline_interaction.is_none() {
             if let Some(pos) = response.interact_pointer_pos() {
@@ -1246,10 +2225,17 @@ impl TimelineView {
                 self.auto_pan_during_drag(response.rect, pos.x);
             }
 
+            if let Some(TimelineInteraction::DragClip {
+                press_pos, moved, ..
+            }) = &mut self.timeline_interaction
+            {
+                *moved |= Self::drag_past_threshold(*press_pos, pos, app);
+            }
+
             // For cross-track moves
             if matches!(
                 self.timeline_interaction,
-                Some(TimelineInteraction::DragClip { .. })
+                Some(TimelineInteraction::DragClip { moved: true, .. })
             ) {
                 self.drag_target_track = self
                     .last_track_blocks
@@ -1295,10 +2281,15 @@ impl TimelineView {
             {
                 if let Some(interaction) = self.timeline_interaction.clone() {
                     match interaction {
+                        // Released without real movement: it was a click, keep the clip put
+                        TimelineInteraction::DragClip {
+                            press_pos, moved, ..
+                        } if !moved && !Self::drag_past_threshold(press_pos, pos, app) => {}
                         TimelineInteraction::DragClip {
                             clip_ids_and_starts,
                             start_drag_beat,
                             duplicate_on_drop,
+                            ..
                         } => {
                             // Compute snapped delta
                             let current = self.x_to_beat(response.rect, pos.x);
@@ -1336,21 +2327,35 @@ impl TimelineView {
                                 });
 
                             // Cache destination track snapshot once
-                            let (dest_is_midi, dest_clips_audio, dest_clips_midi) = {
+                            let (dest_type, dest_clips_audio, dest_clips_midi) = {
                                 let st = app.state.lock_sync();
                                 if let Some(t) = st.tracks.get(&dest_track_id) {
                                     (
-                                        matches!(
-                                            t.track_type,
-                                            crate::model::track::TrackType::Midi
-                                        ),
+                                        Some(t.track_type),
                                         t.audio_clips.clone(),
                                         t.midi_clips.clone(),
                                     )
                                 } else {
-                                    (false, Vec::new(), Vec::new())
+                                    (None, Vec::new(), Vec::new())
                                 }
                             };
+                            let dest_accepts = |is_midi: bool| match dest_type {
+                                Some(TrackType::Midi) => is_midi,
+                                Some(TrackType::Audio) => !is_midi,
+                                _ => false,
+                            };
+                            let drop_behavior = app.config.behavior.incompatible_clip_drop;
+                            let overlap_behavior = {
+                                use crate::config::ClipOverlapDrop;
+                                let preferred = app.config.behavior.clip_overlap_drop;
+                                match (ui.input(|i| i.modifiers.alt), preferred) {
+                                    (false, _) => preferred,
+                                    (true, ClipOverlapDrop::Layer) => ClipOverlapDrop::PunchOut,
+                                    (true, _) => ClipOverlapDrop::Layer,
+                                }
+                            };
+                            let mut created_track: Option<u64> = None;
+                            let mut rejected = 0usize;
 
                             // Build a set of dragged clip IDs so we don’t punch-out ourselves
                             let sel_ids: std::collections::HashSet<u64> =
@@ -1394,7 +2399,48 @@ impl TimelineView {
                                     cmds
                                 };
 
+                            // Helper: fade destination audio clips across the overlap with
+                            // [start, end); returns the fades the dropped clip needs in turn.
+                            // Contained overlaps are left layered.
+                            let build_crossfade_commands = |start: f64, end: f64| {
+                                let mut cmds = Vec::new();
+                                let mut fade_in: Option<f64> = None;
+                                let mut fade_out: Option<f64> = None;
+                                for c in &dest_clips_audio {
+                                    if sel_ids.contains(&c.id) {
+                                        continue;
+                                    }
+                                    let c_start = c.start_beat;
+                                    let c_end = c_start + c.length_beats;
+                                    let overlap = end.min(c_end) - start.max(c_start);
+                                    // A clip inside the other has no edge to fade across
+                                    let contained = (c_start <= start && c_end >= end)
+                                        || (c_start >= start && c_end <= end);
+                                    if overlap <= 0.0 || contained {
+                                        continue;
+                                    }
+                                    if c_start < start {
+                                        // Existing clip runs into the dropped one
+                                        cmds.push(AudioCommand::SetAudioClipFadeOut(
+                                            c.id,
+                                            Some(c_end.min(end) - start),
+                                        ));
+                                        fade_in = Some(fade_in.unwrap_or(0.0).max(overlap));
+                                    } else {
+                                        cmds.push(AudioCommand::SetAudioClipFadeIn(
+                                            c.id,
+                                            Some(end.min(c_end) - c_start),
+                                        ));
+                                        fade_out = Some(fade_out.unwrap_or(0.0).max(overlap));
+                                    }
+                                }
+                                (cmds, fade_in, fade_out)
+                            };
+
+                            // One undo entry for the drop, whatever the commands push
+                            app.begin_undo_batch();
                             let mut pushed_undo = false;
+                            let mut rippled: Vec<(u64, f64)> = Vec::new();
 
                             // For each dragged clip: compute new window, cut-in any overlapped region first, then move/duplicate
                             for (clip_id, original_start) in clip_ids_and_starts.iter().copied() {
@@ -1426,15 +2472,20 @@ impl TimelineView {
                                     }
                                 };
 
-                                // Skip incompatible destination track
-                                if dest_is_midi != is_midi {
-                                    continue;
-                                }
-
                                 let new_start = (original_start + delta).max(0.0);
                                 let new_end = new_start + length_beats.max(0.0);
 
+                                // Incompatible destination: reject, or land on a new track
+                                let compatible = dest_accepts(is_midi);
+                                if !compatible
+                                    && drop_behavior == crate::config::IncompatibleClipDrop::Reject
+                                {
+                                    rejected += 1;
+                                    continue;
+                                }
+
                                 let is_noop_move = !duplicate_on_drop
+                                    && compatible
                                     && dest_track_id == src_track_id
                                     && (new_start - original_start).abs() <= f64::EPSILON;
                                 if is_noop_move {
@@ -1446,11 +2497,86 @@ impl TimelineView {
                                     pushed_undo = true;
                                 }
 
-                                // 1) Default UX: Punch-out overlapped regions on destination
-                                for cmd in build_punch_out_commands(new_start, new_end, is_midi) {
-                                    let _ = app.command_tx.send(cmd);
+                                // Fades the moved clip needs for a crossfade, restored on the
+                                // source after a duplicate so only the copy carries them
+                                let mut moved_fades: Option<(Option<f64>, Option<f64>)> = None;
+
+                                // Rippled moves carry what follows along, so layer over the rest
+                                let ripples = self.ripple_mode != RippleMode::Off
+                                    && !duplicate_on_drop
+                                    && compatible
+                                    && dest_track_id == src_track_id;
+                                if ripples {
+                                    rippled.push((src_track_id, original_start));
                                 }
 
+                                let dest_track_id = if compatible {
+                                    // 1) Punch out, crossfade or layer over clips on destination
+                                    match if ripples {
+                                        crate::config::ClipOverlapDrop::Layer
+                                    } else {
+                                        overlap_behavior
+                                    } {
+                                        crate::config::ClipOverlapDrop::PunchOut => {
+                                            for cmd in build_punch_out_commands(
+                                                new_start, new_end, is_midi,
+                                            ) {
+                                                let _ = app.command_tx.send(cmd);
+                                            }
+                                        }
+                                        crate::config::ClipOverlapDrop::Crossfade if !is_midi => {
+                                            let (cmds, fade_in, fade_out) =
+                                                build_crossfade_commands(new_start, new_end);
+                                            for cmd in cmds {
+                                                let _ = app.command_tx.send(cmd);
+                                            }
+                                            if fade_in.is_some() || fade_out.is_some() {
+                                                let st = app.state.lock_sync();
+                                                let original = st.find_clip(clip_id).and_then(
+                                                    |(track, loc)| match loc {
+                                                        ClipLocation::Audio(idx) => track
+                                                            .audio_clips
+                                                            .get(idx)
+                                                            .map(|c| (c.fade_in, c.fade_out)),
+                                                        ClipLocation::Midi(_) => None,
+                                                    },
+                                                );
+                                                drop(st);
+                                                if let Some(original) = original {
+                                                    if let Some(f) = fade_in {
+                                                        let _ = app.command_tx.send(
+                                                            AudioCommand::SetAudioClipFadeIn(
+                                                                clip_id,
+                                                                Some(f),
+                                                            ),
+                                                        );
+                                                    }
+                                                    if let Some(f) = fade_out {
+                                                        let _ = app.command_tx.send(
+                                                            AudioCommand::SetAudioClipFadeOut(
+                                                                clip_id,
+                                                                Some(f),
+                                                            ),
+                                                        );
+                                                    }
+                                                    moved_fades = Some(original);
+                                                }
+                                            }
+                                        }
+                                        _ => {}
+                                    }
+                                    dest_track_id
+                                } else {
+                                    *created_track.get_or_insert_with(|| {
+                                        let kind = if is_midi {
+                                            crate::track_manager::UITrackType::Midi
+                                        } else {
+                                            crate::track_manager::UITrackType::Audio
+                                        };
+                                        app.insert_track_after(kind, dest_track_id)
+                                    })
+                                };
+
                                 // 2) Move or duplicate onto destination
                                 let cmd = if duplicate_on_drop {
                                     if is_midi {
@@ -1489,22 +2615,45 @@ impl TimelineView {
                                 };
                                 let _ = app.command_tx.send(cmd);
 
-                                let bpm = app.audio_state.bpm.load();
-
-                                if !is_midi && self.auto_crossfade_on_overlap {
-                                    // ~20ms in beats (at current BPM). You can tune this.
-                                    let fade_beats = 0.02f64 * (bpm as f64 / 60.0);
-                                    let _ = app.command_tx.send(AudioCommand::SetAudioClipFadeIn(
-                                        clip_id,
-                                        Some(fade_beats),
-                                    ));
-                                    let _ = app.command_tx.send(AudioCommand::SetAudioClipFadeOut(
-                                        clip_id,
-                                        Some(fade_beats),
-                                    ));
+                                if duplicate_on_drop && let Some((fade_in, fade_out)) = moved_fades
+                                {
+                                    let _ = app
+                                        .command_tx
+                                        .send(AudioCommand::SetAudioClipFadeIn(clip_id, fade_in));
+                                    let _ = app
+                                        .command_tx
+                                        .send(AudioCommand::SetAudioClipFadeOut(clip_id, fade_out));
+                                }
+
+                                if !is_midi
+                                    && self.auto_crossfade_on_overlap
+                                    && moved_fades.is_none()
+                                {
+                                    let _ = app
+                                        .command_tx
+                                        .send(AudioCommand::CrossfadeOverlaps { clip_id });
                                 }
                             }
 
+                            let moved: Vec<u64> = sel_ids.iter().copied().collect();
+                            for cmd in self.ripple_commands(&rippled, delta, &moved) {
+                                let _ = app.command_tx.send(cmd);
+                            }
+
+                            if rejected > 0 {
+                                let dest_kind = match dest_type {
+                                    Some(TrackType::Midi) => "a MIDI track",
+                                    Some(TrackType::Audio) => "an audio track",
+                                    Some(TrackType::Bus) => "a bus",
+                                    None => "that track",
+                                };
+                                app.dialogs.show_message(&format!(
+                                    "{rejected} clip(s) left in place: they can't be placed on {dest_kind}. \
+                                     Change this in Preferences > Behavior to create a matching track instead."
+                                ));
+                            }
+
+                            app.end_undo_batch();
                             let _ = app.command_tx.send(AudioCommand::UpdateTracks);
                         }
                         TimelineInteraction::ResizeClipLeft {
@@ -1575,9 +2724,14 @@ impl TimelineView {
                             clip_id,
                             start_offset,
                             start_mouse_beat,
+                            is_midi,
                         } => {
                             let delta = self.x_to_beat(rect, pos.x) - start_mouse_beat;
-                            let new_off = start_offset + delta;
+                            let new_off = if is_midi {
+                                start_offset + delta
+                            } else {
+                                start_offset - delta
+                            };
                             let _ = app.command_tx.send(AudioCommand::SetClipContentOffset {
                                 clip_id,
                                 new_offset: new_off,
@@ -1664,22 +2818,35 @@ impl TimelineView {
             self.drag_target_track = None;
         }
 
+        let ruler_beat = |x: f32| {
+            let beat = self.x_to_beat(response.rect, x);
+            if self.grid_snap > 0.0 {
+                (beat / self.grid_snap as f64).round() * self.grid_snap as f64
+            } else {
+                beat
+            }
+            .max(0.0)
+        };
+
+        // Double-click the ruler to drop a marker, right-click one to remove it
+        if ruler_resp.double_clicked()
+            && let Some(pos) = ruler_resp.interact_pointer_pos()
+        {
+            app.add_marker(ruler_beat(pos.x));
+            return;
+        }
+        if ruler_resp.secondary_clicked()
+            && let Some(pos) = ruler_resp.interact_pointer_pos()
+            && let Some(idx) = self.marker_near(response.rect, pos.x, app)
+        {
+            app.remove_marker(idx);
+            return;
+        }
+
         // Click on ruler to set playhead
         if ruler_resp.clicked() {
             if let Some(pos) = ruler_resp.interact_pointer_pos() {
-                let mut beat = self.x_to_beat(response.rect, pos.x);
-                beat = if self.grid_snap > 0.0 {
-                    (beat / self.grid_snap as f64).round() * self.grid_snap as f64
-                } else {
-                    beat
-                }
-                .max(0.0);
-                let sr = app.audio_state.sample_rate.load() as f64;
-                let bpm = app.audio_state.bpm.load() as f64;
-                if bpm > 0.0 && sr > 0.0 {
-                    let samples = beat * (60.0 / bpm) * sr;
-                    let _ = app.command_tx.send(AudioCommand::SetPosition(samples));
-                }
+                app.seek_to_beat(ruler_beat(pos.x));
                 return;
             }
         }
@@ -1764,11 +2931,13 @@ impl TimelineView {
                 ui.set_clip_rect(header_rect);
                 ui.horizontal(|ui| {
                     // Clear “this lane belongs to this track + param”
-                    let param_label = match &track.automation_lanes[lane_idx].parameter {
-                        AutomationTarget::TrackVolume => "Volume",
-                        AutomationTarget::TrackPan => "Pan",
-                        AutomationTarget::TrackSend(_) => "Send",
-                        AutomationTarget::PluginParam { param_name, .. } => param_name.as_str(),
+                    let lane = &track.automation_lanes[lane_idx];
+                    let param_label = match &lane.parameter {
+                        AutomationTarget::TrackVolume => "Volume".to_string(),
+                        AutomationTarget::TrackPan => "Pan".to_string(),
+                        AutomationTarget::TrackSend(_) => "Send".to_string(),
+                        AutomationTarget::PluginParam { param_name, .. } => param_name.clone(),
+                        AutomationTarget::MidiCc(cc) => format!("CC {cc}"),
                     };
 
                     ui.label(
@@ -1790,23 +2959,15 @@ impl TimelineView {
                             .unwrap_or(AutomationMode::Read)
                     };
 
-                    let mode = current_mode;
-                    // egui::ComboBox::from_id_salt(("auto_mode", track_id, lane_idx as u64))
-                    //     .width(90.0)
-                    //     .selected_text(match mode {
-                    //         AutomationMode::Off => "Off",
-                    //         AutomationMode::Read => "Read",
-                    //         AutomationMode::Write => "Write",
-                    //         AutomationMode::Touch => "Touch",
-                    //         AutomationMode::Latch => "Latch",
-                    //     })
-                    //     .show_ui(ui, |ui| {
-                    //         ui.selectable_value(&mut mode, AutomationMode::Read, "Read");
-                    //         ui.selectable_value(&mut mode, AutomationMode::Write, "Write");
-                    //         ui.selectable_value(&mut mode, AutomationMode::Touch, "Touch");
-                    //         ui.selectable_value(&mut mode, AutomationMode::Latch, "Latch");
-                    //         ui.selectable_value(&mut mode, AutomationMode::Off, "Off");
-                    //     }); //TODO
+                    let mut mode = current_mode;
+                    egui::ComboBox::from_id_salt(("auto_mode", track_id, lane_idx as u64))
+                        .width(90.0)
+                        .selected_text(mode.name())
+                        .show_ui(ui, |ui| {
+                            for m in AutomationMode::ALL {
+                                ui.selectable_value(&mut mode, m, m.name());
+                            }
+                        });
 
                     if mode != current_mode {
                         let _ = app
@@ -1816,6 +2977,32 @@ impl TimelineView {
 
                     ui.add_space(4.0);
 
+                    // CC lanes already listen to their own controller
+                    if !matches!(lane.parameter, AutomationTarget::MidiCc(_)) {
+                        let learn_text = match (lane.learning, lane.cc_binding) {
+                            (true, _) => "Move a control…".to_string(),
+                            (false, Some(cc)) => format!("CC {cc}"),
+                            (false, None) => "MIDI Learn".to_string(),
+                        };
+                        let learn = ui
+                            .selectable_label(lane.learning, learn_text)
+                            .on_hover_text(
+                                "Bind a controller CC to this lane; right-click to unbind",
+                            );
+                        if learn.clicked() {
+                            let _ = app.command_tx.send(AudioCommand::MidiLearnAutomation(
+                                track_id,
+                                lane_idx,
+                                !lane.learning,
+                            ));
+                        }
+                        if learn.secondary_clicked() && lane.cc_binding.is_some() {
+                            let _ = app
+                                .command_tx
+                                .send(AudioCommand::SetAutomationCc(track_id, lane_idx, None));
+                        }
+                    }
+
                     if ui
                         .button("Clear")
                         .on_hover_text("Clear all automation points in this lane")
@@ -1826,6 +3013,16 @@ impl TimelineView {
                             .send(AudioCommand::ClearAutomationLane(track_id, lane_idx));
                     }
 
+                    if ui
+                        .button("Hide")
+                        .on_hover_text("Collapse this lane; Expand All brings it back")
+                        .clicked()
+                    {
+                        let _ = app.command_tx.send(AudioCommand::SetAutomationLaneVisible(
+                            track_id, lane_idx, false,
+                        ));
+                    }
+
                     if ui.button("Remove lane").clicked() {
                         app.push_undo();
                         let _ = app
@@ -1843,11 +3040,40 @@ impl TimelineView {
             self.automation_hit_regions.push(lane_rect);
 
             while self.automation_widgets.len() <= lane_idx {
-                self.automation_widgets.push(AutomationLaneWidget);
+                self.automation_widgets
+                    .push(AutomationLaneWidget::default());
             }
 
             let id_ns = ui.id().with(("lane", track_id, lane_idx as u64));
 
+            // Keyboard edits go to automation while the pointer is over a lane
+            if ui.rect_contains_pointer(curve_rect) {
+                app.active_edit_target = super::app::ActiveEditTarget::Automation;
+                self.automation_focus = Some((track_id, lane_idx));
+
+                // Snap guide for the point that a click or drag would place
+                if let Some(pos) = ui.ctx().pointer_latest_pos() {
+                    let candidate = self.x_to_beat(curve_rect, pos.x).max(0.0);
+                    if let (_, Some(b)) = self.snap_beat(ui, curve_rect, candidate, app, None) {
+                        let x = self.beat_to_x(curve_rect, b);
+                        ui.painter_at(curve_rect).line_segment(
+                            [
+                                egui::pos2(x, curve_rect.top()),
+                                egui::pos2(x, curve_rect.bottom()),
+                            ],
+                            egui::Stroke::new(1.0, egui::Color32::from_rgb(100, 150, 255)),
+                        );
+                    }
+                }
+            }
+
+            let selected_beats = self
+                .automation_selection
+                .as_ref()
+                .filter(|s| s.track_id == track_id && s.lane_idx == lane_idx)
+                .map(|s| s.beats.clone())
+                .unwrap_or_default();
+
             let actions = self.automation_widgets[lane_idx].ui(
                 ui,
                 &track.automation_lanes[lane_idx],
@@ -1855,29 +3081,96 @@ impl TimelineView {
                 self.zoom_x,
                 self.scroll_x,
                 id_ns,
+                &selected_beats,
+                self.snap_enabled && !ui.input(|i| i.modifiers.shift),
+                app.input_manager.take_long_press_in(curve_rect),
             );
 
+            if let Some((start, end)) = self
+                .automation_selection
+                .as_ref()
+                .filter(|s| s.track_id == track_id && s.lane_idx == lane_idx)
+                .and_then(|s| s.range)
+            {
+                let range_rect = egui::Rect::from_x_y_ranges(
+                    self.beat_to_x(curve_rect, start)..=self.beat_to_x(curve_rect, end),
+                    curve_rect.y_range(),
+                );
+                ui.painter_at(curve_rect).rect_filled(
+                    range_rect,
+                    0.0,
+                    egui::Color32::from_rgba_unmultiplied(100, 150, 255, 40),
+                );
+            }
+
             let mut pushed_undo_for_move = false;
             for action in actions {
                 match action {
                     AutomationAction::AddPoint { beat, value } => {
-                        app.push_undo();
+                        let (beat, _) = self.snap_beat(ui, curve_rect, beat.max(0.0), app, None);
+                        self.automation_selection = None;
                         let target = track.automation_lanes[lane_idx].parameter.clone();
-                        let _ = app.command_tx.send(AudioCommand::AddAutomationPoint(
-                            track_id, target, beat, value,
-                        ));
+                        app.with_undo_batch(|app| {
+                            app.push_undo();
+                            let _ = app.command_tx.send(AudioCommand::AddAutomationPoint(
+                                track_id,
+                                target,
+                                beat,
+                                value,
+                                app.config.ui.automation_lane_height,
+                            ));
+                        });
                     }
                     AutomationAction::RemovePoint(beat) => {
-                        app.push_undo();
-                        let _ = app.command_tx.send(AudioCommand::RemoveAutomationPoint(
-                            track_id, lane_idx, beat,
-                        ));
+                        if let Some(sel) = self.automation_selection.as_mut() {
+                            sel.beats.retain(|b| (b - beat).abs() >= 0.001);
+                        }
+                        app.with_undo_batch(|app| {
+                            app.push_undo();
+                            let _ = app.command_tx.send(AudioCommand::RemoveAutomationPoint(
+                                track_id, lane_idx, beat,
+                            ));
+                        });
+                    }
+                    AutomationAction::SelectPoint { beat, additive } => {
+                        self.select_automation_point(track_id, lane_idx, beat, additive);
+                    }
+                    AutomationAction::SelectRange { from, to } => {
+                        let (a, _) = self.snap_beat(ui, curve_rect, from.max(0.0), app, None);
+                        let (b, _) = self.snap_beat(ui, curve_rect, to.max(0.0), app, None);
+                        let (start, end) = (a.min(b), a.max(b));
+                        let beats = track.automation_lanes[lane_idx]
+                            .points
+                            .iter()
+                            .map(|p| p.beat)
+                            .filter(|&beat| beat >= start && beat <= end)
+                            .collect();
+                        self.automation_selection = Some(AutomationSelection {
+                            track_id,
+                            lane_idx,
+                            beats,
+                            range: Some((start, end)),
+                        });
                     }
                     AutomationAction::MovePoint {
                         old_beat,
                         new_beat,
                         new_value,
                     } => {
+                        let (new_beat, _) =
+                            self.snap_beat(ui, curve_rect, new_beat.max(0.0), app, None);
+                        // Keep the moved point selected
+                        if let Some(sel) = self.automation_selection.as_mut()
+                            && sel.track_id == track_id
+                            && sel.lane_idx == lane_idx
+                        {
+                            sel.range = None;
+                            for b in sel.beats.iter_mut() {
+                                if (*b - old_beat).abs() < 0.001 {
+                                    *b = new_beat;
+                                }
+                            }
+                        }
                         if !pushed_undo_for_move {
                             app.push_undo();
                             pushed_undo_for_move = true;
@@ -1890,6 +3183,23 @@ impl TimelineView {
                             new_value,
                         });
                     }
+                    AutomationAction::SetCurve {
+                        beat,
+                        curve,
+                        tension,
+                        started,
+                    } => {
+                        if started {
+                            app.push_undo();
+                        }
+                        let _ = app.command_tx.send(AudioCommand::SetAutomationCurve {
+                            track_id,
+                            lane_idx,
+                            beat,
+                            curve,
+                            tension,
+                        });
+                    }
                 }
             }
 
@@ -1897,6 +3207,105 @@ impl TimelineView {
         }
     }
 
+    fn select_automation_point(
+        &mut self,
+        track_id: u64,
+        lane_idx: usize,
+        beat: f64,
+        additive: bool,
+    ) {
+        match self.automation_selection.as_mut() {
+            Some(sel) if additive && sel.track_id == track_id && sel.lane_idx == lane_idx => {
+                sel.range = None;
+                if let Some(pos) = sel.beats.iter().position(|b| (b - beat).abs() < 0.001) {
+                    sel.beats.remove(pos);
+                } else {
+                    sel.beats.push(beat);
+                }
+            }
+            _ => {
+                self.automation_selection = Some(AutomationSelection {
+                    track_id,
+                    lane_idx,
+                    beats: vec![beat],
+                    range: None,
+                });
+            }
+        }
+    }
+
+    pub fn take_automation_selection(&mut self) -> Option<AutomationSelection> {
+        self.automation_selection.take()
+    }
+
+    pub fn automation_selection(&self) -> Option<&AutomationSelection> {
+        self.automation_selection.as_ref()
+    }
+
+    pub fn select_automation(&mut self, selection: AutomationSelection) {
+        self.automation_selection = Some(selection);
+    }
+
+    pub fn automation_focus(&self) -> Option<(u64, usize)> {
+        self.automation_focus
+    }
+
+    /// Commands closing up after edits that moved material at `from_beat` on
+    /// a track by `delta`, per the ripple mode. Clips in `exclude` stay put.
+    pub fn ripple_commands(
+        &self,
+        edits: &[(u64, f64)],
+        delta: f64,
+        exclude: &[u64],
+    ) -> Vec<AudioCommand> {
+        if self.ripple_mode == RippleMode::Off || delta == 0.0 {
+            return Vec::new();
+        }
+        let mut origins: Vec<(Option<u64>, f64)> = Vec::new();
+        for &(track_id, beat) in edits {
+            let key = (self.ripple_mode == RippleMode::Track).then_some(track_id);
+            match origins.iter_mut().find(|(k, _)| *k == key) {
+                Some(origin) => origin.1 = origin.1.min(beat),
+                None => origins.push((key, beat)),
+            }
+        }
+        origins
+            .into_iter()
+            .map(|(track_id, from_beat)| AudioCommand::RippleEdit {
+                track_id,
+                from_beat,
+                delta,
+                exclude: exclude.to_vec(),
+                markers: self.ripple_markers,
+            })
+            .collect()
+    }
+
+    /// Commands closing the gaps left by deleting `(track, start, end)` spans
+    pub fn ripple_delete_commands(&self, spans: &[(u64, f64, f64)]) -> Vec<AudioCommand> {
+        // Merge overlapping spans per ripple scope, then close the latest gap
+        // first so earlier gaps still see their own positions
+        let mut spans: Vec<(Option<u64>, f64, f64)> = spans
+            .iter()
+            .map(|&(t, s, e)| ((self.ripple_mode == RippleMode::Track).then_some(t), s, e))
+            .collect();
+        spans.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        let mut merged: Vec<(Option<u64>, f64, f64)> = Vec::new();
+        for (key, start, end) in spans {
+            match merged.last_mut() {
+                Some(last) if last.0 == key && start <= last.2 => last.2 = last.2.max(end),
+                _ => merged.push((key, start, end)),
+            }
+        }
+        merged.sort_by(|a, b| b.1.total_cmp(&a.1));
+        merged
+            .into_iter()
+            .flat_map(|(key, start, end)| {
+                self.ripple_commands(&[(key.unwrap_or_default(), end)], start - end, &[])
+            })
+            .collect()
+    }
+
     fn update_auto_scroll(&mut self, app: &super::app::YadawApp) {
         let position = app.audio_state.get_position();
         let sample_rate = app.audio_state.sample_rate.load();
@@ -1921,6 +3330,10 @@ impl TimelineView {
 
     fn draw_context_menus(&mut self, ui: &mut egui::Ui, app: &mut super::app::YadawApp) {
         if !self.show_clip_menu {
+            // Closing the menu without applying drops the preview
+            if self.quantize_preview.take().is_some() {
+                let _ = app.command_tx.send(AudioCommand::EndQuantizePreview);
+            }
             return;
         }
 
@@ -2015,27 +3428,213 @@ impl TimelineView {
                                     });
                                     close_menu = true;
                                 }
+                                if ui
+                                    .button("Bounce to New Track")
+                                    .on_hover_text(
+                                        "Render the selected clips through the instrument and \
+                                         plugins onto a new audio track and mute them",
+                                    )
+                                    .clicked()
+                                {
+                                    app.bounce_selected_clips();
+                                    close_menu = true;
+                                }
+
+                                ui.separator();
+                                ui.horizontal(|ui| {
+                                    ui.label("Velocity:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.velocity_target)
+                                            .range(1..=127),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    for (label, flatten) in
+                                        [("Normalize", false), ("Set All", true)]
+                                    {
+                                        if ui.button(label).clicked() {
+                                            app.push_undo();
+                                            let _ = app.command_tx.send(
+                                                AudioCommand::NormalizeClipVelocities {
+                                                    clip_id: primary_clip_id,
+                                                    target: self.velocity_target,
+                                                    flatten,
+                                                },
+                                            );
+                                            close_menu = true;
+                                        }
+                                    }
+                                });
+
+                                self.quantize_menu(ui, app, primary_clip_id, &mut close_menu);
+                                self.groove_menu(ui, app, primary_clip_id, &mut close_menu);
                             } else {
-                                let warp_enabled = {
+                                let (warp_enabled, pitch, reversed) = {
                                     let st = app.state.lock_sync();
                                     st.find_clip(primary_clip_id)
                                         .and_then(|(track, loc)| {
                                             if let crate::project::ClipLocation::Audio(idx) = loc {
-                                                track.audio_clips.get(idx).map(|c| c.warp_mode)
+                                                track.audio_clips.get(idx).map(|c| {
+                                                    (c.warp_mode, c.pitch_semitones, c.reversed)
+                                                })
                                             } else {
                                                 None
                                             }
                                         })
-                                        .unwrap_or(false)
+                                        .unwrap_or((false, 0.0, false))
                                 };
 
                                 ui.separator();
+                                let mut reverse = reversed;
+                                if ui.checkbox(&mut reverse, "Reverse").changed() {
+                                    app.reverse_selected();
+                                    close_menu = true;
+                                }
                                 let mut warp_mode = warp_enabled;
                                 if ui.checkbox(&mut warp_mode, "Warp Mode").changed() {
                                     let _ = app
                                         .set_warp_mode_for_audio_clip(primary_clip_id, warp_mode);
                                     close_menu = true;
                                 }
+                                if ui
+                                    .button("Warp to Project Tempo")
+                                    .on_hover_text(
+                                        "Detect the loop's tempo and follow the project tempo, \
+                                         keeping pitch",
+                                    )
+                                    .clicked()
+                                {
+                                    app.warp_audio_clip_to_tempo(primary_clip_id);
+                                    close_menu = true;
+                                }
+                                if ui
+                                    .button("Bounce to New Track")
+                                    .on_hover_text(
+                                        "Render the selected clips through the track's plugins \
+                                         onto a new audio track and mute them",
+                                    )
+                                    .clicked()
+                                {
+                                    app.bounce_selected_clips();
+                                    close_menu = true;
+                                }
+
+                                ui.menu_button("Fades", |ui| {
+                                    let default_ms = app.config.behavior.default_clip_fade_ms;
+                                    let presets = std::iter::once((0.0, "None".to_string()))
+                                        .chain(
+                                            crate::audio_import::FADE_PRESETS_MS
+                                                .iter()
+                                                .map(|&ms| (ms, format!("{ms} ms"))),
+                                        )
+                                        .chain(std::iter::once((
+                                            default_ms,
+                                            format!("Default ({default_ms} ms)"),
+                                        )));
+                                    for (ms, label) in presets {
+                                        if ui.button(label).clicked() {
+                                            app.set_fades_for_selected(ms);
+                                            close_menu = true;
+                                            ui.close();
+                                        }
+                                    }
+                                })
+                                .response
+                                .on_hover_text(
+                                    "Fade both ends of the selected clips; the default is set \
+                                     in Preferences",
+                                );
+
+                                ui.separator();
+                                self.clip_pitch_controls(ui, app, primary_clip_id, pitch);
+                                if ui
+                                    .button("Crossfade Overlaps")
+                                    .on_hover_text(
+                                        "Fade between this clip and the clips it overlaps",
+                                    )
+                                    .clicked()
+                                {
+                                    app.push_undo();
+                                    let _ = app.command_tx.send(AudioCommand::CrossfadeOverlaps {
+                                        clip_id: primary_clip_id,
+                                    });
+                                    close_menu = true;
+                                }
+                            }
+
+                            let loop_state = {
+                                let st = app.state.lock_sync();
+                                st.find_clip(primary_clip_id)
+                                    .and_then(|(track, loc)| match loc {
+                                        ClipLocation::Midi(idx) => track
+                                            .midi_clips
+                                            .get(idx)
+                                            .map(|c| (c.loop_enabled, c.loop_mode, None)),
+                                        ClipLocation::Audio(idx) => {
+                                            track.audio_clips.get(idx).map(|c| {
+                                                (
+                                                    c.loop_enabled,
+                                                    c.loop_mode,
+                                                    Some(c.loop_crossfade),
+                                                )
+                                            })
+                                        }
+                                    })
+                            };
+                            if let Some((loop_enabled, loop_mode, crossfade)) = loop_state {
+                                ui.separator();
+                                let mut looping = loop_enabled;
+                                if ui.checkbox(&mut looping, "Loop Clip").changed() {
+                                    app.push_undo();
+                                    let _ = app.command_tx.send(AudioCommand::ToggleClipLoop {
+                                        clip_id: primary_clip_id,
+                                        enabled: looping,
+                                    });
+                                    close_menu = true;
+                                }
+                                ui.add_enabled_ui(loop_enabled, |ui| {
+                                    for mode in ClipLoopMode::ALL {
+                                        if ui.radio(loop_mode == mode, mode.name()).clicked()
+                                            && mode != loop_mode
+                                        {
+                                            app.push_undo();
+                                            let _ = app.command_tx.send(
+                                                AudioCommand::SetClipLoopMode {
+                                                    clip_id: primary_clip_id,
+                                                    mode,
+                                                },
+                                            );
+                                            close_menu = true;
+                                        }
+                                    }
+                                });
+                                if let Some(mut beats) = crossfade {
+                                    ui.add_enabled_ui(
+                                        loop_enabled && loop_mode != ClipLoopMode::PingPong,
+                                        |ui| {
+                                            ui.horizontal(|ui| {
+                                                ui.label("Loop Crossfade:");
+                                                let resp = ui.add(
+                                                    egui::DragValue::new(&mut beats)
+                                                        .range(0.0..=4.0)
+                                                        .speed(0.01)
+                                                        .suffix(" beats"),
+                                                );
+                                                if resp.drag_started() || resp.gained_focus() {
+                                                    app.push_undo();
+                                                }
+                                                if resp.changed() {
+                                                    let _ = app.command_tx.send(
+                                                        AudioCommand::SetClipLoopCrossfade {
+                                                            clip_id: primary_clip_id,
+                                                            beats,
+                                                        },
+                                                    );
+                                                }
+                                            });
+                                        },
+                                    );
+                                }
                             }
                         }
                     })
@@ -2058,6 +3657,150 @@ impl TimelineView {
         }
     }
 
+    fn quantize_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        app: &mut super::app::YadawApp,
+        clip_id: u64,
+        close_menu: &mut bool,
+    ) {
+        if self.quantize_preview.is_none_or(|p| p.clip_id != clip_id) {
+            let st = app.state.lock_sync();
+            self.quantize_preview = match st.find_clip(clip_id) {
+                Some((track, ClipLocation::Midi(idx))) => {
+                    track.midi_clips.get(idx).map(QuantizePreview::from_clip)
+                }
+                _ => None,
+            };
+        }
+        let Some(preview) = self.quantize_preview.as_mut() else {
+            return;
+        };
+
+        ui.separator();
+        ui.label("Quantize");
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            for (label, grid) in QuantizePreview::GRIDS {
+                changed |= ui
+                    .selectable_value(&mut preview.grid, grid, label)
+                    .changed();
+            }
+        });
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut preview.strength, 0.0..=1.0)
+                    .text("Strength")
+                    .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut preview.swing, 0.0..=1.0)
+                    .text("Swing")
+                    .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+            )
+            .changed();
+
+        let preview = *preview;
+        if changed {
+            let _ = app.command_tx.send(AudioCommand::PreviewQuantize {
+                clip_id,
+                grid: preview.grid,
+                strength: preview.strength,
+                swing: preview.swing,
+            });
+        }
+
+        ui.horizontal(|ui| {
+            for (label, enabled) in [("Apply", true), ("Off", false)] {
+                if ui.button(label).clicked() {
+                    app.push_undo();
+                    let _ = app.command_tx.send(AudioCommand::SetClipQuantize {
+                        clip_id,
+                        grid: preview.grid,
+                        strength: preview.strength,
+                        swing: preview.swing,
+                        enabled,
+                    });
+                    // The stored settings now drive playback, no preview to undo
+                    self.quantize_preview = None;
+                    *close_menu = true;
+                }
+            }
+        });
+    }
+
+    fn groove_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        app: &mut super::app::YadawApp,
+        clip_id: u64,
+        close_menu: &mut bool,
+    ) {
+        ui.separator();
+        ui.label("Groove");
+
+        let grid = self.quantize_preview.map_or(0.25, |p| p.grid);
+        if ui
+            .button("Extract Groove")
+            .on_hover_text("Capture this clip's timing on the quantize grid")
+            .clicked()
+        {
+            app.push_undo();
+            let _ = app
+                .command_tx
+                .send(AudioCommand::ExtractGroove { clip_id, grid });
+            *close_menu = true;
+        }
+
+        let grooves: Vec<(u64, String)> = app
+            .state
+            .lock_sync()
+            .grooves
+            .iter()
+            .map(|g| (g.id, g.name.clone()))
+            .collect();
+        if grooves.is_empty() {
+            return;
+        }
+        if self
+            .groove_choice
+            .is_none_or(|id| !grooves.iter().any(|(gid, _)| *gid == id))
+        {
+            self.groove_choice = grooves.last().map(|(id, _)| *id);
+        }
+
+        let selected_name = grooves
+            .iter()
+            .find(|(id, _)| Some(*id) == self.groove_choice)
+            .map(|(_, name)| name.as_str())
+            .unwrap_or_default();
+        egui::ComboBox::from_id_salt("groove_choice")
+            .selected_text(selected_name)
+            .show_ui(ui, |ui| {
+                for (id, name) in &grooves {
+                    ui.selectable_value(&mut self.groove_choice, Some(*id), name);
+                }
+            });
+        ui.add(
+            egui::Slider::new(&mut self.groove_strength, 0.0..=1.0)
+                .text("Strength")
+                .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+        );
+        if let Some(groove_id) = self.groove_choice
+            && ui.button("Apply Groove").clicked()
+        {
+            app.push_undo();
+            let _ = app.command_tx.send(AudioCommand::ApplyGroove {
+                clip_id,
+                groove_id,
+                strength: self.groove_strength,
+            });
+            *close_menu = true;
+        }
+    }
+
     pub fn compute_project_end_beats(&self, app: &super::app::YadawApp) -> f64 {
         let state = app.state.lock_sync();
         state
@@ -2084,6 +3827,62 @@ impl TimelineView {
         rect.left() + (beat as f32 * self.zoom_x - self.scroll_x)
     }
 
+    /// Keyboard zoom: keeps the playhead where it is on screen, or the view
+    /// center when the playhead is scrolled out of view.
+    pub fn zoom_around_playhead(&mut self, factor: f32, playhead_beat: f64) {
+        let view =
+            egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(self.last_view_width, 1.0));
+        let playhead_x = self.beat_to_x(view, playhead_beat);
+        let anchor_x = if view.x_range().contains(playhead_x) {
+            playhead_x
+        } else {
+            view.center().x
+        };
+        self.zoom_horiz_around(view, anchor_x, factor);
+    }
+
+    /// Two-finger touch: a sideways pinch zooms time around the centroid, an
+    /// up-down pinch resizes tracks, and a pan scrolls both axes
+    fn handle_touch_gestures(
+        &mut self,
+        ui: &mut egui::Ui,
+        response: &egui::Response,
+        app: &mut super::app::YadawApp,
+    ) {
+        let visible = ui.clip_rect().intersect(response.rect);
+        let gestures = app.input_manager.take_view_gestures_in(visible);
+        if gestures.is_empty() {
+            return;
+        }
+
+        // The first finger may have started a clip drag or selection box
+        self.timeline_interaction = None;
+        self.drag_target_track = None;
+
+        for gesture in gestures {
+            match gesture {
+                GestureAction::PinchZoom {
+                    scale,
+                    center,
+                    vertical: false,
+                } => self.zoom_horiz_around(response.rect, center.x, scale),
+                GestureAction::PinchZoom {
+                    scale,
+                    vertical: true,
+                    ..
+                } => {
+                    self.track_height = (self.track_height * scale)
+                        .clamp(self.min_track_height, self.max_track_height);
+                }
+                GestureAction::Pan { delta, .. } => {
+                    self.scroll_x = (self.scroll_x - delta.x).max(0.0);
+                    ui.scroll_with_delta(egui::vec2(0.0, delta.y));
+                }
+                _ => {}
+            }
+        }
+    }
+
     fn zoom_horiz_around(&mut self, rect: egui::Rect, anchor_x: f32, factor: f32) {
         // keep the beat at anchor_x stable while changing zoom_x
         let anchor_beat = self.x_to_beat(rect, anchor_x);
@@ -2145,6 +3944,12 @@ impl TimelineView {
             candidates.push(app.audio_state.loop_end.load());
         }
 
+        // Markers
+        if self.snap_to_markers {
+            let state = app.state.lock_sync();
+            candidates.extend(state.markers.iter().map(|m| m.beat));
+        }
+
         // Find nearest candidate within pixel threshold
         let thresh_beats = (self.snap_px_threshold / self.zoom_x) as f64;
         let mut best: Option<f64> = None;
@@ -2196,10 +4001,19 @@ impl TimelineView {
         }
     }
 
+    fn drag_past_threshold(
+        press_pos: egui::Pos2,
+        pos: egui::Pos2,
+        app: &super::app::YadawApp,
+    ) -> bool {
+        press_pos.distance(pos) >= app.config.behavior.clip_drag_threshold_px.max(0.0)
+    }
+
     fn draw_drag_ghosts(&self, ui: &mut egui::Ui, app: &super::app::YadawApp, rect: egui::Rect) {
         if let Some(TimelineInteraction::DragClip {
             clip_ids_and_starts,
             start_drag_beat,
+            moved: true,
             ..
         }) = &self.timeline_interaction
         {
@@ -2392,7 +4206,7 @@ impl TimelineView {
             }
         }
 
-        // Alt+Arrows: slip MIDI content
+        // Alt+Arrows: slip clip content
         if mods.alt && (pressed(egui::Key::ArrowLeft) || pressed(egui::Key::ArrowRight)) {
             let slip_delta = if pressed(egui::Key::ArrowLeft) {
                 -step
@@ -2402,15 +4216,16 @@ impl TimelineView {
             app.push_undo();
             for &cid in &app.selected_clips {
                 let st = app.state.lock_sync();
-                if let Some((track, loc)) = st.find_clip(cid) {
-                    if let ClipLocation::Midi(i) = loc {
-                        let offset = track.midi_clips[i].content_offset_beats;
-                        drop(st);
-                        let _ = app.command_tx.send(AudioCommand::SetClipContentOffset {
-                            clip_id: cid,
-                            new_offset: offset + slip_delta,
-                        });
-                    }
+                let new_offset = st.find_clip(cid).map(|(track, loc)| match loc {
+                    ClipLocation::Midi(i) => track.midi_clips[i].content_offset_beats + slip_delta,
+                    ClipLocation::Audio(i) => track.audio_clips[i].offset_beats - slip_delta,
+                });
+                drop(st);
+                if let Some(new_offset) = new_offset {
+                    let _ = app.command_tx.send(AudioCommand::SetClipContentOffset {
+                        clip_id: cid,
+                        new_offset,
+                    });
                 }
             }
         }
@@ -2425,8 +4240,17 @@ impl TimelineView {
             };
             app.push_undo();
 
+            // Step through the rows as drawn: folders regroup their members
+            // and collapsed ones hide them, filtered-out tracks are skipped
+            let order: Vec<u64> = app
+                .track_rows()
+                .into_iter()
+                .filter_map(|row| match row {
+                    TrackRow::Track { id, .. } => Some(id),
+                    TrackRow::Folder(_) => None,
+                })
+                .collect();
             let st = app.state.lock_sync();
-            let order = &st.track_order;
             if let Some(&first_cid) = app.selected_clips.first() {
                 if let Some(clip_ref) = st.clips_by_id.get(&first_cid) {
                     if let Some(cur_ix) = order.iter().position(|&tid| tid == clip_ref.track_id) {
@@ -2548,3 +4372,239 @@ impl Default for TimelineView {
         Self::new()
     }
 }
+
+/// Height of one take lane row
+const TAKE_LANE_H: f32 = 28.0;
+
+/// Takes laid out for the lanes: row `n` holds the `n`th pass of every region
+fn take_lane_rows(track: &Track) -> Vec<Vec<&AudioClip>> {
+    let mut rows: Vec<Vec<&AudioClip>> = Vec::new();
+    let mut seen: Vec<(u64, usize)> = Vec::new();
+    for take in track.all_takes() {
+        let region = take.take_region.unwrap_or_default();
+        let row = match seen.iter_mut().find(|(r, _)| *r == region) {
+            Some((_, count)) => {
+                *count += 1;
+                *count - 1
+            }
+            None => {
+                seen.push((region, 1));
+                0
+            }
+        };
+        if rows.len() <= row {
+            rows.push(Vec::new());
+        }
+        rows[row].push(take);
+    }
+    rows
+}
+
+/// Hatched body for a clip whose audio file wasn't found
+fn draw_missing_media(
+    painter: &egui::Painter,
+    clip_rect: egui::Rect,
+    clip: &AudioClip,
+    color: egui::Color32,
+) {
+    const SPACING: f32 = 10.0;
+    let visible = painter.clip_rect().intersect(clip_rect);
+    let painter = painter.with_clip_rect(visible);
+    let stroke = egui::Stroke::new(1.0, color.gamma_multiply(0.25));
+    let h = clip_rect.height();
+    // Start from the first line that reaches the visible part; long clips can be mostly offscreen
+    let skipped = ((visible.left() - clip_rect.left()) / SPACING)
+        .floor()
+        .max(0.0);
+    let mut x = clip_rect.left() - h + skipped * SPACING;
+    while x < visible.right() {
+        painter.line_segment(
+            [
+                egui::pos2(x, clip_rect.bottom()),
+                egui::pos2(x + h, clip_rect.top()),
+            ],
+            stroke,
+        );
+        x += SPACING;
+    }
+
+    let file = clip
+        .media_path
+        .as_deref()
+        .and_then(|p| p.rsplit('/').next())
+        .unwrap_or_default();
+    painter.text(
+        clip_rect.left_center() + egui::vec2(4.0, 4.0),
+        egui::Align2::LEFT_CENTER,
+        format!("⚠ Missing: {file}"),
+        egui::FontId::proportional(11.0),
+        egui::Color32::from_rgb(230, 120, 100),
+    );
+}
+
+/// Repetition boundary of a looped clip. Non-forward modes get an arrow
+/// showing which way the following repetition plays.
+fn draw_loop_boundary(
+    painter: &egui::Painter,
+    clip_rect: egui::Rect,
+    x: f32,
+    mode: ClipLoopMode,
+    rep: i64,
+    color: egui::Color32,
+) {
+    painter.line_segment(
+        [
+            egui::pos2(x, clip_rect.top()),
+            egui::pos2(x, clip_rect.bottom()),
+        ],
+        egui::Stroke::new(1.0, color),
+    );
+    if mode == ClipLoopMode::Forward {
+        return;
+    }
+
+    let arrow_color = color.gamma_multiply(2.5);
+    let y = clip_rect.bottom() - 6.0;
+    let points = if mode.is_reversed(rep) {
+        vec![
+            egui::pos2(x + 2.0, y),
+            egui::pos2(x + 8.0, y - 4.0),
+            egui::pos2(x + 8.0, y + 4.0),
+        ]
+    } else {
+        vec![
+            egui::pos2(x + 8.0, y),
+            egui::pos2(x + 2.0, y - 4.0),
+            egui::pos2(x + 2.0, y + 4.0),
+        ]
+    };
+    painter.add(egui::Shape::convex_polygon(
+        points,
+        arrow_color,
+        egui::Stroke::NONE,
+    ));
+}
+
+/// Fade line between `x0` and `x1` across the clip's height, rising for a
+/// fade-in and falling for a fade-out
+fn fade_curve_points(
+    rect: egui::Rect,
+    x0: f32,
+    x1: f32,
+    curve: FadeCurve,
+    rising: bool,
+) -> Vec<egui::Pos2> {
+    const STEPS: usize = 16;
+    (0..=STEPS)
+        .map(|i| {
+            let t = i as f32 / STEPS as f32;
+            let gain = curve.gain(if rising { t } else { 1.0 - t });
+            egui::pos2(x0 + t * (x1 - x0), rect.bottom() - gain * rect.height())
+        })
+        .collect()
+}
+
+/// Right-click menu on a fade handle
+fn fade_curve_menu(
+    ui: &mut egui::Ui,
+    app: &mut super::app::YadawApp,
+    clip_id: u64,
+    fade_in: bool,
+    current: FadeCurve,
+) {
+    ui.label(if fade_in {
+        "Fade In Curve"
+    } else {
+        "Fade Out Curve"
+    });
+    for curve in FadeCurve::ALL {
+        if ui.radio(current == curve, curve.name()).clicked() {
+            if current != curve {
+                app.push_undo();
+                let _ = app.command_tx.send(AudioCommand::SetAudioClipFadeCurve {
+                    clip_id,
+                    fade_in,
+                    curve,
+                });
+            }
+            ui.close();
+        }
+    }
+}
+
+/// Shades a loop crossfade and marks the outgoing and incoming fades.
+fn draw_loop_crossfade(
+    painter: &egui::Painter,
+    clip_rect: egui::Rect,
+    left: f32,
+    right: f32,
+    color: egui::Color32,
+) {
+    if right <= left {
+        return;
+    }
+    let region = egui::Rect::from_x_y_ranges(left..=right, clip_rect.y_range());
+    painter.rect_filled(region, 0.0, color.gamma_multiply(0.5));
+    let stroke = egui::Stroke::new(1.0, color.gamma_multiply(2.0));
+    painter.line_segment([region.left_top(), region.right_bottom()], stroke);
+    painter.line_segment([region.left_bottom(), region.right_top()], stroke);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ripple_edits(commands: Vec<AudioCommand>) -> Vec<(Option<u64>, f64, f64, bool)> {
+        commands
+            .into_iter()
+            .map(|command| match command {
+                AudioCommand::RippleEdit {
+                    track_id,
+                    from_beat,
+                    delta,
+                    exclude,
+                    markers,
+                } => {
+                    assert!(exclude.is_empty());
+                    (track_id, from_beat, delta, markers)
+                }
+                other => panic!("expected a ripple edit, got {other:?}"),
+            })
+            .collect()
+    }
+
+    const SPANS: [(u64, f64, f64); 4] =
+        [(1, 2.0, 4.0), (1, 3.0, 6.0), (2, 1.0, 2.0), (1, 8.0, 9.0)];
+
+    #[test]
+    fn track_ripple_merges_spans_per_track_latest_first() {
+        let mut view = TimelineView::new();
+        view.ripple_mode = RippleMode::Track;
+        assert_eq!(
+            ripple_edits(view.ripple_delete_commands(&SPANS)),
+            [
+                (Some(1), 9.0, -1.0, false),
+                (Some(1), 6.0, -4.0, false),
+                (Some(2), 2.0, -1.0, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_ripple_merges_spans_across_tracks() {
+        let mut view = TimelineView::new();
+        view.ripple_mode = RippleMode::All;
+        view.ripple_markers = true;
+        assert_eq!(
+            ripple_edits(view.ripple_delete_commands(&SPANS)),
+            [(None, 9.0, -1.0, true), (None, 6.0, -5.0, true)]
+        );
+    }
+
+    #[test]
+    fn ripple_off_closes_no_gaps() {
+        let view = TimelineView::new();
+        assert_eq!(view.ripple_mode, RippleMode::Off);
+        assert!(view.ripple_delete_commands(&SPANS).is_empty());
+    }
+}
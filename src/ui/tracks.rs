@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 
+use super::app::TrackRow;
 use super::*;
 use crate::audio_utils::{format_pan, linear_to_db};
+use crate::config::TrackFilter;
 use crate::level_meter::LevelMeter;
 use crate::messages::{AudioCommand, PluginParamInfo};
 use crate::model::PluginDescriptor;
@@ -16,6 +18,12 @@ pub struct TracksPanel {
     show_automation_buttons: bool,
     show_inputs: bool,
     cached_plugin_chains: HashMap<u64, (u64, Vec<PluginDescriptor>)>,
+    /// A/B parameter sets keyed by plugin id, created on the first switch
+    plugin_ab: HashMap<u64, PluginAbSlots>,
+    /// Name typed into a plugin's "Save As" preset field
+    preset_name_draft: String,
+    /// Name typed into a track's "Save as Template" field
+    template_name_draft: String,
 
     dnd_dragging_track: Option<u64>,
     dnd_dragging_from_idx: Option<usize>,
@@ -32,6 +40,9 @@ impl TracksPanel {
             show_automation_buttons: true,
             show_inputs: true,
             cached_plugin_chains: HashMap::new(),
+            plugin_ab: HashMap::new(),
+            preset_name_draft: String::new(),
+            template_name_draft: String::new(),
 
             dnd_dragging_track: None,
             dnd_dragging_from_idx: None,
@@ -59,6 +70,21 @@ impl TracksPanel {
                     .on_hover_text("Show/Hide Automation");
                 ui.toggle_value(&mut self.show_inputs, "🔣")
                     .on_hover_text("Show/Hide Input Options");
+
+                let mut filter = app.config.ui.track_filter;
+                egui::ComboBox::from_id_salt("track_view_filter")
+                    .selected_text(filter.name())
+                    .show_ui(ui, |ui| {
+                        for f in TrackFilter::ALL {
+                            ui.selectable_value(&mut filter, f, f.name());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Only show some tracks; hidden tracks still play");
+                if filter != app.config.ui.track_filter {
+                    app.config.ui.track_filter = filter;
+                    let _ = app.config.save();
+                }
             });
         });
 
@@ -81,6 +107,28 @@ impl TracksPanel {
             if ui.button("➕ Bus").clicked() {
                 app.add_bus_track();
             }
+            ui.menu_button("➕ From Template", |ui| {
+                let templates = crate::presets::list_track_templates();
+                if templates.is_empty() {
+                    ui.label("No templates saved");
+                }
+                for name in templates {
+                    ui.horizontal(|ui| {
+                        if ui.button(&name).clicked() {
+                            app.add_track_from_template(&name);
+                            ui.close();
+                        }
+                        if ui
+                            .small_button("🗑")
+                            .on_hover_text("Delete template")
+                            .clicked()
+                            && let Err(e) = crate::presets::delete_track_template(&name)
+                        {
+                            app.dialogs.show_message(&e.to_string());
+                        }
+                    });
+                }
+            });
         });
     }
 
@@ -88,18 +136,19 @@ impl TracksPanel {
         let mut track_actions = Vec::new();
         let mut automation_actions = Vec::new();
 
-        // Get ordered track IDs and clone them to avoid holding the lock
-        let track_ids = {
-            let state = app.state.lock_sync();
-            state.track_order.clone()
-        };
-
-        for &track_id in track_ids.iter() {
+        for row in app.track_rows() {
+            let (track_id, nested) = match row {
+                TrackRow::Folder(group_id) => {
+                    self.draw_folder_header(ui, group_id, app);
+                    continue;
+                }
+                TrackRow::Track { id, nested } => (id, nested),
+            };
             let is_selected = track_id == app.selected_track;
 
             // Build the whole track UI inside a group and return the header response
-            let header_resp = ui
-                .group(|ui| {
+            let mut draw_track = |ui: &mut egui::Ui| {
+                ui.group(|ui| {
                     let header_resp =
                         self.draw_track_header(ui, track_id, is_selected, app, |action| {
                             track_actions.push((action, track_id))
@@ -123,7 +172,14 @@ impl TracksPanel {
 
                     header_resp
                 })
-                .inner;
+                .inner
+            };
+            // Members of an open folder sit indented under its header
+            let header_resp = if nested {
+                ui.indent(("folder_member", track_id), draw_track).inner
+            } else {
+                draw_track(ui)
+            };
 
             // Select the track when the header is clicked
             if header_resp.clicked() {
@@ -164,8 +220,55 @@ impl TracksPanel {
         }
     }
 
+    fn draw_folder_header(&self, ui: &mut egui::Ui, group_id: u64, app: &mut super::app::YadawApp) {
+        let Some((name, color, collapsed, members)) = ({
+            let state = app.state.lock_sync();
+            state.groups.get(&group_id).map(|g| {
+                let members = state
+                    .tracks
+                    .values()
+                    .filter(|t| t.group_id == Some(group_id))
+                    .count();
+                (g.name.clone(), g.color_egui(), g.collapsed, members)
+            })
+        }) else {
+            return;
+        };
+
+        egui::Frame::group(ui.style())
+            .fill(color.gamma_multiply(0.12))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let (rect, _) =
+                        ui.allocate_exact_size(egui::vec2(4.0, 24.0), egui::Sense::hover());
+                    ui.painter().rect_filled(rect, 0.0, color);
+
+                    if ui
+                        .small_button(if collapsed { "▸" } else { "▾" })
+                        .on_hover_text(if collapsed {
+                            "Show the folder's tracks"
+                        } else {
+                            "Fold the tracks into the folder"
+                        })
+                        .clicked()
+                    {
+                        let _ = app
+                            .command_tx
+                            .send(AudioCommand::ToggleGroupCollapsed(group_id));
+                    }
+                    ui.label("📁");
+                    ui.strong(&name);
+                    ui.label(
+                        egui::RichText::new(format!("{members} tracks"))
+                            .small()
+                            .weak(),
+                    );
+                });
+            });
+    }
+
     fn draw_track_header<'a>(
-        &self,
+        &mut self,
         ui: &mut egui::Ui,
         track_id: u64,
         is_selected: bool,
@@ -240,6 +343,7 @@ impl TracksPanel {
                             );
                         });
                     }
+                    draw_cpu_bar(ui, app, track_id, 30.0);
 
                     // Group badge
                     if let Some((group_name, (gr, gg, gb))) = &group_info {
@@ -251,8 +355,12 @@ impl TracksPanel {
                         );
                     }
 
-                    ui.label(name);
+                    ui.label(&name);
                     ui.label(if is_midi { "🎹" } else { "🎵" });
+                    if is_frozen {
+                        ui.label("❄")
+                            .on_hover_text("Frozen: playing a render of the clips and plugins");
+                    }
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         ui.menu_button("⚙", |ui| {
@@ -277,6 +385,35 @@ impl TracksPanel {
                                 on_action("freeze_toggle");
                                 ui.close();
                             }
+                            ui.menu_button("Save as Template", |ui| {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.template_name_draft)
+                                        .hint_text(name.as_str())
+                                        .desired_width(140.0),
+                                );
+                                if ui.button("Save").clicked() {
+                                    let draft = self.template_name_draft.trim();
+                                    let template_name = if draft.is_empty() {
+                                        name.clone()
+                                    } else {
+                                        draft.to_string()
+                                    };
+                                    app.save_track_template(track_id, template_name);
+                                    self.template_name_draft.clear();
+                                    ui.close();
+                                }
+                            });
+                            if ui
+                                .button("Bounce Loop Region to New Track")
+                                .on_hover_text(
+                                    "Render the loop region through the plugins onto a new \
+                                     audio track and mute the clips in it",
+                                )
+                                .clicked()
+                            {
+                                on_action("bounce_loop");
+                                ui.close();
+                            }
 
                             ui.separator();
 
@@ -325,8 +462,13 @@ impl TracksPanel {
         drag_resp.union(inner.response)
     }
 
-    fn draw_mixer_strip(&mut self, ui: &mut egui::Ui, track_id: u64, app: &super::app::YadawApp) {
-        let (mut volume, mut pan, muted, solo, armed, monitor_enabled, is_midi) = {
+    fn draw_mixer_strip(
+        &mut self,
+        ui: &mut egui::Ui,
+        track_id: u64,
+        app: &mut super::app::YadawApp,
+    ) {
+        let (mut volume, mut pan, muted, solo, solo_safe, armed, monitor_enabled, is_midi) = {
             let state = app.state.lock_sync();
             state
                 .tracks
@@ -337,13 +479,21 @@ impl TracksPanel {
                         t.pan,
                         t.muted,
                         t.solo,
+                        t.solo_safe,
                         t.armed,
                         t.monitor_enabled,
                         matches!(t.track_type, TrackType::Midi),
                     )
                 })
-                .unwrap_or((0.7, 0.0, false, false, false, false, false))
+                .unwrap_or((0.7, 0.0, false, false, false, false, false, false))
         };
+        let (mut monitor_gain_db, mut input_channel) = app
+            .state
+            .lock_sync()
+            .tracks
+            .get(&track_id)
+            .map(|t| (t.monitor_gain_db, t.input_channel))
+            .unwrap_or_default();
 
         ui.horizontal(|ui| {
             if ui
@@ -355,14 +505,16 @@ impl TracksPanel {
                     .command_tx
                     .send(AudioCommand::SetTrackMute(track_id, !muted));
             }
-            if ui
-                .selectable_label(solo, if solo { "S" } else { "s" })
-                .on_hover_text("Solo")
-                .clicked()
-            {
+            let solo_resp = ui
+                .selectable_label(solo, solo_label(solo, solo_safe))
+                .on_hover_text(SOLO_HOVER);
+            if solo_resp.clicked() {
+                app.solo_track(track_id, !solo, ui.input(|i| i.modifiers.command));
+            }
+            if solo_resp.secondary_clicked() {
                 let _ = app
                     .command_tx
-                    .send(AudioCommand::SetTrackSolo(track_id, !solo));
+                    .send(AudioCommand::SetTrackSoloSafe(track_id, !solo_safe));
             }
             if ui
                 .selectable_label(armed, if armed { "●" } else { "○" })
@@ -385,16 +537,57 @@ impl TracksPanel {
             }
         });
 
+        if !is_midi && (monitor_enabled || armed) {
+            ui.horizontal(|ui| {
+                ui.label("In:");
+                let channel_name = |ch: Option<usize>| match ch {
+                    Some(ch) => format!("{}", ch + 1),
+                    None => "1+2".to_string(),
+                };
+                let before = input_channel;
+                egui::ComboBox::from_id_salt(("track_input_channel", track_id))
+                    .width(44.0)
+                    .selected_text(channel_name(input_channel))
+                    .show_ui(ui, |ui| {
+                        for ch in [None, Some(0), Some(1)] {
+                            ui.selectable_value(&mut input_channel, ch, channel_name(ch));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Interface input this track monitors and records");
+                if input_channel != before {
+                    let _ = app
+                        .command_tx
+                        .send(AudioCommand::SetTrackInputChannel(track_id, input_channel));
+                }
+
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut monitor_gain_db)
+                            .range(-60.0..=24.0)
+                            .speed(0.2)
+                            .suffix(" dB"),
+                    )
+                    .on_hover_text("Monitor gain")
+                    .changed()
+                {
+                    let _ = app
+                        .command_tx
+                        .send(AudioCommand::SetTrackMonitorGain(track_id, monitor_gain_db));
+                }
+            });
+        }
+
         ui.horizontal(|ui| {
             ui.label("Vol:");
-            if ui
-                .add(
-                    egui::Slider::new(&mut volume, 0.0..=1.2)
-                        .show_value(false)
-                        .logarithmic(true),
-                )
-                .changed()
-            {
+            let resp = ui.add(
+                egui::Slider::new(&mut volume, 0.0..=1.2)
+                    .show_value(false)
+                    .logarithmic(true),
+            );
+            app.touch_automation(track_id, AutomationTarget::TrackVolume, &resp);
+            app.undo_gesture(egui::Id::new(("track_volume", track_id)), &resp);
+            if resp.changed() {
                 let _ = app
                     .command_tx
                     .send(AudioCommand::SetTrackVolume(track_id, volume));
@@ -404,10 +597,10 @@ impl TracksPanel {
 
         ui.horizontal(|ui| {
             ui.label("Pan:");
-            if ui
-                .add(egui::Slider::new(&mut pan, -1.0..=1.0).show_value(false))
-                .changed()
-            {
+            let resp = ui.add(egui::Slider::new(&mut pan, -1.0..=1.0).show_value(false));
+            app.touch_automation(track_id, AutomationTarget::TrackPan, &resp);
+            app.undo_gesture(egui::Id::new(("track_pan", track_id)), &resp);
+            if resp.changed() {
                 let _ = app
                     .command_tx
                     .send(AudioCommand::SetTrackPan(track_id, pan));
@@ -423,12 +616,18 @@ impl TracksPanel {
         app: &super::app::YadawApp,
     ) -> Option<(u64, AutomationTarget)> {
         let mut action = None;
-        let (plugin_chain, num_lanes) = {
+        let (plugin_chain, num_lanes, is_midi) = {
             let state = app.state.lock_sync();
             state
                 .tracks
                 .get(&track_id)
-                .map(|t| (t.plugin_chain.clone(), t.automation_lanes.len()))
+                .map(|t| {
+                    (
+                        t.plugin_chain.clone(),
+                        t.automation_lanes.len(),
+                        matches!(t.track_type, TrackType::Midi),
+                    )
+                })
                 .unwrap_or_default()
         };
 
@@ -443,6 +642,24 @@ impl TracksPanel {
                     action = Some((track_id, AutomationTarget::TrackPan));
                     ui.close();
                 }
+                if is_midi {
+                    ui.menu_button("MIDI CC", |ui| {
+                        egui::ScrollArea::vertical()
+                            .max_height(300.0)
+                            .show(ui, |ui| {
+                                for cc in 0..120u8 {
+                                    let label = match cc_name(cc) {
+                                        Some(name) => format!("{cc} {name}"),
+                                        None => cc.to_string(),
+                                    };
+                                    if ui.button(label).clicked() {
+                                        action = Some((track_id, AutomationTarget::MidiCc(cc)));
+                                        ui.close();
+                                    }
+                                }
+                            });
+                    });
+                }
                 ui.separator();
                 for plugin in &plugin_chain {
                     let plugin_id = plugin.id;
@@ -486,6 +703,7 @@ impl TracksPanel {
 
         let mut plugin_to_remove: Option<u64> = None;
         let mut move_action: Option<(usize, usize)> = None;
+        let mut ab_action: Option<(u64, usize, AbAction)> = None;
 
         // Get cached chain to avoid cloning every frame
         let chain_len = {
@@ -499,7 +717,18 @@ impl TracksPanel {
 
         // Only lock when we need to read plugin data
         for plugin_idx in 0..chain_len {
-            let (plugin_id, plugin_name, plugin_uri, backend, bypass, has_editor, params) = {
+            let (
+                plugin_id,
+                plugin_name,
+                plugin_uri,
+                backend,
+                bypass,
+                has_editor,
+                params,
+                sidechain_source,
+                sidechain_choices,
+                preset_name,
+            ) = {
                 let state = app.state.lock_sync();
                 let track = match state.tracks.get(&track_id) {
                     Some(t) => t,
@@ -517,14 +746,45 @@ impl TracksPanel {
                     plugin.bypass,
                     plugin.has_editor,
                     plugin.params.clone(),
+                    plugin.sidechain_source,
+                    state
+                        .track_order
+                        .iter()
+                        .filter(|&&id| id != track_id)
+                        .filter_map(|id| state.tracks.get(id))
+                        .map(|t| (t.id, t.name.clone()))
+                        .collect::<Vec<_>>(),
+                    plugin.preset_name.clone(),
                 )
             };
 
             let mut bypass_local = bypass;
-
-            egui::CollapsingHeader::new(&plugin_name)
-                .id_salt(("plugin", track_id, plugin_id))
-                .show(ui, |ui| {
+            let ab_active = self.plugin_ab.get(&plugin_id).map(|ab| ab.active);
+
+            let header_id = ui.make_persistent_id(("plugin", track_id, plugin_id));
+            let (_, header, body) =
+                egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(),
+                    header_id,
+                    false,
+                )
+                .show_header(ui, |ui| {
+                    ui.dnd_drag_source(
+                        egui::Id::new(("plugin_drag", track_id, plugin_id)),
+                        PluginDrag {
+                            track_id,
+                            from: plugin_idx,
+                        },
+                        |ui| ui.label("⠿"),
+                    )
+                    .response
+                    .on_hover_text("Drag to reorder");
+                    ui.label(&plugin_name);
+                    if let Some(&reduction) = app.plugin_gain_reduction.get(&plugin_id) {
+                        draw_gain_reduction(ui, reduction);
+                    }
+                })
+                .body(|ui| {
                     ui.horizontal(|ui| {
                         if ui.checkbox(&mut bypass_local, "Bypass").changed() {
                             let _ = app.command_tx.send(AudioCommand::SetPluginBypass(
@@ -542,6 +802,28 @@ impl TracksPanel {
                         if plugin_idx < chain_len - 1 && ui.small_button("⏷").clicked() {
                             move_action = Some((plugin_idx, plugin_idx + 1));
                         }
+                        let ab_label = match ab_active {
+                            None => "A/B",
+                            Some(0) => "A",
+                            Some(_) => "B",
+                        };
+                        if ui
+                            .small_button(ab_label)
+                            .on_hover_text("Switch between two parameter settings")
+                            .clicked()
+                        {
+                            ab_action = Some((plugin_id, plugin_idx, AbAction::Switch));
+                        }
+                        if let Some(active) = ab_active {
+                            let copy_label = if active == 0 { "A→B" } else { "B→A" };
+                            if ui
+                                .small_button(copy_label)
+                                .on_hover_text("Copy the current settings to the other slot")
+                                .clicked()
+                            {
+                                ab_action = Some((plugin_id, plugin_idx, AbAction::Copy));
+                            }
+                        }
                         #[cfg(not(target_os = "android"))]
                         if has_editor && ui.button("Open Editor").clicked() {
                             app.open_plugin_editor(track_id, plugin_id);
@@ -551,14 +833,29 @@ impl TracksPanel {
                     ui.separator();
                     ui.horizontal(|ui| {
                         ui.menu_button("Presets 📁", |ui| {
+                            ui.horizontal(|ui| {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.preset_name_draft)
+                                        .hint_text("Preset name")
+                                        .desired_width(120.0),
+                                );
+                                let name = self.preset_name_draft.trim().to_string();
+                                if ui
+                                    .add_enabled(!name.is_empty(), egui::Button::new("Save As"))
+                                    .clicked()
+                                {
+                                    app.save_plugin_preset(track_id, plugin_idx, name);
+                                    self.preset_name_draft.clear();
+                                    ui.close();
+                                }
+                            });
                             if ui.button("Save Snapshot").clicked() {
                                 let ts = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-                                let preset_name = format!("Snapshot_{}", ts);
-                                let _ = app.command_tx.send(AudioCommand::SavePluginPreset(
+                                app.save_plugin_preset(
                                     track_id,
                                     plugin_idx,
-                                    preset_name,
-                                ));
+                                    format!("Snapshot_{}", ts),
+                                );
                                 ui.close();
                             }
 
@@ -596,24 +893,119 @@ impl TracksPanel {
                             } else {
                                 ui.separator();
                                 for pname in presets {
-                                    if ui.button(&pname).clicked() {
-                                        let _ =
-                                            app.command_tx.send(AudioCommand::LoadPluginPreset(
-                                                track_id, plugin_idx, pname,
-                                            ));
-                                        ui.close();
-                                    }
+                                    ui.horizontal(|ui| {
+                                        let current = preset_name.as_deref() == Some(&pname);
+                                        if ui
+                                            .selectable_label(current, &pname)
+                                            .on_hover_text("Load")
+                                            .clicked()
+                                        {
+                                            let _ = app.command_tx.send(
+                                                AudioCommand::LoadPluginPreset(
+                                                    track_id,
+                                                    plugin_idx,
+                                                    pname.clone(),
+                                                ),
+                                            );
+                                            ui.close();
+                                        }
+                                        if ui.small_button("🗑").on_hover_text("Delete").clicked()
+                                            && let Err(e) =
+                                                crate::presets::delete_preset(&plugin_uri, &pname)
+                                        {
+                                            app.dialogs.show_message(&e.to_string());
+                                        }
+                                    });
                                 }
                             }
                         });
+
+                        let mut source = sidechain_source;
+                        let source_name = source
+                            .and_then(|id| sidechain_choices.iter().find(|(tid, _)| *tid == id))
+                            .map_or("None", |(_, name)| name.as_str());
+                        egui::ComboBox::from_id_salt(("sidechain", track_id, plugin_id))
+                            .selected_text(format!("Sidechain: {source_name}"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut source, None, "None");
+                                for (tid, name) in &sidechain_choices {
+                                    ui.selectable_value(&mut source, Some(*tid), name);
+                                }
+                            })
+                            .response
+                            .on_hover_text("Track whose post-fader signal keys this plugin");
+                        if source != sidechain_source {
+                            let _ = app.command_tx.send(AudioCommand::SetPluginSidechain(
+                                track_id, plugin_id, source,
+                            ));
+                        }
                     });
 
                     // Draw parameters based on backend
                     match backend {
-                        BackendKind::Lv2 | BackendKind::Clap | BackendKind::Vst3 => self
+                        BackendKind::Lv2
+                        | BackendKind::Clap
+                        | BackendKind::Vst3
+                        | BackendKind::Native => self
                             .draw_plugin_params(ui, app, track_id, plugin_id, plugin_idx, &params),
                     }
                 });
+
+            let mut row_rect = header.response.rect;
+            if let Some(body) = body {
+                row_rect = row_rect.union(body.response.rect);
+            }
+            let drop_zone = ui.interact(
+                row_rect,
+                egui::Id::new(("plugin_drop", track_id, plugin_id)),
+                egui::Sense::hover(),
+            );
+            if let Some(drag) = drop_zone.dnd_hover_payload::<PluginDrag>() {
+                if drag.track_id == track_id {
+                    let pointer_y = ui
+                        .ctx()
+                        .pointer_interact_pos()
+                        .map_or(row_rect.center().y, |p| p.y);
+                    let before = pointer_y < row_rect.center().y;
+                    let line_y = if before {
+                        row_rect.top()
+                    } else {
+                        row_rect.bottom()
+                    };
+                    ui.painter().hline(
+                        ui.max_rect().x_range(),
+                        line_y,
+                        egui::Stroke::new(2.0, ui.visuals().selection.stroke.color),
+                    );
+
+                    if drop_zone.dnd_release_payload::<PluginDrag>().is_some() {
+                        // Slot between rows, then the index it lands on once removed
+                        let slot = if before { plugin_idx } else { plugin_idx + 1 };
+                        let to = if slot > drag.from { slot - 1 } else { slot };
+                        if to != drag.from {
+                            move_action = Some((drag.from, to));
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((plugin_id, plugin_idx, action)) = ab_action {
+            let live = Self::live_plugin_params(app, track_id, plugin_id, plugin_idx);
+            let ab = self
+                .plugin_ab
+                .entry(plugin_id)
+                .or_insert_with(|| PluginAbSlots::new(&live));
+            match action {
+                AbAction::Switch => {
+                    for (name, value) in ab.switch(&live) {
+                        let _ = app.command_tx.send(AudioCommand::SetPluginParam(
+                            track_id, plugin_id, name, value,
+                        ));
+                    }
+                }
+                AbAction::Copy => ab.copy_to_other(&live),
+            }
         }
 
         if let Some(id_to_remove) = plugin_to_remove {
@@ -621,6 +1013,7 @@ impl TracksPanel {
                 .command_tx
                 .send(AudioCommand::RemovePlugin(track_id, id_to_remove));
             self.cached_plugin_chains.remove(&track_id);
+            self.plugin_ab.remove(&id_to_remove);
 
             app.invalidate_clap_params_for_track(track_id);
             let _ = app.command_tx.send(AudioCommand::RebuildAllRtChains);
@@ -629,18 +1022,40 @@ impl TracksPanel {
         if let Some((from, to)) = move_action {
             let _ = app
                 .command_tx
-                .send(AudioCommand::MovePlugin(track_id, from, to));
+                .send(AudioCommand::ReorderPlugin { track_id, from, to });
             self.cached_plugin_chains.remove(&track_id);
+            app.reorder_clap_params_for_track(track_id, chain_len, from, to);
+        }
+    }
 
-            app.invalidate_clap_params_for_track(track_id);
-            let _ = app.command_tx.send(AudioCommand::RebuildAllRtChains);
+    /// Every parameter's current value: discovered values overlaid with the ones
+    /// the project has stored
+    fn live_plugin_params(
+        app: &super::app::YadawApp,
+        track_id: u64,
+        plugin_id: u64,
+        plugin_idx: usize,
+    ) -> HashMap<String, f32> {
+        let mut live: HashMap<String, f32> = app
+            .clap_param_meta
+            .get(&(track_id, plugin_idx))
+            .map(|meta| meta.iter().map(|p| (p.name.clone(), p.current)).collect())
+            .unwrap_or_default();
+        let state = app.state.lock_sync();
+        if let Some(plugin) = state
+            .tracks
+            .get(&track_id)
+            .and_then(|t| t.plugin_chain.iter().find(|p| p.id == plugin_id))
+        {
+            live.extend(plugin.params.iter().map(|(k, v)| (k.clone(), *v)));
         }
+        live
     }
 
     fn draw_plugin_params(
         &self,
         ui: &mut egui::Ui,
-        app: &super::app::YadawApp,
+        app: &mut super::app::YadawApp,
         track_id: u64,
         plugin_id: u64,
         plugin_idx: usize,
@@ -667,10 +1082,11 @@ impl TracksPanel {
                               params: &HashMap<String, f32>,
                               track_id: u64,
                               plugin_id: u64,
-                              app: &super::app::YadawApp| {
+                              app: &mut super::app::YadawApp| {
                 let mut v = params.get(&pinfo.name).copied().unwrap_or(pinfo.current);
 
                 let is_readonly = pinfo.is_readonly;
+                let undo_key = egui::Id::new(("plugin_param", plugin_id, &pinfo.name));
 
                 ui.horizontal(|ui| {
                     ui.label(&pinfo.name);
@@ -680,6 +1096,7 @@ impl TracksPanel {
                             let mut bool_val = v > 0.5;
                             let resp = ui
                                 .add_enabled(!is_readonly, egui::Checkbox::new(&mut bool_val, ""));
+                            app.undo_gesture(undo_key, &resp);
                             if resp.changed() {
                                 v = if bool_val { 1.0 } else { 0.0 };
                             }
@@ -724,6 +1141,7 @@ impl TracksPanel {
                                     });
 
                                 if changed {
+                                    app.push_undo();
                                     v = pinfo.min + new_idx as f32;
                                 }
                                 changed
@@ -738,6 +1156,7 @@ impl TracksPanel {
                                         .step_by(1.0)
                                         .show_value(true),
                                 );
+                                app.undo_gesture(undo_key, &resp);
                                 if resp.changed() {
                                     v = int_val as f32;
                                 }
@@ -754,6 +1173,7 @@ impl TracksPanel {
                                     .step_by(1.0)
                                     .show_value(true),
                             );
+                            app.undo_gesture(undo_key, &resp);
                             if resp.changed() {
                                 v = int_val as f32;
                             }
@@ -764,6 +1184,17 @@ impl TracksPanel {
                                 !is_readonly,
                                 egui::Slider::new(&mut v, pinfo.min..=pinfo.max).show_value(false), // We'll show formatted value
                             );
+                            if pinfo.is_automatable {
+                                app.touch_automation(
+                                    track_id,
+                                    AutomationTarget::PluginParam {
+                                        plugin_id,
+                                        param_name: pinfo.name.clone(),
+                                    },
+                                    &resp,
+                                );
+                            }
+                            app.undo_gesture(undo_key, &resp);
 
                             if let Some(ref unit) = pinfo.unit {
                                 ui.label(format!("{:.2}{}", v, unit));
@@ -806,6 +1237,7 @@ impl TracksPanel {
                             .on_hover_text(format!("Reset to {}", default_text))
                             .clicked()
                         {
+                            app.push_undo();
                             let _ = app.command_tx.send(AudioCommand::SetPluginParam(
                                 track_id,
                                 plugin_id,
@@ -838,6 +1270,7 @@ impl TracksPanel {
                                 target,
                                 current_beat,
                                 v,
+                                app.config.ui.automation_lane_height,
                             ));
                         }
                     }
@@ -1170,6 +1603,11 @@ impl TracksPanel {
                 };
                 let _ = app.command_tx.send(cmd);
             }
+            "bounce_loop" => {
+                let start = app.audio_state.loop_start.load();
+                let end = app.audio_state.loop_end.load();
+                app.bounce_to_track(track_id, start, end);
+            }
             _ => {}
         }
     }
@@ -1180,3 +1618,127 @@ impl Default for TracksPanel {
         Self::new()
     }
 }
+
+/// Payload carried while a plugin row is dragged to a new chain position
+struct PluginDrag {
+    track_id: u64,
+    from: usize,
+}
+
+#[derive(Clone, Copy)]
+enum AbAction {
+    Switch,
+    Copy,
+}
+
+/// Two full parameter sets for comparing plugin settings. The plugin's live
+/// params always belong to the active slot; the other slot is held here.
+struct PluginAbSlots {
+    slots: [HashMap<String, f32>; 2],
+    active: usize,
+}
+
+impl PluginAbSlots {
+    fn new(live: &HashMap<String, f32>) -> Self {
+        Self {
+            slots: [live.clone(), live.clone()],
+            active: 0,
+        }
+    }
+
+    /// Stores the live params in the active slot, activates the other one and
+    /// returns only the params that differ from what the plugin has now
+    fn switch(&mut self, live: &HashMap<String, f32>) -> Vec<(String, f32)> {
+        self.slots[self.active] = live.clone();
+        self.active ^= 1;
+        param_changes(live, &self.slots[self.active])
+    }
+
+    fn copy_to_other(&mut self, live: &HashMap<String, f32>) {
+        self.slots = [live.clone(), live.clone()];
+    }
+}
+
+/// Bar that fills from the right with gain reduction, full at 24 dB
+fn draw_gain_reduction(ui: &mut egui::Ui, reduction_db: f32) {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(40.0, 8.0), egui::Sense::hover());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 1.0, egui::Color32::from_gray(30));
+    let frac = (reduction_db / 24.0).clamp(0.0, 1.0);
+    let bar = egui::Rect::from_min_max(
+        egui::pos2(rect.right() - rect.width() * frac, rect.top()),
+        rect.right_bottom(),
+    );
+    painter.rect_filled(bar, 1.0, egui::Color32::from_rgb(220, 140, 40));
+    response.on_hover_text(format!("Gain reduction: {reduction_db:.1} dB"));
+}
+
+/// Plugin time of a track against the audio callback budget; turns amber past
+/// half and red near an xrun
+pub(super) fn draw_cpu_bar(
+    ui: &mut egui::Ui,
+    app: &super::app::YadawApp,
+    track_id: u64,
+    width: f32,
+) {
+    let Some(&ms) = app.track_cpu_ms.get(&track_id) else {
+        return;
+    };
+    let budget = app.callback_budget_ms;
+    let load = if budget > 0.0 { ms / budget } else { 0.0 };
+
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, 6.0), egui::Sense::hover());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 1.0, egui::Color32::from_gray(30));
+    let color = if load < 0.5 {
+        egui::Color32::from_rgb(90, 160, 220)
+    } else if load < 0.8 {
+        egui::Color32::from_rgb(220, 170, 40)
+    } else {
+        egui::Color32::from_rgb(220, 60, 60)
+    };
+    let bar = egui::Rect::from_min_size(
+        rect.min,
+        egui::vec2(rect.width() * load.clamp(0.0, 1.0), rect.height()),
+    );
+    painter.rect_filled(bar, 1.0, color);
+    response.on_hover_text(format!(
+        "Plugin CPU: {ms:.2} ms per {budget:.1} ms buffer ({:.0}%)",
+        load * 100.0
+    ));
+}
+
+fn param_changes(from: &HashMap<String, f32>, to: &HashMap<String, f32>) -> Vec<(String, f32)> {
+    to.iter()
+        .filter(|(name, value)| from.get(*name).is_none_or(|old| old != *value))
+        .map(|(name, value)| (name.clone(), *value))
+        .collect()
+}
+
+pub(super) const SOLO_HOVER: &str =
+    "Solo (Ctrl/Cmd-click: other solo mode, right-click: toggle solo safe)";
+
+/// Solo button text; a trailing * marks a solo-safe track.
+pub(super) fn solo_label(solo: bool, safe: bool) -> &'static str {
+    match (solo, safe) {
+        (true, false) => "S",
+        (false, false) => "s",
+        (true, true) => "S*",
+        (false, true) => "s*",
+    }
+}
+
+/// General MIDI names for the controllers people usually automate
+fn cc_name(cc: u8) -> Option<&'static str> {
+    Some(match cc {
+        1 => "Mod Wheel",
+        2 => "Breath",
+        7 => "Volume",
+        10 => "Pan",
+        11 => "Expression",
+        64 => "Sustain",
+        71 => "Resonance",
+        74 => "Cutoff",
+        _ => return None,
+    })
+}
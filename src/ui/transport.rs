@@ -43,16 +43,12 @@ impl TransportUI {
                 .show(ui, |ui| {
                     ui.horizontal(|ui| {
                         // Transport buttons
-                        if ui.button("⏮").on_hover_text("Go to Start").clicked()
-                            && let Some(transport) = &self.transport
-                        {
-                            transport.rewind();
+                        if ui.button("⏮").on_hover_text("Go to Start").clicked() {
+                            app.seek_to_beat(0.0);
                         }
 
-                        if ui.button("⏪").on_hover_text("Rewind").clicked()
-                            && let Some(transport) = &self.transport
-                        {
-                            transport.rewind_beats(4.0);
+                        if ui.button("⏪").on_hover_text("Rewind").clicked() {
+                            app.seek_to_beat(app.playhead_beat() - 4.0);
                         }
 
                         let is_playing = self
@@ -108,10 +104,8 @@ impl TransportUI {
                             let _ = app.command_tx.send(cmd);
                         }
 
-                        if ui.button("⏩").on_hover_text("Fast Forward").clicked()
-                            && let Some(transport) = &self.transport
-                        {
-                            transport.fast_forward(4.0);
+                        if ui.button("⏩").on_hover_text("Fast Forward").clicked() {
+                            app.seek_to_beat(app.playhead_beat() + 4.0);
                         }
 
                         ui.separator();
@@ -122,11 +116,14 @@ impl TransportUI {
                             let sample_rate = app.audio_state.sample_rate.load();
                             let bpm = transport.get_bpm();
                             let beats = (position / sample_rate as f64) * (bpm as f64 / 60.0);
-                            let bar = (beats / 4.0) as u32 + 1;
-                            let beat = (beats % 4.0) as u32 + 1;
-                            let tick = ((beats % 1.0) * 480.0) as u32; // 480 ticks per beat
+                            let time_signature = app.state.lock_sync().time_signature;
 
-                            self.position_display = format!("{}.{}.{:03}", bar, beat, tick);
+                            // 480 ticks per beat
+                            self.position_display = app.config.bar_numbering().format_position(
+                                beats,
+                                time_signature,
+                                480,
+                            );
                         }
 
                         ui.label("Position:");
@@ -140,16 +137,66 @@ impl TransportUI {
                             app.audio_state
                                 .metronome_enabled
                                 .store(metronome, Ordering::Relaxed);
-                            let _ = app.command_tx.send(AudioCommand::SetMetronome(metronome));
+                            let _ = app.command_tx.send(AudioCommand::SetMetronome {
+                                enabled: metronome,
+                                count_in_bars: app
+                                    .audio_state
+                                    .count_in_bars
+                                    .load(Ordering::Relaxed),
+                            });
                         }
+
+                        let mut count_in = app.audio_state.count_in_bars.load(Ordering::Relaxed);
+                        let count_in_text = |bars: u32| match bars {
+                            0 => "Count-in: Off".to_string(),
+                            1 => "Count-in: 1 bar".to_string(),
+                            n => format!("Count-in: {n} bars"),
+                        };
+                        egui::ComboBox::from_id_salt("count_in_bars")
+                            .selected_text(count_in_text(count_in))
+                            .show_ui(ui, |ui| {
+                                for bars in [0, 1, 2, 4] {
+                                    if ui
+                                        .selectable_value(&mut count_in, bars, count_in_text(bars))
+                                        .clicked()
+                                    {
+                                        app.audio_state
+                                            .count_in_bars
+                                            .store(count_in, Ordering::Relaxed);
+                                        let _ = app.command_tx.send(AudioCommand::SetMetronome {
+                                            enabled: app
+                                                .audio_state
+                                                .metronome_enabled
+                                                .load(Ordering::Relaxed),
+                                            count_in_bars: count_in,
+                                        });
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("Bars of click played before recording starts");
                         ui.separator();
 
                         // BPM control
+                        let mut external_sync =
+                            app.audio_state.external_sync.load(Ordering::Relaxed);
+                        if ui
+                            .toggle_value(&mut external_sync, "Ext Sync")
+                            .on_hover_text(
+                                "Follow MIDI clock or timecode from the connected MIDI input",
+                            )
+                            .changed()
+                        {
+                            app.audio_state
+                                .external_sync
+                                .store(external_sync, Ordering::Relaxed);
+                        }
+
                         ui.label("BPM:");
 
                         let bpm_edit =
                             egui::TextEdit::singleline(&mut self.bpm_input).desired_width(60.0);
-                        let bpm_response = ui.add(bpm_edit);
+                        let bpm_response = ui.add_enabled(!external_sync, bpm_edit);
 
                         // Read focus and typed value
                         let field_has_focus = bpm_response.has_focus();
@@ -203,6 +250,17 @@ impl TransportUI {
                             ui.ctx().set_cursor_icon(egui::CursorIcon::NotAllowed);
                         }
 
+                        if ui
+                            .add_enabled(!external_sync, egui::Button::new("Tap"))
+                            .on_hover_text(
+                                "Click in time to set the tempo from the last few taps; \
+                                 a pause of 2 seconds starts over",
+                            )
+                            .clicked()
+                        {
+                            app.tap_tempo();
+                        }
+
                         ui.separator();
 
                         // Loop controls with similar validation
@@ -218,6 +276,28 @@ impl TransportUI {
                         }
 
                         if loop_enabled {
+                            let mut loop_count = app.audio_state.loop_count.load(Ordering::Relaxed);
+                            let count_response = ui
+                                .add(
+                                    egui::DragValue::new(&mut loop_count)
+                                        .range(0..=999)
+                                        .custom_formatter(|n, _| {
+                                            if n < 1.0 {
+                                                "∞".to_string()
+                                            } else {
+                                                format!("{}x", n as usize)
+                                            }
+                                        }),
+                                )
+                                .on_hover_text(
+                                    "Times to play the loop before stopping (0 = forever)",
+                                );
+                            if count_response.changed() {
+                                app.audio_state
+                                    .loop_count
+                                    .store(loop_count, Ordering::Relaxed);
+                            }
+
                             ui.label("Start:");
 
                             let loop_start_response = ui.add(
@@ -275,10 +355,96 @@ impl TransportUI {
                                     format!("{:.1}", app.audio_state.loop_end.load());
                             }
                         }
+
+                        ui.separator();
+                        self.punch_controls(ui, app);
+
+                        ui.separator();
+                        virtual_keyboard_controls(ui, app);
                     });
                 });
         });
     }
+
+    fn punch_controls(&mut self, ui: &mut egui::Ui, app: &mut super::app::YadawApp) {
+        let punch_enabled = app.audio_state.punch_enabled.load(Ordering::Relaxed);
+        let mut punch_checkbox = punch_enabled;
+        if ui
+            .checkbox(&mut punch_checkbox, "Punch")
+            .on_hover_text("Only record between punch-in and punch-out")
+            .clicked()
+        {
+            // Start from the loop region when no punch region has been set
+            if punch_checkbox && app.audio_state.punch_out.load() <= app.audio_state.punch_in.load()
+            {
+                set_punch_to_loop(app);
+            }
+            app.audio_state
+                .punch_enabled
+                .store(punch_checkbox, Ordering::Relaxed);
+            let _ = app
+                .command_tx
+                .send(AudioCommand::SetPunchEnabled(punch_checkbox));
+        }
+
+        if punch_enabled {
+            let mut punch_in = app.audio_state.punch_in.load();
+            let mut punch_out = app.audio_state.punch_out.load();
+            ui.label("In:");
+            let in_changed = ui
+                .add(
+                    egui::DragValue::new(&mut punch_in)
+                        .range(0.0..=punch_out)
+                        .speed(0.25),
+                )
+                .changed();
+            ui.label("Out:");
+            let out_changed = ui
+                .add(
+                    egui::DragValue::new(&mut punch_out)
+                        .range(punch_in..=f64::MAX)
+                        .speed(0.25),
+                )
+                .changed();
+            if in_changed || out_changed {
+                app.audio_state.punch_in.store(punch_in);
+                app.audio_state.punch_out.store(punch_out);
+                let _ = app
+                    .command_tx
+                    .send(AudioCommand::SetPunchRegion(punch_in, punch_out));
+            }
+            if ui.button("From Loop").clicked() {
+                set_punch_to_loop(app);
+            }
+        }
+    }
+}
+
+fn virtual_keyboard_controls(ui: &mut egui::Ui, app: &mut super::app::YadawApp) {
+    let mut enabled = app.input_manager.virtual_keyboard.enabled;
+    if ui
+        .toggle_value(&mut enabled, "⌨")
+        .on_hover_text("Play the selected instrument track from the computer keyboard: A-K white keys, W-U black keys, Z/X octave")
+        .changed()
+    {
+        app.set_virtual_keyboard(enabled);
+    }
+    if enabled {
+        ui.label(format!("C{}", app.input_manager.virtual_keyboard.octave))
+            .on_hover_text("Octave of the A key");
+    }
+}
+
+fn set_punch_to_loop(app: &super::app::YadawApp) {
+    let (start, end) = (
+        app.audio_state.loop_start.load(),
+        app.audio_state.loop_end.load(),
+    );
+    app.audio_state.punch_in.store(start);
+    app.audio_state.punch_out.store(end);
+    let _ = app
+        .command_tx
+        .send(AudioCommand::SetPunchRegion(start, end));
 }
 
 impl Default for TransportUI {
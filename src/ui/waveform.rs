@@ -1,55 +1,69 @@
 use crate::model::AudioClip;
 use eframe::egui;
 
-/// Draws only the waveform lines.
+/// Draws only the waveform lines, one min/max stroke per visible pixel read
+/// from the clip's peak cache. With `show_rms` the peaks are dimmed and the
+/// RMS level is drawn inside them at full `color`.
 pub fn draw_waveform(
     painter: &egui::Painter,
     rect: egui::Rect,
     clip: &AudioClip,
-    zoom_x: f32,
-    scroll_x: f32,
     color: egui::Color32,
+    show_rms: bool,
 ) {
-    let clip_px_total = (clip.length_beats as f32 * zoom_x).max(1.0);
-    let start_px = scroll_x.clamp(0.0, clip_px_total);
-    let start_sample = ((start_px / clip_px_total) * clip.samples.len() as f32) as usize;
-
-    let samples_per_pixel =
-        ((clip.samples.len().saturating_sub(start_sample)) as f32 / rect.width().max(1.0)).max(1.0);
+    let len = clip.samples.len();
+    let visible = rect.intersect(painter.clip_rect());
+    if len == 0 || visible.width() <= 0.0 {
+        return;
+    }
 
-    let mut points = Vec::with_capacity(rect.width() as usize * 2);
+    let peaks = clip.peaks.get_or_build(&clip.samples);
+    let samples_per_pixel = len as f32 / rect.width().max(1.0);
     let center_y = rect.center().y;
     let height = rect.height() * 0.8;
+    let stroke = if show_rms {
+        egui::Stroke::new(1.0, color.gamma_multiply(0.5))
+    } else {
+        egui::Stroke::new(1.0, color)
+    };
+    let rms_stroke = egui::Stroke::new(1.0, color);
 
-    let stroke = egui::Stroke::new(1.0, color);
-
-    for pixel_x in 0..rect.width() as i32 {
-        let s0 = start_sample + (pixel_x as f32 * samples_per_pixel) as usize;
-        let s1 = start_sample + (((pixel_x + 1) as f32) * samples_per_pixel) as usize;
-
-        if s0 >= clip.samples.len() {
+    let first_px = (visible.left() - rect.left()).floor().max(0.0) as i32;
+    let last_px = (visible.right() - rect.left()).ceil() as i32;
+    for pixel_x in first_px..last_px {
+        let s0 = (pixel_x as f32 * samples_per_pixel) as usize;
+        let s1 = (((pixel_x + 1) as f32 * samples_per_pixel) as usize).max(s0 + 1);
+        if s0 >= len {
             break;
         }
-        let end = s1.min(clip.samples.len());
-
-        let mut min_val = 0.0f32;
-        let mut max_val = 0.0f32;
-        for i in s0..end {
-            min_val = min_val.min(clip.samples[i]);
-            max_val = max_val.max(clip.samples[i]);
-        }
+        // Peaks are stored in recorded order; a reversed clip reads the mirror span
+        let (start, end) = if clip.reversed {
+            (len.saturating_sub(s1), len - s0)
+        } else {
+            (s0, s1.min(len))
+        };
+        let peak = peaks.range(&clip.samples, start, end);
 
         let x = rect.left() + pixel_x as f32;
-        let y_min = center_y - max_val * height * 0.5;
-        let y_max = center_y - min_val * height * 0.5;
-
-        points.push(egui::pos2(x, y_min));
-        points.push(egui::pos2(x, y_max));
-    }
-
-    for chunk in points.chunks(2) {
-        if let [a, b] = chunk {
-            painter.line_segment([*a, *b], stroke);
+        painter.line_segment(
+            [
+                egui::pos2(x, center_y - peak.max * height * 0.5),
+                egui::pos2(x, center_y - peak.min * height * 0.5),
+            ],
+            stroke,
+        );
+        if show_rms {
+            // RMS never exceeds the peak, but keep it inside a lopsided outline
+            let rms = peak.rms();
+            let top = rms.min(peak.max.max(0.0));
+            let bottom = rms.min(-peak.min.min(0.0));
+            painter.line_segment(
+                [
+                    egui::pos2(x, center_y - top * height * 0.5),
+                    egui::pos2(x, center_y + bottom * height * 0.5),
+                ],
+                rms_stroke,
+            );
         }
     }
 }